@@ -0,0 +1,209 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::error::Result;
+use crate::models::MemoryType;
+use crate::storage::{ACCESS_HISTORY_DAYS, Storage};
+use crate::tiering::decayed_accesses;
+use crate::timezone::DisplayTimezone;
+
+/// Memories listed per ranking unless asked otherwise
+pub const DEFAULT_ANALYTICS_LIMIT: usize = 10;
+/// Days of daily accesses unless asked otherwise
+pub const DEFAULT_ANALYTICS_DAYS: u32 = 30;
+
+/// Order of [`ReadSnapshot::access_ranking`](crate::storage::ReadSnapshot::access_ranking)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessRank {
+    /// Accessed memories, most accesses first
+    Most,
+    /// Accessed memories, fewest accesses first
+    Least,
+    /// Memories never accessed since they were stored, oldest first
+    Never,
+}
+
+/// Access history of one memory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccessEntry {
+    pub id: String,
+    pub title: String,
+    pub memory_type: MemoryType,
+    pub access_count: u32,
+    /// Accesses decayed to now (see [`decayed_accesses`])
+    pub access_score: f64,
+    pub accessed_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Accesses on one calendar day
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DayAccesses {
+    /// `YYYY-MM-DD` in the display time zone
+    pub date: String,
+    pub accesses: usize,
+}
+
+/// Result of [`access_analytics`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AccessAnalytics {
+    /// Memories covered
+    pub total: usize,
+    pub most_accessed: Vec<AccessEntry>,
+    pub least_accessed: Vec<AccessEntry>,
+    /// Memories never retrieved since they were stored
+    pub never_accessed: usize,
+    /// The oldest of them
+    pub never_accessed_oldest: Vec<AccessEntry>,
+    /// Accesses per day over the window, oldest day first, days without
+    /// accesses included
+    pub daily_accesses: Vec<DayAccesses>,
+    /// Search hits over the window
+    pub search_hits: usize,
+    /// Mean recency component of those hits; low values mean searches
+    /// mostly surface memories not accessed for a long time
+    pub avg_hit_recency: Option<f64>,
+}
+
+/// Access analytics of the memories in `namespace` (or all namespaces):
+/// the `limit` most and least accessed, those never accessed, and daily
+/// accesses and search hit recency over the last `days` days (at most
+/// [`ACCESS_HISTORY_DAYS`], the history kept). Access scores are decayed
+/// with `half_life_days`.
+///
+/// Reads from one [`ReadSnapshot`](crate::storage::ReadSnapshot), so the
+/// figures agree with each other.
+pub fn access_analytics(
+    storage: &Storage,
+    namespace: Option<&str>,
+    limit: usize,
+    days: u32,
+    half_life_days: u32,
+    timezone: DisplayTimezone,
+) -> Result<AccessAnalytics> {
+    let now = Utc::now();
+    let days = (days as i64).clamp(1, ACCESS_HISTORY_DAYS);
+    let snapshot = storage.read_snapshot()?;
+
+    let (total, never_accessed) = snapshot.access_counts(namespace)?;
+    let decay = |mut entries: Vec<AccessEntry>| {
+        for entry in &mut entries {
+            entry.access_score =
+                decayed_accesses(entry.access_score, entry.accessed_at, now, half_life_days);
+        }
+        entries
+    };
+    let most_accessed = decay(snapshot.access_ranking(namespace, AccessRank::Most, limit)?);
+    let least_accessed = decay(snapshot.access_ranking(namespace, AccessRank::Least, limit)?);
+    let never_accessed_oldest = snapshot.access_ranking(namespace, AccessRank::Never, limit)?;
+
+    let first = now - Duration::days(days - 1);
+    // Midnight of the first day in the display zone
+    let since = timezone.parse_bound(&timezone.format_date(first))?;
+    let events = snapshot.access_events(namespace, since)?;
+    let (daily_accesses, search_hits, avg_hit_recency) =
+        summarize_events(&events, first, days, timezone);
+
+    Ok(AccessAnalytics {
+        total,
+        most_accessed,
+        least_accessed,
+        never_accessed,
+        never_accessed_oldest,
+        daily_accesses,
+        search_hits,
+        avg_hit_recency,
+    })
+}
+
+/// Daily access counts for the `days` days from `first`, the number of
+/// search hits (events with a recency) and their mean recency
+fn summarize_events(
+    events: &[(DateTime<Utc>, Option<f32>)],
+    first: DateTime<Utc>,
+    days: i64,
+    timezone: DisplayTimezone,
+) -> (Vec<DayAccesses>, usize, Option<f64>) {
+    let mut per_day: BTreeMap<String, usize> = (0..days)
+        .map(|day| (timezone.format_date(first + Duration::days(day)), 0))
+        .collect();
+    let mut hits = 0;
+    let mut recency_sum = 0.0;
+    for (at, recency) in events {
+        if let Some(count) = per_day.get_mut(&timezone.format_date(*at)) {
+            *count += 1;
+        }
+        if let Some(recency) = recency {
+            hits += 1;
+            recency_sum += *recency as f64;
+        }
+    }
+    let daily = per_day
+        .into_iter()
+        .map(|(date, accesses)| DayAccesses { date, accesses })
+        .collect();
+    let average = (hits > 0).then(|| recency_sum / hits as f64);
+    (daily, hits, average)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Memory;
+
+    #[test]
+    fn test_summarize_events_fills_empty_days() {
+        let first: DateTime<Utc> = "2026-03-01T12:00:00Z".parse().unwrap();
+        let events = vec![
+            (first, Some(0.5)),
+            (first + Duration::hours(1), None),
+            (first + Duration::days(2), Some(1.0)),
+            // Before the first day: a hit, but on no day
+            (first - Duration::days(1), Some(0.0)),
+        ];
+        let (daily, hits, average) = summarize_events(&events, first, 3, DisplayTimezone::Utc);
+        let counts: Vec<(&str, usize)> = daily
+            .iter()
+            .map(|d| (d.date.as_str(), d.accesses))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![("2026-03-01", 2), ("2026-03-02", 0), ("2026-03-03", 1)]
+        );
+        assert_eq!(hits, 3);
+        assert_eq!(average, Some(0.5));
+    }
+
+    #[test]
+    fn test_access_analytics_ranks_and_counts() {
+        let storage = Storage::in_memory().unwrap();
+        let mut ids = Vec::new();
+        for title in ["자주", "가끔", "안 씀"] {
+            let memory = Memory::new("내용".to_string(), title.to_string(), Default::default());
+            storage.insert(&memory).unwrap();
+            ids.push(memory.id);
+        }
+        for _ in 0..3 {
+            storage.touch_hit(&ids[0], 0.8).unwrap();
+        }
+        storage.touch(&ids[1]).unwrap();
+
+        let report = access_analytics(&storage, None, 10, 7, 30, DisplayTimezone::Utc).unwrap();
+        assert_eq!(report.total, 3);
+        assert_eq!(report.most_accessed[0].id, ids[0]);
+        assert_eq!(report.most_accessed[0].access_count, 3);
+        assert_eq!(report.least_accessed[0].id, ids[1]);
+        assert_eq!(report.never_accessed, 1);
+        assert_eq!(report.never_accessed_oldest[0].id, ids[2]);
+        assert_eq!(report.daily_accesses.len(), 7);
+        assert_eq!(report.daily_accesses.last().unwrap().accesses, 4);
+        assert_eq!(report.search_hits, 3);
+        assert!((report.avg_hit_recency.unwrap() - 0.8).abs() < 1e-6);
+
+        storage.delete(&ids[0]).unwrap();
+        let report = access_analytics(&storage, None, 10, 7, 30, DisplayTimezone::Utc).unwrap();
+        assert_eq!(report.search_hits, 0);
+        assert_eq!(report.avg_hit_recency, None);
+    }
+}
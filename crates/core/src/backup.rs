@@ -0,0 +1,183 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::snapshot::{self, SnapshotManifest};
+use crate::storage::Storage;
+
+const BACKUP_PREFIX: &str = "oc-memory-";
+const BACKUP_SUFFIX: &str = ".tar.gz";
+/// Millisecond precision keeps names unique and lexicographically sortable
+const BACKUP_TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%3fZ";
+
+/// A backup archive in the backup directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub name: String,
+    pub path: String,
+    pub size_bytes: u64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Create a timestamped backup archive, then apply the retention policy.
+///
+/// The archive is a regular snapshot (see [`snapshot::create_snapshot`]) so it
+/// can also be restored with the snapshot tooling.
+pub fn create_backup(storage: &Storage, config: &Config) -> Result<BackupInfo> {
+    let dir = config.backup.dir();
+    std::fs::create_dir_all(&dir)?;
+
+    let now = Utc::now();
+    let name = format!(
+        "{BACKUP_PREFIX}{}{BACKUP_SUFFIX}",
+        now.format(BACKUP_TIMESTAMP_FORMAT)
+    );
+    let path = dir.join(&name);
+    snapshot::create_snapshot(storage, config, &path)?;
+
+    let pruned = prune_backups(config)?;
+    if pruned > 0 {
+        tracing::info!(pruned, "Pruned old backups");
+    }
+
+    Ok(BackupInfo {
+        name,
+        size_bytes: std::fs::metadata(&path)?.len(),
+        path: path.display().to_string(),
+        created_at: now,
+    })
+}
+
+/// List backups in the backup directory, newest first
+pub fn list_backups(config: &Config) -> Result<Vec<BackupInfo>> {
+    let dir = config.backup.dir();
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut backups = Vec::new();
+    for entry in std::fs::read_dir(&dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if let Some(created_at) = parse_backup_name(&name) {
+            backups.push(BackupInfo {
+                path: entry.path().display().to_string(),
+                size_bytes: entry.metadata()?.len(),
+                name,
+                created_at,
+            });
+        }
+    }
+    backups.sort_by(|a, b| b.name.cmp(&a.name));
+    Ok(backups)
+}
+
+/// Delete backups beyond `backup.keep_last`. Returns how many were removed.
+pub fn prune_backups(config: &Config) -> Result<usize> {
+    let keep = config.backup.keep_last.max(1);
+    let backups = list_backups(config)?;
+    let mut removed = 0;
+    for backup in backups.iter().skip(keep) {
+        std::fs::remove_file(&backup.path)?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// Restore a backup by file name (from the backup directory) or by path.
+/// The server must not be running against the target data directory.
+pub fn restore_backup(config: &Config, name_or_path: &str) -> Result<SnapshotManifest> {
+    let path = resolve_backup(config, name_or_path)?;
+    snapshot::restore_snapshot(path, config)
+}
+
+/// Resolve an explicit archive path, or a name inside the backup directory
+pub fn resolve_backup(config: &Config, name_or_path: &str) -> Result<PathBuf> {
+    let direct = Path::new(name_or_path);
+    if direct.is_file() {
+        return Ok(direct.to_path_buf());
+    }
+    let in_dir = config.backup.dir().join(name_or_path);
+    if in_dir.is_file() {
+        return Ok(in_dir);
+    }
+    Err(Error::NotFound(format!("backup {name_or_path}")))
+}
+
+fn parse_backup_name(name: &str) -> Option<DateTime<Utc>> {
+    let stamp = name
+        .strip_prefix(BACKUP_PREFIX)?
+        .strip_suffix(BACKUP_SUFFIX)?;
+    NaiveDateTime::parse_from_str(stamp, BACKUP_TIMESTAMP_FORMAT)
+        .ok()
+        .map(|dt| dt.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Memory, MemoryMetadata};
+
+    fn config_at(dir: &Path, keep_last: usize) -> Config {
+        let mut config = Config::default();
+        config.storage.data_dir = dir.join("data").to_string_lossy().to_string();
+        config.backup.dir = dir.join("backups").to_string_lossy().to_string();
+        config.backup.keep_last = keep_last;
+        config
+    }
+
+    #[test]
+    fn test_backup_name_roundtrip() {
+        let parsed = parse_backup_name("oc-memory-20261015T093000123Z.tar.gz").unwrap();
+        assert_eq!(
+            parsed.format("%Y-%m-%d %H:%M:%S").to_string(),
+            "2026-10-15 09:30:00"
+        );
+        assert!(parse_backup_name("notes.tar.gz").is_none());
+    }
+
+    #[test]
+    fn test_retention_keeps_newest() {
+        let tmp = tempfile::tempdir().unwrap();
+        let config = config_at(tmp.path(), 2);
+        std::fs::create_dir_all(config.data_dir()).unwrap();
+        let storage = Storage::open(config.db_path()).unwrap();
+
+        let mut names = Vec::new();
+        for _ in 0..3 {
+            names.push(create_backup(&storage, &config).unwrap().name);
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+
+        let remaining: Vec<String> = list_backups(&config)
+            .unwrap()
+            .into_iter()
+            .map(|b| b.name)
+            .collect();
+        assert_eq!(remaining, vec![names[2].clone(), names[1].clone()]);
+    }
+
+    #[test]
+    fn test_restore_backup_by_name() {
+        let tmp = tempfile::tempdir().unwrap();
+        let config = config_at(tmp.path(), 3);
+        std::fs::create_dir_all(config.data_dir()).unwrap();
+        let storage = Storage::open(config.db_path()).unwrap();
+        let memory = Memory::new(
+            "백업 내용".to_string(),
+            "백업".to_string(),
+            MemoryMetadata::default(),
+        );
+        storage.insert(&memory).unwrap();
+        let backup = create_backup(&storage, &config).unwrap();
+
+        storage.delete(&memory.id).unwrap();
+        drop(storage);
+
+        restore_backup(&config, &backup.name).unwrap();
+        let restored = Storage::open(config.db_path()).unwrap();
+        assert!(restored.get(&memory.id).unwrap().is_some());
+    }
+}
@@ -0,0 +1,242 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::models::Memory;
+
+/// Hit and miss counts of the memory cache since the store was opened
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Memories currently cached
+    pub entries: usize,
+    /// Most memories kept; 0 when caching is off
+    pub capacity: usize,
+    /// Share of lookups served from the cache, 0 before any lookup
+    pub hit_rate: f64,
+}
+
+/// Read-through cache of memories by ID, evicting the least recently used.
+/// Writers call [`MemoryCache::invalidate`] after committing; a read that
+/// started before an invalidation does not fill the cache (see
+/// [`MemoryCache::sync`]), so it never caches a row that was already
+/// stale. Writes by another process sharing the database are noticed
+/// through the head of its audit log.
+#[derive(Debug)]
+pub struct MemoryCache {
+    capacity: usize,
+    inner: Mutex<Inner>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    entries: HashMap<String, Entry>,
+    /// Bumped on every use, to order entries by recency
+    clock: u64,
+    /// Bumped on every invalidation
+    generation: u64,
+    /// Audit log head the entries were read at
+    head: Option<i64>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    memory: Memory,
+    used: u64,
+}
+
+impl MemoryCache {
+    /// A cache of up to `capacity` memories; 0 disables it
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(Inner::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Most memories kept; 0 when caching is off
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The cached memories among `ids`, and the IDs to read from storage,
+    /// both in the order of `ids`
+    pub fn lookup(&self, ids: &[String]) -> (Vec<Memory>, Vec<String>) {
+        if self.capacity == 0 {
+            return (Vec::new(), ids.to_vec());
+        }
+        let mut inner = self.lock();
+        let mut found = Vec::new();
+        let mut missing = Vec::new();
+        for id in ids {
+            inner.clock += 1;
+            let clock = inner.clock;
+            match inner.entries.get_mut(id) {
+                Some(entry) => {
+                    entry.used = clock;
+                    found.push(entry.memory.clone());
+                }
+                None => missing.push(id.clone()),
+            }
+        }
+        self.hits.fetch_add(found.len() as u64, Ordering::Relaxed);
+        self.misses
+            .fetch_add(missing.len() as u64, Ordering::Relaxed);
+        (found, missing)
+    }
+
+    /// Drop every entry when the audit log `head` moved since the last
+    /// read (a write this cache was not told about), and return the
+    /// invalidation generation to hand back to [`MemoryCache::fill`].
+    /// Called before reading from storage.
+    pub fn sync(&self, head: i64) -> u64 {
+        let mut inner = self.lock();
+        if inner.head != Some(head) {
+            inner.head = Some(head);
+            inner.generation += 1;
+            inner.entries.clear();
+        }
+        inner.generation
+    }
+
+    /// Cache memories read from storage, unless an invalidation happened
+    /// since `generation` was taken
+    pub fn fill(&self, generation: u64, memories: &[Memory]) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut inner = self.lock();
+        if inner.generation != generation {
+            return;
+        }
+        for memory in memories {
+            inner.clock += 1;
+            let used = inner.clock;
+            if !inner.entries.contains_key(&memory.id) && inner.entries.len() >= self.capacity {
+                let oldest = inner
+                    .entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.used)
+                    .map(|(id, _)| id.clone());
+                if let Some(oldest) = oldest {
+                    inner.entries.remove(&oldest);
+                }
+            }
+            inner.entries.insert(
+                memory.id.clone(),
+                Entry {
+                    memory: memory.clone(),
+                    used,
+                },
+            );
+        }
+    }
+
+    /// Record an access on a cached memory, as storage did on its row
+    pub fn touch(&self, id: &str, at: DateTime<Utc>) {
+        if let Some(entry) = self.lock().entries.get_mut(id) {
+            entry.memory.accessed_at = at;
+            entry.memory.access_count += 1;
+        }
+    }
+
+    /// Drop `ids` from the cache after their rows changed
+    pub fn invalidate(&self, ids: &[String]) {
+        let mut inner = self.lock();
+        inner.generation += 1;
+        for id in ids {
+            inner.entries.remove(id);
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        CacheStats {
+            hits,
+            misses,
+            entries: self.lock().entries.len(),
+            capacity: self.capacity,
+            hit_rate: if hits + misses == 0 {
+                0.0
+            } else {
+                hits as f64 / (hits + misses) as f64
+            },
+        }
+    }
+
+    /// The cache only holds copies, so a panic while it was locked leaves
+    /// nothing inconsistent
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory(title: &str) -> Memory {
+        Memory::new(title.to_string(), title.to_string(), Default::default())
+    }
+
+    fn ids(memories: &[&Memory]) -> Vec<String> {
+        memories.iter().map(|m| m.id.clone()).collect()
+    }
+
+    #[test]
+    fn test_read_through_and_eviction() {
+        let cache = MemoryCache::new(2);
+        let (a, b, c) = (memory("a"), memory("b"), memory("c"));
+
+        let (found, missing) = cache.lookup(&ids(&[&a, &b]));
+        assert!(found.is_empty());
+        assert_eq!(missing.len(), 2);
+        cache.fill(cache.sync(0), &[a.clone(), b.clone()]);
+
+        // `a` is used more recently than `b`, so `c` evicts `b`
+        assert_eq!(cache.lookup(&ids(&[&a])).0.len(), 1);
+        cache.fill(cache.sync(0), std::slice::from_ref(&c));
+        let (found, missing) = cache.lookup(&ids(&[&a, &b, &c]));
+        assert_eq!(found.len(), 2);
+        assert_eq!(missing, vec![b.id.clone()]);
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (3, 3, 2));
+        assert_eq!(stats.hit_rate, 0.5);
+    }
+
+    #[test]
+    fn test_invalidation_drops_entries_and_stale_fills() {
+        let cache = MemoryCache::new(8);
+        let a = memory("a");
+        cache.fill(cache.sync(0), std::slice::from_ref(&a));
+        cache.touch(&a.id, Utc::now());
+        assert_eq!(cache.lookup(&ids(&[&a])).0[0].access_count, 1);
+
+        // A read that started before a change must not cache its row
+        let generation = cache.sync(0);
+        cache.invalidate(&ids(&[&a]));
+        cache.fill(generation, std::slice::from_ref(&a));
+        assert_eq!(cache.lookup(&ids(&[&a])).1.len(), 1);
+
+        // So does any write another process logged
+        cache.fill(cache.sync(0), std::slice::from_ref(&a));
+        assert_eq!(cache.lookup(&ids(&[&a])).0.len(), 1);
+        cache.sync(1);
+        assert_eq!(cache.lookup(&ids(&[&a])).1.len(), 1);
+
+        let off = MemoryCache::new(0);
+        off.fill(off.sync(0), std::slice::from_ref(&a));
+        assert_eq!(off.stats().entries, 0);
+    }
+}
@@ -1,7 +1,11 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use crate::error::{Error, Result};
+use crate::fields::{FieldRegistry, FieldType};
+use crate::models::{DiskUsage, DuplicateAction, ExpiryAction, MemoryType, Verbosity};
+use crate::timezone::DisplayTimezone;
 
 /// Main configuration for oc-memory engine
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -11,6 +15,46 @@ pub struct Config {
     pub search: SearchConfig,
     pub observer: ObserverConfig,
     pub server: ServerConfig,
+    #[serde(default)]
+    pub export: ExportConfig,
+    #[serde(default)]
+    pub import: ImportConfig,
+    #[serde(default)]
+    pub backup: BackupConfig,
+    #[serde(default)]
+    pub mcp: McpConfig,
+    #[serde(default)]
+    pub pack: PackConfig,
+    #[serde(default)]
+    pub self_test: SelfTestConfig,
+    #[serde(default)]
+    pub dedup: DedupConfig,
+    #[serde(default)]
+    pub escalation: EscalationConfig,
+    #[serde(default)]
+    pub consolidation: ConsolidationConfig,
+    #[serde(default)]
+    pub expiry: ExpiryConfig,
+    #[serde(default)]
+    pub forgetting: ForgettingConfig,
+    #[serde(default)]
+    pub keywords: KeywordsConfig,
+    #[serde(default)]
+    pub display: DisplayConfig,
+    #[serde(default)]
+    pub quarantine: QuarantineConfig,
+    #[serde(default)]
+    pub misses: MissesConfig,
+    #[serde(default)]
+    pub sync: SyncConfig,
+    #[serde(default)]
+    pub hook: HookConfig,
+    /// Typed metadata fields memories may carry, by name
+    #[serde(default)]
+    pub fields: BTreeMap<String, FieldType>,
+    /// File this config was loaded from (target for `save`)
+    #[serde(skip)]
+    pub source_path: Option<PathBuf>,
 }
 
 impl Config {
@@ -18,29 +62,151 @@ impl Config {
     pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
         let content =
             std::fs::read_to_string(path.as_ref()).map_err(|e| Error::Config(e.to_string()))?;
-        toml::from_str(&content).map_err(|e| Error::Config(e.to_string()))
+        let mut config: Self =
+            toml::from_str(&content).map_err(|e| Error::Config(e.to_string()))?;
+        config.source_path = Some(path.as_ref().to_path_buf());
+        Ok(config)
     }
 
     /// Load from default path (~/.config/oc-memory/config.toml)
     pub fn load_default() -> Result<Self> {
-        let home = dirs_path();
-        let config_path = home.join("config.toml");
+        let config_path = Self::default_path();
 
         if config_path.exists() {
             Self::from_file(config_path)
         } else {
-            Ok(Self::default())
+            Ok(Self {
+                source_path: Some(config_path),
+                ..Self::default()
+            })
         }
     }
 
+    /// Default config file path (~/.config/oc-memory/config.toml)
+    pub fn default_path() -> PathBuf {
+        dirs_path().join("config.toml")
+    }
+
+    /// This configuration without inline secrets: the `key` of every API
+    /// key and sync peer is dropped, `key_env` is kept
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        for api_key in &mut config.server.api_keys {
+            api_key.key = None;
+        }
+        for peer in &mut config.sync.peers {
+            peer.key = None;
+        }
+        config
+    }
+
+    /// Write configuration as TOML, creating parent directories as needed
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = toml::to_string_pretty(self).map_err(|e| Error::Config(e.to_string()))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
     /// Data directory path
     pub fn data_dir(&self) -> PathBuf {
-        let path = shellexpand(&self.storage.data_dir);
-        PathBuf::from(path)
+        expand_home(&self.storage.data_dir)
+    }
+
+    /// SQLite database file inside the data directory
+    pub fn db_path(&self) -> PathBuf {
+        self.data_dir().join(DB_FILE_NAME)
+    }
+
+    /// Tantivy (BM25) index directory inside the data directory
+    pub fn tantivy_dir(&self) -> PathBuf {
+        self.data_dir().join(TANTIVY_DIR_NAME)
+    }
+
+    /// usearch HNSW index file inside the data directory
+    pub fn vector_index_path(&self) -> PathBuf {
+        self.data_dir().join(VECTOR_INDEX_FILE_NAME)
+    }
+
+    /// Per-namespace vector index shards inside the data directory
+    pub fn vector_shards_dir(&self) -> PathBuf {
+        self.data_dir().join(VECTOR_SHARDS_DIR_NAME)
+    }
+
+    /// BM25 query synonym groups inside the data directory
+    pub fn synonyms_path(&self) -> PathBuf {
+        self.data_dir().join(SYNONYMS_FILE_NAME)
+    }
+
+    /// Bytes the database and the search indexes take in the data
+    /// directory; missing files count as empty
+    pub fn disk_usage(&self) -> DiskUsage {
+        let db = self.db_path();
+        let beside_db = |suffix: &str| {
+            let mut name = db.as_os_str().to_owned();
+            name.push(suffix);
+            PathBuf::from(name)
+        };
+        let vectors = self.vector_index_path();
+        DiskUsage {
+            database_bytes: file_size(&db)
+                + file_size(&beside_db("-wal"))
+                + file_size(&beside_db("-shm")),
+            text_index_bytes: dir_size(&self.tantivy_dir()),
+            vector_index_bytes: file_size(&vectors)
+                + file_size(&vector_manifest_path(&vectors))
+                + dir_size(&self.vector_shards_dir()),
+        }
     }
+
+    /// Registry of the `[fields]` types, reading zone-less dates in the
+    /// display time zone
+    pub fn field_registry(&self) -> FieldRegistry {
+        FieldRegistry::new(self.fields.clone()).with_timezone(self.display.timezone)
+    }
+}
+
+/// Sidecar manifest (ID map, staleness fingerprint) stored next to a vector index file
+pub fn vector_manifest_path(index_path: &Path) -> PathBuf {
+    let mut name = index_path.as_os_str().to_owned();
+    name.push(".meta.json");
+    PathBuf::from(name)
+}
+
+fn file_size(path: &Path) -> u64 {
+    std::fs::metadata(path).map_or(0, |m| m.len())
 }
 
+/// Total size of the files under `path`
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(kind) if kind.is_dir() => dir_size(&entry.path()),
+            _ => entry.metadata().map_or(0, |m| m.len()),
+        })
+        .sum()
+}
+
+/// SQLite database file name within the data directory
+pub const DB_FILE_NAME: &str = "memories.db";
+/// Tantivy index directory name within the data directory
+pub const TANTIVY_DIR_NAME: &str = "tantivy";
+/// usearch index file name within the data directory
+pub const VECTOR_INDEX_FILE_NAME: &str = "vectors.usearch";
+/// Per-namespace vector shard directory name within the data directory
+pub const VECTOR_SHARDS_DIR_NAME: &str = "vector_shards";
+/// Synonym file name within the data directory
+pub const SYNONYMS_FILE_NAME: &str = "synonyms.toml";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct StorageConfig {
     /// Data directory for SQLite DB and indices
     pub data_dir: String,
@@ -48,6 +214,21 @@ pub struct StorageConfig {
     pub max_hot_memories: usize,
     /// Hot memory TTL in days
     pub hot_ttl_days: u32,
+    /// Days for an access to count half as much when ranking popularity
+    /// (0 = accesses never fade)
+    pub access_half_life_days: u32,
+    /// SQLite journal mode (`wal`, `delete`, `truncate`, `persist`, `memory`, `off`)
+    pub journal_mode: String,
+    /// SQLite synchronous level (`off`, `normal`, `full`, `extra`)
+    pub synchronous: String,
+    /// How long a connection waits on a locked database before failing (ms)
+    pub busy_timeout_ms: u64,
+    /// Maximum number of pooled SQLite connections
+    pub pool_size: u32,
+    /// Compress the content of memories older than this many days (0 = disabled)
+    pub compress_after_days: u32,
+    /// Memories kept in the read cache in front of the database (0 = disabled)
+    pub cache_capacity: usize,
 }
 
 impl Default for StorageConfig {
@@ -56,12 +237,22 @@ impl Default for StorageConfig {
             data_dir: "~/.local/share/oc-memory".to_string(),
             max_hot_memories: 10_000,
             hot_ttl_days: 90,
+            access_half_life_days: 30,
+            journal_mode: "wal".to_string(),
+            synchronous: "normal".to_string(),
+            busy_timeout_ms: 5_000,
+            pool_size: 8,
+            compress_after_days: 0,
+            cache_capacity: 512,
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EmbeddingConfig {
+    /// Which backend produces embeddings
+    #[serde(default)]
+    pub backend: EmbeddingBackend,
     /// Path to ONNX model file
     pub model_path: String,
     /// Path to tokenizer.json
@@ -70,18 +261,262 @@ pub struct EmbeddingConfig {
     pub dimensions: usize,
     /// Max sequence length
     pub max_length: usize,
+    /// Which tokens to keep when input exceeds `max_length`
+    #[serde(default)]
+    pub truncation: TruncationStrategy,
     /// Number of threads for ONNX Runtime
     pub num_threads: usize,
+    /// Hardware acceleration for the ONNX backend
+    #[serde(default)]
+    pub execution_provider: ExecutionProvider,
+    /// ONNX Runtime session tuning (optimization, threads, memory arena)
+    #[serde(default)]
+    pub session: OnnxSessionConfig,
+    /// Where to fetch the model and tokenizer when they are missing
+    #[serde(default)]
+    pub download: ModelDownloadConfig,
+    /// Remote embeddings API, used when `backend = "http"`
+    #[serde(default)]
+    pub http: HttpEmbeddingConfig,
+    /// Extra per-chunk embeddings for long memories
+    #[serde(default)]
+    pub chunking: ChunkingConfig,
+    /// Bounds on concurrent and waiting embedding calls
+    #[serde(default)]
+    pub limits: EmbeddingLimitsConfig,
+}
+
+/// Embedding calls run at most `max_concurrent` at a time with up to
+/// `max_queued` more waiting; beyond that a call fails at once as busy, with
+/// a retry hint of at least `retry_after_ms`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmbeddingLimitsConfig {
+    /// 0 lifts the limit (and the queue bound with it)
+    pub max_concurrent: usize,
+    pub max_queued: usize,
+    pub retry_after_ms: u64,
+}
+
+impl Default for EmbeddingLimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 4,
+            max_queued: 32,
+            retry_after_ms: 250,
+        }
+    }
+}
+
+/// Chunk-level embeddings: content longer than `max_chars` is split on
+/// sentence boundaries into chunks of at most `max_chars`, each embedded and
+/// indexed alongside the whole-memory embedding
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChunkingConfig {
+    pub enabled: bool,
+    pub max_chars: usize,
+}
+
+impl Default for ChunkingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_chars: 1000,
+        }
+    }
+}
+
+/// Embedding backend selection
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingBackend {
+    /// Local ONNX model (`model_path` / `tokenizer_path`)
+    #[default]
+    Onnx,
+    /// OpenAI-compatible `/embeddings` endpoint (`[embedding.http]`)
+    Http,
+}
+
+/// ONNX Runtime execution provider. Anything other than `cpu` falls back to
+/// the CPU when the provider is not available in the loaded runtime.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExecutionProvider {
+    #[default]
+    Cpu,
+    /// The usual accelerator for this platform: CoreML on macOS,
+    /// DirectML then CUDA on Windows, CUDA elsewhere
+    Auto,
+    CoreMl,
+    Cuda,
+    DirectMl,
+}
+
+/// Tokens kept from input longer than the model's `max_length`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TruncationStrategy {
+    /// The beginning of the text
+    #[default]
+    Head,
+    /// The end of the text (after the leading special token)
+    Tail,
+    /// The first and last halves of the window
+    HeadTail,
+}
+
+/// ONNX Runtime session options. The defaults are ONNX Runtime's own; on
+/// small devices turn off `memory_arena` (and `memory_pattern`) so the CPU
+/// allocator stops reserving memory in ever-growing blocks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OnnxSessionConfig {
+    /// Graph optimization level applied when the model is loaded
+    pub optimization_level: GraphOptimization,
+    /// Threads for running independent graph nodes in parallel; 1 runs the
+    /// graph sequentially (`num_threads` still parallelizes each node)
+    pub inter_threads: usize,
+    /// Pool CPU allocations in an arena that grows on demand
+    pub memory_arena: bool,
+    /// Pre-plan allocations from the shapes of earlier runs
+    pub memory_pattern: bool,
+    /// CUDA device memory limit in MiB (0 = no limit)
+    pub gpu_memory_limit_mb: usize,
+    /// How the CUDA memory arena grows
+    pub arena_extend_strategy: ArenaExtendStrategy,
+}
+
+impl Default for OnnxSessionConfig {
+    fn default() -> Self {
+        Self {
+            optimization_level: GraphOptimization::All,
+            inter_threads: 1,
+            memory_arena: true,
+            memory_pattern: true,
+            gpu_memory_limit_mb: 0,
+            arena_extend_strategy: ArenaExtendStrategy::NextPowerOfTwo,
+        }
+    }
+}
+
+/// ONNX Runtime graph optimization level
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphOptimization {
+    Disable,
+    Basic,
+    Extended,
+    Layout,
+    #[default]
+    All,
+}
+
+/// Growth strategy of a device memory arena
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArenaExtendStrategy {
+    /// Double the arena on each extension (fewer, larger allocations)
+    #[default]
+    NextPowerOfTwo,
+    /// Extend by exactly the requested size (less memory, more allocations)
+    SameAsRequested,
+}
+
+/// OpenAI-compatible embeddings API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpEmbeddingConfig {
+    /// Full URL of the embeddings endpoint
+    pub url: String,
+    /// Model name sent with each request, also recorded with embeddings
+    pub model: String,
+    /// Environment variable holding the API key (sent as a bearer token);
+    /// no Authorization header when unset or empty
+    pub api_key_env: String,
+    /// Texts per request
+    pub batch_size: usize,
+    pub timeout_secs: u64,
+}
+
+impl Default for HttpEmbeddingConfig {
+    fn default() -> Self {
+        Self {
+            url: "https://api.openai.com/v1/embeddings".to_string(),
+            model: "text-embedding-3-large".to_string(),
+            api_key_env: "OPENAI_API_KEY".to_string(),
+            batch_size: 64,
+            timeout_secs: 30,
+        }
+    }
+}
+
+/// First-run download of the embedding model and tokenizer. Off until a
+/// mirror of the BGE-m3-ko ONNX files is configured: each URL must be
+/// pinned (a Hugging Face `resolve/<commit>` URL, never a branch) and each
+/// file needs its SHA-256, so a moved branch or a tampered file is never
+/// loaded.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelDownloadConfig {
+    /// Download missing files at startup instead of running keyword-only
+    pub enabled: bool,
+    pub model_url: String,
+    pub tokenizer_url: String,
+    /// Expected SHA-256 (hex) of the model file; required to download it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_sha256: Option<String>,
+    /// Expected SHA-256 (hex) of tokenizer.json; required to download it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokenizer_sha256: Option<String>,
+}
+
+impl EmbeddingConfig {
+    /// ONNX model file with `~` expanded
+    pub fn model_file(&self) -> PathBuf {
+        expand_home(&self.model_path)
+    }
+
+    /// Tokenizer file with `~` expanded
+    pub fn tokenizer_file(&self) -> PathBuf {
+        expand_home(&self.tokenizer_path)
+    }
+
+    /// Identifier recorded with every embedding: the model file name, or
+    /// the API model name for the HTTP backend. Swapping the model changes
+    /// it, which marks existing embeddings for re-embedding.
+    pub fn model_id(&self) -> String {
+        match self.backend {
+            EmbeddingBackend::Onnx => embedding_model_id(&self.model_file()),
+            EmbeddingBackend::Http => self.http.model.clone(),
+        }
+    }
+}
+
+/// Embedding model identifier for a model file (its file name)
+pub fn embedding_model_id(model_path: &Path) -> String {
+    model_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| model_path.to_string_lossy().to_string())
 }
 
 impl Default for EmbeddingConfig {
     fn default() -> Self {
         Self {
+            backend: EmbeddingBackend::Onnx,
             model_path: "~/.local/share/oc-memory/models/bge-m3-ko-int8.onnx".to_string(),
             tokenizer_path: "~/.local/share/oc-memory/models/tokenizer.json".to_string(),
             dimensions: 1024,
             max_length: 8192,
+            truncation: TruncationStrategy::Head,
             num_threads: 4,
+            execution_provider: ExecutionProvider::Cpu,
+            session: OnnxSessionConfig::default(),
+            download: ModelDownloadConfig::default(),
+            http: HttpEmbeddingConfig::default(),
+            chunking: ChunkingConfig::default(),
+            limits: EmbeddingLimitsConfig::default(),
         }
     }
 }
@@ -96,12 +531,67 @@ pub struct SearchConfig {
     pub recency_weight: f32,
     /// Weight for importance/priority score
     pub importance_weight: f32,
+    /// Weight for the access boost of often recalled memories
+    #[serde(default)]
+    pub access_weight: f32,
     /// Recency half-life in days (exponential decay)
     pub recency_half_life_days: f32,
     /// Default number of results
     pub default_limit: usize,
     /// HNSW ef_search parameter
     pub ef_search: usize,
+    /// How vector and keyword results are combined
+    #[serde(default)]
+    pub fusion: FusionStrategy,
+    /// RRF smoothing constant `k` (used with `fusion = "rrf"`)
+    #[serde(default = "default_rrf_k")]
+    pub rrf_k: f32,
+    /// Named weight sets (`[search.profiles.<name>]`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, ScoringProfile>,
+    /// Optional A/B ranking experiment (`[search.experiment]`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub experiment: Option<ExperimentConfig>,
+    /// How much of each memory search responses carry when the request
+    /// doesn't say: `ids`, `index`, `snippets` or `full`
+    #[serde(default)]
+    pub verbosity: Verbosity,
+    /// Per-client overrides of `verbosity`, keyed by the MCP client name
+    /// (`clientInfo.name`) or the REST `X-Client-Name` header
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub client_verbosity: BTreeMap<String, Verbosity>,
+    /// Did-you-mean corrections offered when a query's keywords match
+    /// nothing; 0 turns them off
+    #[serde(default = "default_suggestions")]
+    pub suggestions: usize,
+    /// Show only the best result of each source (e.g. the chunks of one
+    /// document) unless a search asks for all of them
+    #[serde(default = "default_collapse_sources")]
+    pub collapse_sources: bool,
+    /// Analyzer of the BM25 index. Changing it rebuilds the index at the
+    /// next start.
+    #[serde(default)]
+    pub tokenizer: Tokenizer,
+    /// Leave the shipped Korean particles and English stopwords out of
+    /// BM25 matching
+    #[serde(default = "default_default_stopwords")]
+    pub default_stopwords: bool,
+    /// Further words left out of BM25 matching
+    #[serde(default)]
+    pub stopwords: Vec<String>,
+    /// Search results kept for repeated queries (0 = disabled)
+    #[serde(default = "default_query_cache_capacity")]
+    pub query_cache_capacity: usize,
+    /// Seconds a cached search result is served before it is recomputed
+    #[serde(default = "default_query_cache_ttl_secs")]
+    pub query_cache_ttl_secs: u64,
+    /// Most queries one batch search may carry, each run on its own thread
+    #[serde(default = "default_max_batch_queries")]
+    pub max_batch_queries: usize,
+    /// Search a namespace through its own vector index, built the first
+    /// time a search targets it and saved in the `vector_shards` directory
+    #[serde(default = "default_vector_shards")]
+    pub vector_shards: bool,
 }
 
 impl Default for SearchConfig {
@@ -111,13 +601,138 @@ impl Default for SearchConfig {
             keyword_weight: 0.15,
             recency_weight: 0.15,
             importance_weight: 0.10,
+            access_weight: 0.0,
             recency_half_life_days: 30.0,
             default_limit: 10,
             ef_search: 100,
+            fusion: FusionStrategy::WeightedSum,
+            rrf_k: DEFAULT_RRF_K,
+            profiles: BTreeMap::new(),
+            experiment: None,
+            verbosity: Verbosity::Full,
+            client_verbosity: BTreeMap::new(),
+            suggestions: default_suggestions(),
+            collapse_sources: default_collapse_sources(),
+            tokenizer: Tokenizer::KoDic,
+            default_stopwords: default_default_stopwords(),
+            stopwords: Vec::new(),
+            query_cache_capacity: default_query_cache_capacity(),
+            query_cache_ttl_secs: default_query_cache_ttl_secs(),
+            max_batch_queries: default_max_batch_queries(),
+            vector_shards: default_vector_shards(),
         }
     }
 }
 
+impl SearchConfig {
+    /// Default verbosity of searches by `client`, if it is known
+    pub fn verbosity_for(&self, client: Option<&str>) -> Verbosity {
+        client
+            .and_then(|name| self.client_verbosity.get(name))
+            .copied()
+            .unwrap_or(self.verbosity)
+    }
+}
+
+/// Named scoring profile. A search uses it when it asks for it by name, or
+/// when it filters on one of the profile's tags (used as namespaces) or
+/// memory types. Unset weights fall back to the `[search]` values.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoringProfile {
+    pub semantic_weight: Option<f32>,
+    pub keyword_weight: Option<f32>,
+    pub recency_weight: Option<f32>,
+    pub importance_weight: Option<f32>,
+    pub access_weight: Option<f32>,
+    pub recency_half_life_days: Option<f32>,
+    /// Default profile for searches filtered by any of these tags
+    pub tags: Vec<String>,
+    /// Default profile for searches restricted to one of these types
+    pub memory_types: Vec<MemoryType>,
+}
+
+/// Standard RRF constant from Cormack et al.
+pub const DEFAULT_RRF_K: f32 = 60.0;
+
+fn default_rrf_k() -> f32 {
+    DEFAULT_RRF_K
+}
+
+fn default_suggestions() -> usize {
+    3
+}
+
+fn default_collapse_sources() -> bool {
+    true
+}
+
+fn default_default_stopwords() -> bool {
+    true
+}
+
+fn default_query_cache_capacity() -> usize {
+    256
+}
+
+fn default_query_cache_ttl_secs() -> u64 {
+    300
+}
+
+fn default_max_batch_queries() -> usize {
+    16
+}
+
+fn default_vector_shards() -> bool {
+    true
+}
+
+/// Strategy for fusing the vector and BM25 result lists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FusionStrategy {
+    /// Weighted sum of semantic, keyword, recency, importance and access
+    /// scores
+    #[default]
+    WeightedSum,
+    /// Reciprocal Rank Fusion of the vector and BM25 ranks, with recency,
+    /// importance and access breaking ties
+    Rrf,
+}
+
+/// How the BM25 index splits text into terms
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Tokenizer {
+    /// Korean morphemes (lindera ko-dic)
+    #[default]
+    KoDic,
+    /// Japanese morphemes (lindera IPADIC, `ipadic` feature of oc-search)
+    Ipadic,
+    /// Chinese words (lindera CC-CEDICT, `cc-cedict` feature of oc-search)
+    CcCedict,
+    /// Words split at anything but letters and digits, lowercased
+    Unicode,
+    /// Lowercased 2- and 3-character n-grams; no dictionary, any language
+    Ngram,
+}
+
+/// Variant B of an A/B ranking experiment; variant A is the `[search]` weights
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExperimentConfig {
+    /// Fraction of searches ranked with variant B (0.0 - 1.0)
+    pub b_ratio: f32,
+    pub semantic_weight: f32,
+    pub keyword_weight: f32,
+    pub recency_weight: f32,
+    pub importance_weight: f32,
+    #[serde(default)]
+    pub access_weight: f32,
+    pub recency_half_life_days: f32,
+    #[serde(default)]
+    pub fusion: FusionStrategy,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ObserverConfig {
     /// Directories to watch for file changes
@@ -126,6 +741,29 @@ pub struct ObserverConfig {
     pub recursive: bool,
     /// File extensions to monitor
     pub extensions: Vec<String>,
+    /// Quiet time after a file's last change before it is reported, so one
+    /// save that fires several events yields one (0 reports every event)
+    #[serde(default = "default_debounce_ms")]
+    pub debounce_ms: u64,
+    /// Files never ingested, in gitignore syntax relative to each watched
+    /// directory (a matching directory excludes everything in it)
+    #[serde(default = "default_ignore_patterns")]
+    pub ignore_patterns: Vec<String>,
+    /// Files longer than this many characters are stored as one linked
+    /// memory per section, split at headings (0 keeps every file whole)
+    #[serde(default = "default_max_chunk_chars")]
+    pub max_chunk_chars: usize,
+    /// Git repositories whose commits are stored as `bugfix` or `decision`
+    /// memories
+    #[serde(default)]
+    pub git_repos: Vec<String>,
+    /// Seconds between checks of `git_repos` for new commits
+    #[serde(default = "default_git_poll_seconds")]
+    pub git_poll_seconds: u64,
+    /// Most recent commits read per check; the first check stores up to
+    /// this many of a repository's history
+    #[serde(default = "default_git_max_commits")]
+    pub git_max_commits: usize,
 }
 
 impl Default for ObserverConfig {
@@ -134,16 +772,63 @@ impl Default for ObserverConfig {
             watch_dirs: Vec::new(),
             recursive: true,
             extensions: vec!["md".to_string(), "markdown".to_string(), "txt".to_string()],
+            debounce_ms: default_debounce_ms(),
+            ignore_patterns: default_ignore_patterns(),
+            max_chunk_chars: default_max_chunk_chars(),
+            git_repos: Vec::new(),
+            git_poll_seconds: default_git_poll_seconds(),
+            git_max_commits: default_git_max_commits(),
         }
     }
 }
 
+fn default_debounce_ms() -> u64 {
+    500
+}
+
+fn default_git_poll_seconds() -> u64 {
+    60
+}
+
+fn default_git_max_commits() -> usize {
+    100
+}
+
+fn default_max_chunk_chars() -> usize {
+    4000
+}
+
+fn default_ignore_patterns() -> Vec<String> {
+    [
+        ".git/",
+        "node_modules/",
+        "target/",
+        "build/",
+        "dist/",
+        ".obsidian/",
+    ]
+    .iter()
+    .map(|p| p.to_string())
+    .collect()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerConfig {
     /// REST API host
     pub host: String,
     /// REST API port
     pub port: u16,
+    /// Keys accepted on `/api/*` requests (`[[server.api_keys]]`). With
+    /// none, every request is allowed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub api_keys: Vec<ApiKeyConfig>,
+    /// Cross-origin access for browser clients (`[server.cors]`)
+    #[serde(default)]
+    pub cors: CorsConfig,
+    /// Memory change events streamed on `GET /api/v1/events`
+    /// (`[server.events]`)
+    #[serde(default)]
+    pub events: EventsConfig,
 }
 
 impl Default for ServerConfig {
@@ -151,18 +836,563 @@ impl Default for ServerConfig {
         Self {
             host: "127.0.0.1".to_string(),
             port: 6342,
+            api_keys: Vec::new(),
+            cors: CorsConfig::default(),
+            events: EventsConfig::default(),
+        }
+    }
+}
+
+/// Memory change events, read from the audit log so writes by other
+/// processes (the MCP server) are seen too
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventsConfig {
+    /// Milliseconds between audit log reads; 0 disables events
+    pub poll_interval_ms: u64,
+    /// Events a slow subscriber may fall behind before it skips ahead
+    pub buffer: usize,
+}
+
+impl Default for EventsConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval_ms: 250,
+            buffer: 1024,
+        }
+    }
+}
+
+/// CORS headers on REST responses, so web UIs served from another origin
+/// can call the API. Off while `allowed_origins` is empty.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CorsConfig {
+    /// Origins such as `http://localhost:5173`; `*` allows any
+    pub allowed_origins: Vec<String>,
+    /// HTTP methods browsers may use
+    pub allowed_methods: Vec<String>,
+    /// Seconds browsers may cache a preflight response
+    pub max_age_secs: u64,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: Vec::new(),
+            allowed_methods: ["GET", "POST", "PATCH", "DELETE"]
+                .map(String::from)
+                .to_vec(),
+            max_age_secs: 600,
+        }
+    }
+}
+
+/// A REST API key, sent as `Authorization: Bearer <key>` or `X-API-Key`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyConfig {
+    /// Label for logs and the audit log (`rest:<name>`)
+    pub name: String,
+    /// The secret itself; prefer `key_env` to keep it out of the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// Environment variable holding the secret, used when `key` is unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_env: Option<String>,
+    #[serde(default)]
+    pub scope: ApiKeyScope,
+}
+
+impl ApiKeyConfig {
+    /// The secret from `key` or `key_env`; `None` when neither is set
+    pub fn secret(&self) -> Option<String> {
+        resolve_secret(self.key.as_deref(), self.key_env.as_deref())
+    }
+}
+
+/// `key`, or else the value of the environment variable `key_env`;
+/// `None` when neither is set or the secret is empty
+fn resolve_secret(key: Option<&str>, key_env: Option<&str>) -> Option<String> {
+    key.map(str::to_string)
+        .or_else(|| key_env.and_then(|var| std::env::var(var).ok()))
+        .filter(|key| !key.is_empty())
+}
+
+/// What an API key may do
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKeyScope {
+    /// Searches and reads only
+    ReadOnly,
+    /// Everything, including stores, deletes and admin operations
+    #[default]
+    ReadWrite,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportConfig {
+    /// Target directory for the Markdown (Obsidian vault) export
+    pub markdown_dir: String,
+}
+
+impl ExportConfig {
+    /// Markdown export directory with `~` expanded
+    pub fn markdown_dir(&self) -> PathBuf {
+        expand_home(&self.markdown_dir)
+    }
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            markdown_dir: "~/.local/share/oc-memory/vault".to_string(),
+        }
+    }
+}
+
+/// Where `POST /api/v1/admin/import` reads exports from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportConfig {
+    /// Directory holding other tools' exports; import requests name paths
+    /// relative to it
+    pub dir: String,
+}
+
+impl ImportConfig {
+    /// Import directory with `~` expanded
+    pub fn dir(&self) -> PathBuf {
+        expand_home(&self.dir)
+    }
+}
+
+impl Default for ImportConfig {
+    fn default() -> Self {
+        Self {
+            dir: "~/.local/share/oc-memory/import".to_string(),
         }
     }
 }
 
-/// Expand ~ to home directory
-fn shellexpand(path: &str) -> String {
-    if path.starts_with("~/")
-        && let Some(home) = home_dir()
-    {
-        return path.replacen("~", &home.to_string_lossy(), 1);
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupConfig {
+    /// Directory for timestamped backup archives
+    pub dir: String,
+    /// Hours between scheduled backups (0 disables the scheduler)
+    pub interval_hours: u64,
+    /// Number of most recent backups to keep
+    pub keep_last: usize,
+}
+
+impl BackupConfig {
+    /// Backup directory with `~` expanded
+    pub fn dir(&self) -> PathBuf {
+        expand_home(&self.dir)
+    }
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            dir: "~/.local/share/oc-memory/backups".to_string(),
+            interval_hours: 0,
+            keep_last: 7,
+        }
+    }
+}
+
+/// Admission limits for MCP tool calls.
+///
+/// Mutations and reads are admitted through separate lanes so a flood of
+/// searches cannot delay interactive stores.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct McpConfig {
+    /// Mutating tool calls (store/delete/tune) allowed to run at once
+    pub max_concurrent_mutations: usize,
+    /// Mutating calls allowed to wait for a slot before callers are told to retry
+    pub max_queued_mutations: usize,
+    /// Read tool calls (search/get/stats) allowed to run at once
+    pub max_concurrent_reads: usize,
+    /// Read calls allowed to wait for a slot before callers are told to retry
+    pub max_queued_reads: usize,
+    /// Language of the labels in memory_search/get/stats text output;
+    /// tools take a `language` argument to override it per call
+    pub language: ResponseLanguage,
+    /// Namespace tool calls use when they don't pass one: stores go there
+    /// and searches and stats are scoped to it. Unset, stores go to
+    /// `default` and searches cover every namespace. The
+    /// `OC_MEMORY_NAMESPACE` environment variable overrides it.
+    pub namespace: Option<String>,
+}
+
+impl Default for McpConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_mutations: 2,
+            max_queued_mutations: 64,
+            max_concurrent_reads: 4,
+            max_queued_reads: 16,
+            language: ResponseLanguage::En,
+            namespace: None,
+        }
+    }
+}
+
+/// Language of human-readable labels in tool output. Memory content is
+/// never translated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResponseLanguage {
+    #[default]
+    En,
+    Ko,
+}
+
+/// How timestamps are presented to people and agents
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplayConfig {
+    /// Zone of timestamps in tool output and memory packs, and of date
+    /// filters given without an offset (`2026-01-01` is its midnight in this
+    /// zone): `utc`, `local` or an offset such as `+09:00`
+    pub timezone: DisplayTimezone,
+    /// Render retrieved memory text defensively in tool output and memory
+    /// packs: content is escaped and fenced (see [`crate::sanitize`]), and
+    /// control sequences and chat role markers are stripped, so stored
+    /// third-party text cannot pose as instructions to the agent
+    pub safe_content: bool,
+}
+
+/// Templates for the "memory pack" prompt block (see [`crate::pack`])
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PackConfig {
+    /// Line before the entries; `{count}` is the number of memories
+    pub header: String,
+    /// One entry per memory; see [`crate::pack::render_pack`] for placeholders
+    pub entry: String,
+    /// Line after the entries
+    pub footer: String,
+}
+
+impl Default for PackConfig {
+    fn default() -> Self {
+        Self {
+            header: "<memories count=\"{count}\">".to_string(),
+            entry: "<memory id=\"{id}\" type=\"{type}\" priority=\"{priority}\" age=\"{age}\" title=\"{title}\" tags=\"{tags}\">\n{content}\n</memory>".to_string(),
+            footer: "</memories>".to_string(),
+        }
+    }
+}
+
+/// Startup self-test: a probe memory is embedded, stored, searched for and
+/// deleted before serving, so broken model files or indexes show up in the
+/// log (and `/health/ready`) instead of on the first real request
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SelfTestConfig {
+    pub enabled: bool,
+}
+
+/// Near-duplicate detection on store: a new memory whose embedding is at
+/// least `threshold` cosine-similar to an existing one is handled by `action`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DedupConfig {
+    pub enabled: bool,
+    pub threshold: f32,
+    pub action: DuplicateAction,
+}
+
+impl Default for DedupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: 0.95,
+            action: DuplicateAction::Flag,
+        }
+    }
+}
+
+/// Priority escalation for topics that keep being stored: a new memory at
+/// least `similarity` cosine-similar to an existing one counts as a repeat
+/// of it, and `repeats` repeats within `window_days` raise the existing
+/// memory's priority one level (see [`crate::Storage::record_repeat`])
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EscalationConfig {
+    pub enabled: bool,
+    pub similarity: f32,
+    pub repeats: usize,
+    pub window_days: u32,
+}
+
+impl Default for EscalationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            similarity: 0.85,
+            repeats: 3,
+            window_days: 30,
+        }
+    }
+}
+
+/// Consolidation of related session/observation memories into one
+/// summary memory (see [`crate::consolidate`])
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConsolidationConfig {
+    /// Hours between scheduled runs by the REST server (0 = disabled)
+    pub interval_hours: u64,
+    /// Memory types that get consolidated
+    pub source_types: Vec<MemoryType>,
+    /// Type of the consolidated memory
+    pub target_type: MemoryType,
+    /// Minimum cosine similarity to a group's first memory to join it
+    pub similarity: f32,
+    /// Memories of one group were all created within this many hours
+    pub window_hours: u64,
+    /// Smallest group worth consolidating
+    pub min_group_size: usize,
+    /// Memories younger than this are left alone (the session may go on)
+    pub min_age_hours: u64,
+}
+
+impl Default for ConsolidationConfig {
+    fn default() -> Self {
+        Self {
+            interval_hours: 0,
+            source_types: vec![MemoryType::Session, MemoryType::Observation],
+            target_type: MemoryType::Fact,
+            similarity: 0.8,
+            window_hours: 72,
+            min_group_size: 3,
+            min_age_hours: 24,
+        }
+    }
+}
+
+/// Keyword extraction filling `concepts` of stored memories that have none
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeywordsConfig {
+    pub enabled: bool,
+    /// Most concepts extracted per memory
+    pub max_concepts: usize,
+}
+
+impl Default for KeywordsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_concepts: 5,
+        }
     }
-    path.to_string()
+}
+
+/// Screening of stored memories for prompt-injection-looking text (see
+/// [`crate::quarantine`]). Flagged memories are quarantined: left out of
+/// search and retrieval until approved through the admin API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuarantineConfig {
+    pub enabled: bool,
+    /// Phrases to flag in addition to the built-in ones (case-insensitive)
+    pub patterns: Vec<String>,
+}
+
+impl Default for QuarantineConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            patterns: Vec::new(),
+        }
+    }
+}
+
+/// Tracking of searches that found nothing (see
+/// [`crate::Storage::record_miss`]), to show where the store has gaps
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MissesConfig {
+    pub enabled: bool,
+    /// Also count searches whose best score is below this (0 = only empty
+    /// results)
+    pub min_score: f32,
+    /// Store a task memory about the gap once a query was missed this many
+    /// times (0 = never)
+    pub create_task_after: u32,
+}
+
+impl Default for MissesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_score: 0.0,
+            create_task_after: 0,
+        }
+    }
+}
+
+/// Removal of memories past their `expires_at` (see [`crate::expiry`])
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExpiryConfig {
+    /// Minutes between sweeps by the REST server (0 = only at startup)
+    pub sweep_interval_minutes: u64,
+    /// Archive expired memories or delete them outright
+    pub action: ExpiryAction,
+}
+
+impl Default for ExpiryConfig {
+    fn default() -> Self {
+        Self {
+            sweep_interval_minutes: 60,
+            action: ExpiryAction::Archive,
+        }
+    }
+}
+
+/// Syncing memories with other oc-memory instances (see [`crate::sync`])
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncConfig {
+    /// Name of this instance in its peers' audit logs (`sync:<device>`);
+    /// empty for the host name
+    pub device: String,
+    /// Minutes between syncs with every peer by the REST server (0 = only
+    /// through `POST /api/v1/admin/sync`)
+    pub interval_minutes: u64,
+    /// Most audit entries read per request
+    pub batch_size: usize,
+    pub peers: Vec<SyncPeerConfig>,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            device: String::new(),
+            interval_minutes: 0,
+            batch_size: 500,
+            peers: Vec::new(),
+        }
+    }
+}
+
+impl SyncConfig {
+    /// `device`, or else the host name, or else `oc-memory`
+    pub fn device_name(&self) -> String {
+        let device = self.device.trim();
+        if !device.is_empty() {
+            return device.to_string();
+        }
+        ["HOSTNAME", "COMPUTERNAME"]
+            .iter()
+            .find_map(|var| std::env::var(var).ok().filter(|name| !name.is_empty()))
+            .unwrap_or_else(|| "oc-memory".to_string())
+    }
+}
+
+/// Capturing decisions and bug fixes from coding-agent sessions with
+/// `oc-memory-server hook`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HookConfig {
+    /// Most memories stored per session; the latest candidates are kept
+    pub max_candidates: usize,
+    /// Shorter sentences are not taken as candidates, in characters
+    pub min_chars: usize,
+    /// Tags of every captured memory; the session's project directory
+    /// name is added
+    pub tags: Vec<String>,
+}
+
+impl Default for HookConfig {
+    fn default() -> Self {
+        Self {
+            max_candidates: 10,
+            min_chars: 20,
+            tags: vec!["hook".to_string()],
+        }
+    }
+}
+
+/// Another oc-memory REST server to sync with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncPeerConfig {
+    /// Label for logs and the stored sync progress
+    pub name: String,
+    /// Base URL of its REST API, such as `http://desktop.local:8080`
+    pub url: String,
+    /// Its read-write API key; prefer `key_env` to keep it out of the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// Environment variable holding the key, used when `key` is unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_env: Option<String>,
+}
+
+impl SyncPeerConfig {
+    /// The API key from `key` or `key_env`; `None` for a peer without keys
+    pub fn secret(&self) -> Option<String> {
+        resolve_secret(self.key.as_deref(), self.key_env.as_deref())
+    }
+}
+
+/// Archiving or deleting memories nobody uses (see [`crate::forgetting`])
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ForgettingConfig {
+    /// Hours between scheduled runs by the REST server (0 = disabled)
+    pub interval_hours: u64,
+    /// Archive forgotten memories or delete them outright
+    pub action: ExpiryAction,
+    /// Which memories are forgotten; types without a rule never are
+    pub rules: Vec<ForgettingRule>,
+}
+
+impl Default for ForgettingConfig {
+    fn default() -> Self {
+        Self {
+            interval_hours: 0,
+            action: ExpiryAction::Archive,
+            rules: Vec::new(),
+        }
+    }
+}
+
+/// Forget memories of `memory_type` neither stored nor accessed in the last
+/// `min_age_days` that were accessed at most `max_access_count` times
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForgettingRule {
+    pub memory_type: MemoryType,
+    pub min_age_days: u32,
+    #[serde(default)]
+    pub max_access_count: u32,
+}
+
+/// Expand a leading `~` to the home directory: `~`, `~/…` and, on Windows,
+/// `~\…`. The rest of the path is joined component by component, so the
+/// result uses the platform's separators. Other paths are returned unchanged.
+pub fn expand_home(path: &str) -> PathBuf {
+    let rest = if path == "~" {
+        Some("")
+    } else {
+        path.strip_prefix("~/")
+            .or_else(|| path.strip_prefix("~\\").filter(|_| cfg!(windows)))
+    };
+    match (rest, home_dir()) {
+        (Some(rest), Some(home)) => rest
+            .split(is_separator)
+            .filter(|part| !part.is_empty())
+            .fold(home, |path, part| path.join(part)),
+        _ => PathBuf::from(path),
+    }
+}
+
+fn is_separator(c: char) -> bool {
+    c == '/' || (cfg!(windows) && c == '\\')
 }
 
 /// Config directory: ~/.config/oc-memory/
@@ -173,6 +1403,60 @@ fn dirs_path() -> PathBuf {
     path
 }
 
+/// `HOME`, falling back to `USERPROFILE` on Windows (where `HOME` is only
+/// set by Unix-like shells)
 fn home_dir() -> Option<PathBuf> {
-    std::env::var_os("HOME").map(PathBuf::from)
+    let home = std::env::var_os("HOME").filter(|h| !h.is_empty());
+    #[cfg(windows)]
+    let home = home.or_else(|| std::env::var_os("USERPROFILE").filter(|h| !h.is_empty()));
+    home.map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_home_builds_platform_paths() {
+        let home = home_dir().expect("tests need a home directory");
+        assert_eq!(expand_home("~"), home);
+        assert_eq!(
+            expand_home("~/.local/share/oc-memory"),
+            home.join(".local").join("share").join("oc-memory")
+        );
+        assert_eq!(expand_home("~other/data"), PathBuf::from("~other/data"));
+        assert_eq!(expand_home("relative/data"), PathBuf::from("relative/data"));
+
+        let config = Config::default();
+        assert!(config.db_path().starts_with(&home));
+        assert_eq!(config.db_path().file_name().unwrap(), DB_FILE_NAME);
+    }
+
+    #[test]
+    fn test_save_and_reload() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("nested").join("config.toml");
+
+        let mut config = Config::default();
+        config.search.keyword_weight = 0.3;
+        config.save(&path).unwrap();
+
+        let loaded = Config::from_file(&path).unwrap();
+        assert!((loaded.search.keyword_weight - 0.3).abs() < f32::EPSILON);
+        assert_eq!(loaded.source_path.as_deref(), Some(path.as_path()));
+    }
+
+    #[test]
+    fn test_missing_optional_sections_use_defaults() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("config.toml");
+        std::fs::write(&path, include_str!("../../../config/config.example.toml")).unwrap();
+
+        let mut minimal = std::fs::read_to_string(&path).unwrap();
+        minimal.truncate(minimal.find("[export]").unwrap());
+        std::fs::write(&path, minimal).unwrap();
+
+        let loaded = Config::from_file(&path).unwrap();
+        assert_eq!(loaded.backup.keep_last, BackupConfig::default().keep_last);
+    }
 }
@@ -0,0 +1,392 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::ConsolidationConfig;
+use crate::error::Result;
+use crate::models::{Memory, MemoryMetadata, MemoryType, Priority, normalize_tags};
+use crate::storage::Storage;
+
+/// Audit actor recorded for memories created and archived by consolidation
+pub const CONSOLIDATION_ACTOR: &str = "consolidation";
+
+/// Longest excerpt of each original kept by [`ExtractiveSummarizer`]
+const EXCERPT_CHARS: usize = 200;
+
+/// Text of a consolidated memory
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    pub title: String,
+    pub content: String,
+    pub memory_type: MemoryType,
+}
+
+/// Turns a group of related memories into one. Implement this to plug in
+/// an LLM or any other summarizer; [`ExtractiveSummarizer`] is the default.
+pub trait Summarizer: Send + Sync {
+    /// Summarize `memories`, given oldest first
+    fn summarize(&self, memories: &[Memory]) -> Result<Summary>;
+}
+
+/// Summarizer without a model: a dated bullet list of each memory's title
+/// and the first line of its content
+pub struct ExtractiveSummarizer {
+    memory_type: MemoryType,
+}
+
+impl ExtractiveSummarizer {
+    pub fn new(memory_type: MemoryType) -> Self {
+        Self { memory_type }
+    }
+}
+
+impl Summarizer for ExtractiveSummarizer {
+    fn summarize(&self, memories: &[Memory]) -> Result<Summary> {
+        let first = memories
+            .first()
+            .ok_or_else(|| crate::Error::Other("Nothing to summarize".to_string()))?;
+        let title = match memories.len() {
+            1 => first.title.clone(),
+            n => format!("{} (+{} related)", first.title, n - 1),
+        };
+        let content = memories
+            .iter()
+            .map(|m| {
+                let line = m.content.lines().find(|l| !l.trim().is_empty());
+                let excerpt: String = line
+                    .unwrap_or_default()
+                    .trim()
+                    .chars()
+                    .take(EXCERPT_CHARS)
+                    .collect();
+                format!(
+                    "- [{}] {}: {excerpt}",
+                    m.created_at.format("%Y-%m-%d"),
+                    m.title
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        Ok(Summary {
+            title,
+            content,
+            memory_type: self.memory_type,
+        })
+    }
+}
+
+/// A consolidated memory and the originals it replaced
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsolidatedGroup {
+    pub id: String,
+    pub title: String,
+    pub sources: Vec<String>,
+}
+
+/// Result of [`consolidate`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConsolidationReport {
+    /// Memories eligible for consolidation (type, age and embedding)
+    pub candidates: usize,
+    pub groups: Vec<ConsolidatedGroup>,
+    /// Originals moved to the archive
+    pub archived: usize,
+}
+
+/// Group eligible memories by time window and embedding similarity.
+/// `memories` must be sorted oldest first; returns groups of indices into it.
+///
+/// Greedy and deterministic: the oldest ungrouped memory seeds a group and
+/// every later ungrouped one of the same namespace created within
+/// `window_hours` that is at least `similarity` cosine-similar to the seed
+/// joins it. Groups smaller than
+/// `min_group_size` are dropped.
+pub fn find_groups(
+    memories: &[Memory],
+    config: &ConsolidationConfig,
+    now: DateTime<Utc>,
+) -> Vec<Vec<usize>> {
+    let cutoff = now - Duration::hours(config.min_age_hours as i64);
+    let window = Duration::hours(config.window_hours as i64);
+    let eligible: Vec<usize> = (0..memories.len())
+        .filter(|&i| {
+            let m = &memories[i];
+            config.source_types.contains(&m.metadata.memory_type)
+                && m.created_at <= cutoff
+                && m.embedding.as_ref().is_some_and(|e| !e.is_empty())
+        })
+        .collect();
+
+    let mut grouped = vec![false; memories.len()];
+    let mut groups = Vec::new();
+    for (pos, &seed) in eligible.iter().enumerate() {
+        if grouped[seed] {
+            continue;
+        }
+        let seed_memory = &memories[seed];
+        let seed_embedding = seed_memory.embedding.as_deref().unwrap_or_default();
+        let mut group = vec![seed];
+        for &other in &eligible[pos + 1..] {
+            let memory = &memories[other];
+            if memory.created_at - seed_memory.created_at > window {
+                break;
+            }
+            if !grouped[other]
+                && memory.namespace == seed_memory.namespace
+                && cosine(
+                    seed_embedding,
+                    memory.embedding.as_deref().unwrap_or_default(),
+                ) >= config.similarity
+            {
+                group.push(other);
+            }
+        }
+        if group.len() >= config.min_group_size.max(2) {
+            for &i in &group {
+                grouped[i] = true;
+            }
+            groups.push(group);
+        }
+    }
+    groups
+}
+
+/// Consolidate groups of related memories (see [`find_groups`]): each group
+/// is summarized by `summarizer` into a new memory embedded with `embed`
+/// (labelled `model`), and the originals are archived in the same
+/// transaction. A group whose summary fails is logged and skipped.
+///
+/// The caller must update the search indexes with the returned groups.
+pub fn consolidate<F>(
+    storage: &Storage,
+    config: &ConsolidationConfig,
+    summarizer: &dyn Summarizer,
+    mut embed: F,
+    model: Option<&str>,
+) -> Result<ConsolidationReport>
+where
+    F: FnMut(&str) -> Option<Vec<f32>>,
+{
+    // Archived memories were set aside on purpose; leave them as they are
+    let archived = storage.archived_ids()?;
+    let memories: Vec<Memory> = storage
+        .all()?
+        .into_iter()
+        .filter(|m| config.source_types.contains(&m.metadata.memory_type))
+        .filter(|m| !archived.contains(&m.id))
+        .collect();
+    let groups = find_groups(&memories, config, Utc::now());
+    let cutoff = Utc::now() - Duration::hours(config.min_age_hours as i64);
+    let mut report = ConsolidationReport {
+        candidates: memories
+            .iter()
+            .filter(|m| m.created_at <= cutoff && m.embedding.is_some())
+            .count(),
+        ..Default::default()
+    };
+
+    for group in groups {
+        let originals: Vec<Memory> = group.iter().map(|&i| memories[i].clone()).collect();
+        let summary = match summarizer.summarize(&originals) {
+            Ok(summary) => summary,
+            Err(e) => {
+                tracing::warn!("Summarizing {} memories failed: {e}", originals.len());
+                continue;
+            }
+        };
+
+        let mut merged = Memory::new(
+            summary.content,
+            summary.title,
+            MemoryMetadata {
+                memory_type: summary.memory_type,
+                priority: originals
+                    .iter()
+                    .map(|m| m.metadata.priority)
+                    .max()
+                    .unwrap_or(Priority::Medium),
+                source: Some(CONSOLIDATION_ACTOR.to_string()),
+                tags: normalize_tags(
+                    &originals
+                        .iter()
+                        .flat_map(|m| m.metadata.tags.clone())
+                        .collect::<Vec<_>>(),
+                ),
+                concepts: union(originals.iter().map(|m| &m.metadata.concepts)),
+                files: union(originals.iter().map(|m| &m.metadata.files)),
+            },
+        );
+        merged.namespace = originals[0].namespace.clone();
+        merged.embedding = embed(&merged.content);
+        if merged.embedding.is_some() {
+            merged.embedding_model = model.map(str::to_string);
+        }
+
+        let sources: Vec<String> = originals.into_iter().map(|m| m.id).collect();
+        report.archived += storage.consolidate(&merged, &sources, Some(CONSOLIDATION_ACTOR))?;
+        tracing::info!(
+            id = %merged.id,
+            sources = sources.len(),
+            "Consolidated memories"
+        );
+        report.groups.push(ConsolidatedGroup {
+            id: merged.id,
+            title: merged.title,
+            sources,
+        });
+    }
+    Ok(report)
+}
+
+/// Items of all lists, first occurrence order
+pub(crate) fn union<'a>(lists: impl Iterator<Item = &'a Vec<String>>) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for item in lists.flatten() {
+        if !out.contains(item) {
+            out.push(item.clone());
+        }
+    }
+    out
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let denom = norm(a) * norm(b);
+    if denom == 0.0 { 0.0 } else { dot / denom }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory(title: &str, hours_ago: i64, embedding: Vec<f32>, tags: &[&str]) -> Memory {
+        let mut m = Memory::new(
+            format!("{title} 내용\n둘째 줄"),
+            title.to_string(),
+            MemoryMetadata {
+                memory_type: MemoryType::Session,
+                tags: tags.iter().map(|t| t.to_string()).collect(),
+                ..Default::default()
+            },
+        );
+        m.created_at = Utc::now() - Duration::hours(hours_ago);
+        m.embedding = Some(embedding);
+        m
+    }
+
+    #[test]
+    fn test_find_groups_by_similarity_window_and_age() {
+        let config = ConsolidationConfig {
+            min_group_size: 2,
+            ..Default::default()
+        };
+        let memories = vec![
+            memory("a1", 200, vec![1.0, 0.0], &[]),
+            memory("b1", 199, vec![0.0, 1.0], &[]),
+            memory("a2", 190, vec![0.95, 0.05], &[]),
+            // Outside the 72h window of a1
+            memory("a3", 100, vec![1.0, 0.0], &[]),
+            // Too recent
+            memory("a4", 1, vec![1.0, 0.0], &[]),
+        ];
+        let groups = find_groups(&memories, &config, Utc::now());
+        assert_eq!(groups, vec![vec![0, 2]]);
+    }
+
+    #[test]
+    fn test_find_groups_within_one_namespace() {
+        let config = ConsolidationConfig {
+            min_group_size: 2,
+            ..Default::default()
+        };
+        let mut memories = vec![
+            memory("a1", 200, vec![1.0, 0.0], &[]),
+            memory("a2", 199, vec![1.0, 0.0], &[]),
+            memory("a3", 198, vec![1.0, 0.0], &[]),
+        ];
+        memories[1].namespace = "other".to_string();
+        let groups = find_groups(&memories, &config, Utc::now());
+        assert_eq!(groups, vec![vec![0, 2]]);
+    }
+
+    #[test]
+    fn test_consolidate_archives_originals() {
+        let storage = Storage::in_memory().unwrap();
+        let mut originals = vec![
+            memory("배포 1", 50, vec![1.0, 0.0], &["deploy"]),
+            memory("배포 2", 49, vec![0.99, 0.01], &["ops"]),
+            memory("배포 3", 48, vec![0.98, 0.02], &["deploy"]),
+        ];
+        originals[1].metadata.priority = Priority::High;
+        for m in &originals {
+            storage.insert(m).unwrap();
+        }
+
+        let config = ConsolidationConfig::default();
+        let report = consolidate(
+            &storage,
+            &config,
+            &ExtractiveSummarizer::new(MemoryType::Fact),
+            |_| Some(vec![1.0, 0.0]),
+            Some("test-model"),
+        )
+        .unwrap();
+        assert_eq!(report.candidates, 3);
+        assert_eq!(report.archived, 3);
+        assert_eq!(report.groups.len(), 1);
+
+        let group = &report.groups[0];
+        assert_eq!(storage.count().unwrap(), 1);
+        let merged = storage.get(&group.id).unwrap().unwrap();
+        assert_eq!(merged.title, "배포 1 (+2 related)");
+        assert_eq!(merged.metadata.memory_type, MemoryType::Fact);
+        assert_eq!(merged.metadata.priority, Priority::High);
+        assert_eq!(merged.metadata.tags, vec!["deploy", "ops"]);
+        assert_eq!(merged.content.lines().count(), 3);
+        assert!(merged.content.contains("배포 2: 배포 2 내용"));
+
+        let archived = storage.archived(&originals[0].id).unwrap().unwrap();
+        assert_eq!(
+            archived.consolidated_into.as_deref(),
+            Some(group.id.as_str())
+        );
+        assert_eq!(archived.memory.title, "배포 1");
+        let tombstones = storage.tombstones(&group.sources).unwrap();
+        assert_eq!(tombstones.len(), 3);
+        assert_eq!(
+            tombstones[0].deleted_by.as_deref(),
+            Some(CONSOLIDATION_ACTOR)
+        );
+        assert!(storage.verify_integrity().unwrap().ok);
+    }
+    #[test]
+    fn test_consolidate_skips_archived() {
+        let storage = Storage::in_memory().unwrap();
+        let originals = [
+            memory("배포 1", 50, vec![1.0, 0.0], &[]),
+            memory("배포 2", 49, vec![0.99, 0.01], &[]),
+            memory("배포 3", 48, vec![0.98, 0.02], &[]),
+        ];
+        for m in &originals {
+            storage.insert(m).unwrap();
+        }
+        storage
+            .set_archived_state(&originals[1].id, true, None)
+            .unwrap();
+
+        let report = consolidate(
+            &storage,
+            &ConsolidationConfig::default(),
+            &ExtractiveSummarizer::new(MemoryType::Fact),
+            |_| Some(vec![1.0, 0.0]),
+            None,
+        )
+        .unwrap();
+        assert_eq!(report.candidates, 2);
+        assert!(report.groups.is_empty());
+        assert_eq!(storage.count().unwrap(), 3);
+    }
+}
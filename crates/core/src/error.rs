@@ -4,6 +4,9 @@ pub enum Error {
     #[error("Storage error: {0}")]
     Storage(#[from] rusqlite::Error),
 
+    #[error("Connection pool error: {0}")]
+    Pool(#[from] r2d2::Error),
+
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
@@ -13,6 +16,9 @@ pub enum Error {
     #[error("Not found: {0}")]
     NotFound(String),
 
+    #[error("Snapshot error: {0}")]
+    Snapshot(String),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -0,0 +1,98 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::config::ExpiryConfig;
+use crate::error::Result;
+use crate::storage::Storage;
+
+/// Audit actor recorded for memories removed by the expiry sweep
+pub const EXPIRY_ACTOR: &str = "expiry";
+
+/// Expiry time of a memory stored at `now` with a TTL of `ttl_hours`
+pub fn expires_after(now: DateTime<Utc>, ttl_hours: u64) -> DateTime<Utc> {
+    now + Duration::hours(ttl_hours.min(i64::MAX as u64 / 3600) as i64)
+}
+
+/// Archive or delete (per `config.action`) every memory whose `expires_at`
+/// has passed. Returns the IDs removed; the caller must drop them from the
+/// search indexes.
+pub fn sweep(storage: &Storage, config: &ExpiryConfig) -> Result<Vec<String>> {
+    let expired = storage.expired_ids(Utc::now())?;
+    if expired.is_empty() {
+        return Ok(expired);
+    }
+    let removed = storage.expire(&expired, config.action, Some(EXPIRY_ACTOR))?;
+    tracing::info!(
+        removed = removed.len(),
+        action = ?config.action,
+        "Swept expired memories"
+    );
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ExpiryAction, Memory};
+
+    fn memory(title: &str, expires_at: Option<DateTime<Utc>>) -> Memory {
+        let mut m = Memory::new("할 일".to_string(), title.to_string(), Default::default());
+        m.expires_at = expires_at;
+        m
+    }
+
+    #[test]
+    fn test_sweep_archives_or_deletes_expired() {
+        for action in [ExpiryAction::Archive, ExpiryAction::Delete] {
+            let storage = Storage::in_memory().unwrap();
+            let now = Utc::now();
+            let expired = memory("지난 작업", Some(now - Duration::hours(1)));
+            let pending = memory("남은 작업", Some(expires_after(now, 24)));
+            let forever = memory("영구", None);
+            for m in [&expired, &pending, &forever] {
+                storage.insert(m).unwrap();
+            }
+            assert_eq!(
+                storage.get(&pending.id).unwrap().unwrap().expires_at,
+                pending.expires_at
+            );
+
+            let config = ExpiryConfig {
+                action,
+                ..Default::default()
+            };
+            let removed = sweep(&storage, &config).unwrap();
+            assert_eq!(removed, vec![expired.id.clone()]);
+            assert_eq!(storage.count().unwrap(), 2);
+
+            let archived = storage.archived(&expired.id).unwrap();
+            match action {
+                ExpiryAction::Archive => {
+                    let archived = archived.unwrap();
+                    assert_eq!(archived.consolidated_into, None);
+                    assert_eq!(archived.memory.expires_at, expired.expires_at);
+                }
+                ExpiryAction::Delete => assert!(archived.is_none()),
+            }
+            let tombstones = storage
+                .tombstones(std::slice::from_ref(&expired.id))
+                .unwrap();
+            assert_eq!(tombstones[0].deleted_by.as_deref(), Some(EXPIRY_ACTOR));
+            assert!(sweep(&storage, &config).unwrap().is_empty());
+            assert!(storage.verify_integrity().unwrap().ok);
+        }
+    }
+    #[test]
+    fn test_sweep_skips_archived() {
+        let storage = Storage::in_memory().unwrap();
+        let kept = memory("보관한 작업", Some(Utc::now() - Duration::hours(1)));
+        storage.insert(&kept).unwrap();
+        storage.set_archived_state(&kept.id, true, None).unwrap();
+
+        assert!(
+            sweep(&storage, &ExpiryConfig::default())
+                .unwrap()
+                .is_empty()
+        );
+        assert!(storage.get(&kept.id).unwrap().is_some());
+    }
+}
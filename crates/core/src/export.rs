@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::error::Result;
+use crate::models::Memory;
+use crate::storage::Storage;
+
+/// Maximum length (in chars) of the title slug used in file names
+const MAX_SLUG_CHARS: usize = 60;
+
+/// Result of a Markdown export run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportSummary {
+    pub dir: String,
+    pub exported: usize,
+}
+
+/// Export every memory as a Markdown file with YAML frontmatter.
+///
+/// Files are grouped into one sub-directory per memory type
+/// (`<dir>/decision/<title-slug>-<id8>.md`), which Obsidian opens as a vault.
+/// Existing files with the same name are overwritten, so re-running the
+/// export refreshes the vault in place.
+///
+/// Reads from a [`ReadSnapshot`](crate::storage::ReadSnapshot), so the export
+/// is consistent and does not hold up concurrent stores.
+pub fn export_markdown(storage: &Storage, dir: impl AsRef<Path>) -> Result<ExportSummary> {
+    let dir = dir.as_ref();
+    let snapshot = storage.read_snapshot()?;
+
+    let exported = snapshot.for_each_memory(|memory| {
+        let path = markdown_path(dir, &memory);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, render_markdown(&memory))?;
+        Ok(())
+    })?;
+
+    tracing::info!(
+        dir = %dir.display(),
+        count = exported,
+        "Exported memories as Markdown"
+    );
+
+    Ok(ExportSummary {
+        dir: dir.display().to_string(),
+        exported,
+    })
+}
+
+/// Render a single memory as Markdown with YAML frontmatter
+pub fn render_markdown(memory: &Memory) -> String {
+    let meta = &memory.metadata;
+    let mut out = String::from("---\n");
+    out.push_str(&format!("id: {}\n", yaml_str(&memory.id)));
+    out.push_str(&format!("title: {}\n", yaml_str(&memory.title)));
+    out.push_str(&format!("type: {}\n", meta.memory_type.as_str()));
+    out.push_str(&format!("priority: {}\n", meta.priority.as_str()));
+    out.push_str(&format!("tags: {}\n", yaml_list(&meta.tags)));
+    if !meta.concepts.is_empty() {
+        out.push_str(&format!("concepts: {}\n", yaml_list(&meta.concepts)));
+    }
+    if !meta.files.is_empty() {
+        out.push_str(&format!("files: {}\n", yaml_list(&meta.files)));
+    }
+    if let Some(source) = &meta.source {
+        out.push_str(&format!("source: {}\n", yaml_str(source)));
+    }
+    out.push_str(&format!("created: {}\n", memory.created_at.to_rfc3339()));
+    out.push_str(&format!("updated: {}\n", memory.updated_at.to_rfc3339()));
+    out.push_str("---\n\n");
+    out.push_str(&format!("# {}\n\n", memory.title));
+    out.push_str(&memory.content);
+    if !memory.content.ends_with('\n') {
+        out.push('\n');
+    }
+    out
+}
+
+fn markdown_path(dir: &Path, memory: &Memory) -> PathBuf {
+    let short_id: String = memory.id.chars().take(8).collect();
+    let slug = slugify(&memory.title);
+    let file_name = if slug.is_empty() {
+        format!("{short_id}.md")
+    } else {
+        format!("{slug}-{short_id}.md")
+    };
+    dir.join(memory.metadata.memory_type.as_str())
+        .join(file_name)
+}
+
+/// File-name-safe slug that keeps Unicode letters (e.g. Hangul) intact
+fn slugify(title: &str) -> String {
+    let mut slug = String::new();
+    for c in title.chars() {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+        if slug.chars().count() >= MAX_SLUG_CHARS {
+            break;
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// JSON strings are valid double-quoted YAML scalars
+fn yaml_str(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_else(|_| "\"\"".to_string())
+}
+
+fn yaml_list(items: &[String]) -> String {
+    serde_json::to_string(items).unwrap_or_else(|_| "[]".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{MemoryMetadata, MemoryType, Priority};
+
+    #[test]
+    fn test_render_markdown_frontmatter() {
+        let memory = Memory::new(
+            "WAL 모드를 사용한다".to_string(),
+            "SQLite \"WAL\" 결정".to_string(),
+            MemoryMetadata {
+                memory_type: MemoryType::Decision,
+                priority: Priority::High,
+                tags: vec!["sqlite".to_string(), "성능".to_string()],
+                ..Default::default()
+            },
+        );
+        let md = render_markdown(&memory);
+        assert!(md.starts_with("---\n"));
+        assert!(md.contains("type: decision\n"));
+        assert!(md.contains("priority: high\n"));
+        assert!(md.contains("tags: [\"sqlite\",\"성능\"]\n"));
+        assert!(md.contains("title: \"SQLite \\\"WAL\\\" 결정\"\n"));
+        assert!(md.contains("# SQLite \"WAL\" 결정\n\nWAL 모드를 사용한다\n"));
+    }
+
+    #[test]
+    fn test_export_groups_by_type() {
+        let tmp = tempfile::tempdir().unwrap();
+        let storage = Storage::in_memory().unwrap();
+        for (title, memory_type) in [
+            ("한국어 선호", MemoryType::Preference),
+            ("버그 수정", MemoryType::Bugfix),
+        ] {
+            let m = Memory::new(
+                "내용".to_string(),
+                title.to_string(),
+                MemoryMetadata {
+                    memory_type,
+                    ..Default::default()
+                },
+            );
+            storage.insert(&m).unwrap();
+        }
+
+        let summary = export_markdown(&storage, tmp.path()).unwrap();
+        assert_eq!(summary.exported, 2);
+
+        let prefs: Vec<_> = std::fs::read_dir(tmp.path().join("preference"))
+            .unwrap()
+            .collect();
+        assert_eq!(prefs.len(), 1);
+        let name = prefs[0].as_ref().unwrap().file_name();
+        assert!(name.to_string_lossy().starts_with("한국어-선호-"));
+        assert!(tmp.path().join("bugfix").is_dir());
+    }
+}
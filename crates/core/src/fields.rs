@@ -0,0 +1,233 @@
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::error::{Error, Result};
+use crate::timezone::DisplayTimezone;
+
+/// Type of a registered metadata field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    Number,
+    Boolean,
+    /// RFC 3339 timestamp or `YYYY-MM-DD` (midnight in the registry's
+    /// time zone)
+    Date,
+}
+
+/// Comparison of a [`FieldFilter`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FieldOp {
+    #[serde(rename = "=")]
+    Eq,
+    #[serde(rename = "!=")]
+    Ne,
+    #[serde(rename = "<")]
+    Lt,
+    #[serde(rename = "<=")]
+    Le,
+    #[serde(rename = ">")]
+    Gt,
+    #[serde(rename = ">=")]
+    Ge,
+}
+
+impl FieldOp {
+    /// SQL operator
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            Self::Eq => "=",
+            Self::Ne => "!=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+        }
+    }
+}
+
+/// `field op value` on a registered field, with the value in its stored
+/// (numeric) form. Memories without the field never match.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldFilter {
+    pub field: String,
+    pub op: FieldOp,
+    pub value: f64,
+}
+
+/// Registered metadata fields (`[fields]` in config). Values are stored as
+/// numbers: booleans as 0/1 and dates as Unix seconds, so one index serves
+/// range filters on every type.
+#[derive(Debug, Clone, Default)]
+pub struct FieldRegistry {
+    types: BTreeMap<String, FieldType>,
+    timezone: DisplayTimezone,
+}
+
+impl FieldRegistry {
+    pub fn new(types: BTreeMap<String, FieldType>) -> Self {
+        Self {
+            types,
+            timezone: DisplayTimezone::Utc,
+        }
+    }
+
+    /// Read dates without an offset, and show dates, in `timezone`
+    pub fn with_timezone(mut self, timezone: DisplayTimezone) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+
+    fn field_type(&self, name: &str) -> Result<FieldType> {
+        self.types.get(name).copied().ok_or_else(|| {
+            let known: Vec<&str> = self.types.keys().map(String::as_str).collect();
+            Error::Other(format!(
+                "Unknown field {name:?} (registered: {})",
+                known.join(", ")
+            ))
+        })
+    }
+
+    /// Stored form of `value` for field `name`
+    pub fn encode(&self, name: &str, value: &Value) -> Result<f64> {
+        let field_type = self.field_type(name)?;
+        let encoded = match (field_type, value) {
+            (FieldType::Number, Value::Number(n)) => n.as_f64(),
+            (FieldType::Boolean, Value::Bool(b)) => Some(if *b { 1.0 } else { 0.0 }),
+            (FieldType::Date, Value::String(s)) => self
+                .timezone
+                .parse_bound(s)
+                .ok()
+                .map(|d| d.timestamp() as f64),
+            _ => None,
+        };
+        encoded.ok_or_else(|| {
+            Error::Other(format!(
+                "Field {name:?} expects a {field_type:?} value, got {value}"
+            ))
+        })
+    }
+
+    /// Encode every field of a JSON object
+    pub fn encode_all(
+        &self,
+        fields: &serde_json::Map<String, Value>,
+    ) -> Result<BTreeMap<String, f64>> {
+        fields
+            .iter()
+            .map(|(name, value)| Ok((name.clone(), self.encode(name, value)?)))
+            .collect()
+    }
+
+    /// JSON form of a stored value; unregistered fields stay numbers
+    pub fn decode(&self, name: &str, value: f64) -> Value {
+        match self.types.get(name) {
+            Some(FieldType::Boolean) => Value::Bool(value != 0.0),
+            Some(FieldType::Date) => DateTime::from_timestamp(value as i64, 0)
+                .map(|d| Value::String(self.timezone.to_rfc3339(d)))
+                .unwrap_or(Value::Null),
+            _ => serde_json::Number::from_f64(value)
+                .map(Value::Number)
+                .unwrap_or(Value::Null),
+        }
+    }
+
+    /// Parse a filter such as `sprint >= 42`, `billable = true` or
+    /// `due < 2026-01-01`
+    pub fn parse_filter(&self, filter: &str) -> Result<FieldFilter> {
+        const OPS: [(&str, FieldOp); 6] = [
+            (">=", FieldOp::Ge),
+            ("<=", FieldOp::Le),
+            ("!=", FieldOp::Ne),
+            ("=", FieldOp::Eq),
+            ("<", FieldOp::Lt),
+            (">", FieldOp::Gt),
+        ];
+        let (at, token, op) = OPS
+            .iter()
+            .filter_map(|&(token, op)| filter.find(token).map(|at| (at, token, op)))
+            .min_by_key(|&(at, token, _)| (at, std::cmp::Reverse(token.len())))
+            .ok_or_else(|| Error::Other(format!("No comparison operator in {filter:?}")))?;
+        let field = filter[..at].trim();
+        let raw = filter[at + token.len()..].trim();
+        let value = match self.field_type(field)? {
+            FieldType::Number => raw.parse::<f64>().map(Value::from).unwrap_or(Value::Null),
+            FieldType::Boolean => raw.parse::<bool>().map(Value::Bool).unwrap_or(Value::Null),
+            FieldType::Date => Value::String(raw.to_string()),
+        };
+        Ok(FieldFilter {
+            field: field.to_string(),
+            op,
+            value: self.encode(field, &value)?,
+        })
+    }
+
+    /// Parse several filters, stopping at the first invalid one
+    pub fn parse_filters(&self, filters: &[String]) -> Result<Vec<FieldFilter>> {
+        filters.iter().map(|f| self.parse_filter(f)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> FieldRegistry {
+        FieldRegistry::new(BTreeMap::from([
+            ("sprint".to_string(), FieldType::Number),
+            ("billable".to_string(), FieldType::Boolean),
+            ("due".to_string(), FieldType::Date),
+        ]))
+    }
+
+    #[test]
+    fn test_parse_filters() {
+        let registry = registry();
+        assert_eq!(
+            registry.parse_filter("sprint >= 42").unwrap(),
+            FieldFilter {
+                field: "sprint".to_string(),
+                op: FieldOp::Ge,
+                value: 42.0
+            }
+        );
+        let billable = registry.parse_filter("billable=true").unwrap();
+        assert_eq!((billable.op, billable.value), (FieldOp::Eq, 1.0));
+        let due = registry.parse_filter("due < 2026-01-01").unwrap();
+        assert_eq!(due.op, FieldOp::Lt);
+        assert_eq!(
+            registry.decode("due", due.value),
+            Value::String("2026-01-01T00:00:00+00:00".to_string())
+        );
+
+        let kst = self::registry().with_timezone("+09:00".parse().unwrap());
+        let due = kst.parse_filter("due < 2026-01-01").unwrap();
+        assert_eq!(
+            kst.decode("due", due.value),
+            Value::String("2026-01-01T00:00:00+09:00".to_string())
+        );
+
+        assert!(registry.parse_filter("sprint ~ 3").is_err());
+        assert!(registry.parse_filter("unknown = 1").is_err());
+        assert!(registry.parse_filter("billable = yes").is_err());
+    }
+
+    #[test]
+    fn test_encode_checks_types() {
+        let registry = registry();
+        let fields = serde_json::json!({ "sprint": 42, "billable": false });
+        let encoded = registry.encode_all(fields.as_object().unwrap()).unwrap();
+        assert_eq!(encoded["sprint"], 42.0);
+        assert_eq!(encoded["billable"], 0.0);
+        assert_eq!(registry.decode("billable", 0.0), Value::Bool(false));
+
+        assert!(registry.encode("sprint", &Value::from("42")).is_err());
+        assert!(registry.encode("due", &Value::from("someday")).is_err());
+    }
+}
@@ -0,0 +1,167 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::analytics::AccessEntry;
+use crate::config::ForgettingConfig;
+use crate::error::Result;
+use crate::models::ExpiryAction;
+use crate::storage::Storage;
+
+/// Audit actor recorded for memories removed by the forgetting policy
+pub const FORGETTING_ACTOR: &str = "forgetting";
+
+/// Result of [`forget`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForgettingReport {
+    /// Nothing was removed; `memories` is what a run would remove
+    pub dry_run: bool,
+    pub action: ExpiryAction,
+    /// Memories forgotten (or to be forgotten), least recently accessed
+    /// first within each rule
+    pub memories: Vec<AccessEntry>,
+}
+
+/// Memories the rules of `config` select at `now`. A memory matched by
+/// several rules is listed once.
+pub fn candidates(
+    storage: &Storage,
+    config: &ForgettingConfig,
+    now: DateTime<Utc>,
+) -> Result<Vec<AccessEntry>> {
+    let mut selected: Vec<AccessEntry> = Vec::new();
+    for rule in &config.rules {
+        let idle_since = now - Duration::days(rule.min_age_days as i64);
+        for entry in storage.unused_memories(rule.memory_type, idle_since, rule.max_access_count)? {
+            if !selected.iter().any(|e| e.id == entry.id) {
+                selected.push(entry);
+            }
+        }
+    }
+    Ok(selected)
+}
+
+/// Apply the forgetting rules: archive or delete (per `config.action`)
+/// every memory they select, or with `dry_run` only report them. The
+/// caller must drop removed memories from the search indexes.
+pub fn forget(
+    storage: &Storage,
+    config: &ForgettingConfig,
+    dry_run: bool,
+) -> Result<ForgettingReport> {
+    let mut memories = candidates(storage, config, Utc::now())?;
+    if !dry_run && !memories.is_empty() {
+        let ids: Vec<String> = memories.iter().map(|m| m.id.clone()).collect();
+        let removed = storage.expire(&ids, config.action, Some(FORGETTING_ACTOR))?;
+        memories.retain(|m| removed.contains(&m.id));
+        tracing::info!(
+            removed = removed.len(),
+            action = ?config.action,
+            "Forgot unused memories"
+        );
+    }
+    Ok(ForgettingReport {
+        dry_run,
+        action: config.action,
+        memories,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ForgettingRule;
+    use crate::models::{Memory, MemoryMetadata, MemoryType};
+
+    fn memory(title: &str, memory_type: MemoryType, days_ago: i64) -> Memory {
+        let mut m = Memory::new(
+            "내용".to_string(),
+            title.to_string(),
+            MemoryMetadata {
+                memory_type,
+                ..Default::default()
+            },
+        );
+        let at = Utc::now() - Duration::days(days_ago);
+        m.created_at = at;
+        m.updated_at = at;
+        m.accessed_at = at;
+        m
+    }
+
+    #[test]
+    fn test_rules_select_old_unused_memories_per_type() {
+        let storage = Storage::in_memory().unwrap();
+        let stale = memory("오래된 관찰", MemoryType::Observation, 100);
+        let used = memory("자주 본 관찰", MemoryType::Observation, 100);
+        let fresh = memory("새 관찰", MemoryType::Observation, 3);
+        let decision = memory("오래된 결정", MemoryType::Decision, 400);
+        for m in [&stale, &used, &fresh, &decision] {
+            storage.insert(m).unwrap();
+        }
+        // An access resets the idle time as well as counting
+        storage.touch(&used.id).unwrap();
+
+        let config = ForgettingConfig {
+            rules: vec![ForgettingRule {
+                memory_type: MemoryType::Observation,
+                min_age_days: 30,
+                max_access_count: 0,
+            }],
+            ..Default::default()
+        };
+        let report = forget(&storage, &config, true).unwrap();
+        assert!(report.dry_run);
+        let ids: Vec<&str> = report.memories.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec![stale.id.as_str()]);
+        assert_eq!(storage.count().unwrap(), 4);
+
+        let report = forget(&storage, &config, false).unwrap();
+        assert_eq!(report.memories.len(), 1);
+        assert_eq!(storage.count().unwrap(), 3);
+        let archived = storage.archived(&stale.id).unwrap().unwrap();
+        assert_eq!(archived.consolidated_into, None);
+        let tombstones = storage.tombstones(std::slice::from_ref(&stale.id)).unwrap();
+        assert_eq!(tombstones[0].deleted_by.as_deref(), Some(FORGETTING_ACTOR));
+        assert!(
+            forget(&storage, &config, false)
+                .unwrap()
+                .memories
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_archived_memories_are_not_forgotten() {
+        let storage = Storage::in_memory().unwrap();
+        let kept = memory("보관한 관찰", MemoryType::Observation, 100);
+        storage.insert(&kept).unwrap();
+        storage.set_archived_state(&kept.id, true, None).unwrap();
+
+        let config = ForgettingConfig {
+            rules: vec![ForgettingRule {
+                memory_type: MemoryType::Observation,
+                min_age_days: 30,
+                max_access_count: 0,
+            }],
+            ..Default::default()
+        };
+        assert!(
+            forget(&storage, &config, false)
+                .unwrap()
+                .memories
+                .is_empty()
+        );
+        assert!(storage.get(&kept.id).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_without_rules_nothing_is_forgotten() {
+        let storage = Storage::in_memory().unwrap();
+        storage
+            .insert(&memory("아주 오래됨", MemoryType::Fact, 1000))
+            .unwrap();
+        let report = forget(&storage, &ForgettingConfig::default(), false).unwrap();
+        assert!(report.memories.is_empty());
+        assert_eq!(storage.count().unwrap(), 1);
+    }
+}
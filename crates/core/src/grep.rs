@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+
+/// Characters of context kept on each side of a match by default
+pub const DEFAULT_CONTEXT_CHARS: usize = 120;
+
+/// A passage of a memory's content around one or more matches. Offsets
+/// count characters (not bytes) from the start of the content.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Passage {
+    pub start: usize,
+    /// Offset just past the passage
+    pub end: usize,
+    /// `(start, end)` of each match inside the passage
+    pub matches: Vec<(usize, usize)>,
+    pub text: String,
+}
+
+/// Passages of `content` around case-insensitive occurrences of the
+/// literal `pattern`, in document order. Passages whose context overlaps
+/// are merged.
+pub fn grep_pattern(content: &str, pattern: &str, context: usize, limit: usize) -> Vec<Passage> {
+    let chars: Vec<char> = content.chars().collect();
+    let matches = occurrences(&chars, &fold(pattern));
+    let mut passages = passages(&chars, matches, context);
+    passages.truncate(limit);
+    passages
+}
+
+/// Passages of `content` around the words of `query`, those covering the
+/// most distinct words first (then the most matches, then the earliest).
+/// Words match case-insensitively anywhere, so `배포` finds `배포는`.
+pub fn grep_query(content: &str, query: &str, context: usize, limit: usize) -> Vec<Passage> {
+    let chars: Vec<char> = content.chars().collect();
+    let mut terms: Vec<Vec<char>> = query.split_whitespace().map(fold).collect();
+    terms.sort();
+    terms.dedup();
+    let mut matches: Vec<(usize, usize)> = terms
+        .iter()
+        .flat_map(|term| occurrences(&chars, term))
+        .collect();
+    matches.sort();
+    let mut passages = passages(&chars, matches, context);
+    let distinct = |p: &Passage| {
+        terms
+            .iter()
+            .filter(|term| {
+                p.matches
+                    .iter()
+                    .any(|&(s, e)| fold_chars(&chars[s..e]) == **term)
+            })
+            .count()
+    };
+    passages.sort_by_cached_key(|p| {
+        (
+            std::cmp::Reverse(distinct(p)),
+            std::cmp::Reverse(p.matches.len()),
+            p.start,
+        )
+    });
+    passages.truncate(limit);
+    passages
+}
+
+/// Non-overlapping `(start, end)` offsets of `needle` in `haystack`,
+/// compared case-folded
+fn occurrences(haystack: &[char], needle: &[char]) -> Vec<(usize, usize)> {
+    if needle.is_empty() || needle.len() > haystack.len() {
+        return Vec::new();
+    }
+    let folded = fold_chars(haystack);
+    let mut found = Vec::new();
+    let mut at = 0;
+    while at + needle.len() <= folded.len() {
+        if folded[at..at + needle.len()] == *needle {
+            found.push((at, at + needle.len()));
+            at += needle.len();
+        } else {
+            at += 1;
+        }
+    }
+    found
+}
+
+/// Group sorted matches into passages with `context` characters on each
+/// side, merging passages that touch
+fn passages(chars: &[char], matches: Vec<(usize, usize)>, context: usize) -> Vec<Passage> {
+    let mut passages: Vec<Passage> = Vec::new();
+    for (start, end) in matches {
+        let from = start.saturating_sub(context);
+        let to = (end + context).min(chars.len());
+        match passages.last_mut() {
+            Some(last) if from <= last.end => {
+                last.end = last.end.max(to);
+                last.matches.push((start, end));
+            }
+            _ => passages.push(Passage {
+                start: from,
+                end: to,
+                matches: vec![(start, end)],
+                text: String::new(),
+            }),
+        }
+    }
+    for passage in &mut passages {
+        passage.text = chars[passage.start..passage.end].iter().collect();
+    }
+    passages
+}
+
+fn fold(text: &str) -> Vec<char> {
+    text.chars().map(fold_char).collect()
+}
+
+fn fold_chars(chars: &[char]) -> Vec<char> {
+    chars.iter().copied().map(fold_char).collect()
+}
+
+/// Lowercase one character to one character, keeping offsets aligned
+fn fold_char(c: char) -> char {
+    let mut lower = c.to_lowercase();
+    match (lower.next(), lower.next()) {
+        (Some(l), None) => l,
+        _ => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_passages_and_offsets() {
+        let content = "Intro. The WAL mode is on. Filler text here. wal again";
+        let passages = grep_pattern(content, "wal", 4, 10);
+        assert_eq!(passages.len(), 2);
+        assert_eq!(passages[0].matches, vec![(11, 14)]);
+        assert_eq!(passages[0].text, "The WAL mod");
+        assert_eq!(passages[1].matches, vec![(45, 48)]);
+        assert_eq!(passages[1].text, "re. wal aga");
+
+        // Overlapping context merges
+        let merged = grep_pattern(content, "wal", 40, 10);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].matches.len(), 2);
+        assert!(grep_pattern(content, "absent", 4, 10).is_empty());
+        assert_eq!(grep_pattern(content, "wal", 4, 1).len(), 1);
+    }
+
+    #[test]
+    fn test_offsets_count_characters() {
+        let content = "가나다 배포는 금요일";
+        let passages = grep_pattern(content, "배포", 0, 10);
+        assert_eq!(passages[0].matches, vec![(4, 6)]);
+        assert_eq!(passages[0].text, "배포");
+    }
+
+    #[test]
+    fn test_query_ranks_passages_covering_more_words() {
+        let content = format!(
+            "배포 일정 공유. {} 스테이징 배포 후 운영 배포. {} 배포",
+            "x".repeat(50),
+            "y".repeat(50)
+        );
+        let passages = grep_query(&content, "배포 스테이징", 5, 2);
+        assert_eq!(passages.len(), 2);
+        assert!(passages[0].text.contains("스테이징"));
+        assert_eq!(passages[1].start, 0);
+    }
+}
@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::models::Memory;
+
+/// Chain value that precedes the first audit log entry
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Audit log action recorded for a stored memory
+pub const ACTION_INSERT: &str = "insert";
+/// Audit log action recorded when a memory's metadata is changed
+pub const ACTION_UPDATE: &str = "update";
+/// Audit log action recorded for a deleted memory
+pub const ACTION_DELETE: &str = "delete";
+/// Audit log action recorded when a memory's priority is raised because
+/// its topic kept being stored
+pub const ACTION_ESCALATE: &str = "escalate";
+/// Audit log action recorded when a memory is archived
+pub const ACTION_ARCHIVE: &str = "archive";
+/// Audit log action recorded when an archived memory is brought back
+pub const ACTION_UNARCHIVE: &str = "unarchive";
+
+/// SHA-256 over the stored fields of a memory. Metadata changes through
+/// [`crate::Storage::update_metadata`] log the new hash.
+///
+/// Access bookkeeping (`accessed_at`, `access_count`) and the embedding are
+/// excluded; the content is hashed as plain text, so cold compression does
+/// not change the hash.
+pub fn content_hash(memory: &Memory) -> String {
+    let meta = &memory.metadata;
+    let canonical = serde_json::json!([
+        memory.id,
+        memory.title,
+        memory.content,
+        meta.memory_type.as_str(),
+        meta.priority.as_str(),
+        meta.source,
+        meta.tags,
+        meta.concepts,
+        meta.files,
+        memory.created_at.to_rfc3339(),
+    ]);
+    sha256_hex(canonical.to_string().as_bytes())
+}
+
+/// One entry of the store-level hash chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub seq: i64,
+    pub at: String,
+    pub action: String,
+    pub memory_id: String,
+    pub content_hash: String,
+    pub chain_hash: String,
+}
+
+impl AuditEntry {
+    /// Chain hash of this entry given the previous entry's chain hash
+    pub fn compute_chain_hash(&self, prev: &str) -> String {
+        chain_hash(
+            prev,
+            self.seq,
+            &self.at,
+            &self.action,
+            &self.memory_id,
+            &self.content_hash,
+        )
+    }
+}
+
+pub(crate) fn chain_hash(
+    prev: &str,
+    seq: i64,
+    at: &str,
+    action: &str,
+    memory_id: &str,
+    content_hash: &str,
+) -> String {
+    let canonical = serde_json::json!([prev, seq, at, action, memory_id, content_hash]);
+    sha256_hex(canonical.to_string().as_bytes())
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Result of [`crate::Storage::verify_integrity`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    /// True when no problem below was found
+    pub ok: bool,
+    pub memories_checked: usize,
+    /// Rows stored before hashing existed; they cannot be verified
+    pub unhashed: usize,
+    /// Rows whose fields no longer match their stored content hash
+    pub content_mismatches: Vec<String>,
+    /// Rows whose stored hash is not backed by the audit log
+    pub unlogged: Vec<String>,
+    pub audit_entries: usize,
+    /// Sequence number of the first audit entry whose chain hash is wrong
+    pub chain_broken_at: Option<i64>,
+    /// Latest chain hash; record it externally to detect a rewritten log
+    pub head_hash: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::MemoryMetadata;
+
+    #[test]
+    fn test_content_hash_ignores_access_fields() {
+        let mut memory = Memory::new(
+            "내용".to_string(),
+            "제목".to_string(),
+            MemoryMetadata::default(),
+        );
+        let before = content_hash(&memory);
+        memory.access_count = 5;
+        memory.accessed_at = chrono::Utc::now();
+        assert_eq!(content_hash(&memory), before);
+
+        memory.content.push('!');
+        assert_ne!(content_hash(&memory), before);
+        assert_eq!(before.len(), 64);
+    }
+}
@@ -1,9 +1,35 @@
+pub mod analytics;
+pub mod backup;
+pub mod cache;
 pub mod config;
+pub mod consolidate;
 pub mod error;
+pub mod expiry;
+pub mod export;
+pub mod fields;
+pub mod forgetting;
+pub mod grep;
+pub mod integrity;
+pub mod merge;
+pub mod migrations;
 pub mod models;
+pub mod pack;
+pub mod quarantine;
+pub mod reembed;
+pub mod sanitize;
+pub mod snapshot;
 pub mod storage;
+pub mod sync;
+pub mod tiering;
+pub mod timezone;
 
 pub use config::Config;
 pub use error::{Error, Result};
-pub use models::{Memory, MemoryMetadata, MemoryType, Priority, SearchQuery, SearchResult};
+pub use models::{
+    ArchivedMemory, ConceptCount, DEFAULT_NAMESPACE, DuplicateAction, DuplicateMatch, ExpiryAction,
+    Feedback, FeedbackReport, LinkedMemory, Memory, MemoryFilter, MemoryLink, MemoryMetadata,
+    MemoryPatch, MemoryType, NamespaceCount, Priority, QuarantinedMemory, Relation, RetagReport,
+    SearchQuery, SearchResult, TagEdit, Tombstone, Verbosity, namespace_or_default, normalize_tag,
+    normalize_tags,
+};
 pub use storage::Storage;
@@ -0,0 +1,267 @@
+//! Merging duplicate memories into one. The merged memory gets the union of
+//! the originals' tags, concepts and files, their highest priority and
+//! earliest creation time, and a `supersedes` link to each original —
+//! which stays stored, superseded by it, but is archived so default
+//! searches find only the merged memory.
+
+use serde::{Deserialize, Serialize};
+
+use crate::consolidate::{Summarizer, union};
+use crate::error::{Error, Result};
+use crate::models::{Memory, MemoryMetadata, Priority, normalize_tags};
+use crate::storage::Storage;
+
+/// How the merged memory's content is made
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeMode {
+    /// Each original's content, oldest first (identical ones once)
+    #[default]
+    Concatenate,
+    /// What the [`Summarizer`] writes from the originals
+    Summarize,
+}
+
+/// Memories to merge and how
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MergeRequest {
+    pub ids: Vec<String>,
+    #[serde(default)]
+    pub mode: MergeMode,
+    /// Title of the merged memory; the oldest original's (or the summary's)
+    /// when unset
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
+/// Result of [`merge`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeReport {
+    /// ID of the merged memory; the caller must index it
+    pub id: String,
+    pub title: String,
+    /// IDs of the originals, oldest first, now superseded by `id` and
+    /// archived
+    pub superseded: Vec<String>,
+}
+
+/// Merge the memories of `request` into a new one, embedded with `embed`
+/// (labelled `model`), linked `supersedes` to each original and archiving
+/// them in the same transaction, logged with `actor`. The originals must
+/// exist and share a namespace. They stay indexed; searches filter them
+/// out until they are unarchived.
+pub fn merge<F>(
+    storage: &Storage,
+    request: &MergeRequest,
+    summarizer: &dyn Summarizer,
+    embed: F,
+    model: Option<&str>,
+    actor: Option<&str>,
+) -> Result<MergeReport>
+where
+    F: FnOnce(&str) -> Option<Vec<f32>>,
+{
+    let mut ids: Vec<String> = Vec::new();
+    for id in &request.ids {
+        if !ids.contains(id) {
+            ids.push(id.clone());
+        }
+    }
+    if ids.len() < 2 {
+        return Err(Error::Other(
+            "Merging needs at least two distinct memories".to_string(),
+        ));
+    }
+    let mut originals = storage.get_many(&ids)?;
+    if let Some(missing) = ids
+        .iter()
+        .find(|id| !originals.iter().any(|m| &m.id == *id))
+    {
+        return Err(Error::NotFound(missing.clone()));
+    }
+    if originals
+        .iter()
+        .any(|m| m.namespace != originals[0].namespace)
+    {
+        return Err(Error::Other(
+            "Memories of different namespaces cannot be merged".to_string(),
+        ));
+    }
+    originals.sort_by_key(|m| m.created_at);
+    let oldest = &originals[0];
+
+    let (title, content) = match request.mode {
+        MergeMode::Concatenate => {
+            let mut parts: Vec<&str> = Vec::new();
+            for content in originals.iter().map(|m| m.content.trim()) {
+                if !parts.contains(&content) {
+                    parts.push(content);
+                }
+            }
+            (oldest.title.clone(), parts.join("\n\n"))
+        }
+        MergeMode::Summarize => {
+            let summary = summarizer.summarize(&originals)?;
+            (summary.title, summary.content)
+        }
+    };
+    let title = request
+        .title
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map_or(title, str::to_string);
+
+    let mut merged = Memory::new(
+        content,
+        title,
+        MemoryMetadata {
+            memory_type: oldest.metadata.memory_type,
+            priority: originals
+                .iter()
+                .map(|m| m.metadata.priority)
+                .max()
+                .unwrap_or(Priority::Medium),
+            source: None,
+            tags: normalize_tags(
+                &originals
+                    .iter()
+                    .flat_map(|m| m.metadata.tags.clone())
+                    .collect::<Vec<_>>(),
+            ),
+            concepts: union(originals.iter().map(|m| &m.metadata.concepts)),
+            files: union(originals.iter().map(|m| &m.metadata.files)),
+        },
+    );
+    merged.namespace = oldest.namespace.clone();
+    merged.created_at = oldest.created_at;
+    merged.embedding = embed(&merged.content);
+    if merged.embedding.is_some() {
+        merged.embedding_model = model.map(str::to_string);
+    }
+
+    let superseded: Vec<String> = originals.into_iter().map(|m| m.id).collect();
+    storage.insert_superseding(&merged, &superseded, actor)?;
+    tracing::info!(id = %merged.id, originals = superseded.len(), "Merged memories");
+    Ok(MergeReport {
+        id: merged.id,
+        title: merged.title,
+        superseded,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consolidate::ExtractiveSummarizer;
+    use crate::models::{MemoryType, Relation};
+    use std::collections::HashSet;
+
+    fn memory(content: &str, hours_ago: i64, tags: &[&str], files: &[&str]) -> Memory {
+        let mut m = Memory::new(
+            content.to_string(),
+            content.to_string(),
+            MemoryMetadata {
+                memory_type: MemoryType::Decision,
+                tags: tags.iter().map(|t| t.to_string()).collect(),
+                files: files.iter().map(|f| f.to_string()).collect(),
+                ..Default::default()
+            },
+        );
+        m.created_at -= chrono::Duration::hours(hours_ago);
+        m
+    }
+
+    #[test]
+    fn test_merge_unions_metadata_and_supersedes_originals() {
+        let storage = Storage::in_memory().unwrap();
+        let newer = memory("SQLite WAL 모드 사용", 1, &["db"], &["storage.rs"]);
+        let mut older = memory("SQLite는 WAL 모드로 연다", 5, &["sqlite", "db"], &[]);
+        older.metadata.priority = Priority::High;
+        let copy = memory("SQLite WAL 모드 사용", 2, &[], &["config.rs"]);
+        for m in [&newer, &older, &copy] {
+            storage.insert(m).unwrap();
+        }
+
+        let request = MergeRequest {
+            ids: vec![newer.id.clone(), older.id.clone(), copy.id.clone()],
+            ..Default::default()
+        };
+        let summarizer = ExtractiveSummarizer::new(MemoryType::Fact);
+        let report = merge(
+            &storage,
+            &request,
+            &summarizer,
+            |_| Some(vec![1.0]),
+            Some("m"),
+            Some("tester"),
+        )
+        .unwrap();
+        assert_eq!(
+            report.superseded,
+            [older.id.clone(), copy.id.clone(), newer.id.clone()]
+        );
+
+        let merged = storage.get(&report.id).unwrap().unwrap();
+        assert_eq!(merged.title, older.title);
+        assert_eq!(
+            merged.content,
+            "SQLite는 WAL 모드로 연다\n\nSQLite WAL 모드 사용"
+        );
+        assert_eq!(merged.metadata.tags, ["sqlite", "db"]);
+        assert_eq!(merged.metadata.files, ["config.rs", "storage.rs"]);
+        assert_eq!(merged.metadata.priority, Priority::High);
+        assert_eq!(merged.metadata.memory_type, MemoryType::Decision);
+        assert_eq!(merged.created_at, older.created_at);
+        assert_eq!(merged.embedding_model.as_deref(), Some("m"));
+
+        let links = storage.links_of(std::slice::from_ref(&older.id)).unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(
+            (links[0].from_id.as_str(), links[0].relation),
+            (report.id.as_str(), Relation::Supersedes)
+        );
+        // The originals stay readable but are archived
+        assert!(storage.get(&older.id).unwrap().is_some());
+        assert_eq!(
+            storage.archived_among(&report.superseded).unwrap(),
+            report.superseded.iter().cloned().collect::<HashSet<_>>()
+        );
+        assert!(storage.verify_integrity().unwrap().ok);
+    }
+
+    #[test]
+    fn test_merge_summarizes_or_refuses() {
+        let storage = Storage::in_memory().unwrap();
+        let a = memory("배포 절차", 3, &[], &[]);
+        let b = memory("배포 순서", 1, &[], &[]);
+        storage.insert(&a).unwrap();
+        storage.insert(&b).unwrap();
+        let summarizer = ExtractiveSummarizer::new(MemoryType::Fact);
+
+        let request = MergeRequest {
+            ids: vec![a.id.clone(), b.id.clone()],
+            mode: MergeMode::Summarize,
+            title: Some("배포".to_string()),
+        };
+        let report = merge(&storage, &request, &summarizer, |_| None, None, None).unwrap();
+        let merged = storage.get(&report.id).unwrap().unwrap();
+        assert_eq!(merged.title, "배포");
+        assert!(merged.content.starts_with("- ["));
+        assert_eq!(merged.content.lines().count(), 2);
+
+        let one = MergeRequest {
+            ids: vec![a.id.clone(), a.id.clone()],
+            ..Default::default()
+        };
+        assert!(merge(&storage, &one, &summarizer, |_| None, None, None).is_err());
+        let missing = MergeRequest {
+            ids: vec![a.id.clone(), "missing".to_string()],
+            ..Default::default()
+        };
+        assert!(matches!(
+            merge(&storage, &missing, &summarizer, |_| None, None, None),
+            Err(Error::NotFound(id)) if id == "missing"
+        ));
+    }
+}
@@ -0,0 +1,403 @@
+use rusqlite::{Connection, Transaction, TransactionBehavior, params};
+
+use crate::error::{Error, Result};
+
+/// One step of the schema, applied in a transaction together with the
+/// `user_version` bump that records it
+struct Migration {
+    description: &'static str,
+    apply: fn(&Transaction) -> Result<()>,
+}
+
+/// Every schema change in order; migration `i` brings a database from
+/// version `i` to `i + 1`. Append only: never edit or reorder a released
+/// step. Steps must also be safe on databases from before versioning,
+/// which are at version 0 whatever columns they already have.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        description: "base tables",
+        apply: base_tables,
+    },
+    Migration {
+        description: "content compression and hashes",
+        apply: |tx| {
+            add_column_if_missing(
+                tx,
+                "memories",
+                "content_compressed",
+                "INTEGER NOT NULL DEFAULT 0",
+            )?;
+            add_column_if_missing(tx, "memories", "content_hash", "TEXT")?;
+            Ok(())
+        },
+    },
+    Migration {
+        description: "embedding versions",
+        apply: |tx| {
+            add_column_if_missing(tx, "memories", "embedding_model", "TEXT")?;
+            add_column_if_missing(tx, "memories", "embedding_dim", "INTEGER")?;
+            Ok(())
+        },
+    },
+    Migration {
+        description: "audit actors",
+        apply: |tx| {
+            add_column_if_missing(tx, "audit_log", "actor", "TEXT")?;
+            Ok(())
+        },
+    },
+    Migration {
+        description: "cold tier and expiry",
+        apply: |tx| {
+            add_column_if_missing(tx, "memories", "cold", "INTEGER NOT NULL DEFAULT 0")?;
+            add_column_if_missing(tx, "memories", "expires_at", "TEXT")?;
+            tx.execute(
+                "CREATE INDEX IF NOT EXISTS idx_memories_expires ON memories(expires_at)",
+                [],
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        description: "namespaces",
+        apply: |tx| {
+            add_column_if_missing(
+                tx,
+                "memories",
+                "namespace",
+                "TEXT NOT NULL DEFAULT 'default'",
+            )?;
+            tx.execute(
+                "CREATE INDEX IF NOT EXISTS idx_memories_namespace ON memories(namespace)",
+                [],
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        description: "quarantine",
+        apply: |tx| {
+            add_column_if_missing(tx, "memories", "quarantine_reason", "TEXT")?;
+            add_column_if_missing(tx, "memories", "quarantined_at", "TEXT")?;
+            Ok(())
+        },
+    },
+    Migration {
+        description: "decayed access scores",
+        apply: |tx| {
+            if add_column_if_missing(tx, "memories", "access_score", "REAL NOT NULL DEFAULT 0")? {
+                // Existing popularity starts from the raw count and fades from here
+                tx.execute("UPDATE memories SET access_score = access_count", [])?;
+            }
+            Ok(())
+        },
+    },
+    Migration {
+        description: "sync peers",
+        apply: |tx| {
+            tx.execute_batch(
+                "CREATE TABLE IF NOT EXISTS sync_peers (
+                    peer TEXT PRIMARY KEY,
+                    pulled_seq INTEGER NOT NULL DEFAULT 0,
+                    pushed_seq INTEGER NOT NULL DEFAULT 0,
+                    synced_at TEXT
+                )",
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        description: "archive state",
+        apply: |tx| {
+            add_column_if_missing(tx, "memories", "archived_at", "TEXT")?;
+            Ok(())
+        },
+    },
+    Migration {
+        description: "experiment metrics",
+        apply: |tx| {
+            tx.execute_batch(
+                "CREATE TABLE IF NOT EXISTS experiment_metrics (
+                    experiment TEXT NOT NULL,
+                    variant TEXT NOT NULL,
+                    searches INTEGER NOT NULL DEFAULT 0,
+                    results_served INTEGER NOT NULL DEFAULT 0,
+                    fetched INTEGER NOT NULL DEFAULT 0,
+                    fetched_rank_sum INTEGER NOT NULL DEFAULT 0,
+                    PRIMARY KEY (experiment, variant)
+                )",
+            )?;
+            Ok(())
+        },
+    },
+];
+
+/// Schema version of databases written by this build, kept in SQLite's
+/// `user_version` pragma
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Schema version recorded in the database; 0 for a new database or one
+/// from before versioning
+pub fn schema_version(conn: &Connection) -> Result<u32> {
+    Ok(conn.query_row("PRAGMA user_version", [], |row| row.get(0))?)
+}
+
+/// Bring the database up to [`SCHEMA_VERSION`], one migration per
+/// transaction. Returns the version it started from. A database written
+/// by a newer build is refused rather than modified.
+pub(crate) fn migrate(conn: &mut Connection) -> Result<u32> {
+    migrate_to(conn, SCHEMA_VERSION)
+}
+
+fn migrate_to(conn: &mut Connection, target: u32) -> Result<u32> {
+    let from = schema_version(conn)?;
+    if from > SCHEMA_VERSION {
+        return Err(Error::Other(format!(
+            "Database schema version {from} is newer than supported version {SCHEMA_VERSION}"
+        )));
+    }
+    loop {
+        // The version is read again under the write lock, so processes
+        // opening the database together apply each migration once
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let version = schema_version(&tx)?;
+        if version >= target {
+            break;
+        }
+        let migration = &MIGRATIONS[version as usize];
+        (migration.apply)(&tx)?;
+        tx.pragma_update(None, "user_version", version + 1)?;
+        tx.commit()?;
+        tracing::info!(
+            version = version + 1,
+            "Applied schema migration: {}",
+            migration.description
+        );
+    }
+    Ok(from)
+}
+
+fn base_tables(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS memories (
+            id TEXT PRIMARY KEY,
+            content TEXT NOT NULL,
+            title TEXT NOT NULL,
+            memory_type TEXT NOT NULL,
+            priority TEXT NOT NULL,
+            source TEXT,
+            tags TEXT NOT NULL DEFAULT '[]',
+            concepts TEXT NOT NULL DEFAULT '[]',
+            files TEXT NOT NULL DEFAULT '[]',
+            embedding BLOB,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            accessed_at TEXT NOT NULL,
+            access_count INTEGER NOT NULL DEFAULT 0
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_memories_type ON memories(memory_type);
+        CREATE INDEX IF NOT EXISTS idx_memories_priority ON memories(priority);
+        CREATE INDEX IF NOT EXISTS idx_memories_created ON memories(created_at);
+        CREATE INDEX IF NOT EXISTS idx_memories_accessed ON memories(accessed_at);
+
+        CREATE TABLE IF NOT EXISTS memory_chunks (
+            memory_id TEXT NOT NULL,
+            chunk INTEGER NOT NULL,
+            embedding BLOB NOT NULL,
+            PRIMARY KEY (memory_id, chunk)
+        );
+
+        CREATE TABLE IF NOT EXISTS archived_memories (
+            id TEXT PRIMARY KEY,
+            memory TEXT NOT NULL,
+            consolidated_into TEXT NOT NULL,
+            archived_at TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS memory_links (
+            from_id TEXT NOT NULL,
+            to_id TEXT NOT NULL,
+            relation TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            PRIMARY KEY (from_id, to_id, relation)
+        );
+        CREATE INDEX IF NOT EXISTS idx_memory_links_to ON memory_links(to_id);
+
+        CREATE TABLE IF NOT EXISTS memory_fields (
+            memory_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            value REAL NOT NULL,
+            PRIMARY KEY (memory_id, name)
+        );
+        CREATE INDEX IF NOT EXISTS idx_memory_fields_value ON memory_fields(name, value);
+
+        CREATE TABLE IF NOT EXISTS memory_concepts (
+            memory_id TEXT NOT NULL,
+            concept TEXT NOT NULL,
+            PRIMARY KEY (memory_id, concept)
+        );
+        CREATE INDEX IF NOT EXISTS idx_memory_concepts_concept ON memory_concepts(concept);
+
+        CREATE TABLE IF NOT EXISTS memory_feedback (
+            memory_id TEXT PRIMARY KEY,
+            useful INTEGER NOT NULL DEFAULT 0,
+            not_useful INTEGER NOT NULL DEFAULT 0,
+            updated_at TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS missed_queries (
+            query TEXT NOT NULL,
+            namespace TEXT NOT NULL DEFAULT '',
+            count INTEGER NOT NULL DEFAULT 0,
+            best_score REAL,
+            first_at TEXT NOT NULL,
+            last_at TEXT NOT NULL,
+            task_id TEXT,
+            PRIMARY KEY (query, namespace)
+        );
+
+        CREATE TABLE IF NOT EXISTS topic_repeats (
+            memory_id TEXT NOT NULL,
+            at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_topic_repeats_memory ON topic_repeats(memory_id, at);
+
+        CREATE TABLE IF NOT EXISTS access_events (
+            memory_id TEXT NOT NULL,
+            at TEXT NOT NULL,
+            recency REAL
+        );
+        CREATE INDEX IF NOT EXISTS idx_access_events_at ON access_events(at);
+
+        CREATE TABLE IF NOT EXISTS observed_files (
+            path TEXT PRIMARY KEY,
+            content_hash TEXT NOT NULL,
+            ingested_at TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS audit_log (
+            seq INTEGER PRIMARY KEY,
+            at TEXT NOT NULL,
+            action TEXT NOT NULL,
+            memory_id TEXT NOT NULL,
+            content_hash TEXT NOT NULL,
+            chain_hash TEXT NOT NULL
+        );
+        ",
+    )?;
+    Ok(())
+}
+
+/// Add a column unless the table already has it, as databases from
+/// before versioning may. Returns whether it was added.
+fn add_column_if_missing(
+    tx: &Transaction,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<bool> {
+    let exists: bool = tx.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = ?2",
+        params![table, column],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        tx.execute_batch(&format!(
+            "ALTER TABLE {table} ADD COLUMN {column} {definition}"
+        ))?;
+    }
+    Ok(!exists)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Storage;
+
+    fn columns(conn: &Connection, table: &str) -> Vec<String> {
+        let mut stmt = conn
+            .prepare("SELECT name FROM pragma_table_info(?1)")
+            .unwrap();
+        stmt.query_map([table], |row| row.get(0))
+            .unwrap()
+            .collect::<std::result::Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_upgrades_from_every_prior_version() {
+        let latest = {
+            let mut conn = Connection::open_in_memory().unwrap();
+            migrate(&mut conn).unwrap();
+            (columns(&conn, "memories"), columns(&conn, "audit_log"))
+        };
+
+        // Rows written after this migration start with a zero access score
+        let decayed = MIGRATIONS
+            .iter()
+            .position(|m| m.description == "decayed access scores")
+            .unwrap() as u32;
+
+        for version in 0..SCHEMA_VERSION {
+            let dir = tempfile::tempdir().unwrap();
+            let db_path = dir.path().join("old.db");
+            {
+                let mut conn = Connection::open(&db_path).unwrap();
+                migrate_to(&mut conn, version).unwrap();
+                assert_eq!(schema_version(&conn).unwrap(), version);
+                if version > 0 {
+                    conn.execute(
+                        "INSERT INTO memories (id, content, title, memory_type, priority,
+                         created_at, updated_at, accessed_at, access_count)
+                         VALUES ('old', '내용', '이전 버전', 'fact', '\"medium\"', ?1, ?1, ?1, 4)",
+                        ["2026-01-01T00:00:00+00:00"],
+                    )
+                    .unwrap();
+                }
+            }
+
+            let storage = Storage::open(&db_path).unwrap();
+            assert_eq!(
+                storage.schema_version().unwrap(),
+                SCHEMA_VERSION,
+                "from {version}"
+            );
+            let conn = Connection::open(&db_path).unwrap();
+            assert_eq!(columns(&conn, "memories"), latest.0, "from {version}");
+            assert_eq!(columns(&conn, "audit_log"), latest.1, "from {version}");
+            if version > 0 {
+                let old = storage.get("old").unwrap().unwrap();
+                assert_eq!(old.content, "내용");
+                assert_eq!(old.namespace, crate::models::DEFAULT_NAMESPACE);
+                let score: f64 = conn
+                    .query_row(
+                        "SELECT access_score FROM memories WHERE id = 'old'",
+                        [],
+                        |row| row.get(0),
+                    )
+                    .unwrap();
+                let expected = if version > decayed { 0.0 } else { 4.0 };
+                assert_eq!(score, expected, "from {version}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_migrating_twice_changes_nothing() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        assert_eq!(migrate(&mut conn).unwrap(), 0);
+        assert_eq!(migrate(&mut conn).unwrap(), SCHEMA_VERSION);
+        assert_eq!(schema_version(&conn).unwrap(), SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_refuses_newer_schema() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+            .unwrap();
+        assert!(migrate(&mut conn).is_err());
+        assert_eq!(schema_version(&conn).unwrap(), SCHEMA_VERSION + 1);
+    }
+}
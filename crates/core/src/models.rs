@@ -1,7 +1,14 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use unicode_normalization::UnicodeNormalization;
 use uuid::Uuid;
 
+use crate::config::FusionStrategy;
+use crate::error::Result;
+use crate::fields::FieldFilter;
+use crate::timezone::DisplayTimezone;
+
 /// Memory entry — the atomic unit of stored knowledge
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Memory {
@@ -10,10 +17,35 @@ pub struct Memory {
     pub title: String,
     pub metadata: MemoryMetadata,
     pub embedding: Option<Vec<f32>>,
+    /// Model that produced `embedding` (see [`crate::config::EmbeddingConfig::model_id`])
+    #[serde(default)]
+    pub embedding_model: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub accessed_at: DateTime<Utc>,
     pub access_count: u32,
+    /// When the memory expires; the expiry sweep then archives or deletes it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Project or agent the memory belongs to; searches and stats can be
+    /// scoped to one namespace
+    #[serde(default = "default_namespace")]
+    pub namespace: String,
+}
+
+/// Namespace of memories stored without one
+pub const DEFAULT_NAMESPACE: &str = "default";
+
+fn default_namespace() -> String {
+    DEFAULT_NAMESPACE.to_string()
+}
+
+/// `namespace` trimmed, or [`DEFAULT_NAMESPACE`] when unset or blank
+pub fn namespace_or_default(namespace: Option<&str>) -> String {
+    match namespace.map(str::trim) {
+        Some(ns) if !ns.is_empty() => ns.to_string(),
+        _ => default_namespace(),
+    }
 }
 
 impl Memory {
@@ -25,13 +57,21 @@ impl Memory {
             title,
             metadata,
             embedding: None,
+            embedding_model: None,
             created_at: now,
             updated_at: now,
             accessed_at: now,
             access_count: 0,
+            expires_at: None,
+            namespace: default_namespace(),
         }
     }
 
+    /// Whether the memory has expired by `now`
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|at| at <= now)
+    }
+
     /// Token count estimate (rough: 1 token ≈ 3.5 chars for Korean)
     pub fn estimated_tokens(&self) -> usize {
         (self.content.len() as f64 / 3.5).ceil() as usize
@@ -62,6 +102,146 @@ impl Default for MemoryMetadata {
     }
 }
 
+/// Canonical form of a tag: trimmed, NFC-normalized and lowercased, so
+/// "Rust", "rust" and " rust " are the same tag
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim().nfc().collect::<String>().to_lowercase()
+}
+
+/// Normalize every tag, dropping empty ones and duplicates (first wins)
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut out: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags.iter().map(|t| normalize_tag(t)) {
+        if !tag.is_empty() && !out.contains(&tag) {
+            out.push(tag);
+        }
+    }
+    out
+}
+
+/// Canonical form of a concept for indexing and matching, the same as for
+/// tags
+pub fn normalize_concept(concept: &str) -> String {
+    normalize_tag(concept)
+}
+
+/// How many memories carry a concept
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConceptCount {
+    pub concept: String,
+    pub count: usize,
+}
+
+/// How many memories carry a tag
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+/// What the stored memories are made of (see
+/// [`crate::Storage::breakdown`])
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MemoryBreakdown {
+    /// Memories per type
+    pub by_type: BTreeMap<String, usize>,
+    /// Memories per priority
+    pub by_priority: BTreeMap<String, usize>,
+    /// Most carried tags, most common first
+    pub top_tags: Vec<TagCount>,
+    /// Creation time of the oldest and newest memory
+    pub oldest: Option<DateTime<Utc>>,
+    pub newest: Option<DateTime<Utc>>,
+    /// Memories with an embedding, so found by vector search
+    pub embedded: usize,
+    /// Share of memories with an embedding; 0 without memories
+    pub embedding_coverage: f64,
+}
+
+/// Bytes the store takes on disk, per component
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiskUsage {
+    /// SQLite database with its WAL and shared-memory files
+    pub database_bytes: u64,
+    /// Tantivy (BM25) index directory
+    pub text_index_bytes: u64,
+    /// Saved usearch vector index and its manifest
+    pub vector_index_bytes: u64,
+}
+
+impl DiskUsage {
+    pub fn total_bytes(&self) -> u64 {
+        self.database_bytes + self.text_index_bytes + self.vector_index_bytes
+    }
+}
+
+/// How many memories a namespace holds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NamespaceCount {
+    pub namespace: String,
+    pub count: usize,
+    /// Of which in the cold tier
+    pub cold: usize,
+}
+
+/// Partial metadata change applied by [`crate::Storage::update_metadata`].
+/// Content and embedding are never touched; `None` fields are left as is.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MemoryPatch {
+    pub title: Option<String>,
+    pub memory_type: Option<MemoryType>,
+    pub priority: Option<Priority>,
+    pub source: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub concepts: Option<Vec<String>>,
+    pub files: Option<Vec<String>>,
+}
+
+impl MemoryPatch {
+    /// True when the patch changes nothing
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.memory_type.is_none()
+            && self.priority.is_none()
+            && self.source.is_none()
+            && self.tags.is_none()
+            && self.concepts.is_none()
+            && self.files.is_none()
+    }
+
+    /// Apply the patch to `memory`. Returns whether the title changed, which
+    /// is the only patched field the keyword index depends on.
+    pub fn apply(&self, memory: &mut Memory) -> bool {
+        let meta = &mut memory.metadata;
+        if let Some(memory_type) = self.memory_type {
+            meta.memory_type = memory_type;
+        }
+        if let Some(priority) = self.priority {
+            meta.priority = priority;
+        }
+        if let Some(source) = &self.source {
+            meta.source = Some(source.clone());
+        }
+        if let Some(tags) = &self.tags {
+            meta.tags = normalize_tags(tags);
+        }
+        if let Some(concepts) = &self.concepts {
+            meta.concepts = concepts.clone();
+        }
+        if let Some(files) = &self.files {
+            meta.files = files.clone();
+        }
+        match &self.title {
+            Some(title) if *title != memory.title => {
+                memory.title = title.clone();
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
 /// Type of memory entry
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -101,6 +281,14 @@ pub enum Priority {
 }
 
 impl Priority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        }
+    }
+
     /// Weight factor for search scoring
     pub fn weight(&self) -> f32 {
         match self {
@@ -109,29 +297,610 @@ impl Priority {
             Self::High => 1.0,
         }
     }
+
+    /// The next level up, `None` at the highest
+    pub fn raised(&self) -> Option<Self> {
+        match self {
+            Self::Low => Some(Self::Medium),
+            Self::Medium => Some(Self::High),
+            Self::High => None,
+        }
+    }
+}
+
+/// Priority raised automatically because a memory's topic kept being
+/// stored (see [`crate::Storage::record_repeat`])
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Escalation {
+    /// The memory whose priority was raised
+    pub id: String,
+    pub priority: Priority,
+    /// Stores of the topic within the window that triggered it
+    pub repeats: usize,
+}
+
+/// How often agents marked a memory useful or not after retrieving it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Feedback {
+    pub useful: u32,
+    pub not_useful: u32,
+}
+
+impl Feedback {
+    /// Net usefulness in (-1, 1), damped towards 0 while votes are few
+    pub fn usefulness(&self) -> f32 {
+        let (useful, not_useful) = (self.useful as f32, self.not_useful as f32);
+        (useful - not_useful) / (useful + not_useful + 2.0)
+    }
+}
+
+/// Result of recording retrieval feedback
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeedbackReport {
+    /// Memories whose feedback was recorded
+    pub recorded: usize,
+    /// Given IDs that do not exist
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unknown: Vec<String>,
+}
+
+/// Record of a deleted memory, from the audit log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tombstone {
+    pub id: String,
+    pub deleted_at: DateTime<Utc>,
+    /// Client that deleted it, when it identified itself
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_by: Option<String>,
+}
+
+/// A stored, changed or deleted memory, read from the audit log by
+/// [`crate::Storage::changes_since`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemoryEvent {
+    /// Audit log sequence; increases with every change, across processes
+    pub seq: i64,
+    pub kind: MemoryEventKind,
+    pub memory_id: String,
+    pub at: DateTime<Utc>,
+    /// Client that made the change, when it identified itself
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actor: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryEventKind {
+    Created,
+    /// Metadata or content changed (including priority escalation)
+    Updated,
+    /// Deleted, expired or consolidated away
+    Deleted,
+}
+
+impl MemoryEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Updated => "updated",
+            Self::Deleted => "deleted",
+        }
+    }
+}
+
+/// Kind of a [`MemoryLink`], read from `from` to `to`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Relation {
+    /// `from` replaces `to` (a newer decision or fact)
+    Supersedes,
+    /// `from` is related to `to`
+    RelatesTo,
+    /// `from` was caused by `to` (a fix by the bug, a decision by an
+    /// observation)
+    CausedBy,
+    /// `from` is part of `to` (a sub-page of an imported page)
+    PartOf,
+}
+
+impl Relation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Supersedes => "supersedes",
+            Self::RelatesTo => "relates_to",
+            Self::CausedBy => "caused_by",
+            Self::PartOf => "part_of",
+        }
+    }
+}
+
+/// A directed relation between two memories, e.g. a decision that
+/// `supersedes` an older one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemoryLink {
+    pub from_id: String,
+    pub to_id: String,
+    pub relation: Relation,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A link to create from a memory as it is stored, read as: new memory
+/// `relation` `target_id` (see [`crate::storage::Storage::insert_linked`])
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NewLink {
+    pub target_id: String,
+    pub relation: Relation,
+}
+
+/// A memory reached through links from a retrieved one, in brief: title
+/// and a snippet instead of the full content
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinkedMemory {
+    pub id: String,
+    pub title: String,
+    pub memory_type: MemoryType,
+    pub snippet: String,
+    /// Relation of the link that reached it
+    pub relation: Relation,
+    /// The link points at the memory it was reached from
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub incoming: bool,
+    /// Hops from the retrieved memory (1 = directly linked)
+    pub depth: usize,
+}
+
+/// A memory held back from search and retrieval until approved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantinedMemory {
+    pub memory: Memory,
+    /// Why it was quarantined
+    pub reason: String,
+    pub quarantined_at: DateTime<Utc>,
+}
+
+/// A search query that found nothing, counted across repeats
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MissedQuery {
+    /// The query, trimmed and lowercased
+    pub query: String,
+    /// Namespace the searches were scoped to; `None` for all namespaces
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    pub count: u32,
+    /// Best score among low-confidence results; `None` when every search
+    /// returned nothing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub best_score: Option<f32>,
+    pub first_at: DateTime<Utc>,
+    pub last_at: DateTime<Utc>,
+    /// Task memory created for the gap (`[misses] create_task_after`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<String>,
+}
+
+/// Outcome counters for one variant of an A/B ranking experiment
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VariantMetrics {
+    pub searches: u64,
+    pub results_served: u64,
+    /// Served results later fetched via memory_get
+    pub fetched: u64,
+    /// Sum of the 1-based ranks of fetched results
+    pub fetched_rank_sum: u64,
+}
+
+impl VariantMetrics {
+    /// Fraction of served results that were subsequently fetched
+    pub fn fetch_rate(&self) -> f64 {
+        if self.results_served == 0 {
+            0.0
+        } else {
+            self.fetched as f64 / self.results_served as f64
+        }
+    }
+
+    /// Average rank of fetched results (lower is better); 0 when none fetched
+    pub fn mean_fetched_rank(&self) -> f64 {
+        if self.fetched == 0 {
+            0.0
+        } else {
+            self.fetched_rank_sum as f64 / self.fetched as f64
+        }
+    }
+}
+
+/// A memory replaced by a consolidated one or archived on expiry, kept
+/// for recovery
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedMemory {
+    pub memory: Memory,
+    /// ID of the consolidated memory that replaced it; `None` when it
+    /// expired
+    #[serde(default)]
+    pub consolidated_into: Option<String>,
+    pub archived_at: DateTime<Utc>,
+}
+
+/// What the expiry sweep does with a memory past its `expires_at`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExpiryAction {
+    /// Move it to the archive (see [`ArchivedMemory`])
+    #[default]
+    Archive,
+    /// Delete it outright
+    Delete,
+}
+
+/// What to do with a memory that nearly duplicates an existing one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateAction {
+    /// Refuse to store it
+    Reject,
+    /// Fold its tags and priority into the existing memory instead
+    Merge,
+    /// Store it and report the existing memory it resembles
+    #[default]
+    Flag,
+}
+
+impl DuplicateAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Reject => "reject",
+            Self::Merge => "merge",
+            Self::Flag => "flag",
+        }
+    }
+}
+
+/// An existing memory a new one nearly duplicates, and what was done
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateMatch {
+    pub id: String,
+    /// Cosine similarity of the two embeddings
+    pub similarity: f32,
+    pub action: DuplicateAction,
+}
+
+/// Selects memories for bulk operations; unset fields match everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MemoryFilter {
+    /// Case-insensitive substring of the title or content
+    pub query: Option<String>,
+    pub memory_type: Option<MemoryType>,
+    /// Created at or after this time
+    pub created_after: Option<DateTime<Utc>>,
+    /// Created before this time
+    pub created_before: Option<DateTime<Utc>>,
+    pub namespace: Option<String>,
+}
+
+impl MemoryFilter {
+    /// Parse a filter from JSON, reading `created_after` and
+    /// `created_before` with [`DisplayTimezone::parse_bound`], so
+    /// `2026-01-01` is midnight in `timezone`
+    pub fn from_json(value: &serde_json::Value, timezone: DisplayTimezone) -> Result<Self> {
+        let mut value = value.clone();
+        if let Some(obj) = value.as_object_mut() {
+            for key in ["created_after", "created_before"] {
+                if let Some(bound) = obj.get(key).and_then(|v| v.as_str()) {
+                    let at = timezone.parse_bound(bound)?;
+                    obj.insert(key.to_string(), at.to_rfc3339().into());
+                }
+            }
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// True when the filter matches every memory
+    pub fn is_empty(&self) -> bool {
+        self.query.as_deref().is_none_or(|q| q.trim().is_empty())
+            && self.memory_type.is_none()
+            && self.created_after.is_none()
+            && self.created_before.is_none()
+            && self.namespace.is_none()
+    }
+
+    pub fn matches(&self, memory: &Memory) -> bool {
+        if let Some(query) = self.query.as_deref().map(str::trim)
+            && !query.is_empty()
+        {
+            let query = query.to_lowercase();
+            if !memory.title.to_lowercase().contains(&query)
+                && !memory.content.to_lowercase().contains(&query)
+            {
+                return false;
+            }
+        }
+        self.memory_type
+            .is_none_or(|t| t == memory.metadata.memory_type)
+            && self.created_after.is_none_or(|t| memory.created_at >= t)
+            && self.created_before.is_none_or(|t| memory.created_at < t)
+            && self
+                .namespace
+                .as_deref()
+                .is_none_or(|ns| ns == memory.namespace)
+    }
+}
+
+/// Tags to add to and remove from a set of memories
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TagEdit {
+    pub add: Vec<String>,
+    pub remove: Vec<String>,
+}
+
+impl TagEdit {
+    pub fn is_empty(&self) -> bool {
+        normalize_tags(&self.add).is_empty() && normalize_tags(&self.remove).is_empty()
+    }
+
+    /// `tags` with the removals dropped and the additions appended, all
+    /// normalized. A tag in both lists ends up added.
+    pub fn apply(&self, tags: &[String]) -> Vec<String> {
+        let remove = normalize_tags(&self.remove);
+        let mut result: Vec<String> = normalize_tags(tags)
+            .into_iter()
+            .filter(|tag| !remove.contains(tag))
+            .collect();
+        result.extend(normalize_tags(&self.add));
+        normalize_tags(&result)
+    }
+}
+
+/// Outcome of a bulk tag edit
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetagReport {
+    /// Memories matching the filter
+    pub matched: usize,
+    /// IDs of the memories whose tags changed
+    pub changed: Vec<String>,
+}
+
+/// Length of a content snippet (see [`snippet`])
+pub const SNIPPET_CHARS: usize = 160;
+
+/// First `SNIPPET_CHARS` characters of `content` on one line
+pub fn snippet(content: &str) -> String {
+    let flat = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    if flat.chars().count() <= SNIPPET_CHARS {
+        return flat;
+    }
+    let mut cut: String = flat.chars().take(SNIPPET_CHARS).collect();
+    cut.push('…');
+    cut
+}
+
+/// How much of each memory a search response carries, from the least to
+/// the most tokens
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verbosity {
+    /// IDs and scores only
+    Ids,
+    /// Titles and metadata, no content
+    Index,
+    /// Titles, metadata and a one-line content snippet
+    Snippets,
+    /// Everything, including the full content
+    #[default]
+    Full,
+}
+
+impl Verbosity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ids => "ids",
+            Self::Index => "index",
+            Self::Snippets => "snippets",
+            Self::Full => "full",
+        }
+    }
+
+    /// Whether the search needs to load memory content at all
+    pub fn includes_content(&self) -> bool {
+        *self >= Self::Snippets
+    }
+
+    /// Trim a search result to this level. At `ids` only the memory ID
+    /// and score are left meaningful: title, content and linked memories
+    /// are emptied.
+    pub fn shape(&self, result: &mut SearchResult) {
+        let memory = &mut result.memory;
+        match self {
+            Self::Ids => {
+                memory.title.clear();
+                memory.content.clear();
+                result.linked.clear();
+            }
+            Self::Index => memory.content.clear(),
+            Self::Snippets => memory.content = snippet(&memory.content),
+            Self::Full => {}
+        }
+    }
 }
 
 /// Search query parameters
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchQuery {
     pub query: String,
+    /// Other formulations of `query`, retrieved alongside it and merged by
+    /// rank (see [`SearchQuery::texts`])
+    pub alt_queries: Vec<String>,
     pub limit: usize,
     pub memory_type: Option<MemoryType>,
     pub priority: Option<Priority>,
-    pub tags: Option<Vec<String>>,
+    /// Only memories carrying every one of these tags
+    pub tags_all: Option<Vec<String>>,
+    /// Only memories carrying at least one of these tags
+    pub tags_any: Option<Vec<String>>,
+    /// Only memories carrying none of these tags
+    pub exclude_tags: Vec<String>,
+    /// Only memories of none of these types
+    pub exclude_types: Vec<MemoryType>,
     /// If true, return index only (titles + metadata, minimal tokens)
     pub index_only: bool,
+    /// Scoring profile to rank with (`[search.profiles.<name>]`)
+    pub profile: Option<String>,
+    /// Also return memories linked to each result, up to this many hops
+    /// (0 = off)
+    pub expand_links: usize,
+    /// Also search memories in the cold tier
+    pub include_cold: bool,
+    /// Also search archived memories (see `Storage::set_archived_state`)
+    pub include_archived: bool,
+    /// Only memories whose typed metadata fields pass all of these
+    pub fields: Vec<FieldFilter>,
+    /// Only memories carrying every one of these concepts
+    pub concepts: Vec<String>,
+    /// Only memories in this namespace (all namespaces when unset)
+    pub namespace: Option<String>,
+    /// Keep only the best-scoring memory of each `metadata.source`, such as
+    /// the chunks of one document; the others are counted in
+    /// [`SearchResult::collapsed_count`]. Memories without a source are
+    /// never collapsed.
+    pub collapse_sources: bool,
+    /// Match the keyword query as one phrase (an error message, say)
+    /// instead of loose terms, and return only memories that contain it
+    pub exact: bool,
+    /// Attach a [`ScoreExplanation`] to each result
+    pub explain: bool,
+    /// Only memories this boolean expression accepts, as parsed from the
+    /// query syntax (see `oc_search::query_syntax`). A filter with a blank
+    /// `query` lists every matching memory.
+    pub filter: Option<QueryFilter>,
+}
+
+impl SearchQuery {
+    /// `query` followed by its non-blank alternative formulations
+    pub fn texts(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.query.as_str()).chain(
+            self.alt_queries
+                .iter()
+                .map(String::as_str)
+                .filter(|text| !text.trim().is_empty()),
+        )
+    }
+
+    /// Whether `memory` passes both tag filters, compared in normalized
+    /// form. Unset or empty filters match everything.
+    pub fn matches_tags(&self, memory: &Memory) -> bool {
+        let all = self.tags_all.as_deref().unwrap_or_default();
+        let any = self.tags_any.as_deref().unwrap_or_default();
+        if all.is_empty() && any.is_empty() {
+            return true;
+        }
+        let have = normalize_tags(&memory.metadata.tags);
+        normalize_tags(all).iter().all(|tag| have.contains(tag))
+            && (any.is_empty() || normalize_tags(any).iter().any(|tag| have.contains(tag)))
+    }
+
+    /// Whether `memory` escapes both exclusion filters; tags are compared in
+    /// normalized form
+    pub fn passes_exclusions(&self, memory: &Memory) -> bool {
+        if self.exclude_types.contains(&memory.metadata.memory_type) {
+            return false;
+        }
+        if self.exclude_tags.is_empty() {
+            return true;
+        }
+        let have = normalize_tags(&memory.metadata.tags);
+        !normalize_tags(&self.exclude_tags)
+            .iter()
+            .any(|tag| have.contains(tag))
+    }
+
+    /// Whether `memory` is in the namespace filter, if any
+    pub fn matches_namespace(&self, memory: &Memory) -> bool {
+        self.namespace
+            .as_deref()
+            .is_none_or(|ns| ns == memory.namespace)
+    }
+
+    /// Whether `memory` passes the boolean filter, if any
+    pub fn matches_filter(&self, memory: &Memory) -> bool {
+        self.filter
+            .as_ref()
+            .is_none_or(|filter| filter.matches(memory))
+    }
+
+    /// Whether `memory` carries every concept filter, case-insensitively
+    pub fn matches_concepts(&self, memory: &Memory) -> bool {
+        let have: Vec<String> = memory
+            .metadata
+            .concepts
+            .iter()
+            .map(|c| normalize_concept(c))
+            .collect();
+        self.concepts
+            .iter()
+            .all(|c| have.contains(&normalize_concept(c)))
+    }
 }
 
 impl Default for SearchQuery {
     fn default() -> Self {
         Self {
             query: String::new(),
+            alt_queries: Vec::new(),
             limit: 10,
             memory_type: None,
             priority: None,
-            tags: None,
+            tags_all: None,
+            tags_any: None,
+            exclude_tags: Vec::new(),
+            exclude_types: Vec::new(),
             index_only: false,
+            profile: None,
+            expand_links: 0,
+            include_cold: false,
+            include_archived: false,
+            fields: Vec::new(),
+            concepts: Vec::new(),
+            namespace: None,
+            collapse_sources: true,
+            exact: false,
+            explain: false,
+            filter: None,
+        }
+    }
+}
+
+/// Boolean expression over memories, from the field-scoped query syntax
+/// (`type:decision tag:rust NOT deprecated`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryFilter {
+    /// Title or content contains the text, case-insensitively
+    Text(String),
+    /// Title contains the text, case-insensitively
+    Title(String),
+    /// Carries the tag, compared in normalized form
+    Tag(String),
+    Type(MemoryType),
+    Not(Box<QueryFilter>),
+    And(Vec<QueryFilter>),
+    Or(Vec<QueryFilter>),
+}
+
+impl QueryFilter {
+    pub fn matches(&self, memory: &Memory) -> bool {
+        match self {
+            Self::Text(text) => {
+                let text = text.to_lowercase();
+                memory.title.to_lowercase().contains(&text)
+                    || memory.content.to_lowercase().contains(&text)
+            }
+            Self::Title(text) => memory.title.to_lowercase().contains(&text.to_lowercase()),
+            Self::Tag(tag) => normalize_tags(&memory.metadata.tags).contains(&normalize_tag(tag)),
+            Self::Type(memory_type) => memory.metadata.memory_type == *memory_type,
+            Self::Not(filter) => !filter.matches(memory),
+            Self::And(filters) => filters.iter().all(|f| f.matches(memory)),
+            Self::Or(filters) => filters.iter().any(|f| f.matches(memory)),
         }
     }
 }
@@ -143,6 +912,29 @@ pub struct SearchResult {
     pub score: f32,
     /// Breakdown: vector similarity, BM25 score, recency, importance
     pub score_breakdown: ScoreBreakdown,
+    /// Ranking variant ("A"/"B") when an A/B experiment is active
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant: Option<String>,
+    /// Scoring profile the result was ranked with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Ranked keyword-only because the query had no usable embedding
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub degraded: bool,
+    /// Memories linked to this one, with `expand_links`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub linked: Vec<LinkedMemory>,
+    /// Lower-scoring memories from the same source folded into this one
+    /// (see [`SearchQuery::collapse_sources`])
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub collapsed_count: usize,
+    /// Raw inputs of the score, with [`SearchQuery::explain`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<ScoreExplanation>,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -151,4 +943,43 @@ pub struct ScoreBreakdown {
     pub keyword: f32,
     pub recency: f32,
     pub importance: f32,
+    /// Boost from how often the memory was accessed (see
+    /// `Scorer::access_score`)
+    #[serde(default)]
+    pub access: f32,
+}
+
+/// What went into a result's score, for tuning the `[search]` weights
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreExplanation {
+    /// Cosine similarity of the best memory or chunk vector, before
+    /// weighting; absent without a vector hit
+    pub cosine: Option<f32>,
+    /// BM25 score, best over the query's formulations, before
+    /// normalization; absent without a keyword hit
+    pub bm25: Option<f32>,
+    /// 1-based rank in the (fused) vector list
+    pub vector_rank: Option<usize>,
+    /// 1-based rank in the (fused) keyword list
+    pub keyword_rank: Option<usize>,
+    /// Days since the memory was last accessed, the input of recency
+    pub days_since_access: f32,
+    pub access_count: u32,
+    /// Weights the score was computed with: the configured ones, or those
+    /// of the scoring profile or experiment variant, shifted to the other
+    /// components when the search ran keyword-only
+    pub weights: ScoreWeights,
+    pub half_life_days: f32,
+    pub fusion: FusionStrategy,
+    pub rrf_k: f32,
+}
+
+/// Component weights of a scorer
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScoreWeights {
+    pub semantic: f32,
+    pub keyword: f32,
+    pub recency: f32,
+    pub importance: f32,
+    pub access: f32,
 }
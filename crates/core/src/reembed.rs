@@ -0,0 +1,219 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::storage::Storage;
+
+/// Memories embedded per call to the embedding function
+pub const DEFAULT_REEMBED_BATCH_SIZE: usize = 32;
+
+/// Count of stored embeddings produced by one model at one dimension
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmbeddingVersion {
+    /// `None` for embeddings stored before versioning
+    pub model: Option<String>,
+    pub dimensions: Option<usize>,
+    pub count: usize,
+}
+
+/// Target model and batching for [`reembed`]
+#[derive(Debug, Clone)]
+pub struct ReembedOptions {
+    pub model: String,
+    pub dimensions: usize,
+    /// Re-embed every memory, not only stale ones
+    pub all: bool,
+    pub batch_size: usize,
+}
+
+/// Result of [`reembed`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReembedReport {
+    pub model: String,
+    pub dimensions: usize,
+    /// Memories whose embedding was regenerated
+    pub reembedded: usize,
+    /// Memories whose embedding could not be generated; they keep their old one
+    pub failed: usize,
+    /// Embeddings still not produced by the target model afterwards
+    pub remaining_stale: usize,
+}
+
+/// Regenerate embeddings with `embed` for every memory whose embedding is
+/// missing or came from a different model or dimension (or for all memories
+/// with `options.all`), walking the store in batches.
+///
+/// A failing batch is counted and skipped so one bad entry does not stop the
+/// migration. The vector index must be rebuilt afterwards.
+pub fn reembed<F>(
+    storage: &Storage,
+    options: &ReembedOptions,
+    mut embed: F,
+) -> Result<ReembedReport>
+where
+    F: FnMut(&[&str]) -> Result<Vec<Vec<f32>>>,
+{
+    let mut report = ReembedReport {
+        model: options.model.clone(),
+        dimensions: options.dimensions,
+        ..Default::default()
+    };
+    let batch_size = options.batch_size.max(1);
+    let mut after_id = String::new();
+
+    loop {
+        let batch = storage.reembed_candidates(
+            &options.model,
+            options.dimensions,
+            options.all,
+            &after_id,
+            batch_size,
+        )?;
+        let Some((last_id, _)) = batch.last() else {
+            break;
+        };
+        after_id = last_id.clone();
+
+        let texts: Vec<&str> = batch.iter().map(|(_, content)| content.as_str()).collect();
+        let embeddings = match embed(&texts) {
+            Ok(embeddings) if embeddings.len() == batch.len() => embeddings,
+            Ok(embeddings) => {
+                tracing::warn!(
+                    expected = batch.len(),
+                    got = embeddings.len(),
+                    "Embedding batch size mismatch, skipping batch"
+                );
+                report.failed += batch.len();
+                continue;
+            }
+            Err(e) => {
+                tracing::warn!("Embedding batch failed, skipping: {e}");
+                report.failed += batch.len();
+                continue;
+            }
+        };
+
+        for ((id, _), embedding) in batch.iter().zip(embeddings) {
+            if embedding.len() != options.dimensions {
+                report.failed += 1;
+                continue;
+            }
+            if storage.update_embedding(id, &embedding, &options.model)? {
+                report.reembedded += 1;
+            }
+        }
+        tracing::info!(done = report.reembedded, "Re-embedding in progress");
+    }
+
+    report.remaining_stale = storage.count_stale_embeddings(&options.model, options.dimensions)?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::models::{Memory, MemoryMetadata};
+
+    fn options(model: &str, dimensions: usize) -> ReembedOptions {
+        ReembedOptions {
+            model: model.to_string(),
+            dimensions,
+            all: false,
+            batch_size: 2,
+        }
+    }
+
+    fn store(storage: &Storage, content: &str, embedding: Option<(&str, Vec<f32>)>) -> String {
+        let mut m = Memory::new(
+            content.to_string(),
+            content.to_string(),
+            MemoryMetadata::default(),
+        );
+        if let Some((model, emb)) = embedding {
+            m.embedding = Some(emb);
+            m.embedding_model = Some(model.to_string());
+        }
+        storage.insert(&m).unwrap();
+        m.id
+    }
+
+    fn fake_embed(dimensions: usize) -> impl FnMut(&[&str]) -> Result<Vec<Vec<f32>>> {
+        move |texts| {
+            Ok(texts
+                .iter()
+                .map(|t| vec![t.chars().count() as f32; dimensions])
+                .collect())
+        }
+    }
+
+    #[test]
+    fn test_reembeds_only_stale() {
+        let storage = Storage::in_memory().unwrap();
+        let current = store(&storage, "현재", Some(("new.onnx", vec![1.0; 3])));
+        let old_model = store(&storage, "옛 모델", Some(("old.onnx", vec![1.0; 3])));
+        let old_dim = store(&storage, "옛 차원", Some(("new.onnx", vec![1.0; 2])));
+        let missing = store(&storage, "없음", None);
+        assert_eq!(storage.count_stale_embeddings("new.onnx", 3).unwrap(), 3);
+
+        let mut calls = 0;
+        let mut embed = fake_embed(3);
+        let report = reembed(&storage, &options("new.onnx", 3), |texts| {
+            calls += 1;
+            embed(texts)
+        })
+        .unwrap();
+
+        assert_eq!(report.reembedded, 3);
+        assert_eq!(report.failed, 0);
+        assert_eq!(report.remaining_stale, 0);
+        assert_eq!(calls, 2, "3 stale memories in batches of 2");
+
+        for id in [&old_model, &old_dim, &missing] {
+            let m = storage.get(id).unwrap().unwrap();
+            assert_eq!(m.embedding_model.as_deref(), Some("new.onnx"));
+            assert_eq!(m.embedding.unwrap().len(), 3);
+        }
+        let untouched = storage.get(&current).unwrap().unwrap();
+        assert_eq!(untouched.embedding.unwrap(), vec![1.0; 3]);
+        assert!(storage.verify_integrity().unwrap().ok);
+    }
+
+    #[test]
+    fn test_failed_batches_are_skipped() {
+        let storage = Storage::in_memory().unwrap();
+        for i in 0..3 {
+            store(&storage, &format!("메모 {i}"), None);
+        }
+
+        let mut first = true;
+        let mut embed = fake_embed(4);
+        let report = reembed(&storage, &options("m.onnx", 4), |texts| {
+            if std::mem::take(&mut first) {
+                return Err(Error::Other("model crashed".into()));
+            }
+            embed(texts)
+        })
+        .unwrap();
+
+        assert_eq!(report.failed, 2);
+        assert_eq!(report.reembedded, 1);
+        assert_eq!(report.remaining_stale, 2);
+    }
+
+    #[test]
+    fn test_all_reembeds_current_too() {
+        let storage = Storage::in_memory().unwrap();
+        store(&storage, "현재", Some(("m.onnx", vec![0.0; 2])));
+
+        let mut opts = options("m.onnx", 2);
+        assert_eq!(
+            reembed(&storage, &opts, fake_embed(2)).unwrap().reembedded,
+            0
+        );
+        opts.all = true;
+        assert_eq!(
+            reembed(&storage, &opts, fake_embed(2)).unwrap().reembedded,
+            1
+        );
+    }
+}
@@ -0,0 +1,653 @@
+use chrono::{DateTime, Utc};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::config::{
+    Config, DB_FILE_NAME, TANTIVY_DIR_NAME, VECTOR_INDEX_FILE_NAME, VECTOR_SHARDS_DIR_NAME,
+    vector_manifest_path,
+};
+use crate::error::{Error, Result};
+use crate::integrity;
+use crate::storage::Storage;
+
+/// Archive layout version. Bump when the set or layout of packaged files changes.
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const CONFIG_ENTRY: &str = "config.toml";
+
+/// Describes a snapshot archive; always stored as its first entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub format_version: u32,
+    /// oc-memory version that wrote the archive
+    pub engine_version: String,
+    pub created_at: DateTime<Utc>,
+    /// Embedding dimensions of the stored vectors
+    pub dimensions: usize,
+    pub memory_count: usize,
+    /// Whether a persisted HNSW index file is included
+    pub has_vector_index: bool,
+}
+
+/// Package the SQLite DB, tantivy index, HNSW index file and config into a
+/// single gzipped tar archive at `dest`. The packaged config leaves out
+/// inline API and sync keys (see [`Config::redacted`]).
+///
+/// The database is copied with `VACUUM INTO`, so this is safe to run against
+/// a live data directory.
+pub fn create_snapshot(
+    storage: &Storage,
+    config: &Config,
+    dest: impl AsRef<Path>,
+) -> Result<SnapshotManifest> {
+    let dest = dest.as_ref();
+    let manifest = SnapshotManifest {
+        format_version: SNAPSHOT_FORMAT_VERSION,
+        engine_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: Utc::now(),
+        dimensions: config.embedding.dimensions,
+        memory_count: storage.count()?,
+        has_vector_index: config.vector_index_path().is_file(),
+    };
+
+    // VACUUM INTO refuses to overwrite, so stage the copy under a fresh name
+    let db_copy = staging_path(dest);
+    let _ = std::fs::remove_file(&db_copy);
+    storage.vacuum_into(&db_copy)?;
+
+    let result = write_archive(dest, &manifest, config, &db_copy);
+    let _ = std::fs::remove_file(&db_copy);
+    result?;
+
+    tracing::info!(
+        path = %dest.display(),
+        memories = manifest.memory_count,
+        "Snapshot created"
+    );
+    Ok(manifest)
+}
+
+/// Read the manifest of a snapshot archive without extracting it
+pub fn read_manifest(archive: impl AsRef<Path>) -> Result<SnapshotManifest> {
+    let data = read_entry(archive.as_ref(), MANIFEST_ENTRY)?;
+    Ok(serde_json::from_slice(&data)?)
+}
+
+/// Read the configuration packaged in a snapshot archive
+pub fn read_config(archive: impl AsRef<Path>) -> Result<Config> {
+    let data = read_entry(archive.as_ref(), CONFIG_ENTRY)?;
+    let text = String::from_utf8(data).map_err(|e| Error::Snapshot(e.to_string()))?;
+    toml::from_str(&text).map_err(|e| Error::Config(e.to_string()))
+}
+
+/// Restore a snapshot archive into the data directory of `config`.
+///
+/// Validates the archive format version and embedding dimensions, then
+/// extracts the archive into a staging directory inside the data directory
+/// and checks the staged database before touching any existing file. The
+/// database and indices are then swapped in by renames; the files they
+/// replace are kept until every rename succeeded and put back otherwise.
+/// Vector shards are not packaged, so existing ones are removed and
+/// rebuilt on demand. The server must not be running against the target
+/// data directory.
+pub fn restore_snapshot(archive: impl AsRef<Path>, config: &Config) -> Result<SnapshotManifest> {
+    let archive = archive.as_ref();
+    let manifest = read_manifest(archive)?;
+    validate_manifest(&manifest, config)?;
+
+    let data_dir = config.data_dir();
+    std::fs::create_dir_all(&data_dir)?;
+
+    // Staged on the data directory's filesystem, so the swap is renames only
+    let run = uuid::Uuid::new_v4();
+    let staging = data_dir.join(format!(".restore-{run}"));
+    let result = extract_into(archive, &staging)
+        .and_then(|()| check_staged(&staging, &manifest))
+        .and_then(|()| {
+            swap_in(
+                &data_dir,
+                &staging,
+                &data_dir.join(format!(".replaced-{run}")),
+            )
+        });
+    let _ = std::fs::remove_dir_all(&staging);
+    result?;
+
+    tracing::info!(
+        path = %archive.display(),
+        memories = manifest.memory_count,
+        "Snapshot restored"
+    );
+    Ok(manifest)
+}
+
+/// A memory that differs between two snapshots. Hashes are
+/// [`integrity::content_hash`]es; `old_hash` is `None` for added memories
+/// and `new_hash` for removed ones.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiffEntry {
+    pub id: String,
+    /// Title in the newer snapshot, or the older one for removed memories
+    pub title: String,
+    pub old_hash: Option<String>,
+    pub new_hash: Option<String>,
+}
+
+/// Memories added, removed and modified from one snapshot to another,
+/// each sorted by id
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SnapshotDiff {
+    pub added: Vec<DiffEntry>,
+    pub removed: Vec<DiffEntry>,
+    pub modified: Vec<DiffEntry>,
+    pub unchanged: usize,
+}
+
+/// Compare the memories of two snapshot archives by id and content hash.
+/// A memory counts as modified when its content, title or metadata changed.
+pub fn diff_snapshots(old: impl AsRef<Path>, new: impl AsRef<Path>) -> Result<SnapshotDiff> {
+    let before = fingerprints(old.as_ref())?;
+    let mut after = fingerprints(new.as_ref())?;
+
+    let mut diff = SnapshotDiff::default();
+    for (id, (title, old_hash)) in before {
+        match after.remove(&id) {
+            Some((_, new_hash)) if new_hash == old_hash => diff.unchanged += 1,
+            Some((title, new_hash)) => diff.modified.push(DiffEntry {
+                id,
+                title,
+                old_hash: Some(old_hash),
+                new_hash: Some(new_hash),
+            }),
+            None => diff.removed.push(DiffEntry {
+                id,
+                title,
+                old_hash: Some(old_hash),
+                new_hash: None,
+            }),
+        }
+    }
+    diff.added = after
+        .into_iter()
+        .map(|(id, (title, new_hash))| DiffEntry {
+            id,
+            title,
+            old_hash: None,
+            new_hash: Some(new_hash),
+        })
+        .collect();
+    Ok(diff)
+}
+
+/// Title and content hash of every memory in an archive, by id
+fn fingerprints(archive: &Path) -> Result<BTreeMap<String, (String, String)>> {
+    read_manifest(archive)?;
+    // SQLite needs a file, so the database is staged next to the system temp
+    let db_copy = std::env::temp_dir().join(format!("oc-memory-diff-{}.db", uuid::Uuid::new_v4()));
+    std::fs::write(&db_copy, read_entry(archive, DB_FILE_NAME)?)?;
+
+    let result = (|| {
+        let storage = Storage::open(&db_copy)?;
+        let snapshot = storage.read_snapshot()?;
+        let mut memories = BTreeMap::new();
+        snapshot.for_each_memory(|memory| {
+            let hash = integrity::content_hash(&memory);
+            memories.insert(memory.id, (memory.title, hash));
+            Ok(())
+        })?;
+        Ok(memories)
+    })();
+    for path in [
+        db_copy.clone(),
+        with_suffix(&db_copy, "-wal"),
+        with_suffix(&db_copy, "-shm"),
+    ] {
+        let _ = std::fs::remove_file(path);
+    }
+    result
+}
+
+fn write_archive(
+    dest: &Path,
+    manifest: &SnapshotManifest,
+    config: &Config,
+    db_copy: &Path,
+) -> Result<()> {
+    let file = File::create(dest)?;
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+
+    append_bytes(
+        &mut builder,
+        MANIFEST_ENTRY,
+        &serde_json::to_vec_pretty(manifest)?,
+    )?;
+    let config_toml =
+        toml::to_string_pretty(&config.redacted()).map_err(|e| Error::Config(e.to_string()))?;
+    append_bytes(&mut builder, CONFIG_ENTRY, config_toml.as_bytes())?;
+
+    builder.append_path_with_name(db_copy, DB_FILE_NAME)?;
+    let tantivy_dir = config.tantivy_dir();
+    if tantivy_dir.is_dir() {
+        builder.append_dir_all(TANTIVY_DIR_NAME, &tantivy_dir)?;
+    }
+    if manifest.has_vector_index {
+        let index_path = config.vector_index_path();
+        builder.append_path_with_name(&index_path, VECTOR_INDEX_FILE_NAME)?;
+        let meta_path = vector_manifest_path(&index_path);
+        if meta_path.is_file() {
+            let meta_name = vector_manifest_path(Path::new(VECTOR_INDEX_FILE_NAME));
+            builder.append_path_with_name(&meta_path, meta_name)?;
+        }
+    }
+
+    builder.into_inner()?.finish()?.flush()?;
+    Ok(())
+}
+
+/// Entries of the data directory a restore replaces, relative to it. Ones
+/// the archive lacks are removed, so no index outlives the database it was
+/// built from.
+fn restored_entries() -> Vec<PathBuf> {
+    let db = Path::new(DB_FILE_NAME);
+    vec![
+        db.to_path_buf(),
+        with_suffix(db, "-wal"),
+        with_suffix(db, "-shm"),
+        PathBuf::from(VECTOR_INDEX_FILE_NAME),
+        vector_manifest_path(Path::new(VECTOR_INDEX_FILE_NAME)),
+        PathBuf::from(TANTIVY_DIR_NAME),
+        PathBuf::from(VECTOR_SHARDS_DIR_NAME),
+    ]
+}
+
+/// Extract every packaged data file of `archive` into `dir`
+fn extract_into(archive: &Path, dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let mut tar = open_archive(archive)?;
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        if path == Path::new(MANIFEST_ENTRY) || path == Path::new(CONFIG_ENTRY) {
+            continue;
+        }
+        // unpack_in rejects entries that would escape the staging directory
+        entry.unpack_in(dir)?;
+    }
+    Ok(())
+}
+
+/// Check that an extracted archive holds what its manifest promises: a
+/// database that opens with the recorded number of memories, and the
+/// vector index when one was packaged
+fn check_staged(dir: &Path, manifest: &SnapshotManifest) -> Result<()> {
+    let db_path = dir.join(DB_FILE_NAME);
+    if !db_path.is_file() {
+        return Err(Error::Snapshot(format!(
+            "archive is missing {DB_FILE_NAME}"
+        )));
+    }
+    if manifest.has_vector_index && !dir.join(VECTOR_INDEX_FILE_NAME).is_file() {
+        return Err(Error::Snapshot(format!(
+            "archive is missing {VECTOR_INDEX_FILE_NAME}"
+        )));
+    }
+    let count = Storage::open(&db_path)?.count()?;
+    if count != manifest.memory_count {
+        return Err(Error::Snapshot(format!(
+            "archive database holds {count} memories, manifest records {}",
+            manifest.memory_count
+        )));
+    }
+    Ok(())
+}
+
+/// Move the staged entries into `data_dir`. Each entry they replace is
+/// first moved into `aside`; when a rename fails the moves are undone, so
+/// the data directory ends up with either the old files or the restored
+/// ones.
+fn swap_in(data_dir: &Path, staging: &Path, aside: &Path) -> Result<()> {
+    std::fs::create_dir_all(aside)?;
+    let mut moved_aside = Vec::new();
+    let mut moved_in = Vec::new();
+    let result = (|| -> Result<()> {
+        for name in restored_entries() {
+            let target = data_dir.join(&name);
+            if target.symlink_metadata().is_ok() {
+                std::fs::rename(&target, aside.join(&name))?;
+                moved_aside.push(name);
+            }
+        }
+        for name in restored_entries() {
+            let staged = staging.join(&name);
+            if staged.exists() {
+                std::fs::rename(&staged, data_dir.join(&name))?;
+                moved_in.push(name);
+            }
+        }
+        Ok(())
+    })();
+
+    if result.is_err() {
+        for name in &moved_in {
+            let _ = remove_path(&data_dir.join(name));
+        }
+        for name in &moved_aside {
+            if let Err(e) = std::fs::rename(aside.join(name), data_dir.join(name)) {
+                tracing::error!(
+                    "Could not put back {} (kept in {}): {e}",
+                    name.display(),
+                    aside.display()
+                );
+            }
+        }
+        // Only removed when empty, so nothing that failed to move back is lost
+        let _ = std::fs::remove_dir(aside);
+        return result;
+    }
+    for name in &moved_aside {
+        let _ = remove_path(&aside.join(name));
+    }
+    let _ = std::fs::remove_dir(aside);
+    Ok(())
+}
+
+fn remove_path(path: &Path) -> std::io::Result<()> {
+    if path.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+}
+
+fn validate_manifest(manifest: &SnapshotManifest, config: &Config) -> Result<()> {
+    if manifest.format_version != SNAPSHOT_FORMAT_VERSION {
+        return Err(Error::Snapshot(format!(
+            "unsupported snapshot format version {} (expected {})",
+            manifest.format_version, SNAPSHOT_FORMAT_VERSION
+        )));
+    }
+    if manifest.dimensions != config.embedding.dimensions {
+        return Err(Error::Snapshot(format!(
+            "embedding dimension mismatch: snapshot has {}, config expects {}",
+            manifest.dimensions, config.embedding.dimensions
+        )));
+    }
+    Ok(())
+}
+
+fn open_archive(path: &Path) -> Result<tar::Archive<GzDecoder<File>>> {
+    Ok(tar::Archive::new(GzDecoder::new(File::open(path)?)))
+}
+
+fn read_entry(archive: &Path, name: &str) -> Result<Vec<u8>> {
+    let mut tar = open_archive(archive)?;
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        if entry.path()?.as_ref() == Path::new(name) {
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data)?;
+            return Ok(data);
+        }
+    }
+    Err(Error::Snapshot(format!("archive is missing {name}")))
+}
+
+fn append_bytes<W: Write>(builder: &mut tar::Builder<W>, name: &str, data: &[u8]) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(Utc::now().timestamp() as u64);
+    header.set_cksum();
+    builder.append_data(&mut header, name, data)?;
+    Ok(())
+}
+
+fn staging_path(dest: &Path) -> PathBuf {
+    with_suffix(dest, ".db.partial")
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Memory, MemoryMetadata};
+
+    fn config_at(dir: &Path) -> Config {
+        let mut config = Config::default();
+        config.storage.data_dir = dir.to_string_lossy().to_string();
+        config
+    }
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let tmp = tempfile::tempdir().unwrap();
+        let src = config_at(&tmp.path().join("src"));
+        std::fs::create_dir_all(src.tantivy_dir()).unwrap();
+        std::fs::write(src.tantivy_dir().join("meta.json"), b"{}").unwrap();
+
+        let storage = Storage::open(src.db_path()).unwrap();
+        let memory = Memory::new(
+            "스냅샷 내용".to_string(),
+            "스냅샷".to_string(),
+            MemoryMetadata::default(),
+        );
+        storage.insert(&memory).unwrap();
+
+        let archive = tmp.path().join("brain.tar.gz");
+        let manifest = create_snapshot(&storage, &src, &archive).unwrap();
+        assert_eq!(manifest.memory_count, 1);
+        assert!(!manifest.has_vector_index);
+
+        let dst = config_at(&tmp.path().join("dst"));
+        let restored = restore_snapshot(&archive, &dst).unwrap();
+        assert_eq!(restored.format_version, SNAPSHOT_FORMAT_VERSION);
+
+        let restored_storage = Storage::open(dst.db_path()).unwrap();
+        let m = restored_storage.get(&memory.id).unwrap().unwrap();
+        assert_eq!(m.content, "스냅샷 내용");
+        assert!(dst.tantivy_dir().join("meta.json").exists());
+        assert_eq!(read_config(&archive).unwrap().embedding.dimensions, 1024);
+    }
+
+    #[test]
+    fn test_snapshot_config_has_no_inline_keys() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut config = config_at(&tmp.path().join("data"));
+        std::fs::create_dir_all(config.data_dir()).unwrap();
+        config.server.api_keys.push(crate::config::ApiKeyConfig {
+            name: "laptop".to_string(),
+            key: Some("sk-inline-api-secret".to_string()),
+            key_env: None,
+            scope: Default::default(),
+        });
+        config.sync.peers.push(crate::config::SyncPeerConfig {
+            name: "desktop".to_string(),
+            url: "http://desktop.local:8080".to_string(),
+            key: Some("sk-inline-peer-secret".to_string()),
+            key_env: Some("OC_MEMORY_DESKTOP_KEY".to_string()),
+        });
+        let storage = Storage::open(config.db_path()).unwrap();
+        let archive = tmp.path().join("brain.tar.gz");
+        create_snapshot(&storage, &config, &archive).unwrap();
+
+        let text = String::from_utf8(read_entry(&archive, CONFIG_ENTRY).unwrap()).unwrap();
+        assert!(!text.contains("sk-inline"));
+        let packaged = read_config(&archive).unwrap();
+        assert_eq!(packaged.server.api_keys[0].name, "laptop");
+        assert_eq!(packaged.server.api_keys[0].key, None);
+        assert_eq!(packaged.sync.peers[0].key, None);
+        assert_eq!(
+            packaged.sync.peers[0].key_env.as_deref(),
+            Some("OC_MEMORY_DESKTOP_KEY")
+        );
+        // The running configuration keeps its keys
+        assert!(config.server.api_keys[0].key.is_some());
+    }
+
+    #[test]
+    fn test_restore_replaces_data_and_drops_stale_shards() {
+        let tmp = tempfile::tempdir().unwrap();
+        let src = config_at(&tmp.path().join("src"));
+        std::fs::create_dir_all(src.data_dir()).unwrap();
+        let storage = Storage::open(src.db_path()).unwrap();
+        let kept = Memory::new(
+            "복원될 내용".to_string(),
+            "복원".to_string(),
+            MemoryMetadata::default(),
+        );
+        storage.insert(&kept).unwrap();
+        let archive = tmp.path().join("brain.tar.gz");
+        create_snapshot(&storage, &src, &archive).unwrap();
+
+        let dst = config_at(&tmp.path().join("dst"));
+        std::fs::create_dir_all(dst.vector_shards_dir()).unwrap();
+        std::fs::write(dst.vector_shards_dir().join("work.usearch"), b"stale").unwrap();
+        let replaced = Memory::new(
+            "덮어쓸 내용".to_string(),
+            "기존".to_string(),
+            MemoryMetadata::default(),
+        );
+        Storage::open(dst.db_path())
+            .unwrap()
+            .insert(&replaced)
+            .unwrap();
+
+        restore_snapshot(&archive, &dst).unwrap();
+        let restored = Storage::open(dst.db_path()).unwrap();
+        assert!(restored.get(&kept.id).unwrap().is_some());
+        assert!(restored.get(&replaced.id).unwrap().is_none());
+        assert!(!dst.vector_shards_dir().exists());
+        // No staging or set-aside directories are left behind
+        let leftovers: Vec<_> = std::fs::read_dir(dst.data_dir())
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().starts_with('.'))
+            .collect();
+        assert!(leftovers.is_empty());
+    }
+
+    #[test]
+    fn test_restore_of_incomplete_archive_keeps_existing_data() {
+        let tmp = tempfile::tempdir().unwrap();
+        let config = config_at(&tmp.path().join("data"));
+        std::fs::create_dir_all(config.data_dir()).unwrap();
+        let existing = Memory::new(
+            "남아야 할 내용".to_string(),
+            "기존".to_string(),
+            MemoryMetadata::default(),
+        );
+        Storage::open(config.db_path())
+            .unwrap()
+            .insert(&existing)
+            .unwrap();
+
+        // A manifest without the database it describes
+        let manifest = SnapshotManifest {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            engine_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: Utc::now(),
+            dimensions: config.embedding.dimensions,
+            memory_count: 1,
+            has_vector_index: false,
+        };
+        let archive = tmp.path().join("broken.tar.gz");
+        let mut builder = tar::Builder::new(GzEncoder::new(
+            File::create(&archive).unwrap(),
+            Compression::default(),
+        ));
+        append_bytes(
+            &mut builder,
+            MANIFEST_ENTRY,
+            &serde_json::to_vec(&manifest).unwrap(),
+        )
+        .unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        assert!(restore_snapshot(&archive, &config).is_err());
+        let storage = Storage::open(config.db_path()).unwrap();
+        assert!(storage.get(&existing.id).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_diff_snapshots() {
+        let tmp = tempfile::tempdir().unwrap();
+        let config = config_at(&tmp.path().join("data"));
+        std::fs::create_dir_all(config.data_dir()).unwrap();
+        let storage = Storage::open(config.db_path()).unwrap();
+        let memories: Vec<Memory> = ["유지", "수정", "삭제"]
+            .into_iter()
+            .map(|title| {
+                Memory::new(
+                    format!("{title} 내용"),
+                    title.to_string(),
+                    MemoryMetadata::default(),
+                )
+            })
+            .collect();
+        for m in &memories {
+            storage.insert(m).unwrap();
+        }
+        let before = tmp.path().join("before.tar.gz");
+        create_snapshot(&storage, &config, &before).unwrap();
+
+        let patch = crate::models::MemoryPatch {
+            tags: Some(vec!["changed".to_string()]),
+            ..Default::default()
+        };
+        storage.update_metadata(&memories[1].id, &patch).unwrap();
+        storage.delete(&memories[2].id).unwrap();
+        let added = Memory::new(
+            "새 내용".to_string(),
+            "추가".to_string(),
+            MemoryMetadata::default(),
+        );
+        storage.insert(&added).unwrap();
+        let after = tmp.path().join("after.tar.gz");
+        create_snapshot(&storage, &config, &after).unwrap();
+
+        let diff = diff_snapshots(&before, &after).unwrap();
+        assert_eq!(diff.unchanged, 1);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].id, added.id);
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].title, "삭제");
+        assert!(diff.removed[0].new_hash.is_none());
+        assert_eq!(diff.modified.len(), 1);
+        assert_eq!(diff.modified[0].id, memories[1].id);
+        assert_ne!(diff.modified[0].old_hash, diff.modified[0].new_hash);
+
+        assert_eq!(diff_snapshots(&after, &after).unwrap().unchanged, 3);
+    }
+
+    #[test]
+    fn test_restore_rejects_dimension_mismatch() {
+        let tmp = tempfile::tempdir().unwrap();
+        let src = config_at(&tmp.path().join("src"));
+        std::fs::create_dir_all(src.data_dir()).unwrap();
+        let storage = Storage::open(src.db_path()).unwrap();
+
+        let archive = tmp.path().join("brain.tar.gz");
+        create_snapshot(&storage, &src, &archive).unwrap();
+
+        let mut dst = config_at(&tmp.path().join("dst"));
+        dst.embedding.dimensions = 384;
+        let err = restore_snapshot(&archive, &dst).unwrap_err();
+        assert!(err.to_string().contains("dimension mismatch"));
+        assert!(
+            !dst.db_path().exists(),
+            "Nothing should be written on failure"
+        );
+    }
+}
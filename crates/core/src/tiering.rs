@@ -0,0 +1,174 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::StorageConfig;
+use crate::error::Result;
+use crate::storage::Storage;
+
+/// Access history of a hot memory, used to pick what to cool
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessStats {
+    pub id: String,
+    pub accessed_at: DateTime<Utc>,
+    pub access_count: u32,
+    /// Accesses decayed to `accessed_at` (see [`decayed_accesses`])
+    pub access_score: f64,
+}
+
+/// Result of [`enforce_tiers`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TieringReport {
+    /// Cooled because they were not accessed within `hot_ttl_days`
+    pub expired: usize,
+    /// Cooled to bring the hot set down to `max_hot_memories`
+    pub evicted: usize,
+    /// Hot memories afterwards
+    pub hot: usize,
+}
+
+/// Decay an access score recorded at `since` to `now`: each access counts
+/// half as much every `half_life_days`, so a memory that was popular long
+/// ago does not outrank one in use now. Zero keeps the score as is.
+pub fn decayed_accesses(
+    score: f64,
+    since: DateTime<Utc>,
+    now: DateTime<Utc>,
+    half_life_days: u32,
+) -> f64 {
+    if half_life_days == 0 {
+        return score;
+    }
+    let days = ((now - since).num_seconds().max(0) as f64) / 86_400.0;
+    score * 0.5f64.powf(days / half_life_days as f64)
+}
+
+/// Split hot memories into those to cool for being stale (not accessed for
+/// `ttl_days`) and those to cool to keep at most `max_hot` hot, least
+/// accessed (by access score decayed with `half_life_days`) and then least
+/// recently accessed first. Zero disables a limit.
+pub fn select_cold(
+    hot: &[AccessStats],
+    max_hot: usize,
+    ttl_days: u32,
+    half_life_days: u32,
+    now: DateTime<Utc>,
+) -> (Vec<String>, Vec<String>) {
+    let (expired, mut kept): (Vec<&AccessStats>, Vec<&AccessStats>) = if ttl_days == 0 {
+        (Vec::new(), hot.iter().collect())
+    } else {
+        let cutoff = now - Duration::days(ttl_days as i64);
+        hot.iter().partition(|m| m.accessed_at < cutoff)
+    };
+
+    let mut evicted = Vec::new();
+    if max_hot > 0 && kept.len() > max_hot {
+        let popularity =
+            |m: &AccessStats| decayed_accesses(m.access_score, m.accessed_at, now, half_life_days);
+        kept.sort_by(|a, b| {
+            popularity(a)
+                .total_cmp(&popularity(b))
+                .then(a.accessed_at.cmp(&b.accessed_at))
+        });
+        let excess = kept.len() - max_hot;
+        evicted = kept[..excess].iter().map(|m| m.id.clone()).collect();
+    }
+    (expired.into_iter().map(|m| m.id.clone()).collect(), evicted)
+}
+
+/// Apply `hot_ttl_days` and `max_hot_memories`: move stale and surplus
+/// low-access memories to the cold tier. Cold memories are left out of
+/// searches unless asked for and turn hot again when accessed.
+pub fn enforce_tiers(storage: &Storage, config: &StorageConfig) -> Result<TieringReport> {
+    let hot = storage.hot_access_stats()?;
+    let (expired, evicted) = select_cold(
+        &hot,
+        config.max_hot_memories,
+        config.hot_ttl_days,
+        config.access_half_life_days,
+        Utc::now(),
+    );
+    let expired = storage.set_cold(&expired)?;
+    let evicted = storage.set_cold(&evicted)?;
+    Ok(TieringReport {
+        expired,
+        evicted,
+        hot: hot.len() - expired - evicted,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(id: &str, days_ago: i64, access_count: u32) -> AccessStats {
+        AccessStats {
+            id: id.to_string(),
+            accessed_at: Utc::now() - Duration::days(days_ago),
+            access_count,
+            access_score: access_count as f64,
+        }
+    }
+
+    #[test]
+    fn test_select_cold_by_ttl_then_capacity() {
+        let hot = vec![
+            stats("stale", 100, 50),
+            stats("busy", 10, 9),
+            stats("quiet-old", 20, 1),
+            stats("quiet-new", 5, 1),
+        ];
+        let (expired, evicted) = select_cold(&hot, 2, 90, 0, Utc::now());
+        assert_eq!(expired, vec!["stale"]);
+        assert_eq!(evicted, vec!["quiet-old"]);
+
+        let (expired, evicted) = select_cold(&hot, 0, 0, 0, Utc::now());
+        assert!(expired.is_empty() && evicted.is_empty());
+    }
+
+    #[test]
+    fn test_old_popularity_decays() {
+        let now = Utc::now();
+        assert_eq!(
+            decayed_accesses(8.0, now - Duration::days(60), now, 30),
+            2.0
+        );
+        assert_eq!(decayed_accesses(8.0, now - Duration::days(60), now, 0), 8.0);
+
+        // 40 accesses 200 days ago are worth less than 3 this week
+        let hot = vec![stats("once-famous", 200, 40), stats("in-use", 2, 3)];
+        let (_, evicted) = select_cold(&hot, 1, 0, 30, now);
+        assert_eq!(evicted, vec!["once-famous"]);
+        let (_, evicted) = select_cold(&hot, 1, 0, 0, now);
+        assert_eq!(evicted, vec!["in-use"]);
+    }
+
+    #[test]
+    fn test_cold_memories_warm_up_on_access() {
+        let storage = Storage::in_memory().unwrap();
+        let mut ids = Vec::new();
+        for title in ["하나", "둘", "셋"] {
+            let memory = crate::models::Memory::new(
+                "내용".to_string(),
+                title.to_string(),
+                Default::default(),
+            );
+            storage.insert(&memory).unwrap();
+            ids.push(memory.id);
+        }
+        storage.touch(&ids[2]).unwrap();
+
+        let config = StorageConfig {
+            max_hot_memories: 1,
+            ..Default::default()
+        };
+        let report = enforce_tiers(&storage, &config).unwrap();
+        assert_eq!(report.evicted, 2);
+        assert_eq!(report.hot, 1);
+        assert_eq!(storage.count_cold().unwrap(), 2);
+        assert_eq!(storage.cold_among(&ids).unwrap().len(), 2);
+        assert!(!storage.cold_among(&ids).unwrap().contains(&ids[2]));
+
+        storage.touch(&ids[0]).unwrap();
+        assert_eq!(storage.count_cold().unwrap(), 1);
+    }
+}
@@ -0,0 +1,236 @@
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{Error, Result};
+
+/// Time zone timestamps are shown in and zone-less dates are read in
+/// (`[display] timezone`). Storage is always UTC.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum DisplayTimezone {
+    #[default]
+    Utc,
+    /// The system time zone of the running process
+    Local,
+    /// A fixed offset from UTC, e.g. `+09:00` for KST
+    Fixed(FixedOffset),
+}
+
+impl DisplayTimezone {
+    /// `at` formatted with a chrono format string in this zone
+    pub fn format(&self, at: DateTime<Utc>, fmt: &str) -> String {
+        match self {
+            Self::Utc => at.format(fmt).to_string(),
+            Self::Local => at.with_timezone(&Local).format(fmt).to_string(),
+            Self::Fixed(offset) => at.with_timezone(offset).format(fmt).to_string(),
+        }
+    }
+
+    /// `YYYY-MM-DD HH:MM` followed by the zone: `UTC` or the offset
+    pub fn format_minutes(&self, at: DateTime<Utc>) -> String {
+        match self {
+            Self::Utc => self.format(at, "%Y-%m-%d %H:%M UTC"),
+            _ => self.format(at, "%Y-%m-%d %H:%M %:z"),
+        }
+    }
+
+    /// Calendar date of `at` in this zone
+    pub fn format_date(&self, at: DateTime<Utc>) -> String {
+        self.format(at, "%Y-%m-%d")
+    }
+
+    /// RFC 3339 form of `at` with this zone's offset
+    pub fn to_rfc3339(self, at: DateTime<Utc>) -> String {
+        match self {
+            Self::Utc => at.to_rfc3339(),
+            Self::Local => at.with_timezone(&Local).to_rfc3339(),
+            Self::Fixed(offset) => at.with_timezone(&offset).to_rfc3339(),
+        }
+    }
+
+    /// Parse a date bound: an RFC 3339 timestamp (its own offset wins), or
+    /// `YYYY-MM-DD`, `YYYY-MM-DD HH:MM[:SS]` or `YYYY-MM-DDTHH:MM[:SS]`
+    /// read as wall-clock time in this zone (a bare date is its midnight)
+    pub fn parse_bound(&self, s: &str) -> Result<DateTime<Utc>> {
+        let s = s.trim();
+        if let Ok(at) = DateTime::parse_from_rfc3339(s) {
+            return Ok(at.with_timezone(&Utc));
+        }
+        let naive = [
+            "%Y-%m-%dT%H:%M:%S",
+            "%Y-%m-%d %H:%M:%S",
+            "%Y-%m-%dT%H:%M",
+            "%Y-%m-%d %H:%M",
+        ]
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(s, fmt).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })
+        .ok_or_else(|| {
+            Error::Other(format!(
+                "Invalid date {s:?}: expected YYYY-MM-DD, YYYY-MM-DD HH:MM or RFC 3339"
+            ))
+        })?;
+        self.to_utc(naive)
+    }
+
+    /// UTC instant of wall-clock time `naive` in this zone. In a daylight
+    /// saving overlap the earlier instant wins.
+    fn to_utc(self, naive: NaiveDateTime) -> Result<DateTime<Utc>> {
+        let at = match self {
+            Self::Utc => Some(naive.and_utc()),
+            Self::Local => Local
+                .from_local_datetime(&naive)
+                .earliest()
+                .map(|at| at.with_timezone(&Utc)),
+            Self::Fixed(offset) => offset
+                .from_local_datetime(&naive)
+                .earliest()
+                .map(|at| at.with_timezone(&Utc)),
+        };
+        at.ok_or_else(|| Error::Other(format!("{naive} does not exist in time zone {self}")))
+    }
+}
+
+impl fmt::Display for DisplayTimezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Utc => f.write_str("utc"),
+            Self::Local => f.write_str("local"),
+            Self::Fixed(offset) => write!(f, "{offset}"),
+        }
+    }
+}
+
+impl FromStr for DisplayTimezone {
+    type Err = Error;
+
+    /// `utc`, `local`, or an offset: `+09:00`, `+0900`, `+09`, `-05:30`
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        match s.to_lowercase().as_str() {
+            "utc" | "z" | "" => return Ok(Self::Utc),
+            "local" => return Ok(Self::Local),
+            _ => {}
+        }
+        let invalid = || {
+            Error::Config(format!(
+                "Invalid time zone {s:?}: expected utc, local or an offset like +09:00"
+            ))
+        };
+        let (sign, rest) = match s.as_bytes()[0] {
+            b'+' => (1, &s[1..]),
+            b'-' => (-1, &s[1..]),
+            _ => return Err(invalid()),
+        };
+        let digits = rest.replace(':', "");
+        if !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let (hours, minutes) = match digits.len() {
+            1 | 2 => (digits.as_str(), "0"),
+            4 => digits.split_at(2),
+            _ => return Err(invalid()),
+        };
+        let hours: i32 = hours.parse().map_err(|_| invalid())?;
+        let minutes: i32 = minutes.parse().map_err(|_| invalid())?;
+        if minutes >= 60 {
+            return Err(invalid());
+        }
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+            .map(Self::Fixed)
+            .ok_or_else(invalid)
+    }
+}
+
+impl TryFrom<String> for DisplayTimezone {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<DisplayTimezone> for String {
+    fn from(timezone: DisplayTimezone) -> Self {
+        timezone.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kst() -> DisplayTimezone {
+        "+09:00".parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_timezone() {
+        assert_eq!(
+            "UTC".parse::<DisplayTimezone>().unwrap(),
+            DisplayTimezone::Utc
+        );
+        assert_eq!(
+            "local".parse::<DisplayTimezone>().unwrap(),
+            DisplayTimezone::Local
+        );
+        assert_eq!("+0900".parse::<DisplayTimezone>().unwrap(), kst());
+        assert_eq!("+9".parse::<DisplayTimezone>().unwrap(), kst());
+        assert_eq!(kst().to_string(), "+09:00");
+        assert_eq!(
+            "-05:30".parse::<DisplayTimezone>().unwrap().to_string(),
+            "-05:30"
+        );
+        assert!("Asia/Seoul".parse::<DisplayTimezone>().is_err());
+        assert!("+09:75".parse::<DisplayTimezone>().is_err());
+        assert!("+25:00".parse::<DisplayTimezone>().is_err());
+    }
+
+    #[test]
+    fn test_local_dates_are_read_in_zone() {
+        let kst = kst();
+        assert_eq!(
+            kst.parse_bound("2026-01-01").unwrap().to_rfc3339(),
+            "2025-12-31T15:00:00+00:00"
+        );
+        assert_eq!(
+            kst.parse_bound("2026-01-01 09:30").unwrap().to_rfc3339(),
+            "2026-01-01T00:30:00+00:00"
+        );
+        // An explicit offset is kept
+        assert_eq!(
+            kst.parse_bound("2026-01-01T00:00:00Z")
+                .unwrap()
+                .to_rfc3339(),
+            "2026-01-01T00:00:00+00:00"
+        );
+        assert_eq!(
+            DisplayTimezone::Utc
+                .parse_bound("2026-01-01")
+                .unwrap()
+                .to_rfc3339(),
+            "2026-01-01T00:00:00+00:00"
+        );
+        assert!(kst.parse_bound("yesterday").is_err());
+    }
+
+    #[test]
+    fn test_format_in_zone() {
+        let at = DisplayTimezone::Utc
+            .parse_bound("2026-01-01T18:00:00Z")
+            .unwrap();
+        assert_eq!(
+            DisplayTimezone::Utc.format_minutes(at),
+            "2026-01-01 18:00 UTC"
+        );
+        assert_eq!(kst().format_minutes(at), "2026-01-02 03:00 +09:00");
+        assert_eq!(kst().format_date(at), "2026-01-02");
+        assert_eq!(kst().to_rfc3339(at), "2026-01-02T03:00:00+09:00");
+    }
+}
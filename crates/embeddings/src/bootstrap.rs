@@ -0,0 +1,285 @@
+use oc_core::config::EmbeddingConfig;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::error::{EmbeddingError, Result};
+
+/// Idle time after which a stalled download is abandoned
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A file fetched by [`ensure_model_files`]
+#[derive(Debug, Clone)]
+pub struct Downloaded {
+    pub path: PathBuf,
+    pub bytes: u64,
+    pub sha256: String,
+}
+
+/// Download the model and tokenizer into their configured paths when they
+/// are missing and downloads are enabled. Existing files are left alone.
+///
+/// Each missing file needs a pinned URL and a SHA-256 (see
+/// [`require_pinned`]). Files are written to a `.partial` sibling and only
+/// moved into place once the checksum matches.
+pub fn ensure_model_files(config: &EmbeddingConfig) -> Result<Vec<Downloaded>> {
+    let download = &config.download;
+    let files = [
+        (
+            config.model_file(),
+            &download.model_url,
+            &download.model_sha256,
+        ),
+        (
+            config.tokenizer_file(),
+            &download.tokenizer_url,
+            &download.tokenizer_sha256,
+        ),
+    ];
+
+    let mut fetched = Vec::new();
+    for (path, url, sha256) in files {
+        if path.exists() || !download.enabled {
+            continue;
+        }
+        let sha256 = require_pinned(url, sha256.as_deref())?;
+        tracing::info!(url = %url, dest = %path.display(), "Downloading missing model file");
+        fetched.push(download_file(url, &path, Some(sha256))?);
+    }
+    Ok(fetched)
+}
+
+/// The checksum `url` must be verified against. Refuses a download without
+/// one, and a Hugging Face URL that resolves a branch (such as
+/// `resolve/main`) rather than a 40-hex commit, whose content can change
+/// under the same checksum-less name.
+pub fn require_pinned<'a>(url: &str, sha256: Option<&'a str>) -> Result<&'a str> {
+    if url.trim().is_empty() {
+        return Err(EmbeddingError::Download(
+            "no download URL set in [embedding.download]; run scripts/download_model.py instead"
+                .to_string(),
+        ));
+    }
+    if let Some((_, rest)) = url.split_once("/resolve/") {
+        let revision = rest.split('/').next().unwrap_or_default();
+        if revision.len() != 40 || !revision.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(EmbeddingError::Download(format!(
+                "{url} is not pinned to a commit; use resolve/<commit hash>"
+            )));
+        }
+    }
+    sha256
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| {
+            EmbeddingError::Download(format!(
+                "no sha256 pinned for {url} in [embedding.download]"
+            ))
+        })
+}
+
+/// Configured model files that do not exist yet
+pub fn missing_files(config: &EmbeddingConfig) -> Vec<PathBuf> {
+    [config.model_file(), config.tokenizer_file()]
+        .into_iter()
+        .filter(|p| !p.exists())
+        .collect()
+}
+
+/// Fetch `url` (`http(s)://` or `file://`) into `dest`, verifying `sha256`
+/// when given
+pub fn download_file(url: &str, dest: &Path, sha256: Option<&str>) -> Result<Downloaded> {
+    let reader = open_url(url)?;
+    let downloaded = save_verified(reader, dest, sha256)?;
+    if sha256.is_none() {
+        tracing::warn!(
+            path = %dest.display(),
+            sha256 = %downloaded.sha256,
+            "Downloaded without checksum verification; pin this sha256 in [embedding.download]"
+        );
+    }
+    tracing::info!(
+        path = %dest.display(),
+        bytes = downloaded.bytes,
+        "Model file downloaded"
+    );
+    Ok(downloaded)
+}
+
+fn open_url(url: &str) -> Result<Box<dyn Read + Send>> {
+    if let Some(path) = url.strip_prefix("file://") {
+        return Ok(Box::new(File::open(path)?));
+    }
+    let response = ureq::AgentBuilder::new()
+        .timeout_read(READ_TIMEOUT)
+        .build()
+        .get(url)
+        .call()
+        .map_err(|e| EmbeddingError::Download(format!("{url}: {e}")))?;
+    Ok(Box::new(response.into_reader()))
+}
+
+/// Stream `reader` into `dest` while hashing it. On a checksum mismatch the
+/// partial file is removed and `dest` is never created.
+fn save_verified(mut reader: impl Read, dest: &Path, sha256: Option<&str>) -> Result<Downloaded> {
+    if let Some(dir) = dest.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let partial = partial_path(dest);
+    let result = (|| {
+        let mut writer = HashingWriter {
+            inner: BufWriter::new(File::create(&partial)?),
+            hasher: Sha256::new(),
+        };
+        let bytes = io::copy(&mut reader, &mut writer)?;
+        writer
+            .inner
+            .into_inner()
+            .map_err(|e| e.into_error())?
+            .sync_all()?;
+        let actual = format!("{:x}", writer.hasher.finalize());
+
+        if let Some(expected) = sha256
+            && !expected.trim().eq_ignore_ascii_case(&actual)
+        {
+            return Err(EmbeddingError::Download(format!(
+                "checksum mismatch for {}: expected {expected}, got {actual}",
+                dest.display()
+            )));
+        }
+        std::fs::rename(&partial, dest)?;
+        Ok(Downloaded {
+            path: dest.to_path_buf(),
+            bytes,
+            sha256: actual,
+        })
+    })();
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&partial);
+    }
+    result
+}
+
+fn partial_path(dest: &Path) -> PathBuf {
+    let mut name = dest.as_os_str().to_owned();
+    name.push(".partial");
+    PathBuf::from(name)
+}
+
+struct HashingWriter<W> {
+    inner: W,
+    hasher: Sha256,
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sha256_hex(data: &[u8]) -> String {
+        format!("{:x}", Sha256::digest(data))
+    }
+
+    fn config_in(dir: &Path, source: &Path) -> EmbeddingConfig {
+        let mut config = EmbeddingConfig {
+            model_path: dir
+                .join("models")
+                .join("model.onnx")
+                .to_string_lossy()
+                .to_string(),
+            tokenizer_path: dir
+                .join("models")
+                .join("tokenizer.json")
+                .to_string_lossy()
+                .to_string(),
+            ..Default::default()
+        };
+        config.download.enabled = true;
+        config.download.model_url = format!("file://{}", source.join("model.onnx").display());
+        config.download.tokenizer_url =
+            format!("file://{}", source.join("tokenizer.json").display());
+        config
+    }
+
+    #[test]
+    fn test_downloads_missing_files_and_verifies() {
+        let tmp = tempfile::tempdir().unwrap();
+        let source = tmp.path().join("mirror");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::write(source.join("model.onnx"), b"onnx bytes").unwrap();
+        std::fs::write(source.join("tokenizer.json"), b"{}").unwrap();
+
+        let mut config = config_in(tmp.path(), &source);
+        config.download.model_sha256 = Some(sha256_hex(b"onnx bytes").to_uppercase());
+        config.download.tokenizer_sha256 = Some(sha256_hex(b"{}"));
+        assert_eq!(missing_files(&config).len(), 2);
+
+        let fetched = ensure_model_files(&config).unwrap();
+        assert_eq!(fetched.len(), 2);
+        assert_eq!(fetched[0].bytes, 10);
+        assert_eq!(fetched[1].sha256, sha256_hex(b"{}"));
+        assert!(missing_files(&config).is_empty());
+
+        // Present files are never re-downloaded
+        assert!(ensure_model_files(&config).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_checksum_mismatch_leaves_nothing_behind() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dest = tmp.path().join("tokenizer.json");
+        let expected = sha256_hex(b"original");
+
+        let err = save_verified(&b"tampered"[..], &dest, Some(&expected)).unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"));
+        assert!(!dest.exists());
+        assert!(!partial_path(&dest).exists());
+    }
+
+    #[test]
+    fn test_unpinned_downloads_are_refused() {
+        let tmp = tempfile::tempdir().unwrap();
+        let source = tmp.path().join("mirror");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::write(source.join("model.onnx"), b"onnx bytes").unwrap();
+        let config = config_in(tmp.path(), &source);
+        let err = ensure_model_files(&config).unwrap_err();
+        assert!(err.to_string().contains("no sha256 pinned"));
+        assert_eq!(missing_files(&config).len(), 2);
+
+        let hash = sha256_hex(b"x");
+        let branch = "https://huggingface.co/org/model/resolve/main/model.onnx";
+        assert!(require_pinned(branch, Some(&hash)).is_err());
+        let commit = format!(
+            "https://huggingface.co/org/model/resolve/{}/model.onnx",
+            "a".repeat(40)
+        );
+        assert_eq!(require_pinned(&commit, Some(&hash)).unwrap(), hash);
+        assert!(require_pinned("", Some(&hash)).is_err());
+        // Off by default: nothing is fetched from an unknown source
+        assert!(!EmbeddingConfig::default().download.enabled);
+    }
+
+    #[test]
+    fn test_disabled_download_is_noop() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut config = config_in(tmp.path(), &tmp.path().join("nowhere"));
+        config.download.enabled = false;
+        assert!(ensure_model_files(&config).unwrap().is_empty());
+        assert_eq!(missing_files(&config).len(), 2);
+    }
+}
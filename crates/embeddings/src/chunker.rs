@@ -0,0 +1,134 @@
+use oc_core::config::ChunkingConfig;
+
+use crate::engine::Embedder;
+use crate::error::Result;
+
+/// Split `text` into chunks of at most `max_chars` characters, breaking on
+/// sentence boundaries (`.`, `!`, `?`, `。` or a newline) where possible.
+/// Sentences longer than `max_chars` are cut at the limit. Text that fits
+/// in one chunk is returned whole.
+pub fn chunk_text(text: &str, max_chars: usize) -> Vec<String> {
+    let max_chars = max_chars.max(1);
+    let text = text.trim();
+    if text.chars().count() <= max_chars {
+        return if text.is_empty() {
+            Vec::new()
+        } else {
+            vec![text.to_string()]
+        };
+    }
+
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_len = 0;
+    for sentence in sentences(text) {
+        let sentence_len = sentence.chars().count();
+        if current_len + sentence_len > max_chars && current_len > 0 {
+            push_chunk(&mut chunks, &current);
+            current.clear();
+            current_len = 0;
+        }
+        if sentence_len > max_chars {
+            let chars: Vec<char> = sentence.chars().collect();
+            for piece in chars.chunks(max_chars) {
+                push_chunk(&mut chunks, &piece.iter().collect::<String>());
+            }
+            continue;
+        }
+        current.push_str(sentence);
+        current_len += sentence_len;
+    }
+    push_chunk(&mut chunks, &current);
+    chunks
+}
+
+/// Embed the chunks of `text` when chunking is enabled and the text spans
+/// more than one chunk; otherwise nothing (the memory embedding suffices)
+pub fn embed_chunks(
+    embedder: &dyn Embedder,
+    text: &str,
+    config: &ChunkingConfig,
+) -> Result<Vec<Vec<f32>>> {
+    if !config.enabled {
+        return Ok(Vec::new());
+    }
+    let chunks = chunk_text(text, config.max_chars);
+    if chunks.len() < 2 {
+        return Ok(Vec::new());
+    }
+    let refs: Vec<&str> = chunks.iter().map(String::as_str).collect();
+    embedder.embed_batch(&refs)
+}
+
+/// Sentences of `text`, each keeping its terminator and trailing whitespace
+fn sentences(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let mut end = rest.len();
+        let mut chars = rest.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            let boundary = matches!(c, '.' | '!' | '?' | '。' | '\n')
+                && chars.peek().is_none_or(|(_, next)| next.is_whitespace());
+            if boundary {
+                end = i + c.len_utf8();
+                while let Some((j, next)) = chars.peek() {
+                    if !next.is_whitespace() {
+                        break;
+                    }
+                    end = j + next.len_utf8();
+                    chars.next();
+                }
+                break;
+            }
+        }
+        let (sentence, tail) = rest.split_at(end);
+        rest = tail;
+        Some(sentence)
+    })
+}
+
+fn push_chunk(chunks: &mut Vec<String>, chunk: &str) {
+    let chunk = chunk.trim();
+    if !chunk.is_empty() {
+        chunks.push(chunk.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_text_is_one_chunk() {
+        assert_eq!(chunk_text("  짧은 메모. ", 100), vec!["짧은 메모."]);
+        assert!(chunk_text("   ", 100).is_empty());
+    }
+
+    #[test]
+    fn test_splits_on_sentence_boundaries() {
+        let text = "첫 문장입니다. 두 번째 문장! 세 번째 문장?\n버전 1.2는 그대로";
+        let chunks = chunk_text(text, 18);
+        assert_eq!(
+            chunks,
+            vec![
+                "첫 문장입니다. 두 번째 문장!",
+                "세 번째 문장?",
+                "버전 1.2는 그대로"
+            ]
+        );
+        assert!(chunks.iter().all(|c| c.chars().count() <= 18));
+    }
+
+    #[test]
+    fn test_long_sentence_is_cut_at_limit() {
+        let text = "가".repeat(25);
+        let chunks = chunk_text(&text, 10);
+        assert_eq!(
+            chunks.iter().map(|c| c.chars().count()).collect::<Vec<_>>(),
+            vec![10, 10, 5]
+        );
+    }
+}
@@ -11,6 +11,29 @@ pub enum EmbeddingError {
 
     #[error("Invalid input: {0}")]
     InvalidInput(String),
+
+    #[error("Embedding API error: {0}")]
+    Api(String),
+
+    #[error("Model download failed: {0}")]
+    Download(String),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Embedder busy, retry in {retry_after_ms} ms")]
+    Busy { retry_after_ms: u64 },
+}
+
+impl EmbeddingError {
+    /// How long to wait before retrying, when the embedder (or the remote
+    /// API) was too busy to take the call
+    pub fn retry_after_ms(&self) -> Option<u64> {
+        match self {
+            Self::Busy { retry_after_ms } => Some(*retry_after_ms),
+            _ => None,
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, EmbeddingError>;
@@ -0,0 +1,286 @@
+use oc_core::config::HttpEmbeddingConfig;
+use serde::Deserialize;
+use std::time::Duration;
+
+use crate::engine::Embedder;
+use crate::error::{EmbeddingError, Result};
+
+/// Backoff when the API answers 429/503 without a `Retry-After` in seconds
+const DEFAULT_RETRY_AFTER_MS: u64 = 1000;
+
+/// Remote embedding backend for OpenAI-compatible `/embeddings` APIs
+///
+/// Sends `{"model", "input": [...]}` and reads `data[].embedding`, so it
+/// works with OpenAI as well as local servers (Ollama, vLLM, LM Studio)
+/// exposing the same endpoint.
+pub struct HttpEmbedder {
+    agent: ureq::Agent,
+    url: String,
+    model: String,
+    api_key: Option<String>,
+    dimensions: usize,
+    batch_size: usize,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingData {
+    embedding: Vec<f32>,
+    #[serde(default)]
+    index: Option<usize>,
+}
+
+impl HttpEmbedder {
+    /// Create the backend, reading the API key from `config.api_key_env`
+    pub fn new(config: &HttpEmbeddingConfig, dimensions: usize) -> Result<Self> {
+        let api_key = Some(config.api_key_env.as_str())
+            .filter(|name| !name.is_empty())
+            .and_then(|name| std::env::var(name).ok())
+            .filter(|key| !key.is_empty());
+        if api_key.is_none() {
+            tracing::warn!(
+                env = %config.api_key_env,
+                "No API key for the embeddings API; sending unauthenticated requests"
+            );
+        }
+        Self::with_api_key(config, dimensions, api_key)
+    }
+
+    fn with_api_key(
+        config: &HttpEmbeddingConfig,
+        dimensions: usize,
+        api_key: Option<String>,
+    ) -> Result<Self> {
+        if config.url.is_empty() || config.model.is_empty() {
+            return Err(EmbeddingError::InvalidInput(
+                "embedding.http needs both url and model".to_string(),
+            ));
+        }
+        tracing::info!(url = %config.url, model = %config.model, "Using remote embeddings API");
+
+        Ok(Self {
+            agent: ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs(config.timeout_secs.max(1)))
+                .build(),
+            url: config.url.clone(),
+            model: config.model.clone(),
+            api_key,
+            dimensions,
+            batch_size: config.batch_size.max(1),
+        })
+    }
+
+    fn request(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let body = serde_json::json!({ "model": self.model, "input": texts });
+        let mut request = self
+            .agent
+            .post(&self.url)
+            .set("Content-Type", "application/json");
+        if let Some(key) = &self.api_key {
+            request = request.set("Authorization", &format!("Bearer {key}"));
+        }
+
+        let response = match request.send_string(&body.to_string()) {
+            Ok(response) => response,
+            // Rate limited or overloaded: pass the API's backoff hint on
+            Err(ureq::Error::Status(code @ (429 | 503), response)) => {
+                let retry_after_ms = response
+                    .header("Retry-After")
+                    .and_then(|secs| secs.trim().parse::<u64>().ok())
+                    .map_or(DEFAULT_RETRY_AFTER_MS, |secs| secs * 1000);
+                tracing::warn!(code, retry_after_ms, "Embeddings API is busy");
+                return Err(EmbeddingError::Busy { retry_after_ms });
+            }
+            Err(ureq::Error::Status(code, response)) => {
+                let detail = response.into_string().unwrap_or_default();
+                return Err(EmbeddingError::Api(format!("HTTP {code}: {detail}")));
+            }
+            Err(e) => return Err(EmbeddingError::Api(e.to_string())),
+        };
+        let text = response.into_string()?;
+        let parsed: EmbeddingResponse = serde_json::from_str(&text)
+            .map_err(|e| EmbeddingError::Api(format!("Unexpected response: {e}")))?;
+
+        if parsed.data.len() != texts.len() {
+            return Err(EmbeddingError::Api(format!(
+                "Expected {} embeddings, got {}",
+                texts.len(),
+                parsed.data.len()
+            )));
+        }
+        let mut data = parsed.data;
+        if data.iter().all(|d| d.index.is_some()) {
+            data.sort_by_key(|d| d.index);
+        }
+
+        data.into_iter()
+            .map(|d| {
+                if d.embedding.len() != self.dimensions {
+                    return Err(EmbeddingError::Api(format!(
+                        "Model {} returned {} dimensions, embedding.dimensions is {}",
+                        self.model,
+                        d.embedding.len(),
+                        self.dimensions
+                    )));
+                }
+                Ok(l2_normalize(d.embedding))
+            })
+            .collect()
+    }
+}
+
+impl Embedder for HttpEmbedder {
+    fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let mut results = Vec::with_capacity(texts.len());
+        for chunk in texts.chunks(self.batch_size) {
+            results.extend(self.request(chunk)?);
+        }
+        Ok(results)
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    fn model_id(&self) -> &str {
+        &self.model
+    }
+}
+
+fn l2_normalize(mut embedding: Vec<f32>) -> Vec<f32> {
+    let norm: f32 = embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for val in &mut embedding {
+            *val /= norm;
+        }
+    }
+    embedding
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread::JoinHandle;
+
+    /// Answer one request per canned `(status, body)`, returning the raw
+    /// requests received. 429 answers carry `Retry-After: 2`.
+    fn serve(responses: Vec<(u16, String)>) -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/v1/embeddings", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut head = String::new();
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if let Some(len) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                        content_length = len.trim().parse().unwrap();
+                    }
+                    head.push_str(&line);
+                    if line == "\r\n" {
+                        break;
+                    }
+                }
+                let mut request_body = vec![0; content_length];
+                reader.read_exact(&mut request_body).unwrap();
+                requests.push(head + &String::from_utf8(request_body).unwrap());
+
+                let retry_after = if status == 429 {
+                    "Retry-After: 2\r\n"
+                } else {
+                    ""
+                };
+                write!(
+                    stream,
+                    "HTTP/1.1 {status} X\r\nContent-Type: application/json\r\n{retry_after}Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+            }
+            requests
+        });
+        (url, handle)
+    }
+
+    fn embedder(url: String, batch_size: usize, api_key: Option<&str>) -> HttpEmbedder {
+        let config = HttpEmbeddingConfig {
+            url,
+            model: "test-embed".to_string(),
+            batch_size,
+            ..Default::default()
+        };
+        HttpEmbedder::with_api_key(&config, 2, api_key.map(str::to_string)).unwrap()
+    }
+
+    #[test]
+    fn test_embeds_in_input_order_across_batches() {
+        let (url, server) = serve(vec![
+            (
+                200,
+                r#"{"data":[{"index":1,"embedding":[0.0,2.0]},{"index":0,"embedding":[3.0,4.0]}]}"#
+                    .to_string(),
+            ),
+            (200, r#"{"data":[{"embedding":[1.0,0.0]}]}"#.to_string()),
+        ]);
+        let embedder = embedder(url, 2, Some("sk-test"));
+
+        let vectors = embedder.embed_batch(&["첫째", "둘째", "셋째"]).unwrap();
+        assert_eq!(
+            vectors,
+            vec![vec![0.6, 0.8], vec![0.0, 1.0], vec![1.0, 0.0]]
+        );
+        assert_eq!(embedder.model_id(), "test-embed");
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].contains("Bearer sk-test"));
+        assert!(requests[0].contains(r#""input":["첫째","둘째"]"#));
+        assert!(requests[0].contains(r#""model":"test-embed""#));
+    }
+
+    #[test]
+    fn test_api_errors_and_dimension_mismatch() {
+        let (url, server) = serve(vec![
+            (401, r#"{"error":"invalid key"}"#.to_string()),
+            (200, r#"{"data":[{"embedding":[1.0,0.0,0.0]}]}"#.to_string()),
+        ]);
+        let embedder = embedder(url, 8, None);
+
+        let err = embedder.embed("a").unwrap_err().to_string();
+        assert!(
+            err.contains("HTTP 401") && err.contains("invalid key"),
+            "{err}"
+        );
+
+        let err = embedder.embed("b").unwrap_err().to_string();
+        assert!(err.contains("3 dimensions"), "{err}");
+
+        let requests = server.join().unwrap();
+        assert!(!requests[0].to_ascii_lowercase().contains("authorization"));
+    }
+
+    #[test]
+    fn test_busy_api_passes_retry_after_on() {
+        let (url, server) = serve(vec![
+            (429, r#"{"error":"rate limited"}"#.to_string()),
+            (503, r#"{"error":"overloaded"}"#.to_string()),
+        ]);
+        let embedder = embedder(url, 8, None);
+
+        let err = embedder.embed("a").unwrap_err();
+        assert_eq!(err.retry_after_ms(), Some(2000));
+        let err = embedder.embed("b").unwrap_err();
+        assert_eq!(err.retry_after_ms(), Some(DEFAULT_RETRY_AFTER_MS));
+        server.join().unwrap();
+    }
+}
@@ -1,5 +1,13 @@
+pub mod bootstrap;
+pub mod chunker;
 pub mod engine;
 pub mod error;
+pub mod http;
+pub mod limit;
+pub mod onnx;
 
-pub use engine::EmbeddingEngine;
+pub use engine::{Embedder, create_engine};
 pub use error::{EmbeddingError, Result};
+pub use http::HttpEmbedder;
+pub use limit::LimitedEmbedder;
+pub use onnx::OnnxEmbedder;
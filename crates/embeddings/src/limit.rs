@@ -0,0 +1,205 @@
+use oc_core::Storage;
+use oc_core::config::EmbeddingLimitsConfig;
+use oc_core::reembed::ReembedReport;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::engine::Embedder;
+use crate::error::{EmbeddingError, Result};
+
+/// Weight of the latest call in the moving average of call durations
+const LATENCY_SMOOTHING: f64 = 0.2;
+
+/// An [`Embedder`] that runs at most `max_concurrent` calls at once and
+/// lets at most `max_queued` more wait (`[embedding.limits]`). A call that
+/// finds the queue full fails at once with [`EmbeddingError::Busy`], so
+/// callers can tell their clients to back off instead of piling up.
+pub struct LimitedEmbedder {
+    inner: Arc<dyn Embedder>,
+    limits: EmbeddingLimitsConfig,
+    slots: Mutex<Slots>,
+    freed: Condvar,
+}
+
+#[derive(Default)]
+struct Slots {
+    running: usize,
+    queued: usize,
+    /// Moving average of call durations in milliseconds (0 before the first)
+    mean_ms: f64,
+}
+
+/// A running call's slot, given back when dropped
+struct Permit<'a> {
+    embedder: &'a LimitedEmbedder,
+    started: Instant,
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        self.embedder.release(self.started.elapsed());
+    }
+}
+
+impl LimitedEmbedder {
+    pub fn new(inner: Arc<dyn Embedder>, limits: &EmbeddingLimitsConfig) -> Self {
+        Self {
+            inner,
+            limits: limits.clone(),
+            slots: Mutex::new(Slots::default()),
+            freed: Condvar::new(),
+        }
+    }
+
+    /// Calls running and waiting right now
+    pub fn load(&self) -> (usize, usize) {
+        let slots = self.lock();
+        (slots.running, slots.queued)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Slots> {
+        self.slots.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Wait for a slot, or fail as busy when the queue is full
+    fn acquire(&self) -> Result<Permit<'_>> {
+        let max_concurrent = self.limits.max_concurrent;
+        let mut slots = self.lock();
+        if slots.running >= max_concurrent {
+            if slots.queued >= self.limits.max_queued {
+                return Err(EmbeddingError::Busy {
+                    retry_after_ms: self.retry_hint(&slots),
+                });
+            }
+            slots.queued += 1;
+            while slots.running >= max_concurrent {
+                slots = self.freed.wait(slots).unwrap_or_else(|e| e.into_inner());
+            }
+            slots.queued -= 1;
+        }
+        slots.running += 1;
+        Ok(Permit {
+            embedder: self,
+            started: Instant::now(),
+        })
+    }
+
+    fn release(&self, elapsed: Duration) {
+        let mut slots = self.lock();
+        slots.running -= 1;
+        let ms = elapsed.as_secs_f64() * 1000.0;
+        slots.mean_ms = if slots.mean_ms == 0.0 {
+            ms
+        } else {
+            slots.mean_ms * (1.0 - LATENCY_SMOOTHING) + ms * LATENCY_SMOOTHING
+        };
+        drop(slots);
+        self.freed.notify_one();
+    }
+
+    /// Roughly how long the calls ahead take to drain at the recent pace,
+    /// never less than `retry_after_ms`
+    fn retry_hint(&self, slots: &Slots) -> u64 {
+        let rounds = (slots.running + slots.queued) as f64 / self.limits.max_concurrent as f64;
+        ((slots.mean_ms * rounds).ceil() as u64).max(self.limits.retry_after_ms)
+    }
+}
+
+impl Embedder for LimitedEmbedder {
+    fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let _permit = self.acquire()?;
+        self.inner.embed_batch(texts)
+    }
+
+    fn dimensions(&self) -> usize {
+        self.inner.dimensions()
+    }
+
+    fn model_id(&self) -> &str {
+        self.inner.model_id()
+    }
+
+    fn truncates(&self, text: &str) -> bool {
+        self.inner.truncates(text)
+    }
+
+    /// Bulk re-embedding is an admin job: it shares the model without
+    /// taking slots, so it never turns interactive calls away
+    fn reembed(
+        &self,
+        storage: &Storage,
+        all: bool,
+        batch_size: usize,
+    ) -> oc_core::Result<ReembedReport> {
+        self.inner.reembed(storage, all, batch_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    /// Blocks each call until the test sends a go-ahead
+    struct Gated {
+        go: Mutex<mpsc::Receiver<()>>,
+    }
+
+    impl Embedder for Gated {
+        fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+            self.go.lock().unwrap().recv().unwrap();
+            Ok(texts.iter().map(|_| vec![1.0]).collect())
+        }
+
+        fn dimensions(&self) -> usize {
+            1
+        }
+
+        fn model_id(&self) -> &str {
+            "gated"
+        }
+    }
+
+    fn wait_for_load(embedder: &LimitedEmbedder, load: (usize, usize)) {
+        while embedder.load() != load {
+            std::thread::yield_now();
+        }
+    }
+
+    #[test]
+    fn test_full_queue_is_busy_with_retry_hint() {
+        let (go, rx) = mpsc::channel();
+        let limits = EmbeddingLimitsConfig {
+            max_concurrent: 1,
+            max_queued: 1,
+            retry_after_ms: 40,
+        };
+        let embedder = Arc::new(LimitedEmbedder::new(
+            Arc::new(Gated { go: Mutex::new(rx) }),
+            &limits,
+        ));
+
+        let running = {
+            let embedder = Arc::clone(&embedder);
+            std::thread::spawn(move || embedder.embed("a"))
+        };
+        wait_for_load(&embedder, (1, 0));
+        let queued = {
+            let embedder = Arc::clone(&embedder);
+            std::thread::spawn(move || embedder.embed("b"))
+        };
+        wait_for_load(&embedder, (1, 1));
+
+        let err = embedder.embed("c").unwrap_err();
+        assert_eq!(err.retry_after_ms(), Some(40));
+        assert_eq!(err.to_string(), "Embedder busy, retry in 40 ms");
+
+        go.send(()).unwrap();
+        go.send(()).unwrap();
+        assert!(running.join().unwrap().is_ok());
+        assert!(queued.join().unwrap().is_ok());
+        assert_eq!(embedder.load(), (0, 0));
+        go.send(()).unwrap();
+        assert!(embedder.embed("d").is_ok());
+    }
+}
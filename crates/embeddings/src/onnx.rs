@@ -0,0 +1,381 @@
+use oc_core::config::{
+    ArenaExtendStrategy, ExecutionProvider as ProviderChoice, GraphOptimization, OnnxSessionConfig,
+    TruncationStrategy,
+};
+use ort::ep::{self, ExecutionProvider, ExecutionProviderDispatch};
+use ort::session::Session;
+use ort::session::builder::GraphOptimizationLevel;
+use ort::value::Tensor;
+use std::path::Path;
+use std::sync::Mutex;
+use tokenizers::Tokenizer;
+
+use crate::engine::Embedder;
+use crate::error::{EmbeddingError, Result};
+
+/// BGE-m3-ko ONNX embedding backend
+///
+/// Loads an INT8 quantized ONNX model and tokenizer for Korean text embedding.
+/// Produces 1024-dimensional dense vectors.
+pub struct OnnxEmbedder {
+    /// Session requires &mut self for run(), so wrap in Mutex for thread safety
+    session: Mutex<Session>,
+    tokenizer: Tokenizer,
+    dimensions: usize,
+    max_length: usize,
+    truncation: TruncationStrategy,
+    /// Recorded with each embedding (see [`oc_core::config::embedding_model_id`])
+    model_id: String,
+}
+
+impl OnnxEmbedder {
+    /// Initialize the embedding engine with model and tokenizer paths
+    pub fn new(
+        model_path: impl AsRef<Path>,
+        tokenizer_path: impl AsRef<Path>,
+        dimensions: usize,
+        max_length: usize,
+        num_threads: usize,
+        execution_provider: ProviderChoice,
+        options: &OnnxSessionConfig,
+    ) -> Result<Self> {
+        let model_path = model_path.as_ref();
+        let tokenizer_path = tokenizer_path.as_ref();
+
+        if !model_path.exists() {
+            return Err(EmbeddingError::ModelNotFound(
+                model_path.display().to_string(),
+            ));
+        }
+
+        if !tokenizer_path.exists() {
+            return Err(EmbeddingError::ModelNotFound(
+                tokenizer_path.display().to_string(),
+            ));
+        }
+
+        tracing::info!(
+            model = %model_path.display(),
+            threads = num_threads,
+            inter_threads = options.inter_threads,
+            optimization = ?options.optimization_level,
+            memory_arena = options.memory_arena,
+            "Loading ONNX embedding model"
+        );
+
+        let session = match build_session(model_path, num_threads, execution_provider, options) {
+            Ok(session) => session,
+            Err(e) if execution_provider != ProviderChoice::Cpu => {
+                tracing::warn!("ONNX session with {execution_provider:?} failed ({e}); using CPU");
+                build_session(model_path, num_threads, ProviderChoice::Cpu, options)?
+            }
+            Err(e) => return Err(e),
+        };
+
+        let tokenizer = Tokenizer::from_file(tokenizer_path)
+            .map_err(|e| EmbeddingError::Tokenizer(e.to_string()))?;
+
+        tracing::info!(dimensions, max_length, "Embedding engine ready");
+
+        Ok(Self {
+            session: Mutex::new(session),
+            tokenizer,
+            dimensions,
+            max_length,
+            truncation: TruncationStrategy::Head,
+            model_id: oc_core::config::embedding_model_id(model_path),
+        })
+    }
+
+    /// Choose which tokens of over-long input are embedded (default: the
+    /// leading ones)
+    pub fn with_truncation(mut self, truncation: TruncationStrategy) -> Self {
+        self.truncation = truncation;
+        self
+    }
+}
+
+/// Positions of the tokens kept from a `len`-token sequence under `limit`.
+/// `Tail` keeps the first token too, since BERT-style models expect their
+/// `[CLS]`/`<s>` marker up front.
+fn kept_positions(len: usize, limit: usize, strategy: TruncationStrategy) -> Vec<usize> {
+    if len <= limit {
+        return (0..len).collect();
+    }
+    let head = match strategy {
+        TruncationStrategy::Head => limit,
+        TruncationStrategy::Tail => limit.min(1),
+        TruncationStrategy::HeadTail => limit.div_ceil(2),
+    };
+    (0..head).chain(len - (limit - head)..len).collect()
+}
+
+// ort 2.0 API: builder -> options -> commit_from_file
+fn build_session(
+    model_path: &Path,
+    num_threads: usize,
+    choice: ProviderChoice,
+    options: &OnnxSessionConfig,
+) -> Result<Session> {
+    let inter_threads = options.inter_threads.max(1);
+    let mut builder = Session::builder()?
+        .with_optimization_level(optimization_level(options.optimization_level))?
+        .with_intra_threads(num_threads)?
+        .with_inter_threads(inter_threads)?
+        .with_parallel_execution(inter_threads > 1)?
+        .with_memory_pattern(options.memory_pattern)?;
+    let mut providers = execution_providers(choice, options);
+    if !options.memory_arena {
+        // ONNX Runtime's implicit CPU provider uses the arena; register it
+        // explicitly to turn that off
+        providers.push(ep::CPU::default().with_arena_allocator(false).into());
+    }
+    if !providers.is_empty() {
+        builder = builder.with_execution_providers(providers)?;
+    }
+    Ok(builder.commit_from_file(model_path)?)
+}
+
+/// Providers to register for `choice`, skipping (with a warning) those the
+/// loaded ONNX Runtime lacks. An empty list means plain CPU execution.
+fn execution_providers(
+    choice: ProviderChoice,
+    options: &OnnxSessionConfig,
+) -> Vec<ExecutionProviderDispatch> {
+    let mut providers = Vec::new();
+    match choice {
+        ProviderChoice::Cpu => {}
+        ProviderChoice::CoreMl => push_available(&mut providers, ep::CoreML::default()),
+        ProviderChoice::Cuda => push_available(&mut providers, cuda(options)),
+        ProviderChoice::DirectMl => push_available(&mut providers, ep::DirectML::default()),
+        ProviderChoice::Auto => {
+            if cfg!(target_os = "macos") {
+                push_available(&mut providers, ep::CoreML::default());
+            } else {
+                if cfg!(windows) {
+                    push_available(&mut providers, ep::DirectML::default());
+                }
+                push_available(&mut providers, cuda(options));
+            }
+        }
+    }
+    if choice != ProviderChoice::Cpu && providers.is_empty() {
+        tracing::warn!("No {choice:?} execution provider available; embedding on CPU");
+    }
+    providers
+}
+
+fn cuda(options: &OnnxSessionConfig) -> ep::CUDA {
+    let strategy = match options.arena_extend_strategy {
+        ArenaExtendStrategy::NextPowerOfTwo => ep::ArenaExtendStrategy::NextPowerOfTwo,
+        ArenaExtendStrategy::SameAsRequested => ep::ArenaExtendStrategy::SameAsRequested,
+    };
+    let cuda = ep::CUDA::default().with_arena_extend_strategy(strategy);
+    match options.gpu_memory_limit_mb {
+        0 => cuda,
+        mb => cuda.with_memory_limit(mb * 1024 * 1024),
+    }
+}
+
+fn optimization_level(level: GraphOptimization) -> GraphOptimizationLevel {
+    match level {
+        GraphOptimization::Disable => GraphOptimizationLevel::Disable,
+        GraphOptimization::Basic => GraphOptimizationLevel::Level1,
+        GraphOptimization::Extended => GraphOptimizationLevel::Level2,
+        GraphOptimization::Layout => GraphOptimizationLevel::Level3,
+        GraphOptimization::All => GraphOptimizationLevel::All,
+    }
+}
+
+fn push_available<P>(providers: &mut Vec<ExecutionProviderDispatch>, provider: P)
+where
+    P: ExecutionProvider + Into<ExecutionProviderDispatch>,
+{
+    match provider.is_available() {
+        Ok(true) => {
+            tracing::info!(provider = provider.name(), "Using ONNX execution provider");
+            providers.push(provider.into());
+        }
+        Ok(false) => tracing::warn!(
+            provider = provider.name(),
+            "Execution provider not available"
+        ),
+        Err(e) => tracing::warn!(
+            provider = provider.name(),
+            "Execution provider check failed: {e}"
+        ),
+    }
+}
+
+impl Embedder for OnnxEmbedder {
+    fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Tokenize
+        let encodings = self
+            .tokenizer
+            .encode_batch(texts.to_vec(), true)
+            .map_err(|e| EmbeddingError::Tokenizer(e.to_string()))?;
+
+        let batch_size = encodings.len();
+        let max_len = encodings
+            .iter()
+            .map(|e| e.get_ids().len().min(self.max_length))
+            .max()
+            .unwrap_or(0);
+
+        // Prepare input data as flat vectors
+        let mut input_ids_data = vec![0i64; batch_size * max_len];
+        let mut attention_mask_data = vec![0i64; batch_size * max_len];
+        let token_type_ids_data = vec![0i64; batch_size * max_len];
+
+        for (i, encoding) in encodings.iter().enumerate() {
+            let ids = encoding.get_ids();
+            let mask = encoding.get_attention_mask();
+            let kept = kept_positions(ids.len(), max_len, self.truncation);
+
+            for (j, &pos) in kept.iter().enumerate() {
+                input_ids_data[i * max_len + j] = ids[pos] as i64;
+                attention_mask_data[i * max_len + j] = mask[pos] as i64;
+            }
+        }
+
+        // Create ort Tensor values using from_array((shape, data))
+        let shape = vec![batch_size as i64, max_len as i64];
+
+        let input_ids = Tensor::from_array((shape.clone(), input_ids_data.into_boxed_slice()))
+            .map_err(EmbeddingError::OnnxRuntime)?;
+
+        let attention_mask =
+            Tensor::from_array((shape.clone(), attention_mask_data.into_boxed_slice()))
+                .map_err(EmbeddingError::OnnxRuntime)?;
+
+        let token_type_ids = Tensor::from_array((shape, token_type_ids_data.into_boxed_slice()))
+            .map_err(EmbeddingError::OnnxRuntime)?;
+
+        // Run inference — session.run requires &mut self
+        // We extract data into owned arrays within the session scope
+        let (output_shape, output_data) = {
+            let mut session = self
+                .session
+                .lock()
+                .map_err(|e| EmbeddingError::Tokenizer(format!("Session lock poisoned: {e}")))?;
+
+            let has_token_type_ids = session
+                .inputs()
+                .iter()
+                .any(|input| input.name() == "token_type_ids");
+
+            let outputs = if has_token_type_ids {
+                session.run(ort::inputs![
+                    "input_ids" => input_ids,
+                    "attention_mask" => attention_mask,
+                    "token_type_ids" => token_type_ids,
+                ])?
+            } else {
+                session.run(ort::inputs![
+                    "input_ids" => input_ids,
+                    "attention_mask" => attention_mask,
+                ])?
+            };
+
+            // Extract output tensor and copy to owned data
+            let output_value = &outputs[0];
+            let output_tensor = output_value
+                .try_extract_array::<f32>()
+                .map_err(|e| EmbeddingError::Tokenizer(format!("Failed to extract output: {e}")))?;
+
+            let shape = output_tensor.shape().to_vec();
+            let data = output_tensor
+                .as_slice()
+                .map(|s| s.to_vec())
+                .unwrap_or_else(|| output_tensor.iter().copied().collect());
+            (shape, data)
+            // session + outputs dropped here
+        };
+
+        let hidden_dim = if output_shape.len() == 3 {
+            output_shape[2]
+        } else {
+            self.dimensions
+        };
+        let seq_len_total = if output_shape.len() == 3 {
+            output_shape[1]
+        } else {
+            max_len
+        };
+        let mut results = Vec::with_capacity(batch_size);
+
+        for (i, encoding) in encodings.iter().enumerate().take(batch_size) {
+            let actual_seq_len = encoding.get_ids().len().min(max_len);
+            let mut embedding = vec![0f32; hidden_dim];
+
+            // Mean pooling over non-padding tokens using flat output_data
+            let batch_offset = i * seq_len_total * hidden_dim;
+            for j in 0..actual_seq_len {
+                let token_offset = batch_offset + j * hidden_dim;
+                for k in 0..hidden_dim {
+                    embedding[k] += output_data[token_offset + k];
+                }
+            }
+            if actual_seq_len > 0 {
+                for val in &mut embedding {
+                    *val /= actual_seq_len as f32;
+                }
+            }
+
+            // L2 normalize
+            let norm: f32 = embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
+            if norm > 0.0 {
+                for val in &mut embedding {
+                    *val /= norm;
+                }
+            }
+
+            results.push(embedding);
+        }
+
+        Ok(results)
+    }
+
+    fn truncates(&self, text: &str) -> bool {
+        self.tokenizer
+            .encode(text, true)
+            .is_ok_and(|encoding| encoding.get_ids().len() > self.max_length)
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    fn model_id(&self) -> &str {
+        &self.model_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kept_positions_per_strategy() {
+        assert_eq!(
+            kept_positions(3, 4, TruncationStrategy::Tail),
+            vec![0, 1, 2]
+        );
+        assert_eq!(
+            kept_positions(8, 4, TruncationStrategy::Head),
+            vec![0, 1, 2, 3]
+        );
+        assert_eq!(
+            kept_positions(8, 4, TruncationStrategy::Tail),
+            vec![0, 5, 6, 7]
+        );
+        assert_eq!(
+            kept_positions(9, 5, TruncationStrategy::HeadTail),
+            vec![0, 1, 2, 7, 8]
+        );
+    }
+}
@@ -0,0 +1,188 @@
+//! mem0 exports: the JSON of `Memory.get_all()` or the platform export,
+//! `{"results": [...]}` or a bare array of records with `id`, `memory`,
+//! `categories`, `created_at` and `updated_at`. MemGPT archival memory
+//! dumps, whose records carry `text` instead of `memory`, read the same way.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use oc_core::models::{Memory, MemoryMetadata, MemoryType};
+use serde::Deserialize;
+
+use crate::{ImportedMemory, Importer, parse_time};
+
+/// Longest title taken from the first line of a record
+const TITLE_CHARS: usize = 80;
+
+/// mem0 categories that mark a preference rather than a fact
+const PREFERENCE_CATEGORIES: &[&str] = &["preferences", "user_preferences", "preference"];
+
+/// Reads mem0 (and MemGPT archival) JSON exports. Categories become tags;
+/// records in a preference category become preferences and the rest facts.
+pub struct Mem0Importer;
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Export {
+    Records(Vec<Record>),
+    Wrapped {
+        #[serde(alias = "memories", alias = "data")]
+        results: Vec<Record>,
+    },
+}
+
+#[derive(Deserialize)]
+struct Record {
+    /// A string in mem0, sometimes a number elsewhere
+    #[serde(default)]
+    id: Option<serde_json::Value>,
+    #[serde(default, alias = "text", alias = "content")]
+    memory: Option<String>,
+    #[serde(default)]
+    categories: Option<Vec<String>>,
+    #[serde(default)]
+    created_at: Option<String>,
+    #[serde(default)]
+    updated_at: Option<String>,
+}
+
+impl Importer for Mem0Importer {
+    fn format(&self) -> &'static str {
+        "mem0"
+    }
+
+    fn read(&self, path: &Path) -> Result<Vec<ImportedMemory>> {
+        let input = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Ok(self.parse(&input)?.into_iter().map(Into::into).collect())
+    }
+}
+
+impl Mem0Importer {
+    /// The memories of the export JSON `input`. Records without text are
+    /// left out; missing or unreadable timestamps fall back to the creation
+    /// time, or to now.
+    pub fn parse(&self, input: &str) -> Result<Vec<Memory>> {
+        let export: Export = serde_json::from_str(input)
+            .context("Not a mem0 export (a JSON array of memories or {\"results\": [...]})")?;
+        let records = match export {
+            Export::Records(records) | Export::Wrapped { results: records } => records,
+        };
+        Ok(records
+            .into_iter()
+            .filter_map(|record| self.to_memory(record))
+            .collect())
+    }
+
+    fn to_memory(&self, record: Record) -> Option<Memory> {
+        let content = record.memory?.trim().to_string();
+        if content.is_empty() {
+            return None;
+        }
+        let categories = record.categories.unwrap_or_default();
+        let memory_type = if categories
+            .iter()
+            .any(|c| PREFERENCE_CATEGORIES.contains(&c.to_lowercase().as_str()))
+        {
+            MemoryType::Preference
+        } else {
+            MemoryType::Fact
+        };
+        let source = record.id.and_then(|id| match id {
+            serde_json::Value::String(id) if !id.is_empty() => Some(id),
+            serde_json::Value::Number(id) => Some(id.to_string()),
+            _ => None,
+        });
+
+        let mut memory = Memory::new(
+            content.clone(),
+            title_of(&content),
+            MemoryMetadata {
+                memory_type,
+                source: source.map(|id| format!("{}:{id}", self.format())),
+                tags: categories,
+                ..Default::default()
+            },
+        );
+        if let Some(created_at) = record.created_at.as_deref().and_then(parse_time) {
+            memory.created_at = created_at;
+        }
+        memory.updated_at = record
+            .updated_at
+            .as_deref()
+            .and_then(parse_time)
+            .unwrap_or(memory.created_at);
+        memory.accessed_at = memory.updated_at;
+        Some(memory)
+    }
+}
+
+/// First line of `content`, cut to [`TITLE_CHARS`]
+fn title_of(content: &str) -> String {
+    let line = content.lines().next().unwrap_or_default().trim();
+    if line.chars().count() <= TITLE_CHARS {
+        return line.to_string();
+    }
+    let cut: String = line.chars().take(TITLE_CHARS - 1).collect();
+    format!("{}…", cut.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_maps_records_with_categories_and_timestamps() {
+        let export = r#"{"results": [
+            {
+                "id": "3f1c",
+                "memory": "Prefers dark roast coffee",
+                "hash": "abc",
+                "categories": ["food", "user_preferences"],
+                "created_at": "2024-07-20T09:15:00.123456-07:00",
+                "updated_at": "2024-08-01T10:00:00-07:00",
+                "user_id": "alice"
+            },
+            {"id": "9b2e", "memory": "Works at a bank", "categories": null, "created_at": "2024-07-21T08:00:00.5"},
+            {"id": "empty", "memory": "  "}
+        ]}"#;
+        let memories = Mem0Importer.parse(export).unwrap();
+        assert_eq!(memories.len(), 2);
+
+        let coffee = &memories[0];
+        assert_eq!(coffee.title, "Prefers dark roast coffee");
+        assert_eq!(coffee.metadata.memory_type, MemoryType::Preference);
+        assert_eq!(coffee.metadata.tags, ["food", "user_preferences"]);
+        assert_eq!(coffee.metadata.source.as_deref(), Some("mem0:3f1c"));
+        assert_eq!(
+            coffee.created_at.to_rfc3339(),
+            "2024-07-20T16:15:00.123456+00:00"
+        );
+        assert_eq!(coffee.updated_at.to_rfc3339(), "2024-08-01T17:00:00+00:00");
+
+        let bank = &memories[1];
+        assert_eq!(bank.metadata.memory_type, MemoryType::Fact);
+        assert_eq!(
+            bank.created_at.to_rfc3339(),
+            "2024-07-21T08:00:00.500+00:00"
+        );
+        assert_eq!(bank.updated_at, bank.created_at);
+    }
+
+    #[test]
+    fn test_reads_bare_arrays_and_memgpt_text() {
+        let export =
+            r#"[{"id": 7, "text": "첫 줄 제목\n본문", "created_at": "2024-01-02 03:04:05"}]"#;
+        let memories = Mem0Importer.parse(export).unwrap();
+        assert_eq!(memories[0].title, "첫 줄 제목");
+        assert_eq!(memories[0].content, "첫 줄 제목\n본문");
+        assert_eq!(memories[0].metadata.source.as_deref(), Some("mem0:7"));
+        assert_eq!(
+            memories[0].created_at.to_rfc3339(),
+            "2024-01-02T03:04:05+00:00"
+        );
+
+        assert_eq!(title_of(&"가".repeat(100)).chars().count(), TITLE_CHARS);
+        assert!(Mem0Importer.parse(r#"{"memories": "none"}"#).is_err());
+    }
+}
@@ -0,0 +1,482 @@
+//! Notion exports, unzipped: a directory of pages exported as Markdown or
+//! HTML. Each page is a file named after its title and a 32-digit ID
+//! (`Roadmap 0f3b…e21.md`), and its sub-pages sit in the folder of the same
+//! name next to it, which is how the page hierarchy comes through.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use oc_core::models::{Memory, MemoryMetadata, MemoryType};
+
+use crate::{ImportedMemory, Importer, parse_time};
+
+/// Hex digits of the page ID Notion appends to file names
+const ID_DIGITS: usize = 32;
+
+/// Reads Notion Markdown and HTML exports. `Tags` become tags, `Created`
+/// and `Last edited time` the timestamps, and every page is linked
+/// `part_of` the page it sits under.
+pub struct NotionImporter;
+
+/// A page's property that maps onto a memory
+enum Property {
+    Tags,
+    Created,
+    Updated,
+}
+
+/// What one exported page holds
+#[derive(Debug, Default)]
+struct Page {
+    title: Option<String>,
+    body: String,
+    tags: Vec<String>,
+    created: Option<DateTime<Utc>>,
+    updated: Option<DateTime<Utc>>,
+}
+
+impl Importer for NotionImporter {
+    fn format(&self) -> &'static str {
+        "notion"
+    }
+
+    /// `path` is the export directory or a single page. Pages are read in
+    /// path order, so parents come before their sub-pages.
+    fn read(&self, path: &Path) -> Result<Vec<ImportedMemory>> {
+        let mut files = Vec::new();
+        if path.is_dir() {
+            collect_pages(path, &mut files)?;
+        } else if is_page(path) {
+            files.push(path.to_path_buf());
+        } else {
+            bail!(
+                "Not a Notion export (a directory of .md or .html pages): {}",
+                path.display()
+            );
+        }
+        // Byte order puts `Page.md` before the `Page/` folder of its sub-pages
+        files.sort_by(|a, b| a.as_os_str().cmp(b.as_os_str()));
+
+        // A page's sub-pages sit in the folder named like its file
+        let folders: HashMap<PathBuf, usize> = files
+            .iter()
+            .enumerate()
+            .map(|(index, file)| (file.with_extension(""), index))
+            .collect();
+        files
+            .iter()
+            .map(|file| {
+                let text = fs::read_to_string(file)
+                    .with_context(|| format!("Failed to read {}", file.display()))?;
+                let relative = file.strip_prefix(path).unwrap_or(file);
+                Ok(ImportedMemory {
+                    memory: self.to_memory(file, relative, &text),
+                    parent: file.parent().and_then(|dir| folders.get(dir)).copied(),
+                })
+            })
+            .collect()
+    }
+}
+
+impl NotionImporter {
+    fn to_memory(&self, file: &Path, relative: &Path, text: &str) -> Memory {
+        let page = if is_html(file) {
+            parse_html(text)
+        } else {
+            parse_markdown(text)
+        };
+        let stem = file
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let (name, id) = split_id(&stem);
+        let title = page
+            .title
+            .filter(|t| !t.is_empty())
+            .unwrap_or_else(|| name.to_string());
+        // A page holding only sub-pages still stands for them
+        let content = if page.body.is_empty() {
+            title.clone()
+        } else {
+            page.body
+        };
+        let source = match id {
+            Some(id) => id.to_string(),
+            None => relative.to_string_lossy().replace('\\', "/"),
+        };
+
+        let mut memory = Memory::new(
+            content,
+            title,
+            MemoryMetadata {
+                memory_type: MemoryType::Fact,
+                source: Some(format!("{}:{source}", self.format())),
+                tags: page.tags,
+                ..Default::default()
+            },
+        );
+        if let Some(created) = page.created {
+            memory.created_at = created;
+        }
+        memory.updated_at = page.updated.unwrap_or(memory.created_at);
+        memory.accessed_at = memory.updated_at;
+        memory
+    }
+}
+
+fn collect_pages(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let entries = fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))?;
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_pages(&path, files)?;
+        } else if is_page(&path) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn is_page(path: &Path) -> bool {
+    is_html(path) || has_extension(path, &["md", "markdown"])
+}
+
+fn is_html(path: &Path) -> bool {
+    has_extension(path, &["html", "htm"])
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| extensions.iter().any(|x| e.eq_ignore_ascii_case(x)))
+}
+
+/// `Roadmap 0f3b…e21` into the title and the ID Notion appended
+fn split_id(stem: &str) -> (&str, Option<&str>) {
+    match stem.rsplit_once(' ') {
+        Some((name, id)) if id.len() == ID_DIGITS && id.chars().all(|c| c.is_ascii_hexdigit()) => {
+            (name.trim(), Some(id))
+        }
+        _ => (stem.trim(), None),
+    }
+}
+
+fn property(key: &str) -> Option<Property> {
+    match key.trim().to_lowercase().as_str() {
+        "tags" | "tag" | "labels" | "keywords" => Some(Property::Tags),
+        "created" | "created time" | "created at" | "date created" => Some(Property::Created),
+        "last edited time" | "last edited" | "updated" | "updated at" => Some(Property::Updated),
+        _ => None,
+    }
+}
+
+impl Page {
+    /// Take the property `key` when it maps onto the memory. `tags` are
+    /// the separate values of a multi-select, when known.
+    fn set(&mut self, key: &str, value: &str, tags: Vec<String>) {
+        match property(key) {
+            Some(Property::Tags) if !tags.is_empty() => self.tags = tags,
+            Some(Property::Tags) => {
+                self.tags = value
+                    .split(',')
+                    .map(|t| t.trim().to_string())
+                    .filter(|t| !t.is_empty())
+                    .collect();
+            }
+            Some(Property::Created) => self.created = parse_date(value).or(self.created),
+            Some(Property::Updated) => self.updated = parse_date(value).or(self.updated),
+            None => {}
+        }
+    }
+}
+
+/// Notion's `January 5, 2024 3:04 PM` or `January 5, 2024` (a date
+/// mention's leading `@` aside), an ISO date, or an RFC 3339 timestamp
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim().trim_start_matches('@').trim();
+    parse_time(value)
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(value, "%B %d, %Y %I:%M %p")
+                .ok()
+                .map(|t| t.and_utc())
+        })
+        .or_else(|| {
+            ["%B %d, %Y", "%Y-%m-%d", "%Y/%m/%d"]
+                .iter()
+                .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map(|t| t.and_utc())
+        })
+}
+
+/// A Markdown page: `# Title`, then a block of `Key: value` properties
+/// when Notion wrote one, then the content
+fn parse_markdown(text: &str) -> Page {
+    let mut page = Page::default();
+    let lines: Vec<&str> = text.lines().collect();
+    let mut at = 0;
+    let skip_blank = |at: &mut usize| {
+        while lines.get(*at).is_some_and(|l| l.trim().is_empty()) {
+            *at += 1;
+        }
+    };
+
+    skip_blank(&mut at);
+    if let Some(title) = lines.get(at).and_then(|l| l.strip_prefix("# ")) {
+        page.title = Some(title.trim().to_string());
+        at += 1;
+        skip_blank(&mut at);
+    }
+    let block: Vec<(&str, &str)> = lines[at..]
+        .iter()
+        .take_while(|l| !l.trim().is_empty())
+        .map_while(|l| l.split_once(": "))
+        .collect();
+    let paragraph = lines[at..]
+        .iter()
+        .take_while(|l| !l.trim().is_empty())
+        .count();
+    // Only a block made of properties, one of them known, is taken for one
+    if block.len() == paragraph && block.iter().any(|(key, _)| property(key).is_some()) {
+        for (key, value) in block {
+            page.set(key, value, Vec::new());
+        }
+        at += paragraph;
+    }
+    page.body = lines[at..].join("\n").trim().to_string();
+    page
+}
+
+/// An HTML page: the `page-title` heading, the `properties` table and the
+/// `page-body` content as plain text
+fn parse_html(html: &str) -> Page {
+    let mut page = Page {
+        title: element(html, "<h1 class=\"page-title\"")
+            .or_else(|| element(html, "<title"))
+            .map(html_to_text)
+            .filter(|t| !t.is_empty()),
+        ..Default::default()
+    };
+    if let Some(table) = element(html, "<table class=\"properties\"") {
+        for row in table.split("<tr").skip(1) {
+            let (Some(key), Some(value)) = (element(row, "<th"), element(row, "<td")) else {
+                continue;
+            };
+            let tags = value
+                .split("<span")
+                .skip(1)
+                .filter_map(|span| span.split_once('>'))
+                .map(|(_, inner)| html_to_text(inner.split("</span>").next().unwrap_or_default()))
+                .filter(|t| !t.is_empty())
+                .collect();
+            page.set(&html_to_text(key), &html_to_text(value), tags);
+        }
+    }
+    // The rest of the document: the body holds nested elements
+    let body = ["<div class=\"page-body\"", "<body"]
+        .iter()
+        .find_map(|open| {
+            let start = html.find(open)?;
+            html[start..].find('>').map(|end| &html[start + end + 1..])
+        })
+        .unwrap_or(html);
+    page.body = html_to_text(body);
+    page
+}
+
+/// Content of the first element opened by `open` (such as `<td`), up to
+/// the closing tag of its name; to the end when that is missing
+fn element<'a>(html: &'a str, open: &str) -> Option<&'a str> {
+    let start = html.find(open)?;
+    let content = start + html[start..].find('>')? + 1;
+    let name = open.trim_start_matches('<').split(' ').next()?;
+    let end = html[content..]
+        .find(&format!("</{name}>"))
+        .map_or(html.len(), |end| content + end);
+    Some(&html[content..end])
+}
+
+/// Tags to whitespace (a line break for block elements), `<style>` and
+/// `<script>` dropped, entities decoded and blank lines collapsed
+fn html_to_text(html: &str) -> String {
+    const BLOCKS: &[&str] = &[
+        "p",
+        "div",
+        "br",
+        "h1",
+        "h2",
+        "h3",
+        "h4",
+        "h5",
+        "h6",
+        "li",
+        "tr",
+        "ul",
+        "ol",
+        "table",
+        "blockquote",
+        "pre",
+        "hr",
+        "figure",
+        "details",
+        "summary",
+    ];
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(open) = rest.find('<') {
+        text.push_str(&rest[..open]);
+        let Some(close) = rest[open..].find('>') else {
+            rest = &rest[open..];
+            break;
+        };
+        let tag = &rest[open + 1..open + close];
+        rest = &rest[open + close + 1..];
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        if name == "style" || name == "script" {
+            if !tag.starts_with('/') {
+                let end = format!("</{name}>");
+                rest = rest.find(&end).map_or("", |at| &rest[at + end.len()..]);
+            }
+        } else if name == "li" {
+            if !tag.starts_with('/') {
+                text.push_str("\n- ");
+            }
+        } else if BLOCKS.contains(&name.as_str()) {
+            text.push('\n');
+        } else if name == "td" || name == "th" {
+            text.push(' ');
+        }
+    }
+    text.push_str(rest);
+
+    let text = decode_entities(&text);
+    let mut lines: Vec<&str> = Vec::new();
+    for line in text.lines().map(str::trim_end) {
+        if line.trim().is_empty() && lines.last().is_none_or(|l| l.is_empty()) {
+            continue;
+        }
+        lines.push(if line.trim().is_empty() { "" } else { line });
+    }
+    lines.join("\n").trim().to_string()
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(at) = rest.find('&') {
+        decoded.push_str(&rest[..at]);
+        rest = &rest[at..];
+        let entity = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 10)
+            .map(|end| &rest[1..=end]);
+        let ch = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|n| n.parse().ok()))
+                .and_then(char::from_u32),
+        });
+        match (ch, entity) {
+            (Some(ch), Some(entity)) => {
+                decoded.push(ch);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "0f3b5a7c9d1e2f30415263748596a7b8";
+
+    #[test]
+    fn test_reads_markdown_pages_with_hierarchy() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join(format!("Roadmap {ID}.md"));
+        fs::write(
+            &root,
+            "# Roadmap\n\nTags: planning, 2024\nCreated: January 5, 2024 3:04 PM\n\n\
+             Ship the importer.\n\nNote: keep it small.\n",
+        )
+        .unwrap();
+        let sub = dir.path().join(format!("Roadmap {ID}"));
+        fs::create_dir(&sub).unwrap();
+        fs::write(sub.join("Q1.md"), "# Q1\n\nImporters first.\n").unwrap();
+        fs::write(sub.join("image.png"), [0u8; 4]).unwrap();
+
+        let pages = NotionImporter.read(dir.path()).unwrap();
+        assert_eq!(pages.len(), 2);
+        let roadmap = &pages[0].memory;
+        assert_eq!(roadmap.title, "Roadmap");
+        assert_eq!(
+            roadmap.content,
+            "Ship the importer.\n\nNote: keep it small."
+        );
+        assert_eq!(roadmap.metadata.tags, ["planning", "2024"]);
+        assert_eq!(
+            roadmap.metadata.source.as_deref(),
+            Some(&*format!("notion:{ID}"))
+        );
+        assert_eq!(roadmap.created_at.to_rfc3339(), "2024-01-05T15:04:00+00:00");
+        assert_eq!(pages[0].parent, None);
+
+        let q1 = &pages[1];
+        assert_eq!(q1.memory.title, "Q1");
+        assert_eq!(q1.parent, Some(0));
+        assert_eq!(
+            q1.memory.metadata.source.as_deref(),
+            Some(&*format!("notion:Roadmap {ID}/Q1.md"))
+        );
+    }
+
+    #[test]
+    fn test_strips_html_pages() {
+        let html = r#"<html><head><title>Design</title><style>h1 { color: red; }</style></head>
+<body><article><header><h1 class="page-title">설계 &amp; 결정</h1>
+<table class="properties"><tbody>
+<tr class="property-row"><th>Tags</th><td><span class="selected-value">rust</span><span class="selected-value">db</span></td></tr>
+<tr class="property-row"><th>Created</th><td><time>@March 2, 2024</time></td></tr>
+</tbody></table></header>
+<div class="page-body"><p>Use <strong>SQLite</strong>&nbsp;for storage.</p><ul><li>WAL mode</li><li>FTS &lt;later&gt;</li></ul></div>
+</article></body></html>"#;
+        let page = parse_html(html);
+        assert_eq!(page.title.as_deref(), Some("설계 & 결정"));
+        assert_eq!(page.tags, ["rust", "db"]);
+        assert_eq!(
+            page.created.unwrap().to_rfc3339(),
+            "2024-03-02T00:00:00+00:00"
+        );
+        assert_eq!(
+            page.body,
+            "Use SQLite for storage.\n\n- WAL mode\n- FTS <later>"
+        );
+
+        assert_eq!(split_id(&format!("설계 {ID}")), ("설계", Some(ID)));
+        assert_eq!(split_id("Untitled"), ("Untitled", None));
+        assert!(parse_markdown("Note: no properties here").tags.is_empty());
+    }
+}
@@ -0,0 +1,260 @@
+use oc_core::config::McpConfig;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Concurrency class of an MCP tool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolClass {
+    /// Writes that an agent usually waits on interactively
+    Mutation,
+    /// Searches and lookups, which agents tend to issue in bulk
+    Read,
+}
+
+impl ToolClass {
+    /// Class of a known tool, or `None` for unknown tool names
+    pub fn of(tool_name: &str) -> Option<Self> {
+        match tool_name {
+            "memory_store" | "memory_update" | "memory_delete" | "memory_archive"
+            | "memory_unarchive" | "memory_retag" | "memory_tune" | "memory_feedback"
+            | "memory_link" | "memory_merge" | "session_save" => Some(Self::Mutation),
+            "memory_search"
+            | "memory_search_batch"
+            | "memory_get"
+            | "memory_grep"
+            | "memory_related"
+            | "memory_links_get"
+            | "memory_stats"
+            | "memory_analytics"
+            | "memory_health"
+            | "session_recall" => Some(Self::Read),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Mutation => "mutation",
+            Self::Read => "read",
+        }
+    }
+}
+
+/// Rejection returned when a lane's wait queue is full
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Busy {
+    pub class: ToolClass,
+    pub running: usize,
+    pub queued: usize,
+    pub max_queued: usize,
+}
+
+impl fmt::Display for Busy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Server busy: {} {} calls running and {} queued (queue limit {}). Retry shortly",
+            self.running,
+            self.class.as_str(),
+            self.queued,
+            self.max_queued
+        )?;
+        if self.class == ToolClass::Read {
+            write!(f, "; memory_store and other mutations are not affected")?;
+        }
+        Ok(())
+    }
+}
+
+/// Admission control for tool calls: one bounded lane per [`ToolClass`], so
+/// mutations never queue behind a flood of reads.
+pub struct Admission {
+    mutations: Lane,
+    reads: Lane,
+}
+
+impl Admission {
+    pub fn new(config: &McpConfig) -> Self {
+        Self {
+            mutations: Lane::new(config.max_concurrent_mutations, config.max_queued_mutations),
+            reads: Lane::new(config.max_concurrent_reads, config.max_queued_reads),
+        }
+    }
+
+    /// Wait for a slot in the lane of `class`. Fails immediately with [`Busy`]
+    /// when the lane's queue is already full. The slot is held until the
+    /// returned permit is dropped.
+    pub async fn admit(&self, class: ToolClass) -> Result<OwnedSemaphorePermit, Busy> {
+        self.lane(class).admit(class).await
+    }
+
+    /// Calls of `class` currently running
+    pub fn running(&self, class: ToolClass) -> usize {
+        self.lane(class).running()
+    }
+
+    /// Calls of `class` currently waiting for a slot
+    pub fn queued(&self, class: ToolClass) -> usize {
+        self.lane(class).queued.load(Ordering::Acquire)
+    }
+
+    fn lane(&self, class: ToolClass) -> &Lane {
+        match class {
+            ToolClass::Mutation => &self.mutations,
+            ToolClass::Read => &self.reads,
+        }
+    }
+}
+
+impl Default for Admission {
+    fn default() -> Self {
+        Self::new(&McpConfig::default())
+    }
+}
+
+struct Lane {
+    slots: Arc<Semaphore>,
+    limit: usize,
+    max_queued: usize,
+    queued: AtomicUsize,
+}
+
+impl Lane {
+    fn new(limit: usize, max_queued: usize) -> Self {
+        let limit = limit.max(1);
+        Self {
+            slots: Arc::new(Semaphore::new(limit)),
+            limit,
+            max_queued,
+            queued: AtomicUsize::new(0),
+        }
+    }
+
+    fn running(&self) -> usize {
+        self.limit - self.slots.available_permits()
+    }
+
+    async fn admit(&self, class: ToolClass) -> Result<OwnedSemaphorePermit, Busy> {
+        if let Ok(permit) = Arc::clone(&self.slots).try_acquire_owned() {
+            return Ok(permit);
+        }
+
+        let reserved = self
+            .queued
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |queued| {
+                (queued < self.max_queued).then_some(queued + 1)
+            });
+        if let Err(queued) = reserved {
+            return Err(Busy {
+                class,
+                running: self.running(),
+                queued,
+                max_queued: self.max_queued,
+            });
+        }
+
+        // Released on drop, so a cancelled waiter frees its queue spot too
+        let _waiting = QueueSpot(&self.queued);
+        Ok(Arc::clone(&self.slots)
+            .acquire_owned()
+            .await
+            .expect("admission semaphore is never closed"))
+    }
+}
+
+struct QueueSpot<'a>(&'a AtomicUsize);
+
+impl Drop for QueueSpot<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn admission(limit: usize, max_queued: usize) -> Arc<Admission> {
+        Arc::new(Admission::new(&McpConfig {
+            max_concurrent_mutations: limit,
+            max_queued_mutations: max_queued,
+            max_concurrent_reads: limit,
+            max_queued_reads: max_queued,
+            ..Default::default()
+        }))
+    }
+
+    async fn wait_for_queued(admission: &Admission, class: ToolClass, n: usize) {
+        while admission.queued(class) < n {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[test]
+    fn test_tool_classes() {
+        assert_eq!(ToolClass::of("memory_store"), Some(ToolClass::Mutation));
+        assert_eq!(ToolClass::of("memory_tune"), Some(ToolClass::Mutation));
+        assert_eq!(ToolClass::of("memory_search"), Some(ToolClass::Read));
+        assert_eq!(ToolClass::of("memory_stats"), Some(ToolClass::Read));
+        assert_eq!(ToolClass::of("memory_health"), Some(ToolClass::Read));
+        assert_eq!(ToolClass::of("session_save"), Some(ToolClass::Mutation));
+        assert_eq!(ToolClass::of("session_recall"), Some(ToolClass::Read));
+        assert_eq!(ToolClass::of("memory_analytics"), Some(ToolClass::Read));
+        assert_eq!(ToolClass::of("nope"), None);
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_is_busy() {
+        let admission = admission(1, 1);
+        let running = admission.admit(ToolClass::Read).await.unwrap();
+
+        let waiter = tokio::spawn({
+            let admission = Arc::clone(&admission);
+            async move { admission.admit(ToolClass::Read).await.is_ok() }
+        });
+        wait_for_queued(&admission, ToolClass::Read, 1).await;
+
+        let busy = admission.admit(ToolClass::Read).await.unwrap_err();
+        assert_eq!((busy.running, busy.queued, busy.max_queued), (1, 1, 1));
+        assert!(
+            busy.to_string()
+                .contains("1 read calls running and 1 queued")
+        );
+
+        drop(running);
+        assert!(waiter.await.unwrap());
+        assert_eq!(admission.queued(ToolClass::Read), 0);
+    }
+
+    #[tokio::test]
+    async fn test_mutations_not_blocked_by_reads() {
+        let admission = admission(1, 0);
+        let _read = admission.admit(ToolClass::Read).await.unwrap();
+        assert!(admission.admit(ToolClass::Read).await.is_err());
+
+        let mutation =
+            tokio::time::timeout(Duration::from_secs(1), admission.admit(ToolClass::Mutation))
+                .await
+                .expect("mutation should not wait behind reads");
+        assert!(mutation.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_waiter_frees_queue_spot() {
+        let admission = admission(1, 1);
+        let _running = admission.admit(ToolClass::Mutation).await.unwrap();
+
+        let waiter = tokio::spawn({
+            let admission = Arc::clone(&admission);
+            async move { admission.admit(ToolClass::Mutation).await.is_ok() }
+        });
+        wait_for_queued(&admission, ToolClass::Mutation, 1).await;
+        waiter.abort();
+        let _ = waiter.await;
+
+        assert_eq!(admission.queued(ToolClass::Mutation), 0);
+    }
+}
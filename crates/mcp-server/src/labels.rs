@@ -0,0 +1,198 @@
+use oc_core::config::ResponseLanguage;
+use serde_json::Value;
+
+/// Human-readable labels of memory_search, memory_get and memory_stats
+/// output. `{n}` and `{v}` are filled in by the caller.
+pub struct Labels {
+    pub found: &'static str,
+    pub no_results: &'static str,
+    pub no_ids_found: &'static str,
+    pub ranking_variant: &'static str,
+    pub scoring_profile: &'static str,
+    pub degraded: &'static str,
+    pub score: &'static str,
+    pub id: &'static str,
+    pub memory_type: &'static str,
+    pub priority: &'static str,
+    pub tags: &'static str,
+    pub scores: &'static str,
+    pub content: &'static str,
+    pub concepts: &'static str,
+    pub fields: &'static str,
+    pub created: &'static str,
+    pub expires: &'static str,
+    pub linked: &'static str,
+    pub deleted_memories: &'static str,
+    pub deleted_at: &'static str,
+    pub deleted_by: &'static str,
+    pub stats_title: &'static str,
+    pub total_memories: &'static str,
+    pub namespace: &'static str,
+    pub namespaces: &'static str,
+    pub cold: &'static str,
+    pub archived: &'static str,
+    pub indexed: &'static str,
+    pub embedding_engine: &'static str,
+    pub engine_active: &'static str,
+    pub engine_missing: &'static str,
+    pub dimensions: &'static str,
+    pub search_mode: &'static str,
+    pub mode_hybrid: &'static str,
+    pub mode_keyword: &'static str,
+    pub read_cache: &'static str,
+    pub query_cache: &'static str,
+    pub cache_hits: &'static str,
+    pub cache_entries: &'static str,
+    pub missed_queries: &'static str,
+    pub times: &'static str,
+    pub did_you_mean: &'static str,
+    pub by_type: &'static str,
+    pub by_priority: &'static str,
+    pub top_tags: &'static str,
+    pub date_range: &'static str,
+    pub embedding_coverage: &'static str,
+    pub disk_usage: &'static str,
+    pub collapsed: &'static str,
+    pub explanation: &'static str,
+}
+
+const EN: Labels = Labels {
+    found: "Found {n} memories:",
+    no_results: "No memories found matching your query.",
+    no_ids_found: "No memories found with the given IDs.",
+    ranking_variant: "ranking variant",
+    scoring_profile: "scoring profile",
+    degraded: "degraded: keyword-only ranking, query embedding unavailable",
+    score: "score",
+    id: "ID",
+    memory_type: "Type",
+    priority: "Priority",
+    tags: "Tags",
+    scores: "Scores",
+    content: "Content",
+    concepts: "Concepts",
+    fields: "Fields",
+    created: "Created",
+    expires: "Expires",
+    linked: "Linked",
+    deleted_memories: "Deleted memories:",
+    deleted_at: "was deleted at",
+    deleted_by: "by",
+    stats_title: "Memory System Stats:",
+    total_memories: "Total memories",
+    namespace: "namespace",
+    namespaces: "Namespaces",
+    cold: "cold",
+    archived: "archived",
+    indexed: "Indexed for search",
+    embedding_engine: "Embedding engine",
+    engine_active: "✓ active (BGE-m3-ko)",
+    engine_missing: "✗ not loaded",
+    dimensions: "Dimensions",
+    search_mode: "Search mode",
+    mode_hybrid: "hybrid (vector + keyword + time decay)",
+    mode_keyword: "keyword-only (BM25)",
+    read_cache: "Read cache",
+    query_cache: "Query cache",
+    cache_hits: "hits",
+    cache_entries: "entries",
+    missed_queries: "Most missed searches",
+    times: "times",
+    did_you_mean: "Did you mean",
+    by_type: "By type",
+    by_priority: "By priority",
+    top_tags: "Top tags",
+    date_range: "Created",
+    embedding_coverage: "Embedded",
+    disk_usage: "Disk usage",
+    collapsed: "more from the same source",
+    explanation: "Explain",
+};
+
+const KO: Labels = Labels {
+    found: "기억 {n}개를 찾았습니다:",
+    no_results: "검색어와 일치하는 기억이 없습니다.",
+    no_ids_found: "해당 ID의 기억이 없습니다.",
+    ranking_variant: "랭킹 변형",
+    scoring_profile: "점수 프로필",
+    degraded: "성능 저하: 쿼리 임베딩을 쓸 수 없어 키워드로만 순위를 매김",
+    score: "점수",
+    id: "ID",
+    memory_type: "유형",
+    priority: "우선순위",
+    tags: "태그",
+    scores: "점수 구성",
+    content: "내용",
+    concepts: "개념",
+    fields: "필드",
+    created: "생성",
+    expires: "만료",
+    linked: "연결",
+    deleted_memories: "삭제된 기억:",
+    deleted_at: "삭제 시각",
+    deleted_by: "삭제자",
+    stats_title: "메모리 시스템 통계:",
+    total_memories: "전체 기억",
+    namespace: "네임스페이스",
+    namespaces: "네임스페이스",
+    cold: "콜드",
+    archived: "보관",
+    indexed: "검색 색인",
+    embedding_engine: "임베딩 엔진",
+    engine_active: "✓ 사용 중 (BGE-m3-ko)",
+    engine_missing: "✗ 로드되지 않음",
+    dimensions: "차원",
+    search_mode: "검색 모드",
+    mode_hybrid: "하이브리드 (벡터 + 키워드 + 시간 감쇠)",
+    mode_keyword: "키워드 전용 (BM25)",
+    read_cache: "읽기 캐시",
+    query_cache: "검색 캐시",
+    cache_hits: "적중",
+    cache_entries: "항목",
+    missed_queries: "자주 실패한 검색",
+    times: "회",
+    did_you_mean: "혹시 이것을 찾으셨나요",
+    by_type: "유형별",
+    by_priority: "우선순위별",
+    top_tags: "주요 태그",
+    date_range: "생성 기간",
+    embedding_coverage: "임베딩됨",
+    disk_usage: "디스크 사용량",
+    collapsed: "같은 출처의 결과",
+    explanation: "점수 근거",
+};
+
+impl Labels {
+    pub fn of(language: ResponseLanguage) -> &'static Labels {
+        match language {
+            ResponseLanguage::En => &EN,
+            ResponseLanguage::Ko => &KO,
+        }
+    }
+
+    /// Labels for a tool call: its `language` argument, else `default`.
+    /// Unknown languages fall back to `default`.
+    pub fn for_call(args: &Value, default: ResponseLanguage) -> &'static Labels {
+        let language = args["language"]
+            .as_str()
+            .and_then(|s| serde_json::from_value(Value::String(s.to_lowercase())).ok())
+            .unwrap_or(default);
+        Self::of(language)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_for_call_prefers_argument() {
+        let labels = Labels::for_call(&json!({ "language": "KO" }), ResponseLanguage::En);
+        assert_eq!(labels.tags, "태그");
+        let labels = Labels::for_call(&json!({}), ResponseLanguage::Ko);
+        assert_eq!(labels.tags, "태그");
+        let labels = Labels::for_call(&json!({ "language": "fr" }), ResponseLanguage::En);
+        assert_eq!(labels.tags, "Tags");
+    }
+}
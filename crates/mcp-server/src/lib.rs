@@ -1,24 +1,62 @@
-use oc_core::Storage;
-use oc_core::models::{Memory, MemoryMetadata, MemoryType, Priority, SearchQuery};
-use oc_embeddings::EmbeddingEngine;
+use chrono::{Duration, Utc};
+use oc_core::analytics::{
+    AccessEntry, DEFAULT_ANALYTICS_DAYS, DEFAULT_ANALYTICS_LIMIT, access_analytics,
+};
+use oc_core::backup::list_backups;
+use oc_core::config::FusionStrategy;
+use oc_core::consolidate::ExtractiveSummarizer;
+use oc_core::expiry;
+use oc_core::grep::{DEFAULT_CONTEXT_CHARS, grep_pattern, grep_query};
+use oc_core::merge::{self, MergeMode, MergeRequest};
+use oc_core::models::{
+    DuplicateAction, Escalation, LinkedMemory, Memory, MemoryFilter, MemoryMetadata, MemoryPatch,
+    MemoryType, NewLink, Priority, Relation, ScoreExplanation, SearchQuery, SearchResult, TagEdit,
+    Verbosity, namespace_or_default,
+};
+use oc_core::pack::render_pack;
+use oc_core::quarantine::QuarantinePolicy;
+use oc_core::sanitize::{fence, strip_unsafe, xml_escape};
+use oc_core::{Config, Storage};
+use oc_embeddings::Embedder;
+use oc_embeddings::chunker::embed_chunks;
+use oc_observer::IngestTarget;
 use oc_search::bm25::Bm25Index;
 use oc_search::hybrid::HybridSearch;
-use oc_search::scoring::Scorer;
+use oc_search::keywords::KeywordExtractor;
+use oc_search::query_syntax;
+use oc_search::scoring::{Scorer, ScorerUpdate};
 use oc_search::vector::VectorIndex;
 use serde_json::{Value, json};
-use std::sync::{Arc, Mutex};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+pub mod admission;
+pub mod labels;
+
+use admission::{Admission, ToolClass};
+use labels::Labels;
 
 /// Shared application state for MCP server.
+///
+/// `Send + Sync` without unsafe impls: storage is a connection pool and the
+/// search indexes are behind a `Mutex`.
 pub struct McpState {
     pub storage: Arc<Storage>,
     pub search: Mutex<HybridSearch>,
-    pub embedder: Option<Arc<EmbeddingEngine>>,
+    pub embedder: Option<Arc<dyn Embedder>>,
+    pub config: Config,
+    /// Per-class concurrency and queue limits for tool calls
+    pub admission: Admission,
+    /// Client name from `initialize` (`clientInfo.name`), recorded as the
+    /// actor of deletions
+    pub client: OnceLock<String>,
+    /// Fills concepts on store (`[keywords]`); `None` when disabled
+    pub keywords: Option<KeywordExtractor>,
+    /// Screens memories on store (`[quarantine]`); `None` when disabled
+    pub quarantine: Option<QuarantinePolicy>,
 }
 
-// Safety: Storage is behind Arc, HybridSearch behind Mutex
-unsafe impl Send for McpState {}
-unsafe impl Sync for McpState {}
-
 /// Create an in-memory McpState for testing (no embedding engine, 4-dim vectors).
 pub fn test_mcp_state() -> Arc<McpState> {
     let storage = Arc::new(Storage::in_memory().unwrap());
@@ -31,36 +69,137 @@ pub fn test_mcp_state() -> Arc<McpState> {
         storage,
         search: Mutex::new(search),
         embedder: None,
+        config: Config::default(),
+        admission: Admission::default(),
+        client: OnceLock::new(),
+        keywords: Some(KeywordExtractor::new()),
+        quarantine: QuarantinePolicy::from_config(&Default::default()),
     })
 }
 
-/// Handle a JSON-RPC 2.0 request and return a JSON-RPC response.
+/// Watched files are ingested into this server's storage and indexes
+impl IngestTarget for McpState {
+    fn storage(&self) -> &Storage {
+        &self.storage
+    }
+
+    fn search(&self) -> &Mutex<HybridSearch> {
+        &self.search
+    }
+
+    fn embedder(&self) -> Option<&dyn Embedder> {
+        self.embedder.as_deref()
+    }
+
+    fn config(&self) -> &Config {
+        &self.config
+    }
+
+    fn quarantine(&self) -> Option<&QuarantinePolicy> {
+        self.quarantine.as_ref()
+    }
+}
+
+/// Most missed queries listed by `memory_stats`
+const STATS_MISSED_QUERIES: usize = 5;
+
+/// Most used tags listed by `memory_stats`
+const STATS_TOP_TAGS: usize = 10;
+
+/// Sessions returned by `session_recall` by default
+const SESSION_RECALL_LIMIT: usize = 3;
+
+/// Tag of every memory saved by `session_save`
+pub const SESSION_TAG: &str = "session";
+
+/// Tag of saved sessions that left tasks open
+pub const OPEN_TASKS_TAG: &str = "open-tasks";
+
+/// JSON-RPC 2.0 error codes
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+
+/// Handle one JSON-RPC 2.0 message: a request, a notification or a batch
+/// array of them. Returns the response to send, or `None` when there is
+/// nothing to send (a notification, or a batch of only notifications).
+/// Batched requests run concurrently; their responses keep batch order.
+pub async fn handle_message(message: &Value, state: &Arc<McpState>) -> Option<Value> {
+    let Some(batch) = message.as_array() else {
+        return handle_single(message, state).await;
+    };
+    if batch.is_empty() {
+        return Some(jsonrpc_error(Value::Null, INVALID_REQUEST, "Empty batch"));
+    }
+    let tasks: Vec<_> = batch
+        .iter()
+        .map(|request| {
+            let request = request.clone();
+            let state = Arc::clone(state);
+            tokio::spawn(async move { handle_single(&request, &state).await })
+        })
+        .collect();
+    let mut responses = Vec::new();
+    for task in tasks {
+        match task.await {
+            Ok(Some(response)) => responses.push(response),
+            Ok(None) => {}
+            Err(e) => responses.push(jsonrpc_error(
+                Value::Null,
+                INTERNAL_ERROR,
+                &format!("Request task failed: {e}"),
+            )),
+        }
+    }
+    (!responses.is_empty()).then_some(Value::Array(responses))
+}
+
+/// A request, or a notification (a request without `id`) whose response
+/// is dropped
+async fn handle_single(request: &Value, state: &Arc<McpState>) -> Option<Value> {
+    let notification = request.get("id").is_none() && request["method"].is_string();
+    let response = handle_request(request, state).await;
+    (!notification).then_some(response)
+}
+
+/// Handle a JSON-RPC 2.0 request and return a JSON-RPC response: `result`
+/// on success, else a top-level `error`. Tool failures are results with
+/// `isError`, as MCP specifies, so the agent can see and react to them.
 pub async fn handle_request(request: &Value, state: &Arc<McpState>) -> Value {
-    let method = request["method"].as_str().unwrap_or("");
-    let id = request.get("id").cloned();
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let method = match request["method"].as_str() {
+        Some(method) if request["jsonrpc"] == "2.0" => method,
+        _ => return jsonrpc_error(id, INVALID_REQUEST, "Invalid request"),
+    };
 
     let result = match method {
-        "initialize" => handle_initialize(),
-        "tools/list" => handle_tools_list(),
+        "initialize" => Ok(handle_initialize(request, state)),
+        "tools/list" => Ok(handle_tools_list()),
         "tools/call" => handle_tool_call(request, state).await,
-        _ => {
-            json!({ "error": { "code": -32601, "message": format!("Method not found: {method}") } })
-        }
+        _ => Err((METHOD_NOT_FOUND, format!("Method not found: {method}"))),
     };
 
-    let mut response = json!({
-        "jsonrpc": "2.0",
-        "result": result,
-    });
-
-    if let Some(id) = id {
-        response["id"] = id;
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => jsonrpc_error(id, code, &message),
     }
+}
 
-    response
+/// A JSON-RPC error response; `id` is null when the request's is unknown
+pub fn jsonrpc_error(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message }
+    })
 }
 
-fn handle_initialize() -> Value {
+fn handle_initialize(request: &Value, state: &Arc<McpState>) -> Value {
+    if let Some(name) = request["params"]["clientInfo"]["name"].as_str() {
+        let _ = state.client.set(name.to_string());
+    }
     json!({
         "protocolVersion": "2024-11-05",
         "capabilities": {
@@ -74,146 +213,904 @@ fn handle_initialize() -> Value {
 }
 
 fn handle_tools_list() -> Value {
-    json!({
-        "tools": [
-            {
-                "name": "memory_search",
-                "description": "Search memories using hybrid vector + keyword search. Returns ranked results with relevance scores. Use index_only=true for token-efficient browsing, then memory_get for full content.",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {
-                        "query": { "type": "string", "description": "Natural language search query" },
-                        "limit": { "type": "integer", "description": "Maximum results to return (default: 10)", "default": 10 },
-                        "index_only": { "type": "boolean", "description": "If true, return titles/metadata only (saves 90%+ tokens).", "default": false }
+    let expand_links = json!({
+        "type": "integer",
+        "description": "Also list memories linked to each result (superseded decisions, related bugfixes) as title + snippet, following links up to this many hops (max 3)",
+        "default": 0
+    });
+    let format = json!({
+        "type": "string",
+        "enum": ["text", "pack", "json"],
+        "description": "\"pack\" renders a compact <memory ...> prompt block for context injection (templates in [pack] config); \"json\" returns machine-readable results, also as structuredContent",
+        "default": "text"
+    });
+    let stats_format = json!({
+        "type": "string",
+        "enum": ["text", "json"],
+        "description": "\"json\" returns machine-readable statistics, also as structuredContent",
+        "default": "text"
+    });
+    let language = json!({
+        "type": "string",
+        "enum": ["en", "ko"],
+        "description": "Language of the output labels (default: [mcp] language config); content is not translated"
+    });
+    let namespace = json!({
+        "type": "string",
+        "description": "Project or agent namespace to scope to (default: [mcp] namespace config or OC_MEMORY_NAMESPACE; searches cover all namespaces when neither is set)"
+    });
+    // Arguments of one search, shared by memory_search and each query of
+    // memory_search_batch
+    let search_properties = json!({
+        "query": { "type": "string", "description": "Natural language search query; AND, OR, NOT, parentheses and title:, tag:, type: terms filter (type:decision tag:rust NOT deprecated)" },
+        "alt_queries": { "type": "array", "items": { "type": "string" }, "description": "Other phrasings of the query, searched in the same call and merged by rank (RRF); helps recall for vague queries" },
+        "limit": { "type": "integer", "description": "Maximum results to return (default: 10)", "default": 10 },
+        "index_only": { "type": "boolean", "description": "If true, return titles/metadata only (saves 90%+ tokens). Same as verbosity=\"index\".", "default": false },
+        "verbosity": { "type": "string", "enum": ["ids","index","snippets","full"], "description": "How much of each memory to return: ids and scores, titles/metadata, plus a one-line content snippet, or everything (default: [search] verbosity, per client in [search.client_verbosity])" },
+        "tags_all": { "type": "array", "items": { "type": "string" }, "description": "Only return memories carrying all of these tags (case-insensitive)" },
+        "tags_any": { "type": "array", "items": { "type": "string" }, "description": "Only return memories carrying at least one of these tags (case-insensitive)" },
+        "memory_type": { "type": "string", "enum": ["observation","decision","preference","fact","task","session","bugfix","discovery"], "description": "Only return memories of this type" },
+        "exclude_tags": { "type": "array", "items": { "type": "string" }, "description": "Leave out memories carrying any of these tags, e.g. [\"session-log\"]" },
+        "exclude_types": { "type": "array", "items": { "type": "string", "enum": ["observation","decision","preference","fact","task","session","bugfix","discovery"] }, "description": "Leave out memories of these types, e.g. [\"session\"] to search everything except session logs" },
+        "profile": { "type": "string", "description": "Scoring profile from [search.profiles] (default: chosen by tag/type, else the [search] weights)" },
+        "expand_links": expand_links.clone(),
+        "include_cold": { "type": "boolean", "description": "Also search memories moved to the cold tier (long unused)", "default": false },
+        "include_archived": { "type": "boolean", "description": "Also search memories archived with memory_archive", "default": false },
+        "collapse_sources": { "type": "boolean", "description": "Show only the best match of each source (e.g. one chunk per document), with the rest counted; false lists every match (default: [search] collapse_sources)" },
+        "filters": { "type": "array", "items": { "type": "string" }, "description": "Conditions on typed fields registered in [fields] config, e.g. \"sprint >= 42\", \"billable = true\", \"due < 2026-01-01\" (dates in the [display] timezone); operators = != < <= > >=" },
+        "concepts": { "type": "array", "items": { "type": "string" }, "description": "Only return memories carrying all of these concepts (case-insensitive)" },
+        "exact": { "type": "boolean", "description": "Match the whole query as one phrase, e.g. an error message verbatim, and return only memories containing it. Quoted parts of a normal query (\"메모리 안전성\") are matched as phrases too.", "default": false },
+        "explain": { "type": "boolean", "description": "Show what went into each score: raw cosine similarity and BM25 score, rank in each list, the weights applied and days since access. For tuning [search] weights.", "default": false },
+        "namespace": namespace.clone()
+    });
+    let mut single_search = search_properties.clone();
+    single_search["format"] = format.clone();
+    single_search["language"] = language.clone();
+    // One json! per tool: a single literal for the whole list exceeds the
+    // macro recursion limit
+    let tools = vec![
+        json!({
+            "name": "memory_search",
+            "description": "Search memories using hybrid vector + keyword search. Returns ranked results with relevance scores. Use verbosity=\"index\" (or index_only=true) for token-efficient browsing, then memory_get for full content.",
+            "inputSchema": {
+                "type": "object",
+                "properties": single_search,
+                "required": ["query"]
+            }
+        }),
+        json!({
+            "name": "memory_search_batch",
+            "description": "Run several searches in one call, e.g. one per sub-question: the queries are embedded together and searched in parallel, and results come back grouped by query. Cheaper than separate memory_search calls.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "queries": {
+                        "type": "array",
+                        "description": "Searches to run (at most [search] max_batch_queries), each taking the arguments of memory_search",
+                        "items": { "type": "object", "properties": search_properties, "required": ["query"] }
                     },
-                    "required": ["query"]
+                    "namespace": namespace.clone(),
+                    "format": { "type": "string", "enum": ["text", "json"], "description": "Output format: readable text, or JSON grouped by query", "default": "text" },
+                    "language": language.clone()
+                },
+                "required": ["queries"]
+            }
+        }),
+        json!({
+            "name": "memory_store",
+            "description": "Store a new memory (observation, decision, preference, fact, task, etc.)",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "content": { "type": "string", "description": "Memory content to store" },
+                    "title": { "type": "string", "description": "Short title (max 10 words)" },
+                    "memory_type": { "type": "string", "enum": ["observation","decision","preference","fact","task","session","bugfix","discovery"], "default": "observation" },
+                    "priority": { "type": "string", "enum": ["low","medium","high"], "default": "medium" },
+                    "tags": { "type": "array", "items": { "type": "string" } },
+                    "fields": { "type": "object", "description": "Values of typed fields registered in [fields] config (numbers, booleans, or dates as YYYY-MM-DD / RFC 3339)" },
+                    "concepts": { "type": "array", "items": { "type": "string" }, "description": "Key concepts; extracted from the text when omitted" },
+                    "ttl_hours": { "type": "integer", "description": "Expire the memory this many hours from now (for tasks and session notes); expired memories are archived or deleted per [expiry] config" },
+                    "namespace": namespace.clone(),
+                    "links": {
+                        "type": "array",
+                        "description": "Links from the new memory to existing ones, created together with it (nothing is stored when a target does not exist). Read as: new memory <relation> target_id",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "target_id": { "type": "string" },
+                                "relation": { "type": "string", "enum": ["supersedes","relates_to","caused_by","part_of"] }
+                            },
+                            "required": ["target_id", "relation"]
+                        }
+                    }
+                },
+                "required": ["content", "title"]
+            }
+        }),
+        json!({
+            "name": "memory_related",
+            "description": "Find memories similar to a given one (\"what else do I know about this?\"), using its stored embedding plus its title and concepts",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "id": { "type": "string", "description": "Memory ID" },
+                    "limit": { "type": "integer", "default": 5 }
+                },
+                "required": ["id"]
+            }
+        }),
+        json!({
+            "name": "memory_grep",
+            "description": "Search inside one memory's content and return only the matching passages with character offsets, instead of the whole (possibly very long) content. Pass a literal `pattern` (case-insensitive), or a `query` whose words rank passages by how many they contain.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "id": { "type": "string", "description": "Memory ID" },
+                    "pattern": { "type": "string", "description": "Literal text to find (case-insensitive)" },
+                    "query": { "type": "string", "description": "Words to find; passages containing more of them come first" },
+                    "context": { "type": "integer", "description": "Characters of context on each side of a match", "default": DEFAULT_CONTEXT_CHARS },
+                    "limit": { "type": "integer", "description": "Maximum passages to return", "default": 5 }
+                },
+                "required": ["id"]
+            }
+        }),
+        json!({
+            "name": "memory_get",
+            "description": "Get full content of specific memories by ID.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "ids": { "type": "array", "items": { "type": "string" }, "description": "Memory IDs to retrieve" },
+                    "expand_links": expand_links,
+                    "format": format,
+                    "language": language.clone()
+                },
+                "required": ["ids"]
+            }
+        }),
+        json!({
+            "name": "memory_delete",
+            "description": "Delete a memory by ID",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "id": { "type": "string", "description": "Memory ID to delete" }
+                },
+                "required": ["id"]
+            }
+        }),
+        json!({
+            "name": "memory_archive",
+            "description": "Archive a memory: it stays stored and readable with memory_get, but searches (unless include_archived) and stats leave it out. Softer than memory_delete.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "id": { "type": "string", "description": "Memory ID to archive" }
+                },
+                "required": ["id"]
+            }
+        }),
+        json!({
+            "name": "memory_unarchive",
+            "description": "Bring an archived memory back into searches and stats",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "id": { "type": "string", "description": "Memory ID to unarchive" }
+                },
+                "required": ["id"]
+            }
+        }),
+        json!({
+            "name": "memory_update",
+            "description": "Change title, type, priority or tags of a memory without re-embedding its content. Only the given fields change.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "id": { "type": "string", "description": "Memory ID to update" },
+                    "title": { "type": "string" },
+                    "memory_type": { "type": "string", "enum": ["observation","decision","preference","fact","task","session","bugfix","discovery"] },
+                    "priority": { "type": "string", "enum": ["low","medium","high"] },
+                    "tags": { "type": "array", "items": { "type": "string" }, "description": "Replaces the existing tags" }
+                },
+                "required": ["id"]
+            }
+        }),
+        json!({
+            "name": "memory_retag",
+            "description": "Add and remove tags on every memory matching a filter, in one transaction. At least one filter field is required.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "Case-insensitive text the title or content must contain" },
+                    "memory_type": { "type": "string", "enum": ["observation","decision","preference","fact","task","session","bugfix","discovery"] },
+                    "created_after": { "type": "string", "description": "Only memories created at or after it: YYYY-MM-DD or YYYY-MM-DD HH:MM in the [display] timezone, or an RFC 3339 timestamp" },
+                    "created_before": { "type": "string", "description": "Only memories created before it: YYYY-MM-DD or YYYY-MM-DD HH:MM in the [display] timezone, or an RFC 3339 timestamp" },
+                    "add": { "type": "array", "items": { "type": "string" }, "description": "Tags to add" },
+                    "remove": { "type": "array", "items": { "type": "string" }, "description": "Tags to remove" }
                 }
-            },
-            {
-                "name": "memory_store",
-                "description": "Store a new memory (observation, decision, preference, fact, task, etc.)",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {
-                        "content": { "type": "string", "description": "Memory content to store" },
-                        "title": { "type": "string", "description": "Short title (max 10 words)" },
-                        "memory_type": { "type": "string", "enum": ["observation","decision","preference","fact","task","session","bugfix","discovery"], "default": "observation" },
-                        "priority": { "type": "string", "enum": ["low","medium","high"], "default": "medium" },
-                        "tags": { "type": "array", "items": { "type": "string" } }
-                    },
-                    "required": ["content", "title"]
+            }
+        }),
+        json!({
+            "name": "memory_feedback",
+            "description": "After finishing a task, mark which retrieved memories were actually useful (and which were not). Useful memories rank higher in later searches.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "useful": { "type": "array", "items": { "type": "string" }, "description": "IDs of memories that helped" },
+                    "not_useful": { "type": "array", "items": { "type": "string" }, "description": "IDs of retrieved memories that did not help" }
                 }
-            },
-            {
-                "name": "memory_get",
-                "description": "Get full content of specific memories by ID.",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {
-                        "ids": { "type": "array", "items": { "type": "string" }, "description": "Memory IDs to retrieve" }
-                    },
-                    "required": ["ids"]
+            }
+        }),
+        json!({
+            "name": "memory_link",
+            "description": "Record how two memories relate, e.g. a decision that supersedes an older one or a fix caused by a bug. Pass remove to delete the link instead.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "from_id": { "type": "string" },
+                    "to_id": { "type": "string" },
+                    "relation": { "type": "string", "enum": ["supersedes","relates_to","caused_by","part_of"], "description": "Read as: from_id <relation> to_id" },
+                    "remove": { "type": "boolean", "description": "Delete the link (all relations between the two when relation is omitted)", "default": false }
+                },
+                "required": ["from_id", "to_id"]
+            }
+        }),
+        json!({
+            "name": "memory_merge",
+            "description": "Merge duplicate memories into one: union of tags and files, the highest priority and the earliest created_at. The originals stay, each superseded by the merged memory.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "ids": { "type": "array", "items": { "type": "string" }, "description": "IDs of the memories to merge (two or more)" },
+                    "mode": { "type": "string", "enum": ["concatenate","summarize"], "description": "Join the contents, or write a dated summary of them", "default": "concatenate" },
+                    "title": { "type": "string", "description": "Title of the merged memory (default: the oldest original's)" }
+                },
+                "required": ["ids"]
+            }
+        }),
+        json!({
+            "name": "memory_links_get",
+            "description": "List the links of a memory in both directions, with the linked memories' titles",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "id": { "type": "string", "description": "Memory ID" }
+                },
+                "required": ["id"]
+            }
+        }),
+        json!({
+            "name": "memory_stats",
+            "description": "Get memory system statistics",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "namespace": namespace,
+                    "include_archived": { "type": "boolean", "description": "Count archived memories in the totals and breakdown too", "default": false },
+                    "format": stats_format,
+                    "language": language
                 }
-            },
-            {
-                "name": "memory_delete",
-                "description": "Delete a memory by ID",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {
-                        "id": { "type": "string", "description": "Memory ID to delete" }
-                    },
-                    "required": ["id"]
+            }
+        }),
+        json!({
+            "name": "memory_analytics",
+            "description": "Access analytics: most and least accessed memories, memories never retrieved since they were stored, accesses per day and the average recency of search hits",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "limit": { "type": "integer", "description": "Memories per ranking", "default": DEFAULT_ANALYTICS_LIMIT },
+                    "days": { "type": "integer", "description": "Days of daily accesses, at most 90", "default": DEFAULT_ANALYTICS_DAYS },
+                    "namespace": namespace.clone(),
+                    "format": { "type": "string", "enum": ["text","json"], "description": "Output format (default: text)" }
                 }
-            },
-            {
-                "name": "memory_stats",
-                "description": "Get memory system statistics",
-                "inputSchema": { "type": "object", "properties": {} }
             }
-        ]
-    })
+        }),
+        json!({
+            "name": "session_save",
+            "description": "At the end of a session, save what it covered for the next one: a summary, the topics, decisions made and tasks left open. Stored as a session memory tagged \"session\" (and \"open-tasks\" when tasks remain).",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "summary": { "type": "string", "description": "What the session was about, in a few sentences" },
+                    "title": { "type": "string", "description": "Short title (default: \"Session\" and the current time)" },
+                    "topics": { "type": "array", "items": { "type": "string" }, "description": "Topics discussed; also stored as concepts" },
+                    "decisions": { "type": "array", "items": { "type": "string" }, "description": "Decisions made" },
+                    "open_tasks": { "type": "array", "items": { "type": "string" }, "description": "Tasks left for a later session" },
+                    "tags": { "type": "array", "items": { "type": "string" }, "description": "Tags added to the standard ones" },
+                    "namespace": namespace.clone()
+                }
+            }
+        }),
+        json!({
+            "name": "session_recall",
+            "description": "Recall the most recent saved sessions (newest first) to continue where the last one left off",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "limit": { "type": "integer", "description": "Number of sessions to return", "default": SESSION_RECALL_LIMIT },
+                    "namespace": namespace.clone(),
+                    "format": { "type": "string", "enum": ["text","json"], "description": "Output format (default: text)" }
+                }
+            }
+        }),
+        json!({
+            "name": "memory_health",
+            "description": "Machine-readable health summary: memory counts, degraded components, memories awaiting an embedding, last backup time and whether the indexes match storage",
+            "inputSchema": { "type": "object", "properties": {} }
+        }),
+        json!({
+            "name": "memory_tune",
+            "description": "Show or adjust the live search scoring weights without restarting. Call with no arguments to see the current weights.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "semantic_weight": { "type": "number", "description": "Weight for vector similarity (0-1)" },
+                    "keyword_weight": { "type": "number", "description": "Weight for BM25 keyword score (0-1)" },
+                    "recency_weight": { "type": "number", "description": "Weight for recency decay (0-1)" },
+                    "importance_weight": { "type": "number", "description": "Weight for priority (0-1)" },
+                    "access_weight": { "type": "number", "description": "Weight for the boost of often accessed memories (0-1)" },
+                    "half_life_days": { "type": "number", "description": "Recency half-life in days (> 0)" },
+                    "persist": { "type": "boolean", "description": "Also write the weights to config.toml", "default": false }
+                }
+            }
+        }),
+    ];
+    json!({ "tools": tools })
 }
 
-async fn handle_tool_call(request: &Value, state: &Arc<McpState>) -> Value {
-    let tool_name = request["params"]["name"].as_str().unwrap_or("");
-    let arguments = &request["params"]["arguments"];
+/// The tool's result, or an invalid-params error for an unknown tool
+async fn handle_tool_call(request: &Value, state: &Arc<McpState>) -> Result<Value, (i64, String)> {
+    let Some(tool_name) = request["params"]["name"].as_str().map(str::to_string) else {
+        return Err((INVALID_PARAMS, "Missing tool name".to_string()));
+    };
+    let Some(class) = ToolClass::of(&tool_name) else {
+        return Err((INVALID_PARAMS, format!("Unknown tool: {tool_name}")));
+    };
 
+    let _permit = match state.admission.admit(class).await {
+        Ok(permit) => permit,
+        Err(busy) => {
+            tracing::warn!(tool = %tool_name, "{busy}");
+            return Ok(mcp_error(&busy.to_string()));
+        }
+    };
+
+    // Tools block on SQLite, tantivy and ONNX; keep them off the async workers
+    let arguments = request["params"]["arguments"].clone();
+    let state = Arc::clone(state);
+    Ok(
+        tokio::task::spawn_blocking(move || dispatch_tool(&tool_name, &arguments, &state))
+            .await
+            .unwrap_or_else(|e| mcp_error(&format!("Tool task failed: {e}"))),
+    )
+}
+
+fn dispatch_tool(tool_name: &str, arguments: &Value, state: &Arc<McpState>) -> Value {
     match tool_name {
         "memory_search" => tool_memory_search(arguments, state),
+        "memory_search_batch" => tool_memory_search_batch(arguments, state),
         "memory_store" => tool_memory_store(arguments, state),
         "memory_get" => tool_memory_get(arguments, state),
+        "memory_grep" => tool_memory_grep(arguments, state),
+        "memory_related" => tool_memory_related(arguments, state),
+        "memory_update" => tool_memory_update(arguments, state),
         "memory_delete" => tool_memory_delete(arguments, state),
-        "memory_stats" => tool_memory_stats(state),
-        _ => json!({
-            "content": [{ "type": "text", "text": format!("Unknown tool: {tool_name}") }],
-            "isError": true
-        }),
+        "memory_archive" => tool_memory_archive(arguments, state, true),
+        "memory_unarchive" => tool_memory_archive(arguments, state, false),
+        "memory_retag" => tool_memory_retag(arguments, state),
+        "memory_feedback" => tool_memory_feedback(arguments, state),
+        "memory_link" => tool_memory_link(arguments, state),
+        "memory_links_get" => tool_memory_links_get(arguments, state),
+        "memory_merge" => tool_memory_merge(arguments, state),
+        "memory_stats" => tool_memory_stats(arguments, state),
+        "memory_analytics" => tool_memory_analytics(arguments, state),
+        "memory_health" => tool_memory_health(state),
+        "memory_tune" => tool_memory_tune(arguments, state),
+        "session_save" => tool_session_save(arguments, state),
+        "session_recall" => tool_session_recall(arguments, state),
+        _ => mcp_error(&format!("Unknown tool: {tool_name}")),
     }
 }
 
-fn tool_memory_search(args: &Value, state: &Arc<McpState>) -> Value {
+/// The call's `namespace` argument, else the configured default
+fn namespace_arg<'a>(args: &'a Value, state: &'a McpState) -> Option<&'a str> {
+    args["namespace"]
+        .as_str()
+        .map(str::trim)
+        .filter(|ns| !ns.is_empty())
+        .or(state.config.mcp.namespace.as_deref())
+}
+
+fn string_list(value: &Value) -> Option<Vec<String>> {
+    value.as_array().map(|arr| {
+        arr.iter()
+            .filter_map(|v| v.as_str().map(|s| s.to_string()))
+            .collect()
+    })
+}
+
+/// The `links` argument of `memory_store`; empty when absent
+fn link_list(value: &Value) -> Result<Vec<NewLink>, String> {
+    let Some(items) = value.as_array() else {
+        return Ok(Vec::new());
+    };
+    items
+        .iter()
+        .map(|item| -> Result<NewLink, String> {
+            let target_id = item["target_id"]
+                .as_str()
+                .filter(|id| !id.is_empty())
+                .ok_or("every link needs a target_id")?;
+            let relation = item["relation"].as_str().unwrap_or_default();
+            let relation =
+                serde_json::from_str::<Relation>(&format!("\"{relation}\"")).map_err(|_| {
+                    format!(
+                        "unknown relation '{relation}': use supersedes, relates_to, caused_by or part_of"
+                    )
+                })?;
+            Ok(NewLink {
+                target_id: target_id.to_string(),
+                relation,
+            })
+        })
+        .collect()
+}
+
+/// The search `args` of `memory_search` ask for and how much of each
+/// result to return
+fn search_args(args: &Value, state: &McpState) -> Result<(SearchQuery, Verbosity), String> {
     let query_text = args["query"].as_str().unwrap_or("");
+    let alt_queries = string_list(&args["alt_queries"]).unwrap_or_default();
     let limit = args["limit"].as_u64().unwrap_or(10) as usize;
-    let index_only = args["index_only"].as_bool().unwrap_or(false);
+    let verbosity = search_verbosity(args, state)?;
+    let tags_all = string_list(&args["tags_all"]).or_else(|| string_list(&args["tags"]));
+    let tags_any = string_list(&args["tags_any"]);
+    let exclude_tags = string_list(&args["exclude_tags"]).unwrap_or_default();
+    let exclude_types = string_list(&args["exclude_types"])
+        .unwrap_or_default()
+        .iter()
+        .map(|s| {
+            serde_json::from_str::<MemoryType>(&format!("\"{s}\""))
+                .map_err(|_| format!("Unknown memory type in exclude_types: {s}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let memory_type = args["memory_type"]
+        .as_str()
+        .and_then(|s| serde_json::from_str::<MemoryType>(&format!("\"{s}\"")).ok());
+    let profile = args["profile"].as_str().map(str::to_string);
+    let expand_links = args["expand_links"].as_u64().unwrap_or(0) as usize;
+    let include_cold = args["include_cold"].as_bool().unwrap_or(false);
+    let include_archived = args["include_archived"].as_bool().unwrap_or(false);
+    let exact = args["exact"].as_bool().unwrap_or(false);
+    let explain = args["explain"].as_bool().unwrap_or(false);
+    let collapse_sources = args["collapse_sources"]
+        .as_bool()
+        .unwrap_or(state.config.search.collapse_sources);
+    let filters = string_list(&args["filters"]).unwrap_or_default();
+    let concepts = string_list(&args["concepts"]).unwrap_or_default();
+    let fields = state
+        .config
+        .field_registry()
+        .parse_filters(&filters)
+        .map_err(|e| format!("Invalid filter: {e}"))?;
 
     if query_text.is_empty() {
-        return mcp_error("Query cannot be empty");
+        return Err("Query cannot be empty".to_string());
     }
 
-    let search_query = SearchQuery {
+    let mut search_query = SearchQuery {
         query: query_text.to_string(),
+        alt_queries,
         limit,
-        index_only,
+        tags_all,
+        tags_any,
+        exclude_tags,
+        exclude_types,
+        memory_type,
+        index_only: !verbosity.includes_content(),
+        profile,
+        expand_links,
+        include_cold,
+        include_archived,
+        fields,
+        concepts,
+        namespace: namespace_arg(args, state).map(str::to_string),
+        collapse_sources,
+        exact,
+        explain,
         ..Default::default()
     };
+    query_syntax::apply(&mut search_query).map_err(|e| format!("Invalid query: {e}"))?;
+    Ok((search_query, verbosity))
+}
+
+fn tool_memory_search(args: &Value, state: &Arc<McpState>) -> Value {
+    let format = match output_format(args) {
+        Ok(format) => format,
+        Err(e) => return mcp_error(&e),
+    };
+    let (search_query, verbosity) = match search_args(args, state) {
+        Ok(query) => query,
+        Err(e) => return mcp_error(&e),
+    };
 
-    let query_embedding = state
-        .embedder
-        .as_ref()
-        .and_then(|e| match e.embed(query_text) {
-            Ok(emb) => Some(emb),
-            Err(err) => {
+    // Every formulation of the query is embedded in one batch
+    let texts: Vec<&str> = search_query.texts().collect();
+    let query_embeddings = match state.embedder.as_ref().map(|e| e.embed_batch(&texts)) {
+        Some(Ok(embeddings)) => embeddings,
+        // A saturated embedder asks the client to back off rather than
+        // quietly answering keyword-only
+        Some(Err(err)) => match err.retry_after_ms() {
+            Some(retry_after_ms) => return mcp_busy(retry_after_ms),
+            None => {
                 tracing::warn!("Embedding failed: {err}, falling back to keyword-only");
-                None
+                Vec::new()
             }
-        });
+        },
+        None => Vec::new(),
+    };
 
-    let empty_embedding = vec![0f32; state.embedder.as_ref().map_or(1024, |e| e.dimensions())];
-    let embedding_ref = query_embedding.as_deref().unwrap_or(&empty_embedding);
+    // Without an embedding the search runs keyword-only and marks results degraded
+    let embedding_refs: Vec<&[f32]> = query_embeddings.iter().map(Vec::as_slice).collect();
 
-    let search = match state.search.lock() {
+    let mut search = match state.search.lock() {
         Ok(s) => s,
         Err(e) => return mcp_error(&format!("Search lock error: {e}")),
     };
 
-    match search.search(embedding_ref, &search_query) {
-        Ok(results) => {
+    let labels = Labels::for_call(args, state.config.mcp.language);
+    match search.search_fused(&embedding_refs, &search_query) {
+        Ok(mut results) => {
+            if let Err(e) = search.record_miss(&search_query, &results, &state.config.misses) {
+                tracing::warn!("Failed to record missed query: {e}");
+            }
+            for result in &mut results {
+                verbosity.shape(result);
+            }
+            let suggestions = search
+                .suggest(&search_query.query, state.config.search.suggestions)
+                .unwrap_or_else(|e| {
+                    tracing::warn!("Failed to suggest corrections: {e}");
+                    Vec::new()
+                });
+            if format == Format::Json {
+                let results: Vec<Value> = results
+                    .into_iter()
+                    .map(|result| result_json(result, verbosity))
+                    .collect();
+                return mcp_json(&json!({ "results": results, "suggestions": suggestions }));
+            }
+            let safe = state.config.display.safe_content;
+            let did_you_mean = describe_suggestions(&suggestions, labels, safe);
             if results.is_empty() {
-                return mcp_text("No memories found matching your query.");
+                return mcp_text(&format!("{}{did_you_mean}", labels.no_results));
             }
-            let mut output = format!("Found {} memories:\n\n", results.len());
-            for (i, result) in results.iter().enumerate() {
-                let m = &result.memory;
-                let bd = &result.score_breakdown;
-                output.push_str(&format!(
-                    "{}. **{}** (score: {:.3})\n   ID: {}\n   Type: {} | Priority: {:?} | Tags: {}\n   Scores: sem={:.2} kw={:.2} rec={:.2} imp={:.2}\n",
-                    i + 1, m.title, result.score, m.id,
-                    m.metadata.memory_type.as_str(), m.metadata.priority,
-                    m.metadata.tags.join(", "),
-                    bd.semantic, bd.keyword, bd.recency, bd.importance,
+            if format == Format::Pack {
+                let memories: Vec<Memory> = results.into_iter().map(|r| r.memory).collect();
+                return mcp_text(&render_pack(
+                    &memories,
+                    &state.config.pack,
+                    Utc::now(),
+                    &state.config.display,
                 ));
-                if !index_only && !m.content.is_empty() {
-                    output.push_str(&format!("   Content: {}\n", m.content));
-                }
-                output.push('\n');
             }
+            let mut output = format!(
+                "{}\n",
+                labels.found.replace("{n}", &results.len().to_string())
+            );
+            if let Some(variant) = &results[0].variant {
+                output.push_str(&format!("({}: {variant})\n", labels.ranking_variant));
+            }
+            if let Some(profile) = &results[0].profile {
+                output.push_str(&format!("({}: {profile})\n", labels.scoring_profile));
+            }
+            if results[0].degraded {
+                output.push_str(&format!("({})\n", labels.degraded));
+            }
+            output.push_str(&did_you_mean);
+            output.push('\n');
+            output.push_str(&describe_results(&results, verbosity, labels, safe));
             mcp_text(&output)
         }
         Err(e) => mcp_error(&format!("Search failed: {e}")),
     }
 }
 
+/// Several `memory_search` calls in one: every formulation of every query
+/// is embedded in one batch, then the searches run in parallel
+fn tool_memory_search_batch(args: &Value, state: &Arc<McpState>) -> Value {
+    let format = match output_format(args) {
+        Ok(Format::Pack) => return mcp_error("format pack is not supported for batch searches"),
+        Ok(format) => format,
+        Err(e) => return mcp_error(&e),
+    };
+    let items = args["queries"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+    let max = state.config.search.max_batch_queries;
+    if items.is_empty() || items.len() > max {
+        return mcp_error(&format!(
+            "A batch takes 1 to {max} queries, got {}",
+            items.len()
+        ));
+    }
+    let mut queries = Vec::with_capacity(items.len());
+    for (i, item) in items.iter().enumerate() {
+        // Calls without their own namespace inherit the batch's
+        let mut item = item.clone();
+        if item["namespace"].is_null() && !args["namespace"].is_null() {
+            item["namespace"] = args["namespace"].clone();
+        }
+        match search_args(&item, state) {
+            Ok(query) => queries.push(query),
+            Err(e) => return mcp_error(&format!("Query {}: {e}", i + 1)),
+        }
+    }
+
+    let texts: Vec<&str> = queries.iter().flat_map(|(q, _)| q.texts()).collect();
+    let query_embeddings = match state.embedder.as_ref().map(|e| e.embed_batch(&texts)) {
+        Some(Ok(embeddings)) => embeddings,
+        Some(Err(err)) => match err.retry_after_ms() {
+            Some(retry_after_ms) => return mcp_busy(retry_after_ms),
+            None => {
+                tracing::warn!("Embedding failed: {err}, falling back to keyword-only");
+                Vec::new()
+            }
+        },
+        None => Vec::new(),
+    };
+    // Hand each query the embeddings of its own formulations
+    let mut embeddings = query_embeddings.iter().map(Vec::as_slice);
+    let batch: Vec<(Vec<&[f32]>, SearchQuery)> = queries
+        .iter()
+        .map(|(query, _)| {
+            let embs = embeddings.by_ref().take(query.texts().count()).collect();
+            (embs, query.clone())
+        })
+        .collect();
+
+    let mut search = match state.search.lock() {
+        Ok(s) => s,
+        Err(e) => return mcp_error(&format!("Search lock error: {e}")),
+    };
+    let outcomes = search.search_batch(&batch);
+
+    let labels = Labels::for_call(args, state.config.mcp.language);
+    let safe = state.config.display.safe_content;
+    let mut groups = Vec::with_capacity(items.len());
+    let mut output = String::new();
+    for ((outcome, (query, verbosity)), item) in outcomes.into_iter().zip(&queries).zip(items) {
+        let mut results = match outcome {
+            Ok(results) => results,
+            Err(e) => return mcp_error(&format!("Search failed: {e}")),
+        };
+        if let Err(e) = search.record_miss(query, &results, &state.config.misses) {
+            tracing::warn!("Failed to record missed query: {e}");
+        }
+        for result in &mut results {
+            verbosity.shape(result);
+        }
+        let sent = item["query"].as_str().unwrap_or_default();
+        if format == Format::Json {
+            let results: Vec<Value> = results
+                .into_iter()
+                .map(|result| result_json(result, *verbosity))
+                .collect();
+            groups.push(json!({ "query": sent, "results": results }));
+            continue;
+        }
+        output.push_str(&format!("## {}\n", shown_text(sent, safe)));
+        if results.is_empty() {
+            output.push_str(&format!("{}\n\n", labels.no_results));
+            continue;
+        }
+        output.push_str(&format!(
+            "{}\n\n",
+            labels.found.replace("{n}", &results.len().to_string())
+        ));
+        output.push_str(&describe_results(&results, *verbosity, labels, safe));
+    }
+    if format == Format::Json {
+        return mcp_json(&json!({ "results": groups }));
+    }
+    mcp_text(&output)
+}
+
+/// A search result as `format: "json"` returns it: id and score only at
+/// `verbosity: "ids"`
+fn result_json(mut result: SearchResult, verbosity: Verbosity) -> Value {
+    if verbosity == Verbosity::Ids {
+        return json!({ "id": result.memory.id, "score": result.score });
+    }
+    result.memory.embedding = None;
+    json!(result)
+}
+
+/// Numbered lines of search results, as much of each as `verbosity` keeps
+fn describe_results(
+    results: &[SearchResult],
+    verbosity: Verbosity,
+    labels: &Labels,
+    safe: bool,
+) -> String {
+    let mut output = String::new();
+    for (i, result) in results.iter().enumerate() {
+        let m = &result.memory;
+        if verbosity == Verbosity::Ids {
+            output.push_str(&format!(
+                "{}. {} ({}: {:.3})\n",
+                i + 1,
+                m.id,
+                labels.score,
+                result.score
+            ));
+            continue;
+        }
+        let bd = &result.score_breakdown;
+        output.push_str(&format!(
+            "{}. **{}** ({}: {:.3})\n   {}: {}\n   {}: {} | {}: {:?} | {}: {}\n   {}: sem={:.2} kw={:.2} rec={:.2} imp={:.2} acc={:.2}\n",
+            i + 1, shown_text(&m.title, safe), labels.score, result.score, labels.id, m.id,
+            labels.memory_type, m.metadata.memory_type.as_str(),
+            labels.priority, m.metadata.priority,
+            labels.tags, m.metadata.tags.join(", "),
+            labels.scores, bd.semantic, bd.keyword, bd.recency, bd.importance, bd.access,
+        ));
+        if let Some(explanation) = &result.explanation {
+            output.push_str(&format!(
+                "   {}: {}\n",
+                labels.explanation,
+                describe_explanation(explanation)
+            ));
+        }
+        if verbosity.includes_content() && !m.content.is_empty() {
+            let content = if safe {
+                format!("\n{}", fence(&m.content))
+            } else {
+                format!(" {}", m.content)
+            };
+            output.push_str(&format!("   {}:{content}\n", labels.content));
+        }
+        if let (n @ 1.., Some(source)) = (result.collapsed_count, &m.metadata.source) {
+            output.push_str(&format!(
+                "   +{n} {}: {}\n",
+                labels.collapsed,
+                shown_text(source, safe)
+            ));
+        }
+        output.push_str(&describe_linked(&result.linked, safe));
+        output.push('\n');
+    }
+    output
+}
+
+/// One line of raw score inputs, e.g. `cos=0.812 bm25=3.40 rank=v2/k1
+/// days=3.5 access=4 | w sem=0.50 kw=0.30 ... half-life=30d weighted_sum`,
+/// with `-` for a list the memory was not in
+fn describe_explanation(e: &ScoreExplanation) -> String {
+    let score = |v: Option<f32>, precision: usize| {
+        v.map_or("-".to_string(), |v| format!("{v:.precision$}"))
+    };
+    let rank = |r: Option<usize>| r.map_or("-".to_string(), |r| r.to_string());
+    let fusion = match e.fusion {
+        FusionStrategy::WeightedSum => "weighted_sum".to_string(),
+        FusionStrategy::Rrf => format!("rrf k={}", e.rrf_k),
+    };
+    let w = &e.weights;
+    format!(
+        "cos={} bm25={} rank=v{}/k{} days={:.1} access={} | w sem={:.2} kw={:.2} rec={:.2} imp={:.2} acc={:.2} half-life={}d {fusion}",
+        score(e.cosine, 3),
+        score(e.bm25, 2),
+        rank(e.vector_rank),
+        rank(e.keyword_rank),
+        e.days_since_access,
+        e.access_count,
+        w.semantic,
+        w.keyword,
+        w.recency,
+        w.importance,
+        w.access,
+        e.half_life_days,
+    )
+}
+
+/// "Did you mean" line listing corrections of the query, or nothing
+fn describe_suggestions(suggestions: &[String], labels: &Labels, safe: bool) -> String {
+    if suggestions.is_empty() {
+        return String::new();
+    }
+    let quoted: Vec<String> = suggestions
+        .iter()
+        .map(|s| format!("\"{}\"", shown_text(s, safe)))
+        .collect();
+    format!("\n{}: {}?\n", labels.did_you_mean, quoted.join(", "))
+}
+
+/// Verbosity of a memory_search call: the `verbosity` argument, else
+/// `index_only`, else the configured default for the calling client
+fn search_verbosity(args: &Value, state: &McpState) -> Result<Verbosity, String> {
+    match args["verbosity"].as_str() {
+        Some(level) => serde_json::from_value(json!(level)).map_err(|_| {
+            format!("Unknown verbosity: {level} (expected ids, index, snippets or full)")
+        }),
+        None if args["index_only"].as_bool() == Some(true) => Ok(Verbosity::Index),
+        None => Ok(state
+            .config
+            .search
+            .verbosity_for(state.client.get().map(String::as_str))),
+    }
+}
+
+fn tool_memory_related(args: &Value, state: &Arc<McpState>) -> Value {
+    let id = match args["id"].as_str() {
+        Some(id) if !id.is_empty() => id,
+        _ => return mcp_error("id is required"),
+    };
+    let limit = args["limit"].as_u64().unwrap_or(5) as usize;
+    let memory = match state.storage.get(id) {
+        Ok(Some(memory)) => memory,
+        Ok(None) => return mcp_error(&format!("Memory {id} not found")),
+        Err(e) => return mcp_error(&format!("Failed to load memory: {e}")),
+    };
+    let results = match state.search.lock() {
+        Ok(search) => search.related(&memory, limit),
+        Err(e) => return mcp_error(&format!("Search lock error: {e}")),
+    };
+    let results = match results {
+        Ok(results) => results,
+        Err(e) => return mcp_error(&format!("Search failed: {e}")),
+    };
+    let safe = state.config.display.safe_content;
+    if results.is_empty() {
+        return mcp_text(&format!(
+            "No memories related to **{}**.",
+            shown_text(&memory.title, safe)
+        ));
+    }
+
+    let mut output = format!("Related to **{}**:\n\n", shown_text(&memory.title, safe));
+    for (i, result) in results.iter().enumerate() {
+        let m = &result.memory;
+        output.push_str(&format!(
+            "{}. **{}** (score: {:.3})\n   ID: {} | Type: {}\n",
+            i + 1,
+            shown_text(&m.title, safe),
+            result.score,
+            m.id,
+            m.metadata.memory_type.as_str(),
+        ));
+    }
+    mcp_text(&output)
+}
+
+/// A stored title or snippet as shown in tool output: on one line and,
+/// with `[display] safe_content`, stripped of control sequences and role
+/// markers and escaped
+fn shown_text(text: &str, safe: bool) -> Cow<'_, str> {
+    if safe {
+        Cow::Owned(xml_escape(&strip_unsafe(text).replace('\n', " ")))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Brief lines for linked memories, indented under their result
+fn describe_linked(linked: &[LinkedMemory], safe: bool) -> String {
+    linked
+        .iter()
+        .map(|l| {
+            let arrow = if l.incoming { "←" } else { "→" };
+            format!(
+                "   {}{arrow} {} [{}] **{}** ({}): {}\n",
+                "  ".repeat(l.depth - 1),
+                l.relation.as_str(),
+                l.memory_type.as_str(),
+                shown_text(&l.title, safe),
+                l.id,
+                shown_text(&l.snippet, safe),
+            )
+        })
+        .collect()
+}
+
 fn tool_memory_store(args: &Value, state: &Arc<McpState>) -> Value {
     let content = match args["content"].as_str() {
         Some(c) if !c.is_empty() => c.to_string(),
@@ -234,25 +1131,53 @@ fn tool_memory_store(args: &Value, state: &Arc<McpState>) -> Value {
         .and_then(|s| serde_json::from_str::<Priority>(&format!("\"{s}\"")).ok())
         .unwrap_or(Priority::Medium);
 
-    let tags: Vec<String> = args["tags"]
-        .as_array()
-        .map(|arr| {
-            arr.iter()
-                .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                .collect()
-        })
-        .unwrap_or_default();
+    let tags = string_list(&args["tags"]).unwrap_or_default();
+    let registry = state.config.field_registry();
+    let fields = match args["fields"].as_object().map(|f| registry.encode_all(f)) {
+        Some(Ok(fields)) => fields,
+        Some(Err(e)) => return mcp_error(&format!("Invalid fields: {e}")),
+        None => Default::default(),
+    };
+    let links = match link_list(&args["links"]) {
+        Ok(links) => links,
+        Err(e) => return mcp_error(&format!("Invalid links: {e}")),
+    };
 
-    let embedding = state
-        .embedder
-        .as_ref()
-        .and_then(|e| match e.embed(&content) {
-            Ok(emb) => Some(emb),
-            Err(err) => {
+    // A saturated embedder turns the store away rather than storing the
+    // memory without an embedding
+    let embedding = match state.embedder.as_ref().map(|e| e.embed(&content)) {
+        Some(Ok(emb)) => Some(emb),
+        Some(Err(err)) => match err.retry_after_ms() {
+            Some(retry_after_ms) => return mcp_busy(retry_after_ms),
+            None => {
                 tracing::warn!("Embedding failed for new memory: {err}");
                 None
             }
-        });
+        },
+        None => None,
+    };
+    let chunks = match (&state.embedder, &embedding) {
+        (Some(e), Some(_)) => {
+            match embed_chunks(e.as_ref(), &content, &state.config.embedding.chunking) {
+                Ok(chunks) => chunks,
+                Err(err) => match err.retry_after_ms() {
+                    Some(retry_after_ms) => return mcp_busy(retry_after_ms),
+                    None => {
+                        tracing::warn!("Chunk embedding failed for new memory: {err}");
+                        Vec::new()
+                    }
+                },
+            }
+        }
+        _ => Vec::new(),
+    };
+    // Chunk embeddings cover the rest of a long memory
+    let truncated = embedding.is_some()
+        && chunks.is_empty()
+        && state
+            .embedder
+            .as_ref()
+            .is_some_and(|e| e.truncates(&content));
 
     let mut memory = Memory::new(
         content,
@@ -261,34 +1186,158 @@ fn tool_memory_store(args: &Value, state: &Arc<McpState>) -> Value {
             memory_type,
             priority,
             tags,
+            concepts: string_list(&args["concepts"]).unwrap_or_default(),
             ..Default::default()
         },
     );
+    if let Some(keywords) = &state.keywords {
+        keywords.fill_concepts(&mut memory, state.config.keywords.max_concepts);
+    }
+    if embedding.is_some() {
+        memory.embedding_model = state.embedder.as_ref().map(|e| e.model_id().to_string());
+    }
     memory.embedding = embedding;
+    memory.expires_at = args["ttl_hours"]
+        .as_u64()
+        .map(|hours| expiry::expires_after(memory.created_at, hours));
+    memory.namespace = namespace_or_default(namespace_arg(args, state));
 
-    if let Err(e) = state.storage.insert(&memory) {
+    let timezone = state.config.display.timezone;
+    // Flagged memories are stored on their own, never merged
+    let quarantined = state.quarantine.as_ref().and_then(|q| q.check(&memory));
+    let dedup = &state.config.dedup;
+    // A repeat counts whether the memory is then stored, merged or rejected
+    let (duplicate, escalation) = match (&memory.embedding, state.search.lock()) {
+        (Some(embedding), Ok(search)) if quarantined.is_none() => (
+            dedup
+                .enabled
+                .then(|| search.find_duplicate(embedding, dedup.threshold, &memory.namespace))
+                .flatten(),
+            search
+                .record_topic_repeat(embedding, &memory.namespace, &state.config.escalation)
+                .unwrap_or_else(|e| {
+                    tracing::warn!("Failed to record topic repeat: {e}");
+                    None
+                }),
+        ),
+        _ => (None, None),
+    };
+    let escalated = describe_escalation(escalation.as_ref());
+    if let Some((id, similarity)) = &duplicate {
+        match dedup.action {
+            DuplicateAction::Reject => {
+                return mcp_text(&format!(
+                    "Not stored: near-duplicate of memory {id} (similarity {similarity:.3}).\nUse memory_update to change it instead.{escalated}"
+                ));
+            }
+            DuplicateAction::Merge => match state.storage.merge_duplicate(id, &memory) {
+                Ok(Some(existing)) => {
+                    if let Err(e) = state.storage.set_fields(&existing.id, &fields) {
+                        return mcp_error(&format!("Failed to store fields: {e}"));
+                    }
+                    if let Err(e) = state.storage.link_all(&existing.id, &links) {
+                        return mcp_error(&format!("Merged, but failed to link memories: {e}"));
+                    }
+                    return mcp_text(&format!(
+                        "Merged into existing memory (near-duplicate, similarity {similarity:.3}).\nID: {}\nTitle: {}\nTags: {}{}{escalated}",
+                        existing.id,
+                        existing.title,
+                        existing.metadata.tags.join(", "),
+                        describe_new_links(&links),
+                    ));
+                }
+                // Deleted since it was indexed; store anew
+                Ok(None) => {}
+                Err(e) => return mcp_error(&format!("Failed to merge memory: {e}")),
+            },
+            DuplicateAction::Flag => {}
+        }
+    }
+
+    if let Err(e) = state.storage.insert_linked(&memory, &links) {
         return mcp_error(&format!("Failed to store memory: {e}"));
     }
+    // Before indexing, so search never sees the memory unflagged
+    if let Some(reason) = &quarantined
+        && let Err(e) = state.storage.quarantine(&memory.id, reason)
+    {
+        return mcp_error(&format!("Failed to quarantine memory: {e}"));
+    }
+    if let Err(e) = state.storage.set_fields(&memory.id, &fields) {
+        return mcp_error(&format!("Failed to store fields: {e}"));
+    }
+
+    if !chunks.is_empty()
+        && let Err(e) = state.storage.replace_chunk_embeddings(&memory.id, &chunks)
+    {
+        tracing::warn!("Failed to store chunk embeddings of {}: {e}", memory.id);
+    }
 
     if let Ok(mut search) = state.search.lock()
-        && let Err(e) = search.index_memory(&memory)
+        && let Err(e) = search
+            .index_memory(&memory)
+            .and_then(|()| search.index_chunks(&memory.id, &chunks))
     {
         tracing::warn!("Failed to index memory {}: {e}", memory.id);
     }
 
+    let mut note = match &duplicate {
+        Some((id, similarity)) => {
+            format!("\n⚠ Near-duplicate of memory {id} (similarity {similarity:.3})")
+        }
+        None => String::new(),
+    };
+    if let Some(reason) = &quarantined {
+        note.push_str(&format!(
+            "\n⚠ Quarantined ({reason}): hidden from search and retrieval until an admin approves it"
+        ));
+    }
+    note.push_str(&escalated);
     mcp_text(&format!(
-        "Memory stored successfully.\nID: {}\nTitle: {}\nType: {}\nEmbedding: {}",
+        "Memory stored successfully.\nID: {}\nTitle: {}\nType: {}{}{}\nEmbedding: {}{note}",
         memory.id,
         title,
         memory_type.as_str(),
-        if memory.embedding.is_some() {
-            "✓ generated"
-        } else {
+        match memory.expires_at {
+            Some(at) => format!("\nExpires: {}", timezone.format_minutes(at)),
+            None => String::new(),
+        },
+        describe_new_links(&links),
+        if memory.embedding.is_none() {
             "✗ unavailable"
+        } else if truncated {
+            "⚠ truncated (content exceeds the model's input limit; only part was embedded)"
+        } else {
+            "✓ generated"
         }
     ))
 }
 
+/// A note that storing the topic again raised a memory's priority, if it did
+fn describe_escalation(escalation: Option<&Escalation>) -> String {
+    match escalation {
+        Some(e) => format!(
+            "\nPriority of memory {} raised to {}: its topic was stored {} times recently",
+            e.id,
+            e.priority.as_str(),
+            e.repeats
+        ),
+        None => String::new(),
+    }
+}
+
+/// A `Links:` line for the links made on store, if any
+fn describe_new_links(links: &[NewLink]) -> String {
+    if links.is_empty() {
+        return String::new();
+    }
+    let links: Vec<String> = links
+        .iter()
+        .map(|link| format!("{} {}", link.relation.as_str(), link.target_id))
+        .collect();
+    format!("\nLinks: {}", links.join(", "))
+}
+
 fn tool_memory_get(args: &Value, state: &Arc<McpState>) -> Value {
     let ids: Vec<String> = match args["ids"].as_array() {
         Some(arr) => arr
@@ -301,30 +1350,316 @@ fn tool_memory_get(args: &Value, state: &Arc<McpState>) -> Value {
     if ids.is_empty() {
         return mcp_error("ids array cannot be empty");
     }
+    let format = match output_format(args) {
+        Ok(format) => format,
+        Err(e) => return mcp_error(&e),
+    };
+    let expand_links = args["expand_links"].as_u64().unwrap_or(0) as usize;
 
+    let labels = Labels::for_call(args, state.config.mcp.language);
     match state.storage.get_many(&ids) {
-        Ok(memories) => {
+        Ok(mut memories) => {
+            // Quarantined memories read as not found
+            let quarantined = state.storage.quarantined_among(&ids).unwrap_or_default();
+            memories.retain(|m| !quarantined.contains(&m.id));
+            let missing: Vec<String> = ids
+                .iter()
+                .filter(|id| !memories.iter().any(|m| &m.id == *id))
+                .cloned()
+                .collect();
+            if format == Format::Json {
+                return mcp_json(&memories_json(state, memories, &missing, expand_links));
+            }
+            let deleted = describe_tombstones(state, &missing, labels);
             if memories.is_empty() {
-                return mcp_text("No memories found with the given IDs.");
+                return match deleted {
+                    Some(deleted) => mcp_text(&deleted),
+                    None => mcp_text(labels.no_ids_found),
+                };
+            }
+            if let Ok(search) = state.search.lock() {
+                let fetched: Vec<String> = memories.iter().map(|m| m.id.clone()).collect();
+                search.record_fetch(&fetched);
+            }
+            if format == Format::Pack {
+                for m in &memories {
+                    let _ = state.storage.touch(&m.id);
+                }
+                let mut output = render_pack(
+                    &memories,
+                    &state.config.pack,
+                    Utc::now(),
+                    &state.config.display,
+                );
+                if let Some(deleted) = deleted {
+                    output.push('\n');
+                    output.push_str(&deleted);
+                }
+                return mcp_text(&output);
             }
+            let mut linked = if expand_links > 0 {
+                let found: Vec<String> = memories.iter().map(|m| m.id.clone()).collect();
+                state
+                    .storage
+                    .expand_links(&found, expand_links)
+                    .unwrap_or_else(|e| {
+                        tracing::warn!("Failed to expand links: {e}");
+                        Default::default()
+                    })
+            } else {
+                Default::default()
+            };
+            let registry = state.config.field_registry();
+            let timezone = state.config.display.timezone;
+            let safe = state.config.display.safe_content;
             let mut output = String::new();
             for m in &memories {
+                let links = match linked.remove(&m.id) {
+                    Some(links) => {
+                        format!(
+                            "**{}:**\n{}\n",
+                            labels.linked,
+                            describe_linked(&links, safe)
+                        )
+                    }
+                    None => String::new(),
+                };
+                let fields = state.storage.fields_of(&m.id).unwrap_or_default();
+                let fields = if fields.is_empty() {
+                    String::new()
+                } else {
+                    let listed: Vec<String> = fields
+                        .into_iter()
+                        .map(|(name, value)| format!("{name}={}", registry.decode(&name, value)))
+                        .collect();
+                    format!("**{}:** {}\n", labels.fields, listed.join(", "))
+                };
+                let concepts = if m.metadata.concepts.is_empty() {
+                    String::new()
+                } else {
+                    format!(
+                        "**{}:** {}\n",
+                        labels.concepts,
+                        m.metadata.concepts.join(", ")
+                    )
+                };
+                let expires = match m.expires_at {
+                    Some(at) => {
+                        format!("**{}:** {}\n", labels.expires, timezone.format_minutes(at))
+                    }
+                    None => String::new(),
+                };
+                let content = if safe {
+                    fence(&m.content)
+                } else {
+                    m.content.clone()
+                };
                 output.push_str(&format!(
-                    "## {} ({})\n**ID:** {}\n**Type:** {} | **Priority:** {:?}\n**Tags:** {}\n**Created:** {}\n**Content:**\n{}\n\n---\n\n",
-                    m.title, m.metadata.memory_type.as_str(), m.id,
-                    m.metadata.memory_type.as_str(), m.metadata.priority,
-                    m.metadata.tags.join(", "),
-                    m.created_at.format("%Y-%m-%d %H:%M"),
-                    m.content,
+                    "## {} ({})\n**{}:** {}\n**{}:** {} | **{}:** {:?}\n**{}:** {}\n{concepts}{fields}**{}:** {}\n{expires}**{}:**\n{}\n\n{links}---\n\n",
+                    shown_text(&m.title, safe), m.metadata.memory_type.as_str(), labels.id, m.id,
+                    labels.memory_type, m.metadata.memory_type.as_str(),
+                    labels.priority, m.metadata.priority,
+                    labels.tags, m.metadata.tags.join(", "),
+                    labels.created, timezone.format_minutes(m.created_at),
+                    labels.content, content,
                 ));
                 let _ = state.storage.touch(&m.id);
             }
+            if let Some(deleted) = deleted {
+                output.push_str(&deleted);
+            }
             mcp_text(&output)
         }
         Err(e) => mcp_error(&format!("Failed to retrieve memories: {e}")),
     }
 }
 
+/// `memory_get` results for `format: "json"`: the memories without their
+/// embeddings, each with its typed fields and linked memories, and the
+/// tombstones of the requested IDs that were deleted
+fn memories_json(
+    state: &Arc<McpState>,
+    memories: Vec<Memory>,
+    missing: &[String],
+    expand_links: usize,
+) -> Value {
+    let found: Vec<String> = memories.iter().map(|m| m.id.clone()).collect();
+    if let Ok(search) = state.search.lock() {
+        search.record_fetch(&found);
+    }
+    let mut linked = if expand_links > 0 {
+        state
+            .storage
+            .expand_links(&found, expand_links)
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to expand links: {e}");
+                Default::default()
+            })
+    } else {
+        Default::default()
+    };
+    let registry = state.config.field_registry();
+    let memories: Vec<Value> = memories
+        .into_iter()
+        .map(|mut m| {
+            let fields: serde_json::Map<String, Value> = state
+                .storage
+                .fields_of(&m.id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(name, value)| {
+                    let value = registry.decode(&name, value);
+                    (name, value)
+                })
+                .collect();
+            let links = linked.remove(&m.id).unwrap_or_default();
+            let _ = state.storage.touch(&m.id);
+            m.embedding = None;
+            let mut memory = json!(m);
+            memory["fields"] = Value::Object(fields);
+            memory["linked"] = json!(links);
+            memory
+        })
+        .collect();
+    let deleted = if missing.is_empty() {
+        Vec::new()
+    } else {
+        state.storage.tombstones(missing).unwrap_or_else(|e| {
+            tracing::warn!("Failed to look up deleted memories: {e}");
+            Vec::new()
+        })
+    };
+    json!({ "memories": memories, "deleted": deleted })
+}
+
+fn tool_memory_grep(args: &Value, state: &Arc<McpState>) -> Value {
+    let id = match args["id"].as_str() {
+        Some(id) if !id.is_empty() => id,
+        _ => return mcp_error("id is required"),
+    };
+    let pattern = args["pattern"].as_str().filter(|p| !p.is_empty());
+    let query = args["query"].as_str().filter(|q| !q.trim().is_empty());
+    let context = args["context"]
+        .as_u64()
+        .map_or(DEFAULT_CONTEXT_CHARS, |c| c as usize);
+    let limit = args["limit"].as_u64().unwrap_or(5) as usize;
+
+    let quarantined = state
+        .storage
+        .quarantined_among(&[id.to_string()])
+        .unwrap_or_default();
+    let memory = match state.storage.get(id) {
+        Ok(Some(memory)) if quarantined.is_empty() => memory,
+        Ok(_) => return mcp_error(&format!("Memory {id} not found")),
+        Err(e) => return mcp_error(&format!("Failed to load memory: {e}")),
+    };
+    let passages = match (pattern, query) {
+        (Some(pattern), None) => grep_pattern(&memory.content, pattern, context, limit),
+        (None, Some(query)) => grep_query(&memory.content, query, context, limit),
+        _ => return mcp_error("Pass exactly one of pattern or query"),
+    };
+
+    let safe = state.config.display.safe_content;
+    let title = shown_text(&memory.title, safe);
+    let total = memory.content.chars().count();
+    if passages.is_empty() {
+        return mcp_text(&format!("No matches in **{title}** ({id}, {total} chars)."));
+    }
+    let _ = state.storage.touch(id);
+    let mut output = format!(
+        "{} passage(s) in **{title}** ({id}, {total} chars):\n\n",
+        passages.len()
+    );
+    for passage in &passages {
+        let matches: Vec<String> = passage
+            .matches
+            .iter()
+            .map(|(start, end)| format!("{start}-{end}"))
+            .collect();
+        let text = if safe {
+            fence(&passage.text)
+        } else {
+            passage.text.clone()
+        };
+        output.push_str(&format!(
+            "[{}-{}] matches at {}\n{text}\n\n",
+            passage.start,
+            passage.end,
+            matches.join(", "),
+        ));
+    }
+    mcp_text(&output)
+}
+
+/// Explain which of `ids` were deleted (and when, by whom), so an agent
+/// holding a stale ID learns why it is gone
+fn describe_tombstones(state: &Arc<McpState>, ids: &[String], labels: &Labels) -> Option<String> {
+    if ids.is_empty() {
+        return None;
+    }
+    let tombstones = match state.storage.tombstones(ids) {
+        Ok(tombstones) if !tombstones.is_empty() => tombstones,
+        Ok(_) => return None,
+        Err(e) => {
+            tracing::warn!("Failed to look up deleted memories: {e}");
+            return None;
+        }
+    };
+    let mut output = format!("{}\n", labels.deleted_memories);
+    for t in tombstones {
+        output.push_str(&format!(
+            "- {} {} {}",
+            t.id,
+            labels.deleted_at,
+            state.config.display.timezone.format_minutes(t.deleted_at)
+        ));
+        if let Some(by) = &t.deleted_by {
+            output.push_str(&format!(" {} {by}", labels.deleted_by));
+        }
+        output.push('\n');
+    }
+    Some(output)
+}
+
+fn tool_memory_update(args: &Value, state: &Arc<McpState>) -> Value {
+    let id = match args["id"].as_str() {
+        Some(id) if !id.is_empty() => id,
+        _ => return mcp_error("id is required"),
+    };
+    let mut fields = args.clone();
+    if let Some(obj) = fields.as_object_mut() {
+        obj.remove("id");
+    }
+    let patch: MemoryPatch = match serde_json::from_value(fields) {
+        Ok(patch) => patch,
+        Err(e) => return mcp_error(&format!("Invalid update: {e}")),
+    };
+    if patch.is_empty() {
+        return mcp_error("Nothing to update: pass title, memory_type, priority or tags");
+    }
+
+    let memory = match state.storage.update_metadata(id, &patch) {
+        Ok(Some(memory)) => memory,
+        Ok(None) => return mcp_text(&format!("Memory {id} not found.")),
+        Err(e) => return mcp_error(&format!("Failed to update memory: {e}")),
+    };
+    if patch.title.is_some()
+        && let Ok(mut search) = state.search.lock()
+        && let Err(e) = search.index_memory_text(&memory.id, &memory.title, &memory.content)
+    {
+        tracing::warn!("Failed to reindex title: {e}");
+    }
+
+    mcp_text(&format!(
+        "Memory updated.\nID: {}\nTitle: {}\nType: {} | Priority: {:?} | Tags: {}",
+        memory.id,
+        memory.title,
+        memory.metadata.memory_type.as_str(),
+        memory.metadata.priority,
+        memory.metadata.tags.join(", "),
+    ))
+}
+
 fn tool_memory_delete(args: &Value, state: &Arc<McpState>) -> Value {
     let id = match args["id"].as_str() {
         Some(id) if !id.is_empty() => id,
@@ -337,33 +1672,748 @@ fn tool_memory_delete(args: &Value, state: &Arc<McpState>) -> Value {
         tracing::warn!("Failed to remove from search index: {e}");
     }
 
-    match state.storage.delete(id) {
+    let actor = match state.client.get() {
+        Some(name) => format!("mcp:{name}"),
+        None => "mcp".to_string(),
+    };
+    match state.storage.delete_as(id, Some(&actor)) {
         Ok(true) => mcp_text(&format!("Memory {} deleted successfully.", id)),
         Ok(false) => mcp_text(&format!("Memory {} not found.", id)),
         Err(e) => mcp_error(&format!("Failed to delete memory: {e}")),
     }
 }
 
-fn tool_memory_stats(state: &Arc<McpState>) -> Value {
-    let total = state.storage.count().unwrap_or(0);
-    let indexed = state.search.lock().map(|s| s.indexed_count()).unwrap_or(0);
-    let has_embedder = state.embedder.is_some();
+/// Archive the memory `id`, or with `archive` false bring it back. It
+/// stays indexed; searches filter it out.
+fn tool_memory_archive(args: &Value, state: &Arc<McpState>, archive: bool) -> Value {
+    let id = match args["id"].as_str() {
+        Some(id) if !id.is_empty() => id,
+        _ => return mcp_error("id is required"),
+    };
+    let actor = match state.client.get() {
+        Some(name) => format!("mcp:{name}"),
+        None => "mcp".to_string(),
+    };
+    match (
+        state.storage.set_archived_state(id, archive, Some(&actor)),
+        archive,
+    ) {
+        (Ok(true), true) => mcp_text(&format!("Memory {id} archived.")),
+        (Ok(true), false) => mcp_text(&format!("Memory {id} unarchived.")),
+        (Ok(false), true) => mcp_text(&format!("Memory {id} not found or already archived.")),
+        (Ok(false), false) => mcp_text(&format!("Memory {id} is not archived.")),
+        (Err(e), _) => mcp_error(&format!("Failed to change archive state: {e}")),
+    }
+}
+
+fn tool_memory_retag(args: &Value, state: &Arc<McpState>) -> Value {
+    let filter = match MemoryFilter::from_json(args, state.config.display.timezone) {
+        Ok(filter) => filter,
+        Err(e) => return mcp_error(&format!("Invalid filter: {e}")),
+    };
+    let edit: TagEdit = match serde_json::from_value(args.clone()) {
+        Ok(edit) => edit,
+        Err(e) => return mcp_error(&format!("Invalid tags: {e}")),
+    };
+    if filter.is_empty() {
+        return mcp_error(
+            "Pass query, memory_type, created_after or created_before to select memories",
+        );
+    }
+    if edit.is_empty() {
+        return mcp_error("Nothing to do: pass add or remove");
+    }
+
+    let actor = match state.client.get() {
+        Some(name) => format!("mcp:{name}"),
+        None => "mcp".to_string(),
+    };
+    let report = match state.storage.retag(&filter, &edit, Some(&actor)) {
+        Ok(report) => report,
+        Err(e) => return mcp_error(&format!("Failed to retag memories: {e}")),
+    };
+    if let Ok(mut search) = state.search.lock()
+        && let Err(e) = search.reindex_text(&report.changed)
+    {
+        tracing::warn!("Failed to reindex retagged memories: {e}");
+    }
 
     mcp_text(&format!(
-        "Memory System Stats:\n- Total memories: {}\n- Indexed for search: {}\n- Embedding engine: {}\n- Dimensions: 1024\n- Search mode: {}",
+        "Retagged {} of {} matching memories.",
+        report.changed.len(),
+        report.matched
+    ))
+}
+
+fn tool_memory_feedback(args: &Value, state: &Arc<McpState>) -> Value {
+    let useful = string_list(&args["useful"]).unwrap_or_default();
+    let not_useful = string_list(&args["not_useful"]).unwrap_or_default();
+    if useful.is_empty() && not_useful.is_empty() {
+        return mcp_error("Nothing to record: pass useful or not_useful memory IDs");
+    }
+
+    let report = match state.storage.record_feedback(&useful, &not_useful) {
+        Ok(report) => report,
+        Err(e) => return mcp_error(&format!("Failed to record feedback: {e}")),
+    };
+    let mut output = format!("Recorded feedback on {} memories.", report.recorded);
+    if !report.unknown.is_empty() {
+        output.push_str(&format!("\nUnknown IDs: {}", report.unknown.join(", ")));
+    }
+    mcp_text(&output)
+}
+
+fn tool_memory_link(args: &Value, state: &Arc<McpState>) -> Value {
+    let (Some(from_id), Some(to_id)) = (args["from_id"].as_str(), args["to_id"].as_str()) else {
+        return mcp_error("from_id and to_id are required");
+    };
+    let relation = match args["relation"].as_str() {
+        Some(s) => match serde_json::from_str::<Relation>(&format!("\"{s}\"")) {
+            Ok(relation) => Some(relation),
+            Err(_) => {
+                return mcp_error(&format!(
+                    "Unknown relation '{s}': use supersedes, relates_to, caused_by or part_of"
+                ));
+            }
+        },
+        None => None,
+    };
+
+    if args["remove"].as_bool().unwrap_or(false) {
+        return match state.storage.unlink(from_id, to_id, relation) {
+            Ok(removed) => mcp_text(&format!("Removed {removed} link(s).")),
+            Err(e) => mcp_error(&format!("Failed to remove link: {e}")),
+        };
+    }
+    let Some(relation) = relation else {
+        return mcp_error("relation is required unless remove is set");
+    };
+    match state.storage.link(from_id, to_id, relation) {
+        Ok(true) => mcp_text(&format!("Linked {from_id} {} {to_id}.", relation.as_str())),
+        Ok(false) => mcp_text("Link already exists."),
+        Err(e) => mcp_error(&format!("Failed to link memories: {e}")),
+    }
+}
+
+fn tool_memory_links_get(args: &Value, state: &Arc<McpState>) -> Value {
+    let id = match args["id"].as_str() {
+        Some(id) if !id.is_empty() => id,
+        _ => return mcp_error("id is required"),
+    };
+    let links = match state.storage.links_of(&[id.to_string()]) {
+        Ok(links) => links,
+        Err(e) => return mcp_error(&format!("Failed to load links: {e}")),
+    };
+    if links.is_empty() {
+        return mcp_text(&format!("Memory {id} has no links."));
+    }
+
+    let others: Vec<String> = links
+        .iter()
+        .map(|l| {
+            if l.from_id == id {
+                &l.to_id
+            } else {
+                &l.from_id
+            }
+            .clone()
+        })
+        .collect();
+    let memories = state.storage.get_many(&others).unwrap_or_default();
+    let title_of = |other: &str| {
+        memories
+            .iter()
+            .find(|m| m.id == other)
+            .map_or(Cow::Borrowed("(deleted)"), |m| {
+                shown_text(&m.title, state.config.display.safe_content)
+            })
+            .to_string()
+    };
+
+    let mut output = format!("## Links of {id}\n\n");
+    for (link, other) in links.iter().zip(&others) {
+        let arrow = if link.from_id == id { "→" } else { "←" };
+        output.push_str(&format!(
+            "- {} {arrow} **{}** ({other})\n",
+            link.relation.as_str(),
+            title_of(other)
+        ));
+    }
+    mcp_text(&output)
+}
+
+fn tool_memory_merge(args: &Value, state: &Arc<McpState>) -> Value {
+    let mode = match args["mode"].as_str() {
+        Some(s) => match serde_json::from_str::<MergeMode>(&format!("\"{s}\"")) {
+            Ok(mode) => mode,
+            Err(_) => {
+                return mcp_error(&format!("Unknown mode '{s}': use concatenate or summarize"));
+            }
+        },
+        None => MergeMode::default(),
+    };
+    let request = MergeRequest {
+        ids: string_list(&args["ids"]).unwrap_or_default(),
+        mode,
+        title: args["title"].as_str().map(str::to_string),
+    };
+    let summarizer = ExtractiveSummarizer::new(state.config.consolidation.target_type);
+    let actor = match state.client.get() {
+        Some(name) => format!("mcp:{name}"),
+        None => "mcp".to_string(),
+    };
+    let report = match merge::merge(
+        &state.storage,
+        &request,
+        &summarizer,
+        |text| state.embedder.as_ref().and_then(|e| e.embed(text).ok()),
+        state.embedder.as_ref().map(|e| e.model_id()),
+        Some(&actor),
+    ) {
+        Ok(report) => report,
+        Err(e) => return mcp_error(&format!("Failed to merge memories: {e}")),
+    };
+
+    if let Ok(Some(memory)) = state.storage.get(&report.id)
+        && let Ok(mut search) = state.search.lock()
+        && let Err(e) = search.index_memory(&memory)
+    {
+        tracing::warn!("Failed to index memory {}: {e}", memory.id);
+    }
+    mcp_text(&format!(
+        "Merged {} memories into {} (**{}**); each original is superseded by it and archived.",
+        report.superseded.len(),
+        report.id,
+        shown_text(&report.title, state.config.display.safe_content)
+    ))
+}
+
+fn tool_memory_stats(args: &Value, state: &Arc<McpState>) -> Value {
+    let format = match output_format(args) {
+        Ok(Format::Pack) => return mcp_error("Unknown format: pack (expected text or json)"),
+        Ok(format) => format,
+        Err(e) => return mcp_error(&e),
+    };
+    let labels = Labels::for_call(args, state.config.mcp.language);
+    let namespaces = state.storage.namespaces().unwrap_or_default();
+    let namespace = namespace_arg(args, state);
+    let include_archived = args["include_archived"].as_bool().unwrap_or(false);
+    let (total, cold) = match namespace {
+        Some(ns) => namespaces
+            .iter()
+            .find(|c| c.namespace == ns)
+            .map_or((0, 0), |c| (c.count, c.cold)),
+        None => (
+            state.storage.count().unwrap_or(0),
+            state.storage.count_cold().unwrap_or(0),
+        ),
+    };
+    let archived = state.storage.count_archived(namespace).unwrap_or(0);
+    // Archived memories count only when asked for
+    let total = if include_archived {
+        total
+    } else {
+        total.saturating_sub(archived)
+    };
+    let (indexed, experiment, query_cache) = state
+        .search
+        .lock()
+        .map(|s| {
+            (
+                s.indexed_count(),
+                s.experiment_report(),
+                s.query_cache_stats(),
+            )
+        })
+        .unwrap_or_default();
+    let has_embedder = state.embedder.is_some();
+    let cache = state.storage.cache_stats();
+    let misses = state
+        .storage
+        .missed_queries(STATS_MISSED_QUERIES, namespace)
+        .unwrap_or_default();
+    let breakdown = state
+        .storage
+        .breakdown(namespace, STATS_TOP_TAGS, include_archived)
+        .unwrap_or_default();
+    let disk = state.config.disk_usage();
+
+    if format == Format::Json {
+        return mcp_json(&json!({
+            "total_memories": total,
+            "cold_memories": cold,
+            "archived_memories": archived,
+            "indexed_count": indexed,
+            "has_embedder": has_embedder,
+            "search_mode": if has_embedder { "hybrid" } else { "keyword-only" },
+            "namespace": namespace,
+            "namespaces": if namespace.is_none() { namespaces } else { Vec::new() },
+            "cache": cache,
+            "query_cache": query_cache,
+            "missed_queries": misses,
+            "breakdown": breakdown,
+            "disk": disk,
+            "experiment": experiment,
+        }));
+    }
+
+    let mut output = format!(
+        "{}\n- {}: {} ({} {}, {} {})\n- {}: {}\n- {}: {}\n- {}: 1024\n- {}: {}",
+        match namespace {
+            Some(ns) => format!("{} [{}: {ns}]", labels.stats_title, labels.namespace),
+            None => labels.stats_title.to_string(),
+        },
+        labels.total_memories,
         total,
+        cold,
+        labels.cold,
+        archived,
+        labels.archived,
+        labels.indexed,
         indexed,
+        labels.embedding_engine,
         if has_embedder {
-            "✓ active (BGE-m3-ko)"
+            labels.engine_active
         } else {
-            "✗ not loaded"
+            labels.engine_missing
         },
+        labels.dimensions,
+        labels.search_mode,
         if has_embedder {
-            "hybrid (vector + keyword + time decay)"
+            labels.mode_hybrid
         } else {
-            "keyword-only (BM25)"
+            labels.mode_keyword
         },
-    ))
+    );
+
+    let counts = |counts: &BTreeMap<String, usize>| {
+        counts
+            .iter()
+            .map(|(name, n)| format!("{name} {n}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    if !breakdown.by_type.is_empty() {
+        output.push_str(&format!(
+            "\n- {}: {}\n- {}: {}",
+            labels.by_type,
+            counts(&breakdown.by_type),
+            labels.by_priority,
+            counts(&breakdown.by_priority),
+        ));
+    }
+    if !breakdown.top_tags.is_empty() {
+        let tags: Vec<String> = breakdown
+            .top_tags
+            .iter()
+            .map(|t| format!("{} ({})", t.tag, t.count))
+            .collect();
+        output.push_str(&format!("\n- {}: {}", labels.top_tags, tags.join(", ")));
+    }
+    if let (Some(oldest), Some(newest)) = (breakdown.oldest, breakdown.newest) {
+        let timezone = state.config.display.timezone;
+        output.push_str(&format!(
+            "\n- {}: {} – {}",
+            labels.date_range,
+            timezone.format_minutes(oldest),
+            timezone.format_minutes(newest),
+        ));
+    }
+    if total > 0 {
+        output.push_str(&format!(
+            "\n- {}: {}/{} ({:.1}%)",
+            labels.embedding_coverage,
+            breakdown.embedded,
+            total,
+            breakdown.embedding_coverage * 100.0,
+        ));
+    }
+    output.push_str(&format!(
+        "\n- {}: {} (db {}, text {}, vector {})",
+        labels.disk_usage,
+        format_bytes(disk.total_bytes()),
+        format_bytes(disk.database_bytes),
+        format_bytes(disk.text_index_bytes),
+        format_bytes(disk.vector_index_bytes),
+    ));
+
+    for (label, cache) in [
+        (labels.read_cache, &cache),
+        (labels.query_cache, &query_cache),
+    ] {
+        if cache.capacity > 0 {
+            output.push_str(&format!(
+                "\n- {}: {:.1}% {} ({}/{}), {}/{} {}",
+                label,
+                cache.hit_rate * 100.0,
+                labels.cache_hits,
+                cache.hits,
+                cache.hits + cache.misses,
+                cache.entries,
+                cache.capacity,
+                labels.cache_entries,
+            ));
+        }
+    }
+
+    if namespace.is_none() && namespaces.len() > 1 {
+        output.push_str(&format!("\n- {}:", labels.namespaces));
+        for c in &namespaces {
+            output.push_str(&format!("\n  - {}: {}", c.namespace, c.count));
+        }
+    }
+
+    if !misses.is_empty() {
+        output.push_str(&format!("\n- {}:", labels.missed_queries));
+        for m in &misses {
+            output.push_str(&format!(
+                "\n  - \"{}\": {} {}",
+                m.query, m.count, labels.times
+            ));
+        }
+    }
+
+    if let Some(report) = experiment {
+        output.push_str(&format!(
+            "\n\nA/B Experiment (B ratio {:.2}):",
+            report.b_ratio
+        ));
+        for (name, m) in [("A", &report.a), ("B", &report.b)] {
+            output.push_str(&format!(
+                "\n- {name}: {} searches, {} served, {} fetched (rate {:.1}%, mean rank {:.2})",
+                m.searches,
+                m.results_served,
+                m.fetched,
+                m.fetch_rate() * 100.0,
+                m.mean_fetched_rank(),
+            ));
+        }
+    }
+
+    mcp_text(&output)
+}
+
+/// Compact health summary as JSON. A component is listed as degraded when
+/// search runs without it (`embedder`), its index disagrees with storage
+/// (`text_index`, `vector_index`) or scheduled backups are more than two
+/// intervals behind (`backup`).
+/// Store a session summary as a `session` memory through `memory_store`,
+/// so it is embedded, deduplicated and screened like any other memory
+fn tool_memory_analytics(args: &Value, state: &Arc<McpState>) -> Value {
+    let limit = args["limit"]
+        .as_u64()
+        .map_or(DEFAULT_ANALYTICS_LIMIT, |n| n as usize);
+    let days = args["days"]
+        .as_u64()
+        .map_or(DEFAULT_ANALYTICS_DAYS, |n| n.min(u32::MAX as u64) as u32);
+    let json = match args["format"].as_str() {
+        None | Some("text") => false,
+        Some("json") => true,
+        Some(other) => {
+            return mcp_error(&format!("Unknown format: {other} (expected text or json)"));
+        }
+    };
+    let timezone = state.config.display.timezone;
+    let report = match access_analytics(
+        &state.storage,
+        namespace_arg(args, state),
+        limit,
+        days,
+        state.config.storage.access_half_life_days,
+        timezone,
+    ) {
+        Ok(report) => report,
+        Err(e) => return mcp_error(&format!("Failed to read analytics: {e}")),
+    };
+    if json {
+        return mcp_json(&json!(report));
+    }
+
+    let safe = state.config.display.safe_content;
+    let mut output = format!(
+        "Access analytics (last {} days)\n- Memories: {} ({} never retrieved)\n- Search hits: {}",
+        report.daily_accesses.len(),
+        report.total,
+        report.never_accessed,
+        report.search_hits,
+    );
+    if let Some(recency) = report.avg_hit_recency {
+        output.push_str(&format!(", average recency {recency:.2}"));
+    }
+    let entries = |heading: &str, entries: &[AccessEntry], output: &mut String| {
+        if entries.is_empty() {
+            return;
+        }
+        output.push_str(&format!("\n\n{heading}:"));
+        for e in entries {
+            let when = if e.access_count == 0 {
+                format!("stored {}", timezone.format_minutes(e.created_at))
+            } else {
+                format!(
+                    "{} accesses, last {}",
+                    e.access_count,
+                    timezone.format_minutes(e.accessed_at)
+                )
+            };
+            output.push_str(&format!(
+                "\n- {} ({}) — {when}",
+                shown_text(&e.title, safe),
+                e.id
+            ));
+        }
+    };
+    entries("Most accessed", &report.most_accessed, &mut output);
+    entries("Least accessed", &report.least_accessed, &mut output);
+    entries(
+        "Never retrieved (oldest first)",
+        &report.never_accessed_oldest,
+        &mut output,
+    );
+    output.push_str("\n\nAccesses per day:");
+    for day in &report.daily_accesses {
+        output.push_str(&format!("\n{} {:>4}", day.date, day.accesses));
+    }
+    mcp_text(&output)
+}
+
+fn tool_session_save(args: &Value, state: &Arc<McpState>) -> Value {
+    let summary = args["summary"].as_str().map(str::trim).unwrap_or_default();
+    let topics = string_list(&args["topics"]).unwrap_or_default();
+    let decisions = string_list(&args["decisions"]).unwrap_or_default();
+    let open_tasks = string_list(&args["open_tasks"]).unwrap_or_default();
+    if summary.is_empty() && topics.is_empty() && decisions.is_empty() && open_tasks.is_empty() {
+        return mcp_error("Provide a summary, topics, decisions or open_tasks");
+    }
+
+    let mut content = summary.to_string();
+    for (heading, items, marker) in [
+        ("Topics", &topics, "- "),
+        ("Decisions", &decisions, "- "),
+        ("Open tasks", &open_tasks, "- [ ] "),
+    ] {
+        if items.is_empty() {
+            continue;
+        }
+        if !content.is_empty() {
+            content.push_str("\n\n");
+        }
+        content.push_str(&format!("## {heading}\n"));
+        for item in items {
+            content.push_str(&format!("{marker}{item}\n"));
+        }
+    }
+
+    let mut tags = vec![SESSION_TAG.to_string()];
+    if !open_tasks.is_empty() {
+        tags.push(OPEN_TASKS_TAG.to_string());
+    }
+    tags.extend(string_list(&args["tags"]).unwrap_or_default());
+    let title = match args["title"].as_str().map(str::trim) {
+        Some(title) if !title.is_empty() => title.to_string(),
+        _ => format!(
+            "Session {}",
+            state.config.display.timezone.format_minutes(Utc::now())
+        ),
+    };
+
+    let mut store_args = json!({
+        "content": content.trim_end(),
+        "title": title,
+        "memory_type": MemoryType::Session.as_str(),
+        "tags": tags,
+        "concepts": topics,
+    });
+    if let Some(namespace) = args.get("namespace") {
+        store_args["namespace"] = namespace.clone();
+    }
+    tool_memory_store(&store_args, state)
+}
+
+/// The newest saved sessions, for picking up where the last one ended
+fn tool_session_recall(args: &Value, state: &Arc<McpState>) -> Value {
+    let limit = args["limit"]
+        .as_u64()
+        .map_or(SESSION_RECALL_LIMIT, |n| n as usize)
+        .max(1);
+    let json = match args["format"].as_str() {
+        None | Some("text") => false,
+        Some("json") => true,
+        Some(other) => {
+            return mcp_error(&format!("Unknown format: {other} (expected text or json)"));
+        }
+    };
+    let sessions =
+        match state
+            .storage
+            .recent_of_type(MemoryType::Session, namespace_arg(args, state), limit)
+        {
+            Ok(sessions) => sessions,
+            Err(e) => return mcp_error(&format!("Failed to read sessions: {e}")),
+        };
+    if json {
+        return mcp_json(&memories_json(state, sessions, &[], 0));
+    }
+    if sessions.is_empty() {
+        return mcp_text("No saved sessions yet. Use session_save at the end of a session.");
+    }
+
+    let safe = state.config.display.safe_content;
+    let timezone = state.config.display.timezone;
+    let mut output = format!("Last {} sessions (newest first):\n\n", sessions.len());
+    for m in &sessions {
+        let content = if safe {
+            fence(&m.content)
+        } else {
+            m.content.clone()
+        };
+        output.push_str(&format!(
+            "## {}\nID: {} | {}\n{content}\n\n",
+            shown_text(&m.title, safe),
+            m.id,
+            timezone.format_minutes(m.created_at),
+        ));
+    }
+    mcp_text(output.trim_end())
+}
+
+fn tool_memory_health(state: &Arc<McpState>) -> Value {
+    let consistency = match state.search.lock() {
+        Ok(search) => search.consistency(),
+        Err(e) => return mcp_error(&format!("Search lock error: {e}")),
+    };
+    let consistency = match consistency {
+        Ok(consistency) => consistency,
+        Err(e) => return mcp_error(&format!("Failed to check indexes: {e}")),
+    };
+    let total = state.storage.count().unwrap_or(0);
+    let cold = state.storage.count_cold().unwrap_or(0);
+    // Memories an embedder would still have to embed: stale ones for the
+    // loaded model, or all without an embedding when none is loaded
+    let pending_backfill = match &state.embedder {
+        Some(embedder) => state
+            .storage
+            .count_stale_embeddings(embedder.model_id(), embedder.dimensions())
+            .unwrap_or(0),
+        None => {
+            let embedded = state
+                .storage
+                .breakdown(None, 0, true)
+                .map_or(0, |breakdown| breakdown.embedded);
+            total.saturating_sub(embedded)
+        }
+    };
+    let last_backup = list_backups(&state.config)
+        .ok()
+        .and_then(|backups| backups.first().map(|b| b.created_at));
+
+    let mut degraded = Vec::new();
+    if state.embedder.is_none() {
+        degraded.push("embedder");
+    }
+    if !consistency.text_consistent() {
+        degraded.push("text_index");
+    }
+    if !consistency.vector_consistent() {
+        degraded.push("vector_index");
+    }
+    let interval = state.config.backup.interval_hours;
+    if interval > 0
+        && last_backup.is_none_or(|at| Utc::now() - at > Duration::hours(2 * interval as i64))
+    {
+        degraded.push("backup");
+    }
+
+    mcp_json(&json!({
+        "status": if degraded.is_empty() { "ok" } else { "degraded" },
+        "memories": { "total": total, "cold": cold },
+        "degraded": degraded,
+        "pending_backfill": pending_backfill,
+        "last_backup": last_backup,
+        "index": {
+            "consistent": consistency.text_consistent() && consistency.vector_consistent(),
+            "text_indexed": consistency.text_indexed,
+            "stored": consistency.stored,
+            "vector_indexed": consistency.vector_indexed,
+            "embedded": consistency.embedded,
+        },
+    }))
+}
+
+/// Byte count in the largest binary unit that keeps it at or above 1
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+fn tool_memory_tune(args: &Value, state: &Arc<McpState>) -> Value {
+    let arg = |name: &str| args[name].as_f64().map(|v| v as f32);
+    let update = ScorerUpdate {
+        semantic_weight: arg("semantic_weight"),
+        keyword_weight: arg("keyword_weight"),
+        recency_weight: arg("recency_weight"),
+        importance_weight: arg("importance_weight"),
+        access_weight: arg("access_weight"),
+        half_life_days: arg("half_life_days"),
+    };
+    let persist = args["persist"].as_bool().unwrap_or(false);
+
+    let mut search = match state.search.lock() {
+        Ok(s) => s,
+        Err(e) => return mcp_error(&format!("Search lock error: {e}")),
+    };
+
+    let tuned = search.scorer().with_update(&update);
+    if let Err(e) = tuned.validate() {
+        return mcp_error(&format!("Invalid weights: {e}"));
+    }
+    let changed = !update.is_empty();
+    if changed {
+        search.set_scorer(tuned.clone());
+        tracing::info!(?tuned, "Scorer weights updated");
+    }
+    drop(search);
+
+    let mut output = format!(
+        "Scoring weights{}:\n- semantic: {:.3}\n- keyword: {:.3}\n- recency: {:.3}\n- importance: {:.3}\n- access: {:.3}\n- half-life: {:.1} days\n- sum: {:.3}",
+        if changed { " updated" } else { "" },
+        tuned.semantic_weight,
+        tuned.keyword_weight,
+        tuned.recency_weight,
+        tuned.importance_weight,
+        tuned.access_weight,
+        tuned.half_life_days,
+        tuned.weight_sum(),
+    );
+
+    if persist {
+        match persist_scorer(&state.config, &tuned) {
+            Ok(path) => output.push_str(&format!("\nSaved to {}", path.display())),
+            Err(e) => return mcp_error(&format!("Weights applied but not saved: {e}")),
+        }
+    }
+
+    mcp_text(&output)
+}
+
+/// Write the scorer weights into the config file the server was started with.
+fn persist_scorer(config: &Config, scorer: &Scorer) -> anyhow::Result<std::path::PathBuf> {
+    let path = config
+        .source_path
+        .clone()
+        .ok_or_else(|| anyhow::anyhow!("server was not started from a config file"))?;
+    let mut updated = config.clone();
+    scorer.write_to(&mut updated.search);
+    updated.save(&path)?;
+    Ok(path)
 }
 
 /// Format a text response in MCP protocol format.
@@ -373,7 +2423,52 @@ pub fn mcp_text(text: &str) -> Value {
     })
 }
 
+/// Format machine-readable results in MCP protocol format: as
+/// `structuredContent`, and serialized as text for clients that only read
+/// `content`.
+pub fn mcp_json(value: &Value) -> Value {
+    json!({
+        "content": [{ "type": "text", "text": value.to_string() }],
+        "structuredContent": value
+    })
+}
+
+/// Output requested by the `format` argument
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// Human-readable text (the default)
+    Text,
+    /// A memory pack for context injection
+    Pack,
+    /// JSON, for agents that parse IDs and scores
+    Json,
+}
+
+fn output_format(args: &Value) -> Result<Format, String> {
+    match args["format"].as_str() {
+        None | Some("text") => Ok(Format::Text),
+        Some("pack") => Ok(Format::Pack),
+        Some("json") => Ok(Format::Json),
+        Some(other) => Err(format!(
+            "Unknown format: {other} (expected text, pack or json)"
+        )),
+    }
+}
+
 /// Format an error response in MCP protocol format.
+/// Tool error asking the client to retry after `retry_after_ms`, with the
+/// hint also in `structuredContent` for agent frameworks to back off on
+pub fn mcp_busy(retry_after_ms: u64) -> Value {
+    json!({
+        "content": [{
+            "type": "text",
+            "text": format!("Error: Embedder busy, retry in {retry_after_ms} ms")
+        }],
+        "structuredContent": { "error": "busy", "retry_after_ms": retry_after_ms },
+        "isError": true
+    })
+}
+
 pub fn mcp_error(msg: &str) -> Value {
     json!({
         "content": [{ "type": "text", "text": format!("Error: {msg}") }],
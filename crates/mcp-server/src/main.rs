@@ -1,43 +1,95 @@
 use anyhow::Result;
 use oc_core::Config;
-use oc_embeddings::EmbeddingEngine;
-use oc_mcp_server::{McpState, handle_request};
+use oc_core::quarantine::QuarantinePolicy;
+use oc_embeddings::Embedder;
+use oc_mcp_server::admission::Admission;
+use oc_mcp_server::{McpState, PARSE_ERROR, handle_message, jsonrpc_error};
+use oc_observer::{spawn_git_ingestion, spawn_ingestion};
 use oc_search::bm25::Bm25Index;
+use oc_search::experiment::Experiment;
 use oc_search::hybrid::HybridSearch;
+use oc_search::keywords::KeywordExtractor;
+use oc_search::profile::Profiles;
+use oc_search::query_cache::QueryCache;
 use oc_search::scoring::Scorer;
+use oc_search::selftest::run_self_test;
+use oc_search::shards::VectorShards;
+use oc_search::stopwords;
+use oc_search::synonyms::Synonyms;
 use oc_search::vector::VectorIndex;
 use serde_json::Value;
 use std::io::{self, BufRead, Write};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock};
 
 fn init_state(config: &Config) -> Result<Arc<McpState>> {
-    let db_path = shellexpand(&config.storage.data_dir);
-    std::fs::create_dir_all(&db_path)?;
-    let db_file = format!("{}/memories.db", db_path);
-    let storage = Arc::new(oc_core::Storage::open(&db_file)?);
+    std::fs::create_dir_all(config.data_dir())?;
+    let storage = Arc::new(oc_core::Storage::open_with_config(
+        config.db_path(),
+        &config.storage,
+    )?);
+
+    let compressed = storage.compress_cold(config.storage.compress_after_days)?;
+    if compressed > 0 {
+        tracing::info!("Compressed content of {compressed} cold memories");
+    }
+    let tiers = oc_core::tiering::enforce_tiers(&storage, &config.storage)?;
+    if tiers.expired + tiers.evicted > 0 {
+        tracing::info!(
+            expired = tiers.expired,
+            evicted = tiers.evicted,
+            hot = tiers.hot,
+            "Moved memories to the cold tier"
+        );
+    }
+    oc_core::expiry::sweep(&storage, &config.expiry)?;
 
-    let tantivy_path = format!("{}/tantivy", db_path);
+    let tantivy_path = config.tantivy_dir();
     std::fs::create_dir_all(&tantivy_path)?;
 
     let vector_index = VectorIndex::new(config.embedding.dimensions);
-    let bm25_index = Bm25Index::new(&tantivy_path)?;
-    let scorer = Scorer::default();
+    let synonyms = Synonyms::load(config.synonyms_path())?;
+    if !synonyms.is_empty() {
+        tracing::info!("Loaded {} synonym groups", synonyms.len());
+    }
+    let bm25_index = Bm25Index::new_with_tokenizer(&tantivy_path, config.search.tokenizer)?
+        .with_synonyms(synonyms)
+        .with_stopwords(&stopwords::from_config(&config.search))?;
+    let scorer = Scorer::from(&config.search);
     let mut search = HybridSearch::new(Arc::clone(&storage), vector_index, bm25_index, scorer);
+    let profiles = Profiles::from_config(&config.search)?;
+    if !profiles.is_empty() {
+        tracing::info!("Scoring profiles: {}", profiles.names().join(", "));
+    }
+    search.set_profiles(profiles);
+    search.set_query_cache(QueryCache::from_config(&config.search));
+    search.set_vector_shards(VectorShards::from_config(config));
+    if let Some(experiment) = &config.search.experiment {
+        search.set_experiment(Some(
+            Experiment::from_config(experiment)?.persisted_in(Arc::clone(&storage))?,
+        ));
+        tracing::info!(
+            b_ratio = experiment.b_ratio,
+            "A/B ranking experiment active"
+        );
+    }
 
-    // Load existing embeddings into vector index
-    let embeddings = storage.all_embeddings()?;
-    for (id, embedding) in &embeddings {
-        let _ = search
-            .vector_index_mut()
-            .upsert(id.clone(), embedding.clone());
+    // Reuse the persisted vector index when current, otherwise rebuild it
+    let vector_path = config.vector_index_path();
+    if search.load_vector_index(&vector_path)? {
+        tracing::info!(
+            "Loaded vector index with {} vectors",
+            search.indexed_count()
+        );
+    } else {
+        search.index_stored_vectors()?;
+        if let Err(e) = search.save_vector_index(&vector_path) {
+            tracing::warn!("Failed to save vector index: {e}");
+        }
     }
 
     // Rebuild BM25 index from existing memories
     let text_data = storage.all_text_data()?;
-    let bm25_count = text_data.len();
-    for (id, title, content) in &text_data {
-        let _ = search.index_memory_text(id, title, content);
-    }
+    let bm25_count = search.rebuild_text_index(&text_data)?;
     if bm25_count > 0 {
         tracing::info!("Rebuilt BM25 index with {bm25_count} memories");
     }
@@ -45,6 +97,15 @@ fn init_state(config: &Config) -> Result<Arc<McpState>> {
     let embedder = match init_embedder(config) {
         Ok(engine) => {
             tracing::info!("Embedding engine loaded successfully");
+            if let Ok(stale) =
+                storage.count_stale_embeddings(engine.model_id(), engine.dimensions())
+                && stale > 0
+            {
+                tracing::warn!(
+                    "{stale} memories have no embedding from {}; run `oc-memory-server reembed`",
+                    engine.model_id()
+                );
+            }
             Some(engine)
         }
         Err(e) => {
@@ -55,25 +116,25 @@ fn init_state(config: &Config) -> Result<Arc<McpState>> {
         }
     };
 
+    let search = Mutex::new(search);
+    if config.self_test.enabled {
+        run_self_test(&storage, &search, embedder.as_deref()).log();
+    }
+
     Ok(Arc::new(McpState {
         storage,
-        search: Mutex::new(search),
+        search,
         embedder,
+        config: config.clone(),
+        admission: Admission::new(&config.mcp),
+        client: OnceLock::new(),
+        keywords: config.keywords.enabled.then(KeywordExtractor::new),
+        quarantine: QuarantinePolicy::from_config(&config.quarantine),
     }))
 }
 
-fn init_embedder(config: &Config) -> Result<Arc<EmbeddingEngine>> {
-    let model_path = shellexpand(&config.embedding.model_path);
-    let tokenizer_path = shellexpand(&config.embedding.tokenizer_path);
-
-    let engine = EmbeddingEngine::new(
-        &model_path,
-        &tokenizer_path,
-        config.embedding.dimensions,
-        config.embedding.max_length,
-        config.embedding.num_threads,
-    )?;
-    Ok(Arc::new(engine))
+fn init_embedder(config: &Config) -> Result<Arc<dyn Embedder>> {
+    Ok(oc_embeddings::create_engine(&config.embedding)?)
 }
 
 #[tokio::main]
@@ -85,16 +146,32 @@ async fn main() -> Result<()> {
 
     tracing::info!("oc-memory MCP server starting");
 
-    let config = Config::default();
+    let mut config = Config::load_default()?;
+    if let Ok(namespace) = std::env::var("OC_MEMORY_NAMESPACE")
+        && !namespace.trim().is_empty()
+    {
+        config.mcp.namespace = Some(namespace.trim().to_string());
+    }
     let state = init_state(&config)?;
+    spawn_ingestion(Arc::clone(&state));
+    spawn_git_ingestion(Arc::clone(&state));
 
     tracing::info!("oc-memory MCP server ready");
 
-    let stdin = io::stdin();
-    let stdout = io::stdout();
-    let mut stdout = stdout.lock();
+    // Requests are handled concurrently (admission limits apply per tool
+    // class); responses go out through one writer as they complete.
+    let (tx, rx) = std::sync::mpsc::channel::<Value>();
+    let writer = std::thread::spawn(move || -> io::Result<()> {
+        let mut stdout = io::stdout().lock();
+        for response in rx {
+            writeln!(stdout, "{response}")?;
+            stdout.flush()?;
+        }
+        Ok(())
+    });
 
-    for line in stdin.lock().lines() {
+    let mut in_flight = tokio::task::JoinSet::new();
+    for line in io::stdin().lock().lines() {
         let line = line?;
         if line.trim().is_empty() {
             continue;
@@ -103,31 +180,42 @@ async fn main() -> Result<()> {
         let request: Value = match serde_json::from_str(&line) {
             Ok(v) => v,
             Err(e) => {
-                let err_response = serde_json::json!({
-                    "jsonrpc": "2.0",
-                    "error": { "code": -32700, "message": format!("Parse error: {e}") }
-                });
-                writeln!(stdout, "{}", serde_json::to_string(&err_response)?)?;
-                stdout.flush()?;
+                let _ = tx.send(jsonrpc_error(
+                    Value::Null,
+                    PARSE_ERROR,
+                    &format!("Parse error: {e}"),
+                ));
                 continue;
             }
         };
 
-        let response = handle_request(&request, &state).await;
+        let state = Arc::clone(&state);
+        let tx = tx.clone();
+        in_flight.spawn(async move {
+            if let Some(response) = handle_message(&request, &state).await {
+                let _ = tx.send(response);
+            }
+        });
+    }
 
-        let response_str = serde_json::to_string(&response)?;
-        writeln!(stdout, "{response_str}")?;
-        stdout.flush()?;
+    while in_flight.join_next().await.is_some() {}
+    drop(tx);
+    if let Ok(Err(e)) = writer.join() {
+        tracing::warn!("Failed to write response: {e}");
     }
 
+    save_vector_index(&state, &config);
     Ok(())
 }
 
-fn shellexpand(path: &str) -> String {
-    if path.starts_with("~/")
-        && let Some(home) = std::env::var_os("HOME")
-    {
-        return path.replacen("~", &home.to_string_lossy(), 1);
+/// Persist the vector index on shutdown so the next start can skip the rebuild
+fn save_vector_index(state: &McpState, config: &Config) {
+    let result = match state.search.lock() {
+        Ok(search) => search.save_vector_index(config.vector_index_path()),
+        Err(e) => Err(anyhow::anyhow!("Lock: {e}")),
+    };
+    match result {
+        Ok(()) => tracing::info!("Vector index saved"),
+        Err(e) => tracing::warn!("Failed to save vector index: {e}"),
     }
-    path.to_string()
 }
@@ -0,0 +1,386 @@
+//! The whole oc-memory engine in-process: SQLite storage, hybrid search and
+//! the embedding engine behind one [`MemoryClient`], for Rust applications
+//! that want memory without running the MCP or REST server.
+//!
+//! ```no_run
+//! use oc_memory::{Memory, MemoryClient, MemoryMetadata, SearchQuery};
+//!
+//! let client = MemoryClient::open("/var/lib/my-agent/memory")?;
+//! let memory = Memory::new(
+//!     "Deploys go through the staging cluster first".to_string(),
+//!     "Deploy process".to_string(),
+//!     MemoryMetadata::default(),
+//! );
+//! client.store(memory)?;
+//! let results = client.search(&SearchQuery {
+//!     query: "how do we deploy".to_string(),
+//!     ..Default::default()
+//! })?;
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+use anyhow::Result;
+use oc_core::consolidate::{self, ConsolidationReport, ExtractiveSummarizer};
+use oc_core::quarantine::QuarantinePolicy;
+use oc_core::tiering::{self, TieringReport};
+use oc_core::{Storage, expiry};
+use oc_embeddings::chunker::embed_chunks;
+use oc_search::bm25::Bm25Index;
+use oc_search::hybrid::HybridSearch;
+use oc_search::keywords::KeywordExtractor;
+use oc_search::profile::Profiles;
+use oc_search::query_cache::QueryCache;
+use oc_search::scoring::Scorer;
+use oc_search::shards::VectorShards;
+use oc_search::stopwords;
+use oc_search::synonyms::Synonyms;
+use oc_search::vector::VectorIndex;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+pub use oc_core::models::{
+    DuplicateAction, DuplicateMatch, Escalation, Memory, MemoryMetadata, MemoryType, Priority,
+    SearchQuery, SearchResult,
+};
+pub use oc_core::{Config, Error};
+pub use oc_embeddings::{Embedder, EmbeddingError};
+
+/// Outcome of [`MemoryClient::store`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoreOutcome {
+    /// The stored memory, or the existing one it was merged into or
+    /// rejected as a duplicate of
+    pub id: String,
+    /// False when `[dedup]` rejected the memory as a near-duplicate
+    pub stored: bool,
+    pub has_embedding: bool,
+    /// Near-duplicate found on store, per `[dedup]`
+    pub duplicate: Option<DuplicateMatch>,
+    /// Why `[quarantine]` held the memory back from search and retrieval
+    pub quarantined: Option<String>,
+    /// Stored memory on the same topic whose priority this store raised,
+    /// per `[escalation]`
+    pub escalation: Option<Escalation>,
+}
+
+/// What [`MemoryClient::maintain`] changed
+#[derive(Debug, Clone, Default)]
+pub struct MaintenanceReport {
+    /// Cold memories whose content was compressed
+    pub compressed: usize,
+    pub tiers: TieringReport,
+    /// Expired memories archived or deleted
+    pub expired: Vec<String>,
+}
+
+/// An open memory store. Storage, search indexes and the embedding engine
+/// share its lifetime; the vector index is saved when it is dropped.
+pub struct MemoryClient {
+    storage: Arc<Storage>,
+    search: Mutex<HybridSearch>,
+    embedder: Option<Arc<dyn Embedder>>,
+    keywords: Option<KeywordExtractor>,
+    quarantine: Option<QuarantinePolicy>,
+    config: Config,
+}
+
+impl MemoryClient {
+    /// Open (or create) a store in `data_dir` with default settings
+    pub fn open(data_dir: impl AsRef<Path>) -> Result<Self> {
+        let mut config = Config::default();
+        config.storage.data_dir = data_dir.as_ref().to_string_lossy().to_string();
+        Self::open_with_config(config)
+    }
+
+    /// Open the store described by `config`, loading its embedding engine.
+    /// Without a usable engine searches run keyword-only.
+    pub fn open_with_config(config: Config) -> Result<Self> {
+        let embedder = match oc_embeddings::create_engine(&config.embedding) {
+            Ok(engine) => Some(engine),
+            Err(e) => {
+                tracing::warn!("Embedding engine not available: {e}");
+                None
+            }
+        };
+        Self::with_embedder(config, embedder)
+    }
+
+    /// Open the store described by `config` with a caller-provided
+    /// embedding engine (or none)
+    pub fn with_embedder(config: Config, embedder: Option<Arc<dyn Embedder>>) -> Result<Self> {
+        std::fs::create_dir_all(config.data_dir())?;
+        let storage = Arc::new(Storage::open_with_config(
+            config.db_path(),
+            &config.storage,
+        )?);
+
+        let tantivy_path = config.tantivy_dir();
+        std::fs::create_dir_all(&tantivy_path)?;
+        let synonyms = Synonyms::load(config.synonyms_path())?;
+        let bm25_index = Bm25Index::new_with_tokenizer(&tantivy_path, config.search.tokenizer)?
+            .with_synonyms(synonyms)
+            .with_stopwords(&stopwords::from_config(&config.search))?;
+        let vector_index = VectorIndex::new(config.embedding.dimensions);
+        let mut search = HybridSearch::new(
+            Arc::clone(&storage),
+            vector_index,
+            bm25_index,
+            Scorer::from(&config.search),
+        );
+        search.set_profiles(Profiles::from_config(&config.search)?);
+        search.set_query_cache(QueryCache::from_config(&config.search));
+        search.set_vector_shards(VectorShards::from_config(&config));
+
+        if !search.load_vector_index(config.vector_index_path())? {
+            search.index_stored_vectors()?;
+        }
+        search.rebuild_text_index(&storage.all_text_data()?)?;
+
+        Ok(Self {
+            storage,
+            search: Mutex::new(search),
+            embedder,
+            keywords: config.keywords.enabled.then(KeywordExtractor::new),
+            quarantine: QuarantinePolicy::from_config(&config.quarantine),
+            config,
+        })
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// The underlying storage, for operations the client does not wrap
+    pub fn storage(&self) -> &Arc<Storage> {
+        &self.storage
+    }
+
+    /// Embed, deduplicate (per `[dedup]`), store and index a memory.
+    /// Concepts are extracted when the memory has none and `[keywords]`
+    /// is enabled. Memories `[quarantine]` flags are stored quarantined,
+    /// never merged into an existing memory.
+    ///
+    /// Fails with [`EmbeddingError::Busy`] when `[embedding.limits]` has no
+    /// room for the call; nothing is stored then.
+    pub fn store(&self, mut memory: Memory) -> Result<StoreOutcome> {
+        let embedding = match self.embedder.as_ref().map(|e| e.embed(&memory.content)) {
+            Some(Ok(embedding)) => Some(embedding),
+            Some(Err(err @ EmbeddingError::Busy { .. })) => return Err(err.into()),
+            Some(Err(_)) | None => None,
+        };
+        let chunks = match (&self.embedder, &embedding) {
+            (Some(e), Some(_)) => {
+                match embed_chunks(e.as_ref(), &memory.content, &self.config.embedding.chunking) {
+                    Ok(chunks) => chunks,
+                    Err(err @ EmbeddingError::Busy { .. }) => return Err(err.into()),
+                    Err(err) => {
+                        tracing::warn!("Chunk embedding failed: {err}");
+                        Vec::new()
+                    }
+                }
+            }
+            _ => Vec::new(),
+        };
+        if let Some(keywords) = &self.keywords {
+            keywords.fill_concepts(&mut memory, self.config.keywords.max_concepts);
+        }
+        if embedding.is_some() {
+            memory.embedding_model = self.embedder.as_ref().map(|e| e.model_id().to_string());
+        }
+        memory.embedding = embedding;
+        let has_embedding = memory.embedding.is_some();
+        let quarantined = self.quarantine.as_ref().and_then(|q| q.check(&memory));
+
+        let dedup = &self.config.dedup;
+        // A repeat counts whether the memory is then stored, merged or rejected
+        let (duplicate, escalation) = match &memory.embedding {
+            Some(embedding) if quarantined.is_none() => {
+                let index = self.index()?;
+                let duplicate = dedup
+                    .enabled
+                    .then(|| index.find_duplicate(embedding, dedup.threshold, &memory.namespace))
+                    .flatten()
+                    .map(|(id, similarity)| DuplicateMatch {
+                        id,
+                        similarity,
+                        action: dedup.action,
+                    });
+                let escalation = index.record_topic_repeat(
+                    embedding,
+                    &memory.namespace,
+                    &self.config.escalation,
+                )?;
+                (duplicate, escalation)
+            }
+            _ => (None, None),
+        };
+        match &duplicate {
+            Some(found) if found.action == DuplicateAction::Reject => {
+                return Ok(StoreOutcome {
+                    id: found.id.clone(),
+                    stored: false,
+                    has_embedding,
+                    duplicate,
+                    quarantined: None,
+                    escalation,
+                });
+            }
+            // The index can briefly outlive a deleted memory; store anew then
+            Some(found)
+                if found.action == DuplicateAction::Merge
+                    && self.storage.merge_duplicate(&found.id, &memory)?.is_some() =>
+            {
+                return Ok(StoreOutcome {
+                    id: found.id.clone(),
+                    stored: true,
+                    has_embedding,
+                    duplicate,
+                    quarantined: None,
+                    escalation,
+                });
+            }
+            _ => {}
+        }
+
+        self.storage.insert(&memory)?;
+        if let Some(reason) = &quarantined {
+            self.storage.quarantine(&memory.id, reason)?;
+        }
+        if !chunks.is_empty() {
+            self.storage.replace_chunk_embeddings(&memory.id, &chunks)?;
+        }
+        let mut search = self.index()?;
+        search.index_memory(&memory)?;
+        search.index_chunks(&memory.id, &chunks)?;
+        Ok(StoreOutcome {
+            id: memory.id,
+            stored: true,
+            has_embedding,
+            duplicate: duplicate.filter(|d| d.action == DuplicateAction::Flag),
+            quarantined,
+            escalation,
+        })
+    }
+
+    /// Hybrid search, merging the results of `query.alt_queries` by rank.
+    /// Without an embedding engine (or when embedding the query fails)
+    /// results are keyword-only and marked degraded. A saturated embedder
+    /// fails the search with [`EmbeddingError::Busy`] instead.
+    pub fn search(&self, query: &SearchQuery) -> Result<Vec<SearchResult>> {
+        let texts: Vec<&str> = query.texts().collect();
+        let query_embeddings = match self.embedder.as_ref().map(|e| e.embed_batch(&texts)) {
+            Some(Ok(embeddings)) => embeddings,
+            Some(Err(err @ EmbeddingError::Busy { .. })) => return Err(err.into()),
+            Some(Err(err)) => {
+                tracing::warn!("Query embedding failed: {err}, searching keyword-only");
+                Vec::new()
+            }
+            None => Vec::new(),
+        };
+        let embeddings: Vec<&[f32]> = query_embeddings.iter().map(Vec::as_slice).collect();
+        let mut index = self.index()?;
+        let results = index.search_fused(&embeddings, query)?;
+        if let Err(e) = index.record_miss(query, &results, &self.config.misses) {
+            tracing::warn!("Failed to record missed query: {e}");
+        }
+        Ok(results)
+    }
+
+    /// Did-you-mean corrections of a query whose keywords match nothing,
+    /// up to `[search] suggestions`
+    pub fn suggest(&self, query: &str) -> Result<Vec<String>> {
+        self.index()?.suggest(query, self.config.search.suggestions)
+    }
+
+    /// Memories most like the one with this ID, excluding itself; `None`
+    /// when no memory has the ID
+    pub fn related(&self, id: &str, limit: usize) -> Result<Option<Vec<SearchResult>>> {
+        let Some(memory) = self.storage.get(id)? else {
+            return Ok(None);
+        };
+        Ok(Some(self.index()?.related(&memory, limit)?))
+    }
+
+    /// A memory by ID, counted as accessed; `None` while it is quarantined
+    pub fn get(&self, id: &str) -> Result<Option<Memory>> {
+        let ids = [id.to_string()];
+        if !self.storage.quarantined_among(&ids)?.is_empty() {
+            return Ok(None);
+        }
+        let memory = self.storage.get(id)?;
+        if memory.is_some() {
+            self.storage.touch(id)?;
+            self.index()?.record_fetch(&[id.to_string()]);
+        }
+        Ok(memory)
+    }
+
+    /// Delete a memory and drop it from the search indexes. Returns false
+    /// when no memory had the ID.
+    pub fn delete(&self, id: &str) -> Result<bool> {
+        self.index()?.remove_memory(id)?;
+        Ok(self.storage.delete(id)?)
+    }
+
+    /// The periodic upkeep the servers do at startup and on a schedule:
+    /// compress cold content, enforce hot/cold tiers and sweep expired
+    /// memories out of storage and the search indexes
+    pub fn maintain(&self) -> Result<MaintenanceReport> {
+        let compressed = self
+            .storage
+            .compress_cold(self.config.storage.compress_after_days)?;
+        let tiers = tiering::enforce_tiers(&self.storage, &self.config.storage)?;
+        let expired = expiry::sweep(&self.storage, &self.config.expiry)?;
+        let mut search = self.index()?;
+        for id in &expired {
+            search.remove_memory(id)?;
+        }
+        Ok(MaintenanceReport {
+            compressed,
+            tiers,
+            expired,
+        })
+    }
+
+    /// Merge related memories per `[consolidation]` with the extractive
+    /// summarizer, moving the search indexes to the consolidated memories
+    pub fn consolidate(&self) -> Result<ConsolidationReport> {
+        let summarizer = ExtractiveSummarizer::new(self.config.consolidation.target_type);
+        let report = consolidate::consolidate(
+            &self.storage,
+            &self.config.consolidation,
+            &summarizer,
+            |text| self.embedder.as_ref().and_then(|e| e.embed(text).ok()),
+            self.embedder.as_ref().map(|e| e.model_id()),
+        )?;
+        let mut search = self.index()?;
+        for group in &report.groups {
+            for id in &group.sources {
+                search.remove_memory(id)?;
+            }
+            if let Some(memory) = self.storage.get(&group.id)? {
+                search.index_memory(&memory)?;
+            }
+        }
+        Ok(report)
+    }
+
+    /// Persist the vector index so the next open skips the rebuild. Also
+    /// done on drop.
+    pub fn save(&self) -> Result<()> {
+        self.index()?
+            .save_vector_index(self.config.vector_index_path())
+    }
+
+    fn index(&self) -> Result<MutexGuard<'_, HybridSearch>> {
+        self.search.lock().map_err(|e| anyhow::anyhow!("Lock: {e}"))
+    }
+}
+
+impl Drop for MemoryClient {
+    fn drop(&mut self) {
+        if let Err(e) = self.save() {
+            tracing::warn!("Failed to save vector index: {e}");
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::path::{Path, PathBuf};
+
+/// `[observer] ignore_patterns` in gitignore syntax, applied relative to
+/// each watched directory
+#[derive(Default)]
+pub struct ExcludeRules {
+    roots: Vec<(PathBuf, Gitignore)>,
+}
+
+impl ExcludeRules {
+    /// Rules for every directory in `dirs`. Invalid patterns are logged and
+    /// skipped.
+    pub fn new(dirs: &[PathBuf], patterns: &[String]) -> Self {
+        if patterns.is_empty() {
+            return Self::default();
+        }
+        let roots = dirs
+            .iter()
+            .filter_map(|dir| {
+                let mut builder = GitignoreBuilder::new(dir);
+                for pattern in patterns {
+                    if let Err(e) = builder.add_line(None, pattern) {
+                        tracing::warn!("Skipping invalid ignore pattern {pattern:?}: {e}");
+                    }
+                }
+                match builder.build() {
+                    Ok(rules) => Some((dir.clone(), rules)),
+                    Err(e) => {
+                        tracing::warn!(dir = %dir.display(), "Ignore patterns not applied: {e}");
+                        None
+                    }
+                }
+            })
+            .collect();
+        Self { roots }
+    }
+
+    /// Whether `path`, or a directory it is in, matches a pattern
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        self.roots.iter().any(|(root, rules)| {
+            path.starts_with(root)
+                && rules
+                    .matched_path_or_any_parents(path, path.is_dir())
+                    .is_ignore()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_excludes_matching_paths_and_their_contents() {
+        let root = PathBuf::from("/notes");
+        let patterns: Vec<String> = [
+            ".git/",
+            "node_modules/",
+            "*.tmp.md",
+            "drafts/**",
+            "!drafts/keep.md",
+        ]
+        .iter()
+        .map(|p| p.to_string())
+        .collect();
+        let rules = ExcludeRules::new(std::slice::from_ref(&root), &patterns);
+
+        for excluded in [
+            "/notes/.git/COMMIT_EDITMSG.md",
+            "/notes/app/node_modules/pkg/README.md",
+            "/notes/scratch.tmp.md",
+            "/notes/drafts/idea.md",
+        ] {
+            assert!(rules.is_excluded(Path::new(excluded)), "{excluded}");
+        }
+        for kept in [
+            "/notes/rust.md",
+            "/notes/drafts/keep.md",
+            "/elsewhere/.git/x.md",
+        ] {
+            assert!(!rules.is_excluded(Path::new(kept)), "{kept}");
+        }
+        assert!(!ExcludeRules::default().is_excluded(Path::new("/notes/.git/x.md")));
+    }
+}
@@ -0,0 +1,145 @@
+use oc_core::models::MemoryPatch;
+use serde::de::DeserializeOwned;
+
+/// Split a leading YAML frontmatter block (`---` … `---` or `...`) off
+/// `text`, reading `title`, `type`, `priority` and `tags` from it into a
+/// patch. Other keys are ignored, as are values that are not a known type
+/// or priority. Without a closed block the whole text is the body.
+pub fn split_frontmatter(text: &str) -> (MemoryPatch, &str) {
+    let mut patch = MemoryPatch::default();
+    let Some(rest) = text
+        .strip_prefix("---\n")
+        .or_else(|| text.strip_prefix("---\r\n"))
+    else {
+        return (patch, text);
+    };
+
+    let mut offset = 0;
+    let mut block = None;
+    for line in rest.split_inclusive('\n') {
+        let bare = line.trim_end();
+        if bare == "---" || bare == "..." {
+            block = Some((&rest[..offset], &rest[offset + line.len()..]));
+            break;
+        }
+        offset += line.len();
+    }
+    let Some((yaml, body)) = block else {
+        return (patch, text);
+    };
+
+    let mut lines = yaml.lines().peekable();
+    while let Some(line) = lines.next() {
+        if line.starts_with([' ', '\t', '#']) {
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim().to_lowercase().as_str() {
+            "title" => {
+                let title = unquote(value);
+                if !title.is_empty() {
+                    patch.title = Some(title.to_string());
+                }
+            }
+            "type" | "memory_type" => patch.memory_type = parse_name(value),
+            "priority" => patch.priority = parse_name(value),
+            "tags" | "tag" => {
+                let mut tags = if value.is_empty() {
+                    // A block list on the following indented `- ` lines
+                    let mut items = Vec::new();
+                    while let Some(item) = lines
+                        .peek()
+                        .and_then(|next| next.trim_start().strip_prefix('-'))
+                    {
+                        items.push(unquote(item.trim()).to_string());
+                        lines.next();
+                    }
+                    items
+                } else if let Some(inline) =
+                    value.strip_prefix('[').and_then(|v| v.strip_suffix(']'))
+                {
+                    inline
+                        .split(',')
+                        .map(|t| unquote(t.trim()).to_string())
+                        .collect()
+                } else {
+                    unquote(value)
+                        .split([',', ' '])
+                        .map(str::to_string)
+                        .collect()
+                };
+                // Obsidian accepts `#tag` as well as `tag`
+                for tag in &mut tags {
+                    *tag = tag.trim().trim_start_matches('#').to_string();
+                }
+                tags.retain(|tag| !tag.is_empty());
+                patch.tags = Some(tags);
+            }
+            _ => {}
+        }
+    }
+    (patch, body)
+}
+
+/// `value` without its surrounding single or double quotes
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|v| v.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    value
+}
+
+/// A lowercase enum name such as `decision` or `high`
+fn parse_name<T: DeserializeOwned>(value: &str) -> Option<T> {
+    let name = unquote(value).trim().to_lowercase();
+    serde_json::from_value(serde_json::Value::String(name)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oc_core::models::{MemoryType, Priority};
+
+    #[test]
+    fn test_reads_title_type_priority_and_tags() {
+        let text = "---\ntitle: \"WAL 모드\"\ntype: Decision\npriority: high\ntags: [sqlite, \"#성능\"]\naliases: [wal]\n---\n\nWAL을 쓴다.\n";
+        let (patch, body) = split_frontmatter(text);
+        assert_eq!(patch.title.as_deref(), Some("WAL 모드"));
+        assert_eq!(patch.memory_type, Some(MemoryType::Decision));
+        assert_eq!(patch.priority, Some(Priority::High));
+        assert_eq!(
+            patch.tags,
+            Some(vec!["sqlite".to_string(), "성능".to_string()])
+        );
+        assert_eq!(body, "\nWAL을 쓴다.\n");
+    }
+
+    #[test]
+    fn test_block_list_and_unknown_values() {
+        let text = "---\ntags:\n  - rust\n  - '#async'\ntype: musing\n---\nbody";
+        let (patch, body) = split_frontmatter(text);
+        assert_eq!(
+            patch.tags,
+            Some(vec!["rust".to_string(), "async".to_string()])
+        );
+        assert_eq!(patch.memory_type, None);
+        assert_eq!(body, "body");
+    }
+
+    #[test]
+    fn test_text_without_closed_block_is_all_body() {
+        for text in ["plain note", "---\ntitle: open\nno end"] {
+            let (patch, body) = split_frontmatter(text);
+            assert!(patch.is_empty());
+            assert_eq!(body, text);
+        }
+    }
+}
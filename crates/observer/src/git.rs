@@ -0,0 +1,259 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use oc_core::config::expand_home;
+use oc_core::models::{Memory, MemoryMetadata, MemoryType};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::ingest::{IngestTarget, embed_for, index, screen};
+
+/// Separates commits in `git log` output
+const RECORD_SEP: char = '\u{1e}';
+/// Separates the fields of one commit
+const FIELD_SEP: char = '\u{1f}';
+
+/// One commit as read from `git log`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    pub hash: String,
+    pub committed_at: DateTime<Utc>,
+    pub message: String,
+    pub files: Vec<String>,
+}
+
+impl Commit {
+    /// First line of the message
+    pub fn subject(&self) -> &str {
+        self.message.lines().next().unwrap_or_default().trim()
+    }
+}
+
+/// Store the commits of `repo` not stored yet, newest `max_commits` at
+/// most, each as a `bugfix` or `decision` memory dated when it was
+/// committed. Nothing is read while HEAD has not moved since the last
+/// check. Returns how many commits were stored.
+pub fn ingest_commits<T: IngestTarget + ?Sized>(
+    target: &T,
+    repo: &Path,
+    max_commits: usize,
+) -> Result<usize> {
+    let storage = target.storage();
+    let head = git(repo, &["rev-parse", "HEAD"])?.trim().to_string();
+    let key = format!("git:{}", repo.display());
+    if storage.observed_file_hash(&key)?.as_deref() == Some(head.as_str()) {
+        return Ok(0);
+    }
+
+    let format = format!("--format={RECORD_SEP}%H{FIELD_SEP}%cI{FIELD_SEP}%B{FIELD_SEP}");
+    let log = git(
+        repo,
+        &["log", "--name-only", &format!("-n{max_commits}"), &format],
+    )?;
+    let mut stored = 0;
+    // Oldest first, so memories are stored in history order
+    for commit in parse_log(&log).into_iter().rev() {
+        let source = commit_source(repo, &commit.hash);
+        if storage.find_by_source(&source)?.is_some() {
+            continue;
+        }
+        let mut memory = commit_memory(&commit, source);
+        let (embedding, chunks) = embed_for(target, &memory.content);
+        if embedding.is_some() {
+            memory.embedding_model = target.embedder().map(|e| e.model_id().to_string());
+        }
+        memory.embedding = embedding;
+        storage.insert(&memory)?;
+        screen(target, &memory)?;
+        if !chunks.is_empty() {
+            storage.replace_chunk_embeddings(&memory.id, &chunks)?;
+        }
+        index(target, &memory, &chunks)?;
+        stored += 1;
+    }
+    storage.record_observed_file(&key, &head)?;
+    Ok(stored)
+}
+
+/// Check `[observer] git_repos` for new commits every `git_poll_seconds`
+/// in the background, starting at once. `None` when no repository is
+/// configured.
+pub fn spawn_git_ingestion<T: IngestTarget>(target: Arc<T>) -> Option<tokio::task::JoinHandle<()>> {
+    let observer = &target.config().observer;
+    if observer.git_repos.is_empty() {
+        return None;
+    }
+    let repos: Vec<PathBuf> = observer
+        .git_repos
+        .iter()
+        .map(|repo| expand_home(repo))
+        .collect();
+    let seconds = observer.git_poll_seconds.max(1);
+    let max_commits = observer.git_max_commits;
+
+    tracing::info!(repos = repos.len(), "Git commit ingestion enabled");
+    Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(seconds));
+        loop {
+            interval.tick().await;
+            for repo in &repos {
+                let target = Arc::clone(&target);
+                let path = repo.clone();
+                let task = tokio::task::spawn_blocking(move || {
+                    ingest_commits(target.as_ref(), &path, max_commits)
+                });
+                match task.await {
+                    Ok(Ok(0)) => {}
+                    Ok(Ok(stored)) => {
+                        tracing::info!(repo = %repo.display(), stored, "Stored new commits")
+                    }
+                    Ok(Err(e)) => {
+                        tracing::warn!(repo = %repo.display(), "Failed to read commits: {e}")
+                    }
+                    Err(e) => tracing::warn!("Git ingestion task failed: {e}"),
+                }
+            }
+        }
+    }))
+}
+
+/// The memory of `commit`: its message followed by the changed files
+pub fn commit_memory(commit: &Commit, source: String) -> Memory {
+    let mut content = commit.message.trim().to_string();
+    if !commit.files.is_empty() {
+        content.push_str("\n\nFiles changed:\n");
+        for file in &commit.files {
+            content.push_str(&format!("- {file}\n"));
+        }
+    }
+    let short: String = commit.hash.chars().take(8).collect();
+    let mut memory = Memory::new(
+        content.trim_end().to_string(),
+        format!("{} ({short})", commit.subject()),
+        MemoryMetadata {
+            memory_type: commit_type(commit.subject()),
+            tags: vec!["git".to_string(), "commit".to_string()],
+            files: commit.files.clone(),
+            source: Some(source),
+            ..Default::default()
+        },
+    );
+    memory.created_at = commit.committed_at;
+    memory.updated_at = commit.committed_at;
+    memory
+}
+
+/// `bugfix` for a subject like `fix: …`, `fix(parser): …`, `Hotfix …` or
+/// one mentioning a bug; `decision` otherwise
+pub fn commit_type(subject: &str) -> MemoryType {
+    let subject = subject.to_lowercase();
+    let first = subject
+        .split(|c: char| !c.is_alphanumeric())
+        .next()
+        .unwrap_or_default();
+    let mentions_bug = subject
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| matches!(word, "bug" | "bugs" | "bugfix"));
+    if matches!(first, "fix" | "fixes" | "fixed" | "bugfix" | "hotfix") || mentions_bug {
+        MemoryType::Bugfix
+    } else {
+        MemoryType::Decision
+    }
+}
+
+/// Commits in `git log --name-only` output written with [`RECORD_SEP`]
+/// and [`FIELD_SEP`] around hash, commit date and message
+pub fn parse_log(output: &str) -> Vec<Commit> {
+    output
+        .split(RECORD_SEP)
+        .filter_map(|record| {
+            let mut fields = record.splitn(4, FIELD_SEP);
+            let hash = fields.next()?.trim();
+            let committed_at = DateTime::parse_from_rfc3339(fields.next()?.trim()).ok()?;
+            let message = fields.next()?.trim();
+            let files = fields
+                .next()
+                .unwrap_or_default()
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect();
+            (!hash.is_empty()).then(|| Commit {
+                hash: hash.to_string(),
+                committed_at: committed_at.with_timezone(&Utc),
+                message: message.to_string(),
+                files,
+            })
+        })
+        .collect()
+}
+
+fn commit_source(repo: &Path, hash: &str) -> String {
+    format!("{}@{hash}", repo.display())
+}
+
+fn git(repo: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_reads_message_and_files() {
+        let log = format!(
+            "{RECORD_SEP}aaa111{FIELD_SEP}2026-03-01T10:00:00+09:00{FIELD_SEP}fix: handle empty query\n\nLonger body.\n{FIELD_SEP}\n\nsrc/search.rs\ntests/search.rs\n\
+             {RECORD_SEP}bbb222{FIELD_SEP}2026-02-28T09:00:00Z{FIELD_SEP}Switch to WAL mode\n{FIELD_SEP}\n"
+        );
+        let commits = parse_log(&log);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].hash, "aaa111");
+        assert_eq!(commits[0].subject(), "fix: handle empty query");
+        assert_eq!(commits[0].files, vec!["src/search.rs", "tests/search.rs"]);
+        assert_eq!(
+            commits[0].committed_at.to_rfc3339(),
+            "2026-03-01T01:00:00+00:00"
+        );
+        assert!(commits[1].files.is_empty());
+    }
+
+    #[test]
+    fn test_commit_memory_is_typed_and_dated() {
+        let commit = Commit {
+            hash: "0123456789abcdef".to_string(),
+            committed_at: "2026-03-01T01:00:00Z".parse().unwrap(),
+            message: "fix(parser): reject empty tags".to_string(),
+            files: vec!["src/parser.rs".to_string()],
+        };
+        let memory = commit_memory(&commit, "repo@0123456789abcdef".to_string());
+        assert_eq!(memory.title, "fix(parser): reject empty tags (01234567)");
+        assert_eq!(memory.metadata.memory_type, MemoryType::Bugfix);
+        assert_eq!(memory.metadata.files, vec!["src/parser.rs"]);
+        assert!(memory.content.ends_with("Files changed:\n- src/parser.rs"));
+        assert_eq!(memory.created_at, commit.committed_at);
+
+        assert_eq!(commit_type("Hotfix for login"), MemoryType::Bugfix);
+        assert_eq!(commit_type("Work around bug in notify"), MemoryType::Bugfix);
+        assert_eq!(
+            commit_type("Use RRF fusion by default"),
+            MemoryType::Decision
+        );
+        assert_eq!(commit_type("Prefix tags"), MemoryType::Decision);
+    }
+}
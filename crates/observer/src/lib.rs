@@ -1,3 +1,11 @@
+pub mod exclude;
+pub mod frontmatter;
+pub mod git;
+pub mod hook;
+pub mod ingest;
+pub mod sections;
 pub mod watcher;
 
+pub use git::spawn_git_ingestion;
+pub use ingest::{IngestTarget, spawn_ingestion};
 pub use watcher::FileObserver;
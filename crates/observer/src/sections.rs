@@ -0,0 +1,121 @@
+use oc_embeddings::chunker::chunk_text;
+
+/// A piece of an observed file, stored as its own memory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    /// Headings of the sections joined into the piece, in order; empty
+    /// for text under no heading
+    pub headings: Vec<String>,
+    pub text: String,
+}
+
+/// Split `content` into pieces of at most `max_chars` characters: at
+/// Markdown headings (outside code fences) first, joining neighbouring
+/// sections that fit together, then at sentence boundaries for sections
+/// still too long. Content within `max_chars`, or any content when
+/// `max_chars` is 0, is one piece.
+pub fn split_sections(content: &str, max_chars: usize) -> Vec<Section> {
+    if max_chars == 0 || content.chars().count() <= max_chars {
+        return vec![Section {
+            headings: Vec::new(),
+            text: content.to_string(),
+        }];
+    }
+
+    let mut sections: Vec<(Option<String>, String)> = Vec::new();
+    let mut fenced = false;
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fenced = !fenced;
+        }
+        let heading = if fenced { None } else { heading_of(line) };
+        match (heading, sections.last_mut()) {
+            (None, Some((_, text))) => text.push_str(line),
+            (heading, _) => sections.push((heading, line.to_string())),
+        }
+    }
+
+    let mut pieces: Vec<(Section, usize)> = Vec::new();
+    for (heading, text) in sections {
+        let len = text.chars().count();
+        if len > max_chars {
+            for text in chunk_text(&text, max_chars) {
+                let len = text.chars().count();
+                pieces.push((
+                    Section {
+                        headings: heading.iter().cloned().collect(),
+                        text,
+                    },
+                    len,
+                ));
+            }
+            continue;
+        }
+        match pieces.last_mut() {
+            Some((last, last_len)) if *last_len + len <= max_chars => {
+                last.headings.extend(heading);
+                last.text.push_str(&text);
+                *last_len += len;
+            }
+            _ => pieces.push((
+                Section {
+                    headings: heading.into_iter().collect(),
+                    text,
+                },
+                len,
+            )),
+        }
+    }
+
+    pieces
+        .into_iter()
+        .filter_map(|(mut section, _)| {
+            section.text = section.text.trim().to_string();
+            (!section.text.is_empty()).then_some(section)
+        })
+        .collect()
+}
+
+/// The text of a Markdown ATX heading line (`#` to `######`)
+fn heading_of(line: &str) -> Option<String> {
+    let line = line.trim_end();
+    let text = line.trim_start_matches('#');
+    let level = line.len() - text.len();
+    if !(1..=6).contains(&level) || !text.starts_with(' ') {
+        return None;
+    }
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_content_is_one_piece() {
+        let sections = split_sections("# A\n\nshort", 100);
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].text, "# A\n\nshort");
+        assert_eq!(split_sections(&"x".repeat(500), 0).len(), 1);
+    }
+
+    #[test]
+    fn test_splits_at_headings_and_joins_small_sections() {
+        let content = format!(
+            "{}# One\n{}\n## Two\nsmall\n## Three\ntiny\n```\n# not a heading\n```\n# Four\n{}",
+            "intro\n".repeat(3),
+            "a".repeat(40),
+            "b. ".repeat(30)
+        );
+        let sections = split_sections(&content, 60);
+        let headings: Vec<_> = sections.iter().map(|s| s.headings.join(", ")).collect();
+        assert_eq!(headings, vec!["", "One, Two", "Three", "Four", "Four"]);
+        // "Two" fits after "One" and keeps its heading
+        assert!(sections[1].text.contains("## Two"));
+        // The fenced heading stays in the text of "Three"
+        assert!(sections[2].text.contains("# not a heading"));
+        assert!(sections.iter().all(|s| s.text.chars().count() <= 60));
+    }
+}
@@ -1,13 +1,20 @@
 use anyhow::Result;
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
+use crate::exclude::ExcludeRules;
+
 /// File system observer for automatic memory ingestion
 pub struct FileObserver {
     watch_dirs: Vec<PathBuf>,
     extensions: Vec<String>,
     recursive: bool,
+    debounce: Duration,
+    exclude: Arc<ExcludeRules>,
 }
 
 /// Event emitted when a relevant file changes
@@ -17,7 +24,7 @@ pub struct FileEvent {
     pub event_type: FileEventType,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FileEventType {
     Created,
     Modified,
@@ -29,6 +36,57 @@ impl FileObserver {
             watch_dirs,
             extensions,
             recursive,
+            debounce: Duration::ZERO,
+            exclude: Arc::default(),
+        }
+    }
+
+    /// Never report files matching `patterns` (gitignore syntax, relative
+    /// to each watched directory), or files in directories that match
+    pub fn with_ignore_patterns(mut self, patterns: &[String]) -> Self {
+        self.exclude = Arc::new(ExcludeRules::new(&self.watch_dirs, patterns));
+        self
+    }
+
+    /// Report a path only once `debounce` has passed since its last event,
+    /// as one event (`Created` if any of them was)
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Files already in the watched directories that events would be
+    /// reported for, for an initial scan
+    pub fn existing_files(&self) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        for dir in &self.watch_dirs {
+            self.collect_files(dir, &mut files);
+        }
+        files
+    }
+
+    fn collect_files(&self, dir: &Path, files: &mut Vec<PathBuf>) {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::warn!(dir = %dir.display(), "Cannot scan directory: {e}");
+                return;
+            }
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            // Symlinked directories are not followed, so a link loop cannot
+            // recurse forever
+            match entry.file_type() {
+                Ok(kind) if kind.is_dir() && self.recursive && !self.exclude.is_excluded(&path) => {
+                    self.collect_files(&path, files);
+                }
+                Ok(kind) if kind.is_dir() => {}
+                Ok(_) if is_relevant_file(&path, &self.extensions, &self.exclude) => {
+                    files.push(path)
+                }
+                _ => {}
+            }
         }
     }
 
@@ -36,6 +94,8 @@ impl FileObserver {
     pub async fn watch(&self) -> Result<mpsc::Receiver<FileEvent>> {
         let (tx, rx) = mpsc::channel(100);
         let extensions = self.extensions.clone();
+        let exclude = Arc::clone(&self.exclude);
+        let mut debouncer = Debouncer::new(self.debounce);
 
         let (notify_tx, mut notify_rx) = mpsc::channel(100);
 
@@ -67,7 +127,25 @@ impl FileObserver {
         tokio::spawn(async move {
             let _watcher = watcher; // Keep watcher alive
 
-            while let Some(event) = notify_rx.recv().await {
+            loop {
+                let event = match debouncer.next_due() {
+                    Some(due) => tokio::select! {
+                        event = notify_rx.recv() => event,
+                        _ = tokio::time::sleep_until(due.into()) => {
+                            for file_event in debouncer.take_due(Instant::now()) {
+                                if tx.send(file_event).await.is_err() {
+                                    return; // Receiver dropped
+                                }
+                            }
+                            continue;
+                        }
+                    },
+                    None => notify_rx.recv().await,
+                };
+                let Some(event) = event else {
+                    break;
+                };
+
                 let event_type = match event.kind {
                     EventKind::Create(_) => Some(FileEventType::Created),
                     EventKind::Modify(_) => Some(FileEventType::Modified),
@@ -76,17 +154,23 @@ impl FileObserver {
 
                 if let Some(event_type) = event_type {
                     for path in event.paths {
-                        if is_relevant_file(&path, &extensions) {
-                            let file_event = FileEvent {
-                                path,
-                                event_type: event_type.clone(),
-                            };
-                            if tx.send(file_event).await.is_err() {
-                                return; // Receiver dropped
-                            }
+                        if is_relevant_file(&path, &extensions, &exclude) {
+                            debouncer.push(path, event_type.clone(), Instant::now());
                         }
                     }
                 }
+                for file_event in debouncer.take_due(Instant::now()) {
+                    if tx.send(file_event).await.is_err() {
+                        return; // Receiver dropped
+                    }
+                }
+            }
+
+            // Watcher gone: report what is still pending
+            for file_event in debouncer.take_due(Instant::now() + debouncer.window) {
+                if tx.send(file_event).await.is_err() {
+                    return;
+                }
             }
         });
 
@@ -94,9 +178,136 @@ impl FileObserver {
     }
 }
 
-fn is_relevant_file(path: &Path, extensions: &[String]) -> bool {
+fn is_relevant_file(path: &Path, extensions: &[String], exclude: &ExcludeRules) -> bool {
     path.extension()
         .and_then(|ext| ext.to_str())
         .map(|ext| extensions.iter().any(|e| e == ext))
         .unwrap_or(false)
+        && !exclude.is_excluded(path)
+}
+
+/// Events held back until their path has been quiet for `window`, one per
+/// path
+struct Debouncer {
+    window: Duration,
+    pending: HashMap<PathBuf, (FileEventType, Instant)>,
+}
+
+impl Debouncer {
+    fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Record an event at `now`, pushing its path's due time back. A path
+    /// created in the window stays `Created` whatever follows.
+    fn push(&mut self, path: PathBuf, event_type: FileEventType, now: Instant) {
+        let due = now + self.window;
+        self.pending
+            .entry(path)
+            .and_modify(|(pending, pending_due)| {
+                if event_type == FileEventType::Created {
+                    *pending = FileEventType::Created;
+                }
+                *pending_due = due;
+            })
+            .or_insert((event_type, due));
+    }
+
+    /// When the next held event is due
+    fn next_due(&self) -> Option<Instant> {
+        self.pending.values().map(|(_, due)| *due).min()
+    }
+
+    /// Remove and return the events due by `now`, oldest first
+    fn take_due(&mut self, now: Instant) -> Vec<FileEvent> {
+        let mut due: Vec<(Instant, FileEvent)> = Vec::new();
+        self.pending.retain(|path, (event_type, at)| {
+            if *at > now {
+                return true;
+            }
+            due.push((
+                *at,
+                FileEvent {
+                    path: path.clone(),
+                    event_type: event_type.clone(),
+                },
+            ));
+            false
+        });
+        due.sort_by_key(|(at, _)| *at);
+        due.into_iter().map(|(_, event)| event).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_existing_files_apply_extensions_and_ignore_patterns() {
+        let dir = std::env::temp_dir().join(format!("oc_scan_{}", std::process::id()));
+        for sub in ["notes/deep", "node_modules/pkg"] {
+            std::fs::create_dir_all(dir.join(sub)).unwrap();
+        }
+        for file in [
+            "top.md",
+            "notes/deep/nested.txt",
+            "notes/image.png",
+            "node_modules/pkg/README.md",
+        ] {
+            std::fs::write(dir.join(file), "x").unwrap();
+        }
+        let observer = |recursive| {
+            FileObserver::new(
+                vec![dir.clone()],
+                vec!["md".into(), "txt".into()],
+                recursive,
+            )
+            .with_ignore_patterns(&["node_modules/".to_string()])
+        };
+
+        let mut files = observer(true).existing_files();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![dir.join("notes/deep/nested.txt"), dir.join("top.md")]
+        );
+        assert_eq!(observer(false).existing_files(), vec![dir.join("top.md")]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_debouncer_coalesces_bursts_per_path() {
+        let window = Duration::from_millis(100);
+        let mut debouncer = Debouncer::new(window);
+        let start = Instant::now();
+        let note = PathBuf::from("note.md");
+        let other = PathBuf::from("other.md");
+
+        debouncer.push(note.clone(), FileEventType::Created, start);
+        debouncer.push(note.clone(), FileEventType::Modified, start + window / 2);
+        debouncer.push(other.clone(), FileEventType::Modified, start + window / 4);
+        assert!(debouncer.take_due(start + window).is_empty());
+        assert_eq!(debouncer.next_due(), Some(start + window + window / 4));
+
+        let due = debouncer.take_due(start + window * 2);
+        assert_eq!(due.len(), 2);
+        assert_eq!(due[0].path, other);
+        assert_eq!(due[1].path, note);
+        assert_eq!(due[1].event_type, FileEventType::Created);
+        assert_eq!(debouncer.next_due(), None);
+    }
+
+    #[test]
+    fn test_zero_window_reports_every_event() {
+        let mut debouncer = Debouncer::new(Duration::ZERO);
+        let now = Instant::now();
+        debouncer.push(PathBuf::from("a.md"), FileEventType::Modified, now);
+        assert_eq!(debouncer.take_due(now).len(), 1);
+        debouncer.push(PathBuf::from("a.md"), FileEventType::Modified, now);
+        assert_eq!(debouncer.take_due(now).len(), 1);
+    }
 }
@@ -1,148 +1,328 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use lindera::dictionary::{DictionaryKind, load_dictionary_from_kind};
 use lindera::mode::Mode;
 use lindera::segmenter::Segmenter;
 use lindera_tantivy::tokenizer::LinderaTokenizer;
+use oc_core::config::Tokenizer;
+use std::collections::HashMap;
 use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 use tantivy::collector::TopDocs;
-use tantivy::query::QueryParser;
+use tantivy::query::{BooleanQuery, Occur, PhraseQuery, Query, QueryParser, TermQuery};
 use tantivy::schema::*;
-use tantivy::{Index, IndexWriter, ReloadPolicy, doc};
+use tantivy::tokenizer::{
+    LowerCaser, NgramTokenizer, SimpleTokenizer, StopWordFilter, TextAnalyzer, TokenStream,
+};
+use tantivy::{Index, IndexReader, IndexWriter, InvertedIndexReader, ReloadPolicy, doc};
 
-const KOREAN_TOKENIZER_NAME: &str = "korean";
+use crate::suggest::{edit_distance, max_edits, spelling};
+use crate::synonyms::Synonyms;
 
-/// Build a Korean morphological tokenizer using lindera ko-dic.
+/// Name the analyzer of `tokenizer` is registered under, and so recorded
+/// in the schema of indexes built with it
+fn tokenizer_name(tokenizer: Tokenizer) -> &'static str {
+    match tokenizer {
+        // Indexes from before the option exist under this name
+        Tokenizer::KoDic => "korean",
+        Tokenizer::Ipadic => "ipadic",
+        Tokenizer::CcCedict => "cc-cedict",
+        Tokenizer::Unicode => "unicode",
+        Tokenizer::Ngram => "ngram",
+    }
+}
+
+/// Build a morphological tokenizer from a lindera dictionary.
 ///
-/// This tokenizer splits agglutinative Korean text into morphemes:
+/// With ko-dic this splits agglutinative Korean text into morphemes:
 ///   "한국어로" → ["한국어", "로"]
 ///   "프로그래밍을" → ["프로그래밍", "을"]
-fn build_korean_tokenizer() -> LinderaTokenizer {
-    let dictionary =
-        load_dictionary_from_kind(DictionaryKind::KoDic).expect("Failed to load ko-dic dictionary");
+fn build_lindera_tokenizer(kind: DictionaryKind, feature: &str) -> Result<LinderaTokenizer> {
+    let dictionary = load_dictionary_from_kind(kind).with_context(|| {
+        format!(
+            "Failed to load the {feature} dictionary; \
+             is oc-search built with the {feature} feature?"
+        )
+    })?;
     let segmenter = Segmenter::new(Mode::Normal, dictionary, None);
-    LinderaTokenizer::from_segmenter(segmenter)
+    Ok(LinderaTokenizer::from_segmenter(segmenter))
 }
 
-/// Register the Korean tokenizer on a tantivy Index.
-fn register_korean_tokenizer(index: &Index) {
+/// Register the analyzer of `tokenizer` on a tantivy Index: its terms
+/// lowercased, then `stopwords` dropped.
+fn register_tokenizer(index: &Index, tokenizer: Tokenizer, stopwords: &[String]) -> Result<()> {
+    let stop = || StopWordFilter::remove(stopwords.to_vec());
+    let analyzer = match tokenizer {
+        Tokenizer::KoDic => {
+            TextAnalyzer::builder(build_lindera_tokenizer(DictionaryKind::KoDic, "ko-dic")?)
+                .filter(LowerCaser)
+                .filter(stop())
+                .build()
+        }
+        Tokenizer::Ipadic => {
+            TextAnalyzer::builder(build_lindera_tokenizer(DictionaryKind::IPADIC, "ipadic")?)
+                .filter(LowerCaser)
+                .filter(stop())
+                .build()
+        }
+        Tokenizer::CcCedict => TextAnalyzer::builder(build_lindera_tokenizer(
+            DictionaryKind::CcCedict,
+            "cc-cedict",
+        )?)
+        .filter(LowerCaser)
+        .filter(stop())
+        .build(),
+        Tokenizer::Unicode => TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(LowerCaser)
+            .filter(stop())
+            .build(),
+        Tokenizer::Ngram => TextAnalyzer::builder(NgramTokenizer::new(2, 3, false)?)
+            .filter(LowerCaser)
+            .filter(stop())
+            .build(),
+    };
     index
         .tokenizers()
-        .register(KOREAN_TOKENIZER_NAME, build_korean_tokenizer());
+        .register(tokenizer_name(tokenizer), analyzer);
+    Ok(())
+}
+
+/// Tokenizer the text fields of `schema` were indexed with
+fn schema_tokenizer(schema: &Schema) -> Option<String> {
+    let field = schema.get_field("content").ok()?;
+    match schema.get_field_entry(field).field_type() {
+        FieldType::Str(options) => options
+            .get_indexing_options()
+            .map(|indexing| indexing.tokenizer().to_string()),
+        _ => None,
+    }
+}
+
+/// Writer heap budget shared by the indexing threads
+const WRITER_HEAP_BYTES: usize = 50_000_000;
+/// Commit once this many adds/removes are buffered
+const COMMIT_BATCH_SIZE: usize = 256;
+/// ...or once the oldest buffered change is this old
+const COMMIT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Long-lived writer plus the changes it has not committed yet
+struct WriterState {
+    writer: IndexWriter,
+    pending: usize,
+    oldest_pending: Option<Instant>,
 }
 
-/// BM25 full-text search index using Tantivy with Korean morphological analysis
+/// BM25 full-text search index using Tantivy, with Korean morphological
+/// analysis unless another [`Tokenizer`] is chosen.
+///
+/// Keeps one `IndexWriter` for its lifetime and batches commits: changes are
+/// committed every [`COMMIT_BATCH_SIZE`] operations, when the oldest buffered
+/// change is older than [`COMMIT_INTERVAL`], before every search, on
+/// [`commit`](Self::commit), and on drop. The SQLite store is the source of
+/// truth, so changes lost to a crash are restored by the startup rebuild.
 pub struct Bm25Index {
     index: Index,
+    reader: IndexReader,
+    writer: Mutex<WriterState>,
     _schema: Schema,
     id_field: Field,
     content_field: Field,
     title_field: Field,
+    tokenizer: Tokenizer,
+    synonyms: Synonyms,
 }
 
 impl Bm25Index {
-    /// Create a new BM25 index at the given directory
+    /// Create a new BM25 index at the given directory, with the Korean
+    /// tokenizer
     pub fn new(index_dir: impl AsRef<Path>) -> Result<Self> {
-        let mut schema_builder = Schema::builder();
-
-        let id_field = schema_builder.add_text_field("id", STRING | STORED);
-        let content_field = schema_builder.add_text_field(
-            "content",
-            TextOptions::default().set_indexing_options(
-                TextFieldIndexing::default()
-                    .set_tokenizer(KOREAN_TOKENIZER_NAME)
-                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
-            ),
-        );
-        let title_field = schema_builder.add_text_field(
-            "title",
-            TextOptions::default().set_indexing_options(
-                TextFieldIndexing::default()
-                    .set_tokenizer(KOREAN_TOKENIZER_NAME)
-                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
-            ),
-        );
+        Self::new_with_tokenizer(index_dir, Tokenizer::default())
+    }
 
-        let schema = schema_builder.build();
+    /// Create a new BM25 index at the given directory. An index built
+    /// with another tokenizer is deleted and started empty; the SQLite
+    /// store is the source of truth and the startup rebuild refills it.
+    pub fn new_with_tokenizer(index_dir: impl AsRef<Path>, tokenizer: Tokenizer) -> Result<Self> {
+        let schema = build_schema(tokenizer);
         let index_path = index_dir.as_ref();
         std::fs::create_dir_all(index_path)?;
-        let index = Index::open_or_create(
-            tantivy::directory::MmapDirectory::open(index_path)?,
-            schema.clone(),
-        )?;
-
-        register_korean_tokenizer(&index);
-
-        Ok(Self {
-            index,
-            _schema: schema,
-            id_field,
-            content_field,
-            title_field,
-        })
+        let directory = tantivy::directory::MmapDirectory::open(index_path)?;
+        if Index::exists(&directory)? {
+            let built_with = schema_tokenizer(&Index::open(directory.clone())?.schema());
+            if built_with.as_deref() != Some(tokenizer_name(tokenizer)) {
+                tracing::info!(
+                    from = built_with.as_deref().unwrap_or("unknown"),
+                    to = tokenizer_name(tokenizer),
+                    "BM25 tokenizer changed; recreating the index"
+                );
+                drop(directory);
+                std::fs::remove_dir_all(index_path)?;
+                std::fs::create_dir_all(index_path)?;
+                return Self::new_with_tokenizer(index_path, tokenizer);
+            }
+        }
+        let index = Index::open_or_create(directory, schema.clone())?;
+        Self::from_index(index, schema, tokenizer)
     }
 
     /// Create an in-memory index (for testing)
     pub fn in_memory() -> Result<Self> {
-        let mut schema_builder = Schema::builder();
-
-        let id_field = schema_builder.add_text_field("id", STRING | STORED);
-        let content_field = schema_builder.add_text_field(
-            "content",
-            TextOptions::default().set_indexing_options(
-                TextFieldIndexing::default()
-                    .set_tokenizer(KOREAN_TOKENIZER_NAME)
-                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
-            ),
-        );
-        let title_field = schema_builder.add_text_field(
-            "title",
-            TextOptions::default().set_indexing_options(
-                TextFieldIndexing::default()
-                    .set_tokenizer(KOREAN_TOKENIZER_NAME)
-                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
-            ),
-        );
+        Self::in_memory_with_tokenizer(Tokenizer::default())
+    }
 
-        let schema = schema_builder.build();
+    /// Create an in-memory index with `tokenizer` (for testing)
+    pub fn in_memory_with_tokenizer(tokenizer: Tokenizer) -> Result<Self> {
+        let schema = build_schema(tokenizer);
         let index = Index::create_in_ram(schema.clone());
+        Self::from_index(index, schema, tokenizer)
+    }
 
-        register_korean_tokenizer(&index);
+    fn from_index(index: Index, schema: Schema, tokenizer: Tokenizer) -> Result<Self> {
+        register_tokenizer(&index, tokenizer, &[])?;
+
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let writer = index.writer(WRITER_HEAP_BYTES)?;
 
         Ok(Self {
+            id_field: schema.get_field("id")?,
+            content_field: schema.get_field("content")?,
+            title_field: schema.get_field("title")?,
             index,
+            reader,
+            writer: Mutex::new(WriterState {
+                writer,
+                pending: 0,
+                oldest_pending: None,
+            }),
             _schema: schema,
-            id_field,
-            content_field,
-            title_field,
+            tokenizer,
+            synonyms: Synonyms::default(),
         })
     }
 
-    /// Index a document
+    /// Widen queries with these synonym groups
+    pub fn with_synonyms(mut self, synonyms: Synonyms) -> Self {
+        self.synonyms = synonyms;
+        self
+    }
+
+    /// Leave these (lowercase) words out of indexed text and queries.
+    /// Call before adding documents: those already indexed keep theirs
+    /// until the next rebuild.
+    pub fn with_stopwords(self, stopwords: &[String]) -> Result<Self> {
+        register_tokenizer(&self.index, self.tokenizer, stopwords)?;
+        Ok(self)
+    }
+
+    /// Index a document, replacing any existing document with the same ID
     pub fn add(&self, id: &str, title: &str, content: &str) -> Result<()> {
-        let mut writer: IndexWriter = self.index.writer(50_000_000)?;
-        writer.add_document(doc!(
-            self.id_field => id,
-            self.title_field => title,
-            self.content_field => content,
-        ))?;
-        writer.commit()?;
-        Ok(())
+        let mut state = self.lock_writer()?;
+        self.stage_add(&mut state, id, title, content)?;
+        self.commit_if_due(&mut state)
+    }
+
+    /// Index many `(id, title, content)` documents with a single commit.
+    /// Returns how many were added.
+    pub fn add_batch(&self, docs: &[(String, String, String)]) -> Result<usize> {
+        let mut state = self.lock_writer()?;
+        for (id, title, content) in docs {
+            self.stage_add(&mut state, id, title, content)?;
+        }
+        self.commit_locked(&mut state)?;
+        Ok(docs.len())
+    }
+
+    /// Remove every document (before a full rebuild)
+    pub fn clear(&self) -> Result<()> {
+        let mut state = self.lock_writer()?;
+        state.writer.delete_all_documents()?;
+        state.pending += 1;
+        self.commit_locked(&mut state)
     }
 
-    /// Search for documents matching the query
+    /// Commit buffered changes and make them visible to searches
+    pub fn commit(&self) -> Result<()> {
+        let mut state = self.lock_writer()?;
+        self.commit_locked(&mut state)
+    }
+
+    /// Number of changes buffered since the last commit
+    pub fn pending(&self) -> usize {
+        self.writer.lock().map(|s| s.pending).unwrap_or(0)
+    }
+
+    /// Number of indexed documents, counting buffered changes
+    pub fn doc_count(&self) -> Result<usize> {
+        self.commit()?;
+        Ok(self.reader.searcher().num_docs() as usize)
+    }
+
+    /// Search for documents matching the query. Quoted parts
+    /// (`"메모리 안전성"`) match as phrases, the rest as loose terms; query
+    /// syntax the parser cannot read is dropped instead of failing the
+    /// search.
     pub fn search(&self, query_str: &str, limit: usize) -> Result<Vec<(String, f32)>> {
-        let reader = self
-            .index
-            .reader_builder()
-            .reload_policy(ReloadPolicy::OnCommitWithDelay)
-            .try_into()?;
-        let searcher = reader.searcher();
+        // Read-your-writes: flush anything buffered before searching
+        self.commit()?;
 
         let query_parser =
             QueryParser::for_index(&self.index, vec![self.content_field, self.title_field]);
-        let query = query_parser.parse_query(query_str)?;
+        let (query, errors) = query_parser.parse_query_lenient(&self.synonyms.expand(query_str));
+        if !errors.is_empty() {
+            tracing::debug!("Ignored unparsable parts of BM25 query: {errors:?}");
+        }
+        self.top_ids(&query, limit)
+    }
+
+    /// Documents with the whole of `text` as one phrase in their title or
+    /// content: its terms adjacent and in order, for finding an error
+    /// message or other text verbatim. Punctuation and stopwords are not
+    /// compared, as they are not indexed.
+    pub fn search_exact(&self, text: &str, limit: usize) -> Result<Vec<(String, f32)>> {
+        self.commit()?;
 
-        let top_docs = searcher.search(&query, &TopDocs::with_limit(limit))?;
+        let mut terms: Vec<(usize, String)> = Vec::new();
+        let mut analyzer = self.index.tokenizer_for_field(self.content_field)?;
+        analyzer.token_stream(text).process(&mut |token| {
+            terms.push((token.position, token.text.clone()));
+        });
+        let Some(first) = terms.first().map(|(position, _)| *position) else {
+            return Ok(Vec::new());
+        };
+
+        let clauses: Vec<(Occur, Box<dyn Query>)> = [self.content_field, self.title_field]
+            .into_iter()
+            .map(|field| {
+                let mut phrase: Vec<(usize, tantivy::Term)> = terms
+                    .iter()
+                    .map(|(position, term)| {
+                        (
+                            position.saturating_sub(first),
+                            tantivy::Term::from_field_text(field, term),
+                        )
+                    })
+                    .collect();
+                let query: Box<dyn Query> = if phrase.len() == 1 {
+                    Box::new(TermQuery::new(
+                        phrase.remove(0).1,
+                        IndexRecordOption::WithFreqs,
+                    ))
+                } else {
+                    Box::new(PhraseQuery::new_with_offset(phrase))
+                };
+                (Occur::Should, query)
+            })
+            .collect();
+        self.top_ids(&BooleanQuery::new(clauses), limit)
+    }
+
+    /// IDs and scores of the best `limit` documents matching `query`
+    fn top_ids(&self, query: &dyn Query, limit: usize) -> Result<Vec<(String, f32)>> {
+        let searcher = self.reader.searcher();
+        let top_docs = searcher.search(query, &TopDocs::with_limit(limit))?;
 
         let mut results = Vec::new();
         for (score, doc_address) in top_docs {
@@ -157,16 +337,198 @@ impl Bm25Index {
         Ok(results)
     }
 
+    /// Did-you-mean corrections of `query_str`, best first: each query
+    /// word missing from the index is replaced by the indexed terms
+    /// closest in spelling (see [`crate::suggest`]), ties going to the
+    /// term in more documents. Terms are spliced in lowercased, as
+    /// indexed. Empty when every word is indexed or none has a close term.
+    pub fn suggest(&self, query_str: &str, limit: usize) -> Result<Vec<String>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        self.commit()?;
+        let searcher = self.reader.searcher();
+        let mut dictionaries = Vec::new();
+        for segment in searcher.segment_readers() {
+            for field in [self.content_field, self.title_field] {
+                dictionaries.push(segment.inverted_index(field)?);
+            }
+        }
+
+        // Query words with their byte spans, to splice corrections in
+        let mut words = Vec::new();
+        let mut analyzer = self.index.tokenizer_for_field(self.content_field)?;
+        analyzer.token_stream(query_str).process(&mut |token| {
+            if token.text.chars().any(char::is_alphanumeric) {
+                words.push((token.offset_from, token.offset_to, token.text.clone()));
+            }
+        });
+
+        let mut corrections = Vec::new();
+        for (from, to, word) in words {
+            let mut known = false;
+            for dictionary in &dictionaries {
+                known |= dictionary.terms().get(word.as_bytes())?.is_some();
+            }
+            if known {
+                continue;
+            }
+            let closest = closest_terms(&dictionaries, &word, limit)?;
+            if !closest.is_empty() {
+                corrections.push((from, to, closest));
+            }
+        }
+        if corrections.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // The n-th suggestion takes each word's n-th closest term, or its
+        // closest when it has fewer
+        let mut suggestions: Vec<String> = Vec::new();
+        for n in 0..limit {
+            let mut suggestion = String::new();
+            let mut end = 0;
+            for (from, to, closest) in &corrections {
+                suggestion.push_str(&query_str[end..*from]);
+                suggestion.push_str(closest.get(n).unwrap_or(&closest[0]));
+                end = *to;
+            }
+            suggestion.push_str(&query_str[end..]);
+            if !suggestions.contains(&suggestion) {
+                suggestions.push(suggestion);
+            }
+        }
+        Ok(suggestions)
+    }
+
     /// Remove a document by ID
     pub fn remove(&self, id: &str) -> Result<()> {
-        let mut writer: IndexWriter = self.index.writer(50_000_000)?;
+        let mut state = self.lock_writer()?;
         let term = tantivy::Term::from_field_text(self.id_field, id);
-        writer.delete_term(term);
-        writer.commit()?;
+        state.writer.delete_term(term);
+        Self::mark_pending(&mut state);
+        self.commit_if_due(&mut state)
+    }
+
+    fn lock_writer(&self) -> Result<MutexGuard<'_, WriterState>> {
+        self.writer
+            .lock()
+            .map_err(|e| anyhow::anyhow!("BM25 writer lock poisoned: {e}"))
+    }
+
+    fn stage_add(
+        &self,
+        state: &mut WriterState,
+        id: &str,
+        title: &str,
+        content: &str,
+    ) -> Result<()> {
+        state
+            .writer
+            .delete_term(tantivy::Term::from_field_text(self.id_field, id));
+        state.writer.add_document(doc!(
+            self.id_field => id,
+            self.title_field => title,
+            self.content_field => content,
+        ))?;
+        Self::mark_pending(state);
+        Ok(())
+    }
+
+    fn mark_pending(state: &mut WriterState) {
+        state.pending += 1;
+        state.oldest_pending.get_or_insert_with(Instant::now);
+    }
+
+    fn commit_if_due(&self, state: &mut WriterState) -> Result<()> {
+        let overdue = state
+            .oldest_pending
+            .is_some_and(|t| t.elapsed() >= COMMIT_INTERVAL);
+        if state.pending >= COMMIT_BATCH_SIZE || overdue {
+            self.commit_locked(state)?;
+        }
+        Ok(())
+    }
+
+    fn commit_locked(&self, state: &mut WriterState) -> Result<()> {
+        if state.pending == 0 {
+            return Ok(());
+        }
+        let committed = state.writer.commit();
+        state.pending = 0;
+        state.oldest_pending = None;
+        if let Err(e) = committed {
+            // Restart the writer so one bad batch does not wedge later writes
+            state.writer.rollback()?;
+            return Err(e.into());
+        }
+        self.reader.reload()?;
         Ok(())
     }
 }
 
+impl Drop for Bm25Index {
+    fn drop(&mut self) {
+        if let Err(e) = self.commit() {
+            tracing::warn!("Failed to commit BM25 index on drop: {e}");
+        }
+    }
+}
+
+/// Up to `limit` indexed terms within [`max_edits`] of `word`, closest
+/// first, then by the documents they appear in
+fn closest_terms(
+    dictionaries: &[Arc<InvertedIndexReader>],
+    word: &str,
+    limit: usize,
+) -> Result<Vec<String>> {
+    let target = spelling(word);
+    let max = max_edits(target.len());
+    if max == 0 {
+        return Ok(Vec::new());
+    }
+    // Distance and document frequency summed over segments and fields
+    let mut found: HashMap<String, (usize, u64)> = HashMap::new();
+    for dictionary in dictionaries {
+        let mut terms = dictionary.terms().stream()?;
+        while terms.advance() {
+            let Ok(term) = std::str::from_utf8(terms.key()) else {
+                continue;
+            };
+            if term == word {
+                continue;
+            }
+            let letters = spelling(term);
+            if letters.len().abs_diff(target.len()) > max {
+                continue;
+            }
+            let distance = edit_distance(&target, &letters);
+            if distance > max {
+                continue;
+            }
+            let entry = found.entry(term.to_string()).or_insert((distance, 0));
+            entry.1 += u64::from(terms.value().doc_freq);
+        }
+    }
+    let mut found: Vec<(String, (usize, u64))> = found.into_iter().collect();
+    found.sort_by(|(a, (da, fa)), (b, (db, fb))| da.cmp(db).then(fb.cmp(fa)).then(a.cmp(b)));
+    found.truncate(limit);
+    Ok(found.into_iter().map(|(term, _)| term).collect())
+}
+
+fn build_schema(tokenizer: Tokenizer) -> Schema {
+    let mut schema_builder = Schema::builder();
+    schema_builder.add_text_field("id", STRING | STORED);
+    let text = TextOptions::default().set_indexing_options(
+        TextFieldIndexing::default()
+            .set_tokenizer(tokenizer_name(tokenizer))
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+    );
+    schema_builder.add_text_field("content", text.clone());
+    schema_builder.add_text_field("title", text);
+    schema_builder.build()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -214,4 +576,167 @@ mod tests {
         assert!(!results.is_empty(), "Korean morpheme search should work");
         assert_eq!(results[0].0, "1");
     }
+
+    #[test]
+    fn test_add_batches_commits_until_search() {
+        let index = Bm25Index::in_memory().unwrap();
+        index.add("1", "테스트", "테스트 문서").unwrap();
+        assert_eq!(index.pending(), 1, "single add is buffered");
+
+        let results = index.search("테스트", 5).unwrap();
+        assert_eq!(results.len(), 1, "search flushes buffered adds");
+        assert_eq!(index.pending(), 0);
+    }
+
+    #[test]
+    fn test_add_replaces_same_id() {
+        let index = Bm25Index::in_memory().unwrap();
+        index.add("1", "테스트", "첫 버전").unwrap();
+        index.add("1", "테스트", "둘째 버전").unwrap();
+        assert_eq!(index.search("테스트", 5).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_add_batch_and_clear() {
+        let index = Bm25Index::in_memory().unwrap();
+        let docs: Vec<(String, String, String)> = (0..10)
+            .map(|i| (i.to_string(), "테스트".to_string(), format!("문서 {i}")))
+            .collect();
+        assert_eq!(index.add_batch(&docs).unwrap(), 10);
+        assert_eq!(index.pending(), 0, "batch commits once at the end");
+        assert_eq!(index.search("테스트", 20).unwrap().len(), 10);
+
+        index.clear().unwrap();
+        assert!(index.search("테스트", 20).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_suggest_corrects_unknown_words() {
+        let index = Bm25Index::in_memory().unwrap();
+        index
+            .add("1", "deployment 절차", "스테이징을 거쳐 배포한다")
+            .unwrap();
+        index.add("2", "deployments", "배포 기록").unwrap();
+
+        assert_eq!(
+            index.suggest("staging deploymet", 3).unwrap(),
+            vec!["staging deployment", "staging deployments"]
+        );
+        assert_eq!(index.suggest("deploymet", 1).unwrap(), vec!["deployment"]);
+        // Known or hopeless words get nothing
+        assert!(index.suggest("deployment", 3).unwrap().is_empty());
+        assert!(index.suggest("kubernetes", 3).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_plain_tokenizers_need_no_dictionary() {
+        let index = Bm25Index::in_memory_with_tokenizer(Tokenizer::Unicode).unwrap();
+        index
+            .add("1", "Release notes", "Deploy on Fridays")
+            .unwrap();
+        assert_eq!(index.search("deploy", 5).unwrap()[0].0, "1");
+
+        // Bigrams find a word inside an unsegmented sentence
+        let index = Bm25Index::in_memory_with_tokenizer(Tokenizer::Ngram).unwrap();
+        index.add("1", "メモ", "東京都に住んでいます").unwrap();
+        index.add("2", "メモ", "大阪に住んでいます").unwrap();
+        assert_eq!(index.search("東京", 5).unwrap()[0].0, "1");
+    }
+
+    #[test]
+    fn test_changed_tokenizer_recreates_index() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let index = Bm25Index::new(dir.path()).unwrap();
+            index.add("1", "테스트", "한국어 문서").unwrap();
+        }
+        let index = Bm25Index::new(dir.path()).unwrap();
+        assert_eq!(
+            index.doc_count().unwrap(),
+            1,
+            "same tokenizer keeps the index"
+        );
+        drop(index);
+
+        let index = Bm25Index::new_with_tokenizer(dir.path(), Tokenizer::Unicode).unwrap();
+        assert_eq!(index.doc_count().unwrap(), 0);
+        index.add("1", "Test", "English document").unwrap();
+        assert_eq!(index.search("english", 5).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_stopwords_are_not_matched() {
+        let stopwords = crate::stopwords::defaults();
+        let index = Bm25Index::in_memory()
+            .unwrap()
+            .with_stopwords(&stopwords)
+            .unwrap();
+        index
+            .add("1", "한국어 설정", "The user talks in 한국어로")
+            .unwrap();
+        index.add("2", "영어 설정", "영어로 the review").unwrap();
+
+        // "로" is a particle of both, "the" an article of both
+        assert!(index.search("로", 5).unwrap().is_empty());
+        assert!(index.search("The", 5).unwrap().is_empty());
+        let results = index.search("the 한국어", 5).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, "1");
+    }
+
+    #[test]
+    fn test_quoted_phrase_and_exact_search() {
+        let index = Bm25Index::in_memory().unwrap();
+        index
+            .add(
+                "1",
+                "오류",
+                "error: connection refused by the database server",
+            )
+            .unwrap();
+        index
+            .add("2", "메모", "the server refused the database connection")
+            .unwrap();
+
+        // Loose terms match both, the quoted phrase only the first
+        assert_eq!(
+            index
+                .search("connection refused database", 5)
+                .unwrap()
+                .len(),
+            2
+        );
+        let results = index.search("\"connection refused\" server", 5).unwrap();
+        assert_eq!(results[0].0, "1");
+
+        let results = index
+            .search_exact("Connection refused by the database", 5)
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, "1");
+        assert!(
+            index
+                .search_exact("refused connection", 5)
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(index.search_exact("server", 5).unwrap().len(), 2);
+        assert!(index.search_exact("", 5).unwrap().is_empty());
+
+        // A stray quote or field syntax no longer fails the query
+        assert!(index.search("error: \"connection", 5).is_ok());
+    }
+
+    #[test]
+    fn test_synonyms_widen_query() {
+        let synonyms = Synonyms::parse(r#"groups = [["러스트", "Rust"]]"#).unwrap();
+        let index = Bm25Index::in_memory().unwrap().with_synonyms(synonyms);
+        index
+            .add("1", "Rust 메모", "Rust는 시스템 프로그래밍 언어입니다")
+            .unwrap();
+
+        let results = index.search("러스트", 5).unwrap();
+        assert_eq!(results.len(), 1, "Korean spelling finds the English term");
+        assert_eq!(results[0].0, "1");
+    }
 }
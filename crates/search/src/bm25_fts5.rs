@@ -0,0 +1,632 @@
+//! BM25 index for builds without the `lindera` feature: SQLite FTS5 with
+//! the trigram tokenizer instead of tantivy with ko-dic morphemes.
+//!
+//! Trigrams match any substring, so a query word finds the agglutinated
+//! forms a morphological analyzer would split ("한국어" matches
+//! "한국어로"). Words shorter than three characters have no trigram and
+//! are matched with `LIKE`, scored with the same BM25 parameters FTS5
+//! uses. `tokenizer = "unicode"` uses FTS5's `unicode61` word splitting
+//! instead; the dictionary tokenizers need lindera and fall back to
+//! trigrams.
+
+use anyhow::Result;
+use oc_core::config::Tokenizer;
+use rusqlite::{Connection, OptionalExtension, params};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use crate::suggest::{edit_distance, max_edits, spelling};
+use crate::synonyms::Synonyms;
+
+/// Index file inside the index directory
+const INDEX_FILE_NAME: &str = "bm25.db";
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+/// Shortest word the trigram tokenizer can match
+const TRIGRAM_CHARS: usize = 3;
+/// BM25 term frequency saturation and length normalization, as in FTS5
+const K1: f32 = 1.2;
+const B: f32 = 0.75;
+
+const DOCUMENTS_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS documents (
+        doc INTEGER PRIMARY KEY,
+        id TEXT NOT NULL UNIQUE,
+        title TEXT NOT NULL,
+        content TEXT NOT NULL
+    );
+    CREATE TRIGGER IF NOT EXISTS documents_insert AFTER INSERT ON documents BEGIN
+        INSERT INTO documents_fts (rowid, title, content)
+        VALUES (new.doc, new.title, new.content);
+    END;
+    CREATE TRIGGER IF NOT EXISTS documents_delete AFTER DELETE ON documents BEGIN
+        INSERT INTO documents_fts (documents_fts, rowid, title, content)
+        VALUES ('delete', old.doc, old.title, old.content);
+    END;
+";
+
+/// FTS5 tokenizer standing in for `tokenizer`
+fn fts_tokenizer(tokenizer: Tokenizer) -> &'static str {
+    match tokenizer {
+        Tokenizer::Unicode => "unicode61",
+        Tokenizer::Ngram | Tokenizer::KoDic => "trigram",
+        Tokenizer::Ipadic | Tokenizer::CcCedict => {
+            tracing::warn!(
+                ?tokenizer,
+                "No dictionary without the lindera feature; using trigrams"
+            );
+            "trigram"
+        }
+    }
+}
+
+/// BM25 full-text search index using SQLite FTS5 with trigrams.
+///
+/// Same API as the tantivy index, but every change is committed at once,
+/// so [`pending`](Self::pending) is always 0 and
+/// [`commit`](Self::commit) has nothing to do.
+pub struct Bm25Index {
+    conn: Mutex<Connection>,
+    synonyms: Synonyms,
+    stopwords: HashSet<String>,
+    /// Whether the index is of trigrams, which short words cannot match
+    trigrams: bool,
+}
+
+impl Bm25Index {
+    /// Create a new BM25 index at the given directory, with trigrams
+    pub fn new(index_dir: impl AsRef<Path>) -> Result<Self> {
+        Self::new_with_tokenizer(index_dir, Tokenizer::default())
+    }
+
+    /// Create a new BM25 index at the given directory. An index built
+    /// with another tokenizer is re-tokenized from its stored documents.
+    pub fn new_with_tokenizer(index_dir: impl AsRef<Path>, tokenizer: Tokenizer) -> Result<Self> {
+        let index_path = index_dir.as_ref();
+        std::fs::create_dir_all(index_path)?;
+        let conn = Connection::open(index_path.join(INDEX_FILE_NAME))?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.pragma_update(None, "journal_mode", "wal")?;
+        Self::from_connection(conn, tokenizer)
+    }
+
+    /// Create an in-memory index (for testing)
+    pub fn in_memory() -> Result<Self> {
+        Self::in_memory_with_tokenizer(Tokenizer::default())
+    }
+
+    /// Create an in-memory index with `tokenizer` (for testing)
+    pub fn in_memory_with_tokenizer(tokenizer: Tokenizer) -> Result<Self> {
+        Self::from_connection(Connection::open_in_memory()?, tokenizer)
+    }
+
+    fn from_connection(conn: Connection, tokenizer: Tokenizer) -> Result<Self> {
+        let fts = fts_tokenizer(tokenizer);
+        let create = format!(
+            "CREATE VIRTUAL TABLE documents_fts USING fts5(
+                title, content,
+                content = 'documents', content_rowid = 'doc',
+                tokenize = '{fts}'
+            )"
+        );
+        let existing: Option<String> = conn
+            .query_row(
+                "SELECT sql FROM sqlite_master WHERE name = 'documents_fts'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+        conn.execute_batch(DOCUMENTS_SCHEMA)?;
+        if existing.as_deref() != Some(create.as_str()) {
+            if existing.is_some() {
+                tracing::info!(to = fts, "BM25 tokenizer changed; re-tokenizing the index");
+                conn.execute_batch("DROP TABLE documents_fts")?;
+            }
+            conn.execute_batch(&create)?;
+            conn.execute(
+                "INSERT INTO documents_fts (documents_fts) VALUES ('rebuild')",
+                [],
+            )?;
+        }
+        Ok(Self {
+            conn: Mutex::new(conn),
+            synonyms: Synonyms::default(),
+            stopwords: HashSet::new(),
+            trigrams: fts == "trigram",
+        })
+    }
+
+    /// Widen queries with these synonym groups
+    pub fn with_synonyms(mut self, synonyms: Synonyms) -> Self {
+        self.synonyms = synonyms;
+        self
+    }
+
+    /// Leave these (lowercase) words out of queries. FTS5 tokenizers keep
+    /// every word, so they stay in the index but are never searched for.
+    pub fn with_stopwords(mut self, stopwords: &[String]) -> Result<Self> {
+        self.stopwords = stopwords.iter().cloned().collect();
+        Ok(self)
+    }
+
+    /// Index a document, replacing any existing document with the same ID
+    pub fn add(&self, id: &str, title: &str, content: &str) -> Result<()> {
+        let conn = self.lock()?;
+        replace(&conn, id, title, content)
+    }
+
+    /// Index many `(id, title, content)` documents in one transaction.
+    /// Returns how many were added.
+    pub fn add_batch(&self, docs: &[(String, String, String)]) -> Result<usize> {
+        let mut conn = self.lock()?;
+        let tx = conn.transaction()?;
+        for (id, title, content) in docs {
+            replace(&tx, id, title, content)?;
+        }
+        tx.commit()?;
+        Ok(docs.len())
+    }
+
+    /// Remove every document (before a full rebuild)
+    pub fn clear(&self) -> Result<()> {
+        self.lock()?.execute("DELETE FROM documents", [])?;
+        Ok(())
+    }
+
+    /// Changes are committed as they are made; kept for API parity
+    pub fn commit(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Number of changes buffered since the last commit: always 0
+    pub fn pending(&self) -> usize {
+        0
+    }
+
+    /// Number of indexed documents
+    pub fn doc_count(&self) -> Result<usize> {
+        let count: i64 = self
+            .lock()?
+            .query_row("SELECT COUNT(*) FROM documents", [], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    /// Search for documents matching the query
+    pub fn search(&self, query_str: &str, limit: usize) -> Result<Vec<(String, f32)>> {
+        let mut terms = query_terms(&self.synonyms.expand(query_str));
+        terms.retain(|term| !self.stopwords.contains(term));
+        self.search_terms(terms, limit)
+    }
+
+    /// Documents with the whole of `text` in their title or content. With
+    /// trigrams that is a case-insensitive substring match, punctuation
+    /// included.
+    pub fn search_exact(&self, text: &str, limit: usize) -> Result<Vec<(String, f32)>> {
+        let text = text.trim().to_lowercase();
+        if text.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.search_terms(vec![text], limit)
+    }
+
+    /// Each of `terms` matched as a phrase (or substring, for trigrams),
+    /// their BM25 scores summed per document
+    fn search_terms(&self, terms: Vec<String>, limit: usize) -> Result<Vec<(String, f32)>> {
+        if terms.is_empty() || limit == 0 {
+            return Ok(Vec::new());
+        }
+        let (long, short): (Vec<String>, Vec<String>) = terms
+            .into_iter()
+            .partition(|term| !self.trigrams || term.chars().count() >= TRIGRAM_CHARS);
+
+        let conn = self.lock()?;
+        let mut scores: HashMap<String, f32> = HashMap::new();
+        if !long.is_empty() {
+            let query = long
+                .iter()
+                .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+                .collect::<Vec<_>>()
+                .join(" OR ");
+            let mut stmt = conn.prepare(
+                "SELECT d.id, -bm25(documents_fts) FROM documents_fts
+                 JOIN documents d ON d.doc = documents_fts.rowid
+                 WHERE documents_fts MATCH ?1",
+            )?;
+            let rows = stmt.query_map([&query], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
+            })?;
+            for row in rows {
+                let (id, score) = row?;
+                *scores.entry(id).or_default() += score as f32;
+            }
+        }
+        for term in &short {
+            for (id, score) in short_term_scores(&conn, term)? {
+                *scores.entry(id).or_default() += score;
+            }
+        }
+
+        let mut results: Vec<(String, f32)> = scores.into_iter().collect();
+        results.sort_by(|(a, sa), (b, sb)| sb.total_cmp(sa).then(a.cmp(b)));
+        results.truncate(limit);
+        Ok(results)
+    }
+
+    /// Did-you-mean corrections of `query_str`, best first: each query
+    /// word missing from the indexed words is replaced by the words
+    /// closest in spelling (see [`crate::suggest`]), ties going to the
+    /// word in more documents. Words are spliced in lowercased, like the
+    /// terms of the tantivy index. Empty when every word is indexed or
+    /// none has a close word.
+    ///
+    /// Trigrams are no word list, so the indexed words are collected from
+    /// the stored documents on each call.
+    pub fn suggest(&self, query_str: &str, limit: usize) -> Result<Vec<String>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let vocabulary = self.vocabulary()?;
+
+        let mut corrections = Vec::new();
+        for (from, to, word) in words(query_str) {
+            let word = word.to_lowercase();
+            if vocabulary.contains_key(&word) {
+                continue;
+            }
+            let closest = closest_words(&vocabulary, &word, limit);
+            if !closest.is_empty() {
+                corrections.push((from, to, closest));
+            }
+        }
+        if corrections.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // The n-th suggestion takes each word's n-th closest word, or its
+        // closest when it has fewer
+        let mut suggestions: Vec<String> = Vec::new();
+        for n in 0..limit {
+            let mut suggestion = String::new();
+            let mut end = 0;
+            for (from, to, closest) in &corrections {
+                suggestion.push_str(&query_str[end..*from]);
+                suggestion.push_str(closest.get(n).unwrap_or(&closest[0]));
+                end = *to;
+            }
+            suggestion.push_str(&query_str[end..]);
+            if !suggestions.contains(&suggestion) {
+                suggestions.push(suggestion);
+            }
+        }
+        Ok(suggestions)
+    }
+
+    /// Remove a document by ID
+    pub fn remove(&self, id: &str) -> Result<()> {
+        self.lock()?
+            .execute("DELETE FROM documents WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, Connection>> {
+        self.conn
+            .lock()
+            .map_err(|e| anyhow::anyhow!("BM25 connection lock poisoned: {e}"))
+    }
+
+    /// Lowercased words of every document with the number of documents
+    /// containing each
+    fn vocabulary(&self) -> Result<HashMap<String, u64>> {
+        let conn = self.lock()?;
+        let mut stmt = conn.prepare("SELECT title, content FROM documents")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut vocabulary: HashMap<String, u64> = HashMap::new();
+        for row in rows {
+            let (title, content) = row?;
+            let mut seen = HashSet::new();
+            for text in [&title, &content] {
+                for (_, _, word) in words(text) {
+                    let word = word.to_lowercase();
+                    if seen.insert(word.clone()) {
+                        *vocabulary.entry(word).or_default() += 1;
+                    }
+                }
+            }
+        }
+        Ok(vocabulary)
+    }
+}
+
+fn replace(conn: &Connection, id: &str, title: &str, content: &str) -> Result<()> {
+    conn.execute("DELETE FROM documents WHERE id = ?1", [id])?;
+    conn.execute(
+        "INSERT INTO documents (id, title, content) VALUES (?1, ?2, ?3)",
+        params![id, title, content],
+    )?;
+    Ok(())
+}
+
+/// BM25 scores of the documents containing `term`, a word too short for
+/// trigrams: found with `LIKE`, counted in Rust
+fn short_term_scores(conn: &Connection, term: &str) -> Result<Vec<(String, f32)>> {
+    let (total, avg_len): (i64, f64) = conn.query_row(
+        "SELECT COUNT(*), COALESCE(AVG(length(title) + length(content)), 0) FROM documents",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    let pattern = format!(
+        "%{}%",
+        term.replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    );
+    let mut stmt = conn.prepare(
+        "SELECT id, title || ' ' || content FROM documents
+         WHERE title LIKE ?1 ESCAPE '\\' OR content LIKE ?1 ESCAPE '\\'",
+    )?;
+    let matches = stmt
+        .query_map([&pattern], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let found = matches.len() as f32;
+    let idf = (1.0 + (total as f32 - found + 0.5) / (found + 0.5)).ln();
+    let avg_len = avg_len.max(1.0) as f32;
+    Ok(matches
+        .into_iter()
+        .filter_map(|(id, text)| {
+            let text = text.to_lowercase();
+            let frequency = text.matches(term).count() as f32;
+            (frequency > 0.0).then(|| {
+                let length = text.chars().count() as f32 / avg_len;
+                let score =
+                    idf * frequency * (K1 + 1.0) / (frequency + K1 * (1.0 - B + B * length));
+                (id, score)
+            })
+        })
+        .collect())
+}
+
+/// Terms of a query: quoted phrases whole, other text split into words;
+/// lowercased, each once
+fn query_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for (i, part) in query.split('"').enumerate() {
+        let found: Vec<String> = if i % 2 == 1 {
+            vec![part.trim().to_lowercase()]
+        } else {
+            words(part)
+                .into_iter()
+                .map(|(_, _, word)| word.to_lowercase())
+                .collect()
+        };
+        for term in found {
+            if !term.is_empty() && !terms.contains(&term) {
+                terms.push(term);
+            }
+        }
+    }
+    terms
+}
+
+/// Runs of letters and digits with their byte spans
+fn words(text: &str) -> Vec<(usize, usize, &str)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(from)) => {
+                words.push((from, i, &text[from..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(from) = start {
+        words.push((from, text.len(), &text[from..]));
+    }
+    words
+}
+
+/// Up to `limit` words of `vocabulary` within [`max_edits`] of `word`,
+/// closest first, then by the documents they appear in
+fn closest_words(vocabulary: &HashMap<String, u64>, word: &str, limit: usize) -> Vec<String> {
+    let target = spelling(word);
+    let max = max_edits(target.len());
+    if max == 0 {
+        return Vec::new();
+    }
+    let mut found: Vec<(&str, usize, u64)> = vocabulary
+        .iter()
+        .filter_map(|(term, &docs)| {
+            let letters = spelling(term);
+            if letters.len().abs_diff(target.len()) > max {
+                return None;
+            }
+            let distance = edit_distance(&target, &letters);
+            (distance <= max).then_some((term.as_str(), distance, docs))
+        })
+        .collect();
+    found.sort_by(|(a, da, fa), (b, db, fb)| da.cmp(db).then(fb.cmp(fa)).then(a.cmp(b)));
+    found.truncate(limit);
+    found
+        .into_iter()
+        .map(|(term, _, _)| term.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bm25_index_search() {
+        let index = Bm25Index::in_memory().unwrap();
+        index
+            .add(
+                "1",
+                "Rust 프로그래밍",
+                "Rust는 안전하고 빠른 시스템 프로그래밍 언어입니다",
+            )
+            .unwrap();
+        index
+            .add(
+                "2",
+                "Python 프로그래밍",
+                "Python은 간결하고 읽기 쉬운 스크립트 언어입니다",
+            )
+            .unwrap();
+
+        // "rust" goes through FTS5, the two-syllable "안전" through LIKE
+        let results = index.search("Rust 안전", 5).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, "1");
+        assert_eq!(index.search("프로그래밍", 5).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_trigrams_match_inside_agglutinated_words() {
+        let index = Bm25Index::in_memory().unwrap();
+        index
+            .add("1", "한국어 설정", "사용자는 한국어로 대화를 선호합니다")
+            .unwrap();
+        index
+            .add("2", "영어 설정", "영어로 코드 리뷰를 합니다")
+            .unwrap();
+
+        let results = index.search("한국어", 5).unwrap();
+        assert_eq!(results[0].0, "1");
+        assert_eq!(results.len(), 1);
+    }
+
+    #[test]
+    fn test_add_replaces_and_remove_deletes() {
+        let index = Bm25Index::in_memory().unwrap();
+        index.add("1", "테스트", "첫 버전").unwrap();
+        index.add("1", "테스트", "둘째 버전").unwrap();
+        assert_eq!(index.doc_count().unwrap(), 1);
+        assert!(index.search("첫", 5).unwrap().is_empty());
+        assert_eq!(index.pending(), 0);
+
+        index.remove("1").unwrap();
+        assert!(index.search("테스트", 5).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_add_batch_and_clear() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = Bm25Index::new(dir.path()).unwrap();
+        let docs: Vec<(String, String, String)> = (0..10)
+            .map(|i| (i.to_string(), "테스트".to_string(), format!("문서 {i}")))
+            .collect();
+        assert_eq!(index.add_batch(&docs).unwrap(), 10);
+        assert_eq!(index.search("테스트", 20).unwrap().len(), 10);
+        drop(index);
+
+        let index = Bm25Index::new(dir.path()).unwrap();
+        assert_eq!(index.doc_count().unwrap(), 10, "reopened from disk");
+        index.clear().unwrap();
+        assert!(index.search("테스트", 20).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_suggest_corrects_unknown_words() {
+        let index = Bm25Index::in_memory().unwrap();
+        index
+            .add("1", "deployment 절차", "스테이징을 거쳐 배포한다")
+            .unwrap();
+        index.add("2", "deployments", "배포 기록").unwrap();
+
+        assert_eq!(
+            index.suggest("staging deploymet", 3).unwrap(),
+            vec!["staging deployment", "staging deployments"]
+        );
+        assert!(index.suggest("deployment", 3).unwrap().is_empty());
+        assert!(index.suggest("kubernetes", 3).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_synonyms_widen_query() {
+        let synonyms = Synonyms::parse(r#"groups = [["러스트", "Rust"]]"#).unwrap();
+        let index = Bm25Index::in_memory().unwrap().with_synonyms(synonyms);
+        index
+            .add("1", "Rust 메모", "Rust는 시스템 프로그래밍 언어입니다")
+            .unwrap();
+
+        let results = index.search("러스트", 5).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, "1");
+    }
+
+    #[test]
+    fn test_changed_tokenizer_retokenizes_documents() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let index = Bm25Index::new(dir.path()).unwrap();
+            index
+                .add("1", "메모", "사용자는 한국어로 대화합니다")
+                .unwrap();
+            assert_eq!(index.search("한국어", 5).unwrap().len(), 1);
+        }
+
+        // Whole words only: the particle keeps "한국어로" from matching
+        let index = Bm25Index::new_with_tokenizer(dir.path(), Tokenizer::Unicode).unwrap();
+        assert_eq!(index.doc_count().unwrap(), 1);
+        assert!(index.search("한국어", 5).unwrap().is_empty());
+        assert_eq!(index.search("한국어로", 5).unwrap().len(), 1);
+        drop(index);
+
+        let index = Bm25Index::new(dir.path()).unwrap();
+        assert_eq!(index.search("한국어", 5).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_stopwords_are_not_searched_for() {
+        let index = Bm25Index::in_memory()
+            .unwrap()
+            .with_stopwords(&crate::stopwords::defaults())
+            .unwrap();
+        index.add("1", "메모", "The release notes").unwrap();
+        index.add("2", "메모", "the deploy log").unwrap();
+
+        assert!(index.search("the", 5).unwrap().is_empty());
+        let results = index.search("The release", 5).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, "1");
+    }
+
+    #[test]
+    fn test_exact_search_matches_verbatim() {
+        let index = Bm25Index::in_memory().unwrap();
+        index
+            .add("1", "오류", "error: connection refused by server")
+            .unwrap();
+        index
+            .add("2", "메모", "the server refused the connection")
+            .unwrap();
+
+        let results = index.search_exact("Connection refused", 5).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, "1");
+        assert_eq!(index.search_exact("error:", 5).unwrap().len(), 1);
+        assert!(
+            index
+                .search_exact("refused connection", 5)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_query_terms_keep_phrases() {
+        assert_eq!(
+            query_terms("Rust, 안전 \"hot path\" rust"),
+            vec!["rust", "안전", "hot path"]
+        );
+    }
+}
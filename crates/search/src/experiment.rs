@@ -0,0 +1,369 @@
+use anyhow::Result;
+use oc_core::Storage;
+use oc_core::config::ExperimentConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use crate::scoring::Scorer;
+
+/// How many served results are remembered for attributing later fetches
+const MAX_TRACKED_IMPRESSIONS: usize = 10_000;
+
+/// Ranking variant used for a search
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Variant {
+    A,
+    B,
+}
+
+impl Variant {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::A => "A",
+            Self::B => "B",
+        }
+    }
+}
+
+pub use oc_core::models::VariantMetrics;
+
+/// Comparative metrics for an A/B experiment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExperimentReport {
+    pub b_ratio: f32,
+    pub variant_b: Scorer,
+    pub a: VariantMetrics,
+    pub b: VariantMetrics,
+}
+
+struct Impression {
+    variant: Variant,
+    rank: usize,
+    /// Position in [`ExperimentState::served_order`]
+    seq: u64,
+}
+
+#[derive(Default)]
+struct ExperimentState {
+    searches: u64,
+    a: VariantMetrics,
+    b: VariantMetrics,
+    /// Latest impression per memory ID
+    served: HashMap<String, Impression>,
+    /// IDs in `served` by impression sequence, evicted oldest first
+    served_order: BTreeMap<u64, String>,
+    next_seq: u64,
+}
+
+impl ExperimentState {
+    fn metrics_mut(&mut self, variant: Variant) -> &mut VariantMetrics {
+        match variant {
+            Variant::A => &mut self.a,
+            Variant::B => &mut self.b,
+        }
+    }
+}
+
+/// A/B ranking experiment: variant A is the live scorer, variant B an
+/// alternative weighting served to `b_ratio` of searches.
+///
+/// Assignment interleaves deterministically so the observed split matches
+/// the ratio exactly, without needing a random source. Counters survive
+/// restarts once [persisted](Self::persisted_in); served results are only
+/// tracked in memory, so fetches of results served before a restart are
+/// not attributed.
+pub struct Experiment {
+    variant_b: Scorer,
+    b_ratio: f32,
+    state: Mutex<ExperimentState>,
+    /// Storage the counters are added to, and the experiment's key there
+    persist: Option<(Arc<Storage>, String)>,
+}
+
+impl Experiment {
+    pub fn new(variant_b: Scorer, b_ratio: f32) -> Result<Self> {
+        variant_b.validate()?;
+        anyhow::ensure!(
+            (0.0..=1.0).contains(&b_ratio),
+            "b_ratio must be within [0, 1], got {b_ratio}"
+        );
+        Ok(Self {
+            variant_b,
+            b_ratio,
+            state: Mutex::new(ExperimentState::default()),
+            persist: None,
+        })
+    }
+
+    /// Keep the counters in `storage`, continuing from those saved by
+    /// earlier runs of the same experiment. A change of `b_ratio` or of
+    /// the variant B weights starts a new experiment.
+    pub fn persisted_in(mut self, storage: Arc<Storage>) -> Result<Self> {
+        let key = format!(
+            "{}:{}",
+            self.b_ratio,
+            serde_json::to_string(&self.variant_b)?
+        );
+        let mut saved = storage.variant_metrics(&key)?;
+        {
+            let state = self.state.get_mut().unwrap_or_else(|e| e.into_inner());
+            state.a = saved.remove(Variant::A.as_str()).unwrap_or_default();
+            state.b = saved.remove(Variant::B.as_str()).unwrap_or_default();
+            state.searches = state.a.searches + state.b.searches;
+        }
+        self.persist = Some((storage, key));
+        Ok(self)
+    }
+
+    pub fn from_config(config: &ExperimentConfig) -> Result<Self> {
+        let variant_b = Scorer {
+            semantic_weight: config.semantic_weight,
+            keyword_weight: config.keyword_weight,
+            recency_weight: config.recency_weight,
+            importance_weight: config.importance_weight,
+            access_weight: config.access_weight,
+            half_life_days: config.recency_half_life_days,
+            fusion: config.fusion,
+            ..Scorer::default()
+        };
+        Self::new(variant_b, config.b_ratio)
+    }
+
+    /// Pick the variant for the next search
+    pub fn assign(&self) -> Variant {
+        let mut state = self.lock();
+        let n = state.searches as f64;
+        state.searches += 1;
+        let ratio = self.b_ratio as f64;
+        let variant = if ((n + 1.0) * ratio).floor() > (n * ratio).floor() {
+            Variant::B
+        } else {
+            Variant::A
+        };
+        state.metrics_mut(variant).searches += 1;
+        drop(state);
+        self.save(
+            variant,
+            &VariantMetrics {
+                searches: 1,
+                ..Default::default()
+            },
+        );
+        variant
+    }
+
+    /// Scorer to rank with for `variant`; `a` is the live scorer
+    pub fn scorer_for<'a>(&'a self, variant: Variant, a: &'a Scorer) -> &'a Scorer {
+        match variant {
+            Variant::A => a,
+            Variant::B => &self.variant_b,
+        }
+    }
+
+    /// Remember which results a variant served, in rank order
+    pub fn record_served(&self, variant: Variant, ids: &[String]) {
+        let mut state = self.lock();
+        state.metrics_mut(variant).results_served += ids.len() as u64;
+        for (i, id) in ids.iter().enumerate() {
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            let impression = Impression {
+                variant,
+                rank: i + 1,
+                seq,
+            };
+            if let Some(previous) = state.served.insert(id.clone(), impression) {
+                state.served_order.remove(&previous.seq);
+            }
+            state.served_order.insert(seq, id.clone());
+        }
+        while state.served_order.len() > MAX_TRACKED_IMPRESSIONS {
+            if let Some((_, old)) = state.served_order.pop_first() {
+                state.served.remove(&old);
+            }
+        }
+        drop(state);
+        self.save(
+            variant,
+            &VariantMetrics {
+                results_served: ids.len() as u64,
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Attribute fetched memories to the variant that served them.
+    /// Returns how many fetches were attributed.
+    pub fn record_fetch(&self, ids: &[String]) -> usize {
+        let mut state = self.lock();
+        let mut deltas: HashMap<Variant, VariantMetrics> = HashMap::new();
+        let mut attributed = 0;
+        for id in ids {
+            if let Some(impression) = state.served.remove(id) {
+                state.served_order.remove(&impression.seq);
+                for metrics in [
+                    state.metrics_mut(impression.variant),
+                    deltas.entry(impression.variant).or_default(),
+                ] {
+                    metrics.fetched += 1;
+                    metrics.fetched_rank_sum += impression.rank as u64;
+                }
+                tracing::info!(
+                    id = %id,
+                    variant = impression.variant.as_str(),
+                    rank = impression.rank,
+                    "Experiment outcome: result fetched"
+                );
+                attributed += 1;
+            }
+        }
+        drop(state);
+        for (variant, delta) in deltas {
+            self.save(variant, &delta);
+        }
+        attributed
+    }
+
+    /// Counters so far; once persisted, those saved by every process
+    /// sharing the storage
+    pub fn report(&self) -> ExperimentReport {
+        let (a, b) = self.saved().unwrap_or_else(|| {
+            let state = self.lock();
+            (state.a.clone(), state.b.clone())
+        });
+        ExperimentReport {
+            b_ratio: self.b_ratio,
+            variant_b: self.variant_b.clone(),
+            a,
+            b,
+        }
+    }
+
+    /// Saved counters of variants A and B, if persisted and readable
+    fn saved(&self) -> Option<(VariantMetrics, VariantMetrics)> {
+        let (storage, key) = self.persist.as_ref()?;
+        match storage.variant_metrics(key) {
+            Ok(mut saved) => Some((
+                saved.remove(Variant::A.as_str()).unwrap_or_default(),
+                saved.remove(Variant::B.as_str()).unwrap_or_default(),
+            )),
+            Err(e) => {
+                tracing::warn!("Failed to read experiment metrics: {e}");
+                None
+            }
+        }
+    }
+
+    /// Add `delta` to the saved counters of `variant`. A failed write is
+    /// only logged: it must not fail the search or fetch it counts.
+    fn save(&self, variant: Variant, delta: &VariantMetrics) {
+        if let Some((storage, key)) = &self.persist
+            && let Err(e) = storage.add_variant_metrics(key, variant.as_str(), delta)
+        {
+            tracing::warn!("Failed to save experiment metrics: {e}");
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ExperimentState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_assignment_matches_ratio() {
+        let exp = Experiment::new(Scorer::default(), 0.25).unwrap();
+        let b_count = (0..100).filter(|_| exp.assign() == Variant::B).count();
+        assert_eq!(b_count, 25);
+
+        let report = exp.report();
+        assert_eq!(report.a.searches, 75);
+        assert_eq!(report.b.searches, 25);
+    }
+
+    #[test]
+    fn test_fetch_attribution() {
+        let exp = Experiment::new(Scorer::default(), 0.5).unwrap();
+        exp.record_served(Variant::A, &ids(&["a1", "a2"]));
+        exp.record_served(Variant::B, &ids(&["b1", "b2", "b3"]));
+
+        assert_eq!(exp.record_fetch(&ids(&["b3", "unknown"])), 1);
+        // A second fetch of the same result is not double-counted
+        assert_eq!(exp.record_fetch(&ids(&["b3"])), 0);
+
+        let report = exp.report();
+        assert_eq!(report.a.fetched, 0);
+        assert_eq!(report.b.fetched, 1);
+        assert_eq!(report.b.results_served, 3);
+        assert!((report.b.mean_fetched_rank() - 3.0).abs() < f64::EPSILON);
+        assert!((report.b.fetch_rate() - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_oldest_impressions_evicted_first() {
+        let exp = Experiment::new(Scorer::default(), 0.5).unwrap();
+        exp.record_served(Variant::A, &ids(&["old", "kept"]));
+        let filler: Vec<String> = (0..MAX_TRACKED_IMPRESSIONS - 2)
+            .map(|i| format!("m{i}"))
+            .collect();
+        exp.record_served(Variant::A, &filler);
+        // Served again, so now among the newest
+        exp.record_served(Variant::B, &ids(&["kept"]));
+        exp.record_served(Variant::A, &ids(&["new"]));
+
+        assert_eq!(exp.record_fetch(&ids(&["old"])), 0);
+        assert_eq!(exp.record_fetch(&ids(&["kept", "new", "m0"])), 3);
+        let report = exp.report();
+        assert_eq!(report.b.fetched, 1);
+        assert_eq!(report.a.fetched, 2);
+    }
+
+    #[test]
+    fn test_persisted_metrics_survive_restart() {
+        let storage = Arc::new(Storage::in_memory().unwrap());
+        let exp = Experiment::new(Scorer::default(), 0.5)
+            .unwrap()
+            .persisted_in(Arc::clone(&storage))
+            .unwrap();
+        let variant = exp.assign();
+        exp.record_served(variant, &ids(&["m1", "m2"]));
+        assert_eq!(exp.record_fetch(&ids(&["m2"])), 1);
+
+        let restarted = Experiment::new(Scorer::default(), 0.5)
+            .unwrap()
+            .persisted_in(Arc::clone(&storage))
+            .unwrap();
+        let report = restarted.report();
+        assert_eq!(report.a.searches, 1);
+        assert_eq!(report.a.results_served, 2);
+        assert_eq!(report.a.fetched, 1);
+        assert_eq!(report.a.fetched_rank_sum, 2);
+        // Assignment continues the interleaving where it stopped
+        assert_eq!(restarted.assign(), Variant::B);
+
+        // Other weights are another experiment
+        let other = Scorer {
+            semantic_weight: 0.9,
+            keyword_weight: 0.1,
+            ..Scorer::default()
+        };
+        let fresh = Experiment::new(other, 0.5)
+            .unwrap()
+            .persisted_in(storage)
+            .unwrap();
+        assert_eq!(fresh.report().a.searches, 0);
+    }
+
+    #[test]
+    fn test_rejects_invalid_ratio() {
+        assert!(Experiment::new(Scorer::default(), 1.5).is_err());
+    }
+}
@@ -1,21 +1,61 @@
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
 
 use anyhow::Result;
 use chrono::Utc;
 use oc_core::Storage;
-use oc_core::models::{Memory, SearchQuery, SearchResult};
+use oc_core::cache::CacheStats;
+use oc_core::config::{EscalationConfig, FusionStrategy, MissesConfig};
+use oc_core::models::{Escalation, Memory, ScoreExplanation, SearchQuery, SearchResult};
+use serde::Serialize;
 
 use crate::bm25::Bm25Index;
+use crate::experiment::{Experiment, ExperimentReport};
+use crate::profile::Profiles;
+use crate::query_cache::QueryCache;
 use crate::scoring::Scorer;
-use crate::vector::VectorIndex;
+use crate::shards::VectorShards;
+use crate::vector::{VectorIndex, chunk_key, memory_id_of};
+
+/// Nearest vectors examined for a duplicate; chunk vectors may crowd the top
+const DUPLICATE_CANDIDATES: usize = 8;
+
+/// Indexed versus stored counts of both indices
+/// (see [`HybridSearch::consistency`])
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexConsistency {
+    /// Documents in the BM25 index
+    pub text_indexed: usize,
+    /// Memories in storage, each of which should have a BM25 document
+    pub stored: usize,
+    /// Memory vectors in the vector index, not counting chunk vectors
+    pub vector_indexed: usize,
+    /// Stored memory embeddings of the vector index's dimensions
+    pub embedded: usize,
+}
+
+impl IndexConsistency {
+    pub fn text_consistent(&self) -> bool {
+        self.text_indexed == self.stored
+    }
+
+    pub fn vector_consistent(&self) -> bool {
+        self.vector_indexed == self.embedded
+    }
+}
 
 /// Hybrid search combining vector similarity + BM25 keyword search + time decay
 pub struct HybridSearch {
     storage: Arc<Storage>,
     vector_index: VectorIndex,
+    shards: Option<VectorShards>,
     bm25_index: Bm25Index,
     scorer: Scorer,
+    profiles: Profiles,
+    experiment: Option<Experiment>,
+    query_cache: QueryCache,
 }
 
 impl HybridSearch {
@@ -28,32 +68,366 @@ impl HybridSearch {
         Self {
             storage,
             vector_index,
+            shards: None,
             bm25_index,
             scorer,
+            profiles: Profiles::default(),
+            experiment: None,
+            query_cache: QueryCache::default(),
+        }
+    }
+
+    /// Current scoring weights
+    pub fn scorer(&self) -> &Scorer {
+        &self.scorer
+    }
+
+    /// Replace the scoring weights used by subsequent searches
+    pub fn set_scorer(&mut self, scorer: Scorer) {
+        self.scorer = scorer;
+        self.query_cache.clear();
+    }
+
+    /// Replace the named scoring profiles searches can select
+    pub fn set_profiles(&mut self, profiles: Profiles) {
+        self.profiles = profiles;
+        self.query_cache.clear();
+    }
+
+    /// Start (or with `None`, stop) an A/B ranking experiment. Searches
+    /// bypass the query cache while one runs.
+    pub fn set_experiment(&mut self, experiment: Option<Experiment>) {
+        self.experiment = experiment;
+        self.query_cache.clear();
+    }
+
+    /// Serve repeated searches from `cache` (off by default)
+    pub fn set_query_cache(&mut self, cache: QueryCache) {
+        self.query_cache = cache;
+    }
+
+    /// Search namespace-restricted queries through per-namespace `shards`
+    /// (none until set; [`VectorShards::from_config`] gives them unless
+    /// `[search] vector_shards` is off). Shards of other dimensions than
+    /// the vector index are ignored.
+    pub fn set_vector_shards(&mut self, shards: Option<VectorShards>) {
+        self.shards = shards.filter(|shards| shards.dimensions() == self.vector_index.dimensions());
+        self.query_cache.clear();
+    }
+
+    /// Namespaces searched through their own vector shard
+    pub fn shard_namespaces(&self) -> Vec<String> {
+        self.shards
+            .as_ref()
+            .map(VectorShards::namespaces)
+            .unwrap_or_default()
+    }
+
+    /// Hits and misses of the query cache
+    pub fn query_cache_stats(&self) -> CacheStats {
+        self.query_cache.stats()
+    }
+
+    /// Comparative metrics of the active experiment, if any
+    pub fn experiment_report(&self) -> Option<ExperimentReport> {
+        self.experiment.as_ref().map(Experiment::report)
+    }
+
+    /// Report memories fetched after a search so the experiment can
+    /// attribute them to the variant that served them
+    pub fn record_fetch(&self, ids: &[String]) {
+        if let Some(experiment) = &self.experiment {
+            experiment.record_fetch(ids);
+        }
+    }
+
+    /// Persist the vector index and any created shards, stamped with the
+    /// storage data version
+    pub fn save_vector_index(&self, path: impl AsRef<Path>) -> Result<()> {
+        let version = self.storage.data_version()?;
+        self.vector_index.save(path, &version)?;
+        match &self.shards {
+            Some(shards) => shards.save(&version),
+            None => Ok(()),
+        }
+    }
+
+    /// Replace the vector index with the one saved at `path` if it is still
+    /// current. Returns false (leaving the index untouched) when the file is
+    /// missing, unreadable, built for other dimensions, or out of date, in
+    /// which case the caller should rebuild from storage.
+    pub fn load_vector_index(&mut self, path: impl AsRef<Path>) -> Result<bool> {
+        let path = path.as_ref();
+        if !path.is_file() {
+            return Ok(false);
+        }
+        let (index, fingerprint) = match VectorIndex::load(path) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                tracing::warn!("Ignoring unreadable vector index {}: {e}", path.display());
+                return Ok(false);
+            }
+        };
+        if index.dimensions() != self.vector_index.dimensions() {
+            tracing::info!("Vector index dimensions changed; rebuilding");
+            return Ok(false);
+        }
+        if fingerprint != self.storage.data_version()? {
+            tracing::info!("Vector index is out of date; rebuilding");
+            return Ok(false);
         }
+        self.vector_index = index;
+        self.clear_shards();
+        self.query_cache.clear();
+        Ok(true)
     }
 
-    /// Mutable access to vector index (for loading embeddings)
+    /// Replace the vector index with a fresh one of `dimensions` built from
+    /// the stored embeddings (after re-embedding). Embeddings of other
+    /// dimensions are left out. Returns the number of indexed vectors.
+    pub fn rebuild_vector_index(&mut self, dimensions: usize) -> Result<usize> {
+        let entries: Vec<_> = stored_vectors(&self.storage)?
+            .into_iter()
+            .filter(|(_, embedding)| embedding.len() == dimensions)
+            .collect();
+        let mut index = VectorIndex::new(dimensions);
+        index.build_from(entries)?;
+        self.vector_index = index;
+        self.clear_shards();
+        self.query_cache.clear();
+        Ok(self.vector_index.len())
+    }
+
+    /// Add every stored memory and chunk embedding to the vector index
+    /// (startup rebuild). Vectors of other dimensions are skipped.
+    pub fn index_stored_vectors(&mut self) -> Result<usize> {
+        self.clear_shards();
+        self.query_cache.clear();
+        let mut indexed = 0;
+        for (key, embedding) in stored_vectors(&self.storage)? {
+            if self.vector_index.upsert(key, embedding).is_ok() {
+                indexed += 1;
+            }
+        }
+        Ok(indexed)
+    }
+
+    /// Mutable access to vector index (for loading embeddings). Shards
+    /// are dropped, to be rebuilt from storage.
     pub fn vector_index_mut(&mut self) -> &mut VectorIndex {
+        self.clear_shards();
+        self.query_cache.clear();
         &mut self.vector_index
     }
 
-    /// Search memories using hybrid vector + BM25 with RRF fusion
+    fn clear_shards(&mut self) {
+        let dimensions = self.vector_index.dimensions();
+        if let Some(shards) = &mut self.shards {
+            shards.clear(dimensions);
+        }
+    }
+
+    /// The shard registry a search restricted to `namespace` goes through,
+    /// creating its shard if need be; `None` searches the whole index
+    fn shard_for<'a>(
+        &'a self,
+        namespace: Option<&'a str>,
+    ) -> Result<Option<(&'a VectorShards, &'a str)>> {
+        let (Some(shards), Some(namespace)) = (&self.shards, namespace) else {
+            return Ok(None);
+        };
+        shards.ensure(namespace, &self.storage)?;
+        Ok(Some((shards, namespace)))
+    }
+
+    /// Nearest vectors to `embedding`, from the shard of `namespace` when
+    /// there is one
+    fn nearest(
+        &self,
+        shard: Option<(&VectorShards, &str)>,
+        embedding: &[f32],
+        limit: usize,
+    ) -> Vec<(String, f32)> {
+        match shard {
+            Some((shards, namespace)) => shards.search(namespace, embedding, limit),
+            None => self.vector_index.search(embedding, limit),
+        }
+    }
+
+    /// Search memories using hybrid vector + BM25, fused by weighted sum or
+    /// RRF depending on the scorer
     pub fn search(
         &self,
         query_embedding: &[f32],
         query: &SearchQuery,
+    ) -> Result<Vec<SearchResult>> {
+        self.search_fused(&[query_embedding], query)
+    }
+
+    /// [`HybridSearch::search`] with every formulation of the query (see
+    /// [`SearchQuery::texts`]): each one is retrieved on its own, and the
+    /// vector and keyword hits of all of them are merged by RRF before
+    /// scoring. `query_embeddings` are in the same order; a formulation
+    /// with a missing or unusable embedding is only searched by keyword.
+    /// A repeat of a recent search is answered from the query cache.
+    pub fn search_fused(
+        &self,
+        query_embeddings: &[&[f32]],
+        query: &SearchQuery,
+    ) -> Result<Vec<SearchResult>> {
+        // Experiment variants are assigned per search
+        if !self.query_cache.is_enabled() || self.experiment.is_some() {
+            return self.search_uncached(query_embeddings, query);
+        }
+        let key = QueryCache::key(query, query_embeddings);
+        let version = self.storage.search_version()?;
+        if let Some(results) = self.query_cache.get(&key, version) {
+            for result in &results {
+                let _ = self
+                    .storage
+                    .touch_hit(&result.memory.id, result.score_breakdown.recency);
+            }
+            return Ok(results);
+        }
+        let results = self.search_uncached(query_embeddings, query)?;
+        self.query_cache.insert(key, version, &results);
+        Ok(results)
+    }
+
+    /// Run several searches side by side, one thread each, given the
+    /// embeddings of every formulation of each query (empty for
+    /// keyword-only). Results come back in the order of `batch`.
+    pub fn search_batch(
+        &self,
+        batch: &[(Vec<&[f32]>, SearchQuery)],
+    ) -> Vec<Result<Vec<SearchResult>>> {
+        if batch.len() <= 1 {
+            return batch
+                .iter()
+                .map(|(embeddings, query)| self.search_fused(embeddings, query))
+                .collect();
+        }
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = batch
+                .iter()
+                .map(|(embeddings, query)| {
+                    scope.spawn(move || self.search_fused(embeddings, query))
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|_| Err(anyhow::anyhow!("search thread panicked")))
+                })
+                .collect()
+        })
+    }
+
+    fn search_uncached(
+        &self,
+        query_embeddings: &[&[f32]],
+        query: &SearchQuery,
     ) -> Result<Vec<SearchResult>> {
         let expanded_limit = query.limit * 3; // Over-fetch for fusion
+        // A filter without text (`type:decision tag:rust`) lists every
+        // memory it accepts, ranked without the semantic or keyword parts
+        let listing = query.filter.is_some() && query.query.trim().is_empty();
+        let query_embeddings: &[&[f32]] = if listing { &[] } else { query_embeddings };
+
+        // A scoring profile takes the search out of any A/B experiment
+        let profile = self.profiles.select(query)?;
+        let profiled;
+        let (variant, scorer) = match profile {
+            Some(profile) => {
+                profiled = self.scorer.with_update(&profile.update);
+                (None, &profiled)
+            }
+            None => {
+                let variant = self.experiment.as_ref().map(Experiment::assign);
+                let scorer = match (&self.experiment, variant) {
+                    (Some(experiment), Some(v)) => experiment.scorer_for(v, &self.scorer),
+                    _ => &self.scorer,
+                };
+                (variant, scorer)
+            }
+        };
+
+        // A failed or missing query embedding drops the vector leg; its
+        // weight moves to the other components for this query only
+        let dimensions = self.vector_index.dimensions();
+        let vector_leg = query_embeddings
+            .iter()
+            .any(|embedding| is_usable_embedding(embedding, dimensions));
+        let keyword_only;
+        let scorer = if vector_leg {
+            scorer
+        } else {
+            keyword_only = scorer.without_semantic();
+            &keyword_only
+        };
 
-        // 1. Vector search
-        let vector_results = self.vector_index.search(query_embedding, expanded_limit);
+        // 1. Vector search, through the namespace's shard when the query
+        // targets one. Chunk hits count towards their memory with the best
+        // chunk's similarity, so over-fetch further when chunks exist.
+        let shard = if vector_leg {
+            self.shard_for(query.namespace.as_deref())?
+        } else {
+            None
+        };
+        let has_chunks = match shard {
+            Some((shards, namespace)) => shards.has_chunks(namespace),
+            None => self.vector_index.len() > self.vector_index.memory_count(),
+        };
+        let fetch = if has_chunks {
+            expanded_limit * 3
+        } else {
+            expanded_limit
+        };
+        let mut vector_lists = Vec::new();
+        for &embedding in query_embeddings {
+            if !is_usable_embedding(embedding, dimensions) {
+                continue;
+            }
+            let mut vector_results: Vec<(String, f32)> = Vec::new();
+            for (key, score) in self.nearest(shard, embedding, fetch) {
+                let id = memory_id_of(&key);
+                if !vector_results.iter().any(|(seen, _)| seen == id) {
+                    vector_results.push((id.to_string(), score));
+                }
+            }
+            vector_results.truncate(expanded_limit);
+            vector_lists.push(vector_results);
+        }
+        let vector_results = fuse_ranked(vector_lists, scorer.rrf_k, expanded_limit);
 
-        // 2. BM25 keyword search
-        let bm25_results = self
-            .bm25_index
-            .search(&query.query, expanded_limit)
-            .unwrap_or_default();
+        // 2. BM25 keyword search. Scores are normalized per formulation so
+        // that no one query's BM25 scale dominates the fused keyword score.
+        let mut bm25_raw: HashMap<String, f32> = HashMap::new();
+        let bm25_lists = query
+            .texts()
+            .map(|text| {
+                let hits = if query.exact {
+                    self.bm25_index.search_exact(text, expanded_limit)
+                } else {
+                    self.bm25_index.search(text, expanded_limit)
+                };
+                let mut hits = hits.unwrap_or_default();
+                if query.explain {
+                    for (id, score) in &hits {
+                        let best = bm25_raw.entry(id.clone()).or_insert(0.0);
+                        *best = best.max(*score);
+                    }
+                }
+                let max = hits.iter().map(|(_, score)| *score).fold(0.0, f32::max);
+                if max > 0.0 {
+                    hits.iter_mut().for_each(|(_, score)| *score /= max);
+                }
+                hits
+            })
+            .collect();
+        let bm25_results = fuse_ranked(bm25_lists, scorer.rrf_k, expanded_limit);
 
         // 3. Build rank maps
         let mut vector_ranks: HashMap<String, usize> = HashMap::new();
@@ -78,10 +452,40 @@ impl HybridSearch {
             .collect();
         all_ids.sort();
         all_ids.dedup();
+        if query.exact {
+            // Vector neighbours need not contain the phrase
+            all_ids.retain(|id| bm25_ranks.contains_key(id));
+        }
+        if listing {
+            all_ids = self
+                .storage
+                .all()?
+                .into_iter()
+                .filter(|memory| query.matches_filter(memory))
+                .map(|memory| memory.id)
+                .collect();
+        }
+        let quarantined = self.storage.quarantined_among(&all_ids)?;
+        all_ids.retain(|id| !quarantined.contains(id));
+        if !query.include_archived {
+            let archived = self.storage.archived_among(&all_ids)?;
+            all_ids.retain(|id| !archived.contains(id));
+        }
+        if !query.include_cold {
+            let cold = self.storage.cold_among(&all_ids)?;
+            all_ids.retain(|id| !cold.contains(id));
+        }
+        if !query.fields.is_empty() {
+            let matching = self.storage.matching_fields(&all_ids, &query.fields)?;
+            all_ids.retain(|id| matching.contains(id));
+        }
 
         // 5. Score each candidate
         let now = Utc::now();
+        let feedback = self.storage.feedback_of(&all_ids)?;
         let mut scored_results: Vec<(String, f32, oc_core::models::ScoreBreakdown)> = Vec::new();
+        let mut sources: HashMap<String, String> = HashMap::new();
+        let mut explanations: HashMap<String, ScoreExplanation> = HashMap::new();
 
         for id in &all_ids {
             let semantic = *vector_scores.get(id).unwrap_or(&0.0);
@@ -99,34 +503,119 @@ impl HybridSearch {
 
             // Get memory for time/priority info
             if let Ok(Some(memory)) = self.storage.get(id) {
+                // Expired but not yet swept
+                if memory.is_expired(now)
+                    || !query.matches_tags(&memory)
+                    || !query.passes_exclusions(&memory)
+                    || !query.matches_concepts(&memory)
+                    || !query.matches_namespace(&memory)
+                    || !query.matches_filter(&memory)
+                    || query
+                        .memory_type
+                        .is_some_and(|t| t != memory.metadata.memory_type)
+                {
+                    continue;
+                }
                 let days_since = (now - memory.accessed_at).num_hours() as f32 / 24.0;
-                let (score, breakdown) = self.scorer.combined_score(
-                    semantic,
-                    keyword,
-                    days_since,
-                    memory.metadata.priority,
-                );
+                let priority = memory.metadata.priority;
+                let (score, breakdown) = match scorer.fusion {
+                    FusionStrategy::WeightedSum => {
+                        scorer.combined_score(semantic, keyword, days_since, priority)
+                    }
+                    FusionStrategy::Rrf => scorer.fused_score(
+                        vector_ranks.get(id).copied(),
+                        bm25_ranks.get(id).copied(),
+                        semantic,
+                        keyword,
+                        days_since,
+                        priority,
+                    ),
+                };
+                let (score, breakdown) = scorer.with_access(score, breakdown, memory.access_count);
+                let (score, breakdown) = match feedback.get(id) {
+                    Some(feedback) => scorer.with_feedback(score, breakdown, feedback.usefulness()),
+                    None => (score, breakdown),
+                };
+                if let Some(source) = memory.metadata.source {
+                    sources.insert(id.clone(), source);
+                }
+                if query.explain {
+                    explanations.insert(
+                        id.clone(),
+                        ScoreExplanation {
+                            cosine: vector_scores.get(id).copied(),
+                            bm25: bm25_raw.get(id).copied(),
+                            vector_rank: vector_ranks.get(id).copied(),
+                            keyword_rank: bm25_ranks.get(id).copied(),
+                            days_since_access: days_since,
+                            access_count: memory.access_count,
+                            weights: scorer.weights(),
+                            half_life_days: scorer.half_life_days,
+                            fusion: scorer.fusion,
+                            rrf_k: scorer.rrf_k,
+                        },
+                    );
+                }
                 scored_results.push((id.clone(), score, breakdown));
             }
         }
 
-        // 6. Sort by final score
-        scored_results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        // 6. Sort by final score, then recency/importance
+        scored_results.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| {
+                    scorer
+                        .tie_break(&b.2)
+                        .partial_cmp(&scorer.tie_break(&a.2))
+                        .unwrap_or(Ordering::Equal)
+                })
+        });
+
+        // 7. Keep the best result of each source
+        let mut collapsed: HashMap<String, usize> = HashMap::new();
+        if query.collapse_sources {
+            let mut best_of: HashMap<&str, String> = HashMap::new();
+            scored_results.retain(|(id, _, _)| {
+                let Some(source) = sources.get(id) else {
+                    return true;
+                };
+                match best_of.get(source.as_str()) {
+                    Some(best) => {
+                        *collapsed.entry(best.clone()).or_default() += 1;
+                        false
+                    }
+                    None => {
+                        best_of.insert(source, id.clone());
+                        true
+                    }
+                }
+            });
+        }
         scored_results.truncate(query.limit);
 
-        // 7. Fetch full memories and build results
+        // 8. Fetch full memories and build results
         let result_ids: Vec<String> = scored_results.iter().map(|(id, _, _)| id.clone()).collect();
         let memories = self.storage.get_many(&result_ids)?;
 
+        if let (Some(experiment), Some(v)) = (&self.experiment, variant) {
+            experiment.record_served(v, &result_ids);
+        }
+
         let memory_map: HashMap<String, Memory> =
             memories.into_iter().map(|m| (m.id.clone(), m)).collect();
+        let mut linked = if query.expand_links > 0 {
+            self.storage.expand_links(&result_ids, query.expand_links)?
+        } else {
+            HashMap::new()
+        };
 
         let results = scored_results
             .into_iter()
             .filter_map(|(id, score, breakdown)| {
                 memory_map.get(&id).map(|memory| {
-                    // Touch for access tracking
-                    let _ = self.storage.touch(&id);
+                    // Touch for access tracking and hit analytics
+                    let _ = self.storage.touch_hit(&id, breakdown.recency);
 
                     SearchResult {
                         memory: if query.index_only {
@@ -144,6 +633,12 @@ impl HybridSearch {
                         },
                         score,
                         score_breakdown: breakdown,
+                        variant: variant.map(|v| v.as_str().to_string()),
+                        profile: profile.map(|p| p.name.clone()),
+                        degraded: !vector_leg && !listing,
+                        linked: linked.remove(&id).unwrap_or_default(),
+                        collapsed_count: collapsed.get(&id).copied().unwrap_or(0),
+                        explanation: explanations.remove(&id),
                     }
                 })
             })
@@ -152,36 +647,277 @@ impl HybridSearch {
         Ok(results)
     }
 
+    /// Did-you-mean corrections of `query` when its keywords match nothing
+    /// (see [`Bm25Index::suggest`]); empty when they match or `limit` is 0
+    pub fn suggest(&self, query: &str, limit: usize) -> Result<Vec<String>> {
+        if limit == 0
+            || !self
+                .bm25_index
+                .search(query, 1)
+                .unwrap_or_default()
+                .is_empty()
+        {
+            return Ok(Vec::new());
+        }
+        self.bm25_index.suggest(query, limit)
+    }
+
+    /// Memories most like `memory`, best first, never `memory` itself:
+    /// a hybrid search with its stored embedding and its title and concepts
+    /// as the keyword query. Without an embedding the search is keyword-only.
+    pub fn related(&self, memory: &Memory, limit: usize) -> Result<Vec<SearchResult>> {
+        let mut terms = vec![memory.title.as_str()];
+        terms.extend(memory.metadata.concepts.iter().map(String::as_str));
+        let query = SearchQuery {
+            query: terms.join(" "),
+            limit: limit + 1,
+            namespace: Some(memory.namespace.clone()),
+            ..Default::default()
+        };
+        let embedding = memory.embedding.as_deref().unwrap_or_default();
+        let mut results = self.search(embedding, &query)?;
+        results.retain(|r| r.memory.id != memory.id);
+        results.truncate(limit);
+        Ok(results)
+    }
+
     /// Add a memory to both indices
     pub fn index_memory(&mut self, memory: &Memory) -> Result<()> {
         // Add to vector index
         if let Some(ref embedding) = memory.embedding {
             self.vector_index
                 .upsert(memory.id.clone(), embedding.clone())?;
+            if let Some(shards) = &mut self.shards {
+                shards.upsert(&memory.namespace, &memory.id, embedding)?;
+            }
         }
 
         // Add to BM25 index
         self.bm25_index
             .add(&memory.id, &memory.title, &memory.content)?;
 
+        self.query_cache.clear();
+        Ok(())
+    }
+
+    /// Replace the chunk vectors of a memory in the vector index
+    pub fn index_chunks(&mut self, memory_id: &str, chunks: &[Vec<f32>]) -> Result<()> {
+        self.query_cache.clear();
+        self.vector_index.remove_chunks(memory_id);
+        for (chunk, embedding) in chunks.iter().enumerate() {
+            self.vector_index
+                .upsert(chunk_key(memory_id, chunk), embedding.clone())?;
+        }
+        if let Some(shards) = &mut self.shards {
+            shards.remove_chunks(memory_id);
+            let namespace = match shards.namespace_of(memory_id) {
+                Some(namespace) => Some(namespace),
+                None => self.storage.get(memory_id)?.map(|m| m.namespace),
+            };
+            if let Some(namespace) = namespace {
+                for (chunk, embedding) in chunks.iter().enumerate() {
+                    shards.upsert(&namespace, &chunk_key(memory_id, chunk), embedding)?;
+                }
+            }
+        }
         Ok(())
     }
 
     /// Add text only to BM25 index (for rebuilding without full Memory object)
     pub fn index_memory_text(&mut self, id: &str, title: &str, content: &str) -> Result<()> {
+        self.query_cache.clear();
         self.bm25_index.add(id, title, content)?;
         Ok(())
     }
 
+    /// Replace the BM25 index contents with `(id, title, content)` entries,
+    /// committing once (startup rebuild)
+    pub fn rebuild_text_index(&mut self, entries: &[(String, String, String)]) -> Result<usize> {
+        self.query_cache.clear();
+        self.bm25_index.clear()?;
+        self.bm25_index.add_batch(entries)
+    }
+
+    /// The indexed memory of `namespace` most similar to `embedding` if its
+    /// cosine similarity reaches `threshold`. Only whole-memory vectors count,
+    /// so a short note is not a duplicate of a long memory one chunk resembles.
+    pub fn find_duplicate(
+        &self,
+        embedding: &[f32],
+        threshold: f32,
+        namespace: &str,
+    ) -> Option<(String, f32)> {
+        if !is_usable_embedding(embedding, self.vector_index.dimensions()) {
+            return None;
+        }
+        let shard = self.shard_for(Some(namespace)).ok().flatten();
+        self.nearest(shard, embedding, DUPLICATE_CANDIDATES)
+            .into_iter()
+            .filter(|(key, similarity)| memory_id_of(key) == key && *similarity >= threshold)
+            .find(|(id, _)| {
+                self.storage
+                    .get(id)
+                    .ok()
+                    .flatten()
+                    .is_some_and(|m| m.namespace == namespace)
+            })
+    }
+
+    /// Count a store of a memory embedded as `embedding` as a repeat of the
+    /// most similar indexed memory of `namespace`, when it reaches
+    /// `config.similarity`, escalating that memory's priority per
+    /// [`Storage::record_repeat`]. Call before indexing the new memory.
+    pub fn record_topic_repeat(
+        &self,
+        embedding: &[f32],
+        namespace: &str,
+        config: &EscalationConfig,
+    ) -> Result<Option<Escalation>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        let Some((id, _)) = self.find_duplicate(embedding, config.similarity, namespace) else {
+            return Ok(None);
+        };
+        Ok(self
+            .storage
+            .record_repeat(&id, config.repeats, config.window_days)?)
+    }
+
+    /// Record `query` as missed when it found nothing, or nothing scoring
+    /// `config.min_score` (see [`Storage::record_miss`]). A task memory
+    /// stored for the gap is indexed and returned.
+    pub fn record_miss(
+        &mut self,
+        query: &SearchQuery,
+        results: &[SearchResult],
+        config: &MissesConfig,
+    ) -> Result<Option<Memory>> {
+        let best = results.iter().map(|r| r.score).reduce(f32::max);
+        if !config.enabled || best.is_some_and(|score| score >= config.min_score) {
+            return Ok(None);
+        }
+        let task = self.storage.record_miss(
+            &query.query,
+            query.namespace.as_deref(),
+            best,
+            config.create_task_after,
+        )?;
+        if let Some(task) = &task {
+            self.index_memory(task)?;
+        }
+        Ok(task)
+    }
+
+    /// Refresh the BM25 entries of these memories from storage in one
+    /// commit (after bulk edits). Returns how many were reindexed.
+    pub fn reindex_text(&mut self, ids: &[String]) -> Result<usize> {
+        let entries: Vec<(String, String, String)> = self
+            .storage
+            .get_many(ids)?
+            .into_iter()
+            .map(|m| (m.id, m.title, m.content))
+            .collect();
+        self.query_cache.clear();
+        self.bm25_index.add_batch(&entries)
+    }
+
     /// Remove a memory from both indices
     pub fn remove_memory(&mut self, id: &str) -> Result<()> {
+        self.query_cache.clear();
         self.vector_index.remove(id);
+        self.vector_index.remove_chunks(id);
+        if let Some(shards) = &mut self.shards {
+            shards.remove(id);
+            shards.remove_chunks(id);
+        }
         self.bm25_index.remove(id)?;
         Ok(())
     }
 
     /// Number of indexed memories
     pub fn indexed_count(&self) -> usize {
-        self.vector_index.len()
+        self.vector_index.memory_count()
+    }
+
+    /// Dimensions of the vectors the vector index holds
+    pub fn vector_dimensions(&self) -> usize {
+        self.vector_index.dimensions()
     }
+
+    /// Number of documents in the BM25 index, read through its reader
+    /// (after flushing buffered changes); fails when the index cannot be
+    /// read
+    pub fn text_indexed_count(&self) -> Result<usize> {
+        self.bm25_index.doc_count()
+    }
+
+    /// Compare what both indices hold with storage. A mismatch means an
+    /// index missed writes (or kept deleted memories) and should be rebuilt.
+    pub fn consistency(&self) -> Result<IndexConsistency> {
+        let dimensions = self.vector_index.dimensions();
+        let embedded = self
+            .storage
+            .embedding_versions()?
+            .iter()
+            .filter(|v| v.dimensions.is_none_or(|d| d == dimensions))
+            .map(|v| v.count)
+            .sum();
+        Ok(IndexConsistency {
+            text_indexed: self.bm25_index.doc_count()?,
+            stored: self.storage.count()?,
+            vector_indexed: self.vector_index.memory_count(),
+            embedded,
+        })
+    }
+}
+
+/// Stored memory embeddings plus chunk embeddings (keyed by [`chunk_key`])
+pub fn stored_vectors(storage: &Storage) -> Result<Vec<(String, Vec<f32>)>> {
+    let mut entries = storage.all_embeddings()?;
+    entries.extend(
+        storage
+            .all_chunk_embeddings()?
+            .into_iter()
+            .map(|(id, chunk, embedding)| (chunk_key(&id, chunk), embedding)),
+    );
+    Ok(entries)
+}
+
+/// Merge ranked `(id, score)` lists into one ranked by Reciprocal Rank
+/// Fusion, keeping each ID's best score; a single list passes unchanged
+fn fuse_ranked(lists: Vec<Vec<(String, f32)>>, k: f32, limit: usize) -> Vec<(String, f32)> {
+    if lists.len() <= 1 {
+        return lists.into_iter().next().unwrap_or_default();
+    }
+    let mut fused: HashMap<String, (f32, f32)> = HashMap::new();
+    for list in lists {
+        for (rank, (id, score)) in list.into_iter().enumerate() {
+            let entry = fused.entry(id).or_insert((0.0, f32::NEG_INFINITY));
+            entry.0 += Scorer::rrf_score(&[rank + 1], k);
+            entry.1 = entry.1.max(score);
+        }
+    }
+    let mut fused: Vec<(String, f32, f32)> = fused
+        .into_iter()
+        .map(|(id, (rrf, score))| (id, rrf, score))
+        .collect();
+    fused.sort_by(|a, b| {
+        b.1.total_cmp(&a.1)
+            .then_with(|| b.2.total_cmp(&a.2))
+            .then_with(|| a.0.cmp(&b.0))
+    });
+    fused.truncate(limit);
+    fused
+        .into_iter()
+        .map(|(id, _, score)| (id, score))
+        .collect()
+}
+
+/// A query embedding the vector index can use: right dimensions, finite and
+/// not all zeros (the placeholder callers pass without an embedding)
+fn is_usable_embedding(embedding: &[f32], dimensions: usize) -> bool {
+    embedding.len() == dimensions
+        && embedding.iter().all(|v| v.is_finite())
+        && embedding.iter().any(|&v| v != 0.0)
 }
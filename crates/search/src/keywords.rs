@@ -0,0 +1,258 @@
+#[cfg(feature = "lindera")]
+use lindera::dictionary::{DictionaryKind, load_dictionary_from_kind};
+#[cfg(feature = "lindera")]
+use lindera::mode::Mode;
+#[cfg(feature = "lindera")]
+use lindera::segmenter::Segmenter;
+use oc_core::models::Memory;
+#[cfg(feature = "lindera")]
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Candidates within this many positions of each other are linked
+const WINDOW: usize = 3;
+/// TextRank damping factor
+const DAMPING: f32 = 0.85;
+const ITERATIONS: usize = 30;
+
+/// ko-dic parts of speech kept as candidates: common and proper nouns,
+/// foreign words (mostly English identifiers) and Chinese characters
+#[cfg(feature = "lindera")]
+const NOUN_TAGS: [&str; 4] = ["NNG", "NNP", "SL", "SH"];
+
+/// Particles stripped from word ends when ko-dic is unavailable, longest first
+const PARTICLES: [&str; 18] = [
+    "에서는",
+    "으로는",
+    "에서",
+    "으로",
+    "에게",
+    "까지",
+    "부터",
+    "보다",
+    "을",
+    "를",
+    "이",
+    "가",
+    "은",
+    "는",
+    "에",
+    "의",
+    "로",
+    "도",
+];
+
+const STOPWORDS: [&str; 24] = [
+    "the", "and", "for", "with", "this", "that", "from", "are", "was", "were", "not", "but", "you",
+    "have", "has", "will", "can", "into", "when", "then", "것", "수", "등", "및",
+];
+
+/// Picks the key terms of a memory without a model: TextRank over the
+/// nouns (lindera ko-dic morphemes) of its title and content. Falls back to
+/// whitespace words with Korean particles stripped when the dictionary is
+/// not available, as in builds without the `lindera` feature.
+pub struct KeywordExtractor {
+    #[cfg(feature = "lindera")]
+    segmenter: Option<Segmenter>,
+}
+
+impl KeywordExtractor {
+    #[cfg(not(feature = "lindera"))]
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    #[cfg(feature = "lindera")]
+    pub fn new() -> Self {
+        let segmenter = match load_dictionary_from_kind(DictionaryKind::KoDic) {
+            Ok(dictionary) => Some(Segmenter::new(Mode::Normal, dictionary, None)),
+            Err(e) => {
+                tracing::warn!("ko-dic unavailable ({e}); keyword extraction splits on words");
+                None
+            }
+        };
+        // Builds without the embedded dictionary load an empty one
+        let segmenter = segmenter.filter(|s| {
+            let tags = s.segment(Cow::Borrowed("한국어")).is_ok_and(|mut tokens| {
+                tokens.iter_mut().any(|t| {
+                    t.details()
+                        .first()
+                        .is_some_and(|pos| NOUN_TAGS.contains(pos))
+                })
+            });
+            if !tags {
+                tracing::warn!("ko-dic is empty; keyword extraction splits on words");
+            }
+            tags
+        });
+        Self { segmenter }
+    }
+
+    /// Up to `limit` key terms, best first, lowercased
+    pub fn extract(&self, title: &str, content: &str, limit: usize) -> Vec<String> {
+        // The title is repeated so its terms weigh more
+        let text = format!("{title}\n{title}\n{content}");
+        let terms = self.candidates(&text);
+        rank(&terms).into_iter().take(limit).collect()
+    }
+
+    /// Set a memory's concepts to its top `limit` keywords unless it
+    /// already has some
+    pub fn fill_concepts(&self, memory: &mut Memory, limit: usize) {
+        if memory.metadata.concepts.is_empty() {
+            memory.metadata.concepts = self.extract(&memory.title, &memory.content, limit);
+        }
+    }
+
+    /// Candidate terms in text order
+    fn candidates(&self, text: &str) -> Vec<String> {
+        let words = self.nouns(text).unwrap_or_else(|| split_words(text));
+        words.into_iter().filter(|w| is_keyword(w)).collect()
+    }
+
+    /// Nouns of `text` in order; `None` without a usable dictionary
+    #[cfg(feature = "lindera")]
+    fn nouns(&self, text: &str) -> Option<Vec<String>> {
+        let segmenter = self.segmenter.as_ref()?;
+        match segmenter.segment(Cow::Borrowed(text)) {
+            Ok(mut tokens) => Some(
+                tokens
+                    .iter_mut()
+                    .filter_map(|t| {
+                        let noun = t
+                            .details()
+                            .first()
+                            .is_some_and(|pos| NOUN_TAGS.contains(pos));
+                        noun.then(|| t.text.to_lowercase())
+                    })
+                    .collect(),
+            ),
+            Err(e) => {
+                tracing::warn!("Segmenting failed: {e}");
+                None
+            }
+        }
+    }
+
+    #[cfg(not(feature = "lindera"))]
+    fn nouns(&self, _text: &str) -> Option<Vec<String>> {
+        None
+    }
+}
+
+impl Default for KeywordExtractor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn split_words(text: &str) -> Vec<String> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+        .map(|word| {
+            let word = word.trim_matches('-').to_lowercase();
+            match PARTICLES.iter().find_map(|p| {
+                word.strip_suffix(p)
+                    .filter(|stem| stem.chars().count() >= 2)
+            }) {
+                Some(stem) => stem.to_string(),
+                None => word,
+            }
+        })
+        .collect()
+}
+
+fn is_keyword(word: &str) -> bool {
+    let chars = word.chars().count();
+    let hangul = word.chars().any(|c| ('가'..='힣').contains(&c));
+    chars >= if hangul { 2 } else { 3 }
+        && !word.chars().all(|c| c.is_numeric())
+        && !STOPWORDS.contains(&word)
+}
+
+/// TextRank: terms are nodes, co-occurrence within [`WINDOW`] makes edges.
+/// Ties go to the more frequent, then the earlier term.
+fn rank(terms: &[String]) -> Vec<String> {
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    let mut nodes: Vec<&str> = Vec::new();
+    let mut frequency: Vec<usize> = Vec::new();
+    let sequence: Vec<usize> = terms
+        .iter()
+        .map(|term| {
+            let i = *index.entry(term).or_insert_with(|| {
+                nodes.push(term);
+                frequency.push(0);
+                nodes.len() - 1
+            });
+            frequency[i] += 1;
+            i
+        })
+        .collect();
+
+    let n = nodes.len();
+    let mut edges: Vec<HashMap<usize, f32>> = vec![HashMap::new(); n];
+    for (pos, &a) in sequence.iter().enumerate() {
+        for &b in sequence.iter().skip(pos + 1).take(WINDOW - 1) {
+            if a != b {
+                *edges[a].entry(b).or_default() += 1.0;
+                *edges[b].entry(a).or_default() += 1.0;
+            }
+        }
+    }
+    let out_weight: Vec<f32> = edges.iter().map(|e| e.values().sum()).collect();
+
+    let mut scores = vec![1.0_f32; n];
+    for _ in 0..ITERATIONS {
+        let mut next = vec![1.0 - DAMPING; n];
+        for (a, neighbours) in edges.iter().enumerate() {
+            for (&b, &weight) in neighbours {
+                next[b] += DAMPING * scores[a] * weight / out_weight[a];
+            }
+        }
+        scores = next;
+    }
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&a, &b| {
+        scores[b]
+            .total_cmp(&scores[a])
+            .then(frequency[b].cmp(&frequency[a]))
+            .then(a.cmp(&b))
+    });
+    order.into_iter().map(|i| nodes[i].to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_ranks_central_terms() {
+        let extractor = KeywordExtractor::new();
+        let keywords = extractor.extract(
+            "Tantivy 인덱스 커밋",
+            "Tantivy 인덱스는 검색 전에 커밋한다. 인덱스 재구축은 시작할 때 한다. \
+             The index writer batches commits.",
+            3,
+        );
+        assert_eq!(keywords.len(), 3);
+        assert!(keywords.contains(&"tantivy".to_string()), "{keywords:?}");
+        assert!(keywords.contains(&"인덱스".to_string()), "{keywords:?}");
+        assert!(!keywords.iter().any(|k| k == "the"));
+    }
+
+    #[test]
+    fn test_extract_handles_short_text() {
+        let extractor = KeywordExtractor::new();
+        assert!(extractor.extract("", "", 5).is_empty());
+        assert!(extractor.extract("a", "12 34", 5).is_empty());
+        assert_eq!(extractor.extract("배포", "", 5), vec!["배포"]);
+    }
+
+    #[test]
+    fn test_split_words_strips_particles() {
+        assert_eq!(
+            split_words("서버에서 인덱스를 rust-analyzer로"),
+            vec!["서버", "인덱스", "rust-analyzer"]
+        );
+    }
+}
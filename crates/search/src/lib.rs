@@ -1,6 +1,20 @@
+#[cfg(feature = "lindera")]
 pub mod bm25;
+#[cfg(not(feature = "lindera"))]
+#[path = "bm25_fts5.rs"]
+pub mod bm25;
+pub mod experiment;
 pub mod hybrid;
+pub mod keywords;
+pub mod profile;
+pub mod query_cache;
+pub mod query_syntax;
 pub mod scoring;
+pub mod selftest;
+pub mod shards;
+pub mod stopwords;
+pub mod suggest;
+pub mod synonyms;
 pub mod vector;
 
 pub use hybrid::HybridSearch;
@@ -0,0 +1,174 @@
+use anyhow::{Context, Result};
+use oc_core::config::SearchConfig;
+use oc_core::models::{MemoryType, SearchQuery, normalize_tags};
+
+use crate::scoring::{Scorer, ScorerUpdate};
+
+/// A named weight set and the searches it applies to by default
+#[derive(Debug, Clone)]
+pub struct Profile {
+    pub name: String,
+    /// Overrides applied on top of the current `[search]` weights
+    pub update: ScorerUpdate,
+    tags: Vec<String>,
+    memory_types: Vec<MemoryType>,
+}
+
+/// Scoring profiles from `[search.profiles]`
+#[derive(Debug, Clone, Default)]
+pub struct Profiles {
+    profiles: Vec<Profile>,
+}
+
+impl Profiles {
+    /// Load and validate the configured profiles against the `[search]`
+    /// weights they extend
+    pub fn from_config(config: &SearchConfig) -> Result<Self> {
+        let base = Scorer::from(config);
+        let mut profiles = Vec::new();
+        for (name, profile) in &config.profiles {
+            let update = ScorerUpdate {
+                semantic_weight: profile.semantic_weight,
+                keyword_weight: profile.keyword_weight,
+                recency_weight: profile.recency_weight,
+                importance_weight: profile.importance_weight,
+                access_weight: profile.access_weight,
+                half_life_days: profile.recency_half_life_days,
+            };
+            base.with_update(&update)
+                .validate()
+                .with_context(|| format!("Invalid scoring profile {name:?}"))?;
+            profiles.push(Profile {
+                name: name.clone(),
+                update,
+                tags: normalize_tags(&profile.tags),
+                memory_types: profile.memory_types.clone(),
+            });
+        }
+        Ok(Self { profiles })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.profiles.is_empty()
+    }
+
+    /// Profile names, sorted
+    pub fn names(&self) -> Vec<&str> {
+        self.profiles.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    /// The profile named by `query.profile`, else the first one bound to a
+    /// tag or the memory type the query filters on. Naming an unknown
+    /// profile is an error.
+    pub fn select(&self, query: &SearchQuery) -> Result<Option<&Profile>> {
+        if let Some(name) = &query.profile {
+            return self
+                .profiles
+                .iter()
+                .find(|p| &p.name == name)
+                .map(Some)
+                .with_context(|| {
+                    format!(
+                        "Unknown scoring profile {name:?} (available: {})",
+                        self.names().join(", ")
+                    )
+                });
+        }
+
+        let query_tags = normalize_tags(
+            &[
+                query.tags_all.as_deref().unwrap_or_default(),
+                query.tags_any.as_deref().unwrap_or_default(),
+            ]
+            .concat(),
+        );
+        Ok(self.profiles.iter().find(|p| {
+            p.tags.iter().any(|tag| query_tags.contains(tag))
+                || query
+                    .memory_type
+                    .is_some_and(|t| p.memory_types.contains(&t))
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oc_core::config::ScoringProfile;
+
+    fn profiles() -> Profiles {
+        let mut config = SearchConfig::default();
+        config.profiles.insert(
+            "code-work".to_string(),
+            ScoringProfile {
+                recency_weight: Some(0.35),
+                tags: vec!["Code-Work".to_string()],
+                ..Default::default()
+            },
+        );
+        config.profiles.insert(
+            "personal-facts".to_string(),
+            ScoringProfile {
+                importance_weight: Some(0.3),
+                memory_types: vec![MemoryType::Fact],
+                ..Default::default()
+            },
+        );
+        Profiles::from_config(&config).unwrap()
+    }
+
+    fn selected(profiles: &Profiles, query: &SearchQuery) -> Option<String> {
+        profiles.select(query).unwrap().map(|p| p.name.clone())
+    }
+
+    #[test]
+    fn test_select_by_name_tag_and_type() {
+        let profiles = profiles();
+        assert_eq!(profiles.names(), vec!["code-work", "personal-facts"]);
+
+        let by_name = SearchQuery {
+            profile: Some("personal-facts".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            selected(&profiles, &by_name).as_deref(),
+            Some("personal-facts")
+        );
+
+        let by_tag = SearchQuery {
+            tags_any: Some(vec!["code-work ".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(selected(&profiles, &by_tag).as_deref(), Some("code-work"));
+
+        let by_type = SearchQuery {
+            memory_type: Some(MemoryType::Fact),
+            ..Default::default()
+        };
+        assert_eq!(
+            selected(&profiles, &by_type).as_deref(),
+            Some("personal-facts")
+        );
+
+        assert_eq!(selected(&profiles, &SearchQuery::default()), None);
+
+        let unknown = SearchQuery {
+            profile: Some("nope".to_string()),
+            ..Default::default()
+        };
+        assert!(profiles.select(&unknown).is_err());
+    }
+
+    #[test]
+    fn test_invalid_profile_rejected() {
+        let mut config = SearchConfig::default();
+        config.profiles.insert(
+            "broken".to_string(),
+            ScoringProfile {
+                keyword_weight: Some(2.0),
+                ..Default::default()
+            },
+        );
+        assert!(Profiles::from_config(&config).is_err());
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use oc_core::cache::CacheStats;
+use oc_core::config::SearchConfig;
+use oc_core::models::{SearchQuery, SearchResult};
+
+/// Results of recent searches keyed by the query (text, filters and
+/// options) and a hash of its embeddings, evicting the least recently used.
+/// [`HybridSearch`](crate::HybridSearch) clears it whenever the indexes or
+/// the scoring change; entries also lapse when the storage search version
+/// moves (a memory was written, perhaps by another process) and after the
+/// TTL, which bounds staleness from changes neither notices (another
+/// process's tiering or feedback, recency drift).
+#[derive(Debug)]
+pub struct QueryCache {
+    capacity: usize,
+    ttl: Duration,
+    inner: Mutex<Inner>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    entries: HashMap<String, Entry>,
+    /// Bumped on every use, to order entries by recency
+    clock: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    results: Vec<SearchResult>,
+    /// [`Storage::search_version`](oc_core::Storage::search_version) the
+    /// results were computed at
+    version: (i64, u64),
+    stored_at: Instant,
+    used: u64,
+}
+
+impl Default for QueryCache {
+    fn default() -> Self {
+        Self::new(0, Duration::ZERO)
+    }
+}
+
+impl QueryCache {
+    /// A cache of up to `capacity` result lists, each served for `ttl`;
+    /// 0 disables it
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            inner: Mutex::new(Inner::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// The cache `query_cache_capacity` and `query_cache_ttl_secs` of
+    /// `[search]` describe
+    pub fn from_config(config: &SearchConfig) -> Self {
+        Self::new(
+            config.query_cache_capacity,
+            Duration::from_secs(config.query_cache_ttl_secs),
+        )
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0 && !self.ttl.is_zero()
+    }
+
+    /// Cache key of a search: the query serialized with every filter and
+    /// option, and a hash of the embeddings' bits
+    pub fn key(query: &SearchQuery, embeddings: &[&[f32]]) -> String {
+        let mut hasher = DefaultHasher::new();
+        for embedding in embeddings {
+            embedding.len().hash(&mut hasher);
+            for v in *embedding {
+                v.to_bits().hash(&mut hasher);
+            }
+        }
+        let query = serde_json::to_string(query).unwrap_or_default();
+        format!("{:016x}:{query}", hasher.finish())
+    }
+
+    /// Cached results of `key`, unless computed at another storage
+    /// `version` or longer than the TTL ago
+    pub fn get(&self, key: &str, version: (i64, u64)) -> Option<Vec<SearchResult>> {
+        let mut inner = self.lock();
+        inner.clock += 1;
+        let clock = inner.clock;
+        let stale = inner
+            .entries
+            .get(key)
+            .is_some_and(|entry| entry.version != version || entry.stored_at.elapsed() >= self.ttl);
+        if stale {
+            inner.entries.remove(key);
+        }
+        let fresh = inner.entries.get_mut(key).map(|entry| {
+            entry.used = clock;
+            entry.results.clone()
+        });
+        let counter = if fresh.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        fresh
+    }
+
+    /// Remember the results of `key`, computed at storage `version`
+    pub fn insert(&self, key: String, version: (i64, u64), results: &[SearchResult]) {
+        if !self.is_enabled() {
+            return;
+        }
+        let mut inner = self.lock();
+        inner.clock += 1;
+        let used = inner.clock;
+        if !inner.entries.contains_key(&key) && inner.entries.len() >= self.capacity {
+            let oldest = inner
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                inner.entries.remove(&oldest);
+            }
+        }
+        inner.entries.insert(
+            key,
+            Entry {
+                results: results.to_vec(),
+                version,
+                stored_at: Instant::now(),
+                used,
+            },
+        );
+    }
+
+    /// Drop every entry (the indexes or scoring changed)
+    pub fn clear(&self) {
+        self.lock().entries.clear();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        CacheStats {
+            hits,
+            misses,
+            entries: self.lock().entries.len(),
+            capacity: self.capacity,
+            hit_rate: if hits + misses == 0 {
+                0.0
+            } else {
+                hits as f64 / (hits + misses) as f64
+            },
+        }
+    }
+
+    /// The cache only holds copies, so a panic while it was locked leaves
+    /// nothing inconsistent
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(text: &str) -> SearchQuery {
+        SearchQuery {
+            query: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_keys_differ_by_filters_and_embedding() {
+        let plain = QueryCache::key(&query("러스트"), &[&[1.0, 0.0]]);
+        assert_eq!(plain, QueryCache::key(&query("러스트"), &[&[1.0, 0.0]]));
+        assert_ne!(plain, QueryCache::key(&query("러스트"), &[&[0.0, 1.0]]));
+        let filtered = SearchQuery {
+            exclude_tags: vec!["session".to_string()],
+            ..query("러스트")
+        };
+        assert_ne!(plain, QueryCache::key(&filtered, &[&[1.0, 0.0]]));
+    }
+
+    #[test]
+    fn test_hits_evictions_and_staleness() {
+        let cache = QueryCache::new(2, Duration::from_secs(60));
+        assert!(cache.get("a", (1, 0)).is_none());
+        cache.insert("a".to_string(), (1, 0), &[]);
+        cache.insert("b".to_string(), (1, 0), &[]);
+        assert!(cache.get("a", (1, 0)).is_some());
+
+        // `a` is used more recently than `b`, so `c` evicts `b`
+        cache.insert("c".to_string(), (1, 0), &[]);
+        assert!(cache.get("b", (1, 0)).is_none());
+        assert!(cache.get("c", (1, 0)).is_some());
+
+        // A write moved the storage version
+        assert!(cache.get("a", (2, 0)).is_none());
+        cache.clear();
+        assert!(cache.get("c", (1, 0)).is_none());
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (2, 4, 0));
+
+        let expired = QueryCache::new(2, Duration::from_nanos(1));
+        expired.insert("a".to_string(), (1, 0), &[]);
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(expired.get("a", (1, 0)).is_none());
+    }
+}
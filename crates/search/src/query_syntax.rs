@@ -0,0 +1,335 @@
+//! Boolean and field-scoped query syntax, read before the keyword query
+//! reaches the BM25 index:
+//!
+//! ```text
+//! type:decision tag:rust NOT deprecated
+//! title:"connection pool" (timeout OR deadlock)
+//! ```
+//!
+//! `AND`, `OR` and `NOT` (upper case) combine terms; `NOT` binds tightest,
+//! then `AND`, then `OR`, and parentheses group. `title:`, `tag:` and
+//! `type:` scope a term to the title, the tags or the memory type. Words
+//! joined only by spaces rank results like a plain query; under an
+//! operator they must appear in the title or content.
+
+use anyhow::{Result, bail};
+use oc_core::models::{MemoryType, QueryFilter, SearchQuery};
+
+/// A query split into the text to rank by and the filter results must pass
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedQuery {
+    /// Words, phrases and `title:` terms outside `NOT`, for the vector and
+    /// keyword legs; blank for a filter-only query
+    pub text: String,
+    /// `None` for a plain query
+    pub filter: Option<QueryFilter>,
+}
+
+/// Parse `input`. Unbalanced parentheses and dangling operators are
+/// ignored; an unknown `type:` is an error.
+pub fn parse(input: &str) -> Result<ParsedQuery> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        depth: 0,
+    };
+    let Some(root) = parser.or() else {
+        return Ok(ParsedQuery {
+            text: input.trim().to_string(),
+            filter: None,
+        });
+    };
+
+    let mut words = Vec::new();
+    root.ranking_words(&mut words);
+    // Bare words at the top level only rank
+    let filter = match root {
+        Node::Word { .. } => None,
+        Node::And {
+            nodes,
+            explicit: false,
+        } => {
+            let required: Vec<QueryFilter> = nodes
+                .into_iter()
+                .filter(|node| !matches!(node, Node::Word { .. }))
+                .map(Node::into_filter)
+                .collect();
+            match required.len() {
+                0 => None,
+                1 => required.into_iter().next(),
+                _ => Some(QueryFilter::And(required)),
+            }
+        }
+        node => Some(node.into_filter()),
+    };
+    Ok(ParsedQuery {
+        text: words.join(" "),
+        filter,
+    })
+}
+
+/// Parse `query.query` in place: keep its ranking text and set its filter
+pub fn apply(query: &mut SearchQuery) -> Result<()> {
+    let parsed = parse(&query.query)?;
+    query.query = parsed.text;
+    query.filter = parsed.filter;
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+    Word { text: String, quoted: bool },
+    Field(QueryFilter),
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        if c == '(' || c == ')' {
+            chars.next();
+            tokens.push(if c == '(' { Token::Open } else { Token::Close });
+            continue;
+        }
+        // A run up to whitespace or a parenthesis, quotes included
+        let mut raw = String::new();
+        let mut in_quotes = false;
+        while let Some(&c) = chars.peek() {
+            if !in_quotes && (c.is_whitespace() || c == '(' || c == ')') {
+                break;
+            }
+            if c == '"' {
+                in_quotes = !in_quotes;
+            }
+            raw.push(c);
+            chars.next();
+        }
+        match raw.as_str() {
+            "AND" => tokens.push(Token::And),
+            "OR" => tokens.push(Token::Or),
+            "NOT" => tokens.push(Token::Not),
+            _ => match word_or_field(&raw)? {
+                Token::Word { text, .. } if text.is_empty() => {}
+                token => tokens.push(token),
+            },
+        }
+    }
+    Ok(tokens)
+}
+
+fn word_or_field(raw: &str) -> Result<Token> {
+    if let Some((field, value)) = raw.split_once(':') {
+        let value = value.trim_matches('"');
+        if !value.is_empty() {
+            match field.to_lowercase().as_str() {
+                "title" => return Ok(Token::Field(QueryFilter::Title(value.to_string()))),
+                "tag" => return Ok(Token::Field(QueryFilter::Tag(value.to_string()))),
+                "type" => {
+                    let memory_type = serde_json::from_str::<MemoryType>(&format!(
+                        "\"{}\"",
+                        value.to_lowercase()
+                    ));
+                    return match memory_type {
+                        Ok(memory_type) => Ok(Token::Field(QueryFilter::Type(memory_type))),
+                        Err(_) => bail!("unknown memory type '{value}'"),
+                    };
+                }
+                _ => {}
+            }
+        }
+    }
+    let quoted = raw.starts_with('"');
+    Ok(Token::Word {
+        text: raw.trim_matches('"').to_string(),
+        quoted,
+    })
+}
+
+#[derive(Debug)]
+enum Node {
+    Word {
+        text: String,
+        quoted: bool,
+    },
+    Field(QueryFilter),
+    Not(Box<Node>),
+    /// `explicit` when the terms were joined by `AND` rather than spaces
+    And {
+        nodes: Vec<Node>,
+        explicit: bool,
+    },
+    Or(Vec<Node>),
+}
+
+impl Node {
+    /// Words and `title:` terms that are not negated
+    fn ranking_words(&self, out: &mut Vec<String>) {
+        match self {
+            Self::Word { text, quoted: true } => out.push(format!("\"{text}\"")),
+            Self::Word { text, .. } | Self::Field(QueryFilter::Title(text)) => {
+                out.push(text.clone())
+            }
+            Self::Field(_) | Self::Not(_) => {}
+            Self::And { nodes, .. } | Self::Or(nodes) => {
+                nodes.iter().for_each(|node| node.ranking_words(out))
+            }
+        }
+    }
+
+    fn into_filter(self) -> QueryFilter {
+        match self {
+            Self::Word { text, .. } => QueryFilter::Text(text),
+            Self::Field(filter) => filter,
+            Self::Not(node) => QueryFilter::Not(Box::new(node.into_filter())),
+            Self::And { nodes, .. } => {
+                QueryFilter::And(nodes.into_iter().map(Self::into_filter).collect())
+            }
+            Self::Or(nodes) => QueryFilter::Or(nodes.into_iter().map(Self::into_filter).collect()),
+        }
+    }
+}
+
+/// Recursive descent: `or := and (OR and)*`,
+/// `and := unary (AND? unary)*`, `unary := NOT unary | ( or ) | term`
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Open parentheses around the current position
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn or(&mut self) -> Option<Node> {
+        let mut nodes = Vec::new();
+        loop {
+            nodes.extend(self.and());
+            if self.peek() != Some(&Token::Or) {
+                break;
+            }
+            self.pos += 1;
+        }
+        match nodes.len() {
+            0 => None,
+            1 => nodes.pop(),
+            _ => Some(Node::Or(nodes)),
+        }
+    }
+
+    fn and(&mut self) -> Option<Node> {
+        let mut nodes = Vec::new();
+        let mut explicit = false;
+        loop {
+            match self.peek() {
+                // A `)` without its `(`
+                Some(Token::Close) if self.depth == 0 => self.pos += 1,
+                None | Some(Token::Or) | Some(Token::Close) => break,
+                Some(Token::And) => {
+                    self.pos += 1;
+                    explicit = true;
+                }
+                _ => match self.unary() {
+                    // Parentheses around plain words change nothing
+                    Some(Node::And {
+                        nodes: inner,
+                        explicit: false,
+                    }) => nodes.extend(inner),
+                    Some(node) => nodes.push(node),
+                    None => {}
+                },
+            }
+        }
+        match nodes.len() {
+            0 => None,
+            1 if !explicit => nodes.pop(),
+            _ => Some(Node::And { nodes, explicit }),
+        }
+    }
+
+    fn unary(&mut self) -> Option<Node> {
+        let token = self.peek()?.clone();
+        self.pos += 1;
+        match token {
+            Token::Not => self.unary().map(|node| Node::Not(Box::new(node))),
+            Token::Open => {
+                self.depth += 1;
+                let node = self.or();
+                self.depth -= 1;
+                if self.peek() == Some(&Token::Close) {
+                    self.pos += 1;
+                }
+                node
+            }
+            Token::Word { text, quoted } => Some(Node::Word { text, quoted }),
+            Token::Field(filter) => Some(Node::Field(filter)),
+            Token::And | Token::Or | Token::Close => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_query_has_no_filter() {
+        let parsed = parse("러스트 \"memory safety\" (borrow checker)").unwrap();
+        assert_eq!(parsed.text, "러스트 \"memory safety\" borrow checker");
+        assert_eq!(parsed.filter, None);
+    }
+
+    #[test]
+    fn test_fields_and_negation_filter() {
+        let parsed = parse("type:decision tag:rust NOT deprecated").unwrap();
+        assert_eq!(parsed.text, "");
+        assert_eq!(
+            parsed.filter,
+            Some(QueryFilter::And(vec![
+                QueryFilter::Type(MemoryType::Decision),
+                QueryFilter::Tag("rust".to_string()),
+                QueryFilter::Not(Box::new(QueryFilter::Text("deprecated".to_string()))),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_operator_precedence() {
+        let parsed = parse("title:\"connection pool\" timeout OR deadlock AND retry").unwrap();
+        assert_eq!(parsed.text, "connection pool timeout deadlock retry");
+        assert_eq!(
+            parsed.filter,
+            Some(QueryFilter::Or(vec![
+                QueryFilter::And(vec![
+                    QueryFilter::Title("connection pool".to_string()),
+                    QueryFilter::Text("timeout".to_string()),
+                ]),
+                QueryFilter::And(vec![
+                    QueryFilter::Text("deadlock".to_string()),
+                    QueryFilter::Text("retry".to_string()),
+                ]),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_unknown_type_is_an_error() {
+        assert!(parse("type:meeting notes").is_err());
+        // Other prefixes and lower-case operators are words
+        let parsed = parse("error: not found)").unwrap();
+        assert_eq!(parsed.text, "error: not found");
+        assert_eq!(parsed.filter, None);
+    }
+}
@@ -1,16 +1,150 @@
-use oc_core::models::{Priority, ScoreBreakdown};
+use anyhow::Result;
+use oc_core::config::{DEFAULT_RRF_K, FusionStrategy, SearchConfig};
+use oc_core::models::{Priority, ScoreBreakdown, ScoreWeights};
+use serde::{Deserialize, Serialize};
+
+/// Largest change of the importance component from retrieval feedback
+pub const FEEDBACK_SHIFT: f32 = 0.3;
+
+/// Access count at which the access component reaches its maximum of 1
+pub const ACCESS_SATURATION: u32 = 100;
 
 /// Combined scoring with time decay, importance weighting, and RRF fusion
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Scorer {
     pub semantic_weight: f32,
     pub keyword_weight: f32,
     pub recency_weight: f32,
     pub importance_weight: f32,
+    /// Weight of the boost for often accessed memories
+    #[serde(default)]
+    pub access_weight: f32,
     /// Half-life in days for recency decay
     pub half_life_days: f32,
+    /// Weighted-sum or rank fusion
+    #[serde(default)]
+    pub fusion: FusionStrategy,
+    /// RRF smoothing constant
+    #[serde(default = "default_rrf_k")]
+    pub rrf_k: f32,
+}
+
+fn default_rrf_k() -> f32 {
+    DEFAULT_RRF_K
+}
+
+/// Partial update of scorer weights; `None` leaves a field unchanged
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScorerUpdate {
+    pub semantic_weight: Option<f32>,
+    pub keyword_weight: Option<f32>,
+    pub recency_weight: Option<f32>,
+    pub importance_weight: Option<f32>,
+    pub access_weight: Option<f32>,
+    pub half_life_days: Option<f32>,
+}
+
+impl ScorerUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.semantic_weight.is_none()
+            && self.keyword_weight.is_none()
+            && self.recency_weight.is_none()
+            && self.importance_weight.is_none()
+            && self.access_weight.is_none()
+            && self.half_life_days.is_none()
+    }
 }
 
 impl Scorer {
+    /// Copy of this scorer with the given fields replaced
+    pub fn with_update(&self, update: &ScorerUpdate) -> Self {
+        Self {
+            semantic_weight: update.semantic_weight.unwrap_or(self.semantic_weight),
+            keyword_weight: update.keyword_weight.unwrap_or(self.keyword_weight),
+            recency_weight: update.recency_weight.unwrap_or(self.recency_weight),
+            importance_weight: update.importance_weight.unwrap_or(self.importance_weight),
+            access_weight: update.access_weight.unwrap_or(self.access_weight),
+            half_life_days: update.half_life_days.unwrap_or(self.half_life_days),
+            ..self.clone()
+        }
+    }
+
+    /// Reject weights outside [0, 1], an all-zero weighting, or a
+    /// non-positive half-life
+    pub fn validate(&self) -> Result<()> {
+        for (name, w) in [
+            ("semantic_weight", self.semantic_weight),
+            ("keyword_weight", self.keyword_weight),
+            ("recency_weight", self.recency_weight),
+            ("importance_weight", self.importance_weight),
+            ("access_weight", self.access_weight),
+        ] {
+            anyhow::ensure!(
+                w.is_finite() && (0.0..=1.0).contains(&w),
+                "{name} must be within [0, 1], got {w}"
+            );
+        }
+        anyhow::ensure!(
+            self.weight_sum() > 0.0,
+            "At least one weight must be positive"
+        );
+        anyhow::ensure!(
+            self.half_life_days.is_finite() && self.half_life_days > 0.0,
+            "half_life_days must be positive, got {}",
+            self.half_life_days
+        );
+        anyhow::ensure!(
+            self.rrf_k.is_finite() && self.rrf_k >= 0.0,
+            "rrf_k must be non-negative, got {}",
+            self.rrf_k
+        );
+        Ok(())
+    }
+
+    /// Sum of the five component weights (1.0 for the default profile)
+    pub fn weight_sum(&self) -> f32 {
+        self.semantic_weight
+            + self.keyword_weight
+            + self.recency_weight
+            + self.importance_weight
+            + self.access_weight
+    }
+
+    /// This scorer with the semantic weight spread over the other components
+    /// in proportion (or moved onto keyword when they are all zero), for
+    /// queries ranked without a vector leg
+    pub fn without_semantic(&self) -> Self {
+        let rest = self.weight_sum() - self.semantic_weight;
+        if rest <= 0.0 {
+            return Self {
+                semantic_weight: 0.0,
+                keyword_weight: self.semantic_weight,
+                ..self.clone()
+            };
+        }
+        let scale = self.weight_sum() / rest;
+        Self {
+            semantic_weight: 0.0,
+            keyword_weight: self.keyword_weight * scale,
+            recency_weight: self.recency_weight * scale,
+            importance_weight: self.importance_weight * scale,
+            access_weight: self.access_weight * scale,
+            ..self.clone()
+        }
+    }
+
+    /// Write the weights back into a `[search]` config section
+    pub fn write_to(&self, config: &mut SearchConfig) {
+        config.semantic_weight = self.semantic_weight;
+        config.keyword_weight = self.keyword_weight;
+        config.recency_weight = self.recency_weight;
+        config.importance_weight = self.importance_weight;
+        config.access_weight = self.access_weight;
+        config.recency_half_life_days = self.half_life_days;
+        config.fusion = self.fusion;
+        config.rrf_k = self.rrf_k;
+    }
+
     /// Calculate recency score using exponential decay
     ///
     /// score = exp(-λ * days_since_access)
@@ -25,6 +159,14 @@ impl Scorer {
         priority.weight()
     }
 
+    /// Access score from how often a memory was accessed
+    ///
+    /// score = ln(1 + access_count) / ln(1 + ACCESS_SATURATION), at most 1
+    pub fn access_score(&self, access_count: u32) -> f32 {
+        let saturation = (1.0 + ACCESS_SATURATION as f32).ln();
+        ((1.0 + access_count as f32).ln() / saturation).min(1.0)
+    }
+
     /// Combine all scores into a final ranking score
     pub fn combined_score(
         &self,
@@ -46,11 +188,90 @@ impl Scorer {
             keyword,
             recency,
             importance,
+            access: 0.0,
         };
 
         (score, breakdown)
     }
 
+    /// Rank the candidate with RRF over the lists that returned it (1-based
+    /// ranks). Recency and importance go into the breakdown only; see
+    /// [`tie_break`](Self::tie_break).
+    pub fn fused_score(
+        &self,
+        vector_rank: Option<usize>,
+        keyword_rank: Option<usize>,
+        semantic: f32,
+        keyword: f32,
+        days_since_access: f32,
+        priority: Priority,
+    ) -> (f32, ScoreBreakdown) {
+        let ranks: Vec<usize> = vector_rank.into_iter().chain(keyword_rank).collect();
+        let breakdown = ScoreBreakdown {
+            semantic,
+            keyword,
+            recency: self.recency_score(days_since_access),
+            importance: self.importance_score(priority),
+            access: 0.0,
+        };
+        (Self::rrf_score(&ranks, self.rrf_k), breakdown)
+    }
+
+    /// Shift the importance component by retrieval feedback
+    /// ([`oc_core::models::Feedback::usefulness`], in (-1, 1)) by up to [`FEEDBACK_SHIFT`],
+    /// rescoring weighted-sum results. Under RRF the change only affects
+    /// tie-breaking.
+    pub fn with_feedback(
+        &self,
+        score: f32,
+        mut breakdown: ScoreBreakdown,
+        usefulness: f32,
+    ) -> (f32, ScoreBreakdown) {
+        let importance = (breakdown.importance + FEEDBACK_SHIFT * usefulness).clamp(0.0, 1.0);
+        let delta = importance - breakdown.importance;
+        breakdown.importance = importance;
+        match self.fusion {
+            FusionStrategy::WeightedSum => (score + self.importance_weight * delta, breakdown),
+            FusionStrategy::Rrf => (score, breakdown),
+        }
+    }
+
+    /// Add the access component of a memory accessed `access_count` times
+    /// (see [`access_score`](Self::access_score)), rescoring weighted-sum
+    /// results. Under RRF it only affects tie-breaking.
+    pub fn with_access(
+        &self,
+        score: f32,
+        mut breakdown: ScoreBreakdown,
+        access_count: u32,
+    ) -> (f32, ScoreBreakdown) {
+        breakdown.access = self.access_score(access_count);
+        match self.fusion {
+            FusionStrategy::WeightedSum => {
+                (score + self.access_weight * breakdown.access, breakdown)
+            }
+            FusionStrategy::Rrf => (score, breakdown),
+        }
+    }
+
+    /// The component weights
+    pub fn weights(&self) -> ScoreWeights {
+        ScoreWeights {
+            semantic: self.semantic_weight,
+            keyword: self.keyword_weight,
+            recency: self.recency_weight,
+            importance: self.importance_weight,
+            access: self.access_weight,
+        }
+    }
+
+    /// Secondary sort key for results with equal scores
+    pub fn tie_break(&self, breakdown: &ScoreBreakdown) -> f32 {
+        self.recency_weight * breakdown.recency
+            + self.importance_weight * breakdown.importance
+            + self.access_weight * breakdown.access
+    }
+
     /// Reciprocal Rank Fusion: combine vector and BM25 rankings
     ///
     /// RRF(d) = Σ 1 / (k + rank_i(d))
@@ -67,7 +288,25 @@ impl Default for Scorer {
             keyword_weight: 0.15,
             recency_weight: 0.15,
             importance_weight: 0.10,
+            access_weight: 0.0,
             half_life_days: 30.0,
+            fusion: FusionStrategy::WeightedSum,
+            rrf_k: DEFAULT_RRF_K,
+        }
+    }
+}
+
+impl From<&SearchConfig> for Scorer {
+    fn from(config: &SearchConfig) -> Self {
+        Self {
+            semantic_weight: config.semantic_weight,
+            keyword_weight: config.keyword_weight,
+            recency_weight: config.recency_weight,
+            importance_weight: config.importance_weight,
+            access_weight: config.access_weight,
+            half_life_days: config.recency_half_life_days,
+            fusion: config.fusion,
+            rrf_k: config.rrf_k,
         }
     }
 }
@@ -75,6 +314,7 @@ impl Default for Scorer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use oc_core::models::Feedback;
 
     #[test]
     fn test_recency_decay() {
@@ -93,6 +333,133 @@ mod tests {
         assert!((score_60 - 0.25).abs() < 0.01);
     }
 
+    #[test]
+    fn test_feedback_shifts_importance() {
+        let scorer = Scorer::default();
+        let (score, breakdown) = scorer.combined_score(0.5, 0.5, 1.0, Priority::Medium);
+        let useful = Feedback {
+            useful: 8,
+            not_useful: 0,
+        };
+        let (boosted, boosted_breakdown) =
+            scorer.with_feedback(score, breakdown.clone(), useful.usefulness());
+        assert!(boosted > score);
+        assert!((boosted_breakdown.importance - 0.94).abs() < 0.001);
+
+        let useless = Feedback {
+            useful: 0,
+            not_useful: 3,
+        };
+        let (demoted, _) = scorer.with_feedback(score, breakdown.clone(), useless.usefulness());
+        assert!(demoted < score);
+        let (unchanged, _) =
+            scorer.with_feedback(score, breakdown, Feedback::default().usefulness());
+        assert_eq!(unchanged, score);
+
+        let rrf = Scorer {
+            fusion: FusionStrategy::Rrf,
+            ..Scorer::default()
+        };
+        let (score, breakdown) = rrf.fused_score(Some(1), None, 0.9, 0.0, 0.0, Priority::High);
+        let (same, capped) = rrf.with_feedback(score, breakdown, 0.8);
+        assert_eq!(same, score);
+        assert_eq!(capped.importance, 1.0);
+    }
+
+    #[test]
+    fn test_rrf_ties_broken_by_recency_and_importance() {
+        let scorer = Scorer {
+            fusion: FusionStrategy::Rrf,
+            ..Scorer::default()
+        };
+        // Top of the vector list vs. top of the keyword list: same RRF score
+        let (a, fresh) = scorer.fused_score(Some(1), None, 0.9, 0.0, 0.0, Priority::Medium);
+        let (b, stale) = scorer.fused_score(None, Some(1), 0.0, 1.0, 90.0, Priority::Medium);
+        assert!((a - b).abs() < f32::EPSILON);
+        assert!((a - 1.0 / 61.0).abs() < 1e-6);
+        assert!(scorer.tie_break(&fresh) > scorer.tie_break(&stale));
+
+        // Appearing in both lists beats either alone
+        let (both, _) = scorer.fused_score(Some(2), Some(2), 0.5, 0.5, 0.0, Priority::Low);
+        assert!(both > a);
+    }
+
+    #[test]
+    fn test_without_semantic_keeps_weight_sum() {
+        let scorer = Scorer::default().without_semantic();
+        assert_eq!(scorer.semantic_weight, 0.0);
+        assert!((scorer.weight_sum() - 1.0).abs() < 1e-6);
+        assert!((scorer.keyword_weight - 0.375).abs() < 1e-6);
+
+        let semantic_only = Scorer {
+            keyword_weight: 0.0,
+            recency_weight: 0.0,
+            importance_weight: 0.0,
+            ..Scorer::default()
+        };
+        assert!((semantic_only.without_semantic().keyword_weight - 0.6).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_update_and_validate() {
+        let scorer = Scorer::default();
+        let tuned = scorer.with_update(&ScorerUpdate {
+            keyword_weight: Some(0.4),
+            half_life_days: Some(7.0),
+            ..Default::default()
+        });
+        assert!((tuned.keyword_weight - 0.4).abs() < f32::EPSILON);
+        assert!((tuned.semantic_weight - 0.6).abs() < f32::EPSILON);
+        assert!(tuned.validate().is_ok());
+
+        let negative = scorer.with_update(&ScorerUpdate {
+            recency_weight: Some(-0.1),
+            ..Default::default()
+        });
+        assert!(negative.validate().is_err());
+
+        let zero_half_life = scorer.with_update(&ScorerUpdate {
+            half_life_days: Some(0.0),
+            ..Default::default()
+        });
+        assert!(zero_half_life.validate().is_err());
+    }
+
+    #[test]
+    fn test_access_boost_grows_with_log_of_accesses() {
+        let scorer = Scorer {
+            semantic_weight: 0.5,
+            access_weight: 0.1,
+            ..Scorer::default()
+        };
+        assert_eq!(scorer.access_score(0), 0.0);
+        assert!((scorer.access_score(ACCESS_SATURATION) - 1.0).abs() < 1e-6);
+        assert_eq!(scorer.access_score(10_000), 1.0);
+        // Logarithmic: the first accesses count most
+        let ten = scorer.access_score(10);
+        assert!(ten > 0.5 && ten < 0.6);
+
+        let (score, breakdown) = scorer.combined_score(0.5, 0.5, 1.0, Priority::Medium);
+        let (rarely, _) = scorer.with_access(score, breakdown.clone(), 1);
+        let (often, often_breakdown) = scorer.with_access(score, breakdown.clone(), 50);
+        assert!(often > rarely && rarely > score);
+        assert!((often - score - 0.1 * often_breakdown.access).abs() < 1e-6);
+        assert!(scorer.tie_break(&often_breakdown) > scorer.tie_break(&breakdown));
+
+        let rrf = Scorer {
+            fusion: FusionStrategy::Rrf,
+            ..scorer.clone()
+        };
+        let (same, _) = rrf.with_access(score, breakdown, 50);
+        assert_eq!(same, score);
+
+        let too_heavy = scorer.with_update(&ScorerUpdate {
+            access_weight: Some(1.5),
+            ..Default::default()
+        });
+        assert!(too_heavy.validate().is_err());
+    }
+
     #[test]
     fn test_rrf() {
         // Item ranked 1st in both lists
@@ -0,0 +1,205 @@
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use oc_core::Storage;
+use oc_core::models::{Memory, MemoryMetadata, SearchQuery};
+use oc_embeddings::Embedder;
+use serde::{Deserialize, Serialize};
+
+use crate::hybrid::HybridSearch;
+
+/// Text of the probe memory, also used as the search query
+const PROBE_TEXT: &str = "oc-memory self-test probe 자가진단 탐침";
+
+/// Audit actor recorded for the probe's deletion
+const PROBE_ACTOR: &str = "self-test";
+
+/// Outcome of one self-test step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfTestStep {
+    pub name: String,
+    pub ok: bool,
+    pub millis: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Result of [`run_self_test`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfTestReport {
+    pub passed: bool,
+    pub at: DateTime<Utc>,
+    pub total_millis: f64,
+    pub steps: Vec<SelfTestStep>,
+}
+
+impl SelfTestReport {
+    /// Log the outcome: one line per step, then the verdict
+    pub fn log(&self) {
+        for step in &self.steps {
+            let detail = step.detail.as_deref().unwrap_or("");
+            if step.ok {
+                tracing::info!("Self-test {}: ok ({:.1} ms)", step.name, step.millis);
+            } else {
+                tracing::error!(
+                    "Self-test {}: FAILED ({:.1} ms) {detail}",
+                    step.name,
+                    step.millis
+                );
+            }
+        }
+        if self.passed {
+            tracing::info!("Self-test passed in {:.1} ms", self.total_millis);
+        } else {
+            tracing::error!("Self-test failed after {:.1} ms", self.total_millis);
+        }
+    }
+}
+
+/// Store a probe memory, find it again through hybrid search and delete it,
+/// timing each step. The embedding step runs only with an embedder. The
+/// probe is always cleaned up, and a panicking step (e.g. a broken
+/// tokenizer dictionary) is reported as a failure instead of aborting.
+pub fn run_self_test(
+    storage: &Storage,
+    search: &Mutex<HybridSearch>,
+    embedder: Option<&dyn Embedder>,
+) -> SelfTestReport {
+    let started = Instant::now();
+    let at = Utc::now();
+    let mut steps = Vec::new();
+
+    let embedding = embedder.and_then(|e| {
+        step(&mut steps, "embed", || {
+            let embedding = e.embed(PROBE_TEXT).map_err(|err| err.to_string())?;
+            if embedding.len() != e.dimensions() {
+                return Err(format!(
+                    "expected {} dimensions, got {}",
+                    e.dimensions(),
+                    embedding.len()
+                ));
+            }
+            Ok(embedding)
+        })
+    });
+
+    let mut probe = Memory::new(
+        PROBE_TEXT.to_string(),
+        PROBE_TEXT.to_string(),
+        MemoryMetadata::default(),
+    );
+    probe.embedding_model = embedding
+        .as_ref()
+        .and(embedder)
+        .map(|e| e.model_id().to_string());
+    probe.embedding = embedding.clone();
+
+    let stored = step(&mut steps, "store", || {
+        storage.insert(&probe).map_err(|e| e.to_string())?;
+        let mut search = search.lock().map_err(|e| e.to_string())?;
+        search.index_memory(&probe).map_err(|e| e.to_string())
+    });
+
+    if stored.is_some() {
+        step(&mut steps, "search", || {
+            let query = SearchQuery {
+                query: PROBE_TEXT.to_string(),
+                limit: 5,
+                index_only: true,
+                ..Default::default()
+            };
+            let search = search.lock().map_err(|e| e.to_string())?;
+            let results = search
+                .search(embedding.as_deref().unwrap_or_default(), &query)
+                .map_err(|e| e.to_string())?;
+            if results.iter().any(|r| r.memory.id == probe.id) {
+                Ok(())
+            } else {
+                Err(format!("probe not among {} results", results.len()))
+            }
+        });
+    }
+
+    // Clean up even when storing failed half-way
+    step(&mut steps, "delete", || {
+        if let Ok(mut search) = search.lock() {
+            search.remove_memory(&probe.id).map_err(|e| e.to_string())?;
+        }
+        storage
+            .delete_as(&probe.id, Some(PROBE_ACTOR))
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    });
+
+    SelfTestReport {
+        passed: steps.iter().all(|s| s.ok),
+        at,
+        total_millis: elapsed_millis(started),
+        steps,
+    }
+}
+
+/// Run and record one step, turning errors and panics into a failed step
+fn step<T>(
+    steps: &mut Vec<SelfTestStep>,
+    name: &str,
+    f: impl FnOnce() -> Result<T, String>,
+) -> Option<T> {
+    let started = Instant::now();
+    let result = catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|panic| {
+        let message = panic
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown".to_string());
+        Err(format!("panicked: {message}"))
+    });
+    let (ok, detail, value) = match result {
+        Ok(value) => (true, None, Some(value)),
+        Err(e) => (false, Some(e), None),
+    };
+    steps.push(SelfTestStep {
+        name: name.to_string(),
+        ok,
+        millis: elapsed_millis(started),
+        detail,
+    });
+    value
+}
+
+fn elapsed_millis(started: Instant) -> f64 {
+    started.elapsed().as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bm25::Bm25Index;
+    use crate::scoring::Scorer;
+    use crate::vector::VectorIndex;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_self_test_reports_steps_and_cleans_up() {
+        let storage = Arc::new(Storage::in_memory().unwrap());
+        let search = Mutex::new(HybridSearch::new(
+            storage.clone(),
+            VectorIndex::new(4),
+            Bm25Index::in_memory().unwrap(),
+            Scorer::default(),
+        ));
+
+        let report = run_self_test(&storage, &search, None);
+        let names: Vec<&str> = report.steps.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names.first(), Some(&"store"));
+        assert_eq!(names.last(), Some(&"delete"));
+        assert_eq!(report.passed, report.steps.iter().all(|s| s.ok));
+        assert!(report.steps.iter().all(|s| s.ok || s.detail.is_some()));
+
+        // The probe never outlives the test
+        assert_eq!(storage.count().unwrap(), 0);
+        assert_eq!(search.lock().unwrap().indexed_count(), 0);
+    }
+}
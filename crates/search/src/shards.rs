@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{RwLock, RwLockReadGuard};
+
+use anyhow::Result;
+use oc_core::{Config, Storage};
+
+use crate::vector::{VectorIndex, chunk_key, memory_id_of};
+
+/// Per-namespace vector indices, so a search restricted to one namespace
+/// walks only that namespace's neighbours instead of over-fetching from
+/// the index of every namespace and discarding the rest.
+///
+/// A shard is created the first time a search targets its namespace:
+/// loaded from the shard directory when the saved file is current,
+/// otherwise built from the namespace's stored embeddings. Once created,
+/// [`HybridSearch`](crate::HybridSearch) keeps it in step with every write
+/// and saves it next to the main index.
+pub struct VectorShards {
+    dimensions: usize,
+    dir: Option<PathBuf>,
+    shards: RwLock<HashMap<String, VectorIndex>>,
+}
+
+impl VectorShards {
+    /// Shards of `dimensions`, kept in memory only
+    pub fn new(dimensions: usize) -> Self {
+        Self {
+            dimensions,
+            dir: None,
+            shards: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Shards of the configured embedding dimensions in the data
+    /// directory's `vector_shards`, unless `[search] vector_shards` is off
+    pub fn from_config(config: &Config) -> Option<Self> {
+        config.search.vector_shards.then(|| {
+            Self::new(config.embedding.dimensions).persisted_in(config.vector_shards_dir())
+        })
+    }
+
+    /// Save shards to and load them from `dir`
+    pub fn persisted_in(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = Some(dir.into());
+        self
+    }
+
+    pub fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    /// Namespaces whose shard has been created, sorted
+    pub fn namespaces(&self) -> Vec<String> {
+        let mut namespaces: Vec<String> = self.read().keys().cloned().collect();
+        namespaces.sort();
+        namespaces
+    }
+
+    /// Create the shard of `namespace` unless it exists, preferring a
+    /// saved shard stamped with the storage data version
+    pub fn ensure(&self, namespace: &str, storage: &Storage) -> Result<()> {
+        if self.read().contains_key(namespace) {
+            return Ok(());
+        }
+        let shard = match self.load(namespace, &storage.data_version()?) {
+            Some(shard) => shard,
+            None => {
+                let entries = storage
+                    .namespace_embeddings(namespace)?
+                    .into_iter()
+                    .filter(|(_, _, embedding)| embedding.len() == self.dimensions)
+                    .map(|(id, chunk, embedding)| match chunk {
+                        Some(chunk) => (chunk_key(&id, chunk), embedding),
+                        None => (id, embedding),
+                    })
+                    .collect();
+                let mut shard = VectorIndex::new(self.dimensions);
+                shard.build_from(entries)?;
+                shard
+            }
+        };
+        tracing::debug!(namespace, vectors = shard.len(), "Created vector shard");
+        self.shards
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .entry(namespace.to_string())
+            .or_insert(shard);
+        Ok(())
+    }
+
+    /// Nearest neighbours in the shard of `namespace` (see
+    /// [`VectorIndex::search`]); empty when it has not been created
+    pub fn search(&self, namespace: &str, query: &[f32], limit: usize) -> Vec<(String, f32)> {
+        self.read()
+            .get(namespace)
+            .map(|shard| shard.search(query, limit))
+            .unwrap_or_default()
+    }
+
+    /// Whether the shard of `namespace` holds chunk vectors
+    pub fn has_chunks(&self, namespace: &str) -> bool {
+        self.read()
+            .get(namespace)
+            .is_some_and(|shard| shard.len() > shard.memory_count())
+    }
+
+    /// Add or update the vector of key `id` (a memory ID or chunk key) of
+    /// `namespace`, removing the memory's vector and chunk vectors from any
+    /// other shard (the memory moved). Namespaces without a shard are left
+    /// to be built from storage.
+    pub fn upsert(&mut self, namespace: &str, id: &str, vector: &[f32]) -> Result<()> {
+        let memory_id = memory_id_of(id);
+        let shards = self.shards_mut();
+        for (name, shard) in shards.iter_mut() {
+            if name != namespace {
+                shard.remove(memory_id);
+                shard.remove_chunks(memory_id);
+            }
+        }
+        if let Some(shard) = shards.get_mut(namespace) {
+            shard.upsert(id.to_string(), vector.to_vec())?;
+        }
+        Ok(())
+    }
+
+    /// Remove a memory's vector from every shard
+    pub fn remove(&mut self, memory_id: &str) {
+        for shard in self.shards_mut().values_mut() {
+            shard.remove(memory_id);
+        }
+    }
+
+    /// Remove a memory's chunk vectors from every shard
+    pub fn remove_chunks(&mut self, memory_id: &str) {
+        for shard in self.shards_mut().values_mut() {
+            shard.remove_chunks(memory_id);
+        }
+    }
+
+    /// The namespace whose shard holds key `id`, if any
+    pub fn namespace_of(&self, id: &str) -> Option<String> {
+        let memory_id = memory_id_of(id);
+        self.read()
+            .iter()
+            .find(|(_, shard)| shard.contains(memory_id))
+            .map(|(name, _)| name.clone())
+    }
+
+    /// Drop every shard (after the main index was replaced); they are
+    /// created again on demand
+    pub fn clear(&mut self, dimensions: usize) {
+        self.dimensions = dimensions;
+        self.shards_mut().clear();
+    }
+
+    /// Save every created shard to the shard directory, stamped with
+    /// `fingerprint`. Does nothing without a directory.
+    pub fn save(&self, fingerprint: &str) -> Result<()> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        std::fs::create_dir_all(dir)?;
+        for (namespace, shard) in self.read().iter() {
+            shard.save(shard_path(dir, namespace), fingerprint)?;
+        }
+        Ok(())
+    }
+
+    /// The saved shard of `namespace` if it is readable and current
+    fn load(&self, namespace: &str, fingerprint: &str) -> Option<VectorIndex> {
+        let path = shard_path(self.dir.as_ref()?, namespace);
+        if !path.is_file() {
+            return None;
+        }
+        match VectorIndex::load(&path) {
+            Ok((shard, saved)) if saved == fingerprint && shard.dimensions() == self.dimensions => {
+                Some(shard)
+            }
+            Ok(_) => None,
+            Err(e) => {
+                tracing::warn!("Ignoring unreadable vector shard {}: {e}", path.display());
+                None
+            }
+        }
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, HashMap<String, VectorIndex>> {
+        // Shards are rebuilt from storage, so a poisoned lock loses nothing
+        self.shards
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn shards_mut(&mut self) -> &mut HashMap<String, VectorIndex> {
+        self.shards
+            .get_mut()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// File of the shard of `namespace` in `dir`. Bytes outside `[A-Za-z0-9_-]`
+/// are written as `%XX`, so any namespace maps to one safe file name.
+fn shard_path(dir: &Path, namespace: &str) -> PathBuf {
+    let mut name = String::with_capacity(namespace.len() + 8);
+    for byte in namespace.bytes() {
+        if byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'-' {
+            name.push(byte as char);
+        } else {
+            name.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    name.push_str(".usearch");
+    dir.join(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oc_core::models::Memory;
+
+    fn memory(namespace: &str, embedding: Vec<f32>) -> Memory {
+        let mut memory = Memory::new("내용".to_string(), "제목".to_string(), Default::default());
+        memory.namespace = namespace.to_string();
+        memory.embedding = Some(embedding);
+        memory
+    }
+
+    #[test]
+    fn test_shard_holds_only_its_namespace() {
+        let storage = Storage::in_memory().unwrap();
+        let work = memory("work", vec![1.0, 0.0, 0.0]);
+        let home = memory("home", vec![1.0, 0.0, 0.0]);
+        storage.insert(&work).unwrap();
+        storage.insert(&home).unwrap();
+
+        let shards = VectorShards::new(3);
+        assert!(shards.search("work", &[1.0, 0.0, 0.0], 5).is_empty());
+        shards.ensure("work", &storage).unwrap();
+        let hits = shards.search("work", &[1.0, 0.0, 0.0], 5);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].0, work.id);
+        assert_eq!(shards.namespaces(), vec!["work"]);
+    }
+
+    #[test]
+    fn test_upsert_moves_between_shards() {
+        let storage = Storage::in_memory().unwrap();
+        let mut shards = VectorShards::new(3);
+        shards.ensure("a", &storage).unwrap();
+        shards.ensure("b", &storage).unwrap();
+
+        shards.upsert("a", "m1", &[1.0, 0.0, 0.0]).unwrap();
+        shards.upsert("a", "m1#0", &[0.0, 1.0, 0.0]).unwrap();
+        assert_eq!(shards.namespace_of("m1#0").as_deref(), Some("a"));
+        shards.upsert("b", "m1", &[1.0, 0.0, 0.0]).unwrap();
+        // Its chunks leave the old shard with it
+        assert!(shards.search("a", &[1.0, 0.0, 0.0], 5).is_empty());
+        assert!(!shards.has_chunks("a"));
+        assert_eq!(shards.search("b", &[1.0, 0.0, 0.0], 5).len(), 1);
+
+        shards.remove("m1");
+        assert!(shards.search("b", &[1.0, 0.0, 0.0], 5).is_empty());
+    }
+
+    #[test]
+    fn test_saved_shard_reused_until_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::in_memory().unwrap();
+        let first = memory("팀/백엔드", vec![0.0, 1.0, 0.0]);
+        storage.insert(&first).unwrap();
+
+        let shards = VectorShards::new(3).persisted_in(dir.path());
+        shards.ensure("팀/백엔드", &storage).unwrap();
+        shards.save(&storage.data_version().unwrap()).unwrap();
+        let path = shard_path(dir.path(), "팀/백엔드");
+        assert!(path.is_file());
+        assert_eq!(path.parent(), Some(dir.path()));
+
+        let current = VectorShards::new(3).persisted_in(dir.path());
+        assert!(
+            current
+                .load("팀/백엔드", &storage.data_version().unwrap())
+                .is_some()
+        );
+
+        storage
+            .insert(&memory("팀/백엔드", vec![0.0, 0.0, 1.0]))
+            .unwrap();
+        let stale = VectorShards::new(3).persisted_in(dir.path());
+        assert!(
+            stale
+                .load("팀/백엔드", &storage.data_version().unwrap())
+                .is_none()
+        );
+        stale.ensure("팀/백엔드", &storage).unwrap();
+        assert_eq!(stale.search("팀/백엔드", &[0.0, 0.0, 1.0], 5).len(), 2);
+    }
+}
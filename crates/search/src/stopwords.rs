@@ -0,0 +1,98 @@
+//! Words left out of the BM25 index and queries: filler that would
+//! otherwise match nearly every memory.
+
+use oc_core::config::SearchConfig;
+
+/// Korean particles, copulas and bound nouns, as ko-dic splits them off
+/// their words ("한국어로" → "한국어", "로")
+pub const KOREAN: &[&str] = &[
+    "이",
+    "가",
+    "은",
+    "는",
+    "을",
+    "를",
+    "에",
+    "의",
+    "로",
+    "으로",
+    "와",
+    "과",
+    "도",
+    "만",
+    "에서",
+    "에게",
+    "께",
+    "한테",
+    "까지",
+    "부터",
+    "보다",
+    "처럼",
+    "하고",
+    "이나",
+    "나",
+    "요",
+    "이다",
+    "입니다",
+    "것",
+    "수",
+    "등",
+    "및",
+    "또는",
+    "그리고",
+    "그",
+    "저",
+];
+
+/// Common English function words
+pub const ENGLISH: &[&str] = &[
+    "a", "an", "the", "and", "or", "but", "of", "to", "in", "on", "at", "by", "for", "with",
+    "from", "as", "is", "are", "was", "were", "be", "been", "it", "its", "this", "that", "these",
+    "those", "there", "then", "than", "so", "if", "into", "not", "no", "do", "does", "did", "has",
+    "have", "had", "will", "would", "can", "could", "should",
+];
+
+/// The shipped Korean and English lists
+pub fn defaults() -> Vec<String> {
+    KOREAN
+        .iter()
+        .chain(ENGLISH)
+        .map(|word| word.to_string())
+        .collect()
+}
+
+/// Stopwords of `[search]`: the shipped lists unless `default_stopwords`
+/// is off, plus `stopwords`, lowercased
+pub fn from_config(config: &SearchConfig) -> Vec<String> {
+    let mut words = if config.default_stopwords {
+        defaults()
+    } else {
+        Vec::new()
+    };
+    for word in &config.stopwords {
+        let word = word.trim().to_lowercase();
+        if !word.is_empty() && !words.contains(&word) {
+            words.push(word);
+        }
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_config_adds_to_or_replaces_defaults() {
+        let mut config = SearchConfig {
+            stopwords: vec![" Memo ".to_string(), "the".to_string()],
+            ..Default::default()
+        };
+        let words = from_config(&config);
+        assert_eq!(words.len(), defaults().len() + 1);
+        assert!(words.contains(&"memo".to_string()));
+
+        config.default_stopwords = false;
+        assert_eq!(from_config(&config), vec!["memo", "the"]);
+    }
+}
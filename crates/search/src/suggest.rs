@@ -0,0 +1,83 @@
+//! Edit distance for did-you-mean suggestions (see
+//! [`crate::bm25::Bm25Index::suggest`]).
+//!
+//! Words are compared letter by letter, with Hangul syllables decomposed
+//! into their jamo, so a mistyped vowel or final consonant in a Korean word
+//! costs one edit instead of a whole syllable.
+
+const HANGUL_BASE: u32 = 0xAC00;
+const HANGUL_LAST: u32 = 0xD7A3;
+const LEADS: u32 = 0x1100;
+const VOWELS: u32 = 0x1161;
+const TAILS: u32 = 0x11A7;
+const VOWEL_COUNT: u32 = 21;
+const TAIL_COUNT: u32 = 28;
+
+/// Letters of `word` as they are typed: lowercase, Hangul syllables split
+/// into lead, vowel and optional tail jamo
+pub fn spelling(word: &str) -> Vec<char> {
+    let mut letters = Vec::with_capacity(word.len());
+    for c in word.chars().flat_map(char::to_lowercase) {
+        let code = c as u32;
+        if !(HANGUL_BASE..=HANGUL_LAST).contains(&code) {
+            letters.push(c);
+            continue;
+        }
+        let index = code - HANGUL_BASE;
+        let lead = index / (VOWEL_COUNT * TAIL_COUNT);
+        let vowel = index % (VOWEL_COUNT * TAIL_COUNT) / TAIL_COUNT;
+        let tail = index % TAIL_COUNT;
+        letters.extend(char::from_u32(LEADS + lead));
+        letters.extend(char::from_u32(VOWELS + vowel));
+        if tail > 0 {
+            letters.extend(char::from_u32(TAILS + tail));
+        }
+    }
+    letters
+}
+
+/// Levenshtein distance between two spellings
+pub fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// Most edits a correction of a word with `letters` letters may take:
+/// none for very short words, where almost anything is one edit away
+pub fn max_edits(letters: usize) -> usize {
+    match letters {
+        0..=2 => 0,
+        3..=5 => 1,
+        _ => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distance(a: &str, b: &str) -> usize {
+        edit_distance(&spelling(a), &spelling(b))
+    }
+
+    #[test]
+    fn test_edit_distance_by_letter_and_jamo() {
+        assert_eq!(distance("deploy", "deploy"), 0);
+        assert_eq!(distance("Deploy", "deploy"), 0);
+        assert_eq!(distance("deplyo", "deploy"), 2);
+        assert_eq!(distance("databse", "database"), 1);
+        // 배포 → 배표: one vowel differs, not a whole syllable
+        assert_eq!(spelling("배포").len(), 4);
+        assert_eq!(distance("배표", "배포"), 1);
+        assert_eq!(distance("한국", "한구"), 1);
+    }
+}
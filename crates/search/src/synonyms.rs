@@ -0,0 +1,141 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::Path;
+
+/// Synonym groups used to widen BM25 queries, loaded from `synonyms.toml`
+/// in the data directory:
+///
+/// ```toml
+/// groups = [
+///     ["러스트", "Rust"],
+///     ["DB", "database", "데이터베이스"],
+/// ]
+/// ```
+///
+/// A query mentioning any term of a group also matches the others.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Synonyms {
+    groups: Vec<Vec<String>>,
+}
+
+impl Synonyms {
+    /// Read the synonym file; a missing file means no synonyms
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("Invalid synonym file {}", path.display()))
+    }
+
+    /// Parse the TOML synonym format
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut synonyms: Self = toml::from_str(text)?;
+        for group in &mut synonyms.groups {
+            for term in group.iter_mut() {
+                *term = term.replace('"', "").trim().to_string();
+            }
+            group.retain(|term| !term.is_empty());
+        }
+        synonyms.groups.retain(|group| group.len() > 1);
+        Ok(synonyms)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Number of synonym groups
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// `query` followed by the synonyms of every term it mentions.
+    /// Matching ignores case; ASCII terms must stand alone ("DB" does not
+    /// match inside "MongoDB"), other terms may carry particles ("러스트로").
+    pub fn expand(&self, query: &str) -> String {
+        let lower = query.to_lowercase();
+        let mut expanded = query.to_string();
+        for group in &self.groups {
+            if !group
+                .iter()
+                .any(|term| mentions(&lower, &term.to_lowercase()))
+            {
+                continue;
+            }
+            for term in group {
+                if mentions(&lower, &term.to_lowercase()) {
+                    continue;
+                }
+                expanded.push(' ');
+                if term.contains(char::is_whitespace) {
+                    expanded.push_str(&format!("\"{term}\""));
+                } else {
+                    expanded.push_str(term);
+                }
+            }
+        }
+        expanded
+    }
+}
+
+/// Whether lowercased `text` contains lowercased `term`
+fn mentions(text: &str, term: &str) -> bool {
+    if !term.chars().all(|c| c.is_ascii_alphanumeric() || c == ' ') {
+        return text.contains(term);
+    }
+    let is_word = |c: char| c.is_ascii_alphanumeric();
+    text.match_indices(term).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + term.len()..].chars().next();
+        !before.is_some_and(is_word) && !after.is_some_and(is_word)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn synonyms() -> Synonyms {
+        Synonyms::parse(
+            r#"groups = [["러스트", "Rust"], ["DB", "database", "데이터베이스"], ["ML", "machine learning"]]"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_expand_adds_other_group_terms() {
+        let synonyms = synonyms();
+        assert_eq!(synonyms.len(), 3);
+        assert_eq!(
+            synonyms.expand("러스트로 만든 서버"),
+            "러스트로 만든 서버 Rust"
+        );
+        assert_eq!(synonyms.expand("rust 설정"), "rust 설정 러스트");
+        assert_eq!(synonyms.expand("DB 백업"), "DB 백업 database 데이터베이스");
+        assert_eq!(synonyms.expand("ML 모델"), "ML 모델 \"machine learning\"");
+    }
+
+    #[test]
+    fn test_ascii_terms_need_word_boundaries() {
+        let synonyms = synonyms();
+        assert_eq!(synonyms.expand("MongoDB 설정"), "MongoDB 설정");
+        assert_eq!(synonyms.expand("(db)"), "(db) database 데이터베이스");
+    }
+
+    #[test]
+    fn test_missing_file_and_bad_groups() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(
+            Synonyms::load(dir.path().join("synonyms.toml"))
+                .unwrap()
+                .is_empty()
+        );
+
+        let synonyms = Synonyms::parse(r#"groups = [["외톨이"], ["", " "]]"#).unwrap();
+        assert!(synonyms.is_empty());
+        assert!(Synonyms::parse("groups = 3").is_err());
+    }
+}
@@ -1,8 +1,43 @@
 use anyhow::{Context, Result};
+use oc_core::config::vector_manifest_path;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
 use usearch::{Index, IndexOptions, MetricKind, ScalarKind};
 
+/// Sidecar written next to a saved index: the key map usearch does not
+/// store, plus a caller-supplied fingerprint for staleness checks.
+#[derive(Debug, Serialize, Deserialize)]
+struct VectorIndexManifest {
+    dimensions: usize,
+    next_key: u64,
+    fingerprint: String,
+    ids: HashMap<String, u64>,
+}
+
+/// Index key of chunk `chunk` of a memory
+pub fn chunk_key(memory_id: &str, chunk: usize) -> String {
+    format!("{memory_id}#{chunk}")
+}
+
+/// Memory ID of an index key (a memory ID or a [`chunk_key`])
+pub fn memory_id_of(key: &str) -> &str {
+    key.split_once('#').map_or(key, |(id, _)| id)
+}
+
+fn index_options(dimensions: usize) -> IndexOptions {
+    IndexOptions {
+        dimensions,
+        metric: MetricKind::Cos,
+        quantization: ScalarKind::F32,
+        connectivity: 0,     // auto
+        expansion_add: 0,    // auto
+        expansion_search: 0, // auto
+        multi: false,
+    }
+}
+
 /// In-process HNSW vector index backed by usearch.
 ///
 /// Provides O(log n) approximate nearest-neighbor search instead of
@@ -21,16 +56,7 @@ pub struct VectorIndex {
 
 impl VectorIndex {
     pub fn new(dimensions: usize) -> Self {
-        let options = IndexOptions {
-            dimensions,
-            metric: MetricKind::Cos,
-            quantization: ScalarKind::F32,
-            connectivity: 0,     // auto
-            expansion_add: 0,    // auto
-            expansion_search: 0, // auto
-            multi: false,
-        };
-        let index = Index::new(&options).expect("Failed to create usearch index");
+        let index = Index::new(&index_options(dimensions)).expect("Failed to create usearch index");
         // Reserve a reasonable initial capacity
         index
             .reserve(1024)
@@ -102,6 +128,21 @@ impl VectorIndex {
         }
     }
 
+    /// Remove all chunk vectors of a memory. Returns how many existed.
+    pub fn remove_chunks(&mut self, memory_id: &str) -> usize {
+        let prefix = format!("{memory_id}#");
+        let keys: Vec<String> = self
+            .id_to_key
+            .keys()
+            .filter(|key| key.starts_with(&prefix))
+            .cloned()
+            .collect();
+        for key in &keys {
+            self.remove(key);
+        }
+        keys.len()
+    }
+
     /// Search for nearest neighbors. Returns `(id, similarity)` pairs
     /// sorted by descending cosine similarity.
     ///
@@ -140,15 +181,98 @@ impl VectorIndex {
         results
     }
 
+    /// Whether the index holds a vector under `id`
+    pub fn contains(&self, id: &str) -> bool {
+        self.id_to_key.contains_key(id)
+    }
+
     /// Number of vectors in the index.
     pub fn len(&self) -> usize {
         self.id_to_key.len()
     }
 
+    /// Number of memories with a vector, not counting chunk vectors
+    pub fn memory_count(&self) -> usize {
+        self.id_to_key
+            .keys()
+            .filter(|key| !key.contains('#'))
+            .count()
+    }
+
     pub fn is_empty(&self) -> bool {
         self.id_to_key.is_empty()
     }
 
+    pub fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    /// Serialize the index to `path` with a `<path>.meta.json` sidecar.
+    ///
+    /// `fingerprint` identifies the data the index was built from; [`load`]
+    /// returns it so callers can detect a stale file. Both files are written
+    /// to a temporary name first and renamed into place.
+    ///
+    /// [`load`]: Self::load
+    pub fn save(&self, path: impl AsRef<Path>, fingerprint: &str) -> Result<()> {
+        let path = path.as_ref();
+        let tmp = with_suffix(path, ".tmp");
+        self.index
+            .save(utf8_path(&tmp)?)
+            .context("usearch save failed")?;
+        std::fs::rename(&tmp, path)?;
+
+        let manifest = VectorIndexManifest {
+            dimensions: self.dimensions,
+            next_key: self.next_key.load(Ordering::Relaxed),
+            fingerprint: fingerprint.to_string(),
+            ids: self.id_to_key.clone(),
+        };
+        let meta_path = vector_manifest_path(path);
+        let meta_tmp = with_suffix(&meta_path, ".tmp");
+        std::fs::write(&meta_tmp, serde_json::to_vec(&manifest)?)?;
+        std::fs::rename(&meta_tmp, &meta_path)?;
+        Ok(())
+    }
+
+    /// Load an index written by [`save`], returning it with its fingerprint.
+    ///
+    /// [`save`]: Self::save
+    pub fn load(path: impl AsRef<Path>) -> Result<(Self, String)> {
+        let path = path.as_ref();
+        let meta_path = vector_manifest_path(path);
+        let manifest: VectorIndexManifest = serde_json::from_slice(
+            &std::fs::read(&meta_path)
+                .with_context(|| format!("Failed to read {}", meta_path.display()))?,
+        )?;
+
+        let index = Index::new(&index_options(manifest.dimensions))
+            .context("Failed to create usearch index")?;
+        index
+            .load(utf8_path(path)?)
+            .context("usearch load failed")?;
+        anyhow::ensure!(
+            index.size() == manifest.ids.len(),
+            "Vector index holds {} vectors but its manifest lists {}",
+            index.size(),
+            manifest.ids.len()
+        );
+
+        let key_to_id = manifest
+            .ids
+            .iter()
+            .map(|(id, &key)| (key, id.clone()))
+            .collect();
+        let loaded = Self {
+            index,
+            dimensions: manifest.dimensions,
+            id_to_key: manifest.ids,
+            key_to_id,
+            next_key: AtomicU64::new(manifest.next_key),
+        };
+        Ok((loaded, manifest.fingerprint))
+    }
+
     /// Rebuild index from a batch of entries.
     pub fn build_from(&mut self, entries: Vec<(String, Vec<f32>)>) -> Result<()> {
         // Reset everything
@@ -157,16 +281,8 @@ impl VectorIndex {
         self.next_key.store(1, Ordering::Relaxed);
 
         // Create a fresh index
-        let options = IndexOptions {
-            dimensions: self.dimensions,
-            metric: MetricKind::Cos,
-            quantization: ScalarKind::F32,
-            connectivity: 0,
-            expansion_add: 0,
-            expansion_search: 0,
-            multi: false,
-        };
-        self.index = Index::new(&options).context("Failed to recreate usearch index")?;
+        self.index = Index::new(&index_options(self.dimensions))
+            .context("Failed to recreate usearch index")?;
 
         let capacity = entries.len().max(1024);
         self.index
@@ -184,6 +300,18 @@ impl VectorIndex {
     }
 }
 
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// usearch takes paths as `&str`; refuse rather than mangle non-UTF-8 ones
+fn utf8_path(path: &Path) -> Result<&str> {
+    path.to_str()
+        .with_context(|| format!("Path is not valid UTF-8: {}", path.display()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -260,6 +388,35 @@ mod tests {
         assert!(results.is_empty());
     }
 
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vectors.usearch");
+
+        let mut index = VectorIndex::new(3);
+        index.upsert("a".to_string(), vec![1.0, 0.0, 0.0]).unwrap();
+        index.upsert("b".to_string(), vec![0.0, 1.0, 0.0]).unwrap();
+        index.remove("a");
+        index.upsert("c".to_string(), vec![0.0, 0.0, 1.0]).unwrap();
+        index.save(&path, "v1").unwrap();
+
+        let (mut loaded, fingerprint) = VectorIndex::load(&path).unwrap();
+        assert_eq!(fingerprint, "v1");
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.search(&[0.0, 0.0, 1.0], 1)[0].0, "c");
+
+        // New keys must not collide with loaded ones
+        loaded.upsert("d".to_string(), vec![1.0, 0.0, 0.0]).unwrap();
+        assert_eq!(loaded.search(&[0.0, 1.0, 0.0], 1)[0].0, "b");
+        assert_eq!(loaded.search(&[1.0, 0.0, 0.0], 1)[0].0, "d");
+    }
+
+    #[test]
+    fn test_load_missing_file_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(VectorIndex::load(dir.path().join("missing.usearch")).is_err());
+    }
+
     #[test]
     fn test_dimension_mismatch() {
         let mut index = VectorIndex::new(3);
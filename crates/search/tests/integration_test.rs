@@ -3,9 +3,13 @@
 
 use oc_core::Storage;
 use oc_core::models::{Memory, MemoryMetadata, MemoryType, Priority, SearchQuery};
+use oc_core::reembed::{DEFAULT_REEMBED_BATCH_SIZE, ReembedOptions, reembed};
 use oc_search::bm25::Bm25Index;
 use oc_search::hybrid::HybridSearch;
+use oc_search::query_cache::QueryCache;
+use oc_search::query_syntax;
 use oc_search::scoring::Scorer;
+use oc_search::shards::VectorShards;
 use oc_search::vector::VectorIndex;
 use std::sync::Arc;
 
@@ -279,3 +283,719 @@ fn test_korean_keyword_search() {
     assert!(!results2.is_empty(), "BGE keyword search should work");
     assert_eq!(results2[0].memory.title, "프로젝트 결정사항");
 }
+
+#[test]
+fn test_ab_experiment_tags_results_and_attributes_fetches() {
+    use oc_search::experiment::Experiment;
+
+    let (storage, mut search) = create_test_engine();
+    let m1 = make_memory("벡터 A", "첫 번째", &[], Some(vec![1.0, 0.0, 0.0, 0.0]));
+    let m2 = make_memory("벡터 B", "두 번째", &[], Some(vec![0.0, 1.0, 0.0, 0.0]));
+    for m in [&m1, &m2] {
+        storage.insert(m).unwrap();
+        search.index_memory(m).unwrap();
+    }
+
+    let variant_b = Scorer {
+        semantic_weight: 1.0,
+        keyword_weight: 0.0,
+        recency_weight: 0.0,
+        importance_weight: 0.0,
+        half_life_days: 30.0,
+        ..Scorer::default()
+    };
+    search.set_experiment(Some(Experiment::new(variant_b, 1.0).unwrap()));
+
+    let query = SearchQuery {
+        query: "벡터".to_string(),
+        limit: 2,
+        ..Default::default()
+    };
+    let results = search.search(&[1.0, 0.0, 0.0, 0.0], &query).unwrap();
+    assert!(!results.is_empty());
+    assert!(results.iter().all(|r| r.variant.as_deref() == Some("B")));
+    assert_eq!(results[0].memory.id, m1.id);
+
+    search.record_fetch(std::slice::from_ref(&m1.id));
+    let report = search.experiment_report().unwrap();
+    assert_eq!(report.b.searches, 1);
+    assert_eq!(report.b.fetched, 1);
+    assert_eq!(report.a.searches, 0);
+}
+
+#[test]
+fn test_persisted_vector_index_reused_until_stale() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("vectors.usearch");
+    let (storage, mut search) = create_test_engine();
+
+    let m1 = make_memory("벡터", "저장된 벡터", &[], Some(vec![1.0, 0.0, 0.0, 0.0]));
+    storage.insert(&m1).unwrap();
+    search.index_memory(&m1).unwrap();
+    search.save_vector_index(&path).unwrap();
+
+    // Fresh engine over the same storage picks up the saved index
+    let mut reloaded = HybridSearch::new(
+        storage.clone(),
+        VectorIndex::new(4),
+        Bm25Index::in_memory().unwrap(),
+        Scorer::default(),
+    );
+    assert!(reloaded.load_vector_index(&path).unwrap());
+    assert_eq!(reloaded.indexed_count(), 1);
+
+    // A later write makes the saved file stale
+    let m2 = make_memory("새 벡터", "추가", &[], Some(vec![0.0, 1.0, 0.0, 0.0]));
+    storage.insert(&m2).unwrap();
+    let mut stale = HybridSearch::new(
+        storage,
+        VectorIndex::new(4),
+        Bm25Index::in_memory().unwrap(),
+        Scorer::default(),
+    );
+    assert!(!stale.load_vector_index(&path).unwrap());
+    assert_eq!(stale.indexed_count(), 0);
+}
+
+#[test]
+fn test_rebuild_vector_index_after_reembed() {
+    let (storage, mut search) = create_test_engine();
+    let m = make_memory(
+        "모델 교체",
+        "새 모델로 다시 임베딩",
+        &[],
+        Some(vec![1.0, 0.0, 0.0, 0.0]),
+    );
+    storage.insert(&m).unwrap();
+    search.index_memory(&m).unwrap();
+
+    let options = ReembedOptions {
+        model: "new.onnx".to_string(),
+        dimensions: 3,
+        all: false,
+        batch_size: DEFAULT_REEMBED_BATCH_SIZE,
+    };
+    let report = reembed(&storage, &options, |texts| {
+        Ok(texts.iter().map(|_| vec![0.0, 1.0, 0.0]).collect())
+    })
+    .unwrap();
+    assert_eq!(report.reembedded, 1);
+
+    assert_eq!(search.rebuild_vector_index(3).unwrap(), 1);
+    assert_eq!(search.vector_index_mut().dimensions(), 3);
+}
+
+#[test]
+fn test_tag_filter_is_normalization_aware() {
+    let (storage, mut search) = create_test_engine();
+
+    let tagged = make_memory(
+        "러스트 메모",
+        "태그가 정규화되어 저장됨",
+        &[" Rust ", "Tokio"],
+        Some(vec![1.0, 0.0, 0.0, 0.0]),
+    );
+    let untagged = make_memory(
+        "다른 메모",
+        "태그 없음",
+        &["python"],
+        Some(vec![0.9, 0.1, 0.0, 0.0]),
+    );
+    for m in [&tagged, &untagged] {
+        storage.insert(m).unwrap();
+        search.index_memory(m).unwrap();
+    }
+
+    let query = SearchQuery {
+        query: "메모".to_string(),
+        limit: 5,
+        tags_all: Some(vec!["RUST".to_string(), "tokio ".to_string()]),
+        ..Default::default()
+    };
+    let results = search.search(&[1.0, 0.0, 0.0, 0.0], &query).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].memory.id, tagged.id);
+    assert_eq!(results[0].memory.metadata.tags, vec!["rust", "tokio"]);
+
+    // Any-of matches either memory; combined with all-of it narrows again
+    let any = SearchQuery {
+        tags_any: Some(vec!["Python".to_string(), "tokio".to_string()]),
+        ..query.clone()
+    };
+    let any_only = SearchQuery {
+        tags_all: None,
+        ..any.clone()
+    };
+    assert_eq!(
+        search
+            .search(&[1.0, 0.0, 0.0, 0.0], &any_only)
+            .unwrap()
+            .len(),
+        2
+    );
+    assert_eq!(search.search(&[1.0, 0.0, 0.0, 0.0], &any).unwrap().len(), 1);
+
+    let none = SearchQuery {
+        tags_any: Some(vec!["go".to_string()]),
+        ..any_only
+    };
+    assert!(
+        search
+            .search(&[1.0, 0.0, 0.0, 0.0], &none)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_chunk_hits_score_their_memory() {
+    let (storage, mut search) = create_test_engine();
+
+    // The whole-memory embedding points elsewhere; one chunk matches
+    let long = make_memory(
+        "긴 메모",
+        "여러 주제를 다루는 긴 메모",
+        &[],
+        Some(vec![0.0, 0.0, 1.0, 0.0]),
+    );
+    let other = make_memory("다른 메모", "내용", &[], Some(vec![0.6, 0.8, 0.0, 0.0]));
+    let chunks = vec![vec![0.0, 0.0, 0.0, 1.0], vec![1.0, 0.0, 0.0, 0.0]];
+    for m in [&long, &other] {
+        storage.insert(m).unwrap();
+        search.index_memory(m).unwrap();
+    }
+    storage.replace_chunk_embeddings(&long.id, &chunks).unwrap();
+    search.index_chunks(&long.id, &chunks).unwrap();
+    assert_eq!(search.indexed_count(), 2);
+
+    let query = SearchQuery {
+        query: "메모".to_string(),
+        limit: 5,
+        ..Default::default()
+    };
+    let results = search.search(&[1.0, 0.0, 0.0, 0.0], &query).unwrap();
+    assert_eq!(results.len(), 2, "chunks must not duplicate memories");
+    assert_eq!(results[0].memory.id, long.id);
+    assert!((results[0].score_breakdown.semantic - 1.0).abs() < 1e-3);
+
+    // Chunk vectors survive a rebuild from storage and go away on delete
+    assert_eq!(search.rebuild_vector_index(4).unwrap(), 4);
+    search.remove_memory(&long.id).unwrap();
+    storage.delete(&long.id).unwrap();
+    assert!(storage.all_chunk_embeddings().unwrap().is_empty());
+    let results = search.search(&[1.0, 0.0, 0.0, 0.0], &query).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].memory.id, other.id);
+}
+
+#[test]
+fn test_rrf_fusion_scores_by_rank() {
+    use oc_core::config::FusionStrategy;
+
+    let (storage, mut search) = create_test_engine();
+    let near = make_memory("가까운", "첫 번째", &[], Some(vec![1.0, 0.0, 0.0, 0.0]));
+    let far = make_memory("먼", "두 번째", &[], Some(vec![0.6, 0.8, 0.0, 0.0]));
+    for m in [&near, &far] {
+        storage.insert(m).unwrap();
+        search.index_memory(m).unwrap();
+    }
+    search.set_scorer(Scorer {
+        fusion: FusionStrategy::Rrf,
+        ..Scorer::default()
+    });
+
+    // No keyword hits: only the vector ranks contribute
+    let query = SearchQuery {
+        query: "zzzz".to_string(),
+        limit: 5,
+        ..Default::default()
+    };
+    let results = search.search(&[1.0, 0.0, 0.0, 0.0], &query).unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].memory.id, near.id);
+    assert!((results[0].score - 1.0 / 61.0).abs() < 1e-6);
+    assert!((results[1].score - 1.0 / 62.0).abs() < 1e-6);
+    assert!(results[1].score_breakdown.semantic > 0.5);
+}
+
+#[test]
+fn test_unusable_query_embedding_ranks_keyword_only() {
+    let (storage, mut search) = create_test_engine();
+    let m = make_memory(
+        "벡터 메모",
+        "벡터 메모 내용",
+        &[],
+        Some(vec![1.0, 0.0, 0.0, 0.0]),
+    );
+    storage.insert(&m).unwrap();
+    search.index_memory(&m).unwrap();
+
+    let query = SearchQuery {
+        query: "벡터".to_string(),
+        limit: 5,
+        ..Default::default()
+    };
+    let results = search.search(&[1.0, 0.0, 0.0, 0.0], &query).unwrap();
+    assert_eq!(results[0].memory.id, m.id);
+    assert!(!results[0].degraded);
+
+    // Failed embed calls surface as empty, zero-filled or NaN vectors
+    for embedding in [vec![], vec![0.0; 4], vec![f32::NAN; 4], vec![1.0; 3]] {
+        let results = search.search(&embedding, &query).unwrap();
+        assert!(
+            results
+                .iter()
+                .all(|r| r.degraded && r.score_breakdown.semantic == 0.0),
+            "{embedding:?}"
+        );
+    }
+}
+
+#[test]
+fn test_exact_search_returns_only_the_phrase() {
+    let (storage, mut search) = create_test_engine();
+    let verbatim = make_memory(
+        "배포 오류",
+        "error: connection refused by peer",
+        &[],
+        Some(vec![0.0, 1.0, 0.0, 0.0]),
+    );
+    let similar = make_memory(
+        "비슷한 오류",
+        "the peer refused our connection",
+        &[],
+        Some(vec![1.0, 0.0, 0.0, 0.0]),
+    );
+    for m in [&verbatim, &similar] {
+        storage.insert(m).unwrap();
+        search.index_memory(m).unwrap();
+    }
+
+    let mut query = SearchQuery {
+        query: "connection refused by peer".to_string(),
+        limit: 5,
+        ..Default::default()
+    };
+    assert_eq!(
+        search.search(&[1.0, 0.0, 0.0, 0.0], &query).unwrap().len(),
+        2
+    );
+
+    // The nearest vector does not contain the phrase, so it is dropped
+    query.exact = true;
+    let results = search.search(&[1.0, 0.0, 0.0, 0.0], &query).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].memory.id, verbatim.id);
+}
+
+#[test]
+fn test_explain_reports_raw_scores_and_weights() {
+    let (storage, mut search) = create_test_engine();
+    let both = make_memory(
+        "벡터 검색",
+        "HNSW 벡터 검색",
+        &[],
+        Some(vec![1.0, 0.0, 0.0, 0.0]),
+    );
+    let vector_only = make_memory(
+        "다른 주제",
+        "무관한 내용",
+        &[],
+        Some(vec![0.8, 0.6, 0.0, 0.0]),
+    );
+    for m in [&both, &vector_only] {
+        storage.insert(m).unwrap();
+        search.index_memory(m).unwrap();
+    }
+
+    let mut query = SearchQuery {
+        query: "벡터".to_string(),
+        limit: 5,
+        ..Default::default()
+    };
+    let results = search.search(&[1.0, 0.0, 0.0, 0.0], &query).unwrap();
+    assert!(results.iter().all(|r| r.explanation.is_none()));
+
+    query.explain = true;
+    let results = search.search(&[1.0, 0.0, 0.0, 0.0], &query).unwrap();
+    assert_eq!(results[0].memory.id, both.id);
+    let explanation = results[0].explanation.as_ref().unwrap();
+    assert!((explanation.cosine.unwrap() - 1.0).abs() < 1e-3);
+    assert!(explanation.bm25.unwrap() > 0.0);
+    assert_eq!(explanation.vector_rank, Some(1));
+    assert_eq!(explanation.keyword_rank, Some(1));
+    assert_eq!(explanation.weights, search.scorer().weights());
+
+    let other = results
+        .iter()
+        .find(|r| r.memory.id == vector_only.id)
+        .unwrap();
+    let explanation = other.explanation.as_ref().unwrap();
+    assert_eq!(explanation.bm25, None);
+    assert_eq!(explanation.keyword_rank, None);
+    assert_eq!(explanation.vector_rank, Some(2));
+}
+
+#[test]
+fn test_query_cache_serves_repeats_until_the_index_changes() {
+    let (storage, mut search) = create_test_engine();
+    search.set_query_cache(QueryCache::new(8, std::time::Duration::from_secs(60)));
+    let first = make_memory("캐시 설계", "LRU 캐시", &[], None);
+    storage.insert(&first).unwrap();
+    search.index_memory(&first).unwrap();
+
+    let query = SearchQuery {
+        query: "캐시".to_string(),
+        limit: 5,
+        ..Default::default()
+    };
+    assert_eq!(search.search(&[], &query).unwrap().len(), 1);
+    assert_eq!(search.search(&[], &query).unwrap().len(), 1);
+    let stats = search.query_cache_stats();
+    assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+    // Hits still count as accesses
+    assert_eq!(storage.get(&first.id).unwrap().unwrap().access_count, 2);
+
+    let second = make_memory("캐시 무효화", "캐시를 비운다", &[], None);
+    storage.insert(&second).unwrap();
+    search.index_memory(&second).unwrap();
+    assert_eq!(search.search(&[], &query).unwrap().len(), 2);
+
+    // Quarantine writes no audit entry but still invalidates
+    storage.quarantine(&second.id, "test").unwrap();
+    assert_eq!(search.search(&[], &query).unwrap().len(), 1);
+    assert_eq!(search.query_cache_stats().hits, 1);
+}
+
+#[test]
+fn test_exclusion_filters_drop_tags_and_types() {
+    let (storage, mut search) = create_test_engine();
+    let kept = make_memory("배포 메모", "배포 절차", &["deploy"], None);
+    let tagged = make_memory("배포 로그", "배포 기록", &["Session-Log"], None);
+    let mut session = make_memory("배포 세션", "배포 대화", &[], None);
+    session.metadata.memory_type = MemoryType::Session;
+    for m in [&kept, &tagged, &session] {
+        storage.insert(m).unwrap();
+        search.index_memory(m).unwrap();
+    }
+
+    let mut query = SearchQuery {
+        query: "배포".to_string(),
+        limit: 10,
+        ..Default::default()
+    };
+    assert_eq!(search.search(&[], &query).unwrap().len(), 3);
+
+    query.exclude_tags = vec!["session-log".to_string()];
+    query.exclude_types = vec![MemoryType::Session];
+    let results = search.search(&[], &query).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].memory.id, kept.id);
+}
+
+#[test]
+fn test_query_syntax_filters_by_type_tag_and_negation() {
+    let (storage, mut search) = create_test_engine();
+    let mut decision = make_memory("에러 처리 방식", "anyhow로 통일한다", &["rust"], None);
+    decision.metadata.memory_type = MemoryType::Decision;
+    let mut deprecated = make_memory("빌드 도구", "deprecated: make 대신 cargo", &["rust"], None);
+    deprecated.metadata.memory_type = MemoryType::Decision;
+    let observation = make_memory("에러 로그", "rust 빌드가 실패했다", &["rust"], None);
+    for m in [&decision, &deprecated, &observation] {
+        storage.insert(m).unwrap();
+        search.index_memory(m).unwrap();
+    }
+
+    // No text left: every memory the filter accepts is listed
+    let mut query = SearchQuery {
+        query: "type:decision tag:rust NOT deprecated".to_string(),
+        limit: 10,
+        ..Default::default()
+    };
+    query_syntax::apply(&mut query).unwrap();
+    let results = search.search(&[], &query).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].memory.id, decision.id);
+    assert!(!results[0].degraded);
+
+    // Bare words still rank; the field keeps only observations
+    let mut query = SearchQuery {
+        query: "에러 type:observation".to_string(),
+        limit: 10,
+        ..Default::default()
+    };
+    query_syntax::apply(&mut query).unwrap();
+    assert_eq!(query.query, "에러");
+    let results = search.search(&[], &query).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].memory.id, observation.id);
+}
+
+#[test]
+fn test_cold_memories_need_include_cold() {
+    let (storage, mut search) = create_test_engine();
+    let hot = make_memory("자주 씀", "최근 메모", &[], Some(vec![1.0, 0.0, 0.0, 0.0]));
+    let cold = make_memory("오래됨", "잊힌 메모", &[], Some(vec![0.9, 0.1, 0.0, 0.0]));
+    for m in [&hot, &cold] {
+        storage.insert(m).unwrap();
+        search.index_memory(m).unwrap();
+    }
+    assert_eq!(storage.set_cold(std::slice::from_ref(&cold.id)).unwrap(), 1);
+
+    let mut query = SearchQuery {
+        query: "메모".to_string(),
+        limit: 5,
+        ..Default::default()
+    };
+    let results = search.search(&[1.0, 0.0, 0.0, 0.0], &query).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].memory.id, hot.id);
+
+    query.include_cold = true;
+    let results = search.search(&[1.0, 0.0, 0.0, 0.0], &query).unwrap();
+    assert_eq!(results.len(), 2);
+    // Retrieving a cold memory warms it up again
+    assert_eq!(storage.count_cold().unwrap(), 0);
+}
+
+#[test]
+fn test_archived_memories_need_include_archived() {
+    let (storage, mut search) = create_test_engine();
+    let current = make_memory(
+        "현재 규칙",
+        "배포 메모",
+        &[],
+        Some(vec![1.0, 0.0, 0.0, 0.0]),
+    );
+    let old = make_memory(
+        "옛 규칙",
+        "지난 배포 메모",
+        &[],
+        Some(vec![0.9, 0.1, 0.0, 0.0]),
+    );
+    for m in [&current, &old] {
+        storage.insert(m).unwrap();
+        search.index_memory(m).unwrap();
+    }
+
+    let mut query = SearchQuery {
+        query: "메모".to_string(),
+        limit: 5,
+        ..Default::default()
+    };
+    assert_eq!(
+        search.search(&[1.0, 0.0, 0.0, 0.0], &query).unwrap().len(),
+        2
+    );
+    assert!(storage.set_archived_state(&old.id, true, None).unwrap());
+    let results = search.search(&[1.0, 0.0, 0.0, 0.0], &query).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].memory.id, current.id);
+
+    query.include_archived = true;
+    let results = search.search(&[1.0, 0.0, 0.0, 0.0], &query).unwrap();
+    assert_eq!(results.len(), 2);
+}
+
+#[test]
+fn test_related_excludes_the_memory_itself() {
+    let (storage, mut search) = create_test_engine();
+    let deploy = make_memory(
+        "배포 절차",
+        "스테이징을 거쳐 배포한다",
+        &[],
+        Some(vec![1.0, 0.0, 0.0, 0.0]),
+    );
+    let rollback = make_memory(
+        "롤백",
+        "배포 실패 시 이전 버전으로 되돌린다",
+        &[],
+        Some(vec![0.9, 0.1, 0.0, 0.0]),
+    );
+    let lunch = make_memory("점심", "금요일은 국수", &[], Some(vec![0.0, 0.0, 1.0, 0.0]));
+    for m in [&deploy, &rollback, &lunch] {
+        storage.insert(m).unwrap();
+        search.index_memory(m).unwrap();
+    }
+
+    let related = search.related(&deploy, 1).unwrap();
+    assert_eq!(related.len(), 1);
+    assert_eq!(related[0].memory.id, rollback.id);
+
+    let related = search.related(&deploy, 5).unwrap();
+    assert!(related.iter().all(|r| r.memory.id != deploy.id));
+}
+
+#[test]
+fn test_alt_queries_fuse_each_formulation() {
+    let (storage, mut search) = create_test_engine();
+    let deploy = make_memory(
+        "배포 절차",
+        "스테이징을 거쳐 배포한다",
+        &[],
+        Some(vec![1.0, 0.0, 0.0, 0.0]),
+    );
+    let lunch = make_memory("점심", "금요일은 국수", &[], Some(vec![0.0, 0.0, 1.0, 0.0]));
+    for m in [&deploy, &lunch] {
+        storage.insert(m).unwrap();
+        search.index_memory(m).unwrap();
+    }
+
+    // Keyword-only, the alternative finds what the query alone misses
+    let mut query = SearchQuery {
+        query: "배포".to_string(),
+        limit: 5,
+        ..Default::default()
+    };
+    let results = search.search_fused(&[], &query).unwrap();
+    assert_eq!(results.len(), 1);
+    query.alt_queries = vec!["국수".to_string(), " ".to_string()];
+    assert_eq!(query.texts().count(), 2);
+    let results = search.search_fused(&[], &query).unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|r| r.score_breakdown.keyword == 1.0));
+
+    // Each memory keeps its best similarity over the formulations
+    let embeddings: [&[f32]; 2] = [&[1.0, 0.0, 0.0, 0.0], &[0.0, 0.0, 1.0, 0.0]];
+    let results = search.search_fused(&embeddings, &query).unwrap();
+    assert!(!results[0].degraded);
+    assert!(results.iter().all(|r| r.score_breakdown.semantic > 0.9));
+}
+
+#[test]
+fn test_collapse_sources_keeps_best_chunk_per_source() {
+    let (storage, mut search) = create_test_engine();
+    let mut chunks = Vec::new();
+    for (i, content) in [
+        "배포 배포 배포 스테이징 순서",
+        "배포 후 모니터링",
+        "배포 롤백 방법",
+    ]
+    .iter()
+    .enumerate()
+    {
+        let mut chunk = make_memory(&format!("배포 가이드 {i}"), content, &[], None);
+        chunk.metadata.source = Some("docs/deploy.md".to_string());
+        chunks.push(chunk);
+    }
+    let note = make_memory("배포 메모", "금요일 배포 금지", &[], None);
+    for m in chunks.iter().chain([&note]) {
+        storage.insert(m).unwrap();
+        search.index_memory(m).unwrap();
+    }
+
+    let mut query = SearchQuery {
+        query: "배포".to_string(),
+        limit: 10,
+        ..Default::default()
+    };
+    let results = search.search(&[], &query).unwrap();
+    assert_eq!(results.len(), 2);
+    let guide = results
+        .iter()
+        .find(|r| r.memory.metadata.source.is_some())
+        .unwrap();
+    assert_eq!(guide.collapsed_count, 2);
+    let note_result = results.iter().find(|r| r.memory.id == note.id).unwrap();
+    assert_eq!(note_result.collapsed_count, 0);
+
+    // Expanding lists every chunk
+    query.collapse_sources = false;
+    let results = search.search(&[], &query).unwrap();
+    assert_eq!(results.len(), 4);
+    assert!(results.iter().all(|r| r.collapsed_count == 0));
+}
+
+#[test]
+fn test_search_batch_matches_separate_searches() {
+    let (storage, mut search) = create_test_engine();
+    let rust = make_memory(
+        "러스트",
+        "소유권과 빌림",
+        &[],
+        Some(vec![1.0, 0.0, 0.0, 0.0]),
+    );
+    let deploy = make_memory(
+        "배포",
+        "배포 순서 결정",
+        &[],
+        Some(vec![0.0, 1.0, 0.0, 0.0]),
+    );
+    for m in [&rust, &deploy] {
+        storage.insert(m).unwrap();
+        search.index_memory(m).unwrap();
+    }
+
+    let query = |text: &str| SearchQuery {
+        query: text.to_string(),
+        limit: 1,
+        ..Default::default()
+    };
+    let rust_embedding: &[f32] = &[1.0, 0.0, 0.0, 0.0];
+    let batch = vec![
+        (vec![rust_embedding], query("러스트")),
+        (Vec::new(), query("배포")),
+    ];
+    let outcomes = search.search_batch(&batch);
+    assert_eq!(outcomes.len(), 2);
+    let results: Vec<_> = outcomes.into_iter().map(Result::unwrap).collect();
+    assert_eq!(results[0][0].memory.id, rust.id);
+    assert!(!results[0][0].degraded);
+    assert_eq!(results[1][0].memory.id, deploy.id);
+    assert!(results[1][0].degraded);
+
+    let separate = search.search(rust_embedding, &query("러스트")).unwrap();
+    assert_eq!(separate[0].memory.id, results[0][0].memory.id);
+}
+
+#[test]
+fn test_namespace_search_goes_through_its_shard() {
+    let dir = tempfile::tempdir().unwrap();
+    let (storage, mut search) = create_test_engine();
+    search.set_vector_shards(Some(VectorShards::new(4).persisted_in(dir.path())));
+
+    // Many near neighbours in another namespace crowd the shared index
+    for i in 0..40 {
+        let m = make_memory(
+            &format!("다른 팀 {i}"),
+            "다른 팀의 기록",
+            &[],
+            Some(vec![1.0, 0.01 * i as f32, 0.0, 0.0]),
+        );
+        storage.insert(&m).unwrap();
+        search.index_memory(&m).unwrap();
+    }
+    let mut ours = make_memory("우리 팀", "배포 절차", &[], Some(vec![0.5, 0.5, 0.0, 0.0]));
+    ours.namespace = "ours".to_string();
+    storage.insert(&ours).unwrap();
+    search.index_memory(&ours).unwrap();
+    assert!(search.shard_namespaces().is_empty(), "created on demand");
+
+    let query = SearchQuery {
+        query: "없는단어".to_string(),
+        namespace: Some("ours".to_string()),
+        limit: 1,
+        ..Default::default()
+    };
+    let results = search.search(&[1.0, 0.0, 0.0, 0.0], &query).unwrap();
+    assert_eq!(results[0].memory.id, ours.id);
+    assert_eq!(search.shard_namespaces(), vec!["ours"]);
+
+    // Later writes keep the shard current, and it is saved on its own
+    let mut newer = make_memory(
+        "우리 팀 2",
+        "롤백 절차",
+        &[],
+        Some(vec![1.0, 0.0, 0.0, 0.0]),
+    );
+    newer.namespace = "ours".to_string();
+    storage.insert(&newer).unwrap();
+    search.index_memory(&newer).unwrap();
+    let results = search.search(&[1.0, 0.0, 0.0, 0.0], &query).unwrap();
+    assert_eq!(results[0].memory.id, newer.id);
+
+    search
+        .save_vector_index(dir.path().join("vectors.usearch"))
+        .unwrap();
+    assert!(dir.path().join("ours.usearch").is_file());
+}
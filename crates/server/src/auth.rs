@@ -0,0 +1,161 @@
+use axum::extract::{Json, Request, State};
+use axum::http::{HeaderMap, Method, StatusCode, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use oc_core::config::{ApiKeyConfig, ApiKeyScope};
+use std::sync::Arc;
+
+use crate::ApiResponse;
+use crate::openapi::{DOCS_PATH, SPEC_PATH};
+
+/// Request header carrying an API key, as an alternative to
+/// `Authorization: Bearer <key>`
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// An API key with its secret resolved
+#[derive(Debug, Clone)]
+struct ApiKey {
+    name: String,
+    secret: String,
+    scope: ApiKeyScope,
+}
+
+/// Keys accepted by [`require_api_key`], resolved once from
+/// `[[server.api_keys]]`. Empty means authentication is off.
+#[derive(Debug, Clone, Default)]
+pub struct ApiKeys {
+    keys: Vec<ApiKey>,
+}
+
+impl ApiKeys {
+    /// Resolve the configured keys; keys without a secret (unset `key_env`)
+    /// are skipped with a warning
+    pub fn from_config(keys: &[ApiKeyConfig]) -> Self {
+        let keys = keys
+            .iter()
+            .filter_map(|key| match key.secret() {
+                Some(secret) => Some(ApiKey {
+                    name: key.name.clone(),
+                    secret,
+                    scope: key.scope,
+                }),
+                None => {
+                    tracing::warn!(name = %key.name, "API key has no secret; ignoring it");
+                    None
+                }
+            })
+            .collect();
+        Self { keys }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    fn find(&self, presented: &str) -> Option<&ApiKey> {
+        self.keys
+            .iter()
+            .find(|key| constant_time_eq(key.secret.as_bytes(), presented.as_bytes()))
+    }
+}
+
+/// Middleware rejecting `/api/*` requests without a valid key (401) and
+/// write requests made with a read-only key (403). The `/health` probes
+/// and the API docs ([`crate::openapi`]) stay open.
+pub async fn require_api_key(
+    State(keys): State<Arc<ApiKeys>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path();
+    if keys.is_empty() || !path.starts_with("/api/") || path == SPEC_PATH || path == DOCS_PATH {
+        return next.run(request).await;
+    }
+    let Some(presented) = presented_key(request.headers()) else {
+        return unauthorized("Missing API key");
+    };
+    let Some(key) = keys.find(presented) else {
+        return unauthorized("Invalid API key");
+    };
+    if key.scope == ApiKeyScope::ReadOnly && !is_read(request.method(), request.uri().path()) {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::<()>::err(format!(
+                "API key '{}' is read-only",
+                key.name
+            ))),
+        )
+            .into_response();
+    }
+    tracing::debug!(key = %key.name, "Authenticated request");
+    next.run(request).await
+}
+
+/// The key from a bearer token, else from [`API_KEY_HEADER`]
+fn presented_key(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .or_else(|| headers.get(API_KEY_HEADER).and_then(|v| v.to_str().ok()))
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+}
+
+/// Whether a request only reads: any GET but the sync changes, which
+/// hand out quarantined memories for a peer to hold, the search endpoints,
+/// which take their query as a POST body, and embedding, which stores
+/// nothing
+fn is_read(method: &Method, path: &str) -> bool {
+    if method == Method::GET || method == Method::HEAD {
+        return !path.starts_with("/api/v1/sync/");
+    }
+    method == Method::POST
+        && (path == "/api/v1/search"
+            || path == "/api/v1/search/batch"
+            || path == "/api/v1/embed"
+            || (path.starts_with("/api/v1/namespaces/") && path.ends_with("/search")))
+}
+
+fn unauthorized(message: &str) -> Response {
+    (
+        StatusCode::UNAUTHORIZED,
+        [(header::WWW_AUTHENTICATE, "Bearer")],
+        Json(ApiResponse::<()>::err(message)),
+    )
+        .into_response()
+}
+
+/// Compare secrets in time independent of where they differ
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_requests() {
+        assert!(is_read(&Method::GET, "/api/v1/memories/1"));
+        assert!(is_read(&Method::POST, "/api/v1/search"));
+        assert!(is_read(&Method::POST, "/api/v1/search/batch"));
+        assert!(is_read(&Method::POST, "/api/v1/embed"));
+        assert!(is_read(&Method::POST, "/api/v1/namespaces/work/search"));
+        assert!(!is_read(&Method::POST, "/api/v1/memories"));
+        assert!(!is_read(&Method::DELETE, "/api/v1/memories/1"));
+        assert!(!is_read(&Method::POST, "/api/v1/admin/backup"));
+    }
+
+    #[test]
+    fn test_presented_key() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(presented_key(&headers), None);
+        headers.insert(API_KEY_HEADER, "k2".parse().unwrap());
+        assert_eq!(presented_key(&headers), Some("k2"));
+        headers.insert(header::AUTHORIZATION, "Bearer k1".parse().unwrap());
+        assert_eq!(presented_key(&headers), Some("k1"));
+        assert!(constant_time_eq(b"k1", b"k1"));
+        assert!(!constant_time_eq(b"k1", b"k10"));
+    }
+}
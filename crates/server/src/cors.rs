@@ -0,0 +1,55 @@
+use axum::http::{HeaderName, HeaderValue, Method, header};
+use oc_core::config::CorsConfig;
+use std::time::Duration;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+
+use crate::CLIENT_NAME_HEADER;
+use crate::auth::API_KEY_HEADER;
+
+/// The CORS layer for `[server.cors]`; `None` when no origin is allowed.
+/// Origins and methods that do not parse are skipped with a warning.
+pub fn layer(config: &CorsConfig) -> Option<CorsLayer> {
+    if config.allowed_origins.is_empty() {
+        return None;
+    }
+    let origins = if config.allowed_origins.iter().any(|o| o.trim() == "*") {
+        AllowOrigin::from(Any)
+    } else {
+        let origins: Vec<HeaderValue> = config
+            .allowed_origins
+            .iter()
+            .filter_map(|origin| {
+                let origin = origin.trim().trim_end_matches('/');
+                let parsed = HeaderValue::from_str(origin).ok();
+                if parsed.is_none() {
+                    tracing::warn!(origin, "Invalid CORS origin; ignoring it");
+                }
+                parsed
+            })
+            .collect();
+        AllowOrigin::list(origins)
+    };
+    let methods: Vec<Method> = config
+        .allowed_methods
+        .iter()
+        .filter_map(|method| {
+            let parsed = Method::from_bytes(method.trim().to_uppercase().as_bytes()).ok();
+            if parsed.is_none() {
+                tracing::warn!(method = %method, "Invalid CORS method; ignoring it");
+            }
+            parsed
+        })
+        .collect();
+    Some(
+        CorsLayer::new()
+            .allow_origin(origins)
+            .allow_methods(methods)
+            .allow_headers([
+                header::AUTHORIZATION,
+                header::CONTENT_TYPE,
+                HeaderName::from_static(API_KEY_HEADER),
+                HeaderName::from_static(CLIENT_NAME_HEADER),
+            ])
+            .max_age(Duration::from_secs(config.max_age_secs)),
+    )
+}
@@ -0,0 +1,217 @@
+//! Memory change events for `GET /api/v1/events`: a poller reads new audit
+//! log entries (this server's writes and the MCP server's alike) and
+//! broadcasts them to every subscribed SSE stream.
+
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::Json;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures_util::stream;
+use oc_core::models::MemoryEvent;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::{ApiResponse, SharedState};
+
+/// Most audit log entries read at once, per poll or per catch-up
+const READ_BATCH: usize = 1000;
+
+/// Broadcasts memory events to every subscriber
+pub struct EventBus {
+    sender: broadcast::Sender<MemoryEvent>,
+}
+
+impl EventBus {
+    /// A bus whose subscribers may fall `buffer` events behind
+    pub fn new(buffer: usize) -> Self {
+        let (sender, _) = broadcast::channel(buffer.max(1));
+        Self { sender }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<MemoryEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Send `event` to every subscriber; dropped when there are none
+    pub fn publish(&self, event: MemoryEvent) {
+        let _ = self.sender.send(event);
+    }
+}
+
+/// Spawn the audit log poller when `[server.events] poll_interval_ms` is
+/// non-zero. Only changes after startup are published.
+pub fn spawn_event_feed(state: SharedState) -> Option<tokio::task::JoinHandle<()>> {
+    let interval_ms = state.config.server.events.poll_interval_ms;
+    if interval_ms == 0 {
+        return None;
+    }
+    let mut head = match state.storage.change_head() {
+        Ok(head) => head,
+        Err(e) => {
+            tracing::warn!("Memory events disabled, audit log unreadable: {e}");
+            return None;
+        }
+    };
+
+    Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_millis(interval_ms));
+        loop {
+            interval.tick().await;
+            // Read on until the log is drained, a full batch at a time
+            loop {
+                let events = match changes_since(&state, head).await {
+                    Ok(events) => events,
+                    Err(e) => {
+                        // `head` stays, so the next tick retries from there
+                        tracing::warn!("Reading memory events failed: {e}");
+                        break;
+                    }
+                };
+                let drained = events.len() < READ_BATCH;
+                for event in events {
+                    head = event.seq;
+                    state.events.publish(event);
+                }
+                if drained {
+                    break;
+                }
+            }
+        }
+    }))
+}
+
+/// One batch of at most [`READ_BATCH`] audit log changes after `after`,
+/// read on the blocking pool
+async fn changes_since(state: &SharedState, after: i64) -> Result<Vec<MemoryEvent>, String> {
+    let state = Arc::clone(state);
+    match tokio::task::spawn_blocking(move || state.storage.changes_since(after, READ_BATCH)).await
+    {
+        Ok(Ok(events)) => Ok(events),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(e) => Err(format!("task failed: {e}")),
+    }
+}
+
+/// Where one SSE stream stands: events read from the audit log but not
+/// sent yet, and whether the log holds more after them
+struct Feed {
+    state: SharedState,
+    receiver: broadcast::Receiver<MemoryEvent>,
+    missed: VecDeque<MemoryEvent>,
+    /// The last read batch was full, so more may follow it
+    more: bool,
+    /// Sequence of the last event sent
+    last: i64,
+    /// An `error` event was sent; the stream ends
+    failed: bool,
+}
+
+impl Feed {
+    /// Queue the next batch of audit log changes after `last`
+    async fn read_missed(&mut self) -> Result<(), String> {
+        let events = changes_since(&self.state, self.last).await?;
+        self.more = events.len() == READ_BATCH;
+        self.missed = events.into();
+        Ok(())
+    }
+}
+
+/// Server-sent events named after their kind (`created`, `updated`,
+/// `deleted`), with the [`MemoryEvent`] as JSON data and its audit sequence
+/// as the event ID. A reconnecting client's `Last-Event-ID` replays what it
+/// missed, as does falling more than `buffer` events behind, in batches
+/// of [`READ_BATCH`] until the log is drained. A replay that fails to read
+/// the log answers 500, or mid-stream sends an `error` event and ends the
+/// stream, so the client reconnects from its last event ID.
+pub async fn api_events(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+) -> axum::response::Response {
+    if state.config.server.events.poll_interval_ms == 0 {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ApiResponse::<()>::err(
+                "Memory events are disabled ([server.events] poll_interval_ms = 0)",
+            )),
+        )
+            .into_response();
+    }
+
+    // Subscribe before the replay, so nothing falls between the two;
+    // events at or before `last` are skipped
+    let last_seen = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<i64>().ok());
+    let mut feed = Feed {
+        receiver: state.events.subscribe(),
+        state,
+        missed: VecDeque::new(),
+        more: false,
+        last: last_seen.unwrap_or(0),
+        failed: false,
+    };
+    if last_seen.is_some()
+        && let Err(e) = feed.read_missed().await
+    {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err(format!(
+                "Replaying memory events failed: {e}"
+            ))),
+        )
+            .into_response();
+    }
+
+    let events = stream::unfold(feed, |mut feed| async move {
+        if feed.failed {
+            return None;
+        }
+        loop {
+            if let Some(event) = feed.missed.pop_front() {
+                if event.seq > feed.last {
+                    feed.last = event.seq;
+                    return Some((sse_event(&event), feed));
+                }
+                continue;
+            }
+            let caught_up = if feed.more {
+                feed.read_missed().await
+            } else {
+                match feed.receiver.recv().await {
+                    Ok(event) if event.seq <= feed.last => Ok(()),
+                    Ok(event) => {
+                        feed.last = event.seq;
+                        return Some((sse_event(&event), feed));
+                    }
+                    // Catch up from the audit log instead of losing events
+                    Err(RecvError::Lagged(_)) => feed.read_missed().await,
+                    Err(RecvError::Closed) => return None,
+                }
+            };
+            if let Err(e) = caught_up {
+                tracing::warn!("Catching up on memory events failed: {e}");
+                feed.failed = true;
+                let event = Event::default()
+                    .event("error")
+                    .data(format!("Reading memory events failed: {e}"));
+                return Some((Ok(event), feed));
+            }
+        }
+    });
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+fn sse_event(event: &MemoryEvent) -> Result<Event, Infallible> {
+    Ok(Event::default()
+        .event(event.kind.as_str())
+        .id(event.seq.to_string())
+        .data(serde_json::to_string(event).unwrap_or_default()))
+}
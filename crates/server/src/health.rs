@@ -0,0 +1,255 @@
+//! Health probes. `GET /health/live` (and `/health`) only says the process
+//! answers; `GET /health/ready` checks every component requests go through
+//! — SQLite, the tantivy reader, the vector index and the embedder with a
+//! test inference — and answers 503 when one of them is down. The probe is
+//! unauthenticated, so its report is reused for [`READINESS_TTL`] and
+//! concurrent probes wait for one check instead of each running their own.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::Json;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use oc_search::hybrid::HybridSearch;
+use oc_search::selftest::SelfTestReport;
+use serde::{Deserialize, Serialize};
+
+use crate::{ApiResponse, AppState, SharedState, run_blocking};
+
+/// Text embedded by the readiness probe
+const PROBE_TEXT: &str = "readiness probe";
+
+/// How long `GET /health/ready` answers with the last report
+pub const READINESS_TTL: Duration = Duration::from_secs(5);
+
+/// The last [`ReadinessReport`] and when it was checked
+#[derive(Default)]
+pub struct ReadinessCache {
+    last: tokio::sync::Mutex<Option<(Instant, ReadinessReport)>>,
+}
+
+/// State of one component as last checked
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComponentHealth {
+    pub healthy: bool,
+    /// Memories stored, documents in the text index or memory vectors in
+    /// the vector index
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count: Option<usize>,
+    /// Time the check took; for the embedder, one test inference
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_millis: Option<f64>,
+    /// Model the embedder loaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Why the component is unhealthy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Components {
+    pub sqlite: ComponentHealth,
+    pub text_index: ComponentHealth,
+    pub vector_index: ComponentHealth,
+    pub embedder: ComponentHealth,
+}
+
+/// Answer of `GET /health/ready`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadinessReport {
+    /// `ok`, or `degraded` when a component is unhealthy or the startup
+    /// self-test failed
+    pub status: String,
+    pub components: Components,
+    /// Outcome of the startup self-test, when `[self_test]` is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub self_test: Option<SelfTestReport>,
+}
+
+impl ReadinessReport {
+    pub fn is_ready(&self) -> bool {
+        self.status == "ok"
+    }
+}
+
+/// Check each component of `state`. Takes the search lock and runs one
+/// embedding, so call it off the async runtime.
+pub fn readiness(state: &AppState) -> ReadinessReport {
+    let started = Instant::now();
+    let sqlite = match state.storage.count() {
+        Ok(count) => ComponentHealth {
+            healthy: true,
+            count: Some(count),
+            latency_millis: Some(elapsed_millis(started)),
+            ..Default::default()
+        },
+        Err(e) => unhealthy(e.to_string()),
+    };
+
+    let (text_index, vector_index) = match state.search.lock() {
+        Ok(search) => {
+            let started = Instant::now();
+            let text_index = match search.text_indexed_count() {
+                Ok(count) => ComponentHealth {
+                    healthy: true,
+                    count: Some(count),
+                    latency_millis: Some(elapsed_millis(started)),
+                    ..Default::default()
+                },
+                Err(e) => unhealthy(e.to_string()),
+            };
+            let started = Instant::now();
+            let vector_index = check_vector_index(state, &search);
+            let vector_index = ComponentHealth {
+                latency_millis: Some(elapsed_millis(started)),
+                ..vector_index
+            };
+            (text_index, vector_index)
+        }
+        Err(_) => {
+            let poisoned = || unhealthy("Search lock poisoned".to_string());
+            (poisoned(), poisoned())
+        }
+    };
+
+    let embedder = match &state.embedder {
+        Some(embedder) => {
+            let started = Instant::now();
+            let result = embedder.embed(PROBE_TEXT);
+            let latency_millis = Some(elapsed_millis(started));
+            let model = Some(embedder.model_id().to_string());
+            match result {
+                Ok(embedding) if embedding.len() == embedder.dimensions() => ComponentHealth {
+                    healthy: true,
+                    latency_millis,
+                    model,
+                    ..Default::default()
+                },
+                Ok(embedding) => ComponentHealth {
+                    latency_millis,
+                    model,
+                    error: Some(format!(
+                        "Expected {} dimensions, got {}",
+                        embedder.dimensions(),
+                        embedding.len()
+                    )),
+                    ..Default::default()
+                },
+                // Saturated, not broken: stores and searches answer 503 on their own
+                Err(e) if e.retry_after_ms().is_some() => ComponentHealth {
+                    healthy: true,
+                    latency_millis,
+                    model,
+                    ..Default::default()
+                },
+                Err(e) => ComponentHealth {
+                    latency_millis,
+                    model,
+                    error: Some(e.to_string()),
+                    ..Default::default()
+                },
+            }
+        }
+        None => unhealthy("No embedding model loaded".to_string()),
+    };
+
+    let components = Components {
+        sqlite,
+        text_index,
+        vector_index,
+        embedder,
+    };
+    let healthy = [
+        &components.sqlite,
+        &components.text_index,
+        &components.vector_index,
+        &components.embedder,
+    ]
+    .iter()
+    .all(|c| c.healthy)
+        && state.self_test.as_ref().is_none_or(|report| report.passed);
+    ReadinessReport {
+        status: if healthy { "ok" } else { "degraded" }.to_string(),
+        components,
+        self_test: state.self_test.clone(),
+    }
+}
+
+/// The vector index is healthy when its dimensions are those of the
+/// config and the loaded model, and it holds vectors whenever storage has
+/// embeddings of its dimensions (an empty index then failed to load or was
+/// never rebuilt)
+fn check_vector_index(state: &AppState, search: &HybridSearch) -> ComponentHealth {
+    let count = Some(search.indexed_count());
+    let dimensions = search.vector_dimensions();
+    let expected = state
+        .embedder
+        .as_ref()
+        .map_or(state.config.embedding.dimensions, |e| e.dimensions());
+    let error = if dimensions != expected {
+        Some(format!(
+            "Index has {dimensions} dimensions, the model {expected}"
+        ))
+    } else {
+        match search.consistency() {
+            Ok(c) if c.vector_indexed == 0 && c.embedded > 0 => Some(format!(
+                "Index is empty, but {} stored memories are embedded",
+                c.embedded
+            )),
+            Ok(_) => None,
+            Err(e) => Some(e.to_string()),
+        }
+    };
+    ComponentHealth {
+        healthy: error.is_none(),
+        count,
+        error,
+        ..Default::default()
+    }
+}
+
+fn unhealthy(error: String) -> ComponentHealth {
+    ComponentHealth {
+        error: Some(error),
+        ..Default::default()
+    }
+}
+
+fn elapsed_millis(started: Instant) -> f64 {
+    started.elapsed().as_secs_f64() * 1000.0
+}
+
+/// Plain `ok` while the process answers; checks no component
+pub async fn live() -> &'static str {
+    "ok"
+}
+
+/// The [`ReadinessReport`], with 503 unless every component is healthy.
+/// Checked at most once per [`READINESS_TTL`].
+pub async fn ready(State(state): State<SharedState>) -> axum::response::Response {
+    let mut last = state.readiness.last.lock().await;
+    let result = match last.as_ref() {
+        Some((at, report)) if at.elapsed() < READINESS_TTL => Ok(report.clone()),
+        _ => {
+            let check = Arc::clone(&state);
+            let result = run_blocking(move || Ok(readiness(&check))).await;
+            if let Ok(report) = &result {
+                *last = Some((Instant::now(), report.clone()));
+            }
+            result
+        }
+    };
+    drop(last);
+    match result {
+        Ok(report) if report.is_ready() => Json(report).into_response(),
+        Ok(report) => (StatusCode::SERVICE_UNAVAILABLE, Json(report)).into_response(),
+        Err(e) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ApiResponse::<()>::err(e)),
+        )
+            .into_response(),
+    }
+}
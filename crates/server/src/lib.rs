@@ -1,67 +1,222 @@
 use axum::{
     Router,
-    extract::{Json, Path, State},
-    http::StatusCode,
+    extract::{DefaultBodyLimit, Json, Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    middleware,
     response::IntoResponse,
     routing::{get, post},
 };
-use oc_core::Storage;
-use oc_core::models::{Memory, MemoryMetadata, MemoryType, Priority, SearchQuery, SearchResult};
-use oc_embeddings::EmbeddingEngine;
+use oc_core::analytics::{
+    AccessAnalytics, DEFAULT_ANALYTICS_DAYS, DEFAULT_ANALYTICS_LIMIT, access_analytics,
+};
+use oc_core::backup::{self, BackupInfo};
+use oc_core::cache::CacheStats;
+use oc_core::consolidate::{self, ConsolidationReport, ExtractiveSummarizer, Summarizer};
+use oc_core::expiry;
+use oc_core::export::{self, ExportSummary};
+use oc_core::forgetting::{self, ForgettingReport};
+use oc_core::grep::{DEFAULT_CONTEXT_CHARS, Passage, grep_pattern, grep_query};
+use oc_core::integrity::IntegrityReport;
+use oc_core::merge::{self, MergeRequest};
+use oc_core::models::{
+    ConceptCount, DiskUsage, DuplicateAction, DuplicateMatch, Escalation, FeedbackReport,
+    LinkedMemory, Memory, MemoryBreakdown, MemoryFilter, MemoryMetadata, MemoryPatch, MemoryType,
+    MissedQuery, NamespaceCount, NewLink, Priority, QuarantinedMemory, RetagReport, SearchQuery,
+    SearchResult, TagEdit, Verbosity, namespace_or_default,
+};
+use oc_core::quarantine::QuarantinePolicy;
+use oc_core::reembed::{DEFAULT_REEMBED_BATCH_SIZE, ReembedReport};
+use oc_core::snapshot::{self, SnapshotDiff};
+use oc_core::{Config, Storage};
+use oc_embeddings::Embedder;
+use oc_embeddings::chunker::embed_chunks;
+use oc_import::{ImportOptions, ImportReport, Importer};
+use oc_observer::IngestTarget;
 use oc_search::bm25::Bm25Index;
+use oc_search::experiment::ExperimentReport;
 use oc_search::hybrid::HybridSearch;
-use oc_search::scoring::Scorer;
+use oc_search::keywords::KeywordExtractor;
+use oc_search::query_syntax;
+use oc_search::scoring::{Scorer, ScorerUpdate};
+use oc_search::selftest::SelfTestReport;
 use oc_search::vector::VectorIndex;
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
 
-/// Shared application state for REST server
+pub mod auth;
+pub mod cors;
+pub mod events;
+pub mod health;
+pub mod openapi;
+pub mod sync;
+
+use auth::ApiKeys;
+use events::EventBus;
+
+/// Shared application state for REST server.
+///
+/// `Send + Sync` without unsafe impls: storage is a connection pool and the
+/// search indexes are behind a `Mutex`.
 pub struct AppState {
-    pub storage: Mutex<Storage>,
+    pub storage: Arc<Storage>,
     pub search: Mutex<HybridSearch>,
-    pub embedder: Option<Arc<EmbeddingEngine>>,
+    pub embedder: Option<Arc<dyn Embedder>>,
+    pub config: Config,
+    /// Outcome of the startup self-test, when `[self_test]` is enabled
+    pub self_test: Option<SelfTestReport>,
+    /// Summarizer used by memory consolidation
+    pub summarizer: Arc<dyn Summarizer>,
+    /// Fills concepts on store (`[keywords]`); `None` when disabled
+    pub keywords: Option<KeywordExtractor>,
+    /// Screens memories on store (`[quarantine]`); `None` when disabled
+    pub quarantine: Option<QuarantinePolicy>,
+    /// Memory change events for `GET /api/v1/events`, fed by
+    /// [`events::spawn_event_feed`]
+    pub events: EventBus,
+    /// Last `GET /health/ready` report
+    pub readiness: health::ReadinessCache,
 }
 
-// Safety: We manually ensure thread-safety via Mutex wrappers
-unsafe impl Send for AppState {}
-unsafe impl Sync for AppState {}
-
 pub type SharedState = Arc<AppState>;
 
 /// Create an in-memory AppState for testing (no embedding engine).
 ///
-/// Uses a shared temp file so that `state.storage` and `HybridSearch.storage`
-/// read from the same database (in-memory SQLite creates isolated DBs per connection).
+/// Uses a temp file database so the connection pool shared by `state.storage`
+/// and `HybridSearch` sees one database (in-memory SQLite creates isolated DBs
+/// per connection). The config's data, backup and export directories all live
+/// in that temp dir.
 pub fn test_app_state() -> SharedState {
-    let tmp = std::env::temp_dir().join(format!("oc_test_{}.db", uuid::Uuid::new_v4()));
-    let db_path = tmp.to_str().unwrap();
-    let storage = Storage::open(db_path).unwrap();
-    let search_storage = Arc::new(Storage::open(db_path).unwrap());
+    let tmp = std::env::temp_dir().join(format!("oc_test_{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&tmp).unwrap();
+    let mut config = Config::default();
+    config.storage.data_dir = tmp.to_string_lossy().to_string();
+    config.backup.dir = tmp.join("backups").to_string_lossy().to_string();
+    config.export.markdown_dir = tmp.join("vault").to_string_lossy().to_string();
+    config.import.dir = tmp.join("import").to_string_lossy().to_string();
+    config.embedding.dimensions = 4;
+
+    let db_path = config.db_path();
+    let storage = Arc::new(Storage::open_with_config(&db_path, &config.storage).unwrap());
     let vector_index = VectorIndex::new(4);
     let bm25_index = Bm25Index::in_memory().unwrap();
     let scorer = Scorer::default();
-    let search = HybridSearch::new(search_storage, vector_index, bm25_index, scorer);
+    let search = HybridSearch::new(Arc::clone(&storage), vector_index, bm25_index, scorer);
 
     Arc::new(AppState {
-        storage: Mutex::new(storage),
+        storage,
         search: Mutex::new(search),
         embedder: None,
+        summarizer: Arc::new(ExtractiveSummarizer::new(config.consolidation.target_type)),
+        keywords: config.keywords.enabled.then(KeywordExtractor::new),
+        quarantine: QuarantinePolicy::from_config(&config.quarantine),
+        events: EventBus::new(config.server.events.buffer),
+        readiness: Default::default(),
+        config,
+        self_test: None,
     })
 }
 
-/// Build the axum Router with all routes.
+/// Watched files are ingested into this server's storage and indexes
+impl IngestTarget for AppState {
+    fn storage(&self) -> &Storage {
+        &self.storage
+    }
+
+    fn search(&self) -> &Mutex<HybridSearch> {
+        &self.search
+    }
+
+    fn embedder(&self) -> Option<&dyn Embedder> {
+        self.embedder.as_deref()
+    }
+
+    fn config(&self) -> &Config {
+        &self.config
+    }
+
+    fn quarantine(&self) -> Option<&QuarantinePolicy> {
+        self.quarantine.as_ref()
+    }
+}
+
+/// Build the axum Router with all routes. `/api/*` routes require an API
+/// key when `[[server.api_keys]]` are configured; CORS preflights are
+/// answered before that check when `[server.cors]` allows origins.
 pub fn build_router(state: SharedState) -> Router {
-    Router::new()
-        .route("/health", get(health))
+    let keys = Arc::new(ApiKeys::from_config(&state.config.server.api_keys));
+    let cors = cors::layer(&state.config.server.cors);
+    let router = Router::new()
+        .route("/health", get(health::live))
+        .route("/health/live", get(health::live))
+        .route("/health/ready", get(health::ready))
+        .route(openapi::SPEC_PATH, get(openapi::serve_spec))
+        .route(openapi::DOCS_PATH, get(openapi::serve_docs))
         .route("/api/v1/search", post(api_search))
+        .route("/api/v1/search/batch", post(api_search_batch))
+        .route("/api/v1/search/suggestions", get(api_suggestions))
+        .route("/api/v1/embed", post(api_embed))
+        .route("/api/v1/events", get(events::api_events))
+        .route("/api/v1/sync/changes", get(sync::api_sync_changes))
+        .route(
+            "/api/v1/sync/apply",
+            post(sync::api_sync_apply).layer(DefaultBodyLimit::max(sync::MAX_SYNC_BODY_BYTES)),
+        )
+        .route("/api/v1/feedback", post(api_feedback))
         .route("/api/v1/memories", post(api_store))
-        .route("/api/v1/memories/{id}", get(api_get).delete(api_delete))
+        .route("/api/v1/memories/merge", post(api_merge))
+        .route(
+            "/api/v1/memories/{id}",
+            get(api_get).patch(api_update).delete(api_delete),
+        )
+        .route("/api/v1/memories/{id}/archive", post(api_archive))
+        .route("/api/v1/memories/{id}/unarchive", post(api_unarchive))
+        .route("/api/v1/memories/{id}/related", get(api_related))
+        .route("/api/v1/memories/{id}/grep", get(api_grep))
+        .route("/api/v1/concepts", get(api_concepts))
+        .route("/api/v1/concepts/{concept}", get(api_concept_memories))
         .route("/api/v1/stats", get(api_stats))
-        .with_state(state)
-}
-
-async fn health() -> &'static str {
-    "ok"
+        .route("/api/v1/analytics", get(api_analytics))
+        .route("/api/v1/namespaces", get(api_namespaces))
+        .route(
+            "/api/v1/namespaces/{namespace}/memories",
+            get(api_namespace_memories).post(api_namespace_store),
+        )
+        .route(
+            "/api/v1/namespaces/{namespace}/search",
+            post(api_namespace_search),
+        )
+        .route(
+            "/api/v1/namespaces/{namespace}/stats",
+            get(api_namespace_stats),
+        )
+        .route("/api/v1/admin/export", post(api_export))
+        .route("/api/v1/admin/backup", post(api_backup))
+        .route("/api/v1/admin/backups", get(api_list_backups))
+        .route("/api/v1/admin/backups/diff", get(api_diff_backups))
+        .route("/api/v1/admin/tune", get(api_get_tune).post(api_tune))
+        .route("/api/v1/admin/experiment", get(api_experiment))
+        .route("/api/v1/admin/verify", get(api_verify))
+        .route("/api/v1/admin/reembed", post(api_reembed))
+        .route("/api/v1/admin/retag", post(api_retag))
+        .route("/api/v1/admin/consolidate", post(api_consolidate))
+        .route("/api/v1/admin/import", post(api_import))
+        .route("/api/v1/admin/sync", post(sync::api_sync))
+        .route(
+            "/api/v1/admin/forgetting",
+            get(api_forgetting_preview).post(api_forget),
+        )
+        .route("/api/v1/admin/quarantine", get(api_quarantined))
+        .route("/api/v1/admin/misses", get(api_missed_queries))
+        .route(
+            "/api/v1/admin/quarantine/{id}/approve",
+            post(api_approve_quarantined),
+        )
+        .layer(middleware::from_fn_with_state(keys, auth::require_api_key))
+        .with_state(state);
+    match cors {
+        Some(cors) => router.layer(cors),
+        None => router,
+    }
 }
 
 // --- Request / Response types ---
@@ -69,10 +224,64 @@ async fn health() -> &'static str {
 #[derive(Deserialize)]
 pub struct SearchRequest {
     pub query: String,
+    /// Other phrasings of `query`, searched too and merged by rank (RRF)
+    #[serde(default)]
+    pub alt_queries: Vec<String>,
     #[serde(default = "default_limit")]
     pub limit: usize,
+    /// Same as `verbosity: "index"`
     #[serde(default)]
     pub index_only: bool,
+    /// How much of each memory to return (default: `[search] verbosity`,
+    /// per client in `[search.client_verbosity]`)
+    #[serde(default)]
+    pub verbosity: Option<Verbosity>,
+    /// Only match memories carrying all of these tags (normalized)
+    #[serde(default, alias = "tags")]
+    pub tags_all: Vec<String>,
+    /// Only match memories carrying at least one of these tags
+    #[serde(default)]
+    pub tags_any: Vec<String>,
+    /// Leave out memories carrying any of these tags
+    #[serde(default)]
+    pub exclude_tags: Vec<String>,
+    /// Only match memories of this type
+    #[serde(default)]
+    pub memory_type: Option<MemoryType>,
+    /// Leave out memories of these types
+    #[serde(default)]
+    pub exclude_types: Vec<MemoryType>,
+    /// Scoring profile to rank with (`[search.profiles.<name>]`)
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// Also return memories linked to each result, up to this many hops
+    #[serde(default)]
+    pub expand_links: usize,
+    /// Also search memories in the cold tier
+    #[serde(default)]
+    pub include_cold: bool,
+    /// Also search archived memories
+    #[serde(default)]
+    pub include_archived: bool,
+    /// Typed metadata field filters such as `sprint >= 42` (`[fields]`)
+    #[serde(default)]
+    pub filters: Vec<String>,
+    /// Only match memories carrying all of these concepts
+    #[serde(default)]
+    pub concepts: Vec<String>,
+    /// Only match memories in this namespace (all namespaces when unset)
+    #[serde(default)]
+    pub namespace: Option<String>,
+    /// Only the best match of each source (default: `[search]
+    /// collapse_sources`); false lists every chunk
+    #[serde(default)]
+    pub collapse_sources: Option<bool>,
+    /// Match `query` as one phrase and return only memories containing it
+    #[serde(default)]
+    pub exact: bool,
+    /// Attach the raw scores, ranks and weights behind each result
+    #[serde(default)]
+    pub explain: bool,
 }
 
 fn default_limit() -> usize {
@@ -105,34 +314,374 @@ impl<T: Serialize> ApiResponse<T> {
     }
 }
 
+/// Request header naming the calling client, recorded with deletions
+pub const CLIENT_NAME_HEADER: &str = "x-client-name";
+
 // --- Handlers ---
 
-async fn api_search(
-    State(state): State<SharedState>,
-    Json(req): Json<SearchRequest>,
-) -> Json<ApiResponse<Vec<SearchResult>>> {
-    let search_query = SearchQuery {
+/// The embedder was too busy to take a store or search; answered with 503
+/// and a `Retry-After` (whole seconds, rounded up)
+struct EmbedderBusy {
+    retry_after_ms: u64,
+}
+
+impl IntoResponse for EmbedderBusy {
+    fn into_response(self) -> axum::response::Response {
+        let secs = self.retry_after_ms.div_ceil(1000).max(1);
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            [(header::RETRY_AFTER, secs.to_string())],
+            Json(ApiResponse::<()>::err(format!(
+                "Embedder busy, retry in {} ms",
+                self.retry_after_ms
+            ))),
+        )
+            .into_response()
+    }
+}
+
+/// Run storage, search or embedding work on the blocking thread pool so the
+/// async runtime keeps serving other requests meanwhile.
+async fn run_blocking<T, F>(f: F) -> Result<T, String>
+where
+    F: FnOnce() -> Result<T, String> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("Task failed: {e}"))?
+}
+
+/// The search `req` asks for and how much of each result to return
+fn search_query(
+    config: &Config,
+    headers: &HeaderMap,
+    req: &SearchRequest,
+) -> Result<(SearchQuery, Verbosity), String> {
+    let verbosity = match (req.verbosity, req.index_only) {
+        (Some(verbosity), _) => verbosity,
+        (None, true) => Verbosity::Index,
+        (None, false) => config.search.verbosity_for(client_name(headers)),
+    };
+    let fields = config
+        .field_registry()
+        .parse_filters(&req.filters)
+        .map_err(|e| format!("Invalid filter: {e}"))?;
+    let mut search_query = SearchQuery {
         query: req.query.clone(),
+        alt_queries: req.alt_queries.clone(),
         limit: req.limit,
-        index_only: req.index_only,
+        tags_all: (!req.tags_all.is_empty()).then(|| req.tags_all.clone()),
+        tags_any: (!req.tags_any.is_empty()).then(|| req.tags_any.clone()),
+        exclude_tags: req.exclude_tags.clone(),
+        exclude_types: req.exclude_types.clone(),
+        index_only: !verbosity.includes_content(),
+        memory_type: req.memory_type,
+        profile: req.profile.clone(),
+        expand_links: req.expand_links,
+        include_cold: req.include_cold,
+        include_archived: req.include_archived,
+        fields,
+        concepts: req.concepts.clone(),
+        namespace: req.namespace.clone(),
+        collapse_sources: req
+            .collapse_sources
+            .unwrap_or(config.search.collapse_sources),
+        exact: req.exact,
+        explain: req.explain,
         ..Default::default()
     };
+    query_syntax::apply(&mut search_query).map_err(|e| format!("Invalid query: {e}"))?;
+    Ok((search_query, verbosity))
+}
 
-    let query_embedding = state
-        .embedder
-        .as_ref()
-        .and_then(|e| e.embed(&req.query).ok());
-    let empty = vec![0f32; state.embedder.as_ref().map_or(1024, |e| e.dimensions())];
-    let emb = query_embedding.as_deref().unwrap_or(&empty);
+/// Embed query texts in one batch; empty (keyword-only search) without an
+/// embedder or when embedding fails other than by being busy
+fn embed_query_texts(state: &AppState, texts: &[&str]) -> Result<Vec<Vec<f32>>, EmbedderBusy> {
+    match state.embedder.as_ref().map(|e| e.embed_batch(texts)) {
+        Some(Ok(embeddings)) => Ok(embeddings),
+        Some(Err(err)) => match err.retry_after_ms() {
+            Some(retry_after_ms) => Err(EmbedderBusy { retry_after_ms }),
+            None => {
+                tracing::warn!("Query embedding failed: {err}, searching keyword-only");
+                Ok(Vec::new())
+            }
+        },
+        None => Ok(Vec::new()),
+    }
+}
 
-    let search = match state.search.lock() {
-        Ok(s) => s,
-        Err(e) => return Json(ApiResponse::err(format!("Lock error: {e}"))),
+async fn api_search(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Json(req): Json<SearchRequest>,
+) -> axum::response::Response {
+    let (search_query, verbosity) = match search_query(&state.config, &headers, &req) {
+        Ok(query) => query,
+        Err(e) => return Json(ApiResponse::<()>::err(e)).into_response(),
+    };
+
+    let result = run_blocking(move || {
+        let texts: Vec<&str> = search_query.texts().collect();
+        let query_embeddings = match embed_query_texts(&state, &texts) {
+            Ok(embeddings) => embeddings,
+            Err(busy) => return Ok(Err(busy)),
+        };
+        // Without an embedding the search runs keyword-only and marks results degraded
+        let embs: Vec<&[f32]> = query_embeddings.iter().map(Vec::as_slice).collect();
+
+        let mut search = state
+            .search
+            .lock()
+            .map_err(|e| format!("Lock error: {e}"))?;
+        let mut results = search
+            .search_fused(&embs, &search_query)
+            .map_err(|e| format!("Search failed: {e}"))?;
+        if let Err(e) = search.record_miss(&search_query, &results, &state.config.misses) {
+            tracing::warn!("Failed to record missed query: {e}");
+        }
+        for result in &mut results {
+            verbosity.shape(result);
+        }
+        Ok(Ok(results))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(results)) => Json(ApiResponse::ok(results)).into_response(),
+        Ok(Err(busy)) => busy.into_response(),
+        Err(e) => Json(ApiResponse::<()>::err(e)).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SearchBatchRequest {
+    /// Searches to run, each taking every option of a single search (at
+    /// most `[search] max_batch_queries`)
+    pub queries: Vec<SearchRequest>,
+}
+
+/// Results of one search of a batch
+#[derive(Serialize)]
+pub struct SearchGroup {
+    /// The query as sent
+    pub query: String,
+    pub results: Vec<SearchResult>,
+}
+
+/// Several searches in one request: every formulation of every query is
+/// embedded in one batch, then the searches run in parallel
+async fn api_search_batch(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Json(req): Json<SearchBatchRequest>,
+) -> axum::response::Response {
+    let max = state.config.search.max_batch_queries;
+    if req.queries.is_empty() || req.queries.len() > max {
+        return Json(ApiResponse::<()>::err(format!(
+            "A batch takes 1 to {max} queries, got {}",
+            req.queries.len()
+        )))
+        .into_response();
+    }
+    let mut queries = Vec::with_capacity(req.queries.len());
+    for (i, query) in req.queries.iter().enumerate() {
+        match search_query(&state.config, &headers, query) {
+            Ok(query) => queries.push(query),
+            Err(e) => {
+                return Json(ApiResponse::<()>::err(format!("Query {i}: {e}"))).into_response();
+            }
+        }
+    }
+    let sent: Vec<String> = req.queries.into_iter().map(|q| q.query).collect();
+
+    let result = run_blocking(move || {
+        let texts: Vec<&str> = queries.iter().flat_map(|(q, _)| q.texts()).collect();
+        let query_embeddings = match embed_query_texts(&state, &texts) {
+            Ok(embeddings) => embeddings,
+            Err(busy) => return Ok(Err(busy)),
+        };
+        // Hand each query the embeddings of its own formulations
+        let mut embeddings = query_embeddings.iter().map(Vec::as_slice);
+        let batch: Vec<(Vec<&[f32]>, SearchQuery)> = queries
+            .iter()
+            .map(|(query, _)| {
+                let embs = embeddings.by_ref().take(query.texts().count()).collect();
+                (embs, query.clone())
+            })
+            .collect();
+
+        let mut search = state
+            .search
+            .lock()
+            .map_err(|e| format!("Lock error: {e}"))?;
+        let outcomes = search.search_batch(&batch);
+        let mut groups = Vec::with_capacity(batch.len());
+        for ((outcome, (query, verbosity)), sent) in outcomes.into_iter().zip(&queries).zip(sent) {
+            let mut results = outcome.map_err(|e| format!("Search failed: {e}"))?;
+            if let Err(e) = search.record_miss(query, &results, &state.config.misses) {
+                tracing::warn!("Failed to record missed query: {e}");
+            }
+            for result in &mut results {
+                verbosity.shape(result);
+            }
+            groups.push(SearchGroup {
+                query: sent,
+                results,
+            });
+        }
+        Ok(Ok(groups))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(groups)) => Json(ApiResponse::ok(groups)).into_response(),
+        Ok(Err(busy)) => busy.into_response(),
+        Err(e) => Json(ApiResponse::<()>::err(e)).into_response(),
+    }
+}
+
+/// Most texts one `POST /api/v1/embed` may carry
+const MAX_EMBED_TEXTS: usize = 256;
+
+#[derive(Deserialize)]
+pub struct EmbedRequest {
+    /// Texts to embed, at most 256
+    pub texts: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct EmbedResponse {
+    /// Model that produced the vectors, as stored with memory embeddings
+    pub model: String,
+    pub dimensions: usize,
+    /// One vector per text, in request order
+    pub embeddings: Vec<Vec<f32>>,
+    /// Indexes of texts over the model's input limit, embedded from their
+    /// beginning only
+    pub truncated: Vec<usize>,
+}
+
+/// Vectors for arbitrary text from the loaded model, so other tools can
+/// share it instead of loading their own
+async fn api_embed(
+    State(state): State<SharedState>,
+    Json(req): Json<EmbedRequest>,
+) -> axum::response::Response {
+    let Some(embedder) = state.embedder.clone() else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ApiResponse::<()>::err("Embedding engine not available")),
+        )
+            .into_response();
     };
+    if req.texts.is_empty() || req.texts.len() > MAX_EMBED_TEXTS {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::err(format!(
+                "Expected 1 to {MAX_EMBED_TEXTS} texts, got {}",
+                req.texts.len()
+            ))),
+        )
+            .into_response();
+    }
+
+    let result = run_blocking(move || {
+        let texts: Vec<&str> = req.texts.iter().map(String::as_str).collect();
+        let embeddings = match embedder.embed_batch(&texts) {
+            Ok(embeddings) => embeddings,
+            Err(err) => match err.retry_after_ms() {
+                Some(retry_after_ms) => return Ok(Err(EmbedderBusy { retry_after_ms })),
+                None => return Err(format!("Embedding failed: {err}")),
+            },
+        };
+        let truncated = texts
+            .iter()
+            .enumerate()
+            .filter(|(_, text)| embedder.truncates(text))
+            .map(|(i, _)| i)
+            .collect();
+        Ok(Ok(EmbedResponse {
+            model: embedder.model_id().to_string(),
+            dimensions: embedder.dimensions(),
+            embeddings,
+            truncated,
+        }))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(response)) => Json(ApiResponse::ok(response)).into_response(),
+        Ok(Err(busy)) => busy.into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err(e)),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SuggestParams {
+    pub query: String,
+    /// Most corrections (default: `[search] suggestions`)
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// Did-you-mean corrections of a query whose keywords match nothing,
+/// spelled like indexed terms; empty when they match
+async fn api_suggestions(
+    State(state): State<SharedState>,
+    Query(params): Query<SuggestParams>,
+) -> (StatusCode, Json<ApiResponse<Vec<String>>>) {
+    let limit = params.limit.unwrap_or(state.config.search.suggestions);
+    let result = run_blocking(move || {
+        state
+            .search
+            .lock()
+            .map_err(|e| format!("Lock error: {e}"))?
+            .suggest(&params.query, limit)
+            .map_err(|e| format!("Suggestions failed: {e}"))
+    })
+    .await;
+    match result {
+        Ok(suggestions) => (StatusCode::OK, Json(ApiResponse::ok(suggestions))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::err(e))),
+    }
+}
+
+/// Which retrieved memories helped with the task
+#[derive(Deserialize)]
+pub struct FeedbackRequest {
+    #[serde(default)]
+    pub useful: Vec<String>,
+    #[serde(default)]
+    pub not_useful: Vec<String>,
+}
 
-    match search.search(emb, &search_query) {
-        Ok(results) => Json(ApiResponse::ok(results)),
-        Err(e) => Json(ApiResponse::err(format!("Search failed: {e}"))),
+async fn api_feedback(
+    State(state): State<SharedState>,
+    Json(req): Json<FeedbackRequest>,
+) -> (StatusCode, Json<ApiResponse<FeedbackReport>>) {
+    if req.useful.is_empty() && req.not_useful.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::err("No memories marked useful or not useful")),
+        );
+    }
+
+    let result = run_blocking(move || {
+        state
+            .storage
+            .record_feedback(&req.useful, &req.not_useful)
+            .map_err(|e| format!("Recording feedback failed: {e}"))
+    })
+    .await;
+
+    match result {
+        Ok(report) => (StatusCode::OK, Json(ApiResponse::ok(report))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::err(e))),
     }
 }
 
@@ -146,6 +695,25 @@ pub struct StoreRequest {
     pub priority: String,
     #[serde(default)]
     pub tags: Vec<String>,
+    /// Values of typed metadata fields registered in `[fields]`
+    #[serde(default)]
+    pub fields: serde_json::Map<String, serde_json::Value>,
+    /// Expire the memory this many hours after storing it
+    #[serde(default)]
+    pub ttl_hours: Option<u64>,
+    /// Key concepts; extracted from the text when empty (`[keywords]`)
+    #[serde(default)]
+    pub concepts: Vec<String>,
+    /// Namespace to store in; `default` when unset
+    #[serde(default)]
+    pub namespace: Option<String>,
+    /// Links from the new memory to existing ones, created in the same
+    /// transaction; nothing is stored when a target does not exist
+    #[serde(default)]
+    pub links: Vec<NewLink>,
+    /// Where the memory came from, such as `hook:<sentence hash>`
+    #[serde(default)]
+    pub source: Option<String>,
 }
 fn default_type() -> String {
     "observation".to_string()
@@ -159,156 +727,1723 @@ pub struct StoreResponse {
     pub id: String,
     pub title: String,
     pub has_embedding: bool,
+    /// The content exceeded the embedding model's input limit and only part
+    /// of it was embedded
+    #[serde(default)]
+    pub truncated: bool,
+    /// Existing memory this one nearly duplicates. With `merge` the
+    /// response `id` is that memory's; with `reject` nothing was stored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplicate: Option<DuplicateMatch>,
+    /// Why `[quarantine]` held the memory back from search and retrieval
+    /// until approved via `/api/v1/admin/quarantine/{id}/approve`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quarantined: Option<String>,
+    /// Stored memory on the same topic whose priority this store raised
+    /// (`[escalation]`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub escalation: Option<Escalation>,
 }
 
 async fn api_store(
     State(state): State<SharedState>,
     Json(req): Json<StoreRequest>,
-) -> (StatusCode, Json<ApiResponse<StoreResponse>>) {
+) -> axum::response::Response {
     let memory_type = serde_json::from_str::<MemoryType>(&format!("\"{}\"", req.memory_type))
         .unwrap_or(MemoryType::Observation);
     let priority = serde_json::from_str::<Priority>(&format!("\"{}\"", req.priority))
         .unwrap_or(Priority::Medium);
-
-    let embedding = state
-        .embedder
-        .as_ref()
-        .and_then(|e| e.embed(&req.content).ok());
-
-    let mut memory = Memory::new(
-        req.content,
-        req.title.clone(),
-        MemoryMetadata {
-            memory_type,
-            priority,
-            tags: req.tags,
-            ..Default::default()
-        },
-    );
-    memory.embedding = embedding.clone();
-
-    // Store in SQLite
-    {
-        let storage = match state.storage.lock() {
-            Ok(s) => s,
+    let fields = match state.config.field_registry().encode_all(&req.fields) {
+        Ok(fields) => fields,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()>::err(format!("Invalid fields: {e}"))),
+            )
+                .into_response();
+        }
+    };
+    // Checked here to answer 400; the insert checks again in its transaction
+    if !req.links.is_empty() {
+        let targets: Vec<String> = req.links.iter().map(|l| l.target_id.clone()).collect();
+        let storage = Arc::clone(&state.storage);
+        let found = run_blocking(move || {
+            storage
+                .get_many(&targets)
+                .map(|found| (targets, found))
+                .map_err(|e| format!("Storage: {e}"))
+        })
+        .await;
+        let missing = match found {
+            Ok((targets, found)) => targets
+                .into_iter()
+                .find(|id| !found.iter().any(|m| &m.id == id)),
             Err(e) => {
                 return (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ApiResponse::err(format!("Lock: {e}"))),
-                );
+                    Json(ApiResponse::<()>::err(e)),
+                )
+                    .into_response();
             }
         };
-        if let Err(e) = storage.insert(&memory) {
+        if let Some(id) = missing {
             return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::err(format!("Storage: {e}"))),
-            );
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()>::err(format!(
+                    "Invalid links: memory {id} not found"
+                ))),
+            )
+                .into_response();
         }
     }
 
-    // Index in search
-    if let Ok(mut search) = state.search.lock() {
-        let _ = search.index_memory(&memory);
+    let title = req.title.clone();
+    let result = run_blocking(move || {
+        // A saturated embedder turns the store away (503) rather than
+        // storing the memory without an embedding
+        let embedding = match state.embedder.as_ref().map(|e| e.embed(&req.content)) {
+            Some(Ok(embedding)) => Some(embedding),
+            Some(Err(err)) => match err.retry_after_ms() {
+                Some(retry_after_ms) => return Ok(Err(EmbedderBusy { retry_after_ms })),
+                None => None,
+            },
+            None => None,
+        };
+        let chunks = match (&state.embedder, &embedding) {
+            (Some(e), Some(_)) => {
+                match embed_chunks(e.as_ref(), &req.content, &state.config.embedding.chunking) {
+                    Ok(chunks) => chunks,
+                    Err(err) => match err.retry_after_ms() {
+                        Some(retry_after_ms) => return Ok(Err(EmbedderBusy { retry_after_ms })),
+                        None => {
+                            tracing::warn!("Chunk embedding failed: {err}");
+                            Vec::new()
+                        }
+                    },
+                }
+            }
+            _ => Vec::new(),
+        };
+        let truncated = embedding.is_some()
+            && chunks.is_empty()
+            && state
+                .embedder
+                .as_ref()
+                .is_some_and(|e| e.truncates(&req.content));
+
+        let mut memory = Memory::new(
+            req.content,
+            req.title,
+            MemoryMetadata {
+                memory_type,
+                priority,
+                tags: req.tags,
+                concepts: req.concepts,
+                source: req.source,
+                ..Default::default()
+            },
+        );
+        if let Some(keywords) = &state.keywords {
+            keywords.fill_concepts(&mut memory, state.config.keywords.max_concepts);
+        }
+        if embedding.is_some() {
+            memory.embedding_model = state.embedder.as_ref().map(|e| e.model_id().to_string());
+        }
+        memory.embedding = embedding;
+        memory.expires_at = req
+            .ttl_hours
+            .map(|hours| expiry::expires_after(memory.created_at, hours));
+        memory.namespace = namespace_or_default(req.namespace.as_deref());
+        // Flagged memories are stored on their own, never merged
+        let quarantined = state.quarantine.as_ref().and_then(|q| q.check(&memory));
+
+        let dedup = &state.config.dedup;
+        // A repeat counts whether the memory is then stored, merged or rejected
+        let (duplicate, escalation) = match (&memory.embedding, state.search.lock()) {
+            (Some(embedding), Ok(search)) if quarantined.is_none() => (
+                dedup
+                    .enabled
+                    .then(|| search.find_duplicate(embedding, dedup.threshold, &memory.namespace))
+                    .flatten()
+                    .map(|(id, similarity)| DuplicateMatch {
+                        id,
+                        similarity,
+                        action: dedup.action,
+                    }),
+                search
+                    .record_topic_repeat(embedding, &memory.namespace, &state.config.escalation)
+                    .unwrap_or_else(|e| {
+                        tracing::warn!("Failed to record topic repeat: {e}");
+                        None
+                    }),
+            ),
+            _ => (None, None),
+        };
+        let has_embedding = memory.embedding.is_some();
+        match &duplicate {
+            Some(found) if found.action == DuplicateAction::Reject => {
+                return Ok(Ok((
+                    StatusCode::CONFLICT,
+                    found.id.clone(),
+                    has_embedding,
+                    truncated,
+                    duplicate,
+                    None,
+                    escalation,
+                )));
+            }
+            Some(found) if found.action == DuplicateAction::Merge => {
+                let merged = state
+                    .storage
+                    .merge_duplicate(&found.id, &memory)
+                    .map_err(|e| format!("Storage: {e}"))?;
+                // The index can briefly outlive a deleted memory; store anew then
+                if merged.is_some() {
+                    state
+                        .storage
+                        .set_fields(&found.id, &fields)
+                        .map_err(|e| format!("Storage: {e}"))?;
+                    state
+                        .storage
+                        .link_all(&found.id, &req.links)
+                        .map_err(|e| format!("Storage: {e}"))?;
+                    return Ok(Ok((
+                        StatusCode::OK,
+                        found.id.clone(),
+                        has_embedding,
+                        truncated,
+                        duplicate,
+                        None,
+                        escalation,
+                    )));
+                }
+            }
+            _ => {}
+        }
+
+        // Store in SQLite, with its links in the same transaction
+        state
+            .storage
+            .insert_linked(&memory, &req.links)
+            .map_err(|e| format!("Storage: {e}"))?;
+        // Before indexing, so search never sees the memory unflagged
+        if let Some(reason) = &quarantined {
+            state
+                .storage
+                .quarantine(&memory.id, reason)
+                .map_err(|e| format!("Storage: {e}"))?;
+        }
+        state
+            .storage
+            .set_fields(&memory.id, &fields)
+            .map_err(|e| format!("Storage: {e}"))?;
+
+        if !chunks.is_empty()
+            && let Err(e) = state.storage.replace_chunk_embeddings(&memory.id, &chunks)
+        {
+            tracing::warn!("Failed to store chunk embeddings of {}: {e}", memory.id);
+        }
+
+        // Index in search
+        if let Ok(mut search) = state.search.lock() {
+            let _ = search.index_memory(&memory);
+            let _ = search.index_chunks(&memory.id, &chunks);
+        }
+        let duplicate = duplicate.filter(|d| d.action == DuplicateAction::Flag);
+        Ok(Ok((
+            StatusCode::CREATED,
+            memory.id,
+            has_embedding,
+            truncated,
+            duplicate,
+            quarantined,
+            escalation,
+        )))
+    })
+    .await;
+
+    match result {
+        Ok(Ok((status, id, has_embedding, truncated, duplicate, quarantined, escalation))) => {
+            let response = StoreResponse {
+                id,
+                title,
+                has_embedding,
+                truncated,
+                duplicate,
+                quarantined,
+                escalation,
+            };
+            if status == StatusCode::CONFLICT {
+                let error = format!("Near-duplicate of memory {}; not stored", response.id);
+                return (
+                    status,
+                    Json(ApiResponse {
+                        success: false,
+                        data: Some(response),
+                        error: Some(error),
+                    }),
+                )
+                    .into_response();
+            }
+            (status, Json(ApiResponse::ok(response))).into_response()
+        }
+        Ok(Err(busy)) => busy.into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err(e)),
+        )
+            .into_response(),
     }
+}
 
-    (
-        StatusCode::CREATED,
-        Json(ApiResponse::ok(StoreResponse {
-            id: memory.id,
-            title: req.title,
-            has_embedding: embedding.is_some(),
-        })),
-    )
+#[derive(Deserialize, Default)]
+pub struct GetParams {
+    /// Also return memories linked to this one, up to this many hops
+    #[serde(default)]
+    pub expand_links: usize,
 }
 
-async fn api_get(State(state): State<SharedState>, Path(id): Path<String>) -> impl IntoResponse {
-    let storage = match state.storage.lock() {
-        Ok(s) => s,
-        Err(e) => {
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::err(format!("Lock: {e}"))),
-            )
-                .into_response();
+/// A memory with the memories linked to it, when requested
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MemoryWithLinks {
+    #[serde(flatten)]
+    pub memory: Memory,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub linked: Vec<LinkedMemory>,
+    /// Typed metadata fields set on the memory
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub fields: serde_json::Map<String, serde_json::Value>,
+}
+
+async fn api_get(
+    State(state): State<SharedState>,
+    Path(id): Path<String>,
+    Query(params): Query<GetParams>,
+) -> impl IntoResponse {
+    let result = run_blocking(move || {
+        let memory = state.storage.get(&id).map_err(|e| format!("{e}"))?;
+        // A quarantined memory reads as not found
+        let quarantined = state
+            .storage
+            .quarantined_among(std::slice::from_ref(&id))
+            .map_err(|e| format!("{e}"))?;
+        let memory = memory.filter(|_| quarantined.is_empty());
+        let Some(memory) = memory else {
+            let tombstone = state
+                .storage
+                .tombstones(std::slice::from_ref(&id))
+                .map_err(|e| format!("{e}"))?
+                .pop();
+            return Ok(Err(tombstone));
+        };
+        let _ = state.storage.touch(&id);
+        if let Ok(search) = state.search.lock() {
+            search.record_fetch(std::slice::from_ref(&id));
         }
-    };
+        let linked = if params.expand_links > 0 {
+            state
+                .storage
+                .expand_links(std::slice::from_ref(&id), params.expand_links)
+                .map_err(|e| format!("{e}"))?
+                .remove(&id)
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        let registry = state.config.field_registry();
+        let fields = state
+            .storage
+            .fields_of(&id)
+            .map_err(|e| format!("{e}"))?
+            .into_iter()
+            .map(|(name, value)| {
+                let value = registry.decode(&name, value);
+                (name, value)
+            })
+            .collect();
+        Ok(Ok(MemoryWithLinks {
+            memory,
+            linked,
+            fields,
+        }))
+    })
+    .await;
 
-    match storage.get(&id) {
-        Ok(Some(mut memory)) => {
-            let _ = storage.touch(&id);
-            memory.embedding = None;
-            drop(storage);
-            Json(ApiResponse::ok(memory)).into_response()
+    match result {
+        Ok(Ok(mut found)) => {
+            found.memory.embedding = None;
+            Json(ApiResponse::ok(found)).into_response()
         }
-        Ok(None) => (
+        // Deleted: 410 with the tombstone so clients know why it is gone
+        Ok(Err(Some(tombstone))) => (
+            StatusCode::GONE,
+            Json(ApiResponse {
+                success: false,
+                data: Some(tombstone),
+                error: Some("Deleted".to_string()),
+            }),
+        )
+            .into_response(),
+        Ok(Err(None)) => (
             StatusCode::NOT_FOUND,
             Json(ApiResponse::<()>::err("Not found")),
         )
             .into_response(),
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::err(format!("{e}"))),
+            Json(ApiResponse::<()>::err(e)),
         )
             .into_response(),
     }
 }
 
-async fn api_delete(State(state): State<SharedState>, Path(id): Path<String>) -> impl IntoResponse {
-    if let Ok(mut search) = state.search.lock() {
-        let _ = search.remove_memory(&id);
-    }
+#[derive(Deserialize)]
+pub struct RelatedParams {
+    #[serde(default = "default_related_limit")]
+    pub limit: usize,
+}
 
-    let storage = match state.storage.lock() {
-        Ok(s) => s,
-        Err(e) => {
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::<()>::err(format!("Lock: {e}"))),
-            )
-                .into_response();
-        }
-    };
+fn default_related_limit() -> usize {
+    5
+}
 
-    match storage.delete(&id) {
-        Ok(true) => Json(ApiResponse::ok("deleted")).into_response(),
-        Ok(false) => (
+/// Memories similar to a stored one, excluding itself
+async fn api_related(
+    State(state): State<SharedState>,
+    Path(id): Path<String>,
+    Query(params): Query<RelatedParams>,
+) -> impl IntoResponse {
+    let result = run_blocking(move || {
+        let Some(memory) = state.storage.get(&id).map_err(|e| format!("{e}"))? else {
+            return Ok(None);
+        };
+        let search = state
+            .search
+            .lock()
+            .map_err(|e| format!("Lock error: {e}"))?;
+        search
+            .related(&memory, params.limit)
+            .map(Some)
+            .map_err(|e| format!("Search failed: {e}"))
+    })
+    .await;
+
+    match result {
+        Ok(Some(results)) => Json(ApiResponse::ok(results)).into_response(),
+        Ok(None) => (
             StatusCode::NOT_FOUND,
             Json(ApiResponse::<()>::err("Not found")),
         )
             .into_response(),
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::<()>::err(format!("{e}"))),
+            Json(ApiResponse::<()>::err(e)),
         )
             .into_response(),
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct StatsResponse {
-    pub total_memories: usize,
-    pub indexed_count: usize,
-    pub has_embedder: bool,
-    pub search_mode: String,
+#[derive(Deserialize)]
+pub struct GrepParams {
+    /// Literal text to find (case-insensitive)
+    #[serde(default)]
+    pub pattern: Option<String>,
+    /// Words to find; passages containing more of them come first
+    #[serde(default)]
+    pub query: Option<String>,
+    /// Characters of context on each side of a match
+    #[serde(default = "default_grep_context")]
+    pub context: usize,
+    #[serde(default = "default_related_limit")]
+    pub limit: usize,
 }
 
-async fn api_stats(State(state): State<SharedState>) -> Json<ApiResponse<StatsResponse>> {
-    let total = state
-        .storage
-        .lock()
-        .map(|s| s.count().unwrap_or(0))
-        .unwrap_or(0);
-    let indexed = state.search.lock().map(|s| s.indexed_count()).unwrap_or(0);
-    let has_embedder = state.embedder.is_some();
+fn default_grep_context() -> usize {
+    DEFAULT_CONTEXT_CHARS
+}
 
-    Json(ApiResponse::ok(StatsResponse {
-        total_memories: total,
-        indexed_count: indexed,
-        has_embedder,
-        search_mode: if has_embedder {
-            "hybrid".to_string()
-        } else {
+/// Passages of one memory's content that match, with offsets
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GrepResponse {
+    pub id: String,
+    pub title: String,
+    /// Length of the whole content in characters
+    pub length: usize,
+    pub passages: Vec<Passage>,
+}
+
+/// Matching passages of a (long) memory instead of its whole content
+async fn api_grep(
+    State(state): State<SharedState>,
+    Path(id): Path<String>,
+    Query(params): Query<GrepParams>,
+) -> impl IntoResponse {
+    let pattern = params.pattern.filter(|p| !p.is_empty());
+    let query = params.query.filter(|q| !q.trim().is_empty());
+    if pattern.is_some() == query.is_some() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::err(
+                "Pass exactly one of pattern or query",
+            )),
+        )
+            .into_response();
+    }
+    let result = run_blocking(move || {
+        let quarantined = state
+            .storage
+            .quarantined_among(std::slice::from_ref(&id))
+            .map_err(|e| format!("{e}"))?;
+        let memory = state.storage.get(&id).map_err(|e| format!("{e}"))?;
+        let Some(memory) = memory.filter(|_| quarantined.is_empty()) else {
+            return Ok(None);
+        };
+        let passages = match (pattern, query) {
+            (Some(pattern), _) => {
+                grep_pattern(&memory.content, &pattern, params.context, params.limit)
+            }
+            (_, Some(query)) => grep_query(&memory.content, &query, params.context, params.limit),
+            (None, None) => Vec::new(),
+        };
+        let _ = state.storage.touch(&id);
+        Ok(Some(GrepResponse {
+            length: memory.content.chars().count(),
+            id: memory.id,
+            title: memory.title,
+            passages,
+        }))
+    })
+    .await;
+
+    match result {
+        Ok(Some(found)) => Json(ApiResponse::ok(found)).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::err("Not found")),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err(e)),
+        )
+            .into_response(),
+    }
+}
+
+/// Metadata-only update: no re-embedding, and the keyword index is only
+/// touched when the title changes
+async fn api_update(
+    State(state): State<SharedState>,
+    Path(id): Path<String>,
+    Json(patch): Json<MemoryPatch>,
+) -> impl IntoResponse {
+    if patch.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::err("No fields to update")),
+        )
+            .into_response();
+    }
+
+    let result = run_blocking(move || {
+        let updated = state
+            .storage
+            .update_metadata(&id, &patch)
+            .map_err(|e| format!("{e}"))?;
+        if let Some(memory) = &updated
+            && patch.title.is_some()
+            && let Ok(mut search) = state.search.lock()
+        {
+            let _ = search.index_memory_text(&memory.id, &memory.title, &memory.content);
+        }
+        Ok(updated)
+    })
+    .await;
+
+    match result {
+        Ok(Some(mut memory)) => {
+            memory.embedding = None;
+            Json(ApiResponse::ok(memory)).into_response()
+        }
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::err("Not found")),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err(e)),
+        )
+            .into_response(),
+    }
+}
+
+async fn api_delete(
+    State(state): State<SharedState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let actor = client_actor(&headers);
+    let result = run_blocking(move || {
+        if let Ok(mut search) = state.search.lock() {
+            let _ = search.remove_memory(&id);
+        }
+        state
+            .storage
+            .delete_as(&id, Some(&actor))
+            .map_err(|e| format!("{e}"))
+    })
+    .await;
+
+    match result {
+        Ok(true) => Json(ApiResponse::ok("deleted")).into_response(),
+        Ok(false) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::err("Not found")),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err(e)),
+        )
+            .into_response(),
+    }
+}
+
+/// Merge duplicate memories into one that supersedes them, summarized with
+/// the state's summarizer in `summarize` mode, and index it
+async fn api_merge(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Json(req): Json<MergeRequest>,
+) -> impl IntoResponse {
+    let actor = client_actor(&headers);
+    let result = run_blocking(move || {
+        let report = match merge::merge(
+            &state.storage,
+            &req,
+            state.summarizer.as_ref(),
+            |text| state.embedder.as_ref().and_then(|e| e.embed(text).ok()),
+            state.embedder.as_ref().map(|e| e.model_id()),
+            Some(&actor),
+        ) {
+            Ok(report) => report,
+            Err(e) => return Ok(Err(e)),
+        };
+        if let Some(memory) = state.storage.get(&report.id).map_err(|e| format!("{e}"))?
+            && let Ok(mut search) = state.search.lock()
+        {
+            search.index_memory(&memory).map_err(|e| format!("{e}"))?;
+        }
+        Ok(Ok(report))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(report)) => (StatusCode::CREATED, Json(ApiResponse::ok(report))).into_response(),
+        Ok(Err(oc_core::Error::NotFound(id))) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::err(format!("Memory not found: {id}"))),
+        )
+            .into_response(),
+        Ok(Err(oc_core::Error::Other(e))) => {
+            (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::err(e))).into_response()
+        }
+        Ok(Err(e)) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err(format!("Merge failed: {e}"))),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err(e)),
+        )
+            .into_response(),
+    }
+}
+
+/// Archive a memory: it stays stored and readable by ID but drops out of
+/// default searches, listings and stats. It stays indexed, so nothing is
+/// reindexed either way.
+async fn api_archive(
+    State(state): State<SharedState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    set_archived(state, id, client_actor(&headers), true).await
+}
+
+/// Bring an archived memory back into default searches and stats
+async fn api_unarchive(
+    State(state): State<SharedState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    set_archived(state, id, client_actor(&headers), false).await
+}
+
+async fn set_archived(
+    state: SharedState,
+    id: String,
+    actor: String,
+    archive: bool,
+) -> axum::response::Response {
+    let result = run_blocking(move || {
+        state
+            .storage
+            .set_archived_state(&id, archive, Some(&actor))
+            .map_err(|e| format!("{e}"))
+    })
+    .await;
+
+    match (result, archive) {
+        (Ok(true), true) => Json(ApiResponse::ok("archived")).into_response(),
+        (Ok(true), false) => Json(ApiResponse::ok("unarchived")).into_response(),
+        (Ok(false), true) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::err(
+                "No memory with this ID, or it is already archived",
+            )),
+        )
+            .into_response(),
+        (Ok(false), false) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::err("No archived memory with this ID")),
+        )
+            .into_response(),
+        (Err(e), _) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err(e)),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ConceptParams {
+    /// Only concepts starting with this
+    #[serde(default)]
+    pub prefix: Option<String>,
+    #[serde(default = "default_concept_limit")]
+    pub limit: usize,
+}
+
+fn default_concept_limit() -> usize {
+    50
+}
+
+/// Browse concepts by how many memories carry them
+async fn api_concepts(
+    State(state): State<SharedState>,
+    Query(params): Query<ConceptParams>,
+) -> Json<ApiResponse<Vec<ConceptCount>>> {
+    let result = run_blocking(move || {
+        state
+            .storage
+            .concept_counts(params.prefix.as_deref(), params.limit)
+            .map_err(|e| format!("{e}"))
+    })
+    .await;
+    match result {
+        Ok(counts) => Json(ApiResponse::ok(counts)),
+        Err(e) => Json(ApiResponse::err(e)),
+    }
+}
+
+/// Memories carrying a concept, newest first
+async fn api_concept_memories(
+    State(state): State<SharedState>,
+    Path(concept): Path<String>,
+    Query(params): Query<ConceptParams>,
+) -> Json<ApiResponse<Vec<Memory>>> {
+    let result = run_blocking(move || {
+        state
+            .storage
+            .with_concept(&concept, params.limit)
+            .map_err(|e| format!("{e}"))
+    })
+    .await;
+    match result {
+        Ok(mut memories) => {
+            for memory in &mut memories {
+                memory.embedding = None;
+            }
+            Json(ApiResponse::ok(memories))
+        }
+        Err(e) => Json(ApiResponse::err(e)),
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StatsResponse {
+    pub total_memories: usize,
+    /// Memories in the cold tier, left out of default searches
+    #[serde(default)]
+    pub cold_memories: usize,
+    /// Archived memories, left out of the other counts unless
+    /// `include_archived=true`
+    #[serde(default)]
+    pub archived_memories: usize,
+    pub indexed_count: usize,
+    pub has_embedder: bool,
+    pub search_mode: String,
+    /// Namespace the counts are scoped to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// Memories per namespace (unscoped stats only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub namespaces: Vec<NamespaceCount>,
+    /// Hit rate of the read cache in front of storage, across namespaces
+    #[serde(default)]
+    pub cache: CacheStats,
+    /// Hit rate of the search result cache, across namespaces
+    #[serde(default)]
+    pub query_cache: CacheStats,
+    /// Counts by type, priority and tag, age range and embedding coverage
+    #[serde(default)]
+    pub breakdown: MemoryBreakdown,
+    /// Bytes on disk of the database and indexes, across namespaces
+    #[serde(default)]
+    pub disk: DiskUsage,
+}
+
+/// Most carried tags listed by the stats
+const STATS_TOP_TAGS: usize = 10;
+
+#[derive(Deserialize, Default)]
+pub struct StatsParams {
+    /// Count archived memories in the totals and breakdown too
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+async fn api_stats(
+    State(state): State<SharedState>,
+    Query(params): Query<StatsParams>,
+) -> Json<ApiResponse<StatsResponse>> {
+    stats(state, None, params.include_archived).await
+}
+
+async fn stats(
+    state: SharedState,
+    namespace: Option<String>,
+    include_archived: bool,
+) -> Json<ApiResponse<StatsResponse>> {
+    let has_embedder = state.embedder.is_some();
+    let cache = state.storage.cache_stats();
+    let scope = namespace.clone();
+    let (total, cold, archived, indexed, query_cache, namespaces, breakdown, disk) =
+        run_blocking(move || {
+            let namespaces = state.storage.namespaces().unwrap_or_default();
+            let (indexed, query_cache) = state
+                .search
+                .lock()
+                .map(|s| (s.indexed_count(), s.query_cache_stats()))
+                .unwrap_or_default();
+            let breakdown = state
+                .storage
+                .breakdown(scope.as_deref(), STATS_TOP_TAGS, include_archived)
+                .unwrap_or_default();
+            let archived = state.storage.count_archived(scope.as_deref()).unwrap_or(0);
+            let disk = state.config.disk_usage();
+            let (total, cold, indexed, namespaces) = match scope {
+                Some(ns) => {
+                    let counts = namespaces.iter().find(|c| c.namespace == ns);
+                    (
+                        counts.map_or(0, |c| c.count),
+                        counts.map_or(0, |c| c.cold),
+                        indexed,
+                        Vec::new(),
+                    )
+                }
+                None => (
+                    state.storage.count().unwrap_or(0),
+                    state.storage.count_cold().unwrap_or(0),
+                    indexed,
+                    namespaces,
+                ),
+            };
+            // Archived memories count only when asked for
+            let total = if include_archived {
+                total
+            } else {
+                total.saturating_sub(archived)
+            };
+            Ok((
+                total,
+                cold,
+                archived,
+                indexed,
+                query_cache,
+                namespaces,
+                breakdown,
+                disk,
+            ))
+        })
+        .await
+        .unwrap_or_default();
+
+    Json(ApiResponse::ok(StatsResponse {
+        total_memories: total,
+        cold_memories: cold,
+        archived_memories: archived,
+        indexed_count: indexed,
+        has_embedder,
+        search_mode: if has_embedder {
+            "hybrid".to_string()
+        } else {
             "keyword-only".to_string()
         },
+        namespace,
+        namespaces,
+        cache,
+        query_cache,
+        breakdown,
+        disk,
+    }))
+}
+
+/// Namespaces by how many memories they hold
+async fn api_namespaces(
+    State(state): State<SharedState>,
+) -> Json<ApiResponse<Vec<NamespaceCount>>> {
+    let result = run_blocking(move || state.storage.namespaces().map_err(|e| format!("{e}"))).await;
+    match result {
+        Ok(counts) => Json(ApiResponse::ok(counts)),
+        Err(e) => Json(ApiResponse::err(e)),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct NamespaceParams {
+    #[serde(default = "default_concept_limit")]
+    pub limit: usize,
+}
+
+/// Memories of a namespace, newest first
+async fn api_namespace_memories(
+    State(state): State<SharedState>,
+    Path(namespace): Path<String>,
+    Query(params): Query<NamespaceParams>,
+) -> Json<ApiResponse<Vec<Memory>>> {
+    let result = run_blocking(move || {
+        state
+            .storage
+            .in_namespace(&namespace, params.limit)
+            .map_err(|e| format!("{e}"))
+    })
+    .await;
+    match result {
+        Ok(mut memories) => {
+            for memory in &mut memories {
+                memory.embedding = None;
+            }
+            Json(ApiResponse::ok(memories))
+        }
+        Err(e) => Json(ApiResponse::err(e)),
+    }
+}
+
+async fn api_namespace_store(
+    State(state): State<SharedState>,
+    Path(namespace): Path<String>,
+    Json(mut req): Json<StoreRequest>,
+) -> axum::response::Response {
+    req.namespace = Some(namespace);
+    api_store(State(state), Json(req)).await
+}
+
+async fn api_namespace_search(
+    State(state): State<SharedState>,
+    Path(namespace): Path<String>,
+    headers: HeaderMap,
+    Json(mut req): Json<SearchRequest>,
+) -> axum::response::Response {
+    req.namespace = Some(namespace);
+    api_search(State(state), headers, Json(req)).await
+}
+
+async fn api_namespace_stats(
+    State(state): State<SharedState>,
+    Path(namespace): Path<String>,
+    Query(params): Query<StatsParams>,
+) -> Json<ApiResponse<StatsResponse>> {
+    stats(state, Some(namespace), params.include_archived).await
+}
+
+#[derive(Deserialize, Default)]
+pub struct ExportRequest {
+    /// Subdirectory of `export.markdown_dir` to write to; the directory
+    /// itself when unset
+    #[serde(default)]
+    pub dir: Option<String>,
+}
+
+async fn api_export(
+    State(state): State<SharedState>,
+    body: Option<Json<ExportRequest>>,
+) -> (StatusCode, Json<ApiResponse<ExportSummary>>) {
+    let req = body.map(|Json(r)| r).unwrap_or_default();
+    let base = state.config.export.markdown_dir();
+    let Some(dir) = dir_within(&base, req.dir.as_deref().unwrap_or_default()) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::err(
+                "dir must be a relative path inside export.markdown_dir",
+            )),
+        );
+    };
+
+    let result = run_blocking(move || {
+        export::export_markdown(&state.storage, &dir).map_err(|e| format!("Export failed: {e}"))
+    })
+    .await;
+
+    match result {
+        Ok(summary) => (StatusCode::OK, Json(ApiResponse::ok(summary))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::err(e))),
+    }
+}
+
+/// `relative` joined onto `base`, or `None` when it is absolute or climbs
+/// out with `..`, so a request can only name paths under a directory the
+/// config set aside for it
+fn path_within(base: &std::path::Path, relative: &str) -> Option<std::path::PathBuf> {
+    use std::path::Component;
+    let relative = std::path::Path::new(relative);
+    relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        .then(|| base.join(relative))
+}
+
+/// [`path_within`] for a directory to write to: created if missing and
+/// resolved, so a symlink inside `base` cannot lead writes out of it
+fn dir_within(base: &std::path::Path, relative: &str) -> Option<std::path::PathBuf> {
+    let dir = path_within(base, relative)?;
+    std::fs::create_dir_all(base).ok()?;
+    let base = base.canonicalize().ok()?;
+    // New directories are created under the deepest existing one
+    let existing = dir
+        .ancestors()
+        .find(|path| path.symlink_metadata().is_ok())?;
+    if !existing.canonicalize().ok()?.starts_with(&base) {
+        return None;
+    }
+    std::fs::create_dir_all(&dir).ok()?;
+    let dir = dir.canonicalize().ok()?;
+    dir.starts_with(&base).then_some(dir)
+}
+
+async fn api_backup(
+    State(state): State<SharedState>,
+) -> (StatusCode, Json<ApiResponse<BackupInfo>>) {
+    let result = tokio::task::spawn_blocking(move || run_backup(&state)).await;
+    match result {
+        Ok(Ok(info)) => (StatusCode::CREATED, Json(ApiResponse::ok(info))),
+        Ok(Err(e)) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::err(format!("Backup failed: {e}"))),
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::err(format!("Backup task failed: {e}"))),
+        ),
+    }
+}
+
+async fn api_list_backups(
+    State(state): State<SharedState>,
+) -> (StatusCode, Json<ApiResponse<Vec<BackupInfo>>>) {
+    match backup::list_backups(&state.config) {
+        Ok(list) => (StatusCode::OK, Json(ApiResponse::ok(list))),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::err(format!("{e}"))),
+        ),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DiffParams {
+    /// Older backup, by name or path
+    pub from: String,
+    /// Newer backup, by name or path
+    pub to: String,
+}
+
+async fn api_diff_backups(
+    State(state): State<SharedState>,
+    Query(params): Query<DiffParams>,
+) -> (StatusCode, Json<ApiResponse<SnapshotDiff>>) {
+    let result = run_blocking(move || {
+        let from =
+            backup::resolve_backup(&state.config, &params.from).map_err(|e| e.to_string())?;
+        let to = backup::resolve_backup(&state.config, &params.to).map_err(|e| e.to_string())?;
+        snapshot::diff_snapshots(from, to).map_err(|e| format!("Diff failed: {e}"))
+    })
+    .await;
+    match result {
+        Ok(diff) => (StatusCode::OK, Json(ApiResponse::ok(diff))),
+        Err(e) => (StatusCode::BAD_REQUEST, Json(ApiResponse::err(e))),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct QuarantineParams {
+    #[serde(default = "default_concept_limit")]
+    pub limit: usize,
+}
+
+/// Memories held back by `[quarantine]`, most recent first. Approve one
+/// with `POST /api/v1/admin/quarantine/{id}/approve`; reject it by
+/// deleting the memory.
+async fn api_quarantined(
+    State(state): State<SharedState>,
+    Query(params): Query<QuarantineParams>,
+) -> (StatusCode, Json<ApiResponse<Vec<QuarantinedMemory>>>) {
+    let result = run_blocking(move || {
+        state
+            .storage
+            .quarantined(params.limit)
+            .map_err(|e| format!("{e}"))
+    })
+    .await;
+    match result {
+        Ok(mut quarantined) => {
+            for q in &mut quarantined {
+                q.memory.embedding = None;
+            }
+            (StatusCode::OK, Json(ApiResponse::ok(quarantined)))
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::err(e))),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct AnalyticsParams {
+    /// Memories per ranking
+    #[serde(default = "default_analytics_limit")]
+    pub limit: usize,
+    /// Days of daily accesses and search hits
+    #[serde(default = "default_analytics_days")]
+    pub days: u32,
+    #[serde(default)]
+    pub namespace: Option<String>,
+}
+
+fn default_analytics_limit() -> usize {
+    DEFAULT_ANALYTICS_LIMIT
+}
+
+fn default_analytics_days() -> u32 {
+    DEFAULT_ANALYTICS_DAYS
+}
+
+/// Most and least accessed memories, those never retrieved, daily
+/// accesses and the average recency of search hits
+async fn api_analytics(
+    State(state): State<SharedState>,
+    Query(params): Query<AnalyticsParams>,
+) -> (StatusCode, Json<ApiResponse<AccessAnalytics>>) {
+    let result = run_blocking(move || {
+        access_analytics(
+            &state.storage,
+            params.namespace.as_deref(),
+            params.limit,
+            params.days,
+            state.config.storage.access_half_life_days,
+            state.config.display.timezone,
+        )
+        .map_err(|e| format!("{e}"))
+    })
+    .await;
+    match result {
+        Ok(report) => (StatusCode::OK, Json(ApiResponse::ok(report))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::err(e))),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct MissesParams {
+    #[serde(default = "default_concept_limit")]
+    pub limit: usize,
+    /// Only misses of searches scoped to this namespace
+    #[serde(default)]
+    pub namespace: Option<String>,
+}
+
+/// Searches that found nothing (`[misses]`), most frequent first: where
+/// the store has gaps
+async fn api_missed_queries(
+    State(state): State<SharedState>,
+    Query(params): Query<MissesParams>,
+) -> (StatusCode, Json<ApiResponse<Vec<MissedQuery>>>) {
+    let result = run_blocking(move || {
+        state
+            .storage
+            .missed_queries(params.limit, params.namespace.as_deref())
+            .map_err(|e| format!("{e}"))
+    })
+    .await;
+    match result {
+        Ok(misses) => (StatusCode::OK, Json(ApiResponse::ok(misses))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::err(e))),
+    }
+}
+
+/// Release a quarantined memory into search and retrieval. It was indexed
+/// on store, so nothing is reindexed.
+async fn api_approve_quarantined(
+    State(state): State<SharedState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let actor = client_actor(&headers);
+    let result = run_blocking(move || {
+        let approved = state.storage.approve(&id).map_err(|e| format!("{e}"))?;
+        if approved {
+            tracing::info!(id = %id, actor = %actor, "Approved quarantined memory");
+        }
+        Ok(approved)
+    })
+    .await;
+
+    match result {
+        Ok(true) => Json(ApiResponse::ok("approved")).into_response(),
+        Ok(false) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::err("No quarantined memory with this ID")),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err(e)),
+        )
+            .into_response(),
+    }
+}
+
+fn run_backup(state: &AppState) -> anyhow::Result<BackupInfo> {
+    Ok(backup::create_backup(&state.storage, &state.config)?)
+}
+
+/// Spawn the periodic backup task when `backup.interval_hours` is non-zero.
+pub fn spawn_backup_scheduler(state: SharedState) -> Option<tokio::task::JoinHandle<()>> {
+    let hours = state.config.backup.interval_hours;
+    if hours == 0 {
+        return None;
+    }
+
+    tracing::info!(interval_hours = hours, "Backup scheduler enabled");
+    Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(hours * 3600));
+        // The first tick completes immediately; wait a full interval before the first backup
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let state = Arc::clone(&state);
+            match tokio::task::spawn_blocking(move || run_backup(&state)).await {
+                Ok(Ok(info)) => tracing::info!(name = %info.name, "Scheduled backup created"),
+                Ok(Err(e)) => tracing::warn!("Scheduled backup failed: {e}"),
+                Err(e) => tracing::warn!("Scheduled backup task failed: {e}"),
+            }
+        }
+    }))
+}
+
+/// Consolidate related memories with the state's summarizer, then move the
+/// search indexes from the archived originals to the consolidated memories
+pub fn run_consolidation(state: &AppState) -> anyhow::Result<ConsolidationReport> {
+    let report = consolidate::consolidate(
+        &state.storage,
+        &state.config.consolidation,
+        state.summarizer.as_ref(),
+        |text| state.embedder.as_ref().and_then(|e| e.embed(text).ok()),
+        state.embedder.as_ref().map(|e| e.model_id()),
+    )?;
+
+    let mut search = state
+        .search
+        .lock()
+        .map_err(|e| anyhow::anyhow!("Lock: {e}"))?;
+    for group in &report.groups {
+        for id in &group.sources {
+            search.remove_memory(id)?;
+        }
+        if let Some(memory) = state.storage.get(&group.id)? {
+            search.index_memory(&memory)?;
+        }
+    }
+    Ok(report)
+}
+
+/// Archive or delete expired memories (`[expiry]`) and drop them from the
+/// search indexes. Returns the IDs removed.
+pub fn run_expiry_sweep(state: &AppState) -> anyhow::Result<Vec<String>> {
+    let removed = expiry::sweep(&state.storage, &state.config.expiry)?;
+    if !removed.is_empty() {
+        let mut search = state
+            .search
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock: {e}"))?;
+        for id in &removed {
+            search.remove_memory(id)?;
+        }
+    }
+    Ok(removed)
+}
+
+/// Spawn the periodic expiry sweep when `expiry.sweep_interval_minutes`
+/// is non-zero. Startup sweeps before the indexes are loaded.
+pub fn spawn_expiry_sweeper(state: SharedState) -> Option<tokio::task::JoinHandle<()>> {
+    let minutes = state.config.expiry.sweep_interval_minutes;
+    if minutes == 0 {
+        return None;
+    }
+
+    Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(minutes * 60));
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let state = Arc::clone(&state);
+            match tokio::task::spawn_blocking(move || run_expiry_sweep(&state)).await {
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => tracing::warn!("Expiry sweep failed: {e}"),
+                Err(e) => tracing::warn!("Expiry sweep task failed: {e}"),
+            }
+        }
+    }))
+}
+
+/// Apply the forgetting policy (`[forgetting]`), dropping removed memories
+/// from the search indexes; with `dry_run` only report what it would remove
+pub fn run_forgetting(state: &AppState, dry_run: bool) -> anyhow::Result<ForgettingReport> {
+    let report = forgetting::forget(&state.storage, &state.config.forgetting, dry_run)?;
+    if !dry_run && !report.memories.is_empty() {
+        let mut search = state
+            .search
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock: {e}"))?;
+        for memory in &report.memories {
+            search.remove_memory(&memory.id)?;
+        }
+    }
+    Ok(report)
+}
+
+/// Spawn the periodic forgetting task when `forgetting.interval_hours` is
+/// non-zero and a rule is configured
+pub fn spawn_forgetting_scheduler(state: SharedState) -> Option<tokio::task::JoinHandle<()>> {
+    let hours = state.config.forgetting.interval_hours;
+    if hours == 0 || state.config.forgetting.rules.is_empty() {
+        return None;
+    }
+
+    tracing::info!(interval_hours = hours, "Forgetting scheduler enabled");
+    Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(hours * 3600));
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let state = Arc::clone(&state);
+            match tokio::task::spawn_blocking(move || run_forgetting(&state, false)).await {
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => tracing::warn!("Scheduled forgetting failed: {e}"),
+                Err(e) => tracing::warn!("Scheduled forgetting task failed: {e}"),
+            }
+        }
     }))
 }
+
+/// What the forgetting policy would remove now, without removing anything
+async fn api_forgetting_preview(
+    State(state): State<SharedState>,
+) -> (StatusCode, Json<ApiResponse<ForgettingReport>>) {
+    forgetting_response(state, true).await
+}
+
+/// Apply the forgetting policy now
+async fn api_forget(
+    State(state): State<SharedState>,
+) -> (StatusCode, Json<ApiResponse<ForgettingReport>>) {
+    forgetting_response(state, false).await
+}
+
+async fn forgetting_response(
+    state: SharedState,
+    dry_run: bool,
+) -> (StatusCode, Json<ApiResponse<ForgettingReport>>) {
+    match run_blocking(move || run_forgetting(&state, dry_run).map_err(|e| format!("{e}"))).await {
+        Ok(report) => (StatusCode::OK, Json(ApiResponse::ok(report))),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::err(format!("Forgetting failed: {e}"))),
+        ),
+    }
+}
+
+/// Spawn the periodic consolidation task when
+/// `consolidation.interval_hours` is non-zero.
+pub fn spawn_consolidation_scheduler(state: SharedState) -> Option<tokio::task::JoinHandle<()>> {
+    let hours = state.config.consolidation.interval_hours;
+    if hours == 0 {
+        return None;
+    }
+
+    tracing::info!(interval_hours = hours, "Consolidation scheduler enabled");
+    Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(hours * 3600));
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let state = Arc::clone(&state);
+            match tokio::task::spawn_blocking(move || run_consolidation(&state)).await {
+                Ok(Ok(report)) => tracing::info!(
+                    groups = report.groups.len(),
+                    archived = report.archived,
+                    "Scheduled consolidation finished"
+                ),
+                Ok(Err(e)) => tracing::warn!("Scheduled consolidation failed: {e}"),
+                Err(e) => tracing::warn!("Scheduled consolidation task failed: {e}"),
+            }
+        }
+    }))
+}
+
+async fn api_consolidate(
+    State(state): State<SharedState>,
+) -> (StatusCode, Json<ApiResponse<ConsolidationReport>>) {
+    match run_blocking(move || run_consolidation(&state).map_err(|e| format!("{e}"))).await {
+        Ok(report) => (StatusCode::OK, Json(ApiResponse::ok(report))),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::err(format!("Consolidation failed: {e}"))),
+        ),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ImportRequest {
+    /// Export format, one of [`oc_import::FORMATS`]
+    pub format: String,
+    /// Export file or directory, relative to `import.dir`
+    pub path: String,
+    /// Namespace of the imported memories; the default one when unset
+    #[serde(default)]
+    pub namespace: Option<String>,
+}
+
+/// Import the `importer` export at `path`, embedding with the loaded model,
+/// and index what was stored
+pub fn run_import(
+    state: &AppState,
+    importer: &dyn Importer,
+    path: &std::path::Path,
+    namespace: Option<String>,
+) -> anyhow::Result<ImportReport> {
+    let options = ImportOptions {
+        namespace,
+        quarantine: state.quarantine.clone(),
+        ..Default::default()
+    };
+    let report = oc_import::import(
+        &state.storage,
+        importer,
+        path,
+        &options,
+        state.embedder.as_deref(),
+    )?;
+
+    let imported = state.storage.get_many(&report.imported)?;
+    let mut search = state
+        .search
+        .lock()
+        .map_err(|e| anyhow::anyhow!("Lock: {e}"))?;
+    for memory in &imported {
+        search.index_memory(memory)?;
+    }
+    Ok(report)
+}
+
+async fn api_import(
+    State(state): State<SharedState>,
+    Json(req): Json<ImportRequest>,
+) -> (StatusCode, Json<ApiResponse<ImportReport>>) {
+    let Some(importer) = oc_import::importer(&req.format) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::err(format!(
+                "Unknown import format '{}': use {}",
+                req.format,
+                oc_import::FORMATS.join(" or ")
+            ))),
+        );
+    };
+    // Symlinks are resolved, so none leads out of the import directory
+    let base = state.config.import.dir();
+    let path = path_within(&base, &req.path)
+        .and_then(|path| path.canonicalize().ok())
+        .filter(|path| base.canonicalize().is_ok_and(|base| path.starts_with(base)));
+    let Some(path) = path else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::err(format!(
+                "No such file or directory in import.dir: {}",
+                req.path
+            ))),
+        );
+    };
+
+    let result = run_blocking(move || {
+        run_import(&state, importer.as_ref(), &path, req.namespace).map_err(|e| format!("{e}"))
+    })
+    .await;
+    match result {
+        Ok(report) => (StatusCode::OK, Json(ApiResponse::ok(report))),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::err(format!("Import failed: {e}"))),
+        ),
+    }
+}
+
+#[derive(Deserialize, Default)]
+pub struct TuneRequest {
+    #[serde(flatten)]
+    pub update: ScorerUpdate,
+    /// Also write the weights to the config file
+    #[serde(default)]
+    pub persist: bool,
+}
+
+async fn api_get_tune(State(state): State<SharedState>) -> (StatusCode, Json<ApiResponse<Scorer>>) {
+    match state.search.lock() {
+        Ok(search) => (
+            StatusCode::OK,
+            Json(ApiResponse::ok(search.scorer().clone())),
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::err(format!("Lock: {e}"))),
+        ),
+    }
+}
+
+async fn api_tune(
+    State(state): State<SharedState>,
+    Json(req): Json<TuneRequest>,
+) -> (StatusCode, Json<ApiResponse<Scorer>>) {
+    let tuned = {
+        let mut search = match state.search.lock() {
+            Ok(s) => s,
+            Err(e) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::err(format!("Lock: {e}"))),
+                );
+            }
+        };
+        let tuned = search.scorer().with_update(&req.update);
+        if let Err(e) = tuned.validate() {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::err(format!("Invalid weights: {e}"))),
+            );
+        }
+        search.set_scorer(tuned.clone());
+        tuned
+    };
+    tracing::info!(?tuned, "Scorer weights updated");
+
+    if req.persist
+        && let Err(e) = persist_scorer(&state.config, &tuned)
+    {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::err(format!(
+                "Weights applied but not saved: {e}"
+            ))),
+        );
+    }
+
+    (StatusCode::OK, Json(ApiResponse::ok(tuned)))
+}
+
+/// Write the scorer weights into the config file the server was started with.
+fn persist_scorer(config: &Config, scorer: &Scorer) -> anyhow::Result<()> {
+    let path = config
+        .source_path
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("server was not started from a config file"))?;
+    let mut updated = config.clone();
+    scorer.write_to(&mut updated.search);
+    updated.save(path)?;
+    Ok(())
+}
+
+async fn api_experiment(
+    State(state): State<SharedState>,
+) -> (StatusCode, Json<ApiResponse<ExperimentReport>>) {
+    let report = match state.search.lock() {
+        Ok(search) => search.experiment_report(),
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::err(format!("Lock: {e}"))),
+            );
+        }
+    };
+    match report {
+        Some(report) => (StatusCode::OK, Json(ApiResponse::ok(report))),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::err("No experiment configured")),
+        ),
+    }
+}
+
+async fn api_verify(
+    State(state): State<SharedState>,
+) -> (StatusCode, Json<ApiResponse<IntegrityReport>>) {
+    let result = run_blocking(move || {
+        state
+            .storage
+            .verify_integrity()
+            .map_err(|e| format!("Verify failed: {e}"))
+    })
+    .await;
+
+    match result {
+        Ok(report) => (StatusCode::OK, Json(ApiResponse::ok(report))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::err(e))),
+    }
+}
+
+/// Name a REST client gave itself in the `X-Client-Name` header
+fn client_name(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(CLIENT_NAME_HEADER)
+        .and_then(|v| v.to_str().ok())
+}
+
+/// Audit actor of a REST request; clients may name themselves
+fn client_actor(headers: &HeaderMap) -> String {
+    match client_name(headers) {
+        Some(name) => format!("rest:{name}"),
+        None => "rest".to_string(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RetagRequest {
+    /// Memories to edit; must narrow the selection. A [`MemoryFilter`]
+    /// whose date bounds may be local dates in the `[display]` time zone.
+    pub filter: serde_json::Value,
+    #[serde(flatten)]
+    pub edit: TagEdit,
+}
+
+async fn api_retag(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Json(req): Json<RetagRequest>,
+) -> (StatusCode, Json<ApiResponse<RetagReport>>) {
+    let filter = match MemoryFilter::from_json(&req.filter, state.config.display.timezone) {
+        Ok(filter) => filter,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::err(format!("Invalid filter: {e}"))),
+            );
+        }
+    };
+    if filter.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::err(
+                "Filter must set query, memory_type or a date bound",
+            )),
+        );
+    }
+    if req.edit.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::err("No tags to add or remove")),
+        );
+    }
+
+    let actor = client_actor(&headers);
+    let result = run_blocking(move || {
+        let report = state
+            .storage
+            .retag(&filter, &req.edit, Some(&actor))
+            .map_err(|e| format!("Retag failed: {e}"))?;
+        if let Ok(mut search) = state.search.lock()
+            && let Err(e) = search.reindex_text(&report.changed)
+        {
+            tracing::warn!("Failed to reindex retagged memories: {e}");
+        }
+        Ok(report)
+    })
+    .await;
+
+    match result {
+        Ok(report) => (StatusCode::OK, Json(ApiResponse::ok(report))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::err(e))),
+    }
+}
+
+#[derive(Deserialize, Default)]
+pub struct ReembedRequest {
+    /// Re-embed every memory, not only those from another model or dimension
+    #[serde(default)]
+    pub all: bool,
+    pub batch_size: Option<usize>,
+}
+
+async fn api_reembed(
+    State(state): State<SharedState>,
+    Json(req): Json<ReembedRequest>,
+) -> (StatusCode, Json<ApiResponse<ReembedReport>>) {
+    if state.embedder.is_none() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ApiResponse::err("Embedding engine not available")),
+        );
+    }
+
+    let result = run_blocking(move || reembed_and_reindex(&state, &req)).await;
+    match result {
+        Ok(report) => (StatusCode::OK, Json(ApiResponse::ok(report))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::err(e))),
+    }
+}
+
+/// Re-embed with the loaded model, then rebuild and persist the vector index
+fn reembed_and_reindex(state: &AppState, req: &ReembedRequest) -> Result<ReembedReport, String> {
+    let engine = state
+        .embedder
+        .as_ref()
+        .ok_or("Embedding engine not available")?;
+    let batch_size = req.batch_size.unwrap_or(DEFAULT_REEMBED_BATCH_SIZE);
+    let report = engine
+        .reembed(&state.storage, req.all, batch_size)
+        .map_err(|e| format!("Re-embed failed: {e}"))?;
+
+    let mut search = state.search.lock().map_err(|e| format!("Lock: {e}"))?;
+    let indexed = search
+        .rebuild_vector_index(report.dimensions)
+        .map_err(|e| format!("Vector index rebuild failed: {e}"))?;
+    if let Err(e) = search.save_vector_index(state.config.vector_index_path()) {
+        tracing::warn!("Failed to save vector index: {e}");
+    }
+    tracing::info!(
+        reembedded = report.reembedded,
+        failed = report.failed,
+        indexed,
+        "Re-embedding finished"
+    );
+    Ok(report)
+}
@@ -1,51 +1,121 @@
 use anyhow::Result;
 use oc_core::Config;
-use oc_embeddings::EmbeddingEngine;
+use oc_core::config::ApiKeyScope;
+use oc_core::consolidate::ExtractiveSummarizer;
+use oc_core::models::{Memory, MemoryFilter, TagEdit};
+use oc_core::quarantine::QuarantinePolicy;
+use oc_core::reembed::DEFAULT_REEMBED_BATCH_SIZE;
+use oc_core::{backup, export, snapshot};
+use oc_embeddings::Embedder;
+use oc_import::ImportOptions;
+use oc_observer::hook;
+use oc_observer::{spawn_git_ingestion, spawn_ingestion};
 use oc_search::bm25::Bm25Index;
+use oc_search::experiment::Experiment;
 use oc_search::hybrid::HybridSearch;
+use oc_search::keywords::KeywordExtractor;
+use oc_search::profile::Profiles;
+use oc_search::query_cache::QueryCache;
 use oc_search::scoring::Scorer;
+use oc_search::selftest::run_self_test;
+use oc_search::shards::VectorShards;
+use oc_search::stopwords;
+use oc_search::synonyms::Synonyms;
 use oc_search::vector::VectorIndex;
-use oc_server::{AppState, SharedState, build_router};
+use oc_server::events::{EventBus, spawn_event_feed};
+use oc_server::sync::spawn_sync_scheduler;
+use oc_server::{
+    AppState, CLIENT_NAME_HEADER, SharedState, build_router, spawn_backup_scheduler,
+    spawn_consolidation_scheduler, spawn_expiry_sweeper, spawn_forgetting_scheduler,
+};
+use std::io::Read;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Time `oc-memory-server hook` gives a running server to accept a
+/// connection before it stores directly
+const HOOK_CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+/// Time it gives the server to embed and store one memory
+const HOOK_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
 
 fn init_app(config: &Config) -> Result<AppState> {
-    let db_path = shellexpand(&config.storage.data_dir);
-    std::fs::create_dir_all(&db_path)?;
-    let db_file = format!("{}/memories.db", db_path);
-    let storage = oc_core::Storage::open(&db_file)?;
+    std::fs::create_dir_all(config.data_dir())?;
+    let storage = Arc::new(oc_core::Storage::open_with_config(
+        config.db_path(),
+        &config.storage,
+    )?);
 
-    let tantivy_path = format!("{}/tantivy", db_path);
-    std::fs::create_dir_all(&tantivy_path)?;
+    let compressed = storage.compress_cold(config.storage.compress_after_days)?;
+    if compressed > 0 {
+        tracing::info!("Compressed content of {compressed} cold memories");
+    }
+    let tiers = oc_core::tiering::enforce_tiers(&storage, &config.storage)?;
+    if tiers.expired + tiers.evicted > 0 {
+        tracing::info!(
+            expired = tiers.expired,
+            evicted = tiers.evicted,
+            hot = tiers.hot,
+            "Moved memories to the cold tier"
+        );
+    }
+    oc_core::expiry::sweep(&storage, &config.expiry)?;
 
-    // We need a separate storage instance for HybridSearch since it expects Arc<Storage>
-    let search_storage = Arc::new(oc_core::Storage::open(&db_file)?);
+    let tantivy_path = config.tantivy_dir();
+    std::fs::create_dir_all(&tantivy_path)?;
 
     let vector_index = VectorIndex::new(config.embedding.dimensions);
-    let bm25_index = Bm25Index::new(&tantivy_path)?;
-    let scorer = Scorer::default();
-    let mut search = HybridSearch::new(search_storage.clone(), vector_index, bm25_index, scorer);
+    let synonyms = Synonyms::load(config.synonyms_path())?;
+    if !synonyms.is_empty() {
+        tracing::info!("Loaded {} synonym groups", synonyms.len());
+    }
+    let bm25_index = Bm25Index::new_with_tokenizer(&tantivy_path, config.search.tokenizer)?
+        .with_synonyms(synonyms)
+        .with_stopwords(&stopwords::from_config(&config.search))?;
+    let scorer = Scorer::from(&config.search);
+    let mut search = HybridSearch::new(Arc::clone(&storage), vector_index, bm25_index, scorer);
+    let profiles = Profiles::from_config(&config.search)?;
+    if !profiles.is_empty() {
+        tracing::info!("Scoring profiles: {}", profiles.names().join(", "));
+    }
+    search.set_profiles(profiles);
+    search.set_query_cache(QueryCache::from_config(&config.search));
+    search.set_vector_shards(VectorShards::from_config(config));
+    if let Some(experiment) = &config.search.experiment {
+        search.set_experiment(Some(
+            Experiment::from_config(experiment)?.persisted_in(Arc::clone(&storage))?,
+        ));
+        tracing::info!(
+            b_ratio = experiment.b_ratio,
+            "A/B ranking experiment active"
+        );
+    }
 
-    // Load existing embeddings into vector index
-    let embeddings = storage.all_embeddings()?;
-    for (id, embedding) in &embeddings {
-        let _ = search
-            .vector_index_mut()
-            .upsert(id.clone(), embedding.clone());
+    // Reuse the persisted vector index when current, otherwise rebuild it
+    let vector_path = config.vector_index_path();
+    if search.load_vector_index(&vector_path)? {
+        tracing::info!(
+            "Loaded vector index with {} vectors",
+            search.indexed_count()
+        );
+    } else {
+        search.index_stored_vectors()?;
+        if let Err(e) = search.save_vector_index(&vector_path) {
+            tracing::warn!("Failed to save vector index: {e}");
+        }
     }
 
     // Rebuild BM25 index from existing memories
     let text_data = storage.all_text_data()?;
-    let bm25_count = text_data.len();
-    for (id, title, content) in &text_data {
-        let _ = search.index_memory_text(id, title, content);
-    }
+    let bm25_count = search.rebuild_text_index(&text_data)?;
     if bm25_count > 0 {
         tracing::info!("Rebuilt BM25 index with {bm25_count} memories");
     }
 
+    let search = Mutex::new(search);
     let embedder = match init_embedder(config) {
         Ok(engine) => {
             tracing::info!("Embedding engine loaded");
+            warn_stale_embeddings(&storage, engine.as_ref());
             Some(engine)
         }
         Err(e) => {
@@ -54,25 +124,41 @@ fn init_app(config: &Config) -> Result<AppState> {
         }
     };
 
+    let self_test = config.self_test.enabled.then(|| {
+        let report = run_self_test(&storage, &search, embedder.as_deref());
+        report.log();
+        report
+    });
+
     Ok(AppState {
-        storage: Mutex::new(storage),
-        search: Mutex::new(search),
+        storage,
+        search,
         embedder,
+        config: config.clone(),
+        self_test,
+        summarizer: Arc::new(ExtractiveSummarizer::new(config.consolidation.target_type)),
+        keywords: config.keywords.enabled.then(KeywordExtractor::new),
+        quarantine: QuarantinePolicy::from_config(&config.quarantine),
+        events: EventBus::new(config.server.events.buffer),
+        readiness: Default::default(),
     })
 }
 
-fn init_embedder(config: &Config) -> Result<Arc<EmbeddingEngine>> {
-    let model_path = shellexpand(&config.embedding.model_path);
-    let tokenizer_path = shellexpand(&config.embedding.tokenizer_path);
+fn init_embedder(config: &Config) -> Result<Arc<dyn Embedder>> {
+    Ok(oc_embeddings::create_engine(&config.embedding)?)
+}
 
-    let engine = EmbeddingEngine::new(
-        &model_path,
-        &tokenizer_path,
-        config.embedding.dimensions,
-        config.embedding.max_length,
-        config.embedding.num_threads,
-    )?;
-    Ok(Arc::new(engine))
+/// Point out embeddings the loaded model cannot be compared against
+fn warn_stale_embeddings(storage: &oc_core::Storage, engine: &dyn Embedder) {
+    match storage.count_stale_embeddings(engine.model_id(), engine.dimensions()) {
+        Ok(0) => {}
+        Ok(stale) => tracing::warn!(
+            "{stale} memories have no embedding from {} ({} dims); run `oc-memory-server reembed`",
+            engine.model_id(),
+            engine.dimensions()
+        ),
+        Err(e) => tracing::warn!("Failed to check embedding versions: {e}"),
+    }
 }
 
 #[tokio::main]
@@ -81,25 +167,523 @@ async fn main() -> Result<()> {
         .with_env_filter("oc_server=info,tower_http=debug")
         .init();
 
-    let config = Config::default();
+    let config = Config::load_default()?;
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("snapshot") => return run_snapshot(&config, args.get(1)),
+        Some("restore") => return run_restore(&config, args.get(1)),
+        Some("export") => return run_export(&config, args.get(1)),
+        Some("backup") => return run_backup(&config),
+        Some("diff") => return run_diff(&config, args.get(1), args.get(2)),
+        Some("verify") => return run_verify(&config),
+        Some("reembed") => return run_reembed(&config, args.get(1)),
+        Some("retag") => return run_retag(&config, &args[1..]),
+        Some("consolidate") => return run_consolidate(&config),
+        Some("import") => return run_import(&config, &args[1..]),
+        Some("hook") => return run_hook(&config, args.get(1)),
+        Some(other) => {
+            anyhow::bail!(
+                "Unknown command: {other} (expected snapshot|restore|export|backup|diff|verify|reembed|retag|consolidate|import|hook)"
+            )
+        }
+        None => {}
+    }
+
     let state: SharedState = Arc::new(init_app(&config)?);
 
     let addr = format!("{}:{}", config.server.host, config.server.port);
     tracing::info!("oc-memory REST server starting on {addr}");
+    if config.server.api_keys.is_empty() && !is_loopback(&config.server.host) {
+        tracing::warn!(
+            "Listening on {addr} without [[server.api_keys]]: anyone on the network can read and change memories"
+        );
+    }
 
-    let app = build_router(state).layer(tower_http::trace::TraceLayer::new_for_http());
+    spawn_backup_scheduler(Arc::clone(&state));
+    spawn_consolidation_scheduler(Arc::clone(&state));
+    spawn_expiry_sweeper(Arc::clone(&state));
+    spawn_forgetting_scheduler(Arc::clone(&state));
+    spawn_event_feed(Arc::clone(&state));
+    spawn_sync_scheduler(Arc::clone(&state));
+    spawn_ingestion(Arc::clone(&state));
+    spawn_git_ingestion(Arc::clone(&state));
+
+    let app = build_router(Arc::clone(&state)).layer(tower_http::trace::TraceLayer::new_for_http());
 
     let listener = tokio::net::TcpListener::bind(&addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
 
+    save_vector_index(&state, &config);
     Ok(())
 }
 
-fn shellexpand(path: &str) -> String {
-    if path.starts_with("~/")
-        && let Some(home) = std::env::var_os("HOME")
+/// Whether `host` only accepts connections from this machine
+fn is_loopback(host: &str) -> bool {
+    host == "localhost"
+        || host
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Persist the vector index on shutdown so the next start can skip the rebuild
+fn save_vector_index(state: &AppState, config: &Config) {
+    let result = match state.search.lock() {
+        Ok(search) => search.save_vector_index(config.vector_index_path()),
+        Err(e) => Err(anyhow::anyhow!("Lock: {e}")),
+    };
+    match result {
+        Ok(()) => tracing::info!("Vector index saved"),
+        Err(e) => tracing::warn!("Failed to save vector index: {e}"),
+    }
+}
+
+async fn shutdown_signal() {
+    let ctrl_c = tokio::signal::ctrl_c();
+    #[cfg(unix)]
     {
-        return path.replacen("~", &home.to_string_lossy(), 1);
+        let mut terminate =
+            match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+                Ok(signal) => signal,
+                Err(e) => {
+                    tracing::warn!("Cannot listen for SIGTERM: {e}");
+                    let _ = ctrl_c.await;
+                    return;
+                }
+            };
+        tokio::select! {
+            _ = ctrl_c => {}
+            _ = terminate.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = ctrl_c.await;
+    tracing::info!("Shutting down");
+}
+
+/// `oc-memory-server snapshot <archive.tar.gz>`
+fn run_snapshot(config: &Config, path: Option<&String>) -> Result<()> {
+    let path = path.ok_or_else(|| anyhow::anyhow!("Usage: oc-memory-server snapshot <path>"))?;
+    let storage = oc_core::Storage::open_with_config(config.db_path(), &config.storage)?;
+    let manifest = snapshot::create_snapshot(&storage, config, path)?;
+    println!(
+        "Snapshot written to {path} ({} memories, {} dims)",
+        manifest.memory_count, manifest.dimensions
+    );
+    Ok(())
+}
+
+/// `oc-memory-server restore <archive.tar.gz | backup name>` — run with the server stopped
+fn run_restore(config: &Config, path: Option<&String>) -> Result<()> {
+    let path = path.ok_or_else(|| anyhow::anyhow!("Usage: oc-memory-server restore <path>"))?;
+    let manifest = backup::restore_backup(config, path)?;
+    println!(
+        "Restored {} memories from {path} (created {})",
+        manifest.memory_count,
+        config.display.timezone.format_minutes(manifest.created_at)
+    );
+    Ok(())
+}
+
+/// `oc-memory-server export [dir]` — Markdown/Obsidian vault export
+fn run_export(config: &Config, dir: Option<&String>) -> Result<()> {
+    let dir = dir
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(|| config.export.markdown_dir());
+    let storage = oc_core::Storage::open_with_config(config.db_path(), &config.storage)?;
+    let summary = export::export_markdown(&storage, &dir)?;
+    println!("Exported {} memories to {}", summary.exported, summary.dir);
+    Ok(())
+}
+
+/// `oc-memory-server backup` — timestamped archive in `backup.dir`
+fn run_backup(config: &Config) -> Result<()> {
+    let storage = oc_core::Storage::open_with_config(config.db_path(), &config.storage)?;
+    let info = backup::create_backup(&storage, config)?;
+    println!(
+        "Backup written to {} ({} bytes)",
+        info.path, info.size_bytes
+    );
+    Ok(())
+}
+
+/// `oc-memory-server diff <old> <new>` — memories added, removed and modified
+/// between two backups (names or archive paths)
+fn run_diff(config: &Config, old: Option<&String>, new: Option<&String>) -> Result<()> {
+    let (Some(old), Some(new)) = (old, new) else {
+        anyhow::bail!("Usage: oc-memory-server diff <old backup> <new backup>");
+    };
+    let diff = snapshot::diff_snapshots(
+        backup::resolve_backup(config, old)?,
+        backup::resolve_backup(config, new)?,
+    )?;
+    for (mark, entries) in [
+        ("+", &diff.added),
+        ("-", &diff.removed),
+        ("~", &diff.modified),
+    ] {
+        for entry in entries {
+            println!("{mark} {} {}", entry.id, entry.title);
+        }
+    }
+    println!(
+        "{} added, {} removed, {} modified, {} unchanged",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.modified.len(),
+        diff.unchanged
+    );
+    Ok(())
+}
+
+/// `oc-memory-server verify` — check content hashes and the audit hash chain
+fn run_verify(config: &Config) -> Result<()> {
+    let storage = oc_core::Storage::open_with_config(config.db_path(), &config.storage)?;
+    let report = storage.verify_integrity()?;
+    println!(
+        "Checked {} memories and {} audit entries (head {})",
+        report.memories_checked, report.audit_entries, report.head_hash
+    );
+    if report.unhashed > 0 {
+        println!(
+            "{} memories predate integrity hashing and were skipped",
+            report.unhashed
+        );
+    }
+    for id in &report.content_mismatches {
+        println!("MODIFIED  {id}");
+    }
+    for id in &report.unlogged {
+        println!("UNLOGGED  {id}");
+    }
+    if let Some(seq) = report.chain_broken_at {
+        println!("Audit chain broken at entry {seq}");
+    }
+    anyhow::ensure!(report.ok, "Integrity check failed");
+    println!("Integrity OK");
+    Ok(())
+}
+
+/// `oc-memory-server retag [--query TEXT] [--type TYPE] [--since DATE]
+/// [--until DATE] [--add a,b] [--remove c]` — edit the tags of every matching
+/// memory in one transaction. Dates are RFC 3339, or `YYYY-MM-DD` /
+/// `YYYY-MM-DD HH:MM` in the `[display]` time zone.
+fn run_retag(config: &Config, args: &[String]) -> Result<()> {
+    let mut filter = serde_json::Map::new();
+    let mut edit = TagEdit::default();
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("Missing value for {flag}"))?;
+        let list = || value.split(',').map(str::to_string).collect::<Vec<_>>();
+        match flag.as_str() {
+            "--query" => {
+                filter.insert("query".into(), value.as_str().into());
+            }
+            "--type" => {
+                filter.insert("memory_type".into(), value.as_str().into());
+            }
+            "--since" => {
+                filter.insert("created_after".into(), value.as_str().into());
+            }
+            "--until" => {
+                filter.insert("created_before".into(), value.as_str().into());
+            }
+            "--add" => edit.add.extend(list()),
+            "--remove" => edit.remove.extend(list()),
+            other => anyhow::bail!(
+                "Unknown retag option: {other} (expected --query|--type|--since|--until|--add|--remove)"
+            ),
+        }
+    }
+    let filter =
+        MemoryFilter::from_json(&serde_json::Value::Object(filter), config.display.timezone)
+            .map_err(|e| anyhow::anyhow!("Invalid retag filter: {e}"))?;
+    anyhow::ensure!(
+        !filter.is_empty(),
+        "Pass --query, --type, --since or --until to select memories"
+    );
+    anyhow::ensure!(!edit.is_empty(), "Pass --add or --remove");
+
+    let storage = oc_core::Storage::open_with_config(config.db_path(), &config.storage)?;
+    let report = storage.retag(&filter, &edit, Some("cli"))?;
+    println!(
+        "Retagged {} of {} matching memories",
+        report.changed.len(),
+        report.matched
+    );
+
+    // Refresh the keyword index; a running server holds its writer lock
+    // and picks the edits up on its next rebuild instead
+    let index = Bm25Index::new_with_tokenizer(config.tantivy_dir(), config.search.tokenizer)
+        .and_then(|index| index.with_stopwords(&stopwords::from_config(&config.search)));
+    match index {
+        Ok(index) => {
+            let entries: Vec<(String, String, String)> = storage
+                .get_many(&report.changed)?
+                .into_iter()
+                .map(|m| (m.id, m.title, m.content))
+                .collect();
+            index.add_batch(&entries)?;
+        }
+        Err(e) => tracing::warn!("Keyword index not refreshed: {e}"),
+    }
+    Ok(())
+}
+
+/// `oc-memory-server consolidate` — run one consolidation pass with the
+/// server stopped; the indexes are rebuilt on its next start
+fn run_consolidate(config: &Config) -> Result<()> {
+    let storage = oc_core::Storage::open_with_config(config.db_path(), &config.storage)?;
+    let embedder = match init_embedder(config) {
+        Ok(embedder) => Some(embedder),
+        Err(e) => {
+            tracing::warn!("Consolidated memories are stored without embeddings: {e}");
+            None
+        }
+    };
+    let report = oc_core::consolidate::consolidate(
+        &storage,
+        &config.consolidation,
+        &ExtractiveSummarizer::new(config.consolidation.target_type),
+        |text| embedder.as_ref().and_then(|e| e.embed(text).ok()),
+        embedder.as_ref().map(|e| e.model_id()),
+    )?;
+    for group in &report.groups {
+        println!(
+            "{}  {} ({} memories)",
+            group.id,
+            group.title,
+            group.sources.len()
+        );
+    }
+    println!(
+        "Consolidated {} of {} candidate memories into {} memories",
+        report.archived,
+        report.candidates,
+        report.groups.len()
+    );
+    Ok(())
+}
+
+/// `oc-memory-server import <mem0|notion> PATH [--namespace NS]` — import
+/// another tool's export with the server stopped; the indexes are rebuilt
+/// on its next start
+fn run_import(config: &Config, args: &[String]) -> Result<()> {
+    let (Some(format), Some(path)) = (args.first(), args.get(1)) else {
+        anyhow::bail!(
+            "Usage: oc-memory-server import <{}> PATH [--namespace NS]",
+            oc_import::FORMATS.join("|")
+        );
+    };
+    let importer = oc_import::importer(format).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Unknown import format: {format} (expected {})",
+            oc_import::FORMATS.join("|")
+        )
+    })?;
+    let mut options = ImportOptions {
+        quarantine: QuarantinePolicy::from_config(&config.quarantine),
+        ..Default::default()
+    };
+    let mut rest = args[2..].iter();
+    while let Some(flag) = rest.next() {
+        match flag.as_str() {
+            "--namespace" => {
+                options.namespace = Some(
+                    rest.next()
+                        .ok_or_else(|| anyhow::anyhow!("Missing value for {flag}"))?
+                        .clone(),
+                );
+            }
+            other => anyhow::bail!("Unknown import option: {other} (expected --namespace)"),
+        }
+    }
+
+    let storage = oc_core::Storage::open_with_config(config.db_path(), &config.storage)?;
+    let embedder = match init_embedder(config) {
+        Ok(embedder) => Some(embedder),
+        Err(e) => {
+            tracing::warn!("Imported memories are stored without embeddings: {e}");
+            None
+        }
+    };
+    let report = oc_import::import(
+        &storage,
+        importer.as_ref(),
+        std::path::Path::new(path),
+        &options,
+        embedder.as_deref(),
+    )?;
+    println!(
+        "Imported {} memories from {} ({} already stored, {} linked to a parent, {} quarantined)",
+        report.imported.len(),
+        report.format,
+        report.skipped,
+        report.linked,
+        report.quarantined
+    );
+    if report.unembedded > 0 {
+        println!(
+            "{} memories have no embedding; run `oc-memory-server reembed`",
+            report.unembedded
+        );
+    }
+    Ok(())
+}
+
+/// `oc-memory-server hook [--dry-run]` — read a coding agent's session hook
+/// JSON on stdin and store the decisions and bug fixes stated in it; with
+/// `--dry-run` only print them. They go to the REST server running on the
+/// configured host and port, which embeds, screens and indexes them at
+/// once; when none answers they are stored directly, and running servers
+/// index them on their next start.
+fn run_hook(config: &Config, flag: Option<&String>) -> Result<()> {
+    let dry_run = match flag.map(String::as_str) {
+        None => false,
+        Some("--dry-run") => true,
+        Some(other) => anyhow::bail!("Unknown hook option: {other} (expected --dry-run)"),
+    };
+    let mut json = String::new();
+    std::io::stdin().read_to_string(&mut json)?;
+    let input = hook::read_input(&json)?;
+    if dry_run {
+        for candidate in hook::candidates(&input.texts, &config.hook) {
+            println!("{}  {}", candidate.memory_type.as_str(), candidate.title);
+        }
+        return Ok(());
+    }
+
+    let storage = oc_core::Storage::open_with_config(config.db_path(), &config.storage)?;
+    let (memories, skipped) = hook::pending(&storage, &input, &config.hook)?;
+    if memories.is_empty() {
+        println!("Captured 0 memories ({skipped} already stored)");
+        return Ok(());
+    }
+    match post_hook_memories(config, &memories) {
+        Ok(stored) => {
+            println!(
+                "Captured {stored} memories through the running server ({skipped} already stored)"
+            );
+            return Ok(());
+        }
+        Err(e) => eprintln!(
+            "Warning: no oc-memory server took the memories ({e}); storing them directly, \
+             so a server started since only indexes them on its next start"
+        ),
+    }
+
+    let embedder = match init_embedder(config) {
+        Ok(embedder) => Some(embedder),
+        Err(e) => {
+            tracing::warn!("Captured memories are stored without embeddings: {e}");
+            None
+        }
+    };
+    let report = hook::capture(
+        &storage,
+        &input,
+        &config.hook,
+        |text| embedder.as_ref().and_then(|e| e.embed(text).ok()),
+        embedder.as_ref().map(|e| e.model_id()),
+        QuarantinePolicy::from_config(&config.quarantine).as_ref(),
+    )?;
+    println!(
+        "Captured {} memories ({} already stored, {} quarantined)",
+        report.stored.len(),
+        report.skipped,
+        report.quarantined
+    );
+    Ok(())
+}
+
+/// POST `memories` to `/api/v1/memories` of the server `config` describes,
+/// as client `hook` with its first read-write API key. Returns how many
+/// were stored; one the server rejects as a near-duplicate (409) counts as
+/// handled. Fails at the first memory it could not hand over; those before
+/// it keep their `hook:` source, so storing the rest directly skips them.
+fn post_hook_memories(config: &Config, memories: &[Memory]) -> Result<usize> {
+    let host = match config.server.host.as_str() {
+        "" | "0.0.0.0" => "127.0.0.1".to_string(),
+        "::" => "[::1]".to_string(),
+        host if host.contains(':') && !host.starts_with('[') => format!("[{host}]"),
+        host => host.to_string(),
+    };
+    let url = format!("http://{host}:{}/api/v1/memories", config.server.port);
+    let key = config
+        .server
+        .api_keys
+        .iter()
+        .filter(|k| k.scope == ApiKeyScope::ReadWrite)
+        .find_map(|k| k.secret());
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(HOOK_CONNECT_TIMEOUT)
+        .timeout(HOOK_REQUEST_TIMEOUT)
+        .build();
+
+    let mut stored = 0;
+    for memory in memories {
+        let body = serde_json::json!({
+            "content": memory.content,
+            "title": memory.title,
+            "memory_type": memory.metadata.memory_type.as_str(),
+            "tags": memory.metadata.tags,
+            "source": memory.metadata.source,
+        });
+        let mut request = agent
+            .post(&url)
+            .set(CLIENT_NAME_HEADER, "hook")
+            .set("Content-Type", "application/json");
+        if let Some(key) = &key {
+            request = request.set("Authorization", &format!("Bearer {key}"));
+        }
+        match request.send_string(&body.to_string()) {
+            Ok(_) => stored += 1,
+            Err(ureq::Error::Status(409, _)) => {}
+            Err(e) => anyhow::bail!("{url}: {e}"),
+        }
     }
-    path.to_string()
+    Ok(stored)
+}
+
+/// `oc-memory-server reembed [--all]` — regenerate embeddings from another
+/// model or dimension with the configured model and rebuild the vector index
+fn run_reembed(config: &Config, flag: Option<&String>) -> Result<()> {
+    let all = match flag.map(String::as_str) {
+        None => false,
+        Some("--all") => true,
+        Some(other) => anyhow::bail!("Unknown reembed option: {other} (expected --all)"),
+    };
+    let storage = oc_core::Storage::open_with_config(config.db_path(), &config.storage)?;
+    let engine = init_embedder(config)?;
+
+    let report = engine.reembed(&storage, all, DEFAULT_REEMBED_BATCH_SIZE)?;
+    println!(
+        "Re-embedded {} memories with {} ({} dims)",
+        report.reembedded, report.model, report.dimensions
+    );
+    if report.failed > 0 {
+        println!("{} memories could not be embedded", report.failed);
+    }
+
+    let mut index = VectorIndex::new(report.dimensions);
+    index.build_from(
+        oc_search::hybrid::stored_vectors(&storage)?
+            .into_iter()
+            .filter(|(_, embedding)| embedding.len() == report.dimensions)
+            .collect(),
+    )?;
+    index.save(config.vector_index_path(), &storage.data_version()?)?;
+    println!("Vector index rebuilt with {} vectors", index.len());
+
+    anyhow::ensure!(
+        report.remaining_stale == 0,
+        "{} memories still lack an embedding from {}",
+        report.remaining_stale,
+        report.model
+    );
+    Ok(())
 }
@@ -0,0 +1,1356 @@
+use axum::Json;
+use axum::http::header;
+use axum::response::{Html, IntoResponse};
+use serde_json::{Map, Value, json};
+
+use crate::CLIENT_NAME_HEADER;
+use crate::auth::API_KEY_HEADER;
+
+/// Path of the OpenAPI document
+pub const SPEC_PATH: &str = "/api/v1/openapi.json";
+/// Path of the Swagger UI page
+pub const DOCS_PATH: &str = "/api/v1/docs";
+
+/// Serve [`spec`] as JSON
+pub async fn serve_spec() -> impl IntoResponse {
+    ([(header::CACHE_CONTROL, "no-cache")], Json(spec()))
+}
+
+/// Serve a Swagger UI page for [`SPEC_PATH`]. The UI itself is loaded from
+/// the unpkg CDN, so the browser needs internet access.
+pub async fn serve_docs() -> Html<String> {
+    Html(format!(
+        r##"<!doctype html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>oc-memory REST API</title>
+<link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+<div id="swagger-ui"></div>
+<script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+<script>SwaggerUIBundle({{ url: "{SPEC_PATH}", dom_id: "#swagger-ui" }});</script>
+</body>
+</html>
+"##
+    ))
+}
+
+/// OpenAPI 3.1 description of the REST API: every route with its
+/// parameters, request body and the schema of `data` in the response
+/// envelope. Kept by hand next to the routes in [`crate::build_router`],
+/// like the MCP tool schemas, in one small builder per area so no single
+/// `json!` grows past the macro recursion limit; a test checks that every
+/// route is described.
+pub fn spec() -> Value {
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "oc-memory REST API",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Long-term memory for AI agents: hybrid (semantic + BM25) search over stored memories. Every /api/* response is an envelope `{ success, data?, error? }`; the schemas below describe `data`."
+        },
+        "servers": [{ "url": "/" }],
+        "security": [{ "bearer": [] }, { "apiKey": [] }],
+        "tags": [
+            { "name": "memories" },
+            { "name": "search" },
+            { "name": "namespaces" },
+            { "name": "concepts" },
+            { "name": "sync" },
+            { "name": "admin" }
+        ],
+        "paths": paths(),
+        "components": {
+            "securitySchemes": {
+                "bearer": { "type": "http", "scheme": "bearer", "description": "A key from [[server.api_keys]]" },
+                "apiKey": { "type": "apiKey", "in": "header", "name": API_KEY_HEADER }
+            },
+            "parameters": {
+                "id": path_param("id", "Memory ID"),
+                "namespace": path_param("namespace", "Namespace (project or agent)"),
+                "clientName": {
+                    "name": CLIENT_NAME_HEADER,
+                    "in": "header",
+                    "required": false,
+                    "description": "Name of the calling client, recorded in the audit log",
+                    "schema": { "type": "string" }
+                }
+            },
+            "schemas": schemas()
+        }
+    })
+}
+
+fn paths() -> Value {
+    merged([
+        health_paths(),
+        search_paths(),
+        event_paths(),
+        memory_paths(),
+        stats_paths(),
+        admin_paths(),
+    ])
+}
+
+/// Health probes
+fn health_paths() -> Value {
+    json!({
+        "/health": {
+            "get": {
+                "summary": "Liveness (same as /health/live)",
+                "security": [],
+                "responses": {
+                    "200": { "description": "`ok` as text" }
+                }
+            }
+        },
+        "/health/live": {
+            "get": {
+                "summary": "Liveness: the process answers; no component is checked",
+                "security": [],
+                "responses": {
+                    "200": { "description": "`ok` as text" }
+                }
+            }
+        },
+        "/health/ready": {
+            "get": {
+                "summary": "Readiness: SQLite, the text index reader, the vector index and the embedder (one test inference), with the startup self-test report when one ran; checked at most once every 5 seconds",
+                "security": [],
+                "responses": {
+                    "200": { "description": "Every component is healthy", "content": { "application/json": { "schema": schema("ReadinessReport") } } },
+                    "503": { "description": "Degraded", "content": { "application/json": { "schema": schema("ReadinessReport") } } }
+                }
+            }
+        }
+    })
+}
+
+/// Search and embedding
+fn search_paths() -> Value {
+    json!({
+        "/api/v1/search": {
+            "post": op("search", "Hybrid search", Some("SearchRequest"), &[], search_responses())
+        },
+        "/api/v1/search/batch": {
+            "post": op(
+                "search",
+                "Several searches at once, embedded in one batch and run in parallel",
+                Some("SearchBatchRequest"),
+                &[],
+                responses(&[
+                    ("200", "Results grouped by query, in request order", Some(array("SearchGroup"))),
+                    ("503", EMBEDDER_BUSY, None)
+                ])
+            )
+        },
+        "/api/v1/search/suggestions": {
+            "get": op(
+                "search",
+                "Did-you-mean corrections of a query whose keywords match nothing",
+                None,
+                &[
+                    query_param("query", "string", "The query to correct"),
+                    query_param("limit", "integer", "Maximum corrections (default [search] suggestions)")
+                ],
+                ok(strings())
+            )
+        },
+        "/api/v1/embed": {
+            "post": op(
+                "search",
+                "Embed arbitrary text with the loaded model",
+                Some("EmbedRequest"),
+                &[],
+                responses(&[
+                    ("200", "Success", Some(schema("EmbedResponse"))),
+                    ("400", "No texts, or more than 256", None),
+                    ("503", "No embedding engine loaded, or embedder busy (with Retry-After)", None)
+                ])
+            )
+        }
+    })
+}
+
+/// Change events and sync between instances
+fn event_paths() -> Value {
+    json!({
+        "/api/v1/events": {
+            "get": {
+                "tags": ["memories"],
+                "summary": "Stream created/updated/deleted memory events (server-sent events); Last-Event-ID replays missed ones",
+                "parameters": [{
+                    "name": "Last-Event-ID",
+                    "in": "header",
+                    "required": false,
+                    "description": "Audit sequence of the last event seen",
+                    "schema": { "type": "integer" }
+                }],
+                "responses": {
+                    "200": {
+                        "description": "Events named created, updated or deleted, with the audit sequence as ID",
+                        "content": { "text/event-stream": { "schema": schema("MemoryEvent") } }
+                    },
+                    "503": { "description": "Events disabled ([server.events] poll_interval_ms = 0)" }
+                }
+            }
+        },
+        "/api/v1/sync/changes": {
+            "get": op(
+                "sync",
+                "Changes since an audit sequence, for a peer to pull: changed memories and tombstones",
+                None,
+                &[
+                    query_param("after", "integer", "Audit sequence of the last change already synced (default 0)"),
+                    query_param("limit", "integer", "Most audit entries to read (default [sync] batch_size, max 5000)"),
+                    query_param("device", "string", "[sync] device of the asking instance; changes it sent are left out")
+                ],
+                ok(schema("SyncBatch"))
+            )
+        },
+        "/api/v1/sync/apply": {
+            "post": op(
+                "sync",
+                "Apply a peer's changes last-write-wins and reindex them",
+                Some("SyncBatch"),
+                &[],
+                responses(&[
+                    ("200", "What was applied", Some(schema("SyncReport"))),
+                    ("400", "Batch without a device", None)
+                ])
+            )
+        }
+    })
+}
+
+/// Memories and concepts
+fn memory_paths() -> Value {
+    json!({
+        "/api/v1/feedback": {
+            "post": op("search", "Mark retrieved memories useful or not", Some("FeedbackRequest"), &[], ok(schema("FeedbackReport")))
+        },
+        "/api/v1/memories": {
+            "post": store_op("Store a memory", &[])
+        },
+        "/api/v1/memories/merge": {
+            "post": op(
+                "memories",
+                "Merge duplicate memories into one that supersedes each original",
+                Some("MergeRequest"),
+                &[],
+                responses(&[
+                    ("201", "The merged memory", Some(schema("MergeReport"))),
+                    ("400", "Fewer than two memories, or of different namespaces", None),
+                    ("404", "A memory does not exist", None)
+                ])
+            )
+        },
+        "/api/v1/memories/{id}/archive": {
+            "post": op(
+                "memories",
+                "Archive a memory: kept and readable by ID, but left out of default searches and stats",
+                None,
+                &[id_param(), json!({ "$ref": "#/components/parameters/clientName" })],
+                responses(&[("200", "`archived`", Some(json!({ "type": "string" }))), ("404", "Not found or already archived", None)])
+            )
+        },
+        "/api/v1/memories/{id}/unarchive": {
+            "post": op(
+                "memories",
+                "Bring an archived memory back into default searches and stats",
+                None,
+                &[id_param(), json!({ "$ref": "#/components/parameters/clientName" })],
+                responses(&[("200", "`unarchived`", Some(json!({ "type": "string" }))), ("404", "Not archived", None)])
+            )
+        },
+        "/api/v1/memories/{id}": {
+            "get": op(
+                "memories",
+                "Get a memory; 410 with the tombstone when it was deleted",
+                None,
+                &[id_param(), query_param("expand_links", "integer", "Also return linked memories, up to this many hops (max 3)")],
+                responses(&[
+                    ("200", "The memory", Some(schema("MemoryWithLinks"))),
+                    ("404", "Not found or quarantined", None),
+                    ("410", "Deleted", Some(schema("Tombstone")))
+                ])
+            ),
+            "patch": op(
+                "memories",
+                "Update metadata only (no re-embedding)",
+                Some("MemoryPatch"),
+                &[id_param()],
+                responses(&[
+                    ("200", "The updated memory", Some(schema("Memory"))),
+                    ("400", "Empty patch", None),
+                    ("404", "Not found", None)
+                ])
+            ),
+            "delete": op(
+                "memories",
+                "Delete a memory (also rejects a quarantined one)",
+                None,
+                &[id_param(), json!({ "$ref": "#/components/parameters/clientName" })],
+                responses(&[
+                    ("200", "`deleted`", Some(json!({ "type": "string" }))),
+                    ("404", "Not found", None)
+                ])
+            )
+        },
+        "/api/v1/memories/{id}/related": {
+            "get": op(
+                "search",
+                "Memories similar to this one, excluding itself",
+                None,
+                &[id_param(), query_param("limit", "integer", "Maximum results (default 5)")],
+                responses(&[("200", "Related memories", Some(array("SearchResult"))), ("404", "Not found", None)])
+            )
+        },
+        "/api/v1/memories/{id}/grep": {
+            "get": op(
+                "memories",
+                "Passages of one memory that match, with character offsets; pass exactly one of pattern or query",
+                None,
+                &[
+                    id_param(),
+                    query_param("pattern", "string", "Literal text to find (case-insensitive)"),
+                    query_param("query", "string", "Words to find; passages containing more of them come first"),
+                    query_param("context", "integer", "Characters of context on each side of a match (default 120)"),
+                    query_param("limit", "integer", "Maximum passages (default 5)")
+                ],
+                responses(&[
+                    ("200", "Matching passages", Some(schema("GrepResponse"))),
+                    ("400", "Neither or both of pattern and query", None),
+                    ("404", "Not found", None)
+                ])
+            )
+        },
+        "/api/v1/concepts": {
+            "get": op(
+                "concepts",
+                "Concepts by how many memories carry them",
+                None,
+                &[query_param("prefix", "string", "Only concepts starting with this"), query_param("limit", "integer", "Maximum concepts (default 50)")],
+                ok(array("ConceptCount"))
+            )
+        },
+        "/api/v1/concepts/{concept}": {
+            "get": op(
+                "concepts",
+                "Memories carrying a concept, newest first",
+                None,
+                &[path_param("concept", "Concept"), query_param("limit", "integer", "Maximum memories (default 50)")],
+                ok(array("Memory"))
+            )
+        }
+    })
+}
+
+/// Statistics and namespaces
+fn stats_paths() -> Value {
+    json!({
+        "/api/v1/stats": {
+            "get": op("memories", "Counts across every namespace", None, &[query_param("include_archived", "boolean", "Count archived memories too (default false)")], ok(schema("StatsResponse")))
+        },
+        "/api/v1/analytics": {
+            "get": op(
+                "memories",
+                "Access analytics: most and least accessed memories, those never retrieved, daily accesses and search hit recency",
+                None,
+                &[
+                    query_param("limit", "integer", "Memories per ranking (default 10)"),
+                    query_param("days", "integer", "Days of daily accesses, at most 90 (default 30)"),
+                    query_param("namespace", "string", "Only memories of this namespace")
+                ],
+                ok(schema("AccessAnalytics"))
+            )
+        },
+        "/api/v1/namespaces": {
+            "get": op("namespaces", "Namespaces by how many memories they hold", None, &[], ok(array("NamespaceCount")))
+        },
+        "/api/v1/namespaces/{namespace}/memories": {
+            "get": op(
+                "namespaces",
+                "Memories of a namespace, newest first",
+                None,
+                &[namespace_param(), query_param("limit", "integer", "Maximum memories (default 50)")],
+                ok(array("Memory"))
+            ),
+            "post": store_op("Store a memory in this namespace", &[namespace_param()])
+        },
+        "/api/v1/namespaces/{namespace}/search": {
+            "post": op("namespaces", "Search within a namespace", Some("SearchRequest"), &[namespace_param()], search_responses())
+        },
+        "/api/v1/namespaces/{namespace}/stats": {
+            "get": op("namespaces", "Counts within a namespace", None, &[namespace_param(), query_param("include_archived", "boolean", "Count archived memories too (default false)")], ok(schema("StatsResponse")))
+        }
+    })
+}
+
+/// Maintenance under `/api/v1/admin`
+fn admin_paths() -> Value {
+    json!({
+        "/api/v1/admin/export": {
+            "post": op("admin", "Export a Markdown/Obsidian vault", Some("ExportRequest"), &[], ok(object("Files written and where")))
+        },
+        "/api/v1/admin/backup": {
+            "post": op("admin", "Create a backup archive", None, &[], responses(&[("201", "The backup", Some(schema("BackupInfo")))]))
+        },
+        "/api/v1/admin/backups": {
+            "get": op("admin", "Backup archives, newest first", None, &[], ok(array("BackupInfo")))
+        },
+        "/api/v1/admin/backups/diff": {
+            "get": op(
+                "admin",
+                "Memories added, removed and changed between two backups",
+                None,
+                &[query_param_required("from", "Older backup, by name or path"), query_param_required("to", "Newer backup, by name or path")],
+                responses(&[("200", "The difference", Some(object("Added, removed and changed memories"))), ("400", "Unknown backup", None)])
+            )
+        },
+        "/api/v1/admin/tune": {
+            "get": op("admin", "Current scoring weights", None, &[], ok(schema("Scorer"))),
+            "post": op("admin", "Change scoring weights live", Some("TuneRequest"), &[], responses(&[("200", "The new weights", Some(schema("Scorer"))), ("400", "Invalid weights", None)]))
+        },
+        "/api/v1/admin/experiment": {
+            "get": op("admin", "A/B ranking experiment results", None, &[], responses(&[("200", "Per-variant results", Some(object("Per-variant counts and usefulness"))), ("404", "No experiment configured", None)]))
+        },
+        "/api/v1/admin/verify": {
+            "get": op("admin", "Check the audit hash chain against stored memories", None, &[], ok(object("Integrity report")))
+        },
+        "/api/v1/admin/reembed": {
+            "post": op("admin", "Re-embed memories from another model or dimension", Some("ReembedRequest"), &[], responses(&[("200", "What was re-embedded", Some(object("Re-embedding report"))), ("503", "No embedding engine", None)]))
+        },
+        "/api/v1/admin/retag": {
+            "post": op(
+                "admin",
+                "Add and remove tags on every memory matching a filter",
+                Some("RetagRequest"),
+                &[json!({ "$ref": "#/components/parameters/clientName" })],
+                responses(&[("200", "Matched and changed memories", Some(schema("RetagReport"))), ("400", "Empty filter or edit", None)])
+            )
+        },
+        "/api/v1/admin/consolidate": {
+            "post": op("admin", "Merge related session memories into consolidated ones", None, &[], ok(object("Consolidated groups and archived memories")))
+        },
+        "/api/v1/admin/import": {
+            "post": op(
+                "admin",
+                "Import another tool's export from the server's disk, embedded and indexed",
+                Some("ImportRequest"),
+                &[],
+                responses(&[
+                    ("200", "Imported memories", Some(schema("ImportReport"))),
+                    ("400", "Unknown format, or no such path in import.dir", None)
+                ])
+            )
+        },
+        "/api/v1/admin/sync": {
+            "post": op(
+                "admin",
+                "Pull changes from and push changes to the [[sync.peers]] now",
+                None,
+                &[query_param("peer", "string", "Only this peer (default: all)")],
+                responses(&[
+                    ("200", "One report per peer; a failed peer carries its error", Some(object("Pulled and pushed counts per peer"))),
+                    ("400", "No peers configured", None),
+                    ("404", "Unknown peer", None)
+                ])
+            )
+        },
+        "/api/v1/admin/forgetting": {
+            "get": op(
+                "admin",
+                "Dry run of the [forgetting] policy: the memories it would archive or delete now",
+                None,
+                &[],
+                ok(schema("ForgettingReport"))
+            ),
+            "post": op(
+                "admin",
+                "Apply the [forgetting] policy now",
+                None,
+                &[],
+                ok(schema("ForgettingReport"))
+            )
+        },
+        "/api/v1/admin/quarantine": {
+            "get": op(
+                "admin",
+                "Memories held back by [quarantine], most recent first",
+                None,
+                &[query_param("limit", "integer", "Maximum memories (default 50)")],
+                ok(array("QuarantinedMemory"))
+            )
+        },
+        "/api/v1/admin/quarantine/{id}/approve": {
+            "post": op(
+                "admin",
+                "Release a quarantined memory into search and retrieval",
+                None,
+                &[id_param(), json!({ "$ref": "#/components/parameters/clientName" })],
+                responses(&[("200", "`approved`", Some(json!({ "type": "string" }))), ("404", "Not quarantined", None)])
+            )
+        },
+        "/api/v1/admin/misses": {
+            "get": op(
+                "admin",
+                "Searches that found nothing ([misses]), most frequent first",
+                None,
+                &[
+                    query_param("limit", "integer", "Maximum queries (default 50)"),
+                    query_param("namespace", "string", "Only searches scoped to this namespace")
+                ],
+                ok(array("MissedQuery"))
+            )
+        }
+    })
+}
+
+fn schemas() -> Value {
+    merged([
+        memory_schemas(),
+        search_schemas(),
+        event_schemas(),
+        store_schemas(),
+        content_schemas(),
+        stats_schemas(),
+        admin_schemas(),
+    ])
+}
+
+/// Memories and their metadata
+fn memory_schemas() -> Value {
+    json!({
+        "MemoryType": {
+            "type": "string",
+            "enum": ["observation", "decision", "preference", "fact", "task", "session", "bugfix", "discovery"]
+        },
+        "Priority": { "type": "string", "enum": ["low", "medium", "high"] },
+        "Relation": { "type": "string", "enum": ["supersedes", "relates_to", "caused_by", "part_of"] },
+        "DuplicateAction": { "type": "string", "enum": ["reject", "merge", "flag"] },
+        "MemoryMetadata": {
+            "type": "object",
+            "required": ["memory_type", "priority", "tags", "concepts", "files"],
+            "properties": {
+                "memory_type": schema("MemoryType"),
+                "priority": schema("Priority"),
+                "source": nullable("string"),
+                "tags": strings(),
+                "concepts": strings(),
+                "files": strings()
+            }
+        },
+        "Memory": {
+            "type": "object",
+            "required": ["id", "content", "title", "metadata", "created_at", "updated_at", "accessed_at", "access_count", "namespace"],
+            "properties": {
+                "id": { "type": "string" },
+                "content": { "type": "string" },
+                "title": { "type": "string" },
+                "metadata": schema("MemoryMetadata"),
+                "embedding": { "type": ["array", "null"], "items": { "type": "number" }, "description": "Omitted (null) in most responses" },
+                "embedding_model": nullable("string"),
+                "created_at": timestamp(),
+                "updated_at": timestamp(),
+                "accessed_at": timestamp(),
+                "access_count": { "type": "integer" },
+                "expires_at": timestamp(),
+                "namespace": { "type": "string" }
+            }
+        },
+        "MemoryWithLinks": {
+            "allOf": [
+                schema("Memory"),
+                {
+                    "type": "object",
+                    "properties": {
+                        "linked": array("LinkedMemory"),
+                        "fields": { "type": "object", "description": "Typed metadata fields ([fields])" }
+                    }
+                }
+            ]
+        },
+        "LinkedMemory": {
+            "type": "object",
+            "required": ["id", "title", "memory_type", "snippet", "relation", "depth"],
+            "properties": {
+                "id": { "type": "string" },
+                "title": { "type": "string" },
+                "memory_type": schema("MemoryType"),
+                "snippet": { "type": "string" },
+                "relation": schema("Relation"),
+                "incoming": { "type": "boolean", "description": "The link points at the memory it was reached from" },
+                "depth": { "type": "integer", "description": "Hops from the retrieved memory" }
+            }
+        }
+    })
+}
+
+/// Search requests and results
+fn search_schemas() -> Value {
+    json!({
+        "ScoreBreakdown": {
+            "type": "object",
+            "required": ["semantic", "keyword", "recency", "importance", "access"],
+            "properties": {
+                "semantic": { "type": "number" },
+                "keyword": { "type": "number" },
+                "recency": { "type": "number" },
+                "importance": { "type": "number" },
+                "access": { "type": "number", "description": "ln(1 + access_count), normalized to [0, 1]" }
+            }
+        },
+        "ScoreExplanation": {
+            "type": "object",
+            "required": ["days_since_access", "access_count", "weights", "half_life_days", "fusion", "rrf_k"],
+            "properties": {
+                "cosine": { "type": "number", "description": "Cosine similarity of the best memory or chunk vector; absent without a vector hit" },
+                "bm25": { "type": "number", "description": "BM25 score before normalization; absent without a keyword hit" },
+                "vector_rank": { "type": "integer", "description": "1-based rank in the vector list" },
+                "keyword_rank": { "type": "integer", "description": "1-based rank in the keyword list" },
+                "days_since_access": { "type": "number" },
+                "access_count": { "type": "integer" },
+                "weights": {
+                    "type": "object",
+                    "description": "Weights applied, after any profile, experiment variant or keyword-only fallback",
+                    "properties": {
+                        "semantic": { "type": "number" },
+                        "keyword": { "type": "number" },
+                        "recency": { "type": "number" },
+                        "importance": { "type": "number" },
+                        "access": { "type": "number" }
+                    }
+                },
+                "half_life_days": { "type": "number" },
+                "fusion": { "type": "string", "enum": ["weighted_sum", "rrf"] },
+                "rrf_k": { "type": "number" }
+            }
+        },
+        "SearchResult": {
+            "type": "object",
+            "required": ["memory", "score", "score_breakdown"],
+            "properties": {
+                "memory": schema("Memory"),
+                "score": { "type": "number" },
+                "score_breakdown": schema("ScoreBreakdown"),
+                "variant": { "type": "string", "description": "Ranking variant when an A/B experiment is active" },
+                "profile": { "type": "string", "description": "Scoring profile the result was ranked with" },
+                "degraded": { "type": "boolean", "description": "Ranked keyword-only" },
+                "linked": array("LinkedMemory"),
+                "collapsed_count": { "type": "integer", "description": "Lower-scoring matches from the same source left out (collapse_sources)" },
+                "explanation": schema("ScoreExplanation")
+            }
+        },
+        "SearchRequest": {
+            "type": "object",
+            "required": ["query"],
+            "properties": {
+                "query": { "type": "string", "description": "Keywords; AND, OR, NOT, parentheses and title:, tag:, type: terms filter (type:decision tag:rust NOT deprecated)" },
+                "alt_queries": { "type": "array", "items": { "type": "string" }, "description": "Other phrasings of the query, searched too and merged by rank (RRF)" },
+                "limit": { "type": "integer", "default": 10 },
+                "index_only": { "type": "boolean", "default": false, "description": "Same as verbosity = index" },
+                "verbosity": { "type": "string", "enum": ["ids", "index", "snippets", "full"], "description": "How much of each memory to return: ids and scores, titles and metadata, plus a one-line content snippet, or everything (default: [search] verbosity, per X-Client-Name in [search.client_verbosity])" },
+                "tags_all": { "type": "array", "items": { "type": "string" }, "description": "Only memories carrying all of these tags (alias: tags)" },
+                "tags_any": { "type": "array", "items": { "type": "string" }, "description": "Only memories carrying at least one of these tags" },
+                "exclude_tags": { "type": "array", "items": { "type": "string" }, "description": "Leave out memories carrying any of these tags" },
+                "memory_type": schema("MemoryType"),
+                "exclude_types": { "type": "array", "items": schema("MemoryType"), "description": "Leave out memories of these types" },
+                "profile": { "type": "string", "description": "Scoring profile ([search.profiles.<name>])" },
+                "expand_links": { "type": "integer", "default": 0, "description": "Also return linked memories, up to this many hops" },
+                "include_cold": { "type": "boolean", "default": false },
+                "include_archived": { "type": "boolean", "default": false },
+                "collapse_sources": { "type": "boolean", "description": "Only the best match of each source, with the rest counted in collapsed_count (default: [search] collapse_sources)" },
+                "filters": { "type": "array", "items": { "type": "string" }, "description": "Typed field filters such as `sprint >= 42`" },
+                "concepts": { "type": "array", "items": { "type": "string" }, "description": "Only memories carrying all of these concepts" },
+                "namespace": { "type": "string", "description": "Only this namespace (all when unset)" },
+                "exact": { "type": "boolean", "default": false, "description": "Match the query as one phrase (e.g. an error message) and return only memories containing it; quoted parts of a normal query are phrases too" },
+                "explain": { "type": "boolean", "default": false, "description": "Attach an explanation to each result: raw cosine and BM25 scores, ranks, weights and days since access" }
+            }
+        },
+        "SearchBatchRequest": {
+            "type": "object",
+            "required": ["queries"],
+            "properties": {
+                "queries": { "type": "array", "items": schema("SearchRequest"), "description": "At most [search] max_batch_queries searches" }
+            }
+        },
+        "EmbedRequest": {
+            "type": "object",
+            "required": ["texts"],
+            "properties": {
+                "texts": { "type": "array", "items": { "type": "string" }, "description": "1 to 256 texts" }
+            }
+        },
+        "EmbedResponse": {
+            "type": "object",
+            "required": ["model", "dimensions", "embeddings", "truncated"],
+            "properties": {
+                "model": { "type": "string", "description": "Model ID, as stored with memory embeddings" },
+                "dimensions": { "type": "integer" },
+                "embeddings": { "type": "array", "items": { "type": "array", "items": { "type": "number" } }, "description": "One vector per text, in request order" },
+                "truncated": { "type": "array", "items": { "type": "integer" }, "description": "Indexes of texts over the model's input limit, embedded from their beginning only" }
+            }
+        },
+        "SearchGroup": {
+            "type": "object",
+            "required": ["query", "results"],
+            "properties": {
+                "query": { "type": "string" },
+                "results": array("SearchResult")
+            }
+        }
+    })
+}
+
+/// Change events and sync batches
+fn event_schemas() -> Value {
+    json!({
+        "MemoryEvent": {
+            "type": "object",
+            "required": ["seq", "kind", "memory_id", "at"],
+            "properties": {
+                "seq": { "type": "integer", "description": "Audit log sequence" },
+                "kind": { "type": "string", "enum": ["created", "updated", "deleted"] },
+                "memory_id": { "type": "string" },
+                "at": { "type": "string", "format": "date-time" },
+                "actor": { "type": "string", "description": "Client that made the change, when it identified itself" }
+            }
+        },
+        "SyncBatch": {
+            "type": "object",
+            "required": ["device", "head", "more"],
+            "properties": {
+                "device": { "type": "string", "description": "[sync] device of the instance the changes come from" },
+                "head": { "type": "integer", "description": "Audit sequence read up to; ask for changes after it next" },
+                "more": { "type": "boolean", "description": "More changes follow" },
+                "memories": array("Memory"),
+                "tombstones": array("Tombstone"),
+                "states": {
+                    "type": "object",
+                    "description": "Quarantine and archive state of the memories that have any, by ID",
+                    "additionalProperties": {
+                        "type": "object",
+                        "properties": {
+                            "quarantine_reason": { "type": "string" },
+                            "archived_at": { "type": "string", "format": "date-time" }
+                        }
+                    }
+                }
+            }
+        },
+        "SyncReport": {
+            "type": "object",
+            "properties": {
+                "created": { "type": "integer" },
+                "updated": { "type": "integer" },
+                "deleted": { "type": "integer" },
+                "skipped": { "type": "integer", "description": "Changes no newer than the local state" },
+                "quarantined": { "type": "integer", "description": "Applied memories held in quarantine" },
+                "changed": strings(),
+                "removed": strings()
+            }
+        }
+    })
+}
+
+/// Storing and updating memories
+fn store_schemas() -> Value {
+    json!({
+        "StoreRequest": {
+            "type": "object",
+            "required": ["content", "title"],
+            "properties": {
+                "content": { "type": "string" },
+                "title": { "type": "string" },
+                "memory_type": { "allOf": [schema("MemoryType")], "default": "observation" },
+                "priority": { "allOf": [schema("Priority")], "default": "medium" },
+                "tags": strings(),
+                "fields": { "type": "object", "description": "Values of typed fields registered in [fields]" },
+                "ttl_hours": { "type": "integer", "description": "Expire the memory this many hours after storing it" },
+                "concepts": { "type": "array", "items": { "type": "string" }, "description": "Extracted from the text when empty" },
+                "namespace": { "type": "string", "default": "default" },
+                "links": {
+                    "type": "array",
+                    "items": schema("NewLink"),
+                    "description": "Links from the new memory, created in the same transaction; nothing is stored when a target does not exist"
+                },
+                "source": { "type": "string", "description": "Where the memory came from, such as hook:<sentence hash>" }
+            }
+        },
+        "NewLink": {
+            "type": "object",
+            "required": ["target_id", "relation"],
+            "description": "Read as: new memory <relation> target_id",
+            "properties": {
+                "target_id": { "type": "string" },
+                "relation": schema("Relation")
+            }
+        },
+        "StoreResponse": {
+            "type": "object",
+            "required": ["id", "title", "has_embedding"],
+            "properties": {
+                "id": { "type": "string", "description": "The stored memory, or the one it was merged into or rejected as a duplicate of" },
+                "title": { "type": "string" },
+                "has_embedding": { "type": "boolean" },
+                "truncated": { "type": "boolean", "description": "Only part of the content was embedded" },
+                "duplicate": schema("DuplicateMatch"),
+                "quarantined": { "type": "string", "description": "Why [quarantine] held the memory back" },
+                "escalation": schema("Escalation")
+            }
+        },
+        "DuplicateMatch": {
+            "type": "object",
+            "required": ["id", "similarity", "action"],
+            "properties": {
+                "id": { "type": "string" },
+                "similarity": { "type": "number" },
+                "action": schema("DuplicateAction")
+            }
+        },
+        "Escalation": {
+            "type": "object",
+            "description": "Priority of a stored memory raised because its topic kept being stored ([escalation])",
+            "required": ["id", "priority", "repeats"],
+            "properties": {
+                "id": { "type": "string" },
+                "priority": schema("Priority"),
+                "repeats": { "type": "integer" }
+            }
+        },
+        "MemoryPatch": {
+            "type": "object",
+            "description": "Unset fields are left as is",
+            "properties": {
+                "title": { "type": "string" },
+                "memory_type": schema("MemoryType"),
+                "priority": schema("Priority"),
+                "source": { "type": "string" },
+                "tags": strings(),
+                "concepts": strings(),
+                "files": strings()
+            }
+        },
+        "Tombstone": {
+            "type": "object",
+            "required": ["id", "deleted_at"],
+            "properties": {
+                "id": { "type": "string" },
+                "deleted_at": timestamp(),
+                "deleted_by": { "type": "string" }
+            }
+        }
+    })
+}
+
+/// Passages, feedback and counts
+fn content_schemas() -> Value {
+    json!({
+        "GrepResponse": {
+            "type": "object",
+            "required": ["id", "title", "length", "passages"],
+            "properties": {
+                "id": { "type": "string" },
+                "title": { "type": "string" },
+                "length": { "type": "integer", "description": "Content length in characters" },
+                "passages": array("Passage")
+            }
+        },
+        "Passage": {
+            "type": "object",
+            "required": ["start", "end", "matches", "text"],
+            "properties": {
+                "start": { "type": "integer", "description": "Character offset in the content" },
+                "end": { "type": "integer" },
+                "matches": {
+                    "type": "array",
+                    "items": { "type": "array", "items": { "type": "integer" }, "minItems": 2, "maxItems": 2 },
+                    "description": "[start, end] of each match"
+                },
+                "text": { "type": "string" }
+            }
+        },
+        "FeedbackRequest": {
+            "type": "object",
+            "properties": { "useful": strings(), "not_useful": strings() }
+        },
+        "FeedbackReport": {
+            "type": "object",
+            "required": ["recorded"],
+            "properties": {
+                "recorded": { "type": "integer" },
+                "unknown": strings()
+            }
+        },
+        "ConceptCount": {
+            "type": "object",
+            "required": ["concept", "count"],
+            "properties": { "concept": { "type": "string" }, "count": { "type": "integer" } }
+        },
+        "NamespaceCount": {
+            "type": "object",
+            "required": ["namespace", "count", "cold"],
+            "properties": {
+                "namespace": { "type": "string" },
+                "count": { "type": "integer" },
+                "cold": { "type": "integer" }
+            }
+        }
+    })
+}
+
+/// Statistics, health and analytics
+fn stats_schemas() -> Value {
+    json!({
+        "StatsResponse": {
+            "type": "object",
+            "required": ["total_memories", "indexed_count", "has_embedder", "search_mode"],
+            "properties": {
+                "total_memories": { "type": "integer" },
+                "cold_memories": { "type": "integer" },
+                "archived_memories": { "type": "integer", "description": "Left out of the other counts unless include_archived" },
+                "indexed_count": { "type": "integer" },
+                "has_embedder": { "type": "boolean" },
+                "search_mode": { "type": "string", "enum": ["hybrid", "keyword-only"] },
+                "namespace": { "type": "string" },
+                "namespaces": array("NamespaceCount"),
+                "cache": schema("CacheStats"),
+                "query_cache": schema("CacheStats"),
+                "breakdown": schema("MemoryBreakdown"),
+                "disk": schema("DiskUsage")
+            }
+        },
+        "MemoryBreakdown": {
+            "type": "object",
+            "required": ["by_type", "by_priority", "top_tags", "embedded", "embedding_coverage"],
+            "properties": {
+                "by_type": { "type": "object", "additionalProperties": { "type": "integer" } },
+                "by_priority": { "type": "object", "additionalProperties": { "type": "integer" } },
+                "top_tags": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["tag", "count"],
+                        "properties": { "tag": { "type": "string" }, "count": { "type": "integer" } }
+                    }
+                },
+                "oldest": nullable("string"),
+                "newest": nullable("string"),
+                "embedded": { "type": "integer", "description": "Memories with an embedding" },
+                "embedding_coverage": { "type": "number", "description": "Share of memories with an embedding (0-1)" }
+            }
+        },
+        "ReadinessReport": {
+            "type": "object",
+            "required": ["status", "components"],
+            "properties": {
+                "status": { "type": "string", "enum": ["ok", "degraded"] },
+                "components": {
+                    "type": "object",
+                    "required": ["sqlite", "text_index", "vector_index", "embedder"],
+                    "properties": {
+                        "sqlite": schema("ComponentHealth"),
+                        "text_index": schema("ComponentHealth"),
+                        "vector_index": schema("ComponentHealth"),
+                        "embedder": schema("ComponentHealth")
+                    }
+                },
+                "self_test": object("Startup self-test: passed, at, total_millis and timed steps")
+            }
+        },
+        "ComponentHealth": {
+            "type": "object",
+            "required": ["healthy"],
+            "properties": {
+                "healthy": { "type": "boolean" },
+                "count": { "type": "integer", "description": "Memories stored, text index documents or memory vectors loaded" },
+                "latency_millis": { "type": "number", "description": "Time the check took; for the embedder, one test inference" },
+                "model": { "type": "string" },
+                "error": { "type": "string" }
+            }
+        },
+        "DiskUsage": {
+            "type": "object",
+            "description": "Bytes on disk of the data directory's components",
+            "required": ["database_bytes", "text_index_bytes", "vector_index_bytes"],
+            "properties": {
+                "database_bytes": { "type": "integer", "description": "SQLite database with WAL and shm files" },
+                "text_index_bytes": { "type": "integer", "description": "Tantivy (BM25) index" },
+                "vector_index_bytes": { "type": "integer", "description": "Saved vector index and manifest" }
+            }
+        },
+        "CacheStats": {
+            "type": "object",
+            "description": "Hits of the read cache in front of storage (cache) or of the search result cache (query_cache), since the server started",
+            "required": ["hits", "misses", "entries", "capacity", "hit_rate"],
+            "properties": {
+                "hits": { "type": "integer" },
+                "misses": { "type": "integer" },
+                "entries": { "type": "integer" },
+                "capacity": { "type": "integer", "description": "0 when caching is off" },
+                "hit_rate": { "type": "number" }
+            }
+        },
+        "QuarantinedMemory": {
+            "type": "object",
+            "required": ["memory", "reason", "quarantined_at"],
+            "properties": {
+                "memory": schema("Memory"),
+                "reason": { "type": "string" },
+                "quarantined_at": timestamp()
+            }
+        },
+        "AccessEntry": {
+            "type": "object",
+            "required": ["id", "title", "memory_type", "access_count", "access_score", "accessed_at", "created_at"],
+            "properties": {
+                "id": { "type": "string" },
+                "title": { "type": "string" },
+                "memory_type": schema("MemoryType"),
+                "access_count": { "type": "integer" },
+                "access_score": { "type": "number", "description": "Accesses decayed by [storage] access_half_life_days" },
+                "accessed_at": timestamp(),
+                "created_at": timestamp()
+            }
+        },
+        "AccessAnalytics": {
+            "type": "object",
+            "required": ["total", "most_accessed", "least_accessed", "never_accessed", "never_accessed_oldest", "daily_accesses", "search_hits"],
+            "properties": {
+                "total": { "type": "integer" },
+                "most_accessed": array("AccessEntry"),
+                "least_accessed": array("AccessEntry"),
+                "never_accessed": { "type": "integer", "description": "Memories never retrieved since they were stored" },
+                "never_accessed_oldest": array("AccessEntry"),
+                "daily_accesses": {
+                    "type": "array",
+                    "description": "Oldest day first, in the [display] time zone",
+                    "items": {
+                        "type": "object",
+                        "properties": { "date": { "type": "string" }, "accesses": { "type": "integer" } }
+                    }
+                },
+                "search_hits": { "type": "integer" },
+                "avg_hit_recency": { "type": "number", "description": "Mean recency component of search hits; absent without hits" }
+            }
+        },
+        "ForgettingReport": {
+            "type": "object",
+            "required": ["dry_run", "action", "memories"],
+            "properties": {
+                "dry_run": { "type": "boolean" },
+                "action": { "type": "string", "enum": ["archive", "delete"] },
+                "memories": { "description": "Forgotten, or to be forgotten on a dry run", "type": "array", "items": schema("AccessEntry") }
+            }
+        },
+        "MissedQuery": {
+            "type": "object",
+            "required": ["query", "count", "first_at", "last_at"],
+            "properties": {
+                "query": { "type": "string", "description": "Trimmed and lowercased" },
+                "namespace": { "type": "string" },
+                "count": { "type": "integer" },
+                "best_score": { "type": "number", "description": "Best score of low-confidence results; absent when nothing was found" },
+                "first_at": timestamp(),
+                "last_at": timestamp(),
+                "task_id": { "type": "string", "description": "Task memory stored for the gap" }
+            }
+        }
+    })
+}
+
+/// Maintenance requests and reports
+fn admin_schemas() -> Value {
+    json!({
+        "RetagRequest": {
+            "type": "object",
+            "required": ["filter"],
+            "properties": {
+                "filter": {
+                    "type": "object",
+                    "description": "Must narrow the selection. Dates without an offset are read in the [display] time zone.",
+                    "properties": {
+                        "query": { "type": "string" },
+                        "memory_type": schema("MemoryType"),
+                        "created_after": { "type": "string" },
+                        "created_before": { "type": "string" },
+                        "namespace": { "type": "string" }
+                    }
+                },
+                "add": strings(),
+                "remove": strings()
+            }
+        },
+        "RetagReport": {
+            "type": "object",
+            "required": ["matched", "changed"],
+            "properties": { "matched": { "type": "integer" }, "changed": strings() }
+        },
+        "ExportRequest": {
+            "type": "object",
+            "properties": { "dir": { "type": "string", "description": "Relative subdirectory of export.markdown_dir; the directory itself when unset" } }
+        },
+        "MergeRequest": {
+            "type": "object",
+            "required": ["ids"],
+            "properties": {
+                "ids": strings(),
+                "mode": { "type": "string", "enum": ["concatenate", "summarize"], "default": "concatenate" },
+                "title": { "type": "string", "description": "Defaults to the oldest original's title (or the summary's)" }
+            }
+        },
+        "MergeReport": {
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "title": { "type": "string" },
+                "superseded": { "type": "array", "items": { "type": "string" }, "description": "The originals, oldest first" }
+            }
+        },
+        "ImportRequest": {
+            "type": "object",
+            "required": ["format", "path"],
+            "properties": {
+                "format": { "type": "string", "enum": ["mem0", "notion"] },
+                "path": { "type": "string", "description": "mem0 JSON file, or Notion export directory (Markdown or HTML), relative to import.dir" },
+                "namespace": { "type": "string" }
+            }
+        },
+        "ImportReport": {
+            "type": "object",
+            "properties": {
+                "format": { "type": "string" },
+                "imported": strings(),
+                "skipped": { "type": "integer", "description": "Records an earlier import already stored" },
+                "unembedded": { "type": "integer", "description": "Stored without an embedding; reembed fills them in" },
+                "linked": { "type": "integer", "description": "Pages linked part_of their parent page" },
+                "quarantined": { "type": "integer", "description": "Imported memories held back by [quarantine]" }
+            }
+        },
+        "BackupInfo": object("A backup archive: name, path, size and creation time"),
+        "Scorer": object("Scoring weights and recency half-life"),
+        "TuneRequest": {
+            "type": "object",
+            "description": "Weights to change; unset ones are kept",
+            "properties": {
+                "semantic_weight": { "type": "number" },
+                "keyword_weight": { "type": "number" },
+                "recency_weight": { "type": "number" },
+                "importance_weight": { "type": "number" },
+                "access_weight": { "type": "number" },
+                "half_life_days": { "type": "number" },
+                "persist": { "type": "boolean", "default": false, "description": "Also write the weights to the config file" }
+            }
+        },
+        "ReembedRequest": {
+            "type": "object",
+            "properties": {
+                "all": { "type": "boolean", "default": false },
+                "batch_size": { "type": "integer" }
+            }
+        }
+    })
+}
+
+/// The entries of several JSON objects in one object
+fn merged<const N: usize>(parts: [Value; N]) -> Value {
+    let mut merged = Map::new();
+    for part in parts {
+        if let Value::Object(entries) = part {
+            merged.extend(entries);
+        }
+    }
+    Value::Object(merged)
+}
+
+/// An operation under `tag`, with `body` naming its request schema
+fn op(tag: &str, summary: &str, body: Option<&str>, params: &[Value], responses: Value) -> Value {
+    let mut op = json!({
+        "tags": [tag],
+        "summary": summary,
+        "parameters": params,
+        "responses": responses
+    });
+    if let Some(body) = body {
+        op["requestBody"] = json!({
+            "required": true,
+            "content": { "application/json": { "schema": schema(body) } }
+        });
+    }
+    op
+}
+
+fn store_op(summary: &str, params: &[Value]) -> Value {
+    op(
+        "memories",
+        summary,
+        Some("StoreRequest"),
+        params,
+        responses(&[
+            ("201", "Stored", Some(schema("StoreResponse"))),
+            (
+                "200",
+                "Merged into a near-duplicate",
+                Some(schema("StoreResponse")),
+            ),
+            ("400", "Invalid fields or a missing link target", None),
+            (
+                "409",
+                "Rejected as a near-duplicate",
+                Some(schema("StoreResponse")),
+            ),
+            ("503", EMBEDDER_BUSY, None),
+        ]),
+    )
+}
+
+/// 503 answer when `[embedding.limits]` turns a call away
+const EMBEDDER_BUSY: &str = "Embedder busy; retry after the Retry-After header (seconds)";
+
+fn search_responses() -> Value {
+    responses(&[
+        ("200", "Success", Some(array("SearchResult"))),
+        ("503", EMBEDDER_BUSY, None),
+    ])
+}
+
+fn ok(data: Value) -> Value {
+    responses(&[("200", "Success", Some(data))])
+}
+
+/// Responses by status; error statuses carry the envelope with `error`
+fn responses(entries: &[(&str, &str, Option<Value>)]) -> Value {
+    let mut out = serde_json::Map::new();
+    for (status, description, data) in entries {
+        out.insert(
+            status.to_string(),
+            json!({
+                "description": description,
+                "content": { "application/json": { "schema": envelope(data.clone()) } }
+            }),
+        );
+    }
+    out.insert(
+        "401".to_string(),
+        json!({ "description": "Missing or invalid API key", "content": { "application/json": { "schema": envelope(None) } } }),
+    );
+    Value::Object(out)
+}
+
+/// The `{ success, data, error }` envelope around `data`
+fn envelope(data: Option<Value>) -> Value {
+    let mut properties = json!({
+        "success": { "type": "boolean" },
+        "error": { "type": "string" }
+    });
+    if let Some(data) = data {
+        properties["data"] = data;
+    }
+    json!({ "type": "object", "required": ["success"], "properties": properties })
+}
+
+fn schema(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{name}") })
+}
+
+fn array(name: &str) -> Value {
+    json!({ "type": "array", "items": schema(name) })
+}
+
+fn object(description: &str) -> Value {
+    json!({ "type": "object", "description": description })
+}
+
+fn strings() -> Value {
+    json!({ "type": "array", "items": { "type": "string" } })
+}
+
+fn nullable(ty: &str) -> Value {
+    json!({ "type": [ty, "null"] })
+}
+
+fn timestamp() -> Value {
+    json!({ "type": "string", "format": "date-time" })
+}
+
+fn path_param(name: &str, description: &str) -> Value {
+    json!({ "name": name, "in": "path", "required": true, "description": description, "schema": { "type": "string" } })
+}
+
+fn id_param() -> Value {
+    json!({ "$ref": "#/components/parameters/id" })
+}
+
+fn namespace_param() -> Value {
+    json!({ "$ref": "#/components/parameters/namespace" })
+}
+
+fn query_param(name: &str, ty: &str, description: &str) -> Value {
+    json!({ "name": name, "in": "query", "required": false, "description": description, "schema": { "type": ty } })
+}
+
+fn query_param_required(name: &str, description: &str) -> Value {
+    json!({ "name": name, "in": "query", "required": true, "description": description, "schema": { "type": "string" } })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every `$ref` names a defined component
+    #[test]
+    fn test_refs_resolve() {
+        fn walk(value: &Value, spec: &Value, refs: &mut usize) {
+            match value {
+                Value::Object(map) => {
+                    if let Some(Value::String(target)) = map.get("$ref") {
+                        let pointer = target.trim_start_matches('#');
+                        assert!(spec.pointer(pointer).is_some(), "dangling {target}");
+                        *refs += 1;
+                    }
+                    map.values().for_each(|v| walk(v, spec, refs));
+                }
+                Value::Array(items) => items.iter().for_each(|v| walk(v, spec, refs)),
+                _ => {}
+            }
+        }
+        let spec = spec();
+        let mut refs = 0;
+        walk(&spec, &spec, &mut refs);
+        assert!(refs > 50);
+    }
+
+    /// Every route of [`crate::build_router`] is described with each of
+    /// its methods
+    #[test]
+    fn test_every_route_is_documented() {
+        let spec = spec();
+        let source = include_str!("lib.rs");
+        let mut routes = 0;
+        for call in source.split(".route(").skip(1) {
+            let call = call.trim_start();
+            let Some(rest) = call.strip_prefix('"') else {
+                continue;
+            };
+            let (path, handlers) = rest.split_once('"').unwrap();
+            let handlers = handlers.split("\n        .").next().unwrap();
+            let item = &spec["paths"][path];
+            assert!(item.is_object(), "{path} is undocumented");
+            for method in ["get", "post", "put", "patch", "delete"] {
+                let routed = handlers.contains(&format!("({method}("))
+                    || handlers.contains(&format!(" {method}("))
+                    || handlers.contains(&format!(".{method}("));
+                assert_eq!(
+                    routed,
+                    item[method].is_object(),
+                    "{method} {path} differs between the router and the spec"
+                );
+            }
+            routes += 1;
+        }
+        assert!(routes > 40);
+    }
+}
@@ -0,0 +1,410 @@
+//! Syncing with other oc-memory REST servers (`[sync]`, see
+//! [`oc_core::sync`]): `GET /api/v1/sync/changes` hands out local changes,
+//! `POST /api/v1/sync/apply` applies a peer's, and `POST /api/v1/admin/sync`
+//! (or the scheduler) pulls from and pushes to every configured peer.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, anyhow, bail};
+use axum::Json;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use oc_core::config::SyncPeerConfig;
+use oc_core::models::Memory;
+use oc_core::sync::{self, SyncBatch, SyncReport};
+use oc_embeddings::chunker::embed_chunks;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::{ApiResponse, AppState, SharedState, run_blocking};
+
+/// Most audit entries one `GET /api/v1/sync/changes` reads
+pub const MAX_SYNC_BATCH: usize = 5000;
+
+/// Largest request body `POST /api/v1/sync/apply` accepts; batches carry
+/// embeddings, so they outgrow the default limit
+pub const MAX_SYNC_BODY_BYTES: usize = 64 * 1024 * 1024;
+
+const PEER_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Deserialize)]
+pub struct ChangesParams {
+    /// Audit sequence of the last change already synced
+    #[serde(default)]
+    pub after: i64,
+    /// Most audit entries to read; `[sync] batch_size` when unset
+    pub limit: Option<usize>,
+    /// `[sync] device` of the asking instance; changes it sent are left out
+    pub device: Option<String>,
+}
+
+/// Local changes after `after` for a peer to pull
+pub async fn api_sync_changes(
+    State(state): State<SharedState>,
+    Query(params): Query<ChangesParams>,
+) -> (StatusCode, Json<ApiResponse<SyncBatch>>) {
+    let result = run_blocking(move || {
+        let limit = params
+            .limit
+            .unwrap_or(state.config.sync.batch_size)
+            .clamp(1, MAX_SYNC_BATCH);
+        let skip = params.device.as_deref().map(sync::actor);
+        sync::changes(
+            &state.storage,
+            &state.config.sync.device_name(),
+            params.after,
+            limit,
+            skip.as_deref(),
+        )
+        .map_err(|e| format!("Storage: {e}"))
+    })
+    .await;
+    match result {
+        Ok(batch) => (StatusCode::OK, Json(ApiResponse::ok(batch))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::err(e))),
+    }
+}
+
+/// Apply a peer's changes and reindex what they touched
+pub async fn api_sync_apply(
+    State(state): State<SharedState>,
+    Json(batch): Json<SyncBatch>,
+) -> (StatusCode, Json<ApiResponse<SyncReport>>) {
+    if batch.device.trim().is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::err("Batch must name its device")),
+        );
+    }
+    match run_blocking(move || apply_batch(&state, batch).map_err(|e| format!("{e}"))).await {
+        Ok(report) => (StatusCode::OK, Json(ApiResponse::ok(report))),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::err(format!("Sync failed: {e}"))),
+        ),
+    }
+}
+
+#[derive(Deserialize, Default)]
+pub struct SyncParams {
+    /// Only the peer of this name; every configured peer when unset
+    pub peer: Option<String>,
+}
+
+/// Outcome of syncing with one peer
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct PeerSyncReport {
+    pub peer: String,
+    /// The peer's `[sync] device`, once it answered
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    /// Its changes applied here
+    pub pulled: SyncReport,
+    /// Local changes it applied
+    pub pushed: SyncReport,
+    /// Why the sync stopped early; progress up to there is kept
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Sync with the configured peers now
+pub async fn api_sync(
+    State(state): State<SharedState>,
+    Query(params): Query<SyncParams>,
+) -> (StatusCode, Json<ApiResponse<Vec<PeerSyncReport>>>) {
+    let peers = &state.config.sync.peers;
+    if peers.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::err("No [[sync.peers]] configured")),
+        );
+    }
+    if let Some(name) = &params.peer
+        && !peers.iter().any(|p| &p.name == name)
+    {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::err(format!("Unknown sync peer: {name}"))),
+        );
+    }
+    match run_blocking(move || Ok(run_sync(&state, params.peer.as_deref()))).await {
+        Ok(reports) => (StatusCode::OK, Json(ApiResponse::ok(reports))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::err(e))),
+    }
+}
+
+/// Apply `batch`, then drop deleted memories from the search indexes and
+/// reindex changed ones. Embeddings from another model or dimension are
+/// redone with the loaded embedder, or dropped without one.
+pub fn apply_batch(state: &AppState, mut batch: SyncBatch) -> anyhow::Result<SyncReport> {
+    fit_embeddings(state, &mut batch.memories);
+    let report = sync::apply(&state.storage, &batch, state.quarantine.as_ref())?;
+    if report.changed.is_empty() && report.removed.is_empty() {
+        return Ok(report);
+    }
+
+    let changed = state.storage.get_many(&report.changed)?;
+    let mut chunked = Vec::new();
+    if let Some(engine) = &state.embedder {
+        for memory in changed.iter().filter(|m| m.embedding.is_some()) {
+            match embed_chunks(
+                engine.as_ref(),
+                &memory.content,
+                &state.config.embedding.chunking,
+            ) {
+                Ok(chunks) if chunks.is_empty() => {}
+                Ok(chunks) => {
+                    state
+                        .storage
+                        .replace_chunk_embeddings(&memory.id, &chunks)?;
+                    chunked.push((memory.id.as_str(), chunks));
+                }
+                Err(e) => tracing::warn!("Chunk embedding of {} failed: {e}", memory.id),
+            }
+        }
+    }
+
+    let mut search = state.search.lock().map_err(|e| anyhow!("Lock: {e}"))?;
+    for id in &report.removed {
+        search.remove_memory(id)?;
+    }
+    for memory in &changed {
+        search.remove_memory(&memory.id)?;
+        search.index_memory(memory)?;
+    }
+    for (id, chunks) in &chunked {
+        search.index_chunks(id, chunks)?;
+    }
+    Ok(report)
+}
+
+/// Make the embeddings of synced memories match the local vector index
+fn fit_embeddings(state: &AppState, memories: &mut [Memory]) {
+    let dimensions = state.config.embedding.dimensions;
+    let Some(engine) = &state.embedder else {
+        for memory in memories.iter_mut() {
+            if memory
+                .embedding
+                .as_ref()
+                .is_some_and(|v| v.len() != dimensions)
+            {
+                memory.embedding = None;
+                memory.embedding_model = None;
+            }
+        }
+        return;
+    };
+
+    let model = engine.model_id();
+    let stale: Vec<usize> = memories
+        .iter()
+        .enumerate()
+        .filter(|(_, m)| {
+            !m.embedding.as_ref().is_some_and(|v| {
+                v.len() == dimensions && m.embedding_model.as_deref() == Some(model)
+            })
+        })
+        .map(|(i, _)| i)
+        .collect();
+    if stale.is_empty() {
+        return;
+    }
+    let texts: Vec<&str> = stale
+        .iter()
+        .map(|&i| memories[i].content.as_str())
+        .collect();
+    let embeddings: Vec<Option<Vec<f32>>> = match engine.embed_batch(&texts) {
+        Ok(embeddings) if embeddings.len() == stale.len() => {
+            embeddings.into_iter().map(Some).collect()
+        }
+        result => {
+            let error = result.err().map(|e| e.to_string()).unwrap_or_default();
+            tracing::warn!(
+                count = stale.len(),
+                "Embedding synced memories failed, storing them without embeddings: {error}"
+            );
+            vec![None; stale.len()]
+        }
+    };
+    for (i, embedding) in stale.into_iter().zip(embeddings) {
+        let memory = &mut memories[i];
+        memory.embedding_model = embedding.as_ref().map(|_| model.to_string());
+        memory.embedding = embedding;
+    }
+}
+
+/// Sync with every configured peer, or only the one named `only`: pull its
+/// changes, then push local ones. A failing peer does not stop the others.
+pub fn run_sync(state: &AppState, only: Option<&str>) -> Vec<PeerSyncReport> {
+    state
+        .config
+        .sync
+        .peers
+        .iter()
+        .filter(|peer| only.is_none_or(|name| peer.name == name))
+        .map(|peer| {
+            let mut report = PeerSyncReport {
+                peer: peer.name.clone(),
+                ..Default::default()
+            };
+            if let Err(e) = sync_peer(state, peer, &mut report) {
+                tracing::warn!(peer = %peer.name, "Sync failed: {e:#}");
+                report.error = Some(format!("{e:#}"));
+            }
+            report
+        })
+        .collect()
+}
+
+fn sync_peer(
+    state: &AppState,
+    peer: &SyncPeerConfig,
+    report: &mut PeerSyncReport,
+) -> anyhow::Result<()> {
+    let client = PeerClient::new(peer);
+    let device = state.config.sync.device_name();
+    let batch_size = state.config.sync.batch_size.clamp(1, MAX_SYNC_BATCH);
+    let mut cursor = state.storage.sync_cursor(&peer.name)?;
+
+    // Pull first: what it applied from us is skipped on its side, and what
+    // we apply now is logged as its actor and not pushed back below
+    let remote_device = loop {
+        let batch = client.changes(cursor.pulled, batch_size, &device)?;
+        let (head, more, remote) = (batch.head, batch.more, batch.device.clone());
+        report.device = Some(remote.clone());
+        report.pulled.merge(apply_batch(state, batch)?);
+        cursor.pulled = head;
+        state.storage.set_sync_cursor(&peer.name, &cursor)?;
+        if !more {
+            break remote;
+        }
+    };
+
+    let skip = sync::actor(&remote_device);
+    loop {
+        let batch = sync::changes(
+            &state.storage,
+            &device,
+            cursor.pushed,
+            batch_size,
+            Some(&skip),
+        )?;
+        if !batch.is_empty() {
+            report.pushed.merge(client.apply(&batch)?);
+        }
+        cursor.pushed = batch.head;
+        state.storage.set_sync_cursor(&peer.name, &cursor)?;
+        if !batch.more {
+            break;
+        }
+    }
+
+    tracing::info!(
+        peer = %peer.name,
+        pulled = report.pulled.changed.len() + report.pulled.removed.len(),
+        pushed = report.pushed.changed.len() + report.pushed.removed.len(),
+        "Synced with peer"
+    );
+    Ok(())
+}
+
+/// Spawn periodic syncs with every peer when `[sync] interval_minutes` is
+/// non-zero and peers are configured
+pub fn spawn_sync_scheduler(state: SharedState) -> Option<tokio::task::JoinHandle<()>> {
+    let minutes = state.config.sync.interval_minutes;
+    if minutes == 0 || state.config.sync.peers.is_empty() {
+        return None;
+    }
+
+    Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(minutes * 60));
+        loop {
+            interval.tick().await;
+            let state = Arc::clone(&state);
+            if let Err(e) = tokio::task::spawn_blocking(move || run_sync(&state, None)).await {
+                tracing::warn!("Sync task failed: {e}");
+            }
+        }
+    }))
+}
+
+/// The sync endpoints of a peer's REST API
+struct PeerClient {
+    agent: ureq::Agent,
+    url: String,
+    key: Option<String>,
+}
+
+/// The peer's [`ApiResponse`]
+#[derive(Deserialize)]
+struct Envelope<T> {
+    data: Option<T>,
+    error: Option<String>,
+}
+
+impl PeerClient {
+    fn new(peer: &SyncPeerConfig) -> Self {
+        Self {
+            agent: ureq::AgentBuilder::new().timeout(PEER_TIMEOUT).build(),
+            url: peer.url.trim_end_matches('/').to_string(),
+            key: peer.secret(),
+        }
+    }
+
+    fn changes(&self, after: i64, limit: usize, device: &str) -> anyhow::Result<SyncBatch> {
+        let request = self
+            .agent
+            .get(&format!("{}/api/v1/sync/changes", self.url))
+            .query("after", &after.to_string())
+            .query("limit", &limit.to_string())
+            .query("device", device);
+        self.send(request, None)
+    }
+
+    fn apply(&self, batch: &SyncBatch) -> anyhow::Result<SyncReport> {
+        let request = self.agent.post(&format!("{}/api/v1/sync/apply", self.url));
+        self.send(request, Some(serde_json::to_string(batch)?))
+    }
+
+    fn send<T: DeserializeOwned>(
+        &self,
+        mut request: ureq::Request,
+        body: Option<String>,
+    ) -> anyhow::Result<T> {
+        if let Some(key) = &self.key {
+            request = request.set("Authorization", &format!("Bearer {key}"));
+        }
+        let url = request.url().to_string();
+        let response = match body {
+            Some(body) => request
+                .set("Content-Type", "application/json")
+                .send_string(&body),
+            None => request.call(),
+        };
+        let response = match response {
+            Ok(response) => response,
+            Err(ureq::Error::Status(code, response)) => {
+                let body = response.into_string().unwrap_or_default();
+                let detail = serde_json::from_str::<Envelope<serde_json::Value>>(&body)
+                    .ok()
+                    .and_then(|envelope| envelope.error)
+                    .unwrap_or(body);
+                bail!("{url}: HTTP {code}: {detail}");
+            }
+            Err(e) => return Err(e).context(url),
+        };
+        // Batches carry embeddings; read past ureq's string size limit
+        let envelope: Envelope<T> = serde_json::from_reader(response.into_reader())
+            .with_context(|| format!("{url}: unexpected response"))?;
+        match envelope.data {
+            Some(data) => Ok(data),
+            None => bail!(
+                "{url}: {}",
+                envelope
+                    .error
+                    .unwrap_or_else(|| "empty response".to_string())
+            ),
+        }
+    }
+}